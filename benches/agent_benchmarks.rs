@@ -41,6 +41,7 @@ impl BenchProvider {
                 tool_calls: vec![],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             }]),
         }
     }
@@ -57,12 +58,14 @@ impl BenchProvider {
                     }],
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 },
                 ChatResponse {
                     text: Some("done".into()),
                     tool_calls: vec![],
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 },
             ]),
         }
@@ -94,6 +97,7 @@ impl Provider for BenchProvider {
                 tool_calls: vec![],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             });
         }
         Ok(guard.remove(0))
@@ -161,6 +165,7 @@ Let me know if you need more."#
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     let multi_tool = ChatResponse {
@@ -179,6 +184,7 @@ Let me know if you need more."#
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     c.bench_function("xml_parse_single_tool_call", |b| {
@@ -213,6 +219,7 @@ fn bench_native_parsing(c: &mut Criterion) {
         ],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     c.bench_function("native_parse_tool_calls", |b| {
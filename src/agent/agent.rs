@@ -1,16 +1,21 @@
+use crate::agent::branching;
+use crate::agent::context_budget::{self, ContextBudget};
 use crate::agent::dispatcher::{
     NativeToolDispatcher, ParsedToolCall, ToolDispatcher, ToolExecutionResult, XmlToolDispatcher,
 };
+use crate::agent::loop_::build_tool_instructions_from_specs;
 use crate::agent::loop_::detection::{DetectionVerdict, LoopDetectionConfig, LoopDetector};
 use crate::agent::memory_loader::{DefaultMemoryLoader, MemoryLoader};
 use crate::agent::prompt::{PromptContext, SystemPromptBuilder};
 use crate::agent::research;
+use crate::agent::tool_retrieval::{self, scope_tool_retrieval, ToolRetriever};
 use crate::config::{Config, ResearchPhaseConfig};
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::observability::{self, Observer, ObserverEvent};
 use crate::providers::{self, ChatMessage, ChatRequest, ConversationMessage, Provider};
 use crate::runtime;
 use crate::security::SecurityPolicy;
+use crate::tools::artifact_read::ToolOutputSpill;
 use crate::tools::{self, Tool, ToolSpec};
 use anyhow::Result;
 use std::collections::HashMap;
@@ -28,6 +33,8 @@ pub struct Agent {
     tool_dispatcher: Box<dyn ToolDispatcher>,
     memory_loader: Box<dyn MemoryLoader>,
     config: crate::config::AgentConfig,
+    provider_name: String,
+    ollama_config: crate::config::OllamaConfig,
     model_name: String,
     temperature: f64,
     workspace_dir: std::path::PathBuf,
@@ -40,6 +47,7 @@ pub struct Agent {
     available_hints: Vec<String>,
     route_model_by_hint: HashMap<String, String>,
     research_config: ResearchPhaseConfig,
+    tool_retriever: Option<Arc<ToolRetriever>>,
 }
 
 pub struct AgentBuilder {
@@ -51,6 +59,8 @@ pub struct AgentBuilder {
     tool_dispatcher: Option<Box<dyn ToolDispatcher>>,
     memory_loader: Option<Box<dyn MemoryLoader>>,
     config: Option<crate::config::AgentConfig>,
+    provider_name: Option<String>,
+    ollama_config: Option<crate::config::OllamaConfig>,
     model_name: Option<String>,
     temperature: Option<f64>,
     workspace_dir: Option<std::path::PathBuf>,
@@ -62,6 +72,7 @@ pub struct AgentBuilder {
     available_hints: Option<Vec<String>>,
     route_model_by_hint: Option<HashMap<String, String>>,
     research_config: Option<ResearchPhaseConfig>,
+    tool_retriever: Option<Arc<ToolRetriever>>,
 }
 
 impl AgentBuilder {
@@ -75,6 +86,8 @@ impl AgentBuilder {
             tool_dispatcher: None,
             memory_loader: None,
             config: None,
+            provider_name: None,
+            ollama_config: None,
             model_name: None,
             temperature: None,
             workspace_dir: None,
@@ -86,6 +99,7 @@ impl AgentBuilder {
             available_hints: None,
             route_model_by_hint: None,
             research_config: None,
+            tool_retriever: None,
         }
     }
 
@@ -185,11 +199,27 @@ impl AgentBuilder {
         self
     }
 
+    /// Name of the provider serving `model_name`, used for context budgeting.
+    pub fn provider_name(mut self, provider_name: String) -> Self {
+        self.provider_name = Some(provider_name);
+        self
+    }
+
+    pub fn ollama_config(mut self, ollama_config: crate::config::OllamaConfig) -> Self {
+        self.ollama_config = Some(ollama_config);
+        self
+    }
+
     pub fn research_config(mut self, research_config: ResearchPhaseConfig) -> Self {
         self.research_config = Some(research_config);
         self
     }
 
+    pub fn tool_retriever(mut self, tool_retriever: Arc<ToolRetriever>) -> Self {
+        self.tool_retriever = Some(tool_retriever);
+        self
+    }
+
     pub fn build(self) -> Result<Agent> {
         let tools = self
             .tools
@@ -218,6 +248,8 @@ impl AgentBuilder {
                 .memory_loader
                 .unwrap_or_else(|| Box::new(DefaultMemoryLoader::default())),
             config: self.config.unwrap_or_default(),
+            provider_name: self.provider_name.unwrap_or_default(),
+            ollama_config: self.ollama_config.unwrap_or_default(),
             model_name: self
                 .model_name
                 .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into()),
@@ -234,6 +266,7 @@ impl AgentBuilder {
             available_hints: self.available_hints.unwrap_or_default(),
             route_model_by_hint: self.route_model_by_hint.unwrap_or_default(),
             research_config: self.research_config.unwrap_or_default(),
+            tool_retriever: self.tool_retriever,
         })
     }
 }
@@ -304,13 +337,18 @@ impl Agent {
             .unwrap_or("anthropic/claude-sonnet-4-20250514")
             .to_string();
 
-        let provider: Box<dyn Provider> = providers::create_routed_provider(
+        let provider: Box<dyn Provider> = providers::create_routed_provider_with_policy(
             provider_name,
             config.api_key.as_deref(),
             config.api_url.as_deref(),
             &config.reliability,
             &config.model_routes,
             &model_name,
+            &providers::ProviderRuntimeOptions {
+                ollama: config.ollama.clone(),
+                ..providers::ProviderRuntimeOptions::default()
+            },
+            providers::adaptive_routing::AdaptiveRoutingPolicy::from_config(config),
         )?;
 
         let dispatcher_choice = config.agent.tool_dispatcher.as_str();
@@ -328,7 +366,7 @@ impl Agent {
             .collect();
         let available_hints: Vec<String> = route_model_by_hint.keys().cloned().collect();
 
-        Agent::builder()
+        let builder = Agent::builder()
             .provider(provider)
             .tools(tools)
             .memory(memory)
@@ -353,8 +391,13 @@ impl Agent {
             ))
             .skills_prompt_mode(config.skills.prompt_injection_mode)
             .auto_save(config.memory.auto_save)
-            .research_config(config.research.clone())
-            .build()
+            .provider_name(provider_name.to_string())
+            .ollama_config(config.ollama.clone())
+            .research_config(config.research.clone());
+        match ToolRetriever::from_config(config) {
+            Some(tool_retriever) => builder.tool_retriever(tool_retriever).build(),
+            None => builder.build(),
+        }
     }
 
     fn trim_history(&mut self) {
@@ -384,8 +427,18 @@ impl Agent {
         self.history.extend(other_messages);
     }
 
+    /// Whether the XML tool block is added per request for the tools offered
+    /// this turn instead of listing the whole registry in the system prompt.
+    fn describes_tools_per_request(&self) -> bool {
+        self.tool_retriever.is_some() && !self.tool_dispatcher.should_send_tool_specs()
+    }
+
     fn build_system_prompt(&self) -> Result<String> {
-        let instructions = self.tool_dispatcher.prompt_instructions(&self.tools);
+        let instructions = if self.describes_tools_per_request() {
+            String::new()
+        } else {
+            self.tool_dispatcher.prompt_instructions(&self.tools)
+        };
         let ctx = PromptContext {
             workspace_dir: &self.workspace_dir,
             model_name: &self.model_name,
@@ -402,31 +455,47 @@ impl Agent {
         let start = Instant::now();
 
         let result = if let Some(tool) = self.tools.iter().find(|t| t.name() == call.name) {
-            match tool.execute(call.arguments.clone()).await {
-                Ok(r) => {
-                    self.observer.record_event(&ObserverEvent::ToolCall {
-                        tool: call.name.clone(),
-                        duration: start.elapsed(),
-                        success: r.success,
-                    });
-                    if r.success {
-                        r.output
-                    } else {
-                        format!("Error: {}", r.error.unwrap_or(r.output))
+            if let Some(reason) = tools::schema::check_tool_arguments(
+                &call.name,
+                &tool.parameters_schema(),
+                &call.arguments,
+            ) {
+                self.observer.record_event(&ObserverEvent::ToolCall {
+                    tool: call.name.clone(),
+                    duration: start.elapsed(),
+                    success: false,
+                });
+                format!("Error: {reason}")
+            } else {
+                match tool.execute(call.arguments.clone()).await {
+                    Ok(r) => {
+                        self.observer.record_event(&ObserverEvent::ToolCall {
+                            tool: call.name.clone(),
+                            duration: start.elapsed(),
+                            success: r.success,
+                        });
+                        if r.success {
+                            r.output
+                        } else {
+                            format!("Error: {}", r.error.unwrap_or(r.output))
+                        }
+                    }
+                    Err(e) => {
+                        self.observer.record_event(&ObserverEvent::ToolCall {
+                            tool: call.name.clone(),
+                            duration: start.elapsed(),
+                            success: false,
+                        });
+                        format!("Error executing {}: {e}", call.name)
                     }
-                }
-                Err(e) => {
-                    self.observer.record_event(&ObserverEvent::ToolCall {
-                        tool: call.name.clone(),
-                        duration: start.elapsed(),
-                        success: false,
-                    });
-                    format!("Error executing {}: {e}", call.name)
                 }
             }
         } else {
             format!("Unknown tool: {}", call.name)
         };
+        let result = ToolOutputSpill::from_config(&self.config, &self.workspace_dir)
+            .and_then(|spill| spill.apply(&call.name, &result))
+            .unwrap_or(result);
 
         ToolExecutionResult {
             name: call.name.clone(),
@@ -477,6 +546,11 @@ impl Agent {
     }
 
     pub async fn turn(&mut self, user_message: &str) -> Result<String> {
+        let tool_retriever = self.tool_retriever.clone();
+        scope_tool_retrieval(tool_retriever, self.run_turn(user_message)).await
+    }
+
+    async fn run_turn(&mut self, user_message: &str) -> Result<String> {
         if self.history.is_empty() {
             let system_prompt = self.build_system_prompt()?;
             self.history
@@ -564,18 +638,71 @@ impl Agent {
             failure_streak_threshold: self.config.loop_detection_failure_streak,
         });
 
+        // With `[agent.tool_retrieval]`, offer only the tools selected for this
+        // turn; `tool_search` widens the selection between iterations.
+        let tool_selection =
+            tool_retrieval::begin_selection(&self.tool_specs, &[ChatMessage::user(user_message)])
+                .await;
+        let mut tool_specs = tool_selection.as_ref().map_or_else(
+            || self.tool_specs.clone(),
+            |selection| selection.active_specs(),
+        );
+
+        // Hint routes resolve to other providers; only the default model is
+        // known to be served by `provider_name`.
+        let context_budget = if effective_model == self.model_name {
+            ContextBudget::for_provider_model(
+                &self.config,
+                &self.ollama_config,
+                &self.provider_name,
+                &effective_model,
+            )
+        } else {
+            ContextBudget::for_model(&self.config, &effective_model)
+        };
+        let mut tool_specs_tokens = if self.tool_dispatcher.should_send_tool_specs() {
+            context_budget::estimate_tool_specs_tokens(&tool_specs)
+        } else {
+            0
+        };
+
         for iteration in 0..self.config.max_tool_iterations {
-            let messages = self.tool_dispatcher.to_provider_messages(&self.history);
+            let mut messages = self.tool_dispatcher.to_provider_messages(&self.history);
+            if self.describes_tools_per_request() {
+                if let Some(system) = messages.iter_mut().find(|message| message.role == "system") {
+                    system
+                        .content
+                        .push_str(&build_tool_instructions_from_specs(&tool_specs));
+                }
+            }
+            let outcome = context_budget::fit_messages(
+                &mut messages,
+                context_budget
+                    .input_limit()
+                    .saturating_sub(tool_specs_tokens),
+            );
+            if outcome.changed() {
+                tracing::debug!(
+                    model = effective_model.as_str(),
+                    context_window = context_budget.context_window,
+                    estimated_tokens = outcome.estimated_tokens,
+                    fitted_tokens = outcome.fitted_tokens,
+                    truncated = outcome.truncated_messages,
+                    dropped = outcome.dropped_messages,
+                    "Fitted request into context budget"
+                );
+            }
             let response = match self
                 .provider
                 .chat(
                     ChatRequest {
                         messages: &messages,
                         tools: if self.tool_dispatcher.should_send_tool_specs() {
-                            Some(&self.tool_specs)
+                            Some(&tool_specs)
                         } else {
                             None
                         },
+                        response_format: None,
                     },
                     &effective_model,
                     self.temperature,
@@ -616,6 +743,7 @@ impl Agent {
                 text: response.text.clone(),
                 tool_calls: response.tool_calls.clone(),
                 reasoning_content: response.reasoning_content.clone(),
+                thinking: response.thinking.clone(),
             });
 
             let results = self.execute_tools(&calls).await;
@@ -630,6 +758,12 @@ impl Agent {
             let formatted = self.tool_dispatcher.format_results(&results);
             self.history.push(formatted);
             self.trim_history();
+            if let Some(selection) = tool_selection.as_ref().filter(|s| s.take_changed()) {
+                tool_specs = selection.active_specs();
+                if self.tool_dispatcher.should_send_tool_specs() {
+                    tool_specs_tokens = context_budget::estimate_tool_specs_tokens(&tool_specs);
+                }
+            }
 
             // ── Loop detection: check verdict ────────────────────
             match loop_detector.check() {
@@ -661,7 +795,7 @@ impl Agent {
 
     pub async fn run_interactive(&mut self) -> Result<()> {
         println!("🦀 ZeroClaw Interactive Mode");
-        println!("Type /quit to exit.\n");
+        println!("Type /quit to exit; /undo, /retry, /fork, /branches and /switch edit history.\n");
 
         let (tx, mut rx) = tokio::sync::mpsc::channel(32);
         let cli = crate::channels::CliChannel::new();
@@ -670,8 +804,37 @@ impl Agent {
             let _ = crate::channels::Channel::listen(&cli, tx).await;
         });
 
+        let mut branches = branching::ConversationBranches::default();
         while let Some(msg) = rx.recv().await {
-            let response = match self.turn(&msg.content).await {
+            let mut input = msg.content;
+            let mut retry_model = None;
+            if let Some(parsed) = branching::parse_history_command(&input) {
+                let command = match parsed {
+                    Ok(command) => command,
+                    Err(usage) => {
+                        println!("{usage}\n");
+                        continue;
+                    }
+                };
+                let (notice, retry_input) = branches.apply(&command, &mut self.history);
+                let Some(retry_input) = retry_input else {
+                    println!("{}\n", notice.trim_end());
+                    continue;
+                };
+                if let branching::HistoryCommand::Retry(model) = command {
+                    retry_model = model;
+                }
+                input = retry_input;
+            }
+
+            // A `/retry <model>` answers one turn with another model.
+            let default_model =
+                retry_model.map(|model| std::mem::replace(&mut self.model_name, model));
+            let result = self.turn(&input).await;
+            if let Some(default_model) = default_model {
+                self.model_name = default_model;
+            }
+            let response = match result {
                 Ok(resp) => resp,
                 Err(e) => {
                     eprintln!("\nError: {e}\n");
@@ -776,6 +939,7 @@ mod tests {
                     tool_calls: vec![],
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 });
             }
             Ok(guard.remove(0))
@@ -813,6 +977,7 @@ mod tests {
                     tool_calls: vec![],
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 });
             }
             Ok(guard.remove(0))
@@ -852,6 +1017,7 @@ mod tests {
                 tool_calls: vec![],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             }]),
         });
 
@@ -892,12 +1058,14 @@ mod tests {
                     }],
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 },
                 crate::providers::ChatResponse {
                     text: Some("done".into()),
                     tool_calls: vec![],
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 },
             ]),
         });
@@ -939,6 +1107,7 @@ mod tests {
                 tool_calls: vec![],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             }]),
             seen_models: seen_models.clone(),
         });
@@ -983,4 +1152,131 @@ mod tests {
         let seen = seen_models.lock();
         assert_eq!(seen.as_slice(), &["hint:fast".to_string()]);
     }
+
+    struct ToolCaptureProvider {
+        responses: Mutex<Vec<crate::providers::ChatResponse>>,
+        seen_tools: Arc<Mutex<Vec<Vec<String>>>>,
+    }
+
+    #[async_trait]
+    impl Provider for ToolCaptureProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            Ok("ok".into())
+        }
+
+        async fn chat(
+            &self,
+            request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<crate::providers::ChatResponse> {
+            let names = request
+                .tools
+                .unwrap_or_default()
+                .iter()
+                .map(|spec| spec.name.clone())
+                .collect();
+            self.seen_tools.lock().push(names);
+            Ok(self.responses.lock().remove(0))
+        }
+    }
+
+    struct NamedTool(&'static str, &'static str);
+
+    #[async_trait]
+    impl Tool for NamedTool {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            self.1
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> Result<crate::tools::ToolResult> {
+            Ok(crate::tools::ToolResult {
+                success: true,
+                output: format!("{}-out", self.0),
+                error: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn turn_offers_retrieved_tools_and_widens_them_after_tool_search() {
+        let seen_tools = Arc::new(Mutex::new(Vec::new()));
+        let response = |text: &str, tool_calls: Vec<crate::providers::ToolCall>| {
+            crate::providers::ChatResponse {
+                text: Some(text.into()),
+                tool_calls,
+                usage: None,
+                reasoning_content: None,
+                thinking: Vec::new(),
+            }
+        };
+        let provider = Box::new(ToolCaptureProvider {
+            responses: Mutex::new(vec![
+                response(
+                    "",
+                    vec![crate::providers::ToolCall {
+                        id: "tc1".into(),
+                        name: "tool_search".into(),
+                        arguments: r#"{"query": "weather in a city"}"#.into(),
+                    }],
+                ),
+                response("Sunny.", vec![]),
+            ]),
+            seen_tools: seen_tools.clone(),
+        });
+
+        let memory_cfg = crate::config::MemoryConfig {
+            backend: "none".into(),
+            ..crate::config::MemoryConfig::default()
+        };
+        let mem: Arc<dyn Memory> = Arc::from(
+            crate::memory::create_memory(&memory_cfg, std::path::Path::new("/tmp"), None)
+                .expect("memory creation should succeed with valid config"),
+        );
+
+        let observer: Arc<dyn Observer> = Arc::from(crate::observability::NoopObserver {});
+        let mut agent = Agent::builder()
+            .provider(provider)
+            .tools(vec![
+                Box::new(crate::tools::ToolSearchTool::new()),
+                Box::new(NamedTool("calendar_events", "Read calendar events")),
+                Box::new(NamedTool("github_issues", "List and create GitHub issues")),
+                Box::new(NamedTool("weather", "Current weather for a city")),
+            ])
+            .memory(mem)
+            .observer(observer)
+            .tool_dispatcher(Box::new(NativeToolDispatcher))
+            .workspace_dir(std::path::PathBuf::from("/tmp"))
+            .tool_retriever(Arc::new(ToolRetriever::new(
+                crate::config::ToolRetrievalConfig {
+                    enabled: true,
+                    top_k: 1,
+                    min_tools: 0,
+                    pinned_tools: vec![],
+                },
+                Arc::new(crate::memory::embeddings::NoopEmbedding),
+            )))
+            .build()
+            .expect("agent builder should succeed with valid config");
+
+        let reply = agent.turn("what is on my calendar").await.unwrap();
+        assert_eq!(reply, "Sunny.");
+        let seen = seen_tools.lock();
+        assert_eq!(seen[0], vec!["tool_search", "calendar_events"]);
+        assert_eq!(seen[1], vec!["tool_search", "calendar_events", "weather"]);
+    }
 }
@@ -0,0 +1,394 @@
+//! Conversation checkpoints: `/undo`, `/retry`, `/fork` and branch switching.
+//!
+//! An *exchange* starts at a user turn and runs until the next one, so the
+//! checkpoints are simply the user turns already in the history — nothing
+//! extra has to be recorded and compaction or trimming cannot leave stale
+//! bookmarks behind. Tool-result messages sent with the `user` role in
+//! prompt-guided mode and the step prompts of a plan-and-execute turn are not
+//! exchange boundaries.
+//!
+//! The CLI keeps inactive branches in [`ConversationBranches`]; channels keep
+//! them as archived sessions (see `channels::sessions`).
+
+use crate::agent::plan_execute::{PLAN_COMPLETE_PREFIX, PLAN_STEP_PREFIX};
+use crate::providers::{ChatMessage, ConversationMessage};
+use regex::Regex;
+use std::fmt::Write;
+use std::sync::LazyLock;
+
+/// Prefix of prompt-mode tool results, which travel as `user` messages.
+const TOOL_RESULTS_PREFIX: &str = "[Tool results]";
+
+/// Prefixes of `user` messages the runtime injects in the middle of an exchange.
+const MID_EXCHANGE_PREFIXES: [&str; 3] =
+    [TOOL_RESULTS_PREFIX, PLAN_STEP_PREFIX, PLAN_COMPLETE_PREFIX];
+
+/// `[2026-01-01 12:00:00 UTC] ` stamp the runtimes put before each user message.
+static TURN_TIMESTAMP_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}[^\]\n]*\] ").unwrap());
+
+/// A history entry that may open an exchange.
+pub trait BranchMessage: Clone {
+    /// Content of a user-authored turn, or `None` for anything else.
+    fn user_content(&self) -> Option<&str>;
+}
+
+impl BranchMessage for ChatMessage {
+    fn user_content(&self) -> Option<&str> {
+        let injected = MID_EXCHANGE_PREFIXES
+            .iter()
+            .any(|prefix| self.content.starts_with(prefix));
+        (self.role == "user" && !injected).then_some(self.content.as_str())
+    }
+}
+
+impl BranchMessage for ConversationMessage {
+    fn user_content(&self) -> Option<&str> {
+        match self {
+            Self::Chat(message) => message.user_content(),
+            _ => None,
+        }
+    }
+}
+
+/// Index of each exchange's opening user turn, oldest first.
+pub fn exchange_starts<M: BranchMessage>(history: &[M]) -> Vec<usize> {
+    history
+        .iter()
+        .enumerate()
+        .filter_map(|(index, message)| message.user_content().map(|_| index))
+        .collect()
+}
+
+/// The text the user typed, without the injected memory context and timestamp.
+pub fn raw_user_input(content: &str) -> String {
+    TURN_TIMESTAMP_REGEX
+        .find(content)
+        .map_or(content, |stamp| &content[stamp.end()..])
+        .trim()
+        .to_string()
+}
+
+/// Drop the last exchange and return what the user typed to start it.
+pub fn undo_last_exchange<M: BranchMessage>(history: &mut Vec<M>) -> Option<String> {
+    let start = *exchange_starts(history).last()?;
+    let input = history[start].user_content().map(raw_user_input);
+    history.truncate(start);
+    input
+}
+
+/// Keep only the first `keep` exchanges. Returns false when there are not
+/// that many.
+pub fn truncate_to_exchanges<M: BranchMessage>(history: &mut Vec<M>, keep: usize) -> bool {
+    let starts = exchange_starts(history);
+    if keep > starts.len() {
+        return false;
+    }
+    if let Some(&cut) = starts.get(keep) {
+        history.truncate(cut);
+    }
+    true
+}
+
+/// First user input of a history, for branch listings.
+pub fn history_title<M: BranchMessage>(history: &[M], max_chars: usize) -> String {
+    history
+        .iter()
+        .find_map(BranchMessage::user_content)
+        .map(|content| {
+            let input = raw_user_input(content);
+            let line = input.lines().next().unwrap_or_default();
+            crate::util::truncate_with_ellipsis(line, max_chars)
+        })
+        .unwrap_or_default()
+}
+
+/// History-editing command typed in interactive mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryCommand {
+    /// Remove the last exchange.
+    Undo,
+    /// Remove the last exchange and send its message again, optionally with
+    /// another model.
+    Retry(Option<String>),
+    /// Start a new branch after the first N exchanges (default: all).
+    Fork(Option<usize>),
+    /// List branches.
+    Branches,
+    /// Switch to another branch.
+    Switch(usize),
+}
+
+/// Help lines for the commands above, `(usage, description)`.
+pub const HISTORY_COMMAND_HELP: &[(&str, &str)] = &[
+    ("/undo", "Remove the last exchange"),
+    (
+        "/retry [model]",
+        "Re-send the last message, optionally with another model",
+    ),
+    (
+        "/fork [n]",
+        "Branch after exchange n (default: now); keeps the old branch",
+    ),
+    ("/branches", "List conversation branches"),
+    ("/switch <id>", "Switch to another branch"),
+];
+
+/// Parse a history command. `Err` carries a usage message for a recognised
+/// command with bad arguments.
+pub fn parse_history_command(input: &str) -> Option<Result<HistoryCommand, String>> {
+    let mut parts = input.split_whitespace();
+    let command = parts.next()?.to_ascii_lowercase();
+    let argument = parts.next();
+    let parsed = match command.as_str() {
+        "/undo" => Ok(HistoryCommand::Undo),
+        "/retry" => Ok(HistoryCommand::Retry(argument.map(str::to_string))),
+        "/fork" => match argument.map(str::parse::<usize>) {
+            None => Ok(HistoryCommand::Fork(None)),
+            Some(Ok(keep)) => Ok(HistoryCommand::Fork(Some(keep))),
+            Some(Err(_)) => Err("Usage: /fork [number-of-exchanges-to-keep]".to_string()),
+        },
+        "/branches" => Ok(HistoryCommand::Branches),
+        "/switch" => match argument.map(str::parse::<usize>) {
+            Some(Ok(id)) => Ok(HistoryCommand::Switch(id)),
+            _ => Err("Usage: /switch <branch-id> (see /branches)".to_string()),
+        },
+        _ => return None,
+    };
+    Some(parsed)
+}
+
+struct SavedBranch<M> {
+    id: usize,
+    parent: Option<usize>,
+    history: Vec<M>,
+}
+
+/// In-memory branch set for an interactive session. The active branch's
+/// history stays with the caller; only inactive branches are stored here.
+pub struct ConversationBranches<M> {
+    saved: Vec<SavedBranch<M>>,
+    current: usize,
+    current_parent: Option<usize>,
+    next_id: usize,
+}
+
+impl<M> Default for ConversationBranches<M> {
+    fn default() -> Self {
+        Self {
+            saved: Vec::new(),
+            current: 1,
+            current_parent: None,
+            next_id: 2,
+        }
+    }
+}
+
+impl<M: BranchMessage> ConversationBranches<M> {
+    pub fn current_id(&self) -> usize {
+        self.current
+    }
+
+    /// Save the active branch and continue on a new one holding its first
+    /// `keep` exchanges (all of them when `None`). Returns the new branch id.
+    pub fn fork(&mut self, history: &mut Vec<M>, keep: Option<usize>) -> Result<usize, String> {
+        let exchanges = exchange_starts(history).len();
+        let keep = keep.unwrap_or(exchanges);
+        if keep > exchanges {
+            return Err(format!(
+                "Branch {} has only {exchanges} exchange(s).",
+                self.current
+            ));
+        }
+
+        self.saved.push(SavedBranch {
+            id: self.current,
+            parent: self.current_parent,
+            history: history.clone(),
+        });
+        truncate_to_exchanges(history, keep);
+        self.current_parent = Some(self.current);
+        self.current = self.next_id;
+        self.next_id += 1;
+        Ok(self.current)
+    }
+
+    /// Make branch `id` active, saving the current one.
+    pub fn switch(&mut self, history: &mut Vec<M>, id: usize) -> Result<(), String> {
+        if id == self.current {
+            return Err(format!("Already on branch {id}."));
+        }
+        let Some(position) = self.saved.iter().position(|branch| branch.id == id) else {
+            return Err(format!("No branch {id}. Use /branches to list them."));
+        };
+        let target = self.saved.swap_remove(position);
+        self.saved.push(SavedBranch {
+            id: self.current,
+            parent: self.current_parent,
+            history: std::mem::replace(history, target.history),
+        });
+        self.current = target.id;
+        self.current_parent = target.parent;
+        Ok(())
+    }
+
+    /// Human-readable branch list with the active branch marked.
+    pub fn describe(&self, history: &[M]) -> String {
+        let mut rows: Vec<(usize, Option<usize>, &[M])> = self
+            .saved
+            .iter()
+            .map(|branch| (branch.id, branch.parent, branch.history.as_slice()))
+            .collect();
+        rows.push((self.current, self.current_parent, history));
+        rows.sort_by_key(|(id, _, _)| *id);
+
+        let mut out = String::from("Branches:\n");
+        for (id, parent, branch_history) in rows {
+            let _ = write!(
+                out,
+                "{} {id}: {} exchange(s)",
+                if id == self.current { "*" } else { " " },
+                exchange_starts(branch_history).len()
+            );
+            if let Some(parent) = parent {
+                let _ = write!(out, ", forked from {parent}");
+            }
+            let title = branch_history
+                .iter()
+                .rev()
+                .find_map(BranchMessage::user_content)
+                .map(raw_user_input)
+                .unwrap_or_default();
+            if !title.is_empty() {
+                let line = title.lines().next().unwrap_or_default();
+                let _ = write!(
+                    out,
+                    " — last: {}",
+                    crate::util::truncate_with_ellipsis(line, 50)
+                );
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Apply a parsed command. Returns the reply to show and, for `/retry`,
+    /// the input the caller should send again.
+    pub fn apply(
+        &mut self,
+        command: &HistoryCommand,
+        history: &mut Vec<M>,
+    ) -> (String, Option<String>) {
+        match command {
+            HistoryCommand::Undo => match undo_last_exchange(history) {
+                Some(input) => (
+                    format!(
+                        "Removed the last exchange (\"{}\").",
+                        crate::util::truncate_with_ellipsis(&input, 60)
+                    ),
+                    None,
+                ),
+                None => ("Nothing to undo.".to_string(), None),
+            },
+            HistoryCommand::Retry(_) => match undo_last_exchange(history) {
+                Some(input) if !input.is_empty() => (String::new(), Some(input)),
+                _ => ("Nothing to retry.".to_string(), None),
+            },
+            HistoryCommand::Fork(keep) => match self.fork(history, *keep) {
+                Ok(id) => (
+                    format!(
+                        "Now on branch {id} ({} exchange(s) kept). Use /branches to list and /switch <id> to go back.",
+                        exchange_starts(history).len()
+                    ),
+                    None,
+                ),
+                Err(err) => (err, None),
+            },
+            HistoryCommand::Branches => (self.describe(history), None),
+            HistoryCommand::Switch(id) => match self.switch(history, *id) {
+                Ok(()) => (
+                    format!(
+                        "Switched to branch {id} ({} exchange(s)).",
+                        exchange_starts(history).len()
+                    ),
+                    None,
+                ),
+                Err(err) => (err, None),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation(turns: &[&str]) -> Vec<ChatMessage> {
+        let mut history = vec![ChatMessage::system("system")];
+        for turn in turns {
+            history.push(ChatMessage::user(format!(
+                "[Memory context]\n- fact\n[2026-10-18 09:00:00 UTC] {turn}"
+            )));
+            history.push(ChatMessage::assistant(format!("re: {turn}")));
+        }
+        history
+    }
+
+    #[test]
+    fn undo_skips_prompt_mode_tool_results() {
+        let mut history = conversation(&["first", "second"]);
+        history.insert(4, ChatMessage::assistant("<tool_call>…</tool_call>"));
+        history.insert(5, ChatMessage::user("[Tool results]\nok"));
+
+        assert_eq!(exchange_starts(&history), vec![1, 3]);
+        assert_eq!(undo_last_exchange(&mut history).as_deref(), Some("second"));
+        assert_eq!(history.len(), 3);
+        assert_eq!(undo_last_exchange(&mut history).as_deref(), Some("first"));
+        assert_eq!(undo_last_exchange(&mut history), None);
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn raw_user_input_strips_context_and_timestamp() {
+        assert_eq!(
+            raw_user_input("[Memory context]\n- x\n[2026-10-18 09:00:00 CEST] hello there"),
+            "hello there"
+        );
+        assert_eq!(raw_user_input("plain"), "plain");
+    }
+
+    #[test]
+    fn fork_and_switch_preserve_both_branches() {
+        let mut branches = ConversationBranches::default();
+        let mut history = conversation(&["a", "b", "c"]);
+
+        let (_, retry) = branches.apply(&HistoryCommand::Fork(Some(1)), &mut history);
+        assert!(retry.is_none());
+        assert_eq!(branches.current_id(), 2);
+        assert_eq!(exchange_starts(&history).len(), 1);
+
+        history.push(ChatMessage::user("[2026-10-18 09:05:00 UTC] d"));
+        let listing = branches.describe(&history);
+        assert!(listing.contains("* 2: 2 exchange(s), forked from 1 — last: d"));
+        assert!(listing.contains("  1: 3 exchange(s) — last: c"));
+
+        branches.switch(&mut history, 1).unwrap();
+        assert_eq!(exchange_starts(&history).len(), 3);
+        assert!(branches.switch(&mut history, 7).is_err());
+        assert!(branches.fork(&mut history, Some(9)).is_err());
+    }
+
+    #[test]
+    fn parses_history_commands() {
+        assert_eq!(
+            parse_history_command("/retry gpt-4o"),
+            Some(Ok(HistoryCommand::Retry(Some("gpt-4o".into()))))
+        );
+        assert_eq!(
+            parse_history_command("/fork"),
+            Some(Ok(HistoryCommand::Fork(None)))
+        );
+        assert!(matches!(parse_history_command("/switch x"), Some(Err(_))));
+        assert_eq!(parse_history_command("/model"), None);
+    }
+}
@@ -0,0 +1,497 @@
+//! Crash-safe checkpoints for in-flight tool-loop turns.
+//!
+//! A channel turn can run for dozens of tool iterations. When it runs inside
+//! [`scope_turn_checkpoint`], `run_tool_call_loop` writes the turn's history,
+//! the tool calls it is about to execute and the iteration count to SQLite
+//! at `<workspace>/state/turn_checkpoints.db` after every iteration. A turn
+//! that finishes (or is cancelled) removes its row, so any row left at start
+//! up belongs to a turn the process died in the middle of.
+//!
+//! Tool calls that were in flight when the process died may or may not have
+//! taken effect. On resume the model is told which calls were lost, and the
+//! first identical repeat of a side-effecting call is held back with a notice
+//! so that it checks the current state instead of blindly running it twice.
+
+use crate::providers::ChatMessage;
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const CHECKPOINT_DB_FILE: &str = "turn_checkpoints.db";
+
+const CHECKPOINT_COLUMNS: &str = "id, channel, sender, reply_target, thread_ts, history_key, \
+     provider, model, message, history, pending, iteration, attempts, updated_at";
+
+/// Tools that only read state, so repeating a lost call is harmless.
+const RETRY_SAFE_TOOLS: &[&str] = &[
+    "agents_list",
+    "artifact_read",
+    "content_search",
+    "cron_list",
+    "cron_runs",
+    "docx_read",
+    "file_read",
+    "glob_search",
+    "hardware_board_info",
+    "hardware_memory_map",
+    "image_info",
+    "memory_recall",
+    "pdf_read",
+    "sop_list",
+    "sop_status",
+    "state_get",
+    "subagent_list",
+    "web_fetch",
+    "web_search_tool",
+];
+
+/// Where an in-flight turn came from and where its reply goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnOrigin {
+    pub channel: String,
+    pub sender: String,
+    pub reply_target: String,
+    pub thread_ts: Option<String>,
+    /// Conversation history key of the sender.
+    pub history_key: String,
+    pub provider: String,
+    pub model: String,
+    /// The user message that started the turn.
+    pub message: String,
+}
+
+/// A tool call requested by the model whose result was not recorded yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+impl PendingToolCall {
+    /// Whether repeating this call cannot change anything.
+    pub fn is_retry_safe(&self) -> bool {
+        RETRY_SAFE_TOOLS.contains(&self.name.as_str())
+    }
+}
+
+/// A turn whose process stopped before it finished.
+#[derive(Debug, Clone)]
+pub struct InterruptedTurn {
+    pub id: String,
+    pub origin: TurnOrigin,
+    pub history: Vec<ChatMessage>,
+    pub pending: Vec<PendingToolCall>,
+    /// Tool-loop iterations completed before the interruption.
+    pub iteration: usize,
+    /// Times this turn has already been resumed.
+    pub attempts: u32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl InterruptedTurn {
+    /// User message appended to the history when the turn is resumed.
+    pub fn resume_prompt(&self) -> String {
+        let mut prompt = format!(
+            "[Resumed after restart] The service restarted while you were working on this \
+             request, after {} tool iteration(s). Your progress so far is above; continue \
+             from where you left off and finish the original request.",
+            self.iteration
+        );
+        if !self.pending.is_empty() {
+            prompt.push_str("\n\nThese tool calls were in flight and their results were lost:");
+            for call in &self.pending {
+                let note = if call.is_retry_safe() {
+                    "read-only, safe to repeat"
+                } else {
+                    "may already have taken effect; check the current state before repeating it"
+                };
+                let _ = write!(prompt, "\n- {} {} ({note})", call.name, call.arguments);
+            }
+        }
+        prompt
+    }
+}
+
+/// SQLite-backed store of in-flight turns.
+pub struct TurnCheckpointStore {
+    conn: Mutex<Connection>,
+}
+
+impl TurnCheckpointStore {
+    /// Default database location inside a workspace.
+    pub fn db_path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("state").join(CHECKPOINT_DB_FILE)
+    }
+
+    /// Open (creating if needed) the store for a workspace.
+    pub fn open(workspace_dir: &Path) -> Result<Self> {
+        Self::open_at(&Self::db_path(workspace_dir))
+    }
+
+    pub fn open_at(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "Failed to create checkpoint directory: {}",
+                    parent.display()
+                )
+            })?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open checkpoint DB: {}", path.display()))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA busy_timeout = 5000;
+             CREATE TABLE IF NOT EXISTS turn_checkpoints (
+                id           TEXT PRIMARY KEY,
+                channel      TEXT NOT NULL,
+                sender       TEXT NOT NULL,
+                reply_target TEXT NOT NULL,
+                thread_ts    TEXT,
+                history_key  TEXT NOT NULL,
+                provider     TEXT NOT NULL,
+                model        TEXT NOT NULL,
+                message      TEXT NOT NULL,
+                history      TEXT NOT NULL DEFAULT '[]',
+                pending      TEXT NOT NULL DEFAULT '[]',
+                iteration    INTEGER NOT NULL DEFAULT 0,
+                attempts     INTEGER NOT NULL DEFAULT 0,
+                started_at   TEXT NOT NULL,
+                updated_at   TEXT NOT NULL
+             );",
+        )
+        .context("Failed to initialize checkpoint schema")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Record a new in-flight turn and return its ID.
+    pub fn begin(&self, origin: &TurnOrigin, history: &[ChatMessage]) -> Result<String> {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let now = timestamp(Utc::now());
+        self.conn.lock().execute(
+            "INSERT INTO turn_checkpoints
+                (id, channel, sender, reply_target, thread_ts, history_key, provider, model,
+                 message, history, started_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11)",
+            params![
+                id,
+                origin.channel,
+                origin.sender,
+                origin.reply_target,
+                origin.thread_ts,
+                origin.history_key,
+                origin.provider,
+                origin.model,
+                origin.message,
+                serde_json::to_string(history)?,
+                now
+            ],
+        )?;
+        Ok(id)
+    }
+
+    /// Overwrite the turn's progress.
+    pub fn record(
+        &self,
+        id: &str,
+        iteration: usize,
+        history: &[ChatMessage],
+        pending: &[PendingToolCall],
+    ) -> Result<()> {
+        self.conn.lock().execute(
+            "UPDATE turn_checkpoints
+                SET history = ?1, pending = ?2, iteration = ?3, updated_at = ?4
+              WHERE id = ?5",
+            params![
+                serde_json::to_string(history)?,
+                serde_json::to_string(pending)?,
+                i64::try_from(iteration).unwrap_or(i64::MAX),
+                timestamp(Utc::now()),
+                id
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Count a resume attempt, so a turn that keeps crashing the process is
+    /// eventually given up on.
+    pub fn mark_resumed(&self, id: &str) -> Result<()> {
+        self.conn.lock().execute(
+            "UPDATE turn_checkpoints SET attempts = attempts + 1 WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    /// Drop the checkpoint of a finished turn.
+    pub fn finish(&self, id: &str) -> Result<()> {
+        self.conn
+            .lock()
+            .execute("DELETE FROM turn_checkpoints WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Every turn left behind by a previous process, oldest first.
+    pub fn interrupted(&self) -> Result<Vec<InterruptedTurn>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {CHECKPOINT_COLUMNS} FROM turn_checkpoints ORDER BY started_at, rowid"
+        ))?;
+        let rows = stmt.query_map([], map_checkpoint_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+}
+
+/// Checkpoint handle for the turn running in the current task.
+pub struct TurnCheckpoint {
+    store: Arc<TurnCheckpointStore>,
+    id: String,
+    /// Side-effecting calls lost in a crash; the first identical repeat is held back.
+    guarded_calls: Mutex<Vec<PendingToolCall>>,
+}
+
+impl TurnCheckpoint {
+    /// Start checkpointing a new turn.
+    pub fn begin(
+        store: Arc<TurnCheckpointStore>,
+        origin: &TurnOrigin,
+        history: &[ChatMessage],
+    ) -> Result<Arc<Self>> {
+        let id = store.begin(origin, history)?;
+        Ok(Arc::new(Self {
+            store,
+            id,
+            guarded_calls: Mutex::new(Vec::new()),
+        }))
+    }
+
+    /// Continue checkpointing an interrupted turn under its existing ID.
+    pub fn resume(store: Arc<TurnCheckpointStore>, turn: &InterruptedTurn) -> Arc<Self> {
+        let guarded = turn
+            .pending
+            .iter()
+            .filter(|call| !call.is_retry_safe())
+            .cloned()
+            .collect();
+        Arc::new(Self {
+            store,
+            id: turn.id.clone(),
+            guarded_calls: Mutex::new(guarded),
+        })
+    }
+
+    /// Remove the checkpoint once the turn has a reply (or was cancelled).
+    pub fn finish(&self) {
+        if let Err(err) = self.store.finish(&self.id) {
+            tracing::warn!(turn = %self.id, "Failed to clear turn checkpoint: {err}");
+        }
+    }
+}
+
+tokio::task_local! {
+    static TURN_CHECKPOINT: Option<Arc<TurnCheckpoint>>;
+}
+
+/// Run `fut` with tool-loop checkpoints written to `checkpoint`.
+pub(crate) async fn scope_turn_checkpoint<F: std::future::Future>(
+    checkpoint: Option<Arc<TurnCheckpoint>>,
+    fut: F,
+) -> F::Output {
+    TURN_CHECKPOINT.scope(checkpoint, fut).await
+}
+
+/// Persist loop progress for the current turn, if it is checkpointed.
+pub(crate) fn record_iteration(
+    iteration: usize,
+    history: &[ChatMessage],
+    pending: &[PendingToolCall],
+) {
+    let Some(checkpoint) = TURN_CHECKPOINT.try_with(Clone::clone).ok().flatten() else {
+        return;
+    };
+    if let Err(err) = checkpoint
+        .store
+        .record(&checkpoint.id, iteration, history, pending)
+    {
+        tracing::warn!(turn = %checkpoint.id, "Failed to write turn checkpoint: {err}");
+    }
+}
+
+/// Hold back the first repeat of a side-effecting call lost in a crash.
+///
+/// Returns the notice to give the model instead of running the call.
+pub(crate) fn take_resume_guard(name: &str, arguments: &serde_json::Value) -> Option<String> {
+    let checkpoint = TURN_CHECKPOINT.try_with(Clone::clone).ok().flatten()?;
+    let mut guarded = checkpoint.guarded_calls.lock();
+    let index = guarded
+        .iter()
+        .position(|call| call.name == name && &call.arguments == arguments)?;
+    guarded.remove(index);
+    Some(format!(
+        "Not run: this exact `{name}` call was in flight when the service restarted and may \
+         already have taken effect. Check the current state first; call it again if it still \
+         needs to run."
+    ))
+}
+
+fn map_checkpoint_row(row: &Row<'_>) -> rusqlite::Result<InterruptedTurn> {
+    let history: String = row.get(9)?;
+    let pending: String = row.get(10)?;
+    let iteration: i64 = row.get(11)?;
+    let attempts: i64 = row.get(12)?;
+    Ok(InterruptedTurn {
+        id: row.get(0)?,
+        origin: TurnOrigin {
+            channel: row.get(1)?,
+            sender: row.get(2)?,
+            reply_target: row.get(3)?,
+            thread_ts: row.get(4)?,
+            history_key: row.get(5)?,
+            provider: row.get(6)?,
+            model: row.get(7)?,
+            message: row.get(8)?,
+        },
+        // A corrupt snapshot resumes nothing but is still reported to the sender.
+        history: serde_json::from_str(&history).unwrap_or_default(),
+        pending: serde_json::from_str(&pending).unwrap_or_default(),
+        iteration: usize::try_from(iteration).unwrap_or(0),
+        attempts: u32::try_from(attempts).unwrap_or(u32::MAX),
+        updated_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(13)?)
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or_default(),
+    })
+}
+
+/// Fixed-width UTC timestamps so SQL string comparison orders them correctly.
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn origin() -> TurnOrigin {
+        TurnOrigin {
+            channel: "slack".into(),
+            sender: "alice".into(),
+            reply_target: "C123".into(),
+            thread_ts: Some("1700.1".into()),
+            history_key: "slack_1700.1_alice".into(),
+            provider: "openrouter".into(),
+            model: "model".into(),
+            message: "refactor the parser".into(),
+        }
+    }
+
+    #[test]
+    fn unfinished_turns_survive_reopen() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = TurnCheckpointStore::open(tmp.path()).unwrap();
+        let finished = store.begin(&origin(), &[]).unwrap();
+        let crashed = store.begin(&origin(), &[]).unwrap();
+        let pending = vec![PendingToolCall {
+            name: "shell".into(),
+            arguments: json!({"command": "cargo fmt"}),
+        }];
+        store
+            .record(
+                &crashed,
+                3,
+                &[ChatMessage::user("refactor the parser")],
+                &pending,
+            )
+            .unwrap();
+        store.finish(&finished).unwrap();
+        drop(store);
+
+        let store = TurnCheckpointStore::open(tmp.path()).unwrap();
+        store.mark_resumed(&crashed).unwrap();
+        let turns = store.interrupted().unwrap();
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].id, crashed);
+        assert_eq!(turns[0].origin, origin());
+        assert_eq!(turns[0].iteration, 3);
+        assert_eq!(turns[0].attempts, 1);
+        assert_eq!(turns[0].history.len(), 1);
+        assert_eq!(turns[0].pending, pending);
+    }
+
+    #[test]
+    fn resume_prompt_flags_side_effecting_calls() {
+        let turn = InterruptedTurn {
+            id: "t".into(),
+            origin: origin(),
+            history: Vec::new(),
+            pending: vec![
+                PendingToolCall {
+                    name: "file_read".into(),
+                    arguments: json!({"path": "a.rs"}),
+                },
+                PendingToolCall {
+                    name: "shell".into(),
+                    arguments: json!({"command": "git push"}),
+                },
+            ],
+            iteration: 7,
+            attempts: 0,
+            updated_at: Utc::now(),
+        };
+        let prompt = turn.resume_prompt();
+        assert!(prompt.contains("after 7 tool iteration(s)"));
+        assert!(prompt.contains("- file_read {\"path\":\"a.rs\"} (read-only, safe to repeat)"));
+        assert!(
+            prompt.contains("- shell {\"command\":\"git push\"} (may already have taken effect")
+        );
+    }
+
+    #[tokio::test]
+    async fn resume_guard_holds_back_first_repeat_of_side_effecting_call() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Arc::new(TurnCheckpointStore::open(tmp.path()).unwrap());
+        let id = store.begin(&origin(), &[]).unwrap();
+        let push = json!({"command": "git push"});
+        let read = json!({"path": "a.rs"});
+        store
+            .record(
+                &id,
+                2,
+                &[],
+                &[
+                    PendingToolCall {
+                        name: "shell".into(),
+                        arguments: push.clone(),
+                    },
+                    PendingToolCall {
+                        name: "file_read".into(),
+                        arguments: read.clone(),
+                    },
+                ],
+            )
+            .unwrap();
+        let turn = store.interrupted().unwrap().remove(0);
+        let checkpoint = TurnCheckpoint::resume(Arc::clone(&store), &turn);
+
+        scope_turn_checkpoint(Some(Arc::clone(&checkpoint)), async {
+            assert!(take_resume_guard("file_read", &read).is_none());
+            assert!(take_resume_guard("shell", &json!({"command": "ls"})).is_none());
+            assert!(take_resume_guard("shell", &push).is_some());
+            assert!(take_resume_guard("shell", &push).is_none());
+            record_iteration(3, &[ChatMessage::user("x")], &[]);
+        })
+        .await;
+        assert_eq!(store.interrupted().unwrap()[0].iteration, 3);
+
+        checkpoint.finish();
+        assert!(store.interrupted().unwrap().is_empty());
+        assert!(take_resume_guard("shell", &push).is_none());
+    }
+}
@@ -0,0 +1,591 @@
+//! Token-aware context budgeting.
+//!
+//! Estimates the token cost of prompt parts (system prompt, memory context,
+//! tool specs, history) and fits a request into the model's context window.
+//! Oversized messages such as long tool outputs are truncated first; the
+//! oldest history is dropped only when that is not enough.
+
+use crate::config::{AgentConfig, ModelRouteConfig, OllamaConfig};
+use crate::multimodal;
+use crate::providers::ChatMessage;
+use crate::tools::ToolSpec;
+
+/// Context window assumed for models without known metadata.
+pub const DEFAULT_CONTEXT_WINDOW_TOKENS: usize = 32_768;
+
+/// Approximate per-message framing overhead (role markers, separators).
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Flat token estimate per attached image.
+const IMAGE_TOKENS: usize = 1_000;
+
+/// A single message never gets truncated below this many tokens.
+const MIN_TRUNCATED_MESSAGE_TOKENS: usize = 256;
+
+/// Known context windows keyed by model-name prefix (matched after stripping
+/// any `vendor/` namespace). More specific prefixes come first.
+const KNOWN_CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("claude", 200_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-5", 400_000),
+    ("gpt-3.5", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("gemini-1.5-pro", 2_097_152),
+    ("gemini", 1_048_576),
+    ("grok", 131_072),
+    ("deepseek", 128_000),
+    ("llama-3.1", 131_072),
+    ("llama-3.2", 131_072),
+    ("llama-3.3", 131_072),
+    ("llama3.1", 131_072),
+    ("llama3.2", 131_072),
+    ("llama3.3", 131_072),
+    ("llama3", 8_192),
+    ("llama-4", 1_048_576),
+    ("qwen3", 32_768),
+    ("qwen2.5", 32_768),
+    ("qwen", 32_768),
+    ("mistral-large", 131_072),
+    ("mistral-small", 32_768),
+    ("mistral", 32_768),
+    ("mixtral", 32_768),
+    ("gemma3", 131_072),
+    ("gemma2", 8_192),
+    ("phi4", 16_384),
+    ("phi3", 4_096),
+    ("command-r", 128_000),
+    ("kimi", 131_072),
+    ("moonshot", 131_072),
+    ("glm-4", 131_072),
+];
+
+/// Estimate the token count of `text`.
+///
+/// Uses ~4 characters per token for ASCII and one token per non-ASCII
+/// character, which errs on the side of over-counting for CJK and other
+/// scripts. Image markers are charged a flat cost instead of their payload.
+pub fn estimate_tokens(text: &str) -> usize {
+    let (text, images) = if text.contains("[IMAGE:") {
+        let (cleaned, refs) = multimodal::parse_image_markers(text);
+        (std::borrow::Cow::Owned(cleaned), refs.len())
+    } else {
+        (std::borrow::Cow::Borrowed(text), 0)
+    };
+
+    let (ascii, other) = text.chars().fold((0usize, 0usize), |(ascii, other), c| {
+        if c.is_ascii() {
+            (ascii + 1, other)
+        } else {
+            (ascii, other + 1)
+        }
+    });
+    ascii.div_ceil(4) + other + images * IMAGE_TOKENS
+}
+
+/// Estimate the token cost of a single chat message.
+pub fn estimate_message_tokens(message: &ChatMessage) -> usize {
+    estimate_tokens(&message.content) + MESSAGE_OVERHEAD_TOKENS
+}
+
+/// Estimate the token cost of a message list.
+pub fn estimate_messages_tokens(messages: &[ChatMessage]) -> usize {
+    messages.iter().map(estimate_message_tokens).sum()
+}
+
+/// Estimate the token cost of tool specs sent alongside a request.
+pub fn estimate_tool_specs_tokens(specs: &[ToolSpec]) -> usize {
+    specs
+        .iter()
+        .map(|spec| {
+            estimate_tokens(&spec.name)
+                + estimate_tokens(&spec.description)
+                + estimate_tokens(&spec.parameters.to_string())
+        })
+        .sum()
+}
+
+/// Built-in context window for `model` as served by `provider`.
+///
+/// Ollama runs every model with the `num_ctx` it is given rather than the
+/// family's maximum, so the window comes from `[ollama].num_ctx` there.
+fn provider_context_window(ollama: &OllamaConfig, provider: &str, model: &str) -> Option<usize> {
+    if provider.trim().eq_ignore_ascii_case("ollama") {
+        return ollama
+            .num_ctx
+            .and_then(|num_ctx| usize::try_from(num_ctx).ok())
+            .filter(|window| *window > 0);
+    }
+    known_context_window(model)
+}
+
+/// Look up the built-in context window for a model id.
+pub fn known_context_window(model: &str) -> Option<usize> {
+    let name = model
+        .rsplit('/')
+        .next()
+        .unwrap_or(model)
+        .trim()
+        .to_ascii_lowercase();
+    KNOWN_CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, window)| *window)
+}
+
+/// Token budget for requests to a specific model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextBudget {
+    /// Total context window of the model.
+    pub context_window: usize,
+    /// Tokens held back for the model's reply.
+    pub reserved_output: usize,
+}
+
+impl ContextBudget {
+    /// Resolve the budget for `model`: per-model config override, then the
+    /// global `context_window_tokens`, then built-in metadata, then the default.
+    pub fn for_model(config: &AgentConfig, model: &str) -> Self {
+        let context_window = config
+            .model_context_windows
+            .get(model)
+            .copied()
+            .filter(|window| *window > 0)
+            .or_else(|| Some(config.context_window_tokens).filter(|window| *window > 0))
+            .or_else(|| known_context_window(model))
+            .unwrap_or(DEFAULT_CONTEXT_WINDOW_TOKENS);
+        Self::with_window(config, context_window)
+    }
+
+    /// Like [`Self::for_model`], but takes the built-in window from the
+    /// provider serving `model`: for `ollama` that is `[ollama].num_ctx`,
+    /// falling back to the default rather than the model family's maximum.
+    pub fn for_provider_model(
+        config: &AgentConfig,
+        ollama: &OllamaConfig,
+        provider: &str,
+        model: &str,
+    ) -> Self {
+        let context_window = config
+            .model_context_windows
+            .get(model)
+            .copied()
+            .filter(|window| *window > 0)
+            .or_else(|| Some(config.context_window_tokens).filter(|window| *window > 0))
+            .or_else(|| provider_context_window(ollama, provider, model))
+            .unwrap_or(DEFAULT_CONTEXT_WINDOW_TOKENS);
+        Self::with_window(config, context_window)
+    }
+
+    /// Like [`Self::for_provider_model`], but ignores the global
+    /// `context_window_tokens` override, which describes the active model
+    /// rather than every route.
+    pub fn for_route_model(
+        config: &AgentConfig,
+        ollama: &OllamaConfig,
+        route: &ModelRouteConfig,
+    ) -> Self {
+        let context_window = config
+            .model_context_windows
+            .get(&route.model)
+            .copied()
+            .filter(|window| *window > 0)
+            .or_else(|| provider_context_window(ollama, &route.provider, &route.model))
+            .unwrap_or(DEFAULT_CONTEXT_WINDOW_TOKENS);
+        Self::with_window(config, context_window)
+    }
+
+    fn with_window(config: &AgentConfig, context_window: usize) -> Self {
+        Self {
+            context_window,
+            // Never reserve more than a quarter of small windows for output.
+            reserved_output: config.reserved_output_tokens.min(context_window / 4),
+        }
+    }
+
+    /// Tokens available for the prompt (messages plus tool specs).
+    pub fn input_limit(&self) -> usize {
+        self.context_window.saturating_sub(self.reserved_output)
+    }
+}
+
+/// A larger-context model to switch to when a request overflows the window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextEscalation {
+    /// Model id to pass to the provider (`hint:<name>` for routed providers).
+    pub model: String,
+    /// Budget of the escalated model.
+    pub budget: ContextBudget,
+}
+
+/// Routes from `model_routes` with a larger context window than `current`,
+/// smallest first.
+///
+/// A routed provider resolves `hint:` models itself, so every route is
+/// eligible; otherwise only routes served by `provider_name` are.
+pub fn escalation_candidates(
+    config: &AgentConfig,
+    ollama: &OllamaConfig,
+    routes: &[ModelRouteConfig],
+    provider_name: &str,
+    current: ContextBudget,
+    routed: bool,
+) -> Vec<ContextEscalation> {
+    let mut candidates: Vec<ContextEscalation> = routes
+        .iter()
+        .filter(|route| routed || route.provider == provider_name)
+        .map(|route| ContextEscalation {
+            model: if routed {
+                format!("hint:{}", route.hint)
+            } else {
+                route.model.clone()
+            },
+            budget: ContextBudget::for_route_model(config, ollama, route),
+        })
+        .filter(|candidate| candidate.budget.context_window > current.context_window)
+        .collect();
+    candidates.sort_by_key(|candidate| candidate.budget.context_window);
+    candidates.dedup_by(|a, b| a.model == b.model);
+    candidates
+}
+
+/// What [`fit_messages`] had to do to make a request fit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BudgetOutcome {
+    /// Estimated tokens of the messages before fitting.
+    pub estimated_tokens: usize,
+    /// Estimated tokens of the messages after fitting.
+    pub fitted_tokens: usize,
+    /// Messages whose content was truncated.
+    pub truncated_messages: usize,
+    /// Oldest messages dropped entirely.
+    pub dropped_messages: usize,
+}
+
+impl BudgetOutcome {
+    /// Whether the messages were modified.
+    pub fn changed(&self) -> bool {
+        self.truncated_messages > 0 || self.dropped_messages > 0
+    }
+}
+
+/// Fit `messages` into `budget_tokens`.
+///
+/// System messages and the latest message are always kept; the latest is
+/// only shortened when it is tool output. Oversized user/tool messages
+/// (typically tool output) are truncated first; if the
+/// request still does not fit, the oldest non-system messages are dropped,
+/// together with any tool results orphaned by the drop.
+pub fn fit_messages(messages: &mut Vec<ChatMessage>, budget_tokens: usize) -> BudgetOutcome {
+    let estimated_tokens = estimate_messages_tokens(messages);
+    let mut outcome = BudgetOutcome {
+        estimated_tokens,
+        fitted_tokens: estimated_tokens,
+        ..BudgetOutcome::default()
+    };
+    if estimated_tokens <= budget_tokens || messages.is_empty() {
+        return outcome;
+    }
+
+    // Cap any single message at a quarter of the budget. The latest message
+    // is only truncated when it is tool output.
+    let per_message_cap = (budget_tokens / 4).max(MIN_TRUNCATED_MESSAGE_TOKENS);
+    let last = messages.len() - 1;
+    for (index, message) in messages.iter_mut().enumerate() {
+        let truncatable = if index == last {
+            is_tool_output(message)
+        } else {
+            matches!(message.role.as_str(), "user" | "tool")
+        };
+        if !truncatable
+            || message.content.contains("[IMAGE:")
+            || estimate_message_tokens(message) <= per_message_cap
+        {
+            continue;
+        }
+        message.content =
+            truncate_message_content(&message.role, &message.content, per_message_cap);
+        outcome.truncated_messages += 1;
+    }
+
+    let mut total = estimate_messages_tokens(messages);
+    let first_droppable = messages.iter().take_while(|m| m.role == "system").count();
+    while total > budget_tokens && first_droppable < messages.len() - 1 {
+        let removed = messages.remove(first_droppable);
+        total -= estimate_message_tokens(&removed);
+        outcome.dropped_messages += 1;
+        // Tool results must follow the assistant turn that requested them.
+        while first_droppable < messages.len() - 1 && messages[first_droppable].role == "tool" {
+            let orphan = messages.remove(first_droppable);
+            total -= estimate_message_tokens(&orphan);
+            outcome.dropped_messages += 1;
+        }
+    }
+
+    outcome.fitted_tokens = total;
+    outcome
+}
+
+fn is_tool_output(message: &ChatMessage) -> bool {
+    message.role == "tool"
+        || (message.role == "user" && message.content.starts_with("[Tool results]"))
+}
+
+/// Truncate message content to roughly `max_tokens`, keeping native tool
+/// result envelopes (`{"tool_call_id", "content"}`) well-formed.
+fn truncate_message_content(role: &str, content: &str, max_tokens: usize) -> String {
+    if role == "tool" {
+        if let Ok(mut envelope) = serde_json::from_str::<serde_json::Value>(content) {
+            if let Some(inner) = envelope.get("content").and_then(serde_json::Value::as_str) {
+                let truncated = truncate_text(inner, max_tokens);
+                envelope["content"] = serde_json::Value::String(truncated);
+                return envelope.to_string();
+            }
+        }
+    }
+    truncate_text(content, max_tokens)
+}
+
+fn truncate_text(text: &str, max_tokens: usize) -> String {
+    let total = estimate_tokens(text);
+    if total <= max_tokens {
+        return text.to_string();
+    }
+    // Scale the character budget by the observed chars-per-token ratio.
+    let chars = text.chars().count();
+    let keep_chars = chars * max_tokens / total.max(1);
+    let head: String = text.chars().take(keep_chars).collect();
+    format!(
+        "{}\n[... truncated ~{} tokens to fit the context window]",
+        head.trim_end(),
+        total.saturating_sub(max_tokens)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_tokens_counts_ascii_and_non_ascii() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("你好"), 2);
+        assert_eq!(
+            estimate_tokens("see [IMAGE:data:image/png;base64,AAAA] ok"),
+            estimate_tokens("see  ok") + IMAGE_TOKENS
+        );
+    }
+
+    #[test]
+    fn known_context_window_matches_model_families() {
+        assert_eq!(known_context_window("claude-sonnet-4-5"), Some(200_000));
+        assert_eq!(
+            known_context_window("anthropic/claude-3.5-haiku"),
+            Some(200_000)
+        );
+        assert_eq!(known_context_window("gpt-4o-mini"), Some(128_000));
+        assert_eq!(known_context_window("gpt-4"), Some(8_192));
+        assert_eq!(known_context_window("llama3.1:8b"), Some(131_072));
+        assert_eq!(known_context_window("llama3:8b"), Some(8_192));
+        assert_eq!(known_context_window("totally-unknown"), None);
+    }
+
+    #[test]
+    fn budget_resolution_prefers_config_overrides() {
+        let mut config = AgentConfig::default();
+        assert_eq!(
+            ContextBudget::for_model(&config, "unknown").context_window,
+            DEFAULT_CONTEXT_WINDOW_TOKENS
+        );
+        assert_eq!(
+            ContextBudget::for_model(&config, "claude-sonnet-4-5").context_window,
+            200_000
+        );
+
+        config.context_window_tokens = 16_000;
+        assert_eq!(
+            ContextBudget::for_model(&config, "claude-sonnet-4-5").context_window,
+            16_000
+        );
+
+        config
+            .model_context_windows
+            .insert("qwen2.5:7b".into(), 4_096);
+        let budget = ContextBudget::for_model(&config, "qwen2.5:7b");
+        assert_eq!(budget.context_window, 4_096);
+        assert_eq!(budget.reserved_output, 1_024);
+        assert_eq!(budget.input_limit(), 3_072);
+    }
+
+    #[test]
+    fn ollama_budget_uses_num_ctx_instead_of_family_maximum() {
+        let config = AgentConfig::default();
+        let mut ollama = OllamaConfig::default();
+        assert_eq!(
+            ContextBudget::for_provider_model(&config, &ollama, "ollama", "llama3.2:3b")
+                .context_window,
+            DEFAULT_CONTEXT_WINDOW_TOKENS
+        );
+        assert_eq!(
+            ContextBudget::for_provider_model(&config, &ollama, "openrouter", "meta/llama-3.3-70b")
+                .context_window,
+            131_072
+        );
+
+        ollama.num_ctx = Some(16_384);
+        assert_eq!(
+            ContextBudget::for_provider_model(&config, &ollama, "ollama", "llama3.2:3b")
+                .context_window,
+            16_384
+        );
+        let routes = vec![route("local", "ollama", "llama3.3:70b")];
+        let current = ContextBudget::for_provider_model(&config, &ollama, "ollama", "qwen3:4b");
+        assert!(
+            escalation_candidates(&config, &ollama, &routes, "ollama", current, true).is_empty()
+        );
+    }
+
+    fn route(hint: &str, provider: &str, model: &str) -> ModelRouteConfig {
+        ModelRouteConfig {
+            hint: hint.into(),
+            provider: provider.into(),
+            model: model.into(),
+            max_tokens: None,
+            api_key: None,
+            transport: None,
+            quality_tier: None,
+        }
+    }
+
+    #[test]
+    fn escalation_candidates_prefer_smallest_larger_window() {
+        let config = AgentConfig::default();
+        let ollama = OllamaConfig::default();
+        let routes = vec![
+            route("huge", "gemini", "gemini-1.5-pro"),
+            route("long", "openai", "gpt-4.1"),
+            route("fast", "openai", "gpt-4"),
+            route("big", "openai", "gpt-5"),
+        ];
+        let current = ContextBudget::for_model(&config, "gpt-4o");
+
+        let routed = escalation_candidates(&config, &ollama, &routes, "openai", current, true);
+        let models: Vec<&str> = routed.iter().map(|c| c.model.as_str()).collect();
+        assert_eq!(models, ["hint:big", "hint:long", "hint:huge"]);
+
+        let same_provider =
+            escalation_candidates(&config, &ollama, &routes, "openai", current, false);
+        let models: Vec<&str> = same_provider.iter().map(|c| c.model.as_str()).collect();
+        assert_eq!(models, ["gpt-5", "gpt-4.1"]);
+        assert_eq!(same_provider[0].budget.context_window, 400_000);
+    }
+
+    #[test]
+    fn escalation_candidates_ignore_global_window_override() {
+        let config = AgentConfig {
+            context_window_tokens: 64_000,
+            ..AgentConfig::default()
+        };
+        let ollama = OllamaConfig::default();
+        let routes = vec![route("big", "openai", "gpt-5")];
+        let current = ContextBudget::for_model(&config, "gpt-4o");
+        assert_eq!(current.context_window, 64_000);
+
+        let candidates = escalation_candidates(&config, &ollama, &routes, "openai", current, false);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].model, "gpt-5");
+        assert_eq!(candidates[0].budget.context_window, 400_000);
+    }
+
+    #[test]
+    fn fit_messages_is_noop_within_budget() {
+        let mut messages = vec![ChatMessage::system("sys"), ChatMessage::user("hi")];
+        let outcome = fit_messages(&mut messages, 1_000);
+        assert!(!outcome.changed());
+        assert_eq!(messages.len(), 2);
+    }
+
+    #[test]
+    fn fit_messages_truncates_large_tool_output_before_dropping() {
+        let big_output = "x".repeat(40_000);
+        let mut messages = vec![
+            ChatMessage::system("sys"),
+            ChatMessage::user("list files"),
+            ChatMessage::assistant(r#"{"content":null,"tool_calls":[]}"#),
+            ChatMessage::tool(
+                serde_json::json!({"tool_call_id": "call_1", "content": big_output}).to_string(),
+            ),
+            ChatMessage::user("thanks, summarize"),
+        ];
+
+        let outcome = fit_messages(&mut messages, 4_000);
+
+        assert_eq!(outcome.truncated_messages, 1);
+        assert_eq!(outcome.dropped_messages, 0);
+        assert!(outcome.fitted_tokens <= 4_000);
+        assert_eq!(messages.len(), 5);
+        let envelope: serde_json::Value = serde_json::from_str(&messages[3].content).unwrap();
+        assert_eq!(envelope["tool_call_id"], "call_1");
+        assert!(envelope["content"]
+            .as_str()
+            .unwrap()
+            .contains("truncated ~"));
+    }
+
+    #[test]
+    fn fit_messages_truncates_latest_tool_results() {
+        let mut messages = vec![
+            ChatMessage::system("sys"),
+            ChatMessage::user("read the log"),
+            ChatMessage::user(format!("[Tool results]\n{}", "line\n".repeat(10_000))),
+        ];
+
+        let outcome = fit_messages(&mut messages, 2_000);
+
+        assert_eq!(outcome.truncated_messages, 1);
+        assert_eq!(outcome.dropped_messages, 0);
+        assert!(outcome.fitted_tokens <= 2_000);
+        assert!(messages[2].content.starts_with("[Tool results]"));
+    }
+
+    #[test]
+    fn fit_messages_drops_oldest_and_orphaned_tool_results() {
+        let filler = "word ".repeat(200);
+        let mut messages = vec![
+            ChatMessage::system("sys"),
+            ChatMessage::assistant(format!("calling tools {filler}")),
+            ChatMessage::tool(r#"{"tool_call_id":"a","content":"ok"}"#),
+            ChatMessage::assistant(filler.clone()),
+            ChatMessage::user(filler.clone()),
+            ChatMessage::user("latest question"),
+        ];
+
+        let outcome = fit_messages(&mut messages, 600);
+
+        assert!(outcome.fitted_tokens <= 600);
+        assert_eq!(messages[0].role, "system");
+        assert_ne!(messages[1].role, "tool");
+        assert_eq!(messages.last().unwrap().content, "latest question");
+        assert_eq!(outcome.dropped_messages, 2);
+    }
+
+    #[test]
+    fn fit_messages_keeps_system_and_latest_message_even_when_over_budget() {
+        let mut messages = vec![
+            ChatMessage::system("s".repeat(4_000)),
+            ChatMessage::user("old"),
+            ChatMessage::user("u".repeat(4_000)),
+        ];
+        let outcome = fit_messages(&mut messages, 100);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(messages[1].content.len(), 4_000);
+        assert!(outcome.fitted_tokens > 100);
+    }
+}
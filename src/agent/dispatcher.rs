@@ -218,6 +218,7 @@ impl ToolDispatcher for NativeToolDispatcher {
                     text,
                     tool_calls,
                     reasoning_content,
+                    thinking,
                 } => {
                     let mut payload = serde_json::json!({
                         "content": text,
@@ -226,6 +227,9 @@ impl ToolDispatcher for NativeToolDispatcher {
                     if let Some(rc) = reasoning_content {
                         payload["reasoning_content"] = serde_json::json!(rc);
                     }
+                    if !thinking.is_empty() {
+                        payload["thinking"] = serde_json::json!(thinking);
+                    }
                     vec![ChatMessage::assistant(payload.to_string())]
                 }
                 ConversationMessage::ToolResults(results) => results
@@ -263,6 +267,7 @@ mod tests {
             tool_calls: vec![],
             usage: None,
             reasoning_content: None,
+            thinking: Vec::new(),
         };
         let dispatcher = XmlToolDispatcher;
         let (_, calls) = dispatcher.parse_response(&response);
@@ -281,6 +286,7 @@ mod tests {
             }],
             usage: None,
             reasoning_content: None,
+            thinking: Vec::new(),
         };
         let dispatcher = NativeToolDispatcher;
         let (_, calls) = dispatcher.parse_response(&response);
@@ -353,6 +359,11 @@ mod tests {
                 arguments: "{}".into(),
             }],
             reasoning_content: Some("thinking step".into()),
+            thinking: vec![crate::providers::ThinkingBlock {
+                text: "thinking step".into(),
+                signature: Some("sig-1".into()),
+                redacted: None,
+            }],
         }];
 
         let messages = dispatcher.to_provider_messages(&history);
@@ -363,6 +374,7 @@ mod tests {
         assert_eq!(payload["reasoning_content"].as_str(), Some("thinking step"));
         assert_eq!(payload["content"].as_str(), Some("answer"));
         assert!(payload["tool_calls"].is_array());
+        assert_eq!(payload["thinking"][0]["signature"].as_str(), Some("sig-1"));
     }
 
     #[test]
@@ -376,6 +388,7 @@ mod tests {
                 arguments: "{}".into(),
             }],
             reasoning_content: None,
+            thinking: Vec::new(),
         }];
 
         let messages = dispatcher.to_provider_messages(&history);
@@ -396,6 +409,7 @@ mod tests {
                 arguments: "{}".into(),
             }],
             reasoning_content: Some("should be ignored".into()),
+            thinking: Vec::new(),
         }];
 
         let messages = dispatcher.to_provider_messages(&history);
@@ -1,13 +1,21 @@
+use crate::agent::branching;
+use crate::agent::checkpoint::{self, PendingToolCall};
+use crate::agent::context_budget::{self, ContextBudget, ContextEscalation};
+use crate::agent::plan_execute::{self, PlanExecutor};
+use crate::agent::tool_retrieval::{self, scope_tool_retrieval, ToolRetriever};
 use crate::approval::{ApprovalManager, ApprovalRequest, ApprovalResponse};
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::multimodal;
 use crate::observability::{self, runtime_trace, Observer, ObserverEvent};
 use crate::providers::{
-    self, ChatMessage, ChatRequest, Provider, ProviderCapabilityError, ToolCall,
+    self, ChatMessage, ChatRequest, Provider, ProviderCapabilityError, StreamEvent, ThinkingBlock,
+    ToolCall,
 };
 use crate::runtime;
 use crate::security::SecurityPolicy;
+use crate::tools::artifact_read::ToolOutputSpill;
+use crate::tools::ask_user::{scope_ask_user_context, AskUserContext};
 use crate::tools::{self, Tool};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
@@ -32,6 +40,7 @@ pub(crate) mod detection;
 mod execution;
 mod history;
 mod parsing;
+mod streaming;
 
 use context::{build_context, build_hardware_context};
 use detection::{DetectionVerdict, LoopDetectionConfig, LoopDetector};
@@ -41,7 +50,7 @@ use execution::{
 };
 #[cfg(test)]
 use history::{apply_compaction_summary, build_compaction_transcript};
-use history::{auto_compact_history, trim_history};
+use history::{auto_compact_history, force_compact_history, trim_history, ContextRecoveryStep};
 #[allow(unused_imports)]
 use parsing::{
     default_param_for_tool, detect_tool_call_parse_issue, extract_json_values, map_tool_name_alias,
@@ -84,6 +93,19 @@ const SLASH_COMMANDS: &[(&[&str], &str, &str)] = &[
         "/clear /new",
         "Clear conversation history",
     ),
+    (&["/undo"], "/undo", "Remove the last exchange"),
+    (
+        &["/retry"],
+        "/retry [model]",
+        "Re-send the last message, optionally with another model",
+    ),
+    (
+        &["/fork"],
+        "/fork [n]",
+        "Branch after exchange n (default: now)",
+    ),
+    (&["/branches"], "/branches", "List conversation branches"),
+    (&["/switch"], "/switch <id>", "Switch to another branch"),
     (&["/quit", "/exit"], "/quit /exit", "Exit interactive mode"),
 ];
 
@@ -289,6 +311,48 @@ pub(crate) struct NonCliApprovalContext {
 tokio::task_local! {
     static TOOL_LOOP_NON_CLI_APPROVAL_CONTEXT: Option<NonCliApprovalContext>;
     static LOOP_DETECTION_CONFIG: LoopDetectionConfig;
+    static TOOL_LOOP_CONTEXT_BUDGET: ContextBudget;
+    static TOOL_LOOP_CONTEXT_ESCALATIONS: Vec<ContextEscalation>;
+    static TOOL_LOOP_STREAM_EVENTS: tokio::sync::mpsc::Sender<StreamEvent>;
+    static TOOL_LOOP_OUTPUT_SPILL: Option<ToolOutputSpill>;
+}
+
+/// Run `future` with `spill` deciding which tool outputs inside
+/// [`run_tool_call_loop`] are moved to workspace artifacts.
+pub(crate) async fn scope_tool_output_spill<F: std::future::Future>(
+    spill: Option<ToolOutputSpill>,
+    future: F,
+) -> F::Output {
+    TOOL_LOOP_OUTPUT_SPILL.scope(spill, future).await
+}
+
+/// Run `future` with `budget` governing how requests inside
+/// [`run_tool_call_loop`] are fitted into the model's context window.
+pub(crate) async fn scope_context_budget<F: std::future::Future>(
+    budget: ContextBudget,
+    future: F,
+) -> F::Output {
+    TOOL_LOOP_CONTEXT_BUDGET.scope(budget, future).await
+}
+
+/// Run `future` with `escalations` as the larger-context models that
+/// [`run_tool_call_loop`] may switch to when a request overflows the window.
+pub(crate) async fn scope_context_escalations<F: std::future::Future>(
+    escalations: Vec<ContextEscalation>,
+    future: F,
+) -> F::Output {
+    TOOL_LOOP_CONTEXT_ESCALATIONS
+        .scope(escalations, future)
+        .await
+}
+
+/// Run `future` with `events` receiving the raw [`StreamEvent`]s of every
+/// streamed model turn inside [`run_tool_call_loop`].
+pub(crate) async fn scope_tool_stream_events<F: std::future::Future>(
+    events: tokio::sync::mpsc::Sender<StreamEvent>,
+    future: F,
+) -> F::Output {
+    TOOL_LOOP_STREAM_EVENTS.scope(events, future).await
 }
 
 /// Extract a short hint from tool call arguments for progress display.
@@ -460,6 +524,7 @@ fn build_native_assistant_history(
     text: &str,
     tool_calls: &[ToolCall],
     reasoning_content: Option<&str>,
+    thinking: &[ThinkingBlock],
 ) -> String {
     let calls_json: Vec<serde_json::Value> = tool_calls
         .iter()
@@ -490,6 +555,14 @@ fn build_native_assistant_history(
         );
     }
 
+    // Signed thinking blocks must precede the tool calls when replayed
+    // (Anthropic/Bedrock extended thinking).
+    if !thinking.is_empty() {
+        obj.as_object_mut()
+            .unwrap()
+            .insert("thinking".to_string(), serde_json::json!(thinking));
+    }
+
     obj.to_string()
 }
 
@@ -696,7 +769,7 @@ pub(crate) async fn run_tool_call_loop_with_non_cli_approval_context(
             non_cli_approval_context,
             TOOL_LOOP_REPLY_TARGET.scope(
                 reply_target,
-                run_tool_call_loop(
+                Box::pin(run_tool_call_loop(
                     provider,
                     history,
                     tools_registry,
@@ -713,7 +786,7 @@ pub(crate) async fn run_tool_call_loop_with_non_cli_approval_context(
                     on_delta,
                     hooks,
                     excluded_tools,
-                ),
+                )),
             ),
         )
         .await
@@ -772,12 +845,41 @@ pub(crate) async fn run_tool_call_loop(
         max_tool_iterations
     };
 
-    let tool_specs: Vec<crate::tools::ToolSpec> = tools_registry
+    let registry_specs: Vec<crate::tools::ToolSpec> = tools_registry
         .iter()
         .filter(|tool| !excluded_tools.iter().any(|ex| ex == tool.name()))
         .map(|tool| tool.spec())
         .collect();
-    let use_native_tools = provider.supports_native_tools() && !tool_specs.is_empty();
+    let use_native_tools = provider.supports_native_tools() && !registry_specs.is_empty();
+    // With `[agent.tool_retrieval]`, offer only the tools selected for this
+    // turn; `tool_search` widens the selection between iterations.
+    let tool_selection = tool_retrieval::begin_selection(&registry_specs, history).await;
+    // Callers leave the XML tool block out of the system prompt when retrieval
+    // is on; it is added per request for the tools currently offered.
+    let describe_tools_per_request =
+        !use_native_tools && tool_retrieval::current_selection().is_some();
+    let mut tool_specs = tool_selection.as_ref().map_or_else(
+        || registry_specs.clone(),
+        |selection| selection.active_specs(),
+    );
+    let stream_events = TOOL_LOOP_STREAM_EVENTS.try_with(Clone::clone).ok();
+    let mut context_budget = TOOL_LOOP_CONTEXT_BUDGET
+        .try_with(|budget| *budget)
+        .unwrap_or_else(|_| {
+            ContextBudget::for_model(&crate::config::AgentConfig::default(), model)
+        });
+    let context_escalations = TOOL_LOOP_CONTEXT_ESCALATIONS
+        .try_with(Clone::clone)
+        .unwrap_or_default();
+    // Context-window-exceeded recovery may switch models and tighten the budget.
+    let mut model = model;
+    let mut overflow_input_limit: Option<usize> = None;
+    let mut context_recovery_steps = ContextRecoveryStep::ORDER.into_iter();
+    let mut tool_specs_tokens = if use_native_tools {
+        context_budget::estimate_tool_specs_tokens(&tool_specs)
+    } else {
+        0
+    };
     let turn_id = Uuid::new_v4().to_string();
     let mut seen_tool_signatures: HashSet<(String, String)> = HashSet::new();
     let mut missing_tool_call_retry_used = false;
@@ -789,6 +891,34 @@ pub(crate) async fn run_tool_call_loop(
     let mut loop_detection_prompt: Option<String> = None;
     let bypass_non_cli_approval_for_turn =
         approval.is_some_and(|mgr| channel_name != "cli" && mgr.consume_non_cli_allow_all_once());
+    let capture_replay = runtime_trace::replay_capture_enabled();
+    if capture_replay {
+        let messages: Vec<ChatMessage> = history
+            .iter()
+            .map(|message| ChatMessage {
+                role: message.role.clone(),
+                content: scrub_credentials(&message.content),
+            })
+            .collect();
+        let tool_names: Vec<&str> = tool_specs.iter().map(|spec| spec.name.as_str()).collect();
+        runtime_trace::record_event(
+            "turn_replay_input",
+            Some(channel_name),
+            Some(provider_name),
+            Some(model),
+            Some(&turn_id),
+            None,
+            None,
+            serde_json::json!({
+                "history": messages,
+                "tools": tool_names,
+                "excluded_tools": excluded_tools,
+                "temperature": temperature,
+                "max_iterations": max_iterations,
+                "config_hash": runtime_trace::current_config_fingerprint(),
+            }),
+        );
+    }
     if bypass_non_cli_approval_for_turn {
         runtime_trace::record_event(
             "approval_bypass_one_time_all_tools_consumed",
@@ -833,6 +963,47 @@ pub(crate) async fn run_tool_call_loop(
         if let Some(prompt) = loop_detection_prompt.take() {
             request_messages.push(ChatMessage::user(prompt));
         }
+        if describe_tools_per_request {
+            if let Some(system) = request_messages
+                .iter_mut()
+                .find(|message| message.role == "system")
+            {
+                system
+                    .content
+                    .push_str(&build_tool_instructions_from_specs(&tool_specs));
+            }
+        }
+
+        // Fit system prompt, memory context, history and tool specs into the
+        // model's context window; long tool outputs are shortened first.
+        let input_limit = context_budget
+            .input_limit()
+            .saturating_sub(tool_specs_tokens);
+        let budget_outcome = context_budget::fit_messages(
+            &mut request_messages,
+            overflow_input_limit.map_or(input_limit, |limit| limit.min(input_limit)),
+        );
+        if budget_outcome.changed() {
+            runtime_trace::record_event(
+                "context_budget_applied",
+                Some(channel_name),
+                Some(provider_name),
+                Some(model),
+                Some(&turn_id),
+                None,
+                None,
+                serde_json::json!({
+                    "iteration": iteration + 1,
+                    "context_window": context_budget.context_window,
+                    "reserved_output_tokens": context_budget.reserved_output,
+                    "tool_specs_tokens": tool_specs_tokens,
+                    "estimated_tokens": budget_outcome.estimated_tokens,
+                    "fitted_tokens": budget_outcome.fitted_tokens,
+                    "truncated_messages": budget_outcome.truncated_messages,
+                    "dropped_messages": budget_outcome.dropped_messages,
+                }),
+            );
+        }
 
         // ── Progress: LLM thinking ────────────────────────────
         if let Some(ref tx) = on_delta {
@@ -878,22 +1049,47 @@ pub(crate) async fn run_tool_call_loop(
             None
         };
 
-        let chat_future = provider.chat(
-            ChatRequest {
-                messages: &request_messages,
-                tools: request_tools,
-            },
-            model,
-            temperature,
-        );
+        let chat_request = ChatRequest {
+            messages: &request_messages,
+            tools: request_tools,
+            response_format: None,
+        };
 
-        let chat_result = if let Some(token) = cancellation_token.as_ref() {
-            tokio::select! {
-                () = token.cancelled() => return Err(ToolLoopCancelled.into()),
-                result = chat_future => result,
-            }
+        // Stream native tool-calling turns when someone is watching, so text
+        // and tool-call fragments surface while the model is still generating.
+        let streamed = if use_native_tools
+            && provider.supports_tool_call_streaming()
+            && (on_delta.is_some() || stream_events.is_some())
+        {
+            streaming::stream_chat_response(
+                provider,
+                chat_request,
+                model,
+                temperature,
+                on_delta.as_ref(),
+                stream_events.as_ref(),
+                cancellation_token.as_ref(),
+            )
+            .await
         } else {
-            chat_future.await
+            Ok(None)
+        };
+
+        let chat_result = match streamed {
+            Ok(Some(response)) => Ok(response),
+            Err(e) if e.is::<ToolLoopCancelled>() => return Err(e),
+            Err(e) => Err(e),
+            Ok(None) => {
+                let chat_future = provider.chat(chat_request, model, temperature);
+                if let Some(token) = cancellation_token.as_ref() {
+                    tokio::select! {
+                        () = token.cancelled() => return Err(ToolLoopCancelled.into()),
+                        result = chat_future => result,
+                    }
+                } else {
+                    chat_future.await
+                }
+            }
         };
 
         let (
@@ -905,11 +1101,23 @@ pub(crate) async fn run_tool_call_loop(
             parse_issue_detected,
         ) = match chat_result {
             Ok(resp) => {
-                let (resp_input_tokens, resp_output_tokens) = resp
+                let (
+                    resp_input_tokens,
+                    resp_output_tokens,
+                    resp_cache_read_tokens,
+                    resp_cache_write_tokens,
+                ) = resp
                     .usage
                     .as_ref()
-                    .map(|u| (u.input_tokens, u.output_tokens))
-                    .unwrap_or((None, None));
+                    .map(|u| {
+                        (
+                            u.input_tokens,
+                            u.output_tokens,
+                            u.cache_read_tokens,
+                            u.cache_write_tokens,
+                        )
+                    })
+                    .unwrap_or((None, None, None, None));
 
                 observer.record_event(&ObserverEvent::LlmResponse {
                     provider: provider_name.to_string(),
@@ -919,8 +1127,41 @@ pub(crate) async fn run_tool_call_loop(
                     error_message: None,
                     input_tokens: resp_input_tokens,
                     output_tokens: resp_output_tokens,
+                    cache_read_tokens: resp_cache_read_tokens,
+                    cache_write_tokens: resp_cache_write_tokens,
                 });
 
+                let reasoning_text = ThinkingBlock::joined_text(&resp.thinking)
+                    .or_else(|| resp.reasoning_content.clone())
+                    .filter(|text| !text.trim().is_empty());
+                if let Some(reasoning_text) = reasoning_text {
+                    observer.record_event(&ObserverEvent::LlmReasoning {
+                        provider: provider_name.to_string(),
+                        model: model.to_string(),
+                        blocks: resp.thinking.len(),
+                        text: reasoning_text.clone(),
+                    });
+                    let mut payload = serde_json::json!({
+                        "iteration": iteration + 1,
+                        "blocks": resp.thinking.len(),
+                        "chars": reasoning_text.chars().count(),
+                    });
+                    if runtime_trace::reasoning_visible() {
+                        payload["text"] =
+                            serde_json::Value::String(scrub_credentials(&reasoning_text));
+                    }
+                    runtime_trace::record_event(
+                        "llm_reasoning",
+                        Some(channel_name),
+                        Some(provider_name),
+                        Some(model),
+                        Some(&turn_id),
+                        Some(true),
+                        None,
+                        payload,
+                    );
+                }
+
                 let response_text = resp.text_or_empty().to_string();
                 // First try native structured tool calls (OpenAI-format).
                 // Fall back to text-based parsing (XML tags, markdown blocks,
@@ -957,6 +1198,30 @@ pub(crate) async fn run_tool_call_loop(
                     );
                 }
 
+                let mut payload = serde_json::json!({
+                    "iteration": iteration + 1,
+                    "duration_ms": llm_started_at.elapsed().as_millis(),
+                    "input_tokens": resp_input_tokens,
+                    "output_tokens": resp_output_tokens,
+                    "cache_read_tokens": resp_cache_read_tokens,
+                    "cache_write_tokens": resp_cache_write_tokens,
+                    "raw_response": scrub_credentials(&response_text),
+                    "native_tool_calls": resp.tool_calls.len(),
+                    "parsed_tool_calls": calls.len(),
+                });
+                if capture_replay {
+                    payload["tool_calls"] = resp
+                        .tool_calls
+                        .iter()
+                        .map(|call| {
+                            serde_json::json!({
+                                "id": call.id,
+                                "name": call.name,
+                                "arguments": scrub_credentials(&call.arguments),
+                            })
+                        })
+                        .collect();
+                }
                 runtime_trace::record_event(
                     "llm_response",
                     Some(channel_name),
@@ -965,15 +1230,7 @@ pub(crate) async fn run_tool_call_loop(
                     Some(&turn_id),
                     Some(true),
                     None,
-                    serde_json::json!({
-                        "iteration": iteration + 1,
-                        "duration_ms": llm_started_at.elapsed().as_millis(),
-                        "input_tokens": resp_input_tokens,
-                        "output_tokens": resp_output_tokens,
-                        "raw_response": scrub_credentials(&response_text),
-                        "native_tool_calls": resp.tool_calls.len(),
-                        "parsed_tool_calls": calls.len(),
-                    }),
+                    payload,
                 );
 
                 // Preserve native tool call IDs in assistant history so role=tool
@@ -995,6 +1252,7 @@ pub(crate) async fn run_tool_call_loop(
                         &response_text,
                         &resp.tool_calls,
                         reasoning_content.as_deref(),
+                        &resp.thinking,
                     )
                 };
 
@@ -1018,6 +1276,8 @@ pub(crate) async fn run_tool_call_loop(
                     error_message: Some(safe_error.clone()),
                     input_tokens: None,
                     output_tokens: None,
+                    cache_read_tokens: None,
+                    cache_write_tokens: None,
                 });
                 runtime_trace::record_event(
                     "llm_response",
@@ -1032,6 +1292,67 @@ pub(crate) async fn run_tool_call_loop(
                         "duration_ms": llm_started_at.elapsed().as_millis(),
                     }),
                 );
+
+                if providers::reliable::is_context_window_exceeded(&e) {
+                    let mut recovered = None;
+                    for step in context_recovery_steps.by_ref() {
+                        let applied = match step {
+                            ContextRecoveryStep::CompactHistory => {
+                                force_compact_history(history, provider, model).await
+                            }
+                            ContextRecoveryStep::TruncateToolResults => {
+                                // The provider counts more tokens than we estimate:
+                                // refit into two thirds of what was just rejected.
+                                overflow_input_limit = Some(budget_outcome.fitted_tokens / 3 * 2);
+                                true
+                            }
+                            ContextRecoveryStep::EscalateModel => {
+                                match context_escalations.iter().find(|candidate| {
+                                    candidate.budget.context_window > context_budget.context_window
+                                }) {
+                                    Some(candidate) => {
+                                        model = candidate.model.as_str();
+                                        context_budget = candidate.budget;
+                                        overflow_input_limit = None;
+                                        true
+                                    }
+                                    None => false,
+                                }
+                            }
+                        };
+                        if applied {
+                            recovered = Some(step);
+                            break;
+                        }
+                    }
+
+                    if let Some(step) = recovered {
+                        runtime_trace::record_event(
+                            "context_overflow_recovery",
+                            Some(channel_name),
+                            Some(provider_name),
+                            Some(model),
+                            Some(&turn_id),
+                            Some(true),
+                            Some(&safe_error),
+                            serde_json::json!({
+                                "iteration": iteration + 1,
+                                "step": step.as_str(),
+                                "context_window": context_budget.context_window,
+                                "history_messages": history.len(),
+                            }),
+                        );
+                        if let Some(ref tx) = on_delta {
+                            let _ = tx
+                                .send(format!(
+                                    "{DRAFT_PROGRESS_SENTINEL}\u{267b}\u{fe0f} Context window exceeded, retrying ({})\n",
+                                    step.as_str().replace('_', " ")
+                                ))
+                                .await;
+                        }
+                        continue;
+                    }
+                }
                 return Err(e);
             }
         };
@@ -1146,6 +1467,18 @@ pub(crate) async fn run_tool_call_loop(
             let _ = std::io::stdout().flush();
         }
 
+        checkpoint::record_iteration(
+            iteration,
+            history,
+            &tool_calls
+                .iter()
+                .map(|call| PendingToolCall {
+                    name: call.name.clone(),
+                    arguments: call.arguments.clone(),
+                })
+                .collect::<Vec<_>>(),
+        );
+
         // Execute tool calls and build results. `individual_results` tracks per-call output so
         // native-mode history can emit one role=tool message per tool call with the correct ID.
         //
@@ -1241,6 +1574,22 @@ pub(crate) async fn run_tool_call_loop(
                 continue;
             }
 
+            // ── Resume guard: lost side-effecting call ───────
+            if let Some(held) = checkpoint::take_resume_guard(&tool_name, &tool_args) {
+                tracing::info!(tool = %tool_name, "held back repeat of call lost in restart");
+                ordered_results[idx] = Some((
+                    tool_name.clone(),
+                    call.tool_call_id.clone(),
+                    ToolExecutionOutcome {
+                        output: held.clone(),
+                        success: false,
+                        error_reason: Some(held),
+                        duration: Duration::ZERO,
+                    },
+                ));
+                continue;
+            }
+
             // ── Approval hook ────────────────────────────────
             if let Some(mgr) = approval {
                 if bypass_non_cli_approval_for_turn {
@@ -1415,6 +1764,16 @@ pub(crate) async fn run_tool_call_loop(
             .zip(executable_calls.iter())
             .zip(executed_outcomes.into_iter())
         {
+            let mut payload = serde_json::json!({
+                "iteration": iteration + 1,
+                "tool": call.name.clone(),
+                "duration_ms": outcome.duration.as_millis(),
+                "output": scrub_credentials(&outcome.output),
+            });
+            if capture_replay {
+                payload["arguments"] =
+                    serde_json::Value::String(scrub_credentials(&call.arguments.to_string()));
+            }
             runtime_trace::record_event(
                 "tool_call_result",
                 Some(channel_name),
@@ -1423,12 +1782,7 @@ pub(crate) async fn run_tool_call_loop(
                 Some(&turn_id),
                 Some(outcome.success),
                 outcome.error_reason.as_deref(),
-                serde_json::json!({
-                    "iteration": iteration + 1,
-                    "tool": call.name.clone(),
-                    "duration_ms": outcome.duration.as_millis(),
-                    "output": scrub_credentials(&outcome.output),
-                }),
+                payload,
             );
 
             // ── Hook: after_tool_call (void) ─────────────────
@@ -1511,6 +1865,13 @@ pub(crate) async fn run_tool_call_loop(
                 history.push(ChatMessage::tool(tool_msg.to_string()));
             }
         }
+        checkpoint::record_iteration(iteration + 1, history, &[]);
+        if let Some(selection) = tool_selection.as_ref().filter(|s| s.take_changed()) {
+            tool_specs = selection.active_specs();
+            if use_native_tools {
+                tool_specs_tokens = context_budget::estimate_tool_specs_tokens(&tool_specs);
+            }
+        }
 
         // ── Loop detection: check verdict ────────────────────────
         match loop_detector.check() {
@@ -1755,7 +2116,10 @@ pub async fn run(
         crate::peripherals::create_peripheral_tools(&config.peripherals).await?;
     if !peripheral_tools.is_empty() {
         tracing::info!(count = peripheral_tools.len(), "Peripheral tools added");
-        tools_registry.extend(peripheral_tools);
+        tools_registry.extend(tools::policy::apply_tool_policies(
+            peripheral_tools,
+            &config.tools,
+        ));
     }
 
     // ── Resolve provider ─────────────────────────────────────────
@@ -1780,9 +2144,12 @@ pub async fn run(
         custom_provider_api_mode: config.provider_api.map(|mode| mode.as_compatible_mode()),
         max_tokens_override: None,
         model_support_vision: config.model_support_vision,
+        thinking_budget_tokens: config.effective_thinking_budget_tokens(),
+        ollama: config.ollama.clone(),
+        observer: Some(Arc::clone(&observer)),
     };
 
-    let provider: Box<dyn Provider> = providers::create_routed_provider_with_options(
+    let provider: Box<dyn Provider> = providers::create_routed_provider_with_policy(
         provider_name,
         config.api_key.as_deref(),
         config.api_url.as_deref(),
@@ -1790,6 +2157,7 @@ pub async fn run(
         &config.model_routes,
         model_name,
         &provider_runtime_options,
+        providers::adaptive_routing::AdaptiveRoutingPolicy::from_config(&config),
     )?;
 
     observer.record_event(&ObserverEvent::AgentStart {
@@ -1805,7 +2173,14 @@ pub async fn run(
         .filter(|d| !d.trim().is_empty())
         .map(|dir| crate::rag::HardwareRag::load(&config.workspace_dir, dir.trim()))
         .and_then(Result::ok)
-        .filter(|r: &crate::rag::HardwareRag| !r.is_empty());
+        .filter(|r: &crate::rag::HardwareRag| !r.is_empty())
+        .map(|r| {
+            if config.memory.embedding_provider.trim() == "local" {
+                r.with_local_embeddings(config.memory.embedding_dimensions)
+            } else {
+                r
+            }
+        });
     if let Some(ref rag) = hardware_rag {
         tracing::info!(chunks = rag.len(), "Hardware RAG loaded");
     }
@@ -1948,8 +2323,10 @@ pub async fn run(
         config.skills.prompt_injection_mode,
     );
 
-    // Append structured tool-use instructions with schemas (only for non-native providers)
-    if !native_tools {
+    // Append structured tool-use instructions with schemas (only for non-native
+    // providers; with tool retrieval the loop adds them per request)
+    let tool_retriever = ToolRetriever::from_config(&config);
+    if !native_tools && tool_retriever.is_none() {
         system_prompt.push_str(&build_tool_instructions(&tools_registry));
     }
     system_prompt.push_str(&build_shell_policy_instructions(&config.autonomy));
@@ -1966,6 +2343,8 @@ pub async fn run(
     let start = Instant::now();
 
     let mut final_output = String::new();
+    let output_spill = ToolOutputSpill::from_config(&config.agent, &config.workspace_dir);
+    let plan_execute = PlanExecutor::from_config(&config.agent.plan_execute, &config.model_routes);
 
     if let Some(msg) = message {
         // Auto-save user message to memory (skip short/trivial messages)
@@ -2002,26 +2381,51 @@ pub async fn run(
             ping_pong_cycles: config.agent.loop_detection_ping_pong_cycles,
             failure_streak_threshold: config.agent.loop_detection_failure_streak,
         };
+        let context_budget = ContextBudget::for_provider_model(
+            &config.agent,
+            &config.ollama,
+            provider_name,
+            model_name,
+        );
         let response = LOOP_DETECTION_CONFIG
             .scope(
                 ld_cfg,
-                run_tool_call_loop(
-                    provider.as_ref(),
-                    &mut history,
-                    &tools_registry,
-                    observer.as_ref(),
-                    provider_name,
-                    model_name,
-                    temperature,
-                    false,
-                    approval_manager.as_ref(),
-                    channel_name,
-                    &config.multimodal,
-                    config.agent.max_tool_iterations,
-                    None,
-                    None,
-                    None,
-                    &[],
+                scope_context_escalations(
+                    context_budget::escalation_candidates(
+                        &config.agent,
+                        &config.ollama,
+                        &config.model_routes,
+                        provider_name,
+                        context_budget,
+                        true,
+                    ),
+                    scope_context_budget(
+                        context_budget,
+                        scope_tool_output_spill(
+                            output_spill.clone(),
+                            scope_tool_retrieval(
+                                tool_retriever.clone(),
+                                scope_ask_user_context(
+                                    Some(AskUserContext::Cli),
+                                    Box::pin(plan_execute::run_turn(
+                                        plan_execute.as_ref(),
+                                        provider.as_ref(),
+                                        &mut history,
+                                        &tools_registry,
+                                        observer.as_ref(),
+                                        provider_name,
+                                        model_name,
+                                        temperature,
+                                        false,
+                                        approval_manager.as_ref(),
+                                        channel_name,
+                                        &config.multimodal,
+                                        config.agent.max_tool_iterations,
+                                    )),
+                                ),
+                            ),
+                        ),
+                    ),
                 ),
             )
             .await?;
@@ -2035,6 +2439,7 @@ pub async fn run(
 
         // Persistent conversation history across turns
         let mut history = vec![ChatMessage::system(&system_prompt)];
+        let mut branches = branching::ConversationBranches::default();
         // Reusable readline editor for UTF-8 input support
         let mut rl = Editor::with_config(
             RlConfig::builder()
@@ -2055,7 +2460,7 @@ pub async fn run(
                 }
             };
 
-            let user_input = input.trim().to_string();
+            let mut user_input = input.trim().to_string();
             if user_input.is_empty() {
                 continue;
             }
@@ -2064,9 +2469,12 @@ pub async fn run(
                 "/quit" | "/exit" => break,
                 "/help" => {
                     println!("Available commands:");
-                    println!("  /help        Show this help message");
-                    println!("  /clear /new  Clear conversation history");
-                    println!("  /quit /exit  Exit interactive mode\n");
+                    println!("  /help           Show this help message");
+                    println!("  /clear /new     Clear conversation history");
+                    for (usage, description) in branching::HISTORY_COMMAND_HELP {
+                        println!("  {usage:<15} {description}");
+                    }
+                    println!("  /quit /exit     Exit interactive mode\n");
                     continue;
                 }
                 "/clear" | "/new" => {
@@ -2085,6 +2493,7 @@ pub async fn run(
                     rl.clear_history()?;
                     history.clear();
                     history.push(ChatMessage::system(&system_prompt));
+                    branches = branching::ConversationBranches::default();
                     // Clear conversation and daily memory
                     let mut cleared = 0;
                     for category in [MemoryCategory::Conversation, MemoryCategory::Daily] {
@@ -2105,8 +2514,37 @@ pub async fn run(
                 _ => {}
             }
 
+            // /undo, /retry, /fork, /branches and /switch edit the history in place;
+            // /retry then falls through to send the removed message again.
+            let mut retry_model: Option<String> = None;
+            let mut retrying = false;
+            if let Some(parsed) = branching::parse_history_command(&user_input) {
+                let command = match parsed {
+                    Ok(command) => command,
+                    Err(usage) => {
+                        println!("{usage}\n");
+                        continue;
+                    }
+                };
+                let (notice, retry_input) = branches.apply(&command, &mut history);
+                let Some(retry_input) = retry_input else {
+                    println!("{}\n", notice.trim_end());
+                    continue;
+                };
+                if let branching::HistoryCommand::Retry(model) = command {
+                    retry_model = model;
+                }
+                println!("↻ Retrying: {}\n", truncate_with_ellipsis(&retry_input, 80));
+                user_input = retry_input;
+                retrying = true;
+            }
+            let turn_model = retry_model.as_deref().unwrap_or(model_name);
+
             // Auto-save conversation turns (skip short/trivial messages)
-            if config.memory.auto_save && user_input.chars().count() >= AUTOSAVE_MIN_MESSAGE_CHARS {
+            if config.memory.auto_save
+                && !retrying
+                && user_input.chars().count() >= AUTOSAVE_MIN_MESSAGE_CHARS
+            {
                 let user_key = autosave_memory_key("user_msg");
                 let _ = mem
                     .store(&user_key, &user_input, MemoryCategory::Conversation, None)
@@ -2136,26 +2574,51 @@ pub async fn run(
                 ping_pong_cycles: config.agent.loop_detection_ping_pong_cycles,
                 failure_streak_threshold: config.agent.loop_detection_failure_streak,
             };
+            let context_budget = ContextBudget::for_provider_model(
+                &config.agent,
+                &config.ollama,
+                provider_name,
+                turn_model,
+            );
             let response = match LOOP_DETECTION_CONFIG
                 .scope(
                     ld_cfg,
-                    run_tool_call_loop(
-                        provider.as_ref(),
-                        &mut history,
-                        &tools_registry,
-                        observer.as_ref(),
-                        provider_name,
-                        model_name,
-                        temperature,
-                        false,
-                        approval_manager.as_ref(),
-                        channel_name,
-                        &config.multimodal,
-                        config.agent.max_tool_iterations,
-                        None,
-                        None,
-                        None,
-                        &[],
+                    scope_context_escalations(
+                        context_budget::escalation_candidates(
+                            &config.agent,
+                            &config.ollama,
+                            &config.model_routes,
+                            provider_name,
+                            context_budget,
+                            true,
+                        ),
+                        scope_context_budget(
+                            context_budget,
+                            scope_tool_output_spill(
+                                output_spill.clone(),
+                                scope_tool_retrieval(
+                                    tool_retriever.clone(),
+                                    scope_ask_user_context(
+                                        Some(AskUserContext::Cli),
+                                        Box::pin(plan_execute::run_turn(
+                                            plan_execute.as_ref(),
+                                            provider.as_ref(),
+                                            &mut history,
+                                            &tools_registry,
+                                            observer.as_ref(),
+                                            provider_name,
+                                            turn_model,
+                                            temperature,
+                                            false,
+                                            approval_manager.as_ref(),
+                                            channel_name,
+                                            &config.multimodal,
+                                            config.agent.max_tool_iterations,
+                                        )),
+                                    ),
+                                ),
+                            ),
+                        ),
                     ),
                 )
                 .await
@@ -2202,6 +2665,7 @@ pub async fn run(
                 provider.as_ref(),
                 model_name,
                 config.agent.max_history_messages,
+                context_budget.input_limit(),
             )
             .await
             {
@@ -2270,7 +2734,10 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
     );
     let peripheral_tools: Vec<Box<dyn Tool>> =
         crate::peripherals::create_peripheral_tools(&config.peripherals).await?;
-    tools_registry.extend(peripheral_tools);
+    tools_registry.extend(tools::policy::apply_tool_policies(
+        peripheral_tools,
+        &config.tools,
+    ));
 
     let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
     let model_name = config
@@ -2288,8 +2755,11 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         custom_provider_api_mode: config.provider_api.map(|mode| mode.as_compatible_mode()),
         max_tokens_override: None,
         model_support_vision: config.model_support_vision,
+        thinking_budget_tokens: config.effective_thinking_budget_tokens(),
+        ollama: config.ollama.clone(),
+        observer: Some(Arc::clone(&observer)),
     };
-    let provider: Box<dyn Provider> = providers::create_routed_provider_with_options(
+    let provider: Box<dyn Provider> = providers::create_routed_provider_with_policy(
         provider_name,
         config.api_key.as_deref(),
         config.api_url.as_deref(),
@@ -2297,6 +2767,7 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         &config.model_routes,
         &model_name,
         &provider_runtime_options,
+        providers::adaptive_routing::AdaptiveRoutingPolicy::from_config(&config),
     )?;
 
     let hardware_rag: Option<crate::rag::HardwareRag> = config
@@ -2306,7 +2777,14 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         .filter(|d| !d.trim().is_empty())
         .map(|dir| crate::rag::HardwareRag::load(&config.workspace_dir, dir.trim()))
         .and_then(Result::ok)
-        .filter(|r: &crate::rag::HardwareRag| !r.is_empty());
+        .filter(|r: &crate::rag::HardwareRag| !r.is_empty())
+        .map(|r| {
+            if config.memory.embedding_provider.trim() == "local" {
+                r.with_local_embeddings(config.memory.embedding_dimensions)
+            } else {
+                r
+            }
+        });
     let board_names: Vec<String> = config
         .peripherals
         .boards
@@ -2379,7 +2857,8 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         native_tools,
         config.skills.prompt_injection_mode,
     );
-    if !native_tools {
+    let tool_retriever = ToolRetriever::from_config(&config);
+    if !native_tools && tool_retriever.is_none() {
         system_prompt.push_str(&build_tool_instructions(&tools_registry));
     }
     system_prompt.push_str(&build_shell_policy_instructions(&config.autonomy));
@@ -2403,18 +2882,43 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         ChatMessage::user(&enriched),
     ];
 
-    agent_turn(
-        provider.as_ref(),
-        &mut history,
-        &tools_registry,
-        observer.as_ref(),
+    let context_budget = ContextBudget::for_provider_model(
+        &config.agent,
+        &config.ollama,
         provider_name,
         &model_name,
-        config.default_temperature,
-        true,
-        &config.multimodal,
-        config.agent.max_tool_iterations,
-    )
+    );
+    Box::pin(scope_context_escalations(
+        context_budget::escalation_candidates(
+            &config.agent,
+            &config.ollama,
+            &config.model_routes,
+            provider_name,
+            context_budget,
+            true,
+        ),
+        scope_context_budget(
+            context_budget,
+            scope_tool_output_spill(
+                ToolOutputSpill::from_config(&config.agent, &config.workspace_dir),
+                scope_tool_retrieval(
+                    tool_retriever.clone(),
+                    agent_turn(
+                        provider.as_ref(),
+                        &mut history,
+                        &tools_registry,
+                        observer.as_ref(),
+                        provider_name,
+                        &model_name,
+                        config.default_temperature,
+                        true,
+                        &config.multimodal,
+                        config.agent.max_tool_iterations,
+                    ),
+                ),
+            ),
+        ),
+    ))
     .await
 }
 
@@ -2579,6 +3083,7 @@ mod tests {
                 tool_calls: Vec::new(),
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             })
         }
     }
@@ -2597,6 +3102,7 @@ mod tests {
                     tool_calls: Vec::new(),
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 })
                 .collect();
             Self {
@@ -2672,7 +3178,8 @@ mod tests {
                 "type": "object",
                 "properties": {
                     "value": { "type": "string" }
-                }
+                },
+                "required": ["value"]
             })
         }
 
@@ -2731,8 +3238,7 @@ mod tests {
                 "type": "object",
                 "properties": {
                     "value": { "type": "string" }
-                },
-                "required": ["value"]
+                }
             })
         }
 
@@ -3064,11 +3570,130 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn run_tool_call_loop_rejects_arguments_that_violate_tool_schema() {
+        let provider = ScriptedProvider::from_text_responses(vec![
+            r#"<tool_call>
+{"name":"count_tool","arguments":{"value":42}}
+</tool_call>"#,
+            "done",
+        ]);
+        let invocations = Arc::new(AtomicUsize::new(0));
+        let tools_registry: Vec<Box<dyn Tool>> = vec![Box::new(CountingTool::new(
+            "count_tool",
+            Arc::clone(&invocations),
+        ))];
+        let mut history = vec![
+            ChatMessage::system("test-system"),
+            ChatMessage::user("run tool calls"),
+        ];
+        let observer = NoopObserver;
+
+        let result = run_tool_call_loop(
+            &provider,
+            &mut history,
+            &tools_registry,
+            &observer,
+            "mock-provider",
+            "mock-model",
+            0.0,
+            true,
+            None,
+            "cli",
+            &crate::config::MultimodalConfig::default(),
+            4,
+            None,
+            None,
+            None,
+            &[],
+        )
+        .await
+        .expect("loop should complete");
+
+        assert_eq!(result, "done");
+        assert_eq!(invocations.load(Ordering::SeqCst), 0);
+        let tool_results = history
+            .iter()
+            .find(|msg| msg.content.starts_with("[Tool results]"))
+            .expect("tool results message should be present");
+        assert!(tool_results
+            .content
+            .contains("Invalid arguments for tool `count_tool`"));
+        assert!(tool_results
+            .content
+            .contains("`$.value`: expected string, got integer"));
+    }
+
+    #[tokio::test]
+    async fn run_tool_call_loop_spills_large_tool_output_to_artifact() {
+        let big_value = "x".repeat(400);
+        let tool_call = format!(
+            "<tool_call>\n{{\"name\":\"count_tool\",\"arguments\":{{\"value\":\"{big_value}\"}}}}\n</tool_call>"
+        );
+        let provider = ScriptedProvider::from_text_responses(vec![tool_call.as_str(), "done"]);
+        let invocations = Arc::new(AtomicUsize::new(0));
+        let tools_registry: Vec<Box<dyn Tool>> = vec![Box::new(CountingTool::new(
+            "count_tool",
+            Arc::clone(&invocations),
+        ))];
+        let workspace = tempfile::tempdir().unwrap();
+        let spill = ToolOutputSpill::from_config(
+            &crate::config::AgentConfig {
+                tool_output_spill_chars: 100,
+                tool_output_preview_chars: 20,
+                ..crate::config::AgentConfig::default()
+            },
+            workspace.path(),
+        );
+        let mut history = vec![
+            ChatMessage::system("test-system"),
+            ChatMessage::user("run tool calls"),
+        ];
+        let observer = NoopObserver;
+
+        let result = scope_tool_output_spill(
+            spill,
+            run_tool_call_loop(
+                &provider,
+                &mut history,
+                &tools_registry,
+                &observer,
+                "mock-provider",
+                "mock-model",
+                0.0,
+                true,
+                None,
+                "cli",
+                &crate::config::MultimodalConfig::default(),
+                4,
+                None,
+                None,
+                None,
+                &[],
+            ),
+        )
+        .await
+        .expect("loop should complete");
+
+        assert_eq!(result, "done");
+        let tool_results = history
+            .iter()
+            .find(|msg| msg.content.starts_with("[Tool results]"))
+            .expect("tool results message should be present");
+        assert!(tool_results.content.contains("stored as artifact:"));
+        assert!(!tool_results.content.contains(&big_value));
+        let stored: Vec<_> =
+            std::fs::read_dir(crate::tools::artifact_read::artifacts_dir(workspace.path()))
+                .unwrap()
+                .collect();
+        assert_eq!(stored.len(), 1);
+    }
+
     #[tokio::test]
     async fn run_tool_call_loop_denies_supervised_tools_on_non_cli_channels() {
         let provider = ScriptedProvider::from_text_responses(vec![
             r#"<tool_call>
-{"name":"shell","arguments":{"command":"echo hi"}}
+{"name":"shell","arguments":{"value":"echo hi"}}
 </tool_call>"#,
             "done",
         ]);
@@ -3123,7 +3748,7 @@ mod tests {
     async fn run_tool_call_loop_waits_for_non_cli_approval_resolution() {
         let provider = ScriptedProvider::from_text_responses(vec![
             r#"<tool_call>
-{"name":"shell","arguments":{"command":"echo hi"}}
+{"name":"shell","arguments":{"value":"echo hi"}}
 </tool_call>"#,
             "done",
         ]);
@@ -3205,7 +3830,7 @@ mod tests {
     async fn run_tool_call_loop_consumes_one_time_non_cli_allow_all_token() {
         let provider = ScriptedProvider::from_text_responses(vec![
             r#"<tool_call>
-{"name":"shell","arguments":{"command":"echo hi"}}
+{"name":"shell","arguments":{"value":"echo hi"}}
 </tool_call>"#,
             "done",
         ]);
@@ -3263,7 +3888,7 @@ mod tests {
     async fn run_tool_call_loop_blocks_tools_excluded_for_channel() {
         let provider = ScriptedProvider::from_text_responses(vec![
             r#"<tool_call>
-{"name":"shell","arguments":{"command":"echo hi"}}
+{"name":"shell","arguments":{"value":"echo hi"}}
 </tool_call>"#,
             "done",
         ]);
@@ -3441,6 +4066,294 @@ mod tests {
         );
     }
 
+    struct StreamingProvider {
+        turns: Arc<Mutex<VecDeque<Vec<StreamEvent>>>>,
+    }
+
+    #[async_trait]
+    impl Provider for StreamingProvider {
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                native_tool_calling: true,
+                ..ProviderCapabilities::default()
+            }
+        }
+
+        fn supports_tool_call_streaming(&self) -> bool {
+            true
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            anyhow::bail!("chat_with_system should not be used in streaming provider tests");
+        }
+
+        async fn chat(
+            &self,
+            _request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            anyhow::bail!("chat should not be used when tool-call streaming is available");
+        }
+
+        fn stream_chat(
+            &self,
+            _request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+            _options: crate::providers::traits::StreamOptions,
+        ) -> futures_util::stream::BoxStream<
+            'static,
+            crate::providers::traits::StreamResult<StreamEvent>,
+        > {
+            use futures_util::StreamExt;
+            let events = self
+                .turns
+                .lock()
+                .expect("turns lock should be valid")
+                .pop_front()
+                .unwrap_or_default();
+            futures_util::stream::iter(events.into_iter().map(Ok)).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn run_tool_call_loop_streams_tool_call_deltas_to_draft_and_event_sink() {
+        use crate::providers::traits::ToolCallDelta;
+
+        let provider = StreamingProvider {
+            turns: Arc::new(Mutex::new(VecDeque::from([
+                vec![
+                    StreamEvent::ToolCallDelta(ToolCallDelta {
+                        index: 0,
+                        id: Some("call_1".into()),
+                        name: Some("count_tool".into()),
+                        arguments: "{\"value\":".into(),
+                    }),
+                    StreamEvent::ToolCallDelta(ToolCallDelta {
+                        index: 0,
+                        arguments: "\"A\"}".into(),
+                        ..ToolCallDelta::default()
+                    }),
+                    StreamEvent::Done,
+                ],
+                vec![StreamEvent::TextDelta("done".into()), StreamEvent::Done],
+            ]))),
+        };
+
+        let invocations = Arc::new(AtomicUsize::new(0));
+        let tools_registry: Vec<Box<dyn Tool>> = vec![Box::new(CountingTool::new(
+            "count_tool",
+            Arc::clone(&invocations),
+        ))];
+        let mut history = vec![
+            ChatMessage::system("test-system"),
+            ChatMessage::user("run tool calls"),
+        ];
+        let observer = NoopObserver;
+        let (delta_tx, mut delta_rx) = tokio::sync::mpsc::channel::<String>(64);
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::channel::<StreamEvent>(64);
+
+        let result = scope_tool_stream_events(
+            event_tx,
+            run_tool_call_loop(
+                &provider,
+                &mut history,
+                &tools_registry,
+                &observer,
+                "mock-provider",
+                "mock-model",
+                0.0,
+                true,
+                None,
+                "cli",
+                &crate::config::MultimodalConfig::default(),
+                4,
+                None,
+                Some(delta_tx),
+                None,
+                &[],
+            ),
+        )
+        .await
+        .expect("streamed tool loop should complete");
+
+        assert_eq!(result, "done");
+        assert_eq!(invocations.load(Ordering::SeqCst), 1);
+        assert!(history
+            .iter()
+            .any(|msg| msg.role == "tool" && msg.content.contains("counted:A")));
+
+        let mut deltas = Vec::new();
+        while let Ok(delta) = delta_rx.try_recv() {
+            deltas.push(delta);
+        }
+        assert!(deltas
+            .iter()
+            .any(|d| d.starts_with(DRAFT_PROGRESS_SENTINEL) && d.contains("Calling count_tool")));
+
+        let mut arguments = String::new();
+        while let Ok(event) = event_rx.try_recv() {
+            if let StreamEvent::ToolCallDelta(delta) = event {
+                arguments.push_str(&delta.arguments);
+            }
+        }
+        assert_eq!(arguments, "{\"value\":\"A\"}");
+    }
+
+    /// Rejects requests larger than `max_chars` (or sent to any model other
+    /// than `accepted_model`) with a context-window-exceeded error.
+    struct OverflowingProvider {
+        max_chars: usize,
+        accepted_model: Option<&'static str>,
+        models_seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Provider for OverflowingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("- user asked about the logs".to_string())
+        }
+
+        async fn chat(
+            &self,
+            request: ChatRequest<'_>,
+            model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            self.models_seen.lock().unwrap().push(model.to_string());
+            let chars: usize = request.messages.iter().map(|m| m.content.len()).sum();
+            if chars > self.max_chars || self.accepted_model.is_some_and(|m| m != model) {
+                anyhow::bail!("This model's maximum context length is 8192 tokens");
+            }
+            Ok(ChatResponse {
+                text: Some("answer".to_string()),
+                tool_calls: Vec::new(),
+                usage: None,
+                reasoning_content: None,
+                thinking: Vec::new(),
+            })
+        }
+    }
+
+    fn long_history(turns: usize) -> Vec<ChatMessage> {
+        let mut history = vec![ChatMessage::system("test-system")];
+        for turn in 0..turns {
+            history.push(ChatMessage::user(format!(
+                "question {turn} {}",
+                "x".repeat(500)
+            )));
+            history.push(ChatMessage::assistant(format!(
+                "reply {turn} {}",
+                "y".repeat(500)
+            )));
+        }
+        history.push(ChatMessage::user("and now?"));
+        history
+    }
+
+    async fn run_overflow_loop(
+        provider: &OverflowingProvider,
+        history: &mut Vec<ChatMessage>,
+    ) -> Result<String> {
+        run_tool_call_loop(
+            provider,
+            history,
+            &[],
+            &NoopObserver,
+            "mock-provider",
+            "small-model",
+            0.0,
+            true,
+            None,
+            "cli",
+            &crate::config::MultimodalConfig::default(),
+            5,
+            None,
+            None,
+            None,
+            &[],
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn run_tool_call_loop_compacts_history_after_context_overflow() {
+        let provider = OverflowingProvider {
+            max_chars: 30_000,
+            accepted_model: None,
+            models_seen: Arc::new(Mutex::new(Vec::new())),
+        };
+        let mut history = long_history(40);
+
+        let result = run_overflow_loop(&provider, &mut history)
+            .await
+            .expect("loop should recover by compacting history");
+
+        assert_eq!(result, "answer");
+        assert_eq!(provider.models_seen.lock().unwrap().len(), 2);
+        assert!(history
+            .iter()
+            .any(|m| m.content.starts_with("[Compaction summary]")));
+    }
+
+    #[tokio::test]
+    async fn run_tool_call_loop_escalates_to_larger_context_route() {
+        let provider = OverflowingProvider {
+            max_chars: usize::MAX,
+            accepted_model: Some("hint:long"),
+            models_seen: Arc::new(Mutex::new(Vec::new())),
+        };
+        let mut history = long_history(2);
+        let escalation = ContextEscalation {
+            model: "hint:long".into(),
+            budget: ContextBudget {
+                context_window: 1_000_000,
+                reserved_output: 4_096,
+            },
+        };
+
+        let result =
+            scope_context_escalations(vec![escalation], run_overflow_loop(&provider, &mut history))
+                .await
+                .expect("loop should recover by switching to the larger model");
+
+        assert_eq!(result, "answer");
+        let models = provider.models_seen.lock().unwrap().clone();
+        assert_eq!(models.first().map(String::as_str), Some("small-model"));
+        assert_eq!(models.last().map(String::as_str), Some("hint:long"));
+    }
+
+    #[tokio::test]
+    async fn run_tool_call_loop_surfaces_overflow_when_recovery_is_exhausted() {
+        let provider = OverflowingProvider {
+            max_chars: 0,
+            accepted_model: None,
+            models_seen: Arc::new(Mutex::new(Vec::new())),
+        };
+        let mut history = long_history(1);
+
+        let err = run_overflow_loop(&provider, &mut history)
+            .await
+            .expect_err("overflow without a working recovery step should fail");
+
+        assert!(providers::reliable::is_context_window_exceeded(&err));
+        // Initial attempt plus one retry each for compaction and truncation.
+        assert_eq!(provider.models_seen.lock().unwrap().len(), 3);
+    }
+
     #[tokio::test]
     async fn run_tool_call_loop_retries_once_when_response_defers_action_without_tool_call() {
         let provider = ScriptedProvider::from_text_responses(vec![
@@ -5195,7 +6108,7 @@ Let me check the result."#;
             name: "shell".into(),
             arguments: "{}".into(),
         }];
-        let result = build_native_assistant_history("answer", &calls, Some("thinking step"));
+        let result = build_native_assistant_history("answer", &calls, Some("thinking step"), &[]);
         let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
         assert_eq!(parsed["content"].as_str(), Some("answer"));
         assert_eq!(parsed["reasoning_content"].as_str(), Some("thinking step"));
@@ -5209,7 +6122,7 @@ Let me check the result."#;
             name: "shell".into(),
             arguments: "{}".into(),
         }];
-        let result = build_native_assistant_history("answer", &calls, None);
+        let result = build_native_assistant_history("answer", &calls, None, &[]);
         let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
         assert_eq!(parsed["content"].as_str(), Some("answer"));
         assert!(parsed.get("reasoning_content").is_none());
@@ -1,7 +1,8 @@
 use super::parsing::ParsedToolCall;
-use super::{scrub_credentials, ToolLoopCancelled};
+use super::{scrub_credentials, ToolLoopCancelled, TOOL_LOOP_OUTPUT_SPILL};
 use crate::approval::ApprovalManager;
 use crate::observability::{Observer, ObserverEvent};
+use crate::tools::schema::check_tool_arguments;
 use crate::tools::Tool;
 use anyhow::Result;
 use std::time::{Duration, Instant};
@@ -10,6 +11,20 @@ use tokio_util::sync::CancellationToken;
 fn find_tool<'a>(tools: &'a [Box<dyn Tool>], name: &str) -> Option<&'a dyn Tool> {
     tools.iter().find(|t| t.name() == name).map(|t| t.as_ref())
 }
+
+/// Swap an oversized output for an artifact preview when the loop runs with
+/// a spill policy (see [`super::scope_tool_output_spill`]).
+fn spill_large_output(call_name: &str, output: String) -> String {
+    TOOL_LOOP_OUTPUT_SPILL
+        .try_with(|spill| {
+            spill
+                .as_ref()
+                .and_then(|spill| spill.apply(call_name, &output))
+        })
+        .ok()
+        .flatten()
+        .unwrap_or(output)
+}
 async fn execute_one_tool(
     call_name: &str,
     call_arguments: serde_json::Value,
@@ -38,6 +53,23 @@ async fn execute_one_tool(
         });
     };
 
+    if let Some(reason) =
+        check_tool_arguments(call_name, &tool.parameters_schema(), &call_arguments)
+    {
+        let duration = start.elapsed();
+        observer.record_event(&ObserverEvent::ToolCall {
+            tool: call_name.to_string(),
+            duration,
+            success: false,
+        });
+        return Ok(ToolExecutionOutcome {
+            output: format!("Error: {reason}"),
+            success: false,
+            error_reason: Some(scrub_credentials(&reason)),
+            duration,
+        });
+    }
+
     let tool_future = tool.execute(call_arguments);
     let tool_result = if let Some(token) = cancellation_token {
         tokio::select! {
@@ -58,7 +90,7 @@ async fn execute_one_tool(
             });
             if r.success {
                 Ok(ToolExecutionOutcome {
-                    output: scrub_credentials(&r.output),
+                    output: spill_large_output(call_name, scrub_credentials(&r.output)),
                     success: true,
                     error_reason: None,
                     duration,
@@ -66,7 +98,7 @@ async fn execute_one_tool(
             } else {
                 let reason = r.error.unwrap_or(r.output);
                 Ok(ToolExecutionOutcome {
-                    output: format!("Error: {reason}"),
+                    output: spill_large_output(call_name, format!("Error: {reason}")),
                     success: false,
                     error_reason: Some(scrub_credentials(&reason)),
                     duration,
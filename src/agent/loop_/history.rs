@@ -1,3 +1,4 @@
+use crate::agent::context_budget::estimate_messages_tokens;
 use crate::providers::{ChatMessage, Provider};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
@@ -12,6 +13,10 @@ const COMPACTION_MAX_SOURCE_CHARS: usize = 12_000;
 /// Max characters retained in stored compaction summary.
 const COMPACTION_MAX_SUMMARY_CHARS: usize = 2_000;
 
+/// Compact once history uses this share (percent) of the prompt token budget,
+/// leaving headroom for the next user message and tool output.
+const COMPACTION_TOKEN_THRESHOLD_PERCENT: usize = 75;
+
 /// Trim conversation history to prevent unbounded growth.
 /// Preserves the system prompt (first message if role=system) and the most recent messages.
 pub(super) fn trim_history(history: &mut Vec<ChatMessage>, max_history: usize) {
@@ -61,6 +66,7 @@ pub(super) async fn auto_compact_history(
     provider: &dyn Provider,
     model: &str,
     max_history: usize,
+    token_budget: usize,
 ) -> Result<bool> {
     let has_system = history.first().map_or(false, |m| m.role == "system");
     let non_system_count = if has_system {
@@ -69,12 +75,18 @@ pub(super) async fn auto_compact_history(
         history.len()
     };
 
-    if non_system_count <= max_history {
+    let over_token_budget =
+        estimate_messages_tokens(history) > token_budget / 100 * COMPACTION_TOKEN_THRESHOLD_PERCENT;
+    if non_system_count <= max_history && !over_token_budget {
         return Ok(false);
     }
 
     let start = if has_system { 1 } else { 0 };
-    let keep_recent = COMPACTION_KEEP_RECENT_MESSAGES.min(non_system_count);
+    let mut keep_recent = COMPACTION_KEEP_RECENT_MESSAGES.min(non_system_count);
+    if over_token_budget && keep_recent == non_system_count {
+        // Few but large messages: summarize the older half.
+        keep_recent = (non_system_count / 2).max(1);
+    }
     let compact_count = non_system_count.saturating_sub(keep_recent);
     if compact_count == 0 {
         return Ok(false);
@@ -104,3 +116,43 @@ pub(super) async fn auto_compact_history(
 
     Ok(true)
 }
+
+/// Compact history regardless of the usual length thresholds, e.g. after the
+/// provider rejected a request as too long. Returns whether anything changed.
+pub(super) async fn force_compact_history(
+    history: &mut Vec<ChatMessage>,
+    provider: &dyn Provider,
+    model: &str,
+) -> bool {
+    let current_tokens = estimate_messages_tokens(history).max(1);
+    auto_compact_history(history, provider, model, usize::MAX, current_tokens)
+        .await
+        .unwrap_or(false)
+}
+
+/// Recovery steps tried, in order, when a request exceeds the context window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ContextRecoveryStep {
+    /// Summarize older history.
+    CompactHistory,
+    /// Refit requests into a smaller budget, truncating oversized tool results.
+    TruncateToolResults,
+    /// Switch to a larger-context model from `model_routes`.
+    EscalateModel,
+}
+
+impl ContextRecoveryStep {
+    pub(super) const ORDER: [Self; 3] = [
+        Self::CompactHistory,
+        Self::TruncateToolResults,
+        Self::EscalateModel,
+    ];
+
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::CompactHistory => "compact_history",
+            Self::TruncateToolResults => "truncate_tool_results",
+            Self::EscalateModel => "escalate_model",
+        }
+    }
+}
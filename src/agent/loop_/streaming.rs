@@ -0,0 +1,85 @@
+use super::{ToolLoopCancelled, DRAFT_PROGRESS_SENTINEL};
+use crate::providers::traits::{StreamAccumulator, StreamOptions};
+use crate::providers::{ChatRequest, ChatResponse, Provider, StreamEvent};
+use anyhow::Result;
+use futures_util::StreamExt;
+use tokio::sync::mpsc::Sender;
+use tokio_util::sync::CancellationToken;
+
+/// Run one model turn through [`Provider::stream_chat`], relaying events as
+/// they arrive.
+///
+/// Text deltas go to `on_delta` verbatim; the first fragment naming a tool
+/// call produces a progress line. Every event is also forwarded to
+/// `event_sink` when one is attached.
+///
+/// Returns `Ok(None)` when the stream fails before yielding any event so the
+/// caller can retry through the non-streaming `chat()` path.
+pub(super) async fn stream_chat_response(
+    provider: &dyn Provider,
+    request: ChatRequest<'_>,
+    model: &str,
+    temperature: f64,
+    on_delta: Option<&Sender<String>>,
+    event_sink: Option<&Sender<StreamEvent>>,
+    cancellation_token: Option<&CancellationToken>,
+) -> Result<Option<ChatResponse>> {
+    let mut stream = provider.stream_chat(request, model, temperature, StreamOptions::new(true));
+    let mut accumulator = StreamAccumulator::new();
+    let mut received_any = false;
+
+    loop {
+        let next = if let Some(token) = cancellation_token {
+            tokio::select! {
+                () = token.cancelled() => return Err(ToolLoopCancelled.into()),
+                next = stream.next() => next,
+            }
+        } else {
+            stream.next().await
+        };
+
+        let event = match next {
+            None => break,
+            Some(Ok(event)) => event,
+            Some(Err(e)) if !received_any => {
+                tracing::debug!("Tool-call stream failed before any output, falling back: {e}");
+                return Ok(None);
+            }
+            Some(Err(e)) => return Err(anyhow::anyhow!("Streaming response failed: {e}")),
+        };
+        received_any = true;
+
+        let named_before = match &event {
+            StreamEvent::ToolCallDelta(delta) => accumulator.tool_call_name(delta.index).is_some(),
+            _ => true,
+        };
+        accumulator.push(&event);
+
+        if let Some(tx) = on_delta {
+            match &event {
+                StreamEvent::TextDelta(text) => {
+                    let _ = tx.send(text.clone()).await;
+                }
+                StreamEvent::ToolCallDelta(delta) if !named_before => {
+                    if let Some(name) = accumulator.tool_call_name(delta.index) {
+                        let _ = tx
+                            .send(format!(
+                                "{DRAFT_PROGRESS_SENTINEL}\u{1f527} Calling {name}...\n"
+                            ))
+                            .await;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let done = matches!(event, StreamEvent::Done);
+        if let Some(sink) = event_sink {
+            let _ = sink.send(event).await;
+        }
+        if done {
+            break;
+        }
+    }
+
+    Ok(Some(accumulator.finish()))
+}
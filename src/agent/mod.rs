@@ -1,11 +1,16 @@
 #[allow(clippy::module_inception)]
 pub mod agent;
+pub mod branching;
+pub mod checkpoint;
 pub mod classifier;
+pub mod context_budget;
 pub mod dispatcher;
 pub mod loop_;
 pub mod memory_loader;
+pub mod plan_execute;
 pub mod prompt;
 pub mod research;
+pub mod tool_retrieval;
 // brahmastra-fork: multi-agent routing
 pub mod router;
 
@@ -0,0 +1,583 @@
+//! Plan-and-execute mode — a planner model writes the plan, an executor runs it.
+//!
+//! When `[agent.plan_execute]` is enabled, each user turn starts with one
+//! planner call that breaks the request into a short list of steps. The plan
+//! is mirrored into the session's `task_plan` checklist, and every step is
+//! then handed to the executor model as its own `run_tool_call_loop` run over
+//! the shared history. If a step errors, or the executor answers with
+//! [`REPLAN_MARKER`] because the plan no longer fits what it found, the
+//! planner is asked for new remaining steps. A final executor call answers
+//! the original request from the accumulated step results.
+//!
+//! The planner only sees the request, the tool names and step summaries, so
+//! the premium model is paid for a handful of short calls rather than every
+//! tool iteration.
+
+use crate::agent::loop_::{is_tool_loop_cancelled, run_tool_call_loop};
+use crate::approval::ApprovalManager;
+use crate::config::{ModelRouteConfig, MultimodalConfig, PlanExecuteConfig};
+use crate::observability::Observer;
+use crate::providers::structured::chat_structured;
+use crate::providers::{ChatMessage, Provider, ResponseFormat};
+use crate::tools::Tool;
+use crate::util::truncate_with_ellipsis;
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::LazyLock;
+
+/// Executor reply prefix asking the planner to revise the remaining steps.
+pub const REPLAN_MARKER: &str = "[REPLAN]";
+
+/// Prefix of the `user` message that hands one plan step to the executor.
+pub const PLAN_STEP_PREFIX: &str = "[Plan step ";
+
+/// Prefix of the `user` message asking the executor for the final answer.
+pub const PLAN_COMPLETE_PREFIX: &str = "[Plan complete]";
+
+/// Characters of each step outcome shown to the planner when re-planning.
+const PLANNER_OUTCOME_CHARS: usize = 600;
+
+const PLANNER_SYSTEM_PROMPT: &str = "You are the planner for a tool-using assistant. \
+Break the user's request into a short ordered list of concrete steps. Each step is handed \
+to a less capable executor model that carries it out with the listed tools and reports back, \
+so every step must be self-contained and say exactly what to do and what to report.\n\n\
+Return the steps in order. Return an empty step list when the request is a simple question \
+that needs no plan.";
+
+/// Response format the planner must reply with.
+static PLAN_FORMAT: LazyLock<ResponseFormat> = LazyLock::new(|| {
+    ResponseFormat::json_schema(
+        "plan",
+        json!({
+            "type": "object",
+            "properties": {
+                "steps": {
+                    "type": "array",
+                    "items": { "type": "string" }
+                }
+            },
+            "required": ["steps"],
+            "additionalProperties": false
+        }),
+    )
+});
+
+/// Planner reply matching [`PLAN_FORMAT`].
+#[derive(Debug, Deserialize)]
+struct Plan {
+    steps: Vec<String>,
+}
+
+/// Planner and executor selection for plan-and-execute turns.
+#[derive(Debug, Clone)]
+pub struct PlanExecutor {
+    planner_model: Option<String>,
+    executor_model: Option<String>,
+    max_steps: usize,
+    max_replans: usize,
+}
+
+impl PlanExecutor {
+    /// Build from config, or `None` when plan-and-execute is disabled.
+    ///
+    /// A hint is only used when a `[[model_routes]]` entry serves it; otherwise
+    /// that role falls back to the turn's model.
+    pub fn from_config(config: &PlanExecuteConfig, routes: &[ModelRouteConfig]) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let route_model = |hint: &str| {
+            let hint = hint.trim();
+            routes
+                .iter()
+                .any(|route| route.hint == hint)
+                .then(|| format!("hint:{hint}"))
+        };
+        Some(Self {
+            planner_model: route_model(&config.planner_hint),
+            executor_model: route_model(&config.executor_hint),
+            max_steps: config.max_steps.max(1),
+            max_replans: config.max_replans,
+        })
+    }
+
+    async fn plan(
+        &self,
+        provider: &dyn Provider,
+        model: &str,
+        temperature: f64,
+        request: &str,
+        tool_names: &[&str],
+        completed: &[(String, String)],
+        failure: Option<(&str, &str)>,
+    ) -> Result<Vec<String>> {
+        let mut input = format!("Request:\n{request}\n\nAvailable tools: ");
+        input.push_str(&tool_names.join(", "));
+        let _ = write!(input, "\n\nUse at most {} steps.", self.max_steps);
+        if !completed.is_empty() {
+            input.push_str("\n\nCompleted steps:");
+            for (index, (step, outcome)) in completed.iter().enumerate() {
+                let _ = write!(
+                    input,
+                    "\n{}. {step}\n   Result: {}",
+                    index + 1,
+                    truncate_with_ellipsis(outcome.trim(), PLANNER_OUTCOME_CHARS)
+                );
+            }
+        }
+        if let Some((step, reason)) = failure {
+            let _ = write!(
+                input,
+                "\n\nThis step could not be completed:\n{step}\nReason: {reason}\n\n\
+                 Plan only the remaining work, taking the completed steps into account."
+            );
+        }
+
+        let messages = [
+            ChatMessage::system(PLANNER_SYSTEM_PROMPT),
+            ChatMessage::user(input),
+        ];
+        let plan: Plan = chat_structured(provider, &messages, &PLAN_FORMAT, model, temperature)
+            .await?
+            .parse()?;
+        Ok(plan
+            .steps
+            .into_iter()
+            .map(|step| step.trim().to_string())
+            .filter(|step| !step.is_empty())
+            .take(self.max_steps)
+            .collect())
+    }
+
+    /// Plan the latest user message in `history`, then execute it step by step.
+    async fn run(
+        &self,
+        tool_loop: &ToolLoop<'_>,
+        history: &mut Vec<ChatMessage>,
+        model: &str,
+    ) -> Result<String> {
+        let planner_model = self.planner_model.as_deref().unwrap_or(model);
+        let executor_model = self.executor_model.as_deref().unwrap_or(model);
+        let provider = tool_loop.provider;
+        let temperature = tool_loop.temperature;
+        let silent = tool_loop.silent;
+        let request = history
+            .iter()
+            .rfind(|message| message.role == "user")
+            .map(|message| message.content.clone())
+            .unwrap_or_default();
+        let tool_names: Vec<&str> = tool_loop
+            .tools_registry
+            .iter()
+            .map(|tool| tool.name())
+            .collect();
+
+        let mut remaining = self
+            .plan(
+                provider,
+                planner_model,
+                temperature,
+                &request,
+                &tool_names,
+                &[],
+                None,
+            )
+            .await?;
+        if remaining.is_empty() {
+            tracing::debug!("Planner returned no steps; answering directly");
+            return tool_loop.run(history, executor_model).await;
+        }
+
+        let mut completed: Vec<(String, String)> = Vec::new();
+        let mut replans = 0;
+        while !remaining.is_empty() {
+            sync_task_plan(tool_loop.tools_registry, &completed, &remaining).await;
+            let step = remaining[0].clone();
+            let number = completed.len() + 1;
+            let total = completed.len() + remaining.len();
+            if !silent {
+                println!("📋 Step {number}/{total}: {step}");
+            }
+            history.push(ChatMessage::user(step_prompt(&completed, &remaining)));
+
+            let reason = match tool_loop.run(history, executor_model).await {
+                Ok(reply) => match reply.trim_start().strip_prefix(REPLAN_MARKER) {
+                    Some(reason) => reason.trim().to_string(),
+                    None => {
+                        completed.push((remaining.remove(0), reply));
+                        continue;
+                    }
+                },
+                Err(error) if is_tool_loop_cancelled(&error) => return Err(error),
+                Err(error) => {
+                    // Close the step so the history stays a valid exchange.
+                    history.push(ChatMessage::assistant(format!(
+                        "Step {number} failed: {error}"
+                    )));
+                    error.to_string()
+                }
+            };
+
+            if replans >= self.max_replans {
+                anyhow::bail!("Plan step {number} failed after {replans} re-plan(s): {reason}");
+            }
+            replans += 1;
+            tracing::info!(step = number, replans, reason = %reason, "Re-planning remaining steps");
+            if !silent {
+                println!("🔁 Re-planning: {}", truncate_with_ellipsis(&reason, 200));
+            }
+            remaining = self
+                .plan(
+                    provider,
+                    planner_model,
+                    temperature,
+                    &request,
+                    &tool_names,
+                    &completed,
+                    Some((&step, &reason)),
+                )
+                .await?;
+        }
+        sync_task_plan(tool_loop.tools_registry, &completed, &remaining).await;
+
+        history.push(ChatMessage::user(format!(
+            "{PLAN_COMPLETE_PREFIX} All {} step(s) are done. Using their results above, reply to \
+             the original request:\n{request}",
+            completed.len()
+        )));
+        tool_loop.run(history, executor_model).await
+    }
+}
+
+/// Run one user turn: the plain tool loop, or plan-and-execute when enabled.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_turn(
+    plan_execute: Option<&PlanExecutor>,
+    provider: &dyn Provider,
+    history: &mut Vec<ChatMessage>,
+    tools_registry: &[Box<dyn Tool>],
+    observer: &dyn Observer,
+    provider_name: &str,
+    model: &str,
+    temperature: f64,
+    silent: bool,
+    approval: Option<&ApprovalManager>,
+    channel_name: &str,
+    multimodal_config: &MultimodalConfig,
+    max_tool_iterations: usize,
+) -> Result<String> {
+    let tool_loop = ToolLoop {
+        provider,
+        tools_registry,
+        observer,
+        provider_name,
+        temperature,
+        silent,
+        approval,
+        channel_name,
+        multimodal_config,
+        max_tool_iterations,
+    };
+    match plan_execute {
+        Some(plan_execute) => plan_execute.run(&tool_loop, history, model).await,
+        None => tool_loop.run(history, model).await,
+    }
+}
+
+/// The `run_tool_call_loop` arguments shared by every step of a turn.
+struct ToolLoop<'a> {
+    provider: &'a dyn Provider,
+    tools_registry: &'a [Box<dyn Tool>],
+    observer: &'a dyn Observer,
+    provider_name: &'a str,
+    temperature: f64,
+    silent: bool,
+    approval: Option<&'a ApprovalManager>,
+    channel_name: &'a str,
+    multimodal_config: &'a MultimodalConfig,
+    max_tool_iterations: usize,
+}
+
+impl ToolLoop<'_> {
+    async fn run(&self, history: &mut Vec<ChatMessage>, model: &str) -> Result<String> {
+        run_tool_call_loop(
+            self.provider,
+            history,
+            self.tools_registry,
+            self.observer,
+            self.provider_name,
+            model,
+            self.temperature,
+            self.silent,
+            self.approval,
+            self.channel_name,
+            self.multimodal_config,
+            self.max_tool_iterations,
+            None,
+            None,
+            None,
+            &[],
+        )
+        .await
+    }
+}
+
+/// Executor instructions for the first step in `remaining`.
+fn step_prompt(completed: &[(String, String)], remaining: &[String]) -> String {
+    let number = completed.len() + 1;
+    let total = completed.len() + remaining.len();
+    let mut prompt = format!(
+        "{PLAN_STEP_PREFIX}{number}/{total}] {}\n\nPlan:",
+        remaining[0]
+    );
+    for (index, (step, _)) in completed.iter().enumerate() {
+        let _ = write!(prompt, "\n{}. [done] {step}", index + 1);
+    }
+    for (offset, step) in remaining.iter().enumerate() {
+        let marker = if offset == 0 { "[current] " } else { "" };
+        let _ = write!(prompt, "\n{}. {marker}{step}", number + offset);
+    }
+    let _ = write!(
+        prompt,
+        "\n\nCarry out only the current step, using tools as needed, then reply with a brief \
+         summary of the result. If the step cannot be done as written, or what you found means \
+         the plan no longer fits, reply starting with {REPLAN_MARKER} and explain why."
+    );
+    prompt
+}
+
+/// Mirror plan progress into the session's `task_plan` checklist, if registered.
+async fn sync_task_plan(
+    tools_registry: &[Box<dyn Tool>],
+    completed: &[(String, String)],
+    remaining: &[String],
+) {
+    let Some(task_plan) = tools_registry
+        .iter()
+        .find(|tool| tool.name() == "task_plan")
+    else {
+        return;
+    };
+    let tasks: Vec<serde_json::Value> = completed
+        .iter()
+        .map(|(step, _)| json!({"title": step, "status": "completed"}))
+        .chain(remaining.iter().enumerate().map(|(index, step)| {
+            let status = if index == 0 { "in_progress" } else { "pending" };
+            json!({"title": step, "status": status})
+        }))
+        .collect();
+    match task_plan
+        .execute(json!({"action": "create", "tasks": tasks}))
+        .await
+    {
+        Ok(result) if !result.success => {
+            tracing::debug!(error = ?result.error, "task_plan rejected plan update");
+        }
+        Err(error) => tracing::debug!(%error, "task_plan plan update failed"),
+        Ok(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::branching;
+    use crate::observability::NoopObserver;
+    use crate::providers::scripted::{ScriptedProvider, ScriptedStep};
+    use crate::security::SecurityPolicy;
+    use crate::tools::TaskPlanTool;
+    use std::sync::Arc;
+
+    fn route(hint: &str) -> ModelRouteConfig {
+        ModelRouteConfig {
+            hint: hint.into(),
+            provider: "openrouter".into(),
+            model: format!("{hint}-model"),
+            max_tokens: None,
+            api_key: None,
+            transport: None,
+            quality_tier: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn plan_retries_non_conforming_replies_and_caps_steps() {
+        let provider = ScriptedProvider::new(vec![
+            ScriptedStep::text("1. read config\n2. edit it"),
+            ScriptedStep::text(r#"{"steps": [" read config", " ", "edit it", "test it"]}"#),
+        ]);
+        let config = PlanExecuteConfig {
+            enabled: true,
+            max_steps: 2,
+            ..PlanExecuteConfig::default()
+        };
+        let executor = PlanExecutor::from_config(&config, &[]).unwrap();
+
+        let steps = executor
+            .plan(&provider, "model", 0.0, "Update the config", &[], &[], None)
+            .await
+            .unwrap();
+
+        assert_eq!(steps, vec!["read config", "edit it"]);
+        let requests = provider.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1]
+            .last()
+            .unwrap()
+            .content
+            .contains("does not match the required JSON schema `plan`"));
+    }
+
+    #[test]
+    fn from_config_uses_hints_only_when_routed() {
+        let config = PlanExecuteConfig {
+            enabled: true,
+            ..PlanExecuteConfig::default()
+        };
+        let executor = PlanExecutor::from_config(&config, &[route("planner")]).unwrap();
+        assert_eq!(executor.planner_model.as_deref(), Some("hint:planner"));
+        assert_eq!(executor.executor_model, None);
+
+        assert!(PlanExecutor::from_config(&PlanExecuteConfig::default(), &[]).is_none());
+    }
+
+    #[tokio::test]
+    async fn run_replans_after_executor_reports_divergence() {
+        let provider = ScriptedProvider::new(vec![
+            ScriptedStep::text(r#"{"steps": ["inspect the repo", "patch main.rs"]}"#),
+            ScriptedStep::text("The repo is a Rust crate."),
+            ScriptedStep::text("[REPLAN] main.rs does not exist; the entry point is lib.rs"),
+            ScriptedStep::text(r#"{"steps": ["patch lib.rs"]}"#),
+            ScriptedStep::text("Patched lib.rs."),
+            ScriptedStep::text("Done: lib.rs is patched."),
+        ]);
+        let task_plan = TaskPlanTool::new(Arc::new(SecurityPolicy::default()));
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(task_plan)];
+        let config = PlanExecuteConfig {
+            enabled: true,
+            ..PlanExecuteConfig::default()
+        };
+        let executor = PlanExecutor::from_config(&config, &[]).unwrap();
+        let mut history = vec![ChatMessage::user("Fix the entry point")];
+
+        let reply = run_turn(
+            Some(&executor),
+            &provider,
+            &mut history,
+            &tools,
+            &NoopObserver,
+            "scripted",
+            "model",
+            0.0,
+            true,
+            None,
+            "cli",
+            &MultimodalConfig::default(),
+            5,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(reply, "Done: lib.rs is patched.");
+        assert_eq!(provider.remaining(), 0);
+        let requests = provider.requests();
+        let replan_input = &requests[3].last().unwrap().content;
+        assert!(replan_input.contains("1. inspect the repo\n   Result: The repo is a Rust crate."));
+        assert!(replan_input.contains("Reason: main.rs does not exist"));
+        assert!(history
+            .iter()
+            .any(|message| message.content.starts_with("[Plan step 2/2] patch lib.rs")));
+
+        let checklist = tools[0]
+            .execute(json!({"action": "list"}))
+            .await
+            .unwrap()
+            .output;
+        assert!(checklist.starts_with("Tasks (2/2 completed):"));
+        assert!(checklist.contains("patch lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn undo_after_plan_turn_drops_the_whole_turn() {
+        let provider = ScriptedProvider::new(vec![
+            ScriptedStep::text(r#"{"steps": ["list the files", "count them"]}"#),
+            ScriptedStep::text("Found a.rs, b.rs and c.rs."),
+            ScriptedStep::text("3 files."),
+            ScriptedStep::text("There are 3 files."),
+        ]);
+        let config = PlanExecuteConfig {
+            enabled: true,
+            ..PlanExecuteConfig::default()
+        };
+        let executor = PlanExecutor::from_config(&config, &[]).unwrap();
+        let mut history = vec![
+            ChatMessage::system("system"),
+            ChatMessage::user("[2026-10-19 09:00:00 UTC] hi"),
+            ChatMessage::assistant("hello"),
+            ChatMessage::user("[2026-10-19 09:01:00 UTC] How many files are there?"),
+        ];
+
+        run_turn(
+            Some(&executor),
+            &provider,
+            &mut history,
+            &[],
+            &NoopObserver,
+            "scripted",
+            "model",
+            0.0,
+            true,
+            None,
+            "cli",
+            &MultimodalConfig::default(),
+            5,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(branching::exchange_starts(&history), vec![1, 3]);
+        assert_eq!(
+            branching::undo_last_exchange(&mut history).as_deref(),
+            Some("How many files are there?")
+        );
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].content, "hello");
+    }
+
+    #[tokio::test]
+    async fn run_gives_up_after_max_replans() {
+        let provider = ScriptedProvider::new(vec![
+            ScriptedStep::text(r#"{"steps": ["deploy"]}"#),
+            ScriptedStep::text("[REPLAN] cluster unreachable"),
+        ]);
+        let config = PlanExecuteConfig {
+            enabled: true,
+            max_replans: 0,
+            ..PlanExecuteConfig::default()
+        };
+        let executor = PlanExecutor::from_config(&config, &[]).unwrap();
+        let mut history = vec![ChatMessage::user("Deploy it")];
+
+        let error = run_turn(
+            Some(&executor),
+            &provider,
+            &mut history,
+            &[],
+            &NoopObserver,
+            "scripted",
+            "model",
+            0.0,
+            true,
+            None,
+            "cli",
+            &MultimodalConfig::default(),
+            5,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(error.to_string().contains("cluster unreachable"));
+    }
+}
@@ -95,6 +95,7 @@ impl Provider for ScriptedProvider {
                 tool_calls: vec![],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             });
         }
         Ok(guard.remove(0))
@@ -332,6 +333,7 @@ fn tool_response(calls: Vec<ToolCall>) -> ChatResponse {
         tool_calls: calls,
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     }
 }
 
@@ -342,6 +344,7 @@ fn text_response(text: &str) -> ChatResponse {
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     }
 }
 
@@ -354,6 +357,7 @@ fn xml_tool_response(name: &str, args: &str) -> ChatResponse {
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     }
 }
 
@@ -744,6 +748,7 @@ async fn turn_handles_empty_text_response() {
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     }]));
 
     let mut agent = build_agent_with(provider, vec![], Box::new(NativeToolDispatcher));
@@ -759,6 +764,7 @@ async fn turn_handles_none_text_response() {
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     }]));
 
     let mut agent = build_agent_with(provider, vec![], Box::new(NativeToolDispatcher));
@@ -784,6 +790,7 @@ async fn turn_preserves_text_alongside_tool_calls() {
             }],
             usage: None,
             reasoning_content: None,
+            thinking: Vec::new(),
         },
         text_response("Here are the results"),
     ]));
@@ -1022,6 +1029,7 @@ async fn native_dispatcher_handles_stringified_arguments() {
         }],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     let (_, calls) = dispatcher.parse_response(&response);
@@ -1049,6 +1057,7 @@ fn xml_dispatcher_handles_nested_json() {
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     let dispatcher = XmlToolDispatcher;
@@ -1068,6 +1077,7 @@ fn xml_dispatcher_handles_empty_tool_call_tag() {
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     let dispatcher = XmlToolDispatcher;
@@ -1083,6 +1093,7 @@ fn xml_dispatcher_handles_unclosed_tool_call() {
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     let dispatcher = XmlToolDispatcher;
@@ -1109,6 +1120,7 @@ fn conversation_message_serialization_roundtrip() {
                 arguments: "{}".into(),
             }],
             reasoning_content: None,
+            thinking: Vec::new(),
         },
         ConversationMessage::ToolResults(vec![ToolResultMessage {
             tool_call_id: "tc1".into(),
@@ -1234,6 +1246,7 @@ fn xml_dispatcher_converts_history_to_provider_messages() {
                 arguments: "{}".into(),
             }],
             reasoning_content: None,
+            thinking: Vec::new(),
         },
         ConversationMessage::ToolResults(vec![ToolResultMessage {
             tool_call_id: "tc1".into(),
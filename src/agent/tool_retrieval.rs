@@ -0,0 +1,359 @@
+//! Retrieval-based tool selection (`[agent.tool_retrieval]`).
+//!
+//! Large registries (MCP servers, Composio, skills, peripherals, WASM) can
+//! hold well over a hundred tools. Sending every spec on every request is
+//! expensive and confuses smaller models, so each turn offers only the pinned
+//! tools plus the ones most relevant to the request. The model loads more
+//! through the `tool_search` meta-tool, which activates its results for the
+//! rest of the turn. Selection applies to native tool specs and to the XML
+//! tool protocol alike.
+
+use crate::config::{Config, ToolRetrievalConfig};
+use crate::memory::embeddings::EmbeddingProvider;
+use crate::memory::vector::cosine_similarity;
+use crate::providers::ChatMessage;
+use crate::tools::ToolSpec;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Name of the meta-tool that searches the full registry mid-turn.
+pub const TOOL_SEARCH_TOOL_NAME: &str = "tool_search";
+
+/// Ranks registry tools against a request using the configured embedding
+/// provider, falling back to keyword overlap when embeddings are unavailable.
+pub struct ToolRetriever {
+    config: ToolRetrievalConfig,
+    embedder: Arc<dyn EmbeddingProvider>,
+    /// Embedded tool text keyed by tool name; re-embedded when the text changes.
+    index: tokio::sync::Mutex<HashMap<String, (String, Vec<f32>)>>,
+}
+
+impl ToolRetriever {
+    pub fn new(config: ToolRetrievalConfig, embedder: Arc<dyn EmbeddingProvider>) -> Self {
+        Self {
+            config,
+            embedder,
+            index: tokio::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Build the retriever for `config`, or `None` when retrieval is disabled.
+    pub fn from_config(config: &Config) -> Option<Arc<Self>> {
+        if !config.agent.tool_retrieval.enabled {
+            return None;
+        }
+        let embedder = crate::memory::create_embedder(
+            &config.memory,
+            &config.embedding_routes,
+            config.api_key.as_deref(),
+        );
+        Some(Arc::new(Self::new(
+            config.agent.tool_retrieval.clone(),
+            embedder,
+        )))
+    }
+
+    /// Return `specs` ordered by relevance to `query`, best first.
+    /// Keyword ranking drops tools that share no word with the query.
+    pub async fn rank<'a>(&self, query: &str, specs: &'a [ToolSpec]) -> Vec<&'a ToolSpec> {
+        if self.embedder.dimensions() > 0 {
+            match self.rank_by_embedding(query, specs).await {
+                Ok(ranked) => return ranked,
+                Err(err) => {
+                    tracing::warn!("Tool retrieval embedding failed, using keyword ranking: {err}");
+                }
+            }
+        }
+        rank_by_keywords(query, specs)
+    }
+
+    async fn rank_by_embedding<'a>(
+        &self,
+        query: &str,
+        specs: &'a [ToolSpec],
+    ) -> anyhow::Result<Vec<&'a ToolSpec>> {
+        let mut index = self.index.lock().await;
+        let stale: Vec<(&str, String)> = specs
+            .iter()
+            .map(|spec| (spec.name.as_str(), tool_text(spec)))
+            .filter(|(name, text)| index.get(*name).is_none_or(|(indexed, _)| indexed != text))
+            .collect();
+        if !stale.is_empty() {
+            let texts: Vec<&str> = stale.iter().map(|(_, text)| text.as_str()).collect();
+            let vectors = self.embedder.embed(&texts).await?;
+            anyhow::ensure!(
+                vectors.len() == stale.len(),
+                "embedding provider returned {} vectors for {} tools",
+                vectors.len(),
+                stale.len()
+            );
+            for ((name, text), vector) in stale.into_iter().zip(vectors) {
+                index.insert(name.to_string(), (text, vector));
+            }
+        }
+
+        let query_vector = self.embedder.embed_one(query).await?;
+        let mut scored: Vec<(f32, &ToolSpec)> = specs
+            .iter()
+            .map(|spec| {
+                let score = index
+                    .get(&spec.name)
+                    .map_or(0.0, |(_, vector)| cosine_similarity(&query_vector, vector));
+                (score, spec)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(scored.into_iter().map(|(_, spec)| spec).collect())
+    }
+
+    /// Initial tool set for a turn: pinned tools, `tool_search` and the
+    /// `top_k` best matches for `query`.
+    pub async fn select(&self, query: &str, specs: &[ToolSpec]) -> HashSet<String> {
+        let mut selected: HashSet<String> = self
+            .config
+            .pinned_tools
+            .iter()
+            .cloned()
+            .chain(std::iter::once(TOOL_SEARCH_TOOL_NAME.to_string()))
+            .collect();
+        let candidates: Vec<ToolSpec> = specs
+            .iter()
+            .filter(|spec| !selected.contains(&spec.name))
+            .cloned()
+            .collect();
+        for spec in self
+            .rank(query, &candidates)
+            .await
+            .into_iter()
+            .take(self.config.top_k)
+        {
+            selected.insert(spec.name.clone());
+        }
+        selected
+    }
+}
+
+#[derive(Default)]
+struct SelectionState {
+    /// Every tool the turn may call, in registry order.
+    catalog: Vec<ToolSpec>,
+    /// Tools currently offered to the model.
+    active: HashSet<String>,
+    /// Set when `tool_search` activated tools since the loop last looked.
+    changed: bool,
+}
+
+/// Tools offered to the model during one turn.
+pub(crate) struct TurnToolSelection {
+    retriever: Arc<ToolRetriever>,
+    state: Mutex<SelectionState>,
+}
+
+impl TurnToolSelection {
+    /// Specs from the catalog that are currently active, in registry order.
+    pub(crate) fn active_specs(&self) -> Vec<ToolSpec> {
+        let state = self.state.lock();
+        state
+            .catalog
+            .iter()
+            .filter(|spec| state.active.contains(&spec.name))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `tool_search` activated new tools since the last call.
+    pub(crate) fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.state.lock().changed)
+    }
+
+    /// Search the full catalog and activate up to `limit` matches.
+    pub(crate) async fn search(&self, query: &str, limit: usize) -> Vec<ToolSpec> {
+        let catalog = self.state.lock().catalog.clone();
+        let matches: Vec<ToolSpec> = self
+            .retriever
+            .rank(query, &catalog)
+            .await
+            .into_iter()
+            .filter(|spec| spec.name != TOOL_SEARCH_TOOL_NAME)
+            .take(limit)
+            .cloned()
+            .collect();
+        let mut state = self.state.lock();
+        for spec in &matches {
+            if state.active.insert(spec.name.clone()) {
+                state.changed = true;
+            }
+        }
+        matches
+    }
+}
+
+tokio::task_local! {
+    static TURN_TOOL_SELECTION: Option<Arc<TurnToolSelection>>;
+}
+
+/// Run `future` with per-turn tool selection driven by `retriever`.
+pub(crate) async fn scope_tool_retrieval<F: std::future::Future>(
+    retriever: Option<Arc<ToolRetriever>>,
+    future: F,
+) -> F::Output {
+    let selection = retriever.map(|retriever| {
+        Arc::new(TurnToolSelection {
+            retriever,
+            state: Mutex::new(SelectionState::default()),
+        })
+    });
+    TURN_TOOL_SELECTION.scope(selection, future).await
+}
+
+/// The selection of the running turn, if retrieval is active for it.
+pub(crate) fn current_selection() -> Option<Arc<TurnToolSelection>> {
+    TURN_TOOL_SELECTION.try_with(Clone::clone).ok().flatten()
+}
+
+/// Start tool selection for a tool loop over `specs`. Returns `None` when the
+/// loop runs without retrieval or the registry is small enough to send whole.
+pub(crate) async fn begin_selection(
+    specs: &[ToolSpec],
+    history: &[ChatMessage],
+) -> Option<Arc<TurnToolSelection>> {
+    let selection = current_selection()?;
+    if specs.len() <= selection.retriever.config.min_tools {
+        return None;
+    }
+    let query = history
+        .iter()
+        .rev()
+        .find(|message| message.role == "user")
+        .map_or("", |message| message.content.as_str());
+    let active = selection.retriever.select(query, specs).await;
+    tracing::debug!(
+        selected = active.len(),
+        registry = specs.len(),
+        "Selected tools for turn"
+    );
+    *selection.state.lock() = SelectionState {
+        catalog: specs.to_vec(),
+        active,
+        changed: false,
+    };
+    Some(selection)
+}
+
+fn tool_text(spec: &ToolSpec) -> String {
+    format!("{}: {}", spec.name.replace('_', " "), spec.description)
+}
+
+fn keywords(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn rank_by_keywords<'a>(query: &str, specs: &'a [ToolSpec]) -> Vec<&'a ToolSpec> {
+    let query_words = keywords(query);
+    let mut scored: Vec<(usize, &ToolSpec)> = specs
+        .iter()
+        .map(|spec| {
+            let name_words = keywords(&spec.name.replace('_', " "));
+            let description_words = keywords(&spec.description);
+            let score = query_words
+                .iter()
+                .map(|word| {
+                    2 * usize::from(name_words.contains(word))
+                        + usize::from(description_words.contains(word))
+                })
+                .sum();
+            (score, spec)
+        })
+        .filter(|(score, _)| *score > 0)
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, spec)| spec).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::embeddings::NoopEmbedding;
+
+    fn spec(name: &str, description: &str) -> ToolSpec {
+        ToolSpec {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+        }
+    }
+
+    fn registry() -> Vec<ToolSpec> {
+        vec![
+            spec("shell", "Run a shell command"),
+            spec("web_search", "Search the web for pages"),
+            spec("github_issues", "List and create GitHub issues"),
+            spec("calendar_events", "Read calendar events"),
+            spec("weather", "Current weather for a city"),
+        ]
+    }
+
+    fn retriever(top_k: usize, min_tools: usize) -> Arc<ToolRetriever> {
+        Arc::new(ToolRetriever::new(
+            ToolRetrievalConfig {
+                enabled: true,
+                top_k,
+                min_tools,
+                pinned_tools: vec!["shell".into()],
+            },
+            Arc::new(NoopEmbedding),
+        ))
+    }
+
+    #[tokio::test]
+    async fn select_keeps_pinned_tools_and_best_matches() {
+        let selected = retriever(1, 0)
+            .select("open a github issue about the crash", &registry())
+            .await;
+        assert!(selected.contains("shell"));
+        assert!(selected.contains(TOOL_SEARCH_TOOL_NAME));
+        assert!(selected.contains("github_issues"));
+        assert_eq!(selected.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn search_activates_matches_for_the_rest_of_the_turn() {
+        let specs = registry();
+        let history = vec![ChatMessage::user("what is on my calendar")];
+        scope_tool_retrieval(Some(retriever(1, 0)), async {
+            let selection = begin_selection(&specs, &history).await.unwrap();
+            let names = |specs: Vec<ToolSpec>| -> Vec<String> {
+                specs.into_iter().map(|spec| spec.name).collect()
+            };
+            assert_eq!(
+                names(selection.active_specs()),
+                ["shell", "calendar_events"]
+            );
+
+            let found = selection.search("weather forecast", 3).await;
+            assert_eq!(names(found), ["weather"]);
+            assert!(selection.take_changed());
+            assert!(!selection.take_changed());
+            assert_eq!(
+                names(selection.active_specs()),
+                ["shell", "calendar_events", "weather"]
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn small_registries_skip_selection() {
+        let specs = registry();
+        let history = vec![ChatMessage::user("anything")];
+        scope_tool_retrieval(Some(retriever(1, 10)), async {
+            assert!(begin_selection(&specs, &history).await.is_none());
+        })
+        .await;
+        assert!(begin_selection(&specs, &history).await.is_none());
+    }
+}
@@ -694,6 +694,15 @@ mod tests {
         assert!(!mgr.needs_approval("memory_recall"));
     }
 
+    #[test]
+    fn default_config_auto_approves_read_only_agent_tools() {
+        let mgr = ApprovalManager::from_config(&AutonomyConfig::default());
+        assert!(!mgr.needs_approval("ask_user"));
+        assert!(!mgr.needs_approval("artifact_read"));
+        assert!(!mgr.needs_approval("tool_search"));
+        assert!(mgr.needs_approval("file_write"));
+    }
+
     #[test]
     fn always_ask_tools_always_prompt() {
         let mgr = ApprovalManager::from_config(&supervised_config());
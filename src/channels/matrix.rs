@@ -692,8 +692,28 @@ impl Channel for MatrixChannel {
             anyhow::bail!("Matrix room '{}' is not in joined state", target_room_id);
         }
 
-        room.send(RoomMessageEventContent::text_markdown(&message.content))
+        // Voice replies arrive as `[VOICE:<path>]` markers and are uploaded as audio.
+        let (text, audio_files) = super::tts::split_audio_markers(&message.content);
+        if !text.is_empty() || audio_files.is_empty() {
+            room.send(RoomMessageEventContent::text_markdown(&text))
+                .await?;
+        }
+
+        for path in &audio_files {
+            let data = tokio::fs::read(path).await?;
+            let content_type = mime_guess::from_path(path).first_or_octet_stream();
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("voice.ogg");
+            room.send_attachment(
+                file_name,
+                &content_type,
+                data,
+                matrix_sdk::attachment::AttachmentConfig::new(),
+            )
             .await?;
+        }
 
         Ok(())
     }
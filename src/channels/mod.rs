@@ -32,11 +32,13 @@ pub mod mattermost;
 pub mod nextcloud_talk;
 pub mod nostr;
 pub mod qq;
+pub mod sessions;
 pub mod signal;
 pub mod slack;
 pub mod telegram;
 pub mod traits;
 pub mod transcription;
+pub mod tts;
 pub mod wati;
 pub mod whatsapp;
 #[cfg(feature = "whatsapp-web")]
@@ -69,10 +71,17 @@ pub use whatsapp::WhatsAppChannel;
 #[cfg(feature = "whatsapp-web")]
 pub use whatsapp_web::WhatsAppWebChannel;
 
+use crate::agent::branching;
+use crate::agent::checkpoint::{
+    scope_turn_checkpoint, InterruptedTurn, TurnCheckpoint, TurnCheckpointStore, TurnOrigin,
+};
+use crate::agent::context_budget::{self, ContextBudget};
 use crate::agent::loop_::{
     build_shell_policy_instructions, build_tool_instructions_from_specs,
-    run_tool_call_loop_with_non_cli_approval_context, scrub_credentials, NonCliApprovalContext,
+    run_tool_call_loop_with_non_cli_approval_context, scope_context_budget,
+    scope_context_escalations, scope_tool_output_spill, scrub_credentials, NonCliApprovalContext,
 };
+use crate::agent::tool_retrieval::{scope_tool_retrieval, ToolRetriever};
 use crate::approval::{ApprovalManager, ApprovalResponse, PendingApprovalError};
 use crate::config::{Config, NonCliNaturalLanguageApprovalMode};
 use crate::identity;
@@ -81,6 +90,8 @@ use crate::observability::{self, runtime_trace, Observer};
 use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
 use crate::security::{LeakDetector, LeakResult, SecurityPolicy};
+use crate::tools::artifact_read::{self, ToolOutputSpill};
+use crate::tools::ask_user::{self, scope_ask_user_context, AskUserContext};
 use crate::tools::{self, Tool};
 use crate::util::truncate_with_ellipsis;
 use anyhow::{Context, Result};
@@ -96,8 +107,9 @@ use tokio_util::sync::CancellationToken;
 
 /// Per-sender conversation history for channel messages.
 type ConversationHistoryMap = Arc<Mutex<HashMap<String, Vec<ChatMessage>>>>;
-/// Maximum history messages to keep per sender.
-const MAX_CHANNEL_HISTORY: usize = 50;
+/// Hard cap on history messages kept per sender. Within the cap, history is
+/// trimmed against the active model's context window (see `append_sender_turn`).
+const MAX_CHANNEL_HISTORY: usize = 200;
 /// Minimum user-message length (in chars) for auto-save to memory.
 /// Messages shorter than this (e.g. "ok", "thanks") are not stored,
 /// reducing noise in memory recall.
@@ -126,6 +138,7 @@ const MEMORY_CONTEXT_ENTRY_MAX_CHARS: usize = 800;
 const MEMORY_CONTEXT_MAX_CHARS: usize = 4_000;
 const CHANNEL_HISTORY_COMPACT_KEEP_MESSAGES: usize = 12;
 const CHANNEL_HISTORY_COMPACT_CONTENT_CHARS: usize = 600;
+const CHANNEL_SESSIONS_LIST_LIMIT: usize = 10;
 /// Guardrail for hook-modified outbound channel content.
 const CHANNEL_HOOK_MAX_OUTBOUND_CHARS: usize = 20_000;
 
@@ -188,6 +201,11 @@ enum ChannelRuntimeCommand {
     ShowModel,
     SetModel(String),
     NewSession,
+    ListSessions,
+    ResumeSession(String),
+    UndoExchange,
+    RetryExchange(String),
+    ForkSession(String),
     RequestAllToolsOnce,
     RequestToolApproval(String),
     ConfirmToolApproval(String),
@@ -286,6 +304,11 @@ struct ChannelRuntimeContext {
     non_cli_excluded_tools: Arc<Mutex<Vec<String>>>,
     query_classification: crate::config::QueryClassificationConfig,
     model_routes: Vec<crate::config::ModelRouteConfig>,
+    agent_config: crate::config::AgentConfig,
+    tts: crate::config::TtsConfig,
+    sessions: Option<Arc<sessions::ChannelSessionStore>>,
+    checkpoints: Option<Arc<TurnCheckpointStore>>,
+    tool_retriever: Option<Arc<ToolRetriever>>,
     approval_manager: Arc<ApprovalManager>,
 }
 
@@ -736,6 +759,11 @@ fn parse_runtime_command(channel_name: &str, content: &str) -> Option<ChannelRun
     match base_command.as_str() {
         // History reset commands are safe for all channels.
         "/new" | "/clear" => Some(ChannelRuntimeCommand::NewSession),
+        "/sessions" => Some(ChannelRuntimeCommand::ListSessions),
+        "/resume" => Some(ChannelRuntimeCommand::ResumeSession(tail)),
+        "/undo" => Some(ChannelRuntimeCommand::UndoExchange),
+        "/retry" => Some(ChannelRuntimeCommand::RetryExchange(tail)),
+        "/fork" => Some(ChannelRuntimeCommand::ForkSession(tail)),
         "/approve-all-once" => Some(ChannelRuntimeCommand::RequestAllToolsOnce),
         "/approve-request" => Some(ChannelRuntimeCommand::RequestToolApproval(tail)),
         "/approve-confirm" => Some(ChannelRuntimeCommand::ConfirmToolApproval(tail)),
@@ -1023,6 +1051,7 @@ fn build_runtime_tool_visibility_prompt(
     tools_registry: &[Box<dyn Tool>],
     excluded_tools: &[String],
     native_tools: bool,
+    tool_retrieval: bool,
 ) -> String {
     let mut prompt = String::new();
     let mut specs = filtered_tool_specs_for_runtime(tools_registry, excluded_tools);
@@ -1061,6 +1090,12 @@ fn build_runtime_tool_visibility_prompt(
             "Tool calling for this turn uses native provider function-calling. \
              Do not emit `<tool_call>` XML tags.\n",
         );
+    } else if tool_retrieval {
+        prompt.push_str(
+            "Tool calling for this turn uses the XML tool protocol. Only the tools \
+             selected for this turn are described at the end of this prompt; \
+             call `tool_search` to find others.\n",
+        );
     } else {
         prompt.push_str(
             "Tool calling for this turn uses XML tool protocol below. \
@@ -1620,11 +1655,88 @@ fn set_route_selection(ctx: &ChannelRuntimeContext, sender_key: &str, next: Chan
     }
 }
 
-fn clear_sender_history(ctx: &ChannelRuntimeContext, sender_key: &str) {
+/// Archives the sender's session (when persistence is enabled) and drops the
+/// in-memory history. Returns the freshly started session, if any.
+fn clear_sender_history(
+    ctx: &ChannelRuntimeContext,
+    sender_key: &str,
+) -> Option<sessions::ChannelSession> {
     ctx.conversation_histories
         .lock()
         .unwrap_or_else(|e| e.into_inner())
         .remove(sender_key);
+
+    let store = ctx.sessions.as_ref()?;
+    match store.start_new(sender_key, &session_channel_name(ctx, sender_key)) {
+        Ok(session) => Some(session),
+        Err(err) => {
+            tracing::warn!("Failed to start new channel session for {sender_key}: {err}");
+            None
+        }
+    }
+}
+
+/// Channel that owns a history key (`<channel>_<...>`). Prefers the longest
+/// registered channel name so `whatsapp_web_*` is not attributed to `whatsapp`.
+fn session_channel_name(ctx: &ChannelRuntimeContext, sender_key: &str) -> String {
+    ctx.channels_by_name
+        .keys()
+        .filter(|name| {
+            sender_key
+                .strip_prefix(name.as_str())
+                .is_some_and(|rest| rest.starts_with('_'))
+        })
+        .max_by_key(|name| name.len())
+        .cloned()
+        .unwrap_or_else(|| {
+            sender_key
+                .split_once('_')
+                .map_or(sender_key, |(channel, _)| channel)
+                .to_string()
+        })
+}
+
+/// Mirror the sender's in-memory history into the session store.
+fn persist_sender_history(ctx: &ChannelRuntimeContext, sender_key: &str) {
+    let Some(store) = ctx.sessions.as_ref() else {
+        return;
+    };
+    let snapshot = ctx
+        .conversation_histories
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(sender_key)
+        .cloned()
+        .unwrap_or_default();
+    if let Err(err) = store.save_active(
+        sender_key,
+        &session_channel_name(ctx, sender_key),
+        &snapshot,
+    ) {
+        tracing::warn!("Failed to persist channel session for {sender_key}: {err}");
+    }
+}
+
+/// Archive the sender's session if it went idle, so the next message starts
+/// fresh. Returns true when the in-memory history was dropped.
+fn expire_idle_sender_session(ctx: &ChannelRuntimeContext, sender_key: &str) -> bool {
+    let Some(store) = ctx.sessions.as_ref() else {
+        return false;
+    };
+    match store.expire_if_idle(sender_key) {
+        Ok(true) => {
+            ctx.conversation_histories
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(sender_key);
+            true
+        }
+        Ok(false) => false,
+        Err(err) => {
+            tracing::warn!("Failed to check channel session idle timeout for {sender_key}: {err}");
+            false
+        }
+    }
 }
 
 fn compact_sender_history(ctx: &ChannelRuntimeContext, sender_key: &str) -> bool {
@@ -1663,15 +1775,34 @@ fn compact_sender_history(ctx: &ChannelRuntimeContext, sender_key: &str) -> bool
 }
 
 fn append_sender_turn(ctx: &ChannelRuntimeContext, sender_key: &str, turn: ChatMessage) {
-    let mut histories = ctx
-        .conversation_histories
-        .lock()
-        .unwrap_or_else(|e| e.into_inner());
-    let turns = histories.entry(sender_key.to_string()).or_default();
-    turns.push(turn);
-    while turns.len() > MAX_CHANNEL_HISTORY {
-        turns.remove(0);
+    // Keep stored history within the active model's prompt budget so
+    // large-context models retain more turns than small local ones.
+    let route = get_route_selection(ctx, sender_key);
+    let token_budget = ContextBudget::for_provider_model(
+        &ctx.agent_config,
+        &ctx.provider_runtime_options.ollama,
+        &route.provider,
+        &route.model,
+    )
+    .input_limit();
+
+    {
+        let mut histories = ctx
+            .conversation_histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let turns = histories.entry(sender_key.to_string()).or_default();
+        turns.push(turn);
+        while turns.len() > MAX_CHANNEL_HISTORY {
+            turns.remove(0);
+        }
+
+        let mut tokens = context_budget::estimate_messages_tokens(turns);
+        while tokens > token_budget && turns.len() > 1 {
+            tokens -= context_budget::estimate_message_tokens(&turns.remove(0));
+        }
     }
+    persist_sender_history(ctx, sender_key);
 }
 
 fn rollback_orphan_user_turn(
@@ -1714,19 +1845,7 @@ fn should_skip_memory_context_entry(key: &str, content: &str) -> bool {
 }
 
 fn is_context_window_overflow_error(err: &anyhow::Error) -> bool {
-    let lower = err.to_string().to_lowercase();
-    [
-        "exceeds the context window",
-        "context window of this model",
-        "maximum context length",
-        "context length exceeded",
-        "too many tokens",
-        "token limit exceeded",
-        "prompt is too long",
-        "input is too long",
-    ]
-    .iter()
-    .any(|hint| lower.contains(hint))
+    providers::reliable::is_context_window_exceeded(err)
 }
 
 fn is_tool_iteration_limit_error(err: &anyhow::Error) -> bool {
@@ -1904,18 +2023,179 @@ fn build_providers_help_response(current: &ChannelRouteSelection) -> String {
     response
 }
 
+fn build_sessions_response(ctx: &ChannelRuntimeContext, sender_key: &str) -> String {
+    let Some(store) = ctx.sessions.as_ref() else {
+        return "Session persistence is disabled. Enable `[channels_config.sessions]` to keep past conversations.".to_string();
+    };
+    let sessions = match store.sessions_for_sender(sender_key, CHANNEL_SESSIONS_LIST_LIMIT) {
+        Ok(sessions) => sessions,
+        Err(err) => return format!("Failed to load sessions: {err}"),
+    };
+    if sessions.is_empty() {
+        return "No saved sessions yet.".to_string();
+    }
+
+    let mut response = String::from("Sessions (most recent first):\n");
+    for session in &sessions {
+        let title = if session.title.is_empty() {
+            "(empty)"
+        } else {
+            session.title.as_str()
+        };
+        let _ = writeln!(
+            response,
+            "- `{}`{} · {} messages · {} · {title}",
+            session.short_id(),
+            if session.active { " (current)" } else { "" },
+            session.message_count,
+            session
+                .last_activity
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+        );
+    }
+    response.push_str("\nResume one with `/resume <id>`; start another with `/new`.");
+    response
+}
+
+fn resume_sender_session(ctx: &ChannelRuntimeContext, sender_key: &str, raw_id: &str) -> String {
+    let Some(store) = ctx.sessions.as_ref() else {
+        return "Session persistence is disabled. Enable `[channels_config.sessions]` to keep past conversations.".to_string();
+    };
+    let id = raw_id.trim().trim_matches('`');
+    if id.is_empty() {
+        return "Usage: `/resume <session-id>`. Use `/sessions` to list session IDs.".to_string();
+    }
+
+    match store.resume(sender_key, id) {
+        Ok(sessions::ResumeOutcome::Resumed(session, history)) => {
+            let mut histories = ctx
+                .conversation_histories
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if history.is_empty() {
+                histories.remove(sender_key);
+            } else {
+                histories.insert(sender_key.to_string(), history);
+            }
+            format!(
+                "Resumed session `{}` ({} messages).",
+                session.short_id(),
+                session.message_count
+            )
+        }
+        Ok(sessions::ResumeOutcome::NotFound) => {
+            format!("No session matching `{id}`. Use `/sessions` to list session IDs.")
+        }
+        Ok(sessions::ResumeOutcome::Ambiguous(matches)) => {
+            let ids = matches
+                .iter()
+                .map(|session| format!("`{}`", session.id))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("`{id}` matches several sessions: {ids}. Use a longer prefix.")
+        }
+        Err(err) => format!("Failed to resume session: {err}"),
+    }
+}
+
+/// Drop the sender's last exchange and return the message that started it.
+fn undo_sender_exchange(ctx: &ChannelRuntimeContext, sender_key: &str) -> Option<String> {
+    let input = {
+        let mut histories = ctx
+            .conversation_histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let turns = histories.get_mut(sender_key)?;
+        let input = branching::undo_last_exchange(turns)?;
+        if turns.is_empty() {
+            histories.remove(sender_key);
+        }
+        input
+    };
+    persist_sender_history(ctx, sender_key);
+    Some(input)
+}
+
+/// Archive the sender's session and continue in a new one seeded with its
+/// first `raw_keep` exchanges (all of them when empty).
+fn fork_sender_session(ctx: &ChannelRuntimeContext, sender_key: &str, raw_keep: &str) -> String {
+    let Some(store) = ctx.sessions.as_ref() else {
+        return "Forking needs session persistence. Enable `[channels_config.sessions]` to keep branches.".to_string();
+    };
+    let raw_keep = raw_keep.trim();
+    let keep = if raw_keep.is_empty() {
+        None
+    } else if let Ok(keep) = raw_keep.parse::<usize>() {
+        Some(keep)
+    } else {
+        return "Usage: `/fork [number-of-exchanges-to-keep]`".to_string();
+    };
+
+    let mut turns = ctx
+        .conversation_histories
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(sender_key)
+        .cloned()
+        .unwrap_or_default();
+    let exchanges = branching::exchange_starts(&turns).len();
+    let keep = keep.unwrap_or(exchanges);
+    if !branching::truncate_to_exchanges(&mut turns, keep) {
+        return format!("This conversation has only {exchanges} exchange(s).");
+    }
+
+    // Make sure the branch being left is saved in full before archiving it.
+    persist_sender_history(ctx, sender_key);
+    let session = match store.start_new(sender_key, &session_channel_name(ctx, sender_key)) {
+        Ok(session) => session,
+        Err(err) => return format!("Failed to fork session: {err}"),
+    };
+    {
+        let mut histories = ctx
+            .conversation_histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if turns.is_empty() {
+            histories.remove(sender_key);
+        } else {
+            histories.insert(sender_key.to_string(), turns);
+        }
+    }
+    persist_sender_history(ctx, sender_key);
+
+    format!(
+        "Forked into session `{}` keeping {keep} exchange(s). The previous branch is still listed in `/sessions`; use `/resume <id>` to switch back.",
+        session.short_id()
+    )
+}
+
+/// What the runtime-command pre-pass decided about an inbound message.
+#[derive(Debug, PartialEq, Eq)]
+enum RuntimeCommandOutcome {
+    /// Not a runtime command; process normally.
+    NotACommand,
+    /// Command handled (and answered); stop here.
+    Handled,
+    /// `/retry`: process `content` as the user's turn, optionally with `model`.
+    Retry {
+        content: String,
+        model: Option<String>,
+    },
+}
+
 async fn handle_runtime_command_if_needed(
     ctx: &ChannelRuntimeContext,
     msg: &traits::ChannelMessage,
     target_channel: Option<&Arc<dyn Channel>>,
-) -> bool {
+) -> RuntimeCommandOutcome {
     let is_slash_command = msg.content.trim_start().starts_with('/');
     let Some(mut command) = parse_runtime_command(&msg.channel, &msg.content) else {
-        return false;
+        return RuntimeCommandOutcome::NotACommand;
     };
 
     let Some(channel) = target_channel else {
-        return true;
+        return RuntimeCommandOutcome::Handled;
     };
 
     let sender_key = conversation_history_key(msg);
@@ -1969,7 +2249,7 @@ async fn handle_runtime_command_if_needed(
                 channel.name()
             );
         }
-        return true;
+        return RuntimeCommandOutcome::Handled;
     }
 
     if is_natural_language_approval_command {
@@ -2005,7 +2285,7 @@ async fn handle_runtime_command_if_needed(
                         channel.name()
                     );
                 }
-                return true;
+                return RuntimeCommandOutcome::Handled;
             }
             NonCliNaturalLanguageApprovalMode::RequestConfirm => {}
             NonCliNaturalLanguageApprovalMode::Direct => {
@@ -2077,9 +2357,38 @@ async fn handle_runtime_command_if_needed(
                 )
             }
         }
-        ChannelRuntimeCommand::NewSession => {
-            clear_sender_history(ctx, &sender_key);
-            "Conversation history cleared. Starting fresh.".to_string()
+        ChannelRuntimeCommand::NewSession => match clear_sender_history(ctx, &sender_key) {
+            Some(session) => format!(
+                "Started new session `{}`. The previous conversation was saved; use `/sessions` to list and `/resume <id>` to return to it.",
+                session.short_id()
+            ),
+            None => "Conversation history cleared. Starting fresh.".to_string(),
+        },
+        ChannelRuntimeCommand::ListSessions => build_sessions_response(ctx, &sender_key),
+        ChannelRuntimeCommand::ResumeSession(raw_id) => {
+            resume_sender_session(ctx, &sender_key, &raw_id)
+        }
+        ChannelRuntimeCommand::UndoExchange => match undo_sender_exchange(ctx, &sender_key) {
+            Some(input) => format!(
+                "Removed the last exchange (\"{}\").",
+                truncate_with_ellipsis(&input, 60)
+            ),
+            None => "Nothing to undo.".to_string(),
+        },
+        ChannelRuntimeCommand::RetryExchange(raw_model) => {
+            match undo_sender_exchange(ctx, &sender_key) {
+                Some(content) if !content.is_empty() => {
+                    let model = raw_model.trim().trim_matches('`');
+                    return RuntimeCommandOutcome::Retry {
+                        content,
+                        model: (!model.is_empty()).then(|| model.to_string()),
+                    };
+                }
+                _ => "Nothing to retry.".to_string(),
+            }
+        }
+        ChannelRuntimeCommand::ForkSession(raw_keep) => {
+            fork_sender_session(ctx, &sender_key, &raw_keep)
         }
         ChannelRuntimeCommand::RequestAllToolsOnce => {
             let req = ctx.approval_manager.create_non_cli_pending_request(
@@ -2485,7 +2794,7 @@ async fn handle_runtime_command_if_needed(
         );
     }
 
-    true
+    RuntimeCommandOutcome::Handled
 }
 
 async fn build_memory_context(
@@ -2961,7 +3270,7 @@ async fn process_channel_message(
     );
 
     // ── Hook: on_message_received (modifying) ────────────
-    let msg = if let Some(hooks) = &ctx.hooks {
+    let mut msg = if let Some(hooks) = &ctx.hooks {
         match hooks.run_on_message_received(msg).await {
             crate::hooks::HookResult::Cancel(reason) => {
                 tracing::info!(%reason, "incoming message dropped by hook");
@@ -2977,8 +3286,20 @@ async fn process_channel_message(
     if let Err(err) = maybe_apply_runtime_config_update(ctx.as_ref()).await {
         tracing::warn!("Failed to apply runtime config update: {err}");
     }
-    if handle_runtime_command_if_needed(ctx.as_ref(), &msg, target_channel.as_ref()).await {
-        return;
+    let mut retry_model = None;
+    match Box::pin(handle_runtime_command_if_needed(
+        ctx.as_ref(),
+        &msg,
+        target_channel.as_ref(),
+    ))
+    .await
+    {
+        RuntimeCommandOutcome::NotACommand => {}
+        RuntimeCommandOutcome::Handled => return,
+        RuntimeCommandOutcome::Retry { content, model } => {
+            msg.content = content;
+            retry_model = model;
+        }
     }
     if !msg.content.trim_start().starts_with('/') {
         let perplexity_cfg = runtime_perplexity_filter_snapshot(ctx.as_ref());
@@ -3028,8 +3349,12 @@ or tune thresholds in config.",
 
     let history_key = conversation_history_key(&msg);
     // Try classification first, fall back to sender/default route
-    let route = classify_message_route(ctx.as_ref(), &msg.content)
+    let mut route = classify_message_route(ctx.as_ref(), &msg.content)
         .unwrap_or_else(|| get_route_selection(ctx.as_ref(), &history_key));
+    // `/retry <model>` answers this one turn with another model.
+    if let Some(model) = retry_model {
+        route.model = model;
+    }
     let runtime_defaults = runtime_defaults_snapshot(ctx.as_ref());
     let active_provider = match get_or_create_provider(ctx.as_ref(), &route.provider).await {
         Ok(provider) => provider,
@@ -3066,6 +3391,8 @@ or tune thresholds in config.",
     println!("  ⏳ Processing message...");
     let started_at = Instant::now();
 
+    expire_idle_sender_session(ctx.as_ref(), &history_key);
+
     let had_prior_history = ctx
         .conversation_histories
         .lock()
@@ -3120,10 +3447,14 @@ or tune thresholds in config.",
         &msg.reply_target,
         expose_internal_tool_details,
     );
+    if let Some(voice_hint) = tts::voice_prompt_hint(&ctx.tts, &msg.channel) {
+        system_prompt.push_str(voice_hint);
+    }
     system_prompt.push_str(&build_runtime_tool_visibility_prompt(
         ctx.tools_registry.as_ref(),
         &excluded_tools_snapshot,
         active_provider.supports_native_tools(),
+        ctx.tool_retriever.is_some(),
     ));
     let mut history = vec![ChatMessage::system(system_prompt)];
     history.extend(prior_turns);
@@ -3231,36 +3562,19 @@ or tune thresholds in config.",
 
     let timeout_budget_secs =
         channel_message_timeout_budget_secs(ctx.message_timeout_secs, ctx.max_tool_iterations);
-    let (approval_prompt_tx, mut approval_prompt_rx) =
+    let (approval_prompt_tx, approval_prompt_rx) =
         tokio::sync::mpsc::unbounded_channel::<crate::agent::loop_::NonCliApprovalPrompt>();
     let approval_prompt_task = if msg.channel == "cli" {
         None
-    } else if let Some(channel_ref) = target_channel.as_ref() {
-        let channel = Arc::clone(channel_ref);
-        let reply_target = msg.reply_target.clone();
-        let thread_ts = msg.thread_ts.clone();
-        Some(tokio::spawn(async move {
-            while let Some(prompt) = approval_prompt_rx.recv().await {
-                if let Err(err) = channel
-                    .send_approval_prompt(
-                        &reply_target,
-                        &prompt.request_id,
-                        &prompt.tool_name,
-                        &prompt.arguments,
-                        thread_ts.clone(),
-                    )
-                    .await
-                {
-                    tracing::warn!(
-                        channel = %channel.name(),
-                        request_id = %prompt.request_id,
-                        "Failed to send approval prompt: {err}"
-                    );
-                }
-            }
-        }))
     } else {
-        None
+        target_channel.as_ref().map(|channel| {
+            spawn_approval_prompt_forwarder(
+                Arc::clone(channel),
+                msg.reply_target.clone(),
+                msg.thread_ts.clone(),
+                approval_prompt_rx,
+            )
+        })
     };
     let non_cli_approval_context = if msg.channel == "cli" || target_channel.is_none() {
         None
@@ -3271,33 +3585,102 @@ or tune thresholds in config.",
             prompt_tx: approval_prompt_tx.clone(),
         })
     };
+    let ask_user_context = target_channel
+        .as_ref()
+        .filter(|_| msg.channel != "cli")
+        .map(|channel| AskUserContext::Channel {
+            channel: Arc::clone(channel),
+            reply_target: msg.reply_target.clone(),
+            thread_ts: msg.thread_ts.clone(),
+            scope_key: interruption_scope_key(&msg),
+            deadline: Some(
+                tokio::time::Instant::now() + Duration::from_secs(timeout_budget_secs),
+            ),
+        });
+    let turn_checkpoint = ctx
+        .checkpoints
+        .as_ref()
+        .filter(|_| msg.channel != "cli")
+        .and_then(|store| {
+            let origin = TurnOrigin {
+                channel: msg.channel.clone(),
+                sender: msg.sender.clone(),
+                reply_target: msg.reply_target.clone(),
+                thread_ts: msg.thread_ts.clone(),
+                history_key: history_key.clone(),
+                provider: route.provider.clone(),
+                model: route.model.clone(),
+                message: msg.content.clone(),
+            };
+            TurnCheckpoint::begin(Arc::clone(store), &origin, &history)
+                .map_err(|err| tracing::warn!("Failed to checkpoint channel turn: {err}"))
+                .ok()
+        });
+
+    let ollama = &ctx.provider_runtime_options.ollama;
+    let budget =
+        ContextBudget::for_provider_model(&ctx.agent_config, ollama, &route.provider, &route.model);
+    // Channel providers are per-provider, not routed: only escalate to
+    // larger-context models served by the same provider.
+    let context_escalations = context_budget::escalation_candidates(
+        &ctx.agent_config,
+        ollama,
+        &ctx.model_routes,
+        &route.provider,
+        budget,
+        false,
+    );
 
     let llm_result = tokio::select! {
         () = cancellation_token.cancelled() => LlmExecutionResult::Cancelled,
         result = tokio::time::timeout(
             Duration::from_secs(timeout_budget_secs),
-            run_tool_call_loop_with_non_cli_approval_context(
-                active_provider.as_ref(),
-                &mut history,
-                ctx.tools_registry.as_ref(),
-                ctx.observer.as_ref(),
-                route.provider.as_str(),
-                route.model.as_str(),
-                runtime_defaults.temperature,
-                true,
-                Some(ctx.approval_manager.as_ref()),
-                msg.channel.as_str(),
-                non_cli_approval_context,
-                &ctx.multimodal,
-                ctx.max_tool_iterations,
-                Some(cancellation_token.clone()),
-                delta_tx,
-                ctx.hooks.as_deref(),
-                &excluded_tools_snapshot,
+            scope_context_escalations(
+                context_escalations,
+                scope_context_budget(
+                    budget,
+                    scope_tool_output_spill(
+                        ToolOutputSpill::from_config(&ctx.agent_config, ctx.workspace_dir.as_path()),
+                        scope_turn_checkpoint(
+                            turn_checkpoint.clone(),
+                            scope_tool_retrieval(
+                                ctx.tool_retriever.clone(),
+                                scope_ask_user_context(
+                                    ask_user_context,
+                                    run_tool_call_loop_with_non_cli_approval_context(
+                                        active_provider.as_ref(),
+                                        &mut history,
+                                        ctx.tools_registry.as_ref(),
+                                        ctx.observer.as_ref(),
+                                        route.provider.as_str(),
+                                        route.model.as_str(),
+                                        runtime_defaults.temperature,
+                                        true,
+                                        Some(ctx.approval_manager.as_ref()),
+                                        msg.channel.as_str(),
+                                        non_cli_approval_context,
+                                        &ctx.multimodal,
+                                        ctx.max_tool_iterations,
+                                        Some(cancellation_token.clone()),
+                                        delta_tx,
+                                        ctx.hooks.as_deref(),
+                                        &excluded_tools_snapshot,
+                                    ),
+                                ),
+                            ),
+                        ),
+                    ),
+                ),
             ),
         ) => LlmExecutionResult::Completed(result),
     };
 
+    // The turn has an outcome now (reply, error, cancellation or timeout),
+    // so there is nothing left to resume after a restart.
+    if let Some(checkpoint) = turn_checkpoint.as_ref() {
+        checkpoint.finish();
+    }
+
     drop(approval_prompt_tx);
     if let Some(handle) = approval_prompt_task {
         log_worker_join_result(handle.await);
@@ -3406,6 +3789,10 @@ or tune thresholds in config.",
                 }
             }
 
+            let outbound_response = artifact_read::resolve_artifact_markers(
+                &outbound_response,
+                ctx.workspace_dir.as_path(),
+            );
             let sanitized_response =
                 sanitize_channel_response(&outbound_response, ctx.tools_registry.as_ref());
             let delivered_response = if sanitized_response.is_empty()
@@ -3415,6 +3802,15 @@ or tune thresholds in config.",
             } else {
                 sanitized_response
             };
+            let voice_reply = tts::prepare_voice_reply(
+                &ctx.tts,
+                &msg.channel,
+                &msg.content,
+                &delivered_response,
+                ctx.workspace_dir.as_path(),
+            )
+            .await;
+            let delivered_response = voice_reply.text;
             runtime_trace::record_event(
                 "channel_message_outbound",
                 Some(msg.channel.as_str()),
@@ -3464,14 +3860,34 @@ or tune thresholds in config.",
                             )
                             .await;
                     }
-                } else if let Err(e) = channel
-                    .send(
-                        &SendMessage::new(delivered_response, &msg.reply_target)
-                            .in_thread(msg.thread_ts.clone()),
-                    )
-                    .await
-                {
-                    eprintln!("  ❌ Failed to reply on {}: {e}", channel.name());
+                    if let Some(marker) = voice_reply.marker.as_deref() {
+                        if let Err(e) = channel
+                            .send(
+                                &SendMessage::new(marker, &msg.reply_target)
+                                    .in_thread(msg.thread_ts.clone()),
+                            )
+                            .await
+                        {
+                            tracing::warn!("Failed to send voice reply: {e}");
+                        }
+                    }
+                } else {
+                    let outbound = match voice_reply.marker.as_deref() {
+                        Some(marker) if !ctx.tts.include_text || delivered_response.is_empty() => {
+                            marker.to_string()
+                        }
+                        Some(marker) => format!("{delivered_response}\n{marker}"),
+                        None => delivered_response,
+                    };
+                    if let Err(e) = channel
+                        .send(
+                            &SendMessage::new(outbound, &msg.reply_target)
+                                .in_thread(msg.thread_ts.clone()),
+                        )
+                        .await
+                    {
+                        eprintln!("  ❌ Failed to reply on {}: {e}", channel.name());
+                    }
                 }
             }
         }
@@ -3505,6 +3921,9 @@ or tune thresholds in config.",
                 }
             } else if is_context_window_overflow_error(&e) {
                 let compacted = compact_sender_history(ctx.as_ref(), &history_key);
+                if compacted {
+                    persist_sender_history(ctx.as_ref(), &history_key);
+                }
                 let error_text = if compacted {
                     "⚠️ Context window exceeded for this conversation. I compacted recent history and kept the latest context. Please resend your last message."
                 } else {
@@ -3608,7 +4027,9 @@ or tune thresholds in config.",
                 let rolled_back = should_rollback_user_turn
                     && rollback_orphan_user_turn(ctx.as_ref(), &history_key, &timestamped_content);
 
-                if !rolled_back {
+                if rolled_back {
+                    persist_sender_history(ctx.as_ref(), &history_key);
+                } else {
                     // Close the orphan user turn so subsequent messages don't
                     // inherit this failed request as unfinished context.
                     append_sender_turn(
@@ -3693,59 +4114,291 @@ or tune thresholds in config.",
     }
 }
 
-async fn run_message_dispatch_loop(
-    mut rx: tokio::sync::mpsc::Receiver<traits::ChannelMessage>,
+/// Resume channel turns the previous process stopped in the middle of, or
+/// tell their senders they were lost, so no request ends in silence.
+async fn resume_interrupted_turns(
     ctx: Arc<ChannelRuntimeContext>,
-    max_in_flight_messages: usize,
+    turns: Vec<InterruptedTurn>,
+    config: crate::config::ChannelCheckpointsConfig,
 ) {
-    let semaphore = Arc::new(tokio::sync::Semaphore::new(max_in_flight_messages));
-    let mut workers = tokio::task::JoinSet::new();
-    let in_flight_by_sender = Arc::new(tokio::sync::Mutex::new(HashMap::<
-        String,
-        InFlightSenderTaskState,
-    >::new()));
-    let task_sequence = Arc::new(AtomicU64::new(1));
+    let Some(store) = ctx.checkpoints.clone() else {
+        return;
+    };
+    let max_age = chrono::Duration::minutes(
+        i64::try_from(config.max_age_minutes).unwrap_or(i64::MAX / 60_000),
+    );
 
-    while let Some(msg) = rx.recv().await {
-        let permit = match Arc::clone(&semaphore).acquire_owned().await {
-            Ok(permit) => permit,
-            Err(_) => break,
+    for turn in turns {
+        let Some(channel) = ctx.channels_by_name.get(&turn.origin.channel).cloned() else {
+            tracing::warn!(
+                channel = %turn.origin.channel,
+                sender = %turn.origin.sender,
+                "Dropping interrupted turn for a channel that is no longer configured"
+            );
+            if let Err(err) = store.finish(&turn.id) {
+                tracing::warn!("Failed to clear turn checkpoint: {err}");
+            }
+            continue;
         };
 
-        let worker_ctx = Arc::clone(&ctx);
-        let in_flight = Arc::clone(&in_flight_by_sender);
-        let task_sequence = Arc::clone(&task_sequence);
-        workers.spawn(async move {
-            let _permit = permit;
-            let interrupt_enabled =
-                worker_ctx.interrupt_on_new_message && msg.channel == "telegram";
-            let sender_scope_key = interruption_scope_key(&msg);
-            let cancellation_token = CancellationToken::new();
-            let completion = Arc::new(InFlightTaskCompletion::new());
-            let task_id = task_sequence.fetch_add(1, Ordering::Relaxed);
-
-            if interrupt_enabled {
-                let previous = {
-                    let mut active = in_flight.lock().await;
-                    active.insert(
-                        sender_scope_key.clone(),
-                        InFlightSenderTaskState {
-                            task_id,
-                            cancellation: cancellation_token.clone(),
-                            completion: Arc::clone(&completion),
-                        },
-                    )
-                };
-
-                if let Some(previous) = previous {
-                    tracing::info!(
-                        channel = %msg.channel,
-                        sender = %msg.sender,
-                        "Interrupting previous in-flight request for sender"
-                    );
-                    previous.cancellation.cancel();
-                    previous.completion.wait().await;
-                }
+        let request = truncate_with_ellipsis(turn.origin.message.trim(), 80);
+        let resumable = turn.attempts < config.max_resume_attempts
+            && chrono::Utc::now() - turn.updated_at <= max_age
+            && !turn.history.is_empty();
+        if !resumable {
+            tracing::info!(
+                channel = %turn.origin.channel,
+                sender = %turn.origin.sender,
+                attempts = turn.attempts,
+                "Reporting interrupted turn as failed"
+            );
+            send_turn_notice(
+                channel.as_ref(),
+                &turn,
+                &format!(
+                    "⚠️ Your request \"{request}\" was interrupted by a restart and could not be resumed. Please send it again."
+                ),
+            )
+            .await;
+            if let Err(err) = store.finish(&turn.id) {
+                tracing::warn!("Failed to clear turn checkpoint: {err}");
+            }
+            continue;
+        }
+
+        // Count the attempt before running, so a turn that crashes the
+        // process again is reported instead of resumed forever.
+        if let Err(err) = store.mark_resumed(&turn.id) {
+            tracing::warn!("Failed to update turn checkpoint: {err}");
+        }
+        tracing::info!(
+            channel = %turn.origin.channel,
+            sender = %turn.origin.sender,
+            iteration = turn.iteration,
+            "Resuming interrupted turn"
+        );
+        send_turn_notice(
+            channel.as_ref(),
+            &turn,
+            &format!("♻️ Resuming your request \"{request}\" after a restart…"),
+        )
+        .await;
+        tokio::spawn(resume_interrupted_turn(
+            Arc::clone(&ctx),
+            Arc::clone(&store),
+            channel,
+            turn,
+        ));
+    }
+}
+
+async fn resume_interrupted_turn(
+    ctx: Arc<ChannelRuntimeContext>,
+    store: Arc<TurnCheckpointStore>,
+    channel: Arc<dyn Channel>,
+    turn: InterruptedTurn,
+) {
+    let checkpoint = TurnCheckpoint::resume(store, &turn);
+    let mut history = turn.history.clone();
+    history.push(ChatMessage::user(turn.resume_prompt()));
+    let history_len_before_tools = history.len();
+
+    // Supervised tools ask the original sender for approval, as they would
+    // have before the restart.
+    let (approval_prompt_tx, approval_prompt_rx) =
+        tokio::sync::mpsc::unbounded_channel::<crate::agent::loop_::NonCliApprovalPrompt>();
+    let approval_prompt_task = spawn_approval_prompt_forwarder(
+        Arc::clone(&channel),
+        turn.origin.reply_target.clone(),
+        turn.origin.thread_ts.clone(),
+        approval_prompt_rx,
+    );
+    let non_cli_approval_context = NonCliApprovalContext {
+        sender: turn.origin.sender.clone(),
+        reply_target: turn.origin.reply_target.clone(),
+        prompt_tx: approval_prompt_tx,
+    };
+
+    let result = match get_or_create_provider(ctx.as_ref(), &turn.origin.provider).await {
+        Ok(provider) => {
+            let timeout_budget_secs = channel_message_timeout_budget_secs(
+                ctx.message_timeout_secs,
+                ctx.max_tool_iterations,
+            );
+            let excluded_tools = snapshot_non_cli_excluded_tools(ctx.as_ref());
+            Box::pin(tokio::time::timeout(
+                Duration::from_secs(timeout_budget_secs),
+                scope_tool_output_spill(
+                    ToolOutputSpill::from_config(&ctx.agent_config, ctx.workspace_dir.as_path()),
+                    scope_turn_checkpoint(
+                        Some(Arc::clone(&checkpoint)),
+                        scope_tool_retrieval(
+                            ctx.tool_retriever.clone(),
+                            run_tool_call_loop_with_non_cli_approval_context(
+                                provider.as_ref(),
+                                &mut history,
+                                ctx.tools_registry.as_ref(),
+                                ctx.observer.as_ref(),
+                                &turn.origin.provider,
+                                &turn.origin.model,
+                                runtime_defaults_snapshot(ctx.as_ref()).temperature,
+                                true,
+                                Some(ctx.approval_manager.as_ref()),
+                                &turn.origin.channel,
+                                Some(non_cli_approval_context),
+                                &ctx.multimodal,
+                                ctx.max_tool_iterations,
+                                None,
+                                None,
+                                ctx.hooks.as_deref(),
+                                &excluded_tools,
+                            ),
+                        ),
+                    ),
+                ),
+            ))
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("timed out after {timeout_budget_secs}s")))
+        }
+        Err(err) => Err(err),
+    };
+    checkpoint.finish();
+    log_worker_join_result(approval_prompt_task.await);
+
+    let reply = match result {
+        Ok(response) => {
+            let response =
+                artifact_read::resolve_artifact_markers(&response, ctx.workspace_dir.as_path());
+            let delivered = sanitize_channel_response(&response, ctx.tools_registry.as_ref());
+            let tool_summary = extract_tool_context_summary(&history, history_len_before_tools);
+            let history_response = if tool_summary.is_empty() {
+                delivered.clone()
+            } else {
+                format!("{tool_summary}\n{delivered}")
+            };
+            append_sender_turn(
+                ctx.as_ref(),
+                &turn.origin.history_key,
+                ChatMessage::assistant(&history_response),
+            );
+            delivered
+        }
+        Err(err) => {
+            tracing::warn!(
+                channel = %turn.origin.channel,
+                sender = %turn.origin.sender,
+                "Resumed turn failed: {err}"
+            );
+            format!(
+                "⚠️ Resuming your interrupted request failed: {}. Please send it again.",
+                truncate_with_ellipsis(&scrub_credentials(&err.to_string()), 300)
+            )
+        }
+    };
+    send_turn_notice(channel.as_ref(), &turn, &reply).await;
+}
+
+/// Forward non-CLI approval prompts raised by the tool loop to `channel`.
+fn spawn_approval_prompt_forwarder(
+    channel: Arc<dyn Channel>,
+    reply_target: String,
+    thread_ts: Option<String>,
+    mut prompts: tokio::sync::mpsc::UnboundedReceiver<crate::agent::loop_::NonCliApprovalPrompt>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(prompt) = prompts.recv().await {
+            if let Err(err) = channel
+                .send_approval_prompt(
+                    &reply_target,
+                    &prompt.request_id,
+                    &prompt.tool_name,
+                    &prompt.arguments,
+                    thread_ts.clone(),
+                )
+                .await
+            {
+                tracing::warn!(
+                    channel = %channel.name(),
+                    request_id = %prompt.request_id,
+                    "Failed to send approval prompt: {err}"
+                );
+            }
+        }
+    })
+}
+
+async fn send_turn_notice(channel: &dyn Channel, turn: &InterruptedTurn, text: &str) {
+    let message =
+        SendMessage::new(text, &turn.origin.reply_target).in_thread(turn.origin.thread_ts.clone());
+    if let Err(err) = channel.send(&message).await {
+        tracing::warn!("Failed to send to {}: {err}", channel.name());
+    }
+}
+
+async fn run_message_dispatch_loop(
+    mut rx: tokio::sync::mpsc::Receiver<traits::ChannelMessage>,
+    ctx: Arc<ChannelRuntimeContext>,
+    max_in_flight_messages: usize,
+) {
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(max_in_flight_messages));
+    let mut workers = tokio::task::JoinSet::new();
+    let in_flight_by_sender = Arc::new(tokio::sync::Mutex::new(HashMap::<
+        String,
+        InFlightSenderTaskState,
+    >::new()));
+    let task_sequence = Arc::new(AtomicU64::new(1));
+
+    while let Some(msg) = rx.recv().await {
+        // A turn blocked on `ask_user` is waiting for this sender's reply;
+        // hand it over instead of starting (or interrupting with) a new turn.
+        if ask_user::answer_pending_question(&interruption_scope_key(&msg), &msg.content) {
+            tracing::info!(
+                channel = %msg.channel,
+                sender = %msg.sender,
+                "Delivered reply to pending ask_user question"
+            );
+            continue;
+        }
+
+        let permit = match Arc::clone(&semaphore).acquire_owned().await {
+            Ok(permit) => permit,
+            Err(_) => break,
+        };
+
+        let worker_ctx = Arc::clone(&ctx);
+        let in_flight = Arc::clone(&in_flight_by_sender);
+        let task_sequence = Arc::clone(&task_sequence);
+        workers.spawn(async move {
+            let _permit = permit;
+            let interrupt_enabled =
+                worker_ctx.interrupt_on_new_message && msg.channel == "telegram";
+            let sender_scope_key = interruption_scope_key(&msg);
+            let cancellation_token = CancellationToken::new();
+            let completion = Arc::new(InFlightTaskCompletion::new());
+            let task_id = task_sequence.fetch_add(1, Ordering::Relaxed);
+
+            if interrupt_enabled {
+                let previous = {
+                    let mut active = in_flight.lock().await;
+                    active.insert(
+                        sender_scope_key.clone(),
+                        InFlightSenderTaskState {
+                            task_id,
+                            cancellation: cancellation_token.clone(),
+                            completion: Arc::clone(&completion),
+                        },
+                    )
+                };
+
+                if let Some(previous) = previous {
+                    tracing::info!(
+                        channel = %msg.channel,
+                        sender = %msg.sender,
+                        "Interrupting previous in-flight request for sender"
+                    );
+                    previous.cancellation.cancel();
+                    previous.completion.wait().await;
+                }
             }
 
             process_channel_message(worker_ctx, msg, cancellation_token).await;
@@ -4730,6 +5383,8 @@ pub async fn start_channels(config: Config) -> Result<()> {
     clear_live_channels();
 
     let provider_name = resolved_default_provider(&config);
+    let observer: Arc<dyn Observer> =
+        Arc::from(observability::create_observer(&config.observability));
     let provider_runtime_options = providers::ProviderRuntimeOptions {
         auth_profile_override: None,
         provider_api_url: config.api_url.clone(),
@@ -4741,6 +5396,9 @@ pub async fn start_channels(config: Config) -> Result<()> {
         custom_provider_api_mode: config.provider_api.map(|mode| mode.as_compatible_mode()),
         max_tokens_override: None,
         model_support_vision: config.model_support_vision,
+        thinking_budget_tokens: config.effective_thinking_budget_tokens(),
+        ollama: config.ollama.clone(),
+        observer: Some(Arc::clone(&observer)),
     };
     let provider: Arc<dyn Provider> = Arc::from(
         create_resilient_provider_nonblocking(
@@ -4774,8 +5432,6 @@ pub async fn start_channels(config: Config) -> Result<()> {
         );
     }
 
-    let observer: Arc<dyn Observer> =
-        Arc::from(observability::create_observer(&config.observability));
     let runtime: Arc<dyn runtime::RuntimeAdapter> =
         Arc::from(runtime::create_runtime(&config.runtime)?);
     let security = Arc::new(SecurityPolicy::from_config(
@@ -4826,7 +5482,7 @@ pub async fn start_channels(config: Config) -> Result<()> {
             Ok(registry) => {
                 let registry = std::sync::Arc::new(registry);
                 let names = registry.tool_names();
-                let mut registered = 0usize;
+                let mut mcp_tools: Vec<Box<dyn Tool>> = Vec::new();
                 for name in names {
                     if let Some(def) = registry.get_tool_def(&name).await {
                         let wrapper = crate::tools::McpToolWrapper::new(
@@ -4834,10 +5490,14 @@ pub async fn start_channels(config: Config) -> Result<()> {
                             def,
                             std::sync::Arc::clone(&registry),
                         );
-                        built_tools.push(Box::new(wrapper));
-                        registered += 1;
+                        mcp_tools.push(Box::new(wrapper));
                     }
                 }
+                let registered = mcp_tools.len();
+                built_tools.extend(crate::tools::policy::apply_tool_policies(
+                    mcp_tools,
+                    &config.tools,
+                ));
                 tracing::info!(
                     "MCP: {} tool(s) registered from {} server(s)",
                     registered,
@@ -4949,7 +5609,9 @@ pub async fn start_channels(config: Config) -> Result<()> {
         native_tools,
         config.skills.prompt_injection_mode,
     );
-    if !native_tools {
+    // With tool retrieval the tool loop describes the selected tools per request.
+    let tool_retriever = ToolRetriever::from_config(&config);
+    if !native_tools && tool_retriever.is_none() {
         let filtered_specs = filtered_tool_specs_for_runtime(tools_registry.as_ref(), excluded);
         system_prompt.push_str(&build_tool_instructions_from_specs(&filtered_specs));
     }
@@ -5070,6 +5732,53 @@ pub async fn start_channels(config: Config) -> Result<()> {
         .as_ref()
         .is_some_and(|tg| tg.interrupt_on_new_message);
 
+    let mut restored_histories = HashMap::new();
+    let session_store = if config.channels_config.sessions.enabled {
+        match sessions::ChannelSessionStore::open(
+            &config.workspace_dir,
+            &config.channels_config.sessions,
+        ) {
+            Ok(store) => {
+                match store.restore_active() {
+                    Ok(histories) => restored_histories = histories,
+                    Err(err) => tracing::warn!("Failed to restore channel sessions: {err}"),
+                }
+                if !restored_histories.is_empty() {
+                    println!(
+                        "  💬 Restored {} channel conversation(s)",
+                        restored_histories.len()
+                    );
+                }
+                Some(Arc::new(store))
+            }
+            Err(err) => {
+                tracing::warn!("Channel session persistence disabled: {err}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut interrupted_turns = Vec::new();
+    let checkpoint_store = if config.channels_config.checkpoints.enabled {
+        match TurnCheckpointStore::open(&config.workspace_dir) {
+            Ok(store) => {
+                match store.interrupted() {
+                    Ok(turns) => interrupted_turns = turns,
+                    Err(err) => tracing::warn!("Failed to load interrupted turns: {err}"),
+                }
+                Some(Arc::new(store))
+            }
+            Err(err) => {
+                tracing::warn!("Turn checkpointing disabled: {err}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let runtime_ctx = Arc::new(ChannelRuntimeContext {
         channels_by_name,
         provider: Arc::clone(&provider),
@@ -5083,7 +5792,7 @@ pub async fn start_channels(config: Config) -> Result<()> {
         auto_save_memory: config.memory.auto_save,
         max_tool_iterations: config.agent.max_tool_iterations,
         min_relevance_score: config.memory.min_relevance_score,
-        conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+        conversation_histories: Arc::new(Mutex::new(restored_histories)),
         provider_cache: Arc::new(Mutex::new(provider_cache_seed)),
         route_overrides: Arc::new(Mutex::new(HashMap::new())),
         api_key: config.api_key.clone(),
@@ -5108,6 +5817,11 @@ pub async fn start_channels(config: Config) -> Result<()> {
         )),
         query_classification: config.query_classification.clone(),
         model_routes: config.model_routes.clone(),
+        agent_config: config.agent.clone(),
+        tts: config.tts.clone(),
+        sessions: session_store,
+        checkpoints: checkpoint_store,
+        tool_retriever,
         // WASM skill tools are sandboxed by the WASM engine and cannot access the
         // host filesystem, network, or shell. Pre-approve them so they are not
         // denied on non-CLI channels (which have no interactive stdin to prompt).
@@ -5123,6 +5837,18 @@ pub async fn start_channels(config: Config) -> Result<()> {
         },
     });
 
+    if !interrupted_turns.is_empty() {
+        println!(
+            "  ♻️ Found {} turn(s) interrupted by the last shutdown",
+            interrupted_turns.len()
+        );
+        tokio::spawn(resume_interrupted_turns(
+            Arc::clone(&runtime_ctx),
+            interrupted_turns,
+            config.channels_config.checkpoints.clone(),
+        ));
+    }
+
     run_message_dispatch_loop(rx, runtime_ctx, max_in_flight_messages).await;
 
     // Wait for all channel tasks
@@ -5248,6 +5974,34 @@ mod tests {
         assert_eq!(parse_runtime_command("slack", "/models"), None);
     }
 
+    #[test]
+    fn parse_runtime_command_supports_session_commands_on_all_channels() {
+        assert_eq!(
+            parse_runtime_command("whatsapp", "/sessions"),
+            Some(ChannelRuntimeCommand::ListSessions)
+        );
+        assert_eq!(
+            parse_runtime_command("signal", "/resume 1a2b3c4d"),
+            Some(ChannelRuntimeCommand::ResumeSession("1a2b3c4d".to_string()))
+        );
+        assert_eq!(
+            parse_runtime_command("telegram", "/resume@zeroclaw_bot"),
+            Some(ChannelRuntimeCommand::ResumeSession(String::new()))
+        );
+        assert_eq!(
+            parse_runtime_command("slack", "/undo"),
+            Some(ChannelRuntimeCommand::UndoExchange)
+        );
+        assert_eq!(
+            parse_runtime_command("matrix", "/retry gpt-4o"),
+            Some(ChannelRuntimeCommand::RetryExchange("gpt-4o".to_string()))
+        );
+        assert_eq!(
+            parse_runtime_command("discord", "/fork 2"),
+            Some(ChannelRuntimeCommand::ForkSession("2".to_string()))
+        );
+    }
+
     #[test]
     fn parse_runtime_command_supports_natural_language_approval_intents() {
         assert_eq!(
@@ -5446,6 +6200,11 @@ mod tests {
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -5500,6 +6259,11 @@ mod tests {
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -5517,6 +6281,363 @@ mod tests {
         assert_eq!(turns[0].content, "hello");
     }
 
+    #[test]
+    fn channel_sessions_persist_and_resume_sender_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Arc::new(
+            sessions::ChannelSessionStore::open(
+                tmp.path(),
+                &crate::config::ChannelSessionsConfig::default(),
+            )
+            .unwrap(),
+        );
+        let sender = "slack_alice".to_string();
+        let ctx = ChannelRuntimeContext {
+            channels_by_name: Arc::new(HashMap::from([(
+                "slack".to_string(),
+                Arc::new(RecordingChannel::default()) as Arc<dyn Channel>,
+            )])),
+            provider: Arc::new(DummyProvider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("system".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            hooks: None,
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(std::env::temp_dir()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
+            query_classification: crate::config::QueryClassificationConfig::default(),
+            model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: Some(Arc::clone(&store)),
+            checkpoints: None,
+            tool_retriever: None,
+            approval_manager: Arc::new(ApprovalManager::from_config(
+                &crate::config::AutonomyConfig::default(),
+            )),
+        };
+
+        append_sender_turn(&ctx, &sender, ChatMessage::user("first topic"));
+        append_sender_turn(&ctx, &sender, ChatMessage::assistant("noted"));
+        let restored = store.restore_active().unwrap();
+        assert_eq!(restored[&sender].len(), 2);
+
+        let fresh = clear_sender_history(&ctx, &sender).expect("new session");
+        assert!(store.restore_active().unwrap().is_empty());
+
+        let listing = build_sessions_response(&ctx, &sender);
+        assert!(listing.contains(&format!("`{}` (current)", fresh.short_id())));
+        assert!(listing.contains("first topic"));
+
+        let archived = store
+            .sessions_for_sender(&sender, 10)
+            .unwrap()
+            .into_iter()
+            .find(|session| !session.active)
+            .expect("archived session");
+        assert_eq!(archived.channel, "slack");
+        let reply = resume_sender_session(&ctx, &sender, archived.short_id());
+        assert!(reply.starts_with("Resumed session"), "{reply}");
+        let histories = ctx
+            .conversation_histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        assert_eq!(histories[&sender][0].content, "first topic");
+    }
+
+    #[test]
+    fn channel_undo_and_fork_rewind_sender_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Arc::new(
+            sessions::ChannelSessionStore::open(
+                tmp.path(),
+                &crate::config::ChannelSessionsConfig::default(),
+            )
+            .unwrap(),
+        );
+        let sender = "slack_alice".to_string();
+        let ctx = ChannelRuntimeContext {
+            channels_by_name: Arc::new(HashMap::from([(
+                "slack".to_string(),
+                Arc::new(RecordingChannel::default()) as Arc<dyn Channel>,
+            )])),
+            provider: Arc::new(DummyProvider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("system".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            hooks: None,
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(std::env::temp_dir()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
+            query_classification: crate::config::QueryClassificationConfig::default(),
+            model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: Some(Arc::clone(&store)),
+            checkpoints: None,
+            tool_retriever: None,
+            approval_manager: Arc::new(ApprovalManager::from_config(
+                &crate::config::AutonomyConfig::default(),
+            )),
+        };
+
+        for (question, answer) in [("one", "1"), ("two", "2"), ("three", "3")] {
+            append_sender_turn(
+                &ctx,
+                &sender,
+                ChatMessage::user(format!("[2026-10-18 09:00:00 UTC] {question}")),
+            );
+            append_sender_turn(&ctx, &sender, ChatMessage::assistant(answer));
+        }
+
+        assert_eq!(
+            undo_sender_exchange(&ctx, &sender).as_deref(),
+            Some("three")
+        );
+        assert_eq!(store.restore_active().unwrap()[&sender].len(), 4);
+
+        let reply = fork_sender_session(&ctx, &sender, "1");
+        assert!(reply.starts_with("Forked into session"), "{reply}");
+        assert_eq!(store.restore_active().unwrap()[&sender].len(), 2);
+        let sessions = store.sessions_for_sender(&sender, 10).unwrap();
+        let archived = sessions
+            .iter()
+            .find(|session| !session.active)
+            .expect("archived branch");
+        assert_eq!(archived.message_count, 4);
+
+        assert!(fork_sender_session(&ctx, &sender, "5").contains("only 1 exchange"));
+        assert_eq!(undo_sender_exchange(&ctx, &sender).as_deref(), Some("one"));
+        assert_eq!(undo_sender_exchange(&ctx, &sender), None);
+    }
+
+    #[tokio::test]
+    async fn interrupted_turns_are_resumed_or_reported_after_restart() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Arc::new(TurnCheckpointStore::open(tmp.path()).unwrap());
+        let origin = |message: &str| TurnOrigin {
+            channel: "test-channel".into(),
+            sender: "alice".into(),
+            reply_target: "chat-1".into(),
+            thread_ts: None,
+            history_key: "test-channel_alice".into(),
+            provider: "test-provider".into(),
+            model: "test-model".into(),
+            message: message.into(),
+        };
+        store
+            .begin(
+                &origin("summarize the logs"),
+                &[ChatMessage::user("summarize the logs")],
+            )
+            .unwrap();
+        let exhausted = store
+            .begin(
+                &origin("deploy staging"),
+                &[ChatMessage::user("deploy staging")],
+            )
+            .unwrap();
+        store.mark_resumed(&exhausted).unwrap();
+
+        let channel = Arc::new(RecordingChannel::default());
+        let ctx = Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(HashMap::from([(
+                "test-channel".to_string(),
+                Arc::clone(&channel) as Arc<dyn Channel>,
+            )])),
+            provider: Arc::new(DummyProvider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("system".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            hooks: None,
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(tmp.path().to_path_buf()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
+            query_classification: crate::config::QueryClassificationConfig::default(),
+            model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: Some(Arc::clone(&store)),
+            tool_retriever: None,
+            approval_manager: Arc::new(ApprovalManager::from_config(
+                &crate::config::AutonomyConfig::default(),
+            )),
+        });
+
+        resume_interrupted_turns(
+            Arc::clone(&ctx),
+            store.interrupted().unwrap(),
+            crate::config::ChannelCheckpointsConfig::default(),
+        )
+        .await;
+        for _ in 0..200 {
+            if channel.sent_messages.lock().await.len() >= 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let sent = channel.sent_messages.lock().await.clone();
+        assert_eq!(sent.len(), 3, "{sent:?}");
+        assert!(sent[0].starts_with("chat-1:♻️ Resuming your request \"summarize the logs\""));
+        assert!(sent[1].contains("\"deploy staging\" was interrupted by a restart"));
+        assert_eq!(sent[2], "chat-1:ok");
+        assert!(store.interrupted().unwrap().is_empty());
+        let histories = ctx.conversation_histories.lock().unwrap();
+        assert_eq!(
+            histories["test-channel_alice"].last().unwrap().content,
+            "ok"
+        );
+    }
+
+    #[tokio::test]
+    async fn resumed_turn_asks_the_original_sender_to_approve_supervised_tools() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Arc::new(TurnCheckpointStore::open(tmp.path()).unwrap());
+        let origin = TurnOrigin {
+            channel: "test-channel".into(),
+            sender: "alice".into(),
+            reply_target: "chat-1".into(),
+            thread_ts: None,
+            history_key: "test-channel_alice".into(),
+            provider: "test-provider".into(),
+            model: "test-model".into(),
+            message: "price check".into(),
+        };
+        store
+            .begin(&origin, &[ChatMessage::user("price check")])
+            .unwrap();
+
+        let channel = Arc::new(RecordingChannel::default());
+        let approval_manager = Arc::new(ApprovalManager::from_config(
+            &crate::config::AutonomyConfig::default(),
+        ));
+        let ctx = Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(HashMap::from([(
+                "test-channel".to_string(),
+                Arc::clone(&channel) as Arc<dyn Channel>,
+            )])),
+            provider: Arc::new(ToolCallingProvider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("system".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            hooks: None,
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(tmp.path().to_path_buf()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
+            query_classification: crate::config::QueryClassificationConfig::default(),
+            model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: Some(Arc::clone(&store)),
+            tool_retriever: None,
+            approval_manager: Arc::clone(&approval_manager),
+        });
+
+        resume_interrupted_turns(
+            Arc::clone(&ctx),
+            store.interrupted().unwrap(),
+            crate::config::ChannelCheckpointsConfig::default(),
+        )
+        .await;
+
+        let mut request_id = None;
+        for _ in 0..200 {
+            request_id = channel.sent_messages.lock().await.iter().find_map(|sent| {
+                let rest = sent.split("Request ID: `").nth(1)?;
+                Some(rest.split('`').next()?.to_string())
+            });
+            if request_id.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let request_id = request_id.expect("resumed turn should send an approval prompt");
+        approval_manager
+            .confirm_non_cli_pending_request(&request_id, "alice", "test-channel", "chat-1")
+            .expect("pending approval should belong to the original sender");
+        approval_manager.record_non_cli_pending_resolution(&request_id, ApprovalResponse::Yes);
+
+        for _ in 0..200 {
+            if store.interrupted().unwrap().is_empty()
+                && channel.sent_messages.lock().await.len() >= 3
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let sent = channel.sent_messages.lock().await.clone();
+        assert_eq!(sent.len(), 3, "{sent:?}");
+        assert!(sent[1].contains("Approval required for tool `mock_price`"));
+        assert!(sent[2].contains("BTC is currently around $65,000"));
+    }
+
     #[test]
     fn rollback_orphan_user_turn_removes_only_latest_matching_user_turn() {
         let sender = "telegram_u3".to_string();
@@ -5557,6 +6678,11 @@ mod tests {
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -6101,14 +7227,14 @@ BTC is currently around $65,000 based on latest tool output."#
         let tools: Vec<Box<dyn Tool>> = vec![Box::new(MockPriceTool), Box::new(MockEchoTool)];
         let excluded = vec!["mock_price".to_string()];
 
-        let non_native = build_runtime_tool_visibility_prompt(&tools, &excluded, false);
+        let non_native = build_runtime_tool_visibility_prompt(&tools, &excluded, false, false);
         assert!(non_native.contains("Runtime Tool Availability (Authoritative)"));
         assert!(non_native.contains("Excluded by runtime policy: mock_price"));
         assert!(non_native.contains("`mock_echo`"));
         assert!(!non_native.contains("**mock_price**:"));
         assert!(non_native.contains("## Tool Use Protocol"));
 
-        let native = build_runtime_tool_visibility_prompt(&tools, &excluded, true);
+        let native = build_runtime_tool_visibility_prompt(&tools, &excluded, true, false);
         assert!(native.contains("Runtime Tool Availability (Authoritative)"));
         assert!(native.contains("native provider function-calling"));
         assert!(!native.contains("## Tool Use Protocol"));
@@ -6155,6 +7281,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(vec!["mock_price".to_string()])),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -6230,6 +7361,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -6260,6 +7396,116 @@ BTC is currently around $65,000 based on latest tool output."#
         assert!(!sent_messages[0].contains("mock_price"));
     }
 
+    /// Asks a question without a turn-specific timeout, then reports the
+    /// tool result it got back.
+    struct AskingProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for AskingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            unreachable!("chat_with_history is overridden")
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            match messages
+                .iter()
+                .rev()
+                .find(|msg| msg.role == "user" && msg.content.contains("[Tool results]"))
+            {
+                Some(results) if results.content.contains("default answer: staging") => {
+                    Ok("Deploying to staging.".to_string())
+                }
+                Some(results) => Ok(format!("unexpected tool result: {}", results.content)),
+                None => Ok(r#"<tool_call>
+{"name":"ask_user","arguments":{"question":"Which environment?","default":"staging"}}
+</tool_call>"#
+                    .to_string()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn process_channel_message_clamps_ask_user_wait_to_turn_budget() {
+        let channel_impl = Arc::new(RecordingChannel::default());
+        let channel: Arc<dyn Channel> = channel_impl.clone();
+
+        let mut channels_by_name = HashMap::new();
+        channels_by_name.insert(channel.name().to_string(), channel);
+
+        // Turn budget is 1s x min(4, 4) = 4s, far below the 300s question default.
+        let runtime_ctx = Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(channels_by_name),
+            provider: Arc::new(AskingProvider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![Box::new(crate::tools::AskUserTool::new())]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("test-system-prompt".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 4,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(std::env::temp_dir()),
+            message_timeout_secs: 1,
+            interrupt_on_new_message: false,
+            non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
+            query_classification: crate::config::QueryClassificationConfig::default(),
+            model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
+            approval_manager: Arc::new(ApprovalManager::from_config(
+                &crate::config::AutonomyConfig::default(),
+            )),
+            multimodal: crate::config::MultimodalConfig::default(),
+            hooks: None,
+        });
+
+        let started = Instant::now();
+        process_channel_message(
+            runtime_ctx,
+            traits::ChannelMessage {
+                id: "msg-1".to_string(),
+                sender: "alice".to_string(),
+                reply_target: "chat-42".to_string(),
+                content: "Deploy the release".to_string(),
+                channel: "test-channel".to_string(),
+                timestamp: 1,
+                thread_ts: None,
+            },
+            CancellationToken::new(),
+        )
+        .await;
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        let sent_messages = channel_impl.sent_messages.lock().await;
+        assert_eq!(sent_messages.len(), 2, "{sent_messages:?}");
+        assert!(sent_messages[0].contains("Which environment?"));
+        assert!(sent_messages[1].contains("Deploying to staging."));
+        assert!(!sent_messages[1].contains("timed out"));
+    }
+
     #[tokio::test]
     async fn process_channel_message_telegram_does_not_persist_tool_summary_prefix() {
         let channel_impl = Arc::new(TelegramRecordingChannel::default());
@@ -6294,6 +7540,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -6370,6 +7621,11 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -6447,6 +7703,11 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -6520,6 +7781,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -6584,6 +7850,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -6657,6 +7928,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -6761,6 +8037,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(vec!["mock_price".to_string()])),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
         });
         assert_eq!(
@@ -6911,6 +8192,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
         });
         assert_eq!(
@@ -7021,6 +8307,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager,
         });
 
@@ -7126,6 +8417,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(vec!["shell".to_string()])),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager,
         });
 
@@ -7222,6 +8518,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(vec!["mock_price".to_string()])),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
         });
 
@@ -7370,6 +8671,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
         });
 
@@ -7515,6 +8821,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
         });
 
@@ -7630,6 +8941,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
         });
 
@@ -7725,6 +9041,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
         });
 
@@ -7839,6 +9160,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
         });
 
@@ -7954,6 +9280,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8030,6 +9361,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8122,6 +9458,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8267,6 +9608,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8370,6 +9716,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8435,6 +9786,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8612,6 +9968,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8697,6 +10058,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8794,6 +10160,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8873,6 +10244,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -8937,6 +10313,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -9458,6 +10839,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -9548,6 +10934,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -9638,6 +11029,11 @@ BTC is currently around $65,000 based on latest tool output."#
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -10357,6 +11753,11 @@ BTC is currently around $65,000 based on latest tool output."#;
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
@@ -10428,6 +11829,11 @@ BTC is currently around $65,000 based on latest tool output."#;
             non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
             query_classification: crate::config::QueryClassificationConfig::default(),
             model_routes: Vec::new(),
+            agent_config: crate::config::AgentConfig::default(),
+            tts: crate::config::TtsConfig::default(),
+            sessions: None,
+            checkpoints: None,
+            tool_retriever: None,
             approval_manager: Arc::new(ApprovalManager::from_config(
                 &crate::config::AutonomyConfig::default(),
             )),
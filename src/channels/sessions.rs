@@ -0,0 +1,519 @@
+//! Persistent per-sender conversation sessions for channel runtimes.
+//!
+//! The channel runtime keeps the working history for each sender in memory;
+//! [`ChannelSessionStore`] mirrors it into SQLite at
+//! `<workspace>/state/channel_sessions.db` so a daemon restart or update can
+//! restore every ongoing conversation. Each sender has at most one *active*
+//! session; `/new` archives it and starts another, `/resume` re-activates an
+//! archived one, and sessions idle for longer than the configured timeout are
+//! archived instead of restored.
+
+use crate::config::ChannelSessionsConfig;
+use crate::providers::ChatMessage;
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const SESSION_DB_FILE: &str = "channel_sessions.db";
+/// Characters of the first user message kept as the session title.
+const SESSION_TITLE_MAX_CHARS: usize = 60;
+/// Characters of a session ID shown to users; any unique prefix resumes it.
+pub const SESSION_ID_DISPLAY_CHARS: usize = 8;
+
+const SESSION_COLUMNS: &str =
+    "id, sender_key, channel, title, active, message_count, created_at, last_activity";
+
+/// Summary of a stored session (history excluded).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChannelSession {
+    pub id: String,
+    pub sender_key: String,
+    pub channel: String,
+    pub title: String,
+    pub active: bool,
+    pub message_count: usize,
+    pub created_at: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+}
+
+impl ChannelSession {
+    /// Short form of the ID used in chat replies.
+    pub fn short_id(&self) -> &str {
+        let end = self
+            .id
+            .char_indices()
+            .nth(SESSION_ID_DISPLAY_CHARS)
+            .map_or(self.id.len(), |(idx, _)| idx);
+        &self.id[..end]
+    }
+}
+
+/// Result of resolving a `/resume` argument.
+#[derive(Debug)]
+pub enum ResumeOutcome {
+    Resumed(ChannelSession, Vec<ChatMessage>),
+    NotFound,
+    Ambiguous(Vec<ChannelSession>),
+}
+
+/// SQLite-backed session store shared by all channels.
+pub struct ChannelSessionStore {
+    conn: Mutex<Connection>,
+    idle_timeout: Option<chrono::Duration>,
+    max_per_sender: usize,
+}
+
+impl ChannelSessionStore {
+    /// Default database location inside a workspace.
+    pub fn db_path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("state").join(SESSION_DB_FILE)
+    }
+
+    /// Open (creating if needed) the store for a workspace.
+    pub fn open(workspace_dir: &Path, config: &ChannelSessionsConfig) -> Result<Self> {
+        Self::open_at(&Self::db_path(workspace_dir), config)
+    }
+
+    pub fn open_at(path: &Path, config: &ChannelSessionsConfig) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create session directory: {}", parent.display())
+            })?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open session DB: {}", path.display()))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA busy_timeout = 5000;
+             CREATE TABLE IF NOT EXISTS channel_sessions (
+                id            TEXT PRIMARY KEY,
+                sender_key    TEXT NOT NULL,
+                channel       TEXT NOT NULL,
+                title         TEXT NOT NULL DEFAULT '',
+                active        INTEGER NOT NULL DEFAULT 0,
+                message_count INTEGER NOT NULL DEFAULT 0,
+                history       TEXT NOT NULL DEFAULT '[]',
+                created_at    TEXT NOT NULL,
+                last_activity TEXT NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_channel_sessions_sender
+                ON channel_sessions(sender_key, last_activity);",
+        )
+        .context("Failed to initialize session schema")?;
+
+        let idle_timeout = (config.idle_timeout_minutes > 0).then(|| {
+            chrono::Duration::minutes(
+                i64::try_from(config.idle_timeout_minutes).unwrap_or(i64::MAX / 60_000),
+            )
+        });
+        Ok(Self {
+            conn: Mutex::new(conn),
+            idle_timeout,
+            max_per_sender: config.max_sessions_per_sender.max(1),
+        })
+    }
+
+    /// Write `history` to the sender's active session, creating one if needed.
+    pub fn save_active(
+        &self,
+        sender_key: &str,
+        channel: &str,
+        history: &[ChatMessage],
+    ) -> Result<()> {
+        let now = timestamp(Utc::now());
+        let history_json = serde_json::to_string(history)?;
+        let message_count = i64::try_from(history.len()).unwrap_or(i64::MAX);
+        let title = session_title(history);
+        let conn = self.conn.lock();
+
+        let updated = conn.execute(
+            "UPDATE channel_sessions
+                SET history = ?1, message_count = ?2, last_activity = ?3,
+                    title = CASE WHEN title = '' THEN ?4 ELSE title END
+              WHERE sender_key = ?5 AND active = 1",
+            params![history_json, message_count, now, title, sender_key],
+        )?;
+        if updated == 0 {
+            conn.execute(
+                "INSERT INTO channel_sessions
+                    (id, sender_key, channel, title, active, message_count, history, created_at, last_activity)
+                 VALUES (?1, ?2, ?3, ?4, 1, ?5, ?6, ?7, ?7)",
+                params![
+                    new_session_id(),
+                    sender_key,
+                    channel,
+                    title,
+                    message_count,
+                    history_json,
+                    now
+                ],
+            )?;
+            prune_sender(&conn, sender_key, self.max_per_sender)?;
+        }
+        Ok(())
+    }
+
+    /// Archive the sender's active session and start an empty one.
+    pub fn start_new(&self, sender_key: &str, channel: &str) -> Result<ChannelSession> {
+        let now = Utc::now();
+        let session = ChannelSession {
+            id: new_session_id(),
+            sender_key: sender_key.to_string(),
+            channel: channel.to_string(),
+            title: String::new(),
+            active: true,
+            message_count: 0,
+            created_at: now,
+            last_activity: now,
+        };
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE channel_sessions SET active = 0 WHERE sender_key = ?1 AND active = 1",
+            params![sender_key],
+        )?;
+        conn.execute(
+            "INSERT INTO channel_sessions
+                (id, sender_key, channel, title, active, message_count, history, created_at, last_activity)
+             VALUES (?1, ?2, ?3, '', 1, 0, '[]', ?4, ?4)",
+            params![session.id, sender_key, channel, timestamp(now)],
+        )?;
+        prune_sender(&conn, sender_key, self.max_per_sender)?;
+        Ok(session)
+    }
+
+    /// Make the sender's session whose ID starts with `id_prefix` active.
+    pub fn resume(&self, sender_key: &str, id_prefix: &str) -> Result<ResumeOutcome> {
+        let prefix = id_prefix.trim().to_ascii_lowercase();
+        if prefix.is_empty() {
+            return Ok(ResumeOutcome::NotFound);
+        }
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SESSION_COLUMNS} FROM channel_sessions
+              WHERE sender_key = ?1 AND substr(id, 1, ?2) = ?3
+              ORDER BY last_activity DESC, rowid DESC"
+        ))?;
+        let prefix_len = i64::try_from(prefix.len()).unwrap_or(i64::MAX);
+        let matches = stmt
+            .query_map(params![sender_key, prefix_len, prefix], map_session_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        drop(stmt);
+
+        let mut session = match matches.len() {
+            0 => return Ok(ResumeOutcome::NotFound),
+            1 => matches.into_iter().next().expect("one match"),
+            _ => return Ok(ResumeOutcome::Ambiguous(matches)),
+        };
+
+        let now = Utc::now();
+        conn.execute(
+            "UPDATE channel_sessions SET active = 0 WHERE sender_key = ?1 AND active = 1",
+            params![sender_key],
+        )?;
+        conn.execute(
+            "UPDATE channel_sessions SET active = 1, last_activity = ?1 WHERE id = ?2",
+            params![timestamp(now), session.id],
+        )?;
+        let history = load_history(&conn, &session.id)?;
+        session.active = true;
+        session.last_activity = now;
+        Ok(ResumeOutcome::Resumed(session, history))
+    }
+
+    /// Sessions for one sender, most recently active first.
+    pub fn sessions_for_sender(
+        &self,
+        sender_key: &str,
+        limit: usize,
+    ) -> Result<Vec<ChannelSession>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SESSION_COLUMNS} FROM channel_sessions
+              WHERE sender_key = ?1
+              ORDER BY active DESC, last_activity DESC, rowid DESC
+              LIMIT ?2"
+        ))?;
+        let rows = stmt.query_map(params![sender_key, sql_limit(limit)], map_session_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// All sessions, optionally for one channel, most recently active first.
+    pub fn list(&self, channel: Option<&str>, limit: usize) -> Result<Vec<ChannelSession>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SESSION_COLUMNS} FROM channel_sessions
+              WHERE ?1 IS NULL OR channel = ?1
+              ORDER BY last_activity DESC, rowid DESC
+              LIMIT ?2"
+        ))?;
+        let rows = stmt.query_map(params![channel, sql_limit(limit)], map_session_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// One session with its full history.
+    pub fn get(&self, id: &str) -> Result<Option<(ChannelSession, Vec<ChatMessage>)>> {
+        let conn = self.conn.lock();
+        let session = conn
+            .query_row(
+                &format!("SELECT {SESSION_COLUMNS} FROM channel_sessions WHERE id = ?1"),
+                params![id],
+                map_session_row,
+            )
+            .optional()?;
+        match session {
+            Some(session) => {
+                let history = load_history(&conn, &session.id)?;
+                Ok(Some((session, history)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Archive the sender's active session if it has been idle past the
+    /// timeout. Returns true when a session was archived.
+    pub fn expire_if_idle(&self, sender_key: &str) -> Result<bool> {
+        let Some(cutoff) = self.idle_cutoff() else {
+            return Ok(false);
+        };
+        let archived = self.conn.lock().execute(
+            "UPDATE channel_sessions SET active = 0
+              WHERE sender_key = ?1 AND active = 1 AND last_activity < ?2",
+            params![sender_key, cutoff],
+        )?;
+        Ok(archived > 0)
+    }
+
+    /// Histories of every active, non-idle session keyed by sender, for
+    /// seeding the runtime on startup. Idle sessions are archived.
+    pub fn restore_active(&self) -> Result<HashMap<String, Vec<ChatMessage>>> {
+        let conn = self.conn.lock();
+        if let Some(cutoff) = self.idle_cutoff() {
+            conn.execute(
+                "UPDATE channel_sessions SET active = 0 WHERE active = 1 AND last_activity < ?1",
+                params![cutoff],
+            )?;
+        }
+        let mut stmt = conn.prepare(
+            "SELECT sender_key, history FROM channel_sessions
+              WHERE active = 1 AND message_count > 0",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut restored = HashMap::new();
+        for row in rows {
+            let (sender_key, history_json) = row?;
+            match serde_json::from_str::<Vec<ChatMessage>>(&history_json) {
+                Ok(history) => {
+                    restored.insert(sender_key, history);
+                }
+                Err(err) => {
+                    tracing::warn!("Skipping unreadable channel session for {sender_key}: {err}");
+                }
+            }
+        }
+        Ok(restored)
+    }
+
+    fn idle_cutoff(&self) -> Option<String> {
+        self.idle_timeout
+            .map(|timeout| timestamp(Utc::now() - timeout))
+    }
+}
+
+/// Delete the sender's least recently active sessions beyond `keep`, plus
+/// archived sessions that never received a message.
+fn prune_sender(conn: &Connection, sender_key: &str, keep: usize) -> Result<()> {
+    conn.execute(
+        "DELETE FROM channel_sessions
+          WHERE sender_key = ?1 AND active = 0 AND message_count = 0",
+        params![sender_key],
+    )?;
+    conn.execute(
+        "DELETE FROM channel_sessions
+          WHERE sender_key = ?1 AND active = 0 AND id NOT IN (
+              SELECT id FROM channel_sessions
+               WHERE sender_key = ?1
+               ORDER BY active DESC, last_activity DESC, rowid DESC
+               LIMIT ?2
+          )",
+        params![sender_key, sql_limit(keep)],
+    )?;
+    Ok(())
+}
+
+fn load_history(conn: &Connection, id: &str) -> Result<Vec<ChatMessage>> {
+    let history_json: String = conn.query_row(
+        "SELECT history FROM channel_sessions WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    serde_json::from_str(&history_json).context("Stored session history is not valid JSON")
+}
+
+fn map_session_row(row: &Row<'_>) -> rusqlite::Result<ChannelSession> {
+    let message_count: i64 = row.get(5)?;
+    Ok(ChannelSession {
+        id: row.get(0)?,
+        sender_key: row.get(1)?,
+        channel: row.get(2)?,
+        title: row.get(3)?,
+        active: row.get::<_, i64>(4)? != 0,
+        message_count: usize::try_from(message_count).unwrap_or(0),
+        created_at: parse_timestamp(&row.get::<_, String>(6)?),
+        last_activity: parse_timestamp(&row.get::<_, String>(7)?),
+    })
+}
+
+/// Fixed-width UTC timestamps so SQL string comparison orders them correctly.
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(raw: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
+fn sql_limit(limit: usize) -> i64 {
+    i64::try_from(limit).unwrap_or(i64::MAX)
+}
+
+fn new_session_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// First user message without the runtime's `[timestamp] ` prefix.
+fn session_title(history: &[ChatMessage]) -> String {
+    crate::agent::branching::history_title(history, SESSION_TITLE_MAX_CHARS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(dir: &Path, idle_timeout_minutes: u64) -> ChannelSessionStore {
+        ChannelSessionStore::open(
+            dir,
+            &ChannelSessionsConfig {
+                enabled: true,
+                idle_timeout_minutes,
+                max_sessions_per_sender: 3,
+            },
+        )
+        .unwrap()
+    }
+
+    fn history(text: &str) -> Vec<ChatMessage> {
+        vec![
+            ChatMessage::user(format!("[2026-10-18 09:00:00 UTC] {text}")),
+            ChatMessage::assistant("ok"),
+        ]
+    }
+
+    #[test]
+    fn active_history_survives_reopen() {
+        let tmp = tempfile::tempdir().unwrap();
+        store(tmp.path(), 60)
+            .save_active("telegram_alice", "telegram", &history("plan the trip"))
+            .unwrap();
+
+        let reopened = store(tmp.path(), 60);
+        let restored = reopened.restore_active().unwrap();
+        assert_eq!(restored["telegram_alice"].len(), 2);
+
+        let sessions = reopened.sessions_for_sender("telegram_alice", 10).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].title, "plan the trip");
+        assert_eq!(sessions[0].channel, "telegram");
+        assert!(sessions[0].active);
+        assert_eq!(sessions[0].short_id().len(), SESSION_ID_DISPLAY_CHARS);
+    }
+
+    #[test]
+    fn new_archives_current_and_resume_switches_back() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = store(tmp.path(), 0);
+        store
+            .save_active("slack_bob", "slack", &history("first topic"))
+            .unwrap();
+        let first_id = store.sessions_for_sender("slack_bob", 10).unwrap()[0]
+            .id
+            .clone();
+
+        let second = store.start_new("slack_bob", "slack").unwrap();
+        store
+            .save_active("slack_bob", "slack", &history("second topic"))
+            .unwrap();
+        let sessions = store.sessions_for_sender("slack_bob", 10).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, second.id);
+        assert!(sessions[0].active && !sessions[1].active);
+
+        let ResumeOutcome::Resumed(resumed, turns) =
+            store.resume("slack_bob", &first_id[..6]).unwrap()
+        else {
+            panic!("expected resume");
+        };
+        assert_eq!(resumed.id, first_id);
+        assert!(turns[0].content.contains("first topic"));
+        let restored = store.restore_active().unwrap();
+        assert!(restored["slack_bob"][0].content.contains("first topic"));
+        assert!(matches!(
+            store.resume("other_sender", &first_id).unwrap(),
+            ResumeOutcome::NotFound
+        ));
+    }
+
+    #[test]
+    fn idle_sessions_are_archived_not_restored() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = store(tmp.path(), 30);
+        store
+            .save_active("discord_carol", "discord", &history("old chat"))
+            .unwrap();
+        store
+            .conn
+            .lock()
+            .execute(
+                "UPDATE channel_sessions SET last_activity = ?1",
+                params![timestamp(Utc::now() - chrono::Duration::hours(2))],
+            )
+            .unwrap();
+
+        assert!(store.expire_if_idle("discord_carol").unwrap());
+        assert!(store.restore_active().unwrap().is_empty());
+        let sessions = store.sessions_for_sender("discord_carol", 10).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert!(!sessions[0].active);
+    }
+
+    #[test]
+    fn prunes_oldest_and_empty_sessions_per_sender() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = store(tmp.path(), 0);
+        for topic in ["a", "b", "c", "d"] {
+            store.start_new("irc_dave", "irc").unwrap();
+            store
+                .save_active("irc_dave", "irc", &history(topic))
+                .unwrap();
+        }
+        store.start_new("irc_dave", "irc").unwrap();
+        store.start_new("irc_dave", "irc").unwrap();
+
+        let sessions = store.sessions_for_sender("irc_dave", 10).unwrap();
+        assert_eq!(sessions.len(), 3);
+        assert!(sessions[0].active && sessions[0].message_count == 0);
+        assert_eq!(sessions[1].title, "d");
+        assert_eq!(store.list(Some("irc"), 10).unwrap().len(), 3);
+        assert!(store.list(Some("slack"), 10).unwrap().is_empty());
+        let (_, turns) = store.get(&sessions[1].id).unwrap().unwrap();
+        assert_eq!(turns.len(), 2);
+    }
+}
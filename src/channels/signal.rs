@@ -277,18 +277,27 @@ impl Channel for SignalChannel {
     }
 
     async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
-        let params = match Self::parse_recipient_target(&message.recipient) {
+        // Voice replies arrive as `[VOICE:<path>]` markers; signal-cli uploads
+        // local files passed as attachments.
+        let (text, audio_files) = super::tts::split_audio_markers(&message.content);
+        let mut params = match Self::parse_recipient_target(&message.recipient) {
             RecipientTarget::Direct(number) => serde_json::json!({
                 "recipient": [number],
-                "message": &message.content,
+                "message": text,
                 "account": &self.account,
             }),
             RecipientTarget::Group(group_id) => serde_json::json!({
                 "groupId": group_id,
-                "message": &message.content,
+                "message": text,
                 "account": &self.account,
             }),
         };
+        if !audio_files.is_empty() {
+            params["attachments"] = audio_files
+                .iter()
+                .map(|path| serde_json::Value::String(path.display().to_string()))
+                .collect();
+        }
 
         self.rpc_request("send", params).await?;
         Ok(())
@@ -148,6 +148,33 @@ pub trait Channel: Send + Sync {
             .await
     }
 
+    /// Ask the user a clarifying question on behalf of a running turn (see the
+    /// `ask_user` tool). The next plain-text reply from the same sender is
+    /// taken as the answer.
+    ///
+    /// Default behavior sends a plain-text question with numbered options.
+    async fn send_question_prompt(
+        &self,
+        recipient: &str,
+        question: &str,
+        options: &[String],
+        thread_ts: Option<String>,
+    ) -> anyhow::Result<()> {
+        use std::fmt::Write as _;
+
+        let mut message = format!("❓ {question}");
+        for (index, option) in options.iter().enumerate() {
+            let _ = write!(message, "\n{}. {option}", index + 1);
+        }
+        message.push_str(if options.is_empty() {
+            "\nReply to this message to answer."
+        } else {
+            "\nReply with an option number or your own answer."
+        });
+        self.send(&SendMessage::new(message, recipient).in_thread(thread_ts))
+            .await
+    }
+
     /// Add a reaction (emoji) to a message.
     ///
     /// `channel_id` is the platform channel/conversation identifier (e.g. Discord channel ID).
@@ -1,11 +1,20 @@
 use anyhow::{bail, Context, Result};
 use reqwest::multipart::{Form, Part};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
 
-use crate::config::TranscriptionConfig;
+use crate::config::{TranscriptionBackend, TranscriptionConfig};
 
 /// Maximum upload size accepted by the Groq Whisper API (25 MB).
 const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
 
+/// Maximum input size for local backends, which have no upload limit (200 MB).
+const MAX_LOCAL_AUDIO_BYTES: usize = 200 * 1024 * 1024;
+
+/// Default faster-whisper model when `local_model` is unset.
+const DEFAULT_FASTER_WHISPER_MODEL: &str = "small";
+
 /// Map file extension to MIME type for Whisper-compatible transcription APIs.
 fn mime_for_audio(extension: &str) -> Option<&'static str> {
     match extension.to_ascii_lowercase().as_str() {
@@ -31,20 +40,34 @@ fn normalize_audio_filename(file_name: &str) -> String {
     }
 }
 
-/// Transcribe audio bytes via a Whisper-compatible transcription API.
+/// Transcribe audio bytes with the backend selected by `config.backend`.
 ///
 /// Returns the transcribed text on success.
 ///
-/// Credential resolution order:
-/// 1. `config.transcription.api_key`
-/// 2. `GROQ_API_KEY` environment variable (backward compatibility)
-///
 /// The caller is responsible for enforcing duration limits *before* downloading
 /// the file; this function enforces the byte-size cap.
 pub async fn transcribe_audio(
     audio_data: Vec<u8>,
     file_name: &str,
     config: &TranscriptionConfig,
+) -> Result<String> {
+    match config.backend {
+        TranscriptionBackend::Api => transcribe_via_api(audio_data, file_name, config).await,
+        TranscriptionBackend::WhisperCpp | TranscriptionBackend::FasterWhisper => {
+            transcribe_locally(audio_data, file_name, config).await
+        }
+    }
+}
+
+/// Transcribe audio bytes via a Whisper-compatible transcription API.
+///
+/// Credential resolution order:
+/// 1. `config.transcription.api_key`
+/// 2. `GROQ_API_KEY` environment variable (backward compatibility)
+async fn transcribe_via_api(
+    audio_data: Vec<u8>,
+    file_name: &str,
+    config: &TranscriptionConfig,
 ) -> Result<String> {
     if audio_data.len() > MAX_AUDIO_BYTES {
         bail!(
@@ -122,6 +145,247 @@ pub async fn transcribe_audio(
     Ok(text)
 }
 
+// ── Local backends (whisper.cpp / faster-whisper) ────────────
+
+/// Transcribe with a local binary: convert to 16 kHz mono WAV chunks with
+/// ffmpeg, run the backend on each chunk and join the results. Audio never
+/// leaves the machine.
+async fn transcribe_locally(
+    audio_data: Vec<u8>,
+    file_name: &str,
+    config: &TranscriptionConfig,
+) -> Result<String> {
+    if audio_data.len() > MAX_LOCAL_AUDIO_BYTES {
+        bail!(
+            "Audio file too large ({} bytes, max {MAX_LOCAL_AUDIO_BYTES})",
+            audio_data.len()
+        );
+    }
+    let backend = LocalBackend::from_config(config)?;
+
+    let timeout = Duration::from_secs(config.timeout_secs.max(1));
+    tokio::time::timeout(timeout, async {
+        let workdir = tempfile::Builder::new().prefix("zeroclaw-stt-").tempdir()?;
+        let extension = file_name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext)
+            .filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("bin");
+        let input = workdir.path().join(format!("input.{extension}"));
+        tokio::fs::write(&input, &audio_data).await?;
+
+        let chunks = split_to_wav_chunks(config, &input, workdir.path()).await?;
+        let mut parts = Vec::with_capacity(chunks.len());
+        for chunk in &chunks {
+            let text = backend.transcribe_chunk(chunk, workdir.path()).await?;
+            let text = text.trim();
+            if !text.is_empty() {
+                parts.push(text.to_string());
+            }
+        }
+        Ok(parts.join(" "))
+    })
+    .await
+    .map_err(|_| {
+        anyhow::anyhow!(
+            "Local transcription timed out after {}s",
+            config.timeout_secs
+        )
+    })?
+}
+
+/// Convert `input` into numbered 16 kHz mono PCM WAV chunks of
+/// `config.chunk_secs` seconds, returned in playback order.
+async fn split_to_wav_chunks(
+    config: &TranscriptionConfig,
+    input: &Path,
+    workdir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let pattern = workdir.join("chunk_%03d.wav");
+    let args = ffmpeg_chunk_args(input, &pattern, config.chunk_secs);
+    run_local_command(&config.ffmpeg_path, &args).await?;
+
+    let mut chunks = Vec::new();
+    let mut entries = tokio::fs::read_dir(workdir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with("chunk_") && name.ends_with(".wav") {
+            chunks.push(entry.path());
+        }
+    }
+    chunks.sort();
+    if chunks.is_empty() {
+        bail!("ffmpeg produced no audio to transcribe");
+    }
+    Ok(chunks)
+}
+
+fn ffmpeg_chunk_args(input: &Path, pattern: &Path, chunk_secs: u64) -> Vec<String> {
+    vec![
+        "-hide_banner".into(),
+        "-loglevel".into(),
+        "error".into(),
+        "-y".into(),
+        "-i".into(),
+        input.display().to_string(),
+        "-ar".into(),
+        "16000".into(),
+        "-ac".into(),
+        "1".into(),
+        "-c:a".into(),
+        "pcm_s16le".into(),
+        "-f".into(),
+        "segment".into(),
+        "-segment_time".into(),
+        chunk_secs.max(10).to_string(),
+        "-reset_timestamps".into(),
+        "1".into(),
+        pattern.display().to_string(),
+    ]
+}
+
+/// A configured local speech-to-text binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LocalBackend {
+    WhisperCpp {
+        binary: String,
+        model: String,
+        language: Option<String>,
+    },
+    FasterWhisper {
+        binary: String,
+        model: String,
+        language: Option<String>,
+    },
+}
+
+impl LocalBackend {
+    fn from_config(config: &TranscriptionConfig) -> Result<Self> {
+        let binary = config
+            .local_binary
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        let model = config
+            .local_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        let language = config
+            .language
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToOwned::to_owned);
+
+        match config.backend {
+            TranscriptionBackend::WhisperCpp => Ok(Self::WhisperCpp {
+                binary: binary.unwrap_or("whisper-cli").to_string(),
+                model: model
+                    .context("The whisper_cpp backend requires [transcription].local_model (path to a ggml model)")?
+                    .to_string(),
+                language,
+            }),
+            TranscriptionBackend::FasterWhisper => Ok(Self::FasterWhisper {
+                binary: binary.unwrap_or("whisper-ctranslate2").to_string(),
+                model: model.unwrap_or(DEFAULT_FASTER_WHISPER_MODEL).to_string(),
+                language,
+            }),
+            TranscriptionBackend::Api => bail!("The api backend is not a local transcription backend"),
+        }
+    }
+
+    fn args(&self, chunk: &Path, output_dir: &Path) -> Vec<String> {
+        match self {
+            // whisper.cpp defaults to English; `auto` enables language detection.
+            Self::WhisperCpp {
+                model, language, ..
+            } => vec![
+                "-m".into(),
+                model.clone(),
+                "-f".into(),
+                chunk.display().to_string(),
+                "-l".into(),
+                language.clone().unwrap_or_else(|| "auto".into()),
+                "-nt".into(),
+                "-np".into(),
+            ],
+            Self::FasterWhisper {
+                model, language, ..
+            } => {
+                let mut args = vec![
+                    chunk.display().to_string(),
+                    "--model".into(),
+                    model.clone(),
+                    "--output_format".into(),
+                    "txt".into(),
+                    "--output_dir".into(),
+                    output_dir.display().to_string(),
+                    "--verbose".into(),
+                    "False".into(),
+                ];
+                if let Some(language) = language {
+                    args.push("--language".into());
+                    args.push(language.clone());
+                }
+                args
+            }
+        }
+    }
+
+    async fn transcribe_chunk(&self, chunk: &Path, workdir: &Path) -> Result<String> {
+        match self {
+            Self::WhisperCpp { binary, .. } => {
+                let stdout = run_local_command(binary, &self.args(chunk, workdir)).await?;
+                Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+            }
+            Self::FasterWhisper { binary, .. } => {
+                let output_dir = workdir.join("out");
+                tokio::fs::create_dir_all(&output_dir).await?;
+                run_local_command(binary, &self.args(chunk, &output_dir)).await?;
+                let stem = chunk
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or("chunk");
+                let transcript = output_dir.join(format!("{stem}.txt"));
+                let text = tokio::fs::read_to_string(&transcript)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "faster-whisper wrote no transcript to {}",
+                            transcript.display()
+                        )
+                    })?;
+                Ok(text.trim().to_string())
+            }
+        }
+    }
+}
+
+/// Run a local tool, returning stdout; killed if the caller's timeout fires.
+async fn run_local_command(binary: &str, args: &[String]) -> Result<Vec<u8>> {
+    let output = tokio::process::Command::new(binary)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run '{binary}' — is it installed and on PATH?"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "'{binary}' exited with {}: {}",
+            output.status,
+            crate::util::truncate_with_ellipsis(stderr.trim(), 300)
+        );
+    }
+    Ok(output.stdout)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,4 +514,83 @@ mod tests {
             "error should mention the rejected extension, got: {msg}"
         );
     }
+
+    #[test]
+    fn whisper_cpp_requires_model_and_defaults_to_auto_language() {
+        let mut config = TranscriptionConfig {
+            backend: TranscriptionBackend::WhisperCpp,
+            ..TranscriptionConfig::default()
+        };
+        let err = LocalBackend::from_config(&config).unwrap_err();
+        assert!(err.to_string().contains("local_model"));
+
+        config.local_model = Some("/models/ggml-base.bin".into());
+        let backend = LocalBackend::from_config(&config).unwrap();
+        let args = backend.args(Path::new("/tmp/chunk_000.wav"), Path::new("/tmp"));
+        assert_eq!(
+            args,
+            vec![
+                "-m",
+                "/models/ggml-base.bin",
+                "-f",
+                "/tmp/chunk_000.wav",
+                "-l",
+                "auto",
+                "-nt",
+                "-np"
+            ]
+        );
+        assert!(matches!(
+            backend,
+            LocalBackend::WhisperCpp { ref binary, .. } if binary == "whisper-cli"
+        ));
+    }
+
+    #[test]
+    fn faster_whisper_passes_language_hint_and_output_dir() {
+        let config = TranscriptionConfig {
+            backend: TranscriptionBackend::FasterWhisper,
+            language: Some("de".into()),
+            local_binary: Some("/opt/bin/whisper-ctranslate2".into()),
+            ..TranscriptionConfig::default()
+        };
+        let backend = LocalBackend::from_config(&config).unwrap();
+        let args = backend.args(Path::new("/w/chunk_001.wav"), Path::new("/w/out"));
+        assert_eq!(args[0], "/w/chunk_001.wav");
+        assert!(args.windows(2).any(|w| w == ["--model", "small"]));
+        assert!(args.windows(2).any(|w| w == ["--output_dir", "/w/out"]));
+        assert!(args.windows(2).any(|w| w == ["--language", "de"]));
+    }
+
+    #[test]
+    fn ffmpeg_args_resample_and_segment() {
+        let args = ffmpeg_chunk_args(
+            Path::new("/w/input.ogg"),
+            Path::new("/w/chunk_%03d.wav"),
+            120,
+        );
+        assert!(args.windows(2).any(|w| w == ["-ar", "16000"]));
+        assert!(args.windows(2).any(|w| w == ["-ac", "1"]));
+        assert!(args.windows(2).any(|w| w == ["-segment_time", "120"]));
+        assert_eq!(args.last().map(String::as_str), Some("/w/chunk_%03d.wav"));
+
+        let args = ffmpeg_chunk_args(Path::new("/in.ogg"), Path::new("/c_%03d.wav"), 0);
+        assert!(args.windows(2).any(|w| w == ["-segment_time", "10"]));
+    }
+
+    #[tokio::test]
+    async fn local_backend_reports_missing_ffmpeg() {
+        let config = TranscriptionConfig {
+            backend: TranscriptionBackend::FasterWhisper,
+            ffmpeg_path: "/nonexistent/ffmpeg".into(),
+            ..TranscriptionConfig::default()
+        };
+        let err = transcribe_audio(vec![0u8; 100], "voice.ogg", &config)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("/nonexistent/ffmpeg"),
+            "expected ffmpeg launch error, got: {err}"
+        );
+    }
 }
@@ -0,0 +1,683 @@
+//! Text-to-speech for voice replies on channels.
+//!
+//! Backends:
+//! - `openai`: any OpenAI-compatible `/audio/speech` endpoint (Ogg/Opus output)
+//! - `piper`: local Piper subprocess (WAV, transcoded to Ogg/Opus when `ffmpeg` is available)
+//! - `espeak`: local espeak/espeak-ng subprocess (same transcoding as Piper)
+//!
+//! Channels deliver the synthesized file through the `[VOICE:<path>]` /
+//! `[AUDIO:<path>]` attachment markers they already understand.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::config::{TtsConfig, TtsReplyMode};
+
+/// Channels that can deliver voice notes from `[VOICE:<path>]` markers.
+const VOICE_CHANNELS: &[&str] = &["telegram", "whatsapp", "signal", "matrix"];
+
+/// Workspace subdirectory holding synthesized replies.
+const VOICE_REPLY_DIR: &str = "voice_replies";
+
+/// Synthesized replies older than this are removed on the next write.
+const VOICE_REPLY_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Bare marker a reply can carry to ask for voice delivery.
+const VOICE_REQUEST_MARKER: &str = "[VOICE]";
+
+/// Audio produced by a [`SpeechSynthesizer`].
+#[derive(Debug, Clone)]
+pub struct SynthesizedSpeech {
+    pub audio: Vec<u8>,
+    /// File extension matching the container (`ogg`, `mp3`, `wav`).
+    pub extension: &'static str,
+}
+
+/// Converts text into spoken audio.
+#[async_trait]
+pub trait SpeechSynthesizer: Send + Sync {
+    /// Backend name
+    fn name(&self) -> &str;
+
+    /// Synthesize `text` into an audio file body.
+    async fn synthesize(&self, text: &str) -> Result<SynthesizedSpeech>;
+}
+
+// ── OpenAI-compatible /audio/speech ──────────────────────────
+
+pub struct OpenAiSpeechSynthesizer {
+    api_url: String,
+    api_key: String,
+    model: String,
+    voice: String,
+}
+
+impl OpenAiSpeechSynthesizer {
+    pub fn new(api_url: &str, api_key: &str, model: &str, voice: &str) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+            voice: voice.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechSynthesizer for OpenAiSpeechSynthesizer {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    async fn synthesize(&self, text: &str) -> Result<SynthesizedSpeech> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "voice": self.voice,
+            "input": text,
+            "response_format": "opus",
+        });
+
+        let client = crate::config::build_runtime_proxy_client("tts.openai");
+        let resp = client
+            .post(&self.api_url)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .context("Failed to send speech synthesis request")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            bail!(
+                "Speech API error ({status}): {}",
+                crate::providers::sanitize_api_error(&body)
+            );
+        }
+
+        let audio = resp.bytes().await?.to_vec();
+        if audio.is_empty() {
+            bail!("Speech API returned empty audio");
+        }
+        Ok(SynthesizedSpeech {
+            audio,
+            extension: "ogg",
+        })
+    }
+}
+
+// ── Local subprocess backends ────────────────────────────────
+
+pub struct PiperSpeechSynthesizer {
+    binary: String,
+    model: String,
+}
+
+impl PiperSpeechSynthesizer {
+    pub fn new(binary: &str, model: &str) -> Self {
+        Self {
+            binary: binary.to_string(),
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechSynthesizer for PiperSpeechSynthesizer {
+    fn name(&self) -> &str {
+        "piper"
+    }
+
+    async fn synthesize(&self, text: &str) -> Result<SynthesizedSpeech> {
+        let output = tempfile::Builder::new()
+            .prefix("zeroclaw-tts-")
+            .suffix(".wav")
+            .tempfile()?;
+        let output_path = output.path().to_string_lossy().to_string();
+
+        run_with_stdin(
+            &self.binary,
+            &["--model", &self.model, "--output_file", &output_path],
+            text,
+        )
+        .await?;
+
+        let wav = tokio::fs::read(output.path()).await?;
+        if wav.is_empty() {
+            bail!("Piper produced no audio");
+        }
+        Ok(transcode_wav_to_opus(wav).await)
+    }
+}
+
+pub struct EspeakSpeechSynthesizer {
+    binary: String,
+    voice: String,
+}
+
+impl EspeakSpeechSynthesizer {
+    pub fn new(binary: &str, voice: &str) -> Self {
+        Self {
+            binary: binary.to_string(),
+            voice: voice.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechSynthesizer for EspeakSpeechSynthesizer {
+    fn name(&self) -> &str {
+        "espeak"
+    }
+
+    async fn synthesize(&self, text: &str) -> Result<SynthesizedSpeech> {
+        let mut args = vec!["--stdin", "--stdout"];
+        if !self.voice.trim().is_empty() {
+            args.extend(["-v", self.voice.trim()]);
+        }
+        let wav = run_with_stdin(&self.binary, &args, text).await?;
+        if wav.is_empty() {
+            bail!("espeak produced no audio");
+        }
+        Ok(transcode_wav_to_opus(wav).await)
+    }
+}
+
+/// Run `binary args…`, feed `input` on stdin and return stdout.
+async fn run_with_stdin(binary: &str, args: &[&str], input: &str) -> Result<Vec<u8>> {
+    let mut child = tokio::process::Command::new(binary)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start '{binary}'"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).await?;
+    }
+
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "'{binary}' exited with {}: {}",
+            output.status,
+            crate::util::truncate_with_ellipsis(stderr.trim(), 300)
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Voice notes on Telegram and WhatsApp must be Ogg/Opus. Transcode with
+/// `ffmpeg` when it is installed; otherwise keep the WAV, which channels
+/// deliver as a regular audio file.
+async fn transcode_wav_to_opus(wav: Vec<u8>) -> SynthesizedSpeech {
+    let args = [
+        "-hide_banner",
+        "-loglevel",
+        "error",
+        "-f",
+        "wav",
+        "-i",
+        "pipe:0",
+        "-c:a",
+        "libopus",
+        "-b:a",
+        "32k",
+        "-f",
+        "ogg",
+        "pipe:1",
+    ];
+    let transcoded = match tokio::process::Command::new("ffmpeg")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(mut child) => {
+            let stdin = child.stdin.take();
+            let input = wav.clone();
+            // Write concurrently so ffmpeg never blocks on a full stdout pipe.
+            let writer = tokio::spawn(async move {
+                if let Some(mut stdin) = stdin {
+                    let _ = stdin.write_all(&input).await;
+                }
+            });
+            let output = child.wait_with_output().await;
+            let _ = writer.await;
+            output
+                .ok()
+                .filter(|output| output.status.success() && !output.stdout.is_empty())
+                .map(|output| output.stdout)
+        }
+        Err(_) => None,
+    };
+
+    match transcoded {
+        Some(audio) => SynthesizedSpeech {
+            audio,
+            extension: "ogg",
+        },
+        None => {
+            tracing::debug!("ffmpeg unavailable or failed; sending WAV voice reply");
+            SynthesizedSpeech {
+                audio: wav,
+                extension: "wav",
+            }
+        }
+    }
+}
+
+// ── Factory ──────────────────────────────────────────────────
+
+pub fn create_speech_synthesizer(config: &TtsConfig) -> Result<Box<dyn SpeechSynthesizer>> {
+    match config.provider.trim() {
+        "openai" => {
+            let api_key = config
+                .api_key
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(ToOwned::to_owned)
+                .or_else(|| {
+                    std::env::var("OPENAI_API_KEY")
+                        .ok()
+                        .map(|value| value.trim().to_string())
+                        .filter(|value| !value.is_empty())
+                })
+                .context(
+                    "Missing speech API key: set [tts].api_key or OPENAI_API_KEY environment variable",
+                )?;
+            Ok(Box::new(OpenAiSpeechSynthesizer::new(
+                &config.api_url,
+                &api_key,
+                &config.model,
+                &config.voice,
+            )))
+        }
+        "piper" => {
+            let model = config
+                .piper_model
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .context("The piper TTS backend requires [tts].piper_model")?;
+            Ok(Box::new(PiperSpeechSynthesizer::new(
+                &config.piper_path,
+                model,
+            )))
+        }
+        "espeak" => Ok(Box::new(EspeakSpeechSynthesizer::new(
+            &config.espeak_path,
+            &config.voice,
+        ))),
+        other => bail!("Unknown TTS provider '{other}' — supported: openai, piper, espeak"),
+    }
+}
+
+// ── Reply orchestration ──────────────────────────────────────
+
+/// Whether `channel` can deliver voice notes.
+pub fn supports_voice_replies(channel: &str) -> bool {
+    VOICE_CHANNELS.contains(&channel)
+}
+
+/// System-prompt addition telling the model how to request a voice reply.
+pub fn voice_prompt_hint(config: &TtsConfig, channel: &str) -> Option<&'static str> {
+    if !config.enabled || !supports_voice_replies(channel) {
+        return None;
+    }
+    Some(
+        "\n\nVoice replies: this channel can answer with a voice note. Add a bare [VOICE] \
+         marker anywhere in your reply to have it spoken (for example when the user asks to \
+         hear the answer). Keep spoken replies short and free of tables or code.",
+    )
+}
+
+/// Inbound content produced from a transcribed voice message (`[Voice] …`).
+pub fn is_voice_message(content: &str) -> bool {
+    content
+        .lines()
+        .any(|line| line.trim_start().starts_with("[Voice] "))
+}
+
+/// Remove bare `[VOICE]` markers; reports whether any were present.
+pub fn take_voice_request(reply: &str) -> (String, bool) {
+    if !reply.contains(VOICE_REQUEST_MARKER) {
+        return (reply.to_string(), false);
+    }
+    let cleaned = reply
+        .replace(VOICE_REQUEST_MARKER, "")
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    (cleaned.trim().to_string(), true)
+}
+
+/// Plain text suitable for speaking: no code blocks, attachment markers or
+/// markdown punctuation, capped at `max_chars` (cut at a sentence end when possible).
+pub fn speakable_text(reply: &str, max_chars: usize) -> String {
+    let mut spoken = String::with_capacity(reply.len());
+    let mut in_code_block = false;
+    for line in reply.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        let line = strip_attachment_markers(line);
+        let line: String = line
+            .chars()
+            .filter(|c| !matches!(c, '*' | '_' | '`' | '#' | '>' | '|'))
+            .collect();
+        let line = line.trim();
+        if !line.is_empty() {
+            if !spoken.is_empty() {
+                spoken.push(' ');
+            }
+            spoken.push_str(line);
+        }
+    }
+
+    if max_chars == 0 || spoken.chars().count() <= max_chars {
+        return spoken;
+    }
+    let cut = spoken
+        .char_indices()
+        .nth(max_chars)
+        .map_or(spoken.len(), |(idx, _)| idx);
+    let head = &spoken[..cut];
+    let sentence_end = head
+        .rfind(['.', '!', '?'])
+        .filter(|idx| *idx > cut / 2)
+        .map(|idx| idx + 1);
+    head[..sentence_end.unwrap_or(cut)].trim().to_string()
+}
+
+fn strip_attachment_markers(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('[') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open..];
+        let is_marker = after.find(']').is_some_and(|close| {
+            after[1..close].split_once(':').is_some_and(|(kind, _)| {
+                matches!(
+                    kind.trim().to_ascii_uppercase().as_str(),
+                    "IMAGE" | "PHOTO" | "DOCUMENT" | "FILE" | "VIDEO" | "AUDIO" | "VOICE"
+                )
+            })
+        });
+        if is_marker {
+            let close = after.find(']').unwrap_or(after.len() - 1);
+            rest = &after[close + 1..];
+        } else {
+            out.push('[');
+            rest = &after[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Attachment marker for a synthesized file: `[VOICE:…]` for formats chat
+/// apps play as voice notes, `[AUDIO:…]` otherwise.
+pub fn voice_marker(path: &Path) -> String {
+    let is_voice_format = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            matches!(
+                ext.to_ascii_lowercase().as_str(),
+                "ogg" | "oga" | "opus" | "mp3" | "m4a"
+            )
+        });
+    let kind = if is_voice_format { "VOICE" } else { "AUDIO" };
+    format!("[{kind}:{}]", path.display())
+}
+
+/// Extract `[VOICE:<path>]` / `[AUDIO:<path>]` markers that point at local
+/// files, for channels that upload audio themselves. Returns the remaining
+/// text and the file paths in order.
+pub fn split_audio_markers(content: &str) -> (String, Vec<PathBuf>) {
+    let mut cleaned = String::with_capacity(content.len());
+    let mut files = Vec::new();
+    let mut rest = content;
+    while let Some(open) = rest.find('[') {
+        cleaned.push_str(&rest[..open]);
+        let after = &rest[open..];
+        let file = after.find(']').and_then(|close| {
+            let (kind, target) = after[1..close].split_once(':')?;
+            let kind = kind.trim().to_ascii_uppercase();
+            let target = target.trim();
+            let path = Path::new(target);
+            ((kind == "VOICE" || kind == "AUDIO") && path.is_absolute() && path.is_file())
+                .then(|| (close, path.to_path_buf()))
+        });
+        if let Some((close, path)) = file {
+            files.push(path);
+            rest = &after[close + 1..];
+        } else {
+            cleaned.push('[');
+            rest = &after[1..];
+        }
+    }
+    cleaned.push_str(rest);
+    (cleaned.trim().to_string(), files)
+}
+
+/// Synthesize `text` and store it under `<workspace>/voice_replies/`.
+pub async fn synthesize_to_file(
+    config: &TtsConfig,
+    workspace_dir: &Path,
+    text: &str,
+) -> Result<PathBuf> {
+    let synthesizer = create_speech_synthesizer(config)?;
+    let speech = synthesizer.synthesize(text).await?;
+
+    let dir = workspace_dir.join(VOICE_REPLY_DIR);
+    tokio::fs::create_dir_all(&dir).await?;
+    prune_old_replies(&dir).await;
+
+    let path = dir.join(format!(
+        "reply-{}.{}",
+        uuid::Uuid::new_v4(),
+        speech.extension
+    ));
+    tokio::fs::write(&path, &speech.audio).await?;
+    Ok(path)
+}
+
+async fn prune_old_replies(dir: &Path) {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let expired = entry
+            .metadata()
+            .await
+            .ok()
+            .and_then(|meta| meta.modified().ok())
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > VOICE_REPLY_RETENTION);
+        if expired {
+            let _ = tokio::fs::remove_file(entry.path()).await;
+        }
+    }
+}
+
+/// Outcome of [`prepare_voice_reply`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoiceReply {
+    /// Reply text with `[VOICE]` request markers removed.
+    pub text: String,
+    /// `[VOICE:<path>]` / `[AUDIO:<path>]` marker for the synthesized note.
+    pub marker: Option<String>,
+}
+
+/// Decide whether `reply` should be spoken and synthesize it if so.
+///
+/// Synthesis failures are logged and the reply falls back to text only.
+pub async fn prepare_voice_reply(
+    config: &TtsConfig,
+    channel: &str,
+    inbound: &str,
+    reply: &str,
+    workspace_dir: &Path,
+) -> VoiceReply {
+    let (text, requested) = take_voice_request(reply);
+    let wanted = match config.reply_mode {
+        TtsReplyMode::Always => true,
+        TtsReplyMode::Mirror => requested || is_voice_message(inbound),
+        TtsReplyMode::Requested => requested,
+    };
+    if !config.enabled || !wanted || !supports_voice_replies(channel) {
+        return VoiceReply { text, marker: None };
+    }
+
+    let spoken = speakable_text(&text, config.max_chars);
+    if spoken.is_empty() {
+        return VoiceReply { text, marker: None };
+    }
+
+    match synthesize_to_file(config, workspace_dir, &spoken).await {
+        Ok(path) => VoiceReply {
+            text,
+            marker: Some(voice_marker(&path)),
+        },
+        Err(e) => {
+            tracing::warn!(channel, "Voice reply synthesis failed: {e}");
+            VoiceReply { text, marker: None }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factory_rejects_unknown_and_incomplete_backends() {
+        let config = TtsConfig {
+            provider: "nope".into(),
+            ..TtsConfig::default()
+        };
+        assert!(create_speech_synthesizer(&config).is_err());
+
+        let config = TtsConfig {
+            provider: "piper".into(),
+            ..TtsConfig::default()
+        };
+        let err = create_speech_synthesizer(&config).err().unwrap();
+        assert!(err.to_string().contains("piper_model"));
+
+        let config = TtsConfig {
+            provider: "espeak".into(),
+            ..TtsConfig::default()
+        };
+        assert_eq!(create_speech_synthesizer(&config).unwrap().name(), "espeak");
+
+        let config = TtsConfig {
+            api_key: Some("sk-test".into()),
+            ..TtsConfig::default()
+        };
+        assert_eq!(create_speech_synthesizer(&config).unwrap().name(), "openai");
+    }
+
+    #[test]
+    fn voice_request_marker_is_removed() {
+        let (text, requested) = take_voice_request("Sure! [VOICE]\nHere you go.");
+        assert!(requested);
+        assert_eq!(text, "Sure!\nHere you go.");
+
+        let (text, requested) = take_voice_request("See [VOICE:/tmp/a.ogg]");
+        assert!(!requested);
+        assert_eq!(text, "See [VOICE:/tmp/a.ogg]");
+    }
+
+    #[test]
+    fn detects_transcribed_voice_messages() {
+        assert!(is_voice_message("[Voice] what's the weather"));
+        assert!(is_voice_message("> quoted\n\n[Voice] and now?"));
+        assert!(!is_voice_message("plain text"));
+    }
+
+    #[test]
+    fn speakable_text_strips_markup_and_truncates_at_sentence() {
+        let reply = "**Done.** Saved `notes.md`.\n```rust\nfn main() {}\n```\n[IMAGE:/tmp/x.png] See chart.";
+        assert_eq!(speakable_text(reply, 0), "Done. Saved notes.md. See chart.");
+        assert_eq!(
+            speakable_text("First sentence here. Second sentence is longer.", 30),
+            "First sentence here."
+        );
+    }
+
+    #[test]
+    fn voice_marker_depends_on_format() {
+        assert_eq!(
+            voice_marker(Path::new("/w/voice_replies/a.ogg")),
+            "[VOICE:/w/voice_replies/a.ogg]"
+        );
+        assert_eq!(
+            voice_marker(Path::new("/w/voice_replies/a.wav")),
+            "[AUDIO:/w/voice_replies/a.wav]"
+        );
+    }
+
+    #[test]
+    fn split_audio_markers_extracts_existing_files_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("reply.ogg");
+        std::fs::write(&file, b"ogg").unwrap();
+        let content = format!(
+            "Hello [VOICE:{}] and [AUDIO:/missing/file.mp3] [IMAGE:/x.png]",
+            file.display()
+        );
+        let (text, files) = split_audio_markers(&content);
+        assert_eq!(files, vec![file]);
+        assert_eq!(text, "Hello  and [AUDIO:/missing/file.mp3] [IMAGE:/x.png]");
+    }
+
+    #[tokio::test]
+    async fn prepare_voice_reply_respects_mode_and_channel() {
+        let tmp = tempfile::tempdir().unwrap();
+        let disabled = TtsConfig::default();
+        let reply = prepare_voice_reply(
+            &disabled,
+            "telegram",
+            "[Voice] hi",
+            "Hi [VOICE]",
+            tmp.path(),
+        )
+        .await;
+        assert_eq!(reply.text, "Hi");
+        assert!(reply.marker.is_none());
+
+        let requested_only = TtsConfig {
+            enabled: true,
+            provider: "nope".into(),
+            reply_mode: TtsReplyMode::Requested,
+            ..TtsConfig::default()
+        };
+        let reply =
+            prepare_voice_reply(&requested_only, "slack", "hi", "Hi [VOICE]", tmp.path()).await;
+        assert!(reply.marker.is_none());
+
+        // Synthesis errors fall back to text-only replies.
+        let reply =
+            prepare_voice_reply(&requested_only, "telegram", "hi", "Hi [VOICE]", tmp.path()).await;
+        assert_eq!(reply.text, "Hi");
+        assert!(reply.marker.is_none());
+    }
+}
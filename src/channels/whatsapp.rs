@@ -58,6 +58,97 @@ impl WhatsAppChannel {
         &self.verify_token
     }
 
+    /// WhatsApp Cloud API: POST to /v18.0/{phone_number_id}/messages
+    async fn post_message(&self, body: &serde_json::Value) -> anyhow::Result<()> {
+        let url = format!(
+            "https://graph.facebook.com/v18.0/{}/messages",
+            self.endpoint_id
+        );
+        ensure_https(&url)?;
+
+        let resp = self
+            .http_client()
+            .post(&url)
+            .bearer_auth(&self.access_token)
+            .header("Content-Type", "application/json")
+            .json(body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let error_body = resp.text().await.unwrap_or_default();
+            let sanitized = crate::providers::sanitize_api_error(&error_body);
+            tracing::error!("WhatsApp send failed: {status} — {sanitized}");
+            anyhow::bail!("WhatsApp API error: {status}");
+        }
+
+        Ok(())
+    }
+
+    /// Upload a local audio file and return its media ID.
+    async fn upload_audio(&self, path: &std::path::Path) -> anyhow::Result<String> {
+        let url = format!(
+            "https://graph.facebook.com/v18.0/{}/media",
+            self.endpoint_id
+        );
+        ensure_https(&url)?;
+
+        let mime = match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("ogg" | "oga" | "opus") => "audio/ogg",
+            Some("mp3") => "audio/mpeg",
+            Some("m4a") => "audio/mp4",
+            Some("amr") => "audio/amr",
+            Some("aac") => "audio/aac",
+            _ => anyhow::bail!(
+                "WhatsApp does not accept audio file '{}' (use ogg/opus, mp3, m4a, aac or amr)",
+                path.display()
+            ),
+        };
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("voice.ogg")
+            .to_string();
+        let data = tokio::fs::read(path).await?;
+        let form = reqwest::multipart::Form::new()
+            .text("messaging_product", "whatsapp")
+            .text("type", mime)
+            .part(
+                "file",
+                reqwest::multipart::Part::bytes(data)
+                    .file_name(file_name)
+                    .mime_str(mime)?,
+            );
+
+        let resp = self
+            .http_client()
+            .post(&url)
+            .bearer_auth(&self.access_token)
+            .multipart(form)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let error_body = resp.text().await.unwrap_or_default();
+            let sanitized = crate::providers::sanitize_api_error(&error_body);
+            tracing::error!("WhatsApp media upload failed: {status} — {sanitized}");
+            anyhow::bail!("WhatsApp media upload error: {status}");
+        }
+
+        let body: serde_json::Value = resp.json().await?;
+        body.get("id")
+            .and_then(serde_json::Value::as_str)
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| anyhow::anyhow!("WhatsApp media upload returned no id"))
+    }
+
     /// Parse an incoming webhook payload from Meta and extract messages
     pub fn parse_webhook_payload(&self, payload: &serde_json::Value) -> Vec<ChannelMessage> {
         let mut messages = Vec::new();
@@ -158,46 +249,39 @@ impl Channel for WhatsAppChannel {
     }
 
     async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
-        // WhatsApp Cloud API: POST to /v18.0/{phone_number_id}/messages
-        let url = format!(
-            "https://graph.facebook.com/v18.0/{}/messages",
-            self.endpoint_id
-        );
-
         // Normalize recipient (remove leading + if present for API)
         let to = message
             .recipient
             .strip_prefix('+')
             .unwrap_or(&message.recipient);
 
-        let body = serde_json::json!({
-            "messaging_product": "whatsapp",
-            "recipient_type": "individual",
-            "to": to,
-            "type": "text",
-            "text": {
-                "preview_url": false,
-                "body": message.content
-            }
-        });
-
-        ensure_https(&url)?;
-
-        let resp = self
-            .http_client()
-            .post(&url)
-            .bearer_auth(&self.access_token)
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+        // Voice replies arrive as `[VOICE:<path>]` markers and are uploaded as audio.
+        let (text, audio_files) = super::tts::split_audio_markers(&message.content);
+
+        if !text.is_empty() {
+            self.post_message(&serde_json::json!({
+                "messaging_product": "whatsapp",
+                "recipient_type": "individual",
+                "to": to,
+                "type": "text",
+                "text": {
+                    "preview_url": false,
+                    "body": text
+                }
+            }))
             .await?;
+        }
 
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let error_body = resp.text().await.unwrap_or_default();
-            let sanitized = crate::providers::sanitize_api_error(&error_body);
-            tracing::error!("WhatsApp send failed: {status} — {sanitized}");
-            anyhow::bail!("WhatsApp API error: {status}");
+        for path in &audio_files {
+            let media_id = self.upload_audio(path).await?;
+            self.post_message(&serde_json::json!({
+                "messaging_product": "whatsapp",
+                "recipient_type": "individual",
+                "to": to,
+                "type": "audio",
+                "audio": { "id": media_id }
+            }))
+            .await?;
         }
 
         Ok(())
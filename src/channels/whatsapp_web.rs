@@ -44,6 +44,8 @@ enum WaAttachmentKind {
     Document,
     Video,
     Audio,
+    /// Audio sent as a push-to-talk voice note.
+    Voice,
 }
 
 #[cfg(feature = "whatsapp-web")]
@@ -55,6 +57,7 @@ impl WaAttachmentKind {
             "DOCUMENT" => Some(Self::Document),
             "VIDEO" => Some(Self::Video),
             "AUDIO" => Some(Self::Audio),
+            "VOICE" => Some(Self::Voice),
             _ => None,
         }
     }
@@ -65,7 +68,7 @@ impl WaAttachmentKind {
             Self::Image => wa_rs_core::download::MediaType::Image,
             Self::Document => wa_rs_core::download::MediaType::Document,
             Self::Video => wa_rs_core::download::MediaType::Video,
-            Self::Audio => wa_rs_core::download::MediaType::Audio,
+            Self::Audio | Self::Voice => wa_rs_core::download::MediaType::Audio,
         }
     }
 }
@@ -302,7 +305,13 @@ impl WhatsAppWebChannel {
 
         let data = tokio::fs::read(path).await?;
         let file_len = data.len() as u64;
-        let mimetype = mime_from_path(path).to_string();
+        let mimetype = match attachment.kind {
+            // Voice notes only render as such with the Opus codec parameter.
+            WaAttachmentKind::Voice if mime_from_path(path) == "audio/ogg" => {
+                "audio/ogg; codecs=opus".to_string()
+            }
+            _ => mime_from_path(path).to_string(),
+        };
 
         tracing::info!(
             "WhatsApp Web: uploading {:?} ({} bytes, {})",
@@ -363,7 +372,7 @@ impl WhatsAppWebChannel {
                 })),
                 ..Default::default()
             },
-            WaAttachmentKind::Audio => wa_rs_proto::whatsapp::Message {
+            WaAttachmentKind::Audio | WaAttachmentKind::Voice => wa_rs_proto::whatsapp::Message {
                 audio_message: Some(Box::new(wa_rs_proto::whatsapp::message::AudioMessage {
                     url: Some(upload.url),
                     direct_path: Some(upload.direct_path),
@@ -372,6 +381,7 @@ impl WhatsAppWebChannel {
                     file_sha256: Some(upload.file_sha256),
                     file_length: Some(upload.file_length),
                     mimetype: Some(mimetype),
+                    ptt: Some(matches!(attachment.kind, WaAttachmentKind::Voice)),
                     ..Default::default()
                 })),
                 ..Default::default()
@@ -888,6 +898,15 @@ mod tests {
         assert!(matches!(attachments[0].kind, WaAttachmentKind::Image));
     }
 
+    #[test]
+    #[cfg(feature = "whatsapp-web")]
+    fn parse_wa_markers_voice() {
+        let (text, attachments) = parse_wa_attachment_markers("[VOICE:/tmp/reply.ogg]");
+        assert!(text.is_empty());
+        assert_eq!(attachments.len(), 1);
+        assert!(matches!(attachments[0].kind, WaAttachmentKind::Voice));
+    }
+
     #[test]
     #[cfg(feature = "whatsapp-web")]
     fn parse_wa_markers_multiple() {
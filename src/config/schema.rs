@@ -43,6 +43,7 @@ const SUPPORTED_PROXY_SERVICE_KEYS: &[&str] = &[
     "memory.embeddings",
     "tunnel.custom",
     "transcription.groq",
+    "tts.openai",
 ];
 
 const SUPPORTED_PROXY_SERVICE_SELECTORS: &[&str] = &[
@@ -52,6 +53,7 @@ const SUPPORTED_PROXY_SERVICE_SELECTORS: &[&str] = &[
     "memory.*",
     "tunnel.*",
     "transcription.*",
+    "tts.*",
 ];
 
 static RUNTIME_PROXY_CONFIG: OnceLock<RwLock<ProxyConfig>> = OnceLock::new();
@@ -165,6 +167,15 @@ pub struct Config {
     #[serde(default)]
     pub query_classification: QueryClassificationConfig,
 
+    /// Adaptive model routing — learn which `[[model_routes]]` candidate to use
+    /// for a hint from observed latency, errors and cost (`[adaptive_routing]`).
+    #[serde(default)]
+    pub adaptive_routing: AdaptiveRoutingConfig,
+
+    /// Local Ollama model lifecycle: auto-pull, keep-alive and preloading (`[ollama]`).
+    #[serde(default)]
+    pub ollama: OllamaConfig,
+
     /// Heartbeat configuration for periodic health pings (`[heartbeat]`).
     #[serde(default)]
     pub heartbeat: HeartbeatConfig,
@@ -270,6 +281,10 @@ pub struct Config {
     #[serde(default)]
     pub transcription: TranscriptionConfig,
 
+    /// Text-to-speech configuration for voice replies on channels (`[tts]`).
+    #[serde(default)]
+    pub tts: TtsConfig,
+
     /// Inter-process agent communication (`[agents_ipc]`).
     #[serde(default)]
     pub agents_ipc: AgentsIpcConfig,
@@ -278,6 +293,10 @@ pub struct Config {
     #[serde(default, alias = "mcpServers")]
     pub mcp: McpConfig,
 
+    /// Per-tool execution policies keyed by tool name (`[tools.<name>]`).
+    #[serde(default)]
+    pub tools: HashMap<String, ToolPolicyConfig>,
+
     /// Vision support override for the active provider/model.
     /// - `None` (default): use provider's built-in default
     /// - `Some(true)`: force vision support on (e.g. Ollama running llava)
@@ -320,6 +339,10 @@ pub struct ProviderConfig {
     /// (e.g. OpenAI Codex `/responses` reasoning effort).
     #[serde(default)]
     pub reasoning_level: Option<String>,
+    /// Token budget for extended thinking (Anthropic, Bedrock Claude, Gemini).
+    /// Setting it enables thinking unless `runtime.reasoning_enabled = false`.
+    #[serde(default)]
+    pub thinking_budget_tokens: Option<u32>,
     /// Optional transport override for providers that support multiple transports.
     /// Supported values: "auto", "websocket", "sse".
     ///
@@ -601,12 +624,41 @@ fn default_transcription_max_duration_secs() -> u64 {
     120
 }
 
-/// Voice transcription configuration (Whisper API via Groq).
+fn default_transcription_ffmpeg_path() -> String {
+    "ffmpeg".into()
+}
+
+fn default_transcription_chunk_secs() -> u64 {
+    300
+}
+
+fn default_transcription_timeout_secs() -> u64 {
+    300
+}
+
+/// Where voice messages are transcribed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionBackend {
+    /// Remote Whisper-compatible HTTP API (`api_url`).
+    #[default]
+    Api,
+    /// Local whisper.cpp binary (`whisper-cli`) with a ggml model file.
+    WhisperCpp,
+    /// Local faster-whisper CLI (`whisper-ctranslate2`).
+    FasterWhisper,
+}
+
+/// Voice transcription configuration (Whisper API via Groq, or a local
+/// whisper.cpp / faster-whisper binary).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TranscriptionConfig {
     /// Enable voice transcription for channels that support it.
     #[serde(default)]
     pub enabled: bool,
+    /// Transcription backend: `api`, `whisper_cpp` or `faster_whisper`.
+    #[serde(default)]
+    pub backend: TranscriptionBackend,
     /// API key used for transcription requests.
     ///
     /// If unset, runtime falls back to `GROQ_API_KEY` for backward compatibility.
@@ -624,6 +676,23 @@ pub struct TranscriptionConfig {
     /// Maximum voice duration in seconds (messages longer than this are skipped).
     #[serde(default = "default_transcription_max_duration_secs")]
     pub max_duration_secs: u64,
+    /// Local backend binary. Defaults to `whisper-cli` (whisper.cpp) or
+    /// `whisper-ctranslate2` (faster-whisper).
+    #[serde(default)]
+    pub local_binary: Option<String>,
+    /// Local model: ggml model file for whisper.cpp (required), or model
+    /// size/path for faster-whisper (default `small`).
+    #[serde(default)]
+    pub local_model: Option<String>,
+    /// ffmpeg binary used to convert audio to 16 kHz mono WAV for local backends.
+    #[serde(default = "default_transcription_ffmpeg_path")]
+    pub ffmpeg_path: String,
+    /// Local backends split audio into chunks of this many seconds.
+    #[serde(default = "default_transcription_chunk_secs")]
+    pub chunk_secs: u64,
+    /// Overall time limit for one local transcription.
+    #[serde(default = "default_transcription_timeout_secs")]
+    pub timeout_secs: u64,
 }
 
 impl Default for TranscriptionConfig {
@@ -635,6 +704,123 @@ impl Default for TranscriptionConfig {
             model: default_transcription_model(),
             language: None,
             max_duration_secs: default_transcription_max_duration_secs(),
+            backend: TranscriptionBackend::default(),
+            local_binary: None,
+            local_model: None,
+            ffmpeg_path: default_transcription_ffmpeg_path(),
+            chunk_secs: default_transcription_chunk_secs(),
+            timeout_secs: default_transcription_timeout_secs(),
+        }
+    }
+}
+
+// ── Text-to-speech ───────────────────────────────────────────────
+
+fn default_tts_provider() -> String {
+    "openai".into()
+}
+
+fn default_tts_api_url() -> String {
+    "https://api.openai.com/v1/audio/speech".into()
+}
+
+fn default_tts_model() -> String {
+    "gpt-4o-mini-tts".into()
+}
+
+fn default_tts_voice() -> String {
+    "alloy".into()
+}
+
+fn default_tts_piper_path() -> String {
+    "piper".into()
+}
+
+fn default_tts_espeak_path() -> String {
+    "espeak-ng".into()
+}
+
+fn default_tts_max_chars() -> usize {
+    1500
+}
+
+/// When channels answer with a voice note.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsReplyMode {
+    /// Reply with voice when the user sent a voice message or the reply asks for `[VOICE]`.
+    #[default]
+    Mirror,
+    /// Reply with voice only when the reply asks for it with a `[VOICE]` marker.
+    Requested,
+    /// Always add a voice note to replies.
+    Always,
+}
+
+/// Text-to-speech configuration (`[tts]`).
+///
+/// ```toml
+/// [tts]
+/// enabled = true
+/// provider = "piper"          # "openai" | "piper" | "espeak"
+/// piper_model = "/opt/piper/en_US-lessac-medium.onnx"
+/// reply_mode = "mirror"       # "mirror" | "requested" | "always"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TtsConfig {
+    /// Enable voice replies on channels that support them.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Speech backend: `openai` (any OpenAI-compatible `/audio/speech`), `piper` or `espeak`.
+    #[serde(default = "default_tts_provider")]
+    pub provider: String,
+    /// API key for the `openai` backend. Falls back to `OPENAI_API_KEY`.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Speech endpoint URL for the `openai` backend.
+    #[serde(default = "default_tts_api_url")]
+    pub api_url: String,
+    /// Speech model for the `openai` backend.
+    #[serde(default = "default_tts_model")]
+    pub model: String,
+    /// Voice name for the `openai` backend, or the voice passed to espeak (`-v`).
+    #[serde(default = "default_tts_voice")]
+    pub voice: String,
+    /// Piper binary.
+    #[serde(default = "default_tts_piper_path")]
+    pub piper_path: String,
+    /// Piper voice model (`.onnx`). Required for the `piper` backend.
+    #[serde(default)]
+    pub piper_model: Option<String>,
+    /// espeak binary (`espeak-ng` or `espeak`).
+    #[serde(default = "default_tts_espeak_path")]
+    pub espeak_path: String,
+    /// When to answer with a voice note.
+    #[serde(default)]
+    pub reply_mode: TtsReplyMode,
+    /// Keep the text reply alongside the voice note.
+    #[serde(default = "default_true")]
+    pub include_text: bool,
+    /// Replies longer than this many characters are truncated before synthesis.
+    #[serde(default = "default_tts_max_chars")]
+    pub max_chars: usize,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: default_tts_provider(),
+            api_key: None,
+            api_url: default_tts_api_url(),
+            model: default_tts_model(),
+            voice: default_tts_voice(),
+            piper_path: default_tts_piper_path(),
+            piper_model: None,
+            espeak_path: default_tts_espeak_path(),
+            reply_mode: TtsReplyMode::default(),
+            include_text: true,
+            max_chars: default_tts_max_chars(),
         }
     }
 }
@@ -693,6 +879,47 @@ pub struct McpConfig {
     pub servers: Vec<McpServerConfig>,
 }
 
+// ── Tool execution policy ───────────────────────────────────────
+
+fn default_tool_retry_backoff_ms() -> u64 {
+    500
+}
+
+/// Per-tool execution policy (`[tools.<name>]` section).
+///
+/// Enforced around every call to the named tool, on top of any timeout the
+/// tool applies itself. Tools without a section run unrestricted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ToolPolicyConfig {
+    /// Abort a single attempt after this many seconds.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Retries after a transient failure (timeout, connection, 429 or 5xx error).
+    #[serde(default)]
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds; doubles on each further retry.
+    #[serde(default = "default_tool_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// Maximum invocations of this tool running at the same time.
+    #[serde(default)]
+    pub max_concurrent: Option<usize>,
+    /// Reuse a successful result for identical arguments for this many seconds (0 = off).
+    #[serde(default)]
+    pub cache_ttl_secs: u64,
+}
+
+impl Default for ToolPolicyConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: None,
+            max_retries: 0,
+            retry_backoff_ms: default_tool_retry_backoff_ms(),
+            max_concurrent: None,
+            cache_ttl_secs: 0,
+        }
+    }
+}
+
 // ── Agents IPC ──────────────────────────────────────────────────
 
 fn default_agents_ipc_db_path() -> String {
@@ -828,12 +1055,166 @@ pub struct AgentConfig {
     /// Set to `0` to disable. Default: `3`.
     #[serde(default = "default_loop_detection_failure_streak")]
     pub loop_detection_failure_streak: usize,
+    /// Context window (tokens) used to budget prompts and history.
+    /// `0` = detect from the model name, falling back to `32768`. Default: `0`.
+    #[serde(default)]
+    pub context_window_tokens: usize,
+    /// Tokens reserved for the model's reply when budgeting prompts. Default: `4096`.
+    #[serde(default = "default_agent_reserved_output_tokens")]
+    pub reserved_output_tokens: usize,
+    /// Per-model context windows in tokens, keyed by model id.
+    /// Takes precedence over `context_window_tokens` and built-in model metadata.
+    #[serde(default)]
+    pub model_context_windows: HashMap<String, usize>,
+    /// Tool outputs longer than this many characters are stored as workspace
+    /// artifacts and replaced by a preview plus an `artifact:<id>` handle that
+    /// the `artifact_read` tool can page through. Set to `0` to disable.
+    /// Default: `16000`.
+    #[serde(default = "default_agent_tool_output_spill_chars")]
+    pub tool_output_spill_chars: usize,
+    /// Characters of head/tail preview kept in context for a spilled output.
+    /// Default: `2000`.
+    #[serde(default = "default_agent_tool_output_preview_chars")]
+    pub tool_output_preview_chars: usize,
+    /// Maximum number of stored tool-output artifacts; the oldest are pruned.
+    /// Default: `200`.
+    #[serde(default = "default_agent_tool_output_artifact_limit")]
+    pub tool_output_artifact_limit: usize,
+    /// Plan-and-execute mode (`[agent.plan_execute]`).
+    #[serde(default)]
+    pub plan_execute: PlanExecuteConfig,
+    /// Retrieval-based tool selection (`[agent.tool_retrieval]`).
+    #[serde(default)]
+    pub tool_retrieval: ToolRetrievalConfig,
+}
+
+/// Plan-and-execute agent mode.
+///
+/// A planner model writes an explicit step plan, a cheaper executor model runs
+/// each step through the tool loop, and the planner re-plans when a step fails
+/// or the executor reports that the plan no longer fits. Both models are picked
+/// through `[[model_routes]]` hints; a hint without a route uses the default model.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlanExecuteConfig {
+    /// Plan every CLI agent turn before executing it. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Model route hint for the planner. Default: `"planner"`.
+    #[serde(default = "default_plan_execute_planner_hint")]
+    pub planner_hint: String,
+    /// Model route hint for the step executor. Default: `"executor"`.
+    #[serde(default = "default_plan_execute_executor_hint")]
+    pub executor_hint: String,
+    /// Maximum number of steps in a plan. Default: `8`.
+    #[serde(default = "default_plan_execute_max_steps")]
+    pub max_steps: usize,
+    /// Re-plans allowed per turn before giving up. Default: `2`.
+    #[serde(default = "default_plan_execute_max_replans")]
+    pub max_replans: usize,
+}
+
+fn default_plan_execute_planner_hint() -> String {
+    "planner".into()
+}
+
+fn default_plan_execute_executor_hint() -> String {
+    "executor".into()
+}
+
+fn default_plan_execute_max_steps() -> usize {
+    8
+}
+
+fn default_plan_execute_max_replans() -> usize {
+    2
+}
+
+impl Default for PlanExecuteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            planner_hint: default_plan_execute_planner_hint(),
+            executor_hint: default_plan_execute_executor_hint(),
+            max_steps: default_plan_execute_max_steps(),
+            max_replans: default_plan_execute_max_replans(),
+        }
+    }
+}
+
+/// Retrieval-based tool selection for large tool registries.
+///
+/// Instead of sending every tool spec on every request, each turn offers the
+/// pinned tools plus the `top_k` tools whose name and description best match
+/// the request, ranked with the `[memory]` embedding provider (keyword overlap
+/// when embeddings are off). The model can load more through `tool_search`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ToolRetrievalConfig {
+    /// Select tools per turn instead of sending the whole registry. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Retrieved tools offered per turn on top of the pinned set. Default: `8`.
+    #[serde(default = "default_tool_retrieval_top_k")]
+    pub top_k: usize,
+    /// Registries with at most this many tools are sent whole. Default: `30`.
+    #[serde(default = "default_tool_retrieval_min_tools")]
+    pub min_tools: usize,
+    /// Tools offered on every turn regardless of the request.
+    #[serde(default = "default_tool_retrieval_pinned_tools")]
+    pub pinned_tools: Vec<String>,
+}
+
+fn default_tool_retrieval_top_k() -> usize {
+    8
+}
+
+fn default_tool_retrieval_min_tools() -> usize {
+    30
+}
+
+fn default_tool_retrieval_pinned_tools() -> Vec<String> {
+    [
+        "shell",
+        "file_read",
+        "file_write",
+        "file_edit",
+        "memory_recall",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl Default for ToolRetrievalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            top_k: default_tool_retrieval_top_k(),
+            min_tools: default_tool_retrieval_min_tools(),
+            pinned_tools: default_tool_retrieval_pinned_tools(),
+        }
+    }
+}
+
+fn default_agent_tool_output_spill_chars() -> usize {
+    16_000
+}
+
+fn default_agent_tool_output_preview_chars() -> usize {
+    2_000
+}
+
+fn default_agent_tool_output_artifact_limit() -> usize {
+    200
 }
 
 fn default_agent_max_tool_iterations() -> usize {
     20
 }
 
+fn default_agent_reserved_output_tokens() -> usize {
+    4096
+}
+
 fn default_agent_max_history_messages() -> usize {
     50
 }
@@ -865,6 +1246,14 @@ impl Default for AgentConfig {
             loop_detection_no_progress_threshold: default_loop_detection_no_progress_threshold(),
             loop_detection_ping_pong_cycles: default_loop_detection_ping_pong_cycles(),
             loop_detection_failure_streak: default_loop_detection_failure_streak(),
+            context_window_tokens: 0,
+            reserved_output_tokens: default_agent_reserved_output_tokens(),
+            model_context_windows: HashMap::new(),
+            tool_output_spill_chars: default_agent_tool_output_spill_chars(),
+            tool_output_preview_chars: default_agent_tool_output_preview_chars(),
+            tool_output_artifact_limit: default_agent_tool_output_artifact_limit(),
+            plan_execute: PlanExecuteConfig::default(),
+            tool_retrieval: ToolRetrievalConfig::default(),
         }
     }
 }
@@ -1071,7 +1460,7 @@ pub struct CostConfig {
 }
 
 /// Per-model pricing entry (USD per 1M tokens).
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ModelPricing {
     /// Input price per 1M tokens
     #[serde(default)]
@@ -1080,6 +1469,28 @@ pub struct ModelPricing {
     /// Output price per 1M tokens
     #[serde(default)]
     pub output: f64,
+
+    /// Price per 1M prompt tokens read from the provider's prompt cache.
+    /// Falls back to `input` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+
+    /// Price per 1M prompt tokens written to the provider's prompt cache.
+    /// Falls back to `input` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPricing {
+    /// Effective cache-read price (USD per 1M tokens).
+    pub fn cache_read_price(&self) -> f64 {
+        self.cache_read.unwrap_or(self.input)
+    }
+
+    /// Effective cache-write price (USD per 1M tokens).
+    pub fn cache_write_price(&self) -> f64 {
+        self.cache_write.unwrap_or(self.input)
+    }
 }
 
 fn default_daily_limit() -> f64 {
@@ -1117,6 +1528,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 3.0,
             output: 15.0,
+            cache_read: Some(0.30),
+            cache_write: Some(3.75),
         },
     );
     prices.insert(
@@ -1124,6 +1537,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 15.0,
             output: 75.0,
+            cache_read: Some(1.50),
+            cache_write: Some(18.75),
         },
     );
     prices.insert(
@@ -1131,6 +1546,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 3.0,
             output: 15.0,
+            cache_read: Some(0.30),
+            cache_write: Some(3.75),
         },
     );
     prices.insert(
@@ -1138,6 +1555,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 0.25,
             output: 1.25,
+            cache_read: Some(0.03),
+            cache_write: Some(0.30),
         },
     );
 
@@ -1147,6 +1566,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 5.0,
             output: 15.0,
+            cache_read: Some(2.50),
+            cache_write: None,
         },
     );
     prices.insert(
@@ -1154,6 +1575,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 0.15,
             output: 0.60,
+            cache_read: Some(0.075),
+            cache_write: None,
         },
     );
     prices.insert(
@@ -1161,6 +1584,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 15.0,
             output: 60.0,
+            cache_read: Some(7.50),
+            cache_write: None,
         },
     );
 
@@ -1170,6 +1595,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 0.10,
             output: 0.40,
+            cache_read: Some(0.025),
+            cache_write: None,
         },
     );
     prices.insert(
@@ -1177,6 +1604,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 1.25,
             output: 5.0,
+            cache_read: Some(0.3125),
+            cache_write: None,
         },
     );
 
@@ -2387,7 +2816,7 @@ pub struct MemoryConfig {
     /// For sqlite backend: prune conversation rows older than this many days
     #[serde(default = "default_conversation_retention_days")]
     pub conversation_retention_days: u32,
-    /// Embedding provider: "none" | "openai" | "custom:URL"
+    /// Embedding provider: "none" | "openai" | "local" | "custom:URL"
     #[serde(default = "default_embedding_provider")]
     pub embedding_provider: String,
     /// Embedding model name (e.g. "text-embedding-3-small")
@@ -2549,6 +2978,17 @@ pub struct ObservabilityConfig {
     /// Maximum entries retained when runtime_trace_mode = "rolling".
     #[serde(default = "default_runtime_trace_max_entries")]
     pub runtime_trace_max_entries: usize,
+
+    /// Include model reasoning text in logs, runtime traces and `/ws/chat`.
+    /// When false only reasoning sizes are reported.
+    #[serde(default)]
+    pub show_reasoning: bool,
+
+    /// Record each tool-loop turn's starting conversation, provider responses
+    /// and tool I/O so `zeroclaw doctor replay` can re-run it. Traces grow
+    /// with conversation size; requires `runtime_trace_mode` other than "none".
+    #[serde(default)]
+    pub runtime_trace_replay: bool,
 }
 
 impl Default for ObservabilityConfig {
@@ -2560,6 +3000,8 @@ impl Default for ObservabilityConfig {
             runtime_trace_mode: default_runtime_trace_mode(),
             runtime_trace_path: default_runtime_trace_path(),
             runtime_trace_max_entries: default_runtime_trace_max_entries(),
+            show_reasoning: false,
+            runtime_trace_replay: false,
         }
     }
 }
@@ -2798,7 +3240,13 @@ pub struct AutonomyConfig {
 }
 
 fn default_auto_approve() -> Vec<String> {
-    vec!["file_read".into(), "memory_recall".into()]
+    vec![
+        "file_read".into(),
+        "memory_recall".into(),
+        "ask_user".into(),
+        "artifact_read".into(),
+        "tool_search".into(),
+    ]
 }
 
 fn default_always_ask() -> Vec<String> {
@@ -3289,6 +3737,26 @@ pub struct ReliabilityConfig {
     /// Max retries for cron job execution attempts.
     #[serde(default = "default_scheduler_retries")]
     pub scheduler_retries: u32,
+    /// Send a hedged request to the first fallback provider when the primary
+    /// is slower than `hedge_percentile` of its recent latencies. The first
+    /// successful answer wins and the other request is cancelled. Default: `false`.
+    #[serde(default)]
+    pub hedge_enabled: bool,
+    /// Latency percentile (0–100) of recent primary calls after which to hedge.
+    #[serde(default = "default_hedge_percentile")]
+    pub hedge_percentile: f64,
+    /// Hedge delay (ms) used until enough primary latencies have been observed.
+    #[serde(default = "default_hedge_initial_delay_ms")]
+    pub hedge_initial_delay_ms: u64,
+    /// Pace outgoing provider requests from rate-limit headers so that
+    /// concurrent sessions share each provider/key budget instead of hitting 429s.
+    /// Default: `true`.
+    #[serde(default = "default_true")]
+    pub rate_limit_pacing: bool,
+    /// Longest a single request will wait for rate-limit budget before being
+    /// sent anyway (seconds). Default: `60`.
+    #[serde(default = "default_rate_limit_max_wait_secs")]
+    pub rate_limit_max_wait_secs: u64,
 }
 
 fn default_provider_retries() -> u32 {
@@ -3315,6 +3783,18 @@ fn default_scheduler_retries() -> u32 {
     2
 }
 
+fn default_hedge_percentile() -> f64 {
+    95.0
+}
+
+fn default_hedge_initial_delay_ms() -> u64 {
+    3000
+}
+
+fn default_rate_limit_max_wait_secs() -> u64 {
+    60
+}
+
 impl Default for ReliabilityConfig {
     fn default() -> Self {
         Self {
@@ -3327,6 +3807,11 @@ impl Default for ReliabilityConfig {
             channel_max_backoff_secs: default_channel_backoff_max_secs(),
             scheduler_poll_secs: default_scheduler_poll_secs(),
             scheduler_retries: default_scheduler_retries(),
+            hedge_enabled: false,
+            hedge_percentile: default_hedge_percentile(),
+            hedge_initial_delay_ms: default_hedge_initial_delay_ms(),
+            rate_limit_pacing: true,
+            rate_limit_max_wait_secs: default_rate_limit_max_wait_secs(),
         }
     }
 }
@@ -3409,6 +3894,124 @@ pub struct ModelRouteConfig {
     /// Existing configs without this field remain valid.
     #[serde(default)]
     pub transport: Option<String>,
+    /// Optional quality tier for adaptive routing (higher is better).
+    /// Routes without a tier are treated as tier 0.
+    #[serde(default)]
+    pub quality_tier: Option<u8>,
+}
+
+// ── Adaptive routing ────────────────────────────────────────────
+
+/// Adaptive model routing (`[adaptive_routing]` section).
+///
+/// When enabled, several `[[model_routes]]` entries may share a hint. Each
+/// request for that hint goes to the cheapest candidate that meets
+/// `min_quality_tier`, `latency_slo_ms` and `max_error_rate`, based on
+/// observed per-route latency, error rate and token cost. Decisions are
+/// logged to runtime traces as `route_decision` events.
+///
+/// ```toml
+/// [adaptive_routing]
+/// enabled = true
+/// min_quality_tier = 2
+/// latency_slo_ms = 8000
+///
+/// [[model_routes]]
+/// hint = "chat"
+/// provider = "anthropic"
+/// model = "claude-sonnet-4-20250514"
+/// quality_tier = 3
+///
+/// [[model_routes]]
+/// hint = "chat"
+/// provider = "openai"
+/// model = "gpt-4o-mini"
+/// quality_tier = 2
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AdaptiveRoutingConfig {
+    /// Enable adaptive route selection. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minimum `quality_tier` a route must declare to be eligible. Default: `0`.
+    #[serde(default)]
+    pub min_quality_tier: u8,
+    /// Smoothed per-call latency target in milliseconds (`0` disables). Default: `0`.
+    #[serde(default)]
+    pub latency_slo_ms: u64,
+    /// Maximum smoothed error rate (0.0–1.0) for a route to stay eligible. Default: `0.25`.
+    #[serde(default = "default_adaptive_max_error_rate")]
+    pub max_error_rate: f64,
+    /// Calls each route receives before its observed stats drive selection. Default: `3`.
+    #[serde(default = "default_adaptive_min_samples")]
+    pub min_samples: u32,
+    /// Weight of the newest observation in the moving averages (0.0–1.0]. Default: `0.2`.
+    #[serde(default = "default_adaptive_smoothing")]
+    pub smoothing: f64,
+    /// Seconds before a route that failed the SLO or error budget is probed again. Default: `300`.
+    #[serde(default = "default_adaptive_reprobe_secs")]
+    pub reprobe_secs: u64,
+}
+
+fn default_adaptive_max_error_rate() -> f64 {
+    0.25
+}
+
+fn default_adaptive_min_samples() -> u32 {
+    3
+}
+
+fn default_adaptive_smoothing() -> f64 {
+    0.2
+}
+
+fn default_adaptive_reprobe_secs() -> u64 {
+    300
+}
+
+impl Default for AdaptiveRoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_quality_tier: 0,
+            latency_slo_ms: 0,
+            max_error_rate: default_adaptive_max_error_rate(),
+            min_samples: default_adaptive_min_samples(),
+            smoothing: default_adaptive_smoothing(),
+            reprobe_secs: default_adaptive_reprobe_secs(),
+        }
+    }
+}
+
+// ── Ollama ──────────────────────────────────────────────────────
+
+/// Local Ollama model lifecycle (`[ollama]` section).
+///
+/// ```toml
+/// [ollama]
+/// auto_pull = true
+/// keep_alive = "30m"
+/// preload_models = ["qwen2.5:3b"]
+/// num_ctx = 16384
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct OllamaConfig {
+    /// Pull a model from the Ollama registry when a request reports it missing.
+    /// Default: `false`.
+    #[serde(default)]
+    pub auto_pull: bool,
+    /// How long Ollama keeps a model loaded after a request, e.g. `"5m"`, `"1h"`,
+    /// `"-1"` (forever) or `"0"` (unload immediately). Unset uses Ollama's default.
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+    /// Models to load into memory (pulling them first when `auto_pull` is set)
+    /// when the provider warms up. Default: none.
+    #[serde(default)]
+    pub preload_models: Vec<String>,
+    /// Context window (`num_ctx`) requested for every chat call. Also used as
+    /// the context budget for Ollama models. Unset uses Ollama's default.
+    #[serde(default)]
+    pub num_ctx: Option<u32>,
 }
 
 // ── Embedding routing ───────────────────────────────────────────
@@ -3429,7 +4032,7 @@ pub struct ModelRouteConfig {
 pub struct EmbeddingRouteConfig {
     /// Route hint name (e.g. "semantic", "archive", "faq")
     pub hint: String,
-    /// Embedding provider (`none`, `openai`, or `custom:<url>`)
+    /// Embedding provider (`none`, `openai`, `local`, or `custom:<url>`)
     pub provider: String,
     /// Embedding model to use with that provider
     pub model: String,
@@ -3715,6 +4318,12 @@ pub struct ChannelsConfig {
     /// Default: 300s for on-device LLMs (Ollama) which are slower than cloud APIs.
     #[serde(default = "default_channel_message_timeout_secs")]
     pub message_timeout_secs: u64,
+    /// Persistent per-sender conversation sessions (`[channels_config.sessions]`).
+    #[serde(default)]
+    pub sessions: ChannelSessionsConfig,
+    /// Crash-safe checkpoints of in-flight turns (`[channels_config.checkpoints]`).
+    #[serde(default)]
+    pub checkpoints: ChannelCheckpointsConfig,
 }
 
 impl ChannelsConfig {
@@ -3817,6 +4426,79 @@ fn default_channel_message_timeout_secs() -> u64 {
     300
 }
 
+fn default_channel_session_idle_timeout_minutes() -> u64 {
+    720
+}
+
+fn default_channel_session_max_per_sender() -> usize {
+    20
+}
+
+/// Persistent channel conversation sessions.
+///
+/// Per-sender history is written through to `<workspace>/state/channel_sessions.db`
+/// and restored on startup, so restarts do not drop ongoing conversations.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChannelSessionsConfig {
+    /// Persist sessions to SQLite. When false, history is in-memory only.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Start a fresh session when the active one has been idle this long.
+    /// The idle session is kept and can be resumed with `/resume`. `0` disables expiry.
+    #[serde(default = "default_channel_session_idle_timeout_minutes")]
+    pub idle_timeout_minutes: u64,
+    /// Sessions kept per sender; the least recently active ones beyond this are deleted.
+    #[serde(default = "default_channel_session_max_per_sender")]
+    pub max_sessions_per_sender: usize,
+}
+
+impl Default for ChannelSessionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_timeout_minutes: default_channel_session_idle_timeout_minutes(),
+            max_sessions_per_sender: default_channel_session_max_per_sender(),
+        }
+    }
+}
+
+/// Crash-safe checkpointing of in-flight channel turns.
+///
+/// After every tool-loop iteration the turn's history and pending tool calls
+/// are written to `<workspace>/state/turn_checkpoints.db`. Turns interrupted
+/// by a crash or restart are resumed on the next start, or reported to the
+/// sender as failed.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChannelCheckpointsConfig {
+    /// Checkpoint in-flight turns and resume them after a restart.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Interrupted turns older than this are reported as failed instead of resumed.
+    #[serde(default = "default_channel_checkpoint_max_age_minutes")]
+    pub max_age_minutes: u64,
+    /// How many times one turn may be resumed before it is reported as failed.
+    #[serde(default = "default_channel_checkpoint_max_resume_attempts")]
+    pub max_resume_attempts: u32,
+}
+
+fn default_channel_checkpoint_max_age_minutes() -> u64 {
+    60
+}
+
+fn default_channel_checkpoint_max_resume_attempts() -> u32 {
+    1
+}
+
+impl Default for ChannelCheckpointsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_age_minutes: default_channel_checkpoint_max_age_minutes(),
+            max_resume_attempts: default_channel_checkpoint_max_resume_attempts(),
+        }
+    }
+}
+
 impl Default for ChannelsConfig {
     fn default() -> Self {
         Self {
@@ -3842,6 +4524,8 @@ impl Default for ChannelsConfig {
             nostr: None,
             clawdtalk: None,
             message_timeout_secs: default_channel_message_timeout_secs(),
+            sessions: ChannelSessionsConfig::default(),
+            checkpoints: ChannelCheckpointsConfig::default(),
         }
     }
 }
@@ -5312,9 +5996,13 @@ impl Default for Config {
             plugins: PluginsConfig::default(),
             hardware: HardwareConfig::default(),
             query_classification: QueryClassificationConfig::default(),
+            adaptive_routing: AdaptiveRoutingConfig::default(),
+            ollama: OllamaConfig::default(),
             transcription: TranscriptionConfig::default(),
+            tts: TtsConfig::default(),
             agents_ipc: AgentsIpcConfig::default(),
             mcp: McpConfig::default(),
+            tools: HashMap::new(),
             model_support_vision: None,
             wasm: WasmConfig::default(),
             routing: None,
@@ -6173,6 +6861,7 @@ impl Config {
                 &mut config.transcription.api_key,
                 "config.transcription.api_key",
             )?;
+            decrypt_optional_secret(&store, &mut config.tts.api_key, "config.tts.api_key")?;
             decrypt_optional_secret(
                 &store,
                 &mut config.composio.api_key,
@@ -6348,6 +7037,18 @@ impl Config {
         }
     }
 
+    /// Resolve the extended-thinking budget (`provider.thinking_budget_tokens`).
+    ///
+    /// `runtime.reasoning_enabled = false` wins over a configured budget.
+    pub fn effective_thinking_budget_tokens(&self) -> Option<u32> {
+        if self.runtime.reasoning_enabled == Some(false) {
+            return None;
+        }
+        self.provider
+            .thinking_budget_tokens
+            .filter(|budget| *budget > 0)
+    }
+
     /// Resolve provider transport mode (`provider.transport`).
     ///
     /// Supported values:
@@ -6735,6 +7436,13 @@ impl Config {
             }
         }
 
+        if !(0.0..=1.0).contains(&self.adaptive_routing.max_error_rate) {
+            anyhow::bail!("adaptive_routing.max_error_rate must be between 0.0 and 1.0");
+        }
+        if !(self.adaptive_routing.smoothing > 0.0 && self.adaptive_routing.smoothing <= 1.0) {
+            anyhow::bail!("adaptive_routing.smoothing must be in (0.0, 1.0]");
+        }
+
         if self
             .provider
             .transport
@@ -6846,6 +7554,16 @@ impl Config {
             validate_mcp_config(&self.mcp)?;
         }
 
+        // Per-tool execution policies
+        for (name, policy) in &self.tools {
+            if policy.timeout_secs == Some(0) {
+                anyhow::bail!("tools.{name}.timeout_secs must be greater than 0");
+            }
+            if policy.max_concurrent == Some(0) {
+                anyhow::bail!("tools.{name}.max_concurrent must be greater than 0");
+            }
+        }
+
         // Proxy (delegate to existing validation)
         self.proxy.validate()?;
 
@@ -7259,6 +7977,11 @@ impl Config {
             &mut config_to_save.transcription.api_key,
             "config.transcription.api_key",
         )?;
+        encrypt_optional_secret(
+            &store,
+            &mut config_to_save.tts.api_key,
+            "config.tts.api_key",
+        )?;
         encrypt_optional_secret(
             &store,
             &mut config_to_save.composio.api_key,
@@ -7826,6 +8549,24 @@ default_temperature = 0.7
         assert!(c.cli);
         assert!(c.telegram.is_none());
         assert!(c.discord.is_none());
+        assert!(c.sessions.enabled);
+        assert_eq!(c.sessions.idle_timeout_minutes, 720);
+    }
+
+    #[test]
+    async fn channels_config_parses_session_settings() {
+        let c: ChannelsConfig = toml::from_str(
+            r#"
+            cli = true
+            [sessions]
+            idle_timeout_minutes = 0
+            max_sessions_per_sender = 5
+            "#,
+        )
+        .unwrap();
+        assert!(c.sessions.enabled);
+        assert_eq!(c.sessions.idle_timeout_minutes, 0);
+        assert_eq!(c.sessions.max_sessions_per_sender, 5);
     }
 
     // ── Serde round-trip ─────────────────────────────────────
@@ -7880,6 +8621,8 @@ default_temperature = 0.7
             model_routes: Vec::new(),
             embedding_routes: Vec::new(),
             query_classification: QueryClassificationConfig::default(),
+            adaptive_routing: AdaptiveRoutingConfig::default(),
+            ollama: OllamaConfig::default(),
             heartbeat: HeartbeatConfig {
                 enabled: true,
                 interval_minutes: 15,
@@ -7921,6 +8664,8 @@ default_temperature = 0.7
                 nostr: None,
                 clawdtalk: None,
                 message_timeout_secs: 300,
+                sessions: ChannelSessionsConfig::default(),
+                checkpoints: ChannelCheckpointsConfig::default(),
             },
             memory: MemoryConfig::default(),
             storage: StorageConfig::default(),
@@ -7943,8 +8688,10 @@ default_temperature = 0.7
             hooks: HooksConfig::default(),
             hardware: HardwareConfig::default(),
             transcription: TranscriptionConfig::default(),
+            tts: TtsConfig::default(),
             agents_ipc: AgentsIpcConfig::default(),
             mcp: McpConfig::default(),
+            tools: HashMap::new(),
             model_support_vision: None,
             wasm: WasmConfig::default(),
             routing: None,
@@ -8026,6 +8773,34 @@ connect_timeout_secs = 12
         );
     }
 
+    #[test]
+    async fn tool_policies_deserialize_and_validate() {
+        let raw = r#"
+default_temperature = 0.7
+
+[tools.web_search]
+timeout_secs = 20
+max_retries = 2
+max_concurrent = 1
+cache_ttl_secs = 120
+"#;
+
+        let mut parsed: Config = toml::from_str(raw).unwrap();
+        let policy = &parsed.tools["web_search"];
+        assert_eq!(policy.timeout_secs, Some(20));
+        assert_eq!(policy.max_retries, 2);
+        assert_eq!(policy.retry_backoff_ms, 500);
+        assert_eq!(policy.max_concurrent, Some(1));
+        assert_eq!(policy.cache_ttl_secs, 120);
+        assert!(parsed.validate().is_ok());
+
+        parsed.tools.get_mut("web_search").unwrap().max_concurrent = Some(0);
+        let error = parsed.validate().expect_err("expected validation failure");
+        assert!(error
+            .to_string()
+            .contains("tools.web_search.max_concurrent must be greater than 0"));
+    }
+
     #[test]
     async fn runtime_reasoning_enabled_deserializes() {
         let raw = r#"
@@ -8218,6 +8993,31 @@ reasoning_level = "high"
         );
     }
 
+    #[test]
+    async fn thinking_budget_disabled_by_runtime_reasoning_off() {
+        let raw = r#"
+default_temperature = 0.7
+
+[provider]
+thinking_budget_tokens = 4096
+
+[observability]
+backend = "none"
+show_reasoning = true
+"#;
+
+        let mut parsed: Config = toml::from_str(raw).unwrap();
+        assert!(parsed.observability.show_reasoning);
+        assert_eq!(parsed.effective_thinking_budget_tokens(), Some(4096));
+
+        parsed.runtime.reasoning_enabled = Some(false);
+        assert_eq!(parsed.effective_thinking_budget_tokens(), None);
+
+        parsed.runtime.reasoning_enabled = None;
+        parsed.provider.thinking_budget_tokens = Some(0);
+        assert_eq!(parsed.effective_thinking_budget_tokens(), None);
+    }
+
     #[test]
     async fn agent_config_defaults() {
         let cfg = AgentConfig::default();
@@ -8226,6 +9026,9 @@ reasoning_level = "high"
         assert_eq!(cfg.max_history_messages, 50);
         assert!(!cfg.parallel_tools);
         assert_eq!(cfg.tool_dispatcher, "auto");
+        assert_eq!(cfg.context_window_tokens, 0);
+        assert_eq!(cfg.reserved_output_tokens, 4096);
+        assert!(cfg.model_context_windows.is_empty());
     }
 
     #[test]
@@ -8238,6 +9041,10 @@ max_tool_iterations = 20
 max_history_messages = 80
 parallel_tools = true
 tool_dispatcher = "xml"
+context_window_tokens = 8192
+
+[agent.model_context_windows]
+"qwen2.5:7b" = 4096
 "#;
         let parsed: Config = toml::from_str(raw).unwrap();
         assert!(parsed.agent.compact_context);
@@ -8245,6 +9052,8 @@ tool_dispatcher = "xml"
         assert_eq!(parsed.agent.max_history_messages, 80);
         assert!(parsed.agent.parallel_tools);
         assert_eq!(parsed.agent.tool_dispatcher, "xml");
+        assert_eq!(parsed.agent.context_window_tokens, 8192);
+        assert_eq!(parsed.agent.model_context_windows["qwen2.5:7b"], 4096);
     }
 
     #[tokio::test]
@@ -8291,6 +9100,8 @@ tool_dispatcher = "xml"
             model_routes: Vec::new(),
             embedding_routes: Vec::new(),
             query_classification: QueryClassificationConfig::default(),
+            adaptive_routing: AdaptiveRoutingConfig::default(),
+            ollama: OllamaConfig::default(),
             heartbeat: HeartbeatConfig::default(),
             cron: CronConfig::default(),
             goal_loop: GoalLoopConfig::default(),
@@ -8316,8 +9127,10 @@ tool_dispatcher = "xml"
             hooks: HooksConfig::default(),
             hardware: HardwareConfig::default(),
             transcription: TranscriptionConfig::default(),
+            tts: TtsConfig::default(),
             agents_ipc: AgentsIpcConfig::default(),
             mcp: McpConfig::default(),
+            tools: HashMap::new(),
             model_support_vision: None,
             wasm: WasmConfig::default(),
             routing: None,
@@ -8836,6 +9649,8 @@ allowed_users = ["@ops:matrix.org"]
             nostr: None,
             clawdtalk: None,
             message_timeout_secs: 300,
+            sessions: ChannelSessionsConfig::default(),
+            checkpoints: ChannelCheckpointsConfig::default(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -9114,6 +9929,8 @@ channel_id = "C123"
             nostr: None,
             clawdtalk: None,
             message_timeout_secs: 300,
+            sessions: ChannelSessionsConfig::default(),
+            checkpoints: ChannelCheckpointsConfig::default(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -9696,6 +10513,7 @@ provider_api = "not-a-real-mode"
             max_tokens: Some(0),
             api_key: None,
             transport: None,
+            quality_tier: None,
         }];
 
         let err = config
@@ -9738,6 +10556,7 @@ provider_api = "not-a-real-mode"
             max_tokens: None,
             api_key: None,
             transport: Some("udp".to_string()),
+            quality_tier: None,
         }];
 
         let err = config
@@ -11118,6 +11937,28 @@ default_model = "legacy-model"
         let parsed: Config = toml::from_str(toml_str).unwrap();
         assert!(!parsed.transcription.enabled);
         assert_eq!(parsed.transcription.max_duration_secs, 120);
+        assert_eq!(parsed.transcription.backend, TranscriptionBackend::Api);
+    }
+
+    #[test]
+    async fn config_parses_local_transcription_backend() {
+        let parsed: TranscriptionConfig = toml::from_str(
+            r#"
+            enabled = true
+            backend = "whisper_cpp"
+            local_model = "/models/ggml-base.en.bin"
+            chunk_secs = 60
+        "#,
+        )
+        .unwrap();
+        assert_eq!(parsed.backend, TranscriptionBackend::WhisperCpp);
+        assert_eq!(
+            parsed.local_model.as_deref(),
+            Some("/models/ggml-base.en.bin")
+        );
+        assert_eq!(parsed.chunk_secs, 60);
+        assert_eq!(parsed.ffmpeg_path, "ffmpeg");
+        assert_eq!(parsed.timeout_secs, 300);
     }
 
     #[test]
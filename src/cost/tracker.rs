@@ -214,11 +214,15 @@ fn build_session_model_stats(session_costs: &[CostRecord]) -> HashMap<String, Mo
                 model: record.usage.model.clone(),
                 cost_usd: 0.0,
                 total_tokens: 0,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
                 request_count: 0,
             });
 
         entry.cost_usd += record.usage.cost_usd;
         entry.total_tokens += record.usage.total_tokens;
+        entry.cache_read_tokens += record.usage.cache_read_tokens;
+        entry.cache_write_tokens += record.usage.cache_write_tokens;
         entry.request_count += 1;
     }
 
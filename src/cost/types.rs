@@ -1,3 +1,4 @@
+use crate::config::schema::ModelPricing;
 use serde::{Deserialize, Serialize};
 
 /// Token usage information from a single API call.
@@ -9,6 +10,12 @@ pub struct TokenUsage {
     pub input_tokens: u64,
     /// Output/completion tokens
     pub output_tokens: u64,
+    /// Prompt tokens served from the provider's prompt cache (subset of `input_tokens`)
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    /// Prompt tokens written to the provider's prompt cache (subset of `input_tokens`)
+    #[serde(default)]
+    pub cache_write_tokens: u64,
     /// Total tokens
     pub total_tokens: u64,
     /// Calculated cost in USD
@@ -33,21 +40,56 @@ impl TokenUsage {
         output_tokens: u64,
         input_price_per_million: f64,
         output_price_per_million: f64,
+    ) -> Self {
+        Self::with_cache(
+            model,
+            input_tokens,
+            output_tokens,
+            0,
+            0,
+            &ModelPricing {
+                input: input_price_per_million,
+                output: output_price_per_million,
+                ..ModelPricing::default()
+            },
+        )
+    }
+
+    /// Create a token usage record that prices cached prompt tokens separately.
+    ///
+    /// `input_tokens` covers the whole prompt; cache reads and writes are
+    /// billed at their own rates and the remainder at the plain input rate.
+    pub fn with_cache(
+        model: impl Into<String>,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: u64,
+        cache_write_tokens: u64,
+        pricing: &ModelPricing,
     ) -> Self {
         let model = model.into();
-        let input_price_per_million = Self::sanitize_price(input_price_per_million);
-        let output_price_per_million = Self::sanitize_price(output_price_per_million);
+        let input_price = Self::sanitize_price(pricing.input);
+        let output_price = Self::sanitize_price(pricing.output);
+        let cache_read_price = Self::sanitize_price(pricing.cache_read_price());
+        let cache_write_price = Self::sanitize_price(pricing.cache_write_price());
         let total_tokens = input_tokens.saturating_add(output_tokens);
+        let uncached_input = input_tokens
+            .saturating_sub(cache_read_tokens)
+            .saturating_sub(cache_write_tokens);
 
         // Calculate cost: (tokens / 1M) * price_per_million
-        let input_cost = (input_tokens as f64 / 1_000_000.0) * input_price_per_million;
-        let output_cost = (output_tokens as f64 / 1_000_000.0) * output_price_per_million;
-        let cost_usd = input_cost + output_cost;
+        let per_million = |tokens: u64, price: f64| (tokens as f64 / 1_000_000.0) * price;
+        let cost_usd = per_million(uncached_input, input_price)
+            + per_million(cache_read_tokens, cache_read_price)
+            + per_million(cache_write_tokens, cache_write_price)
+            + per_million(output_tokens, output_price);
 
         Self {
             model,
             input_tokens,
             output_tokens,
+            cache_read_tokens,
+            cache_write_tokens,
             total_tokens,
             cost_usd,
             timestamp: chrono::Utc::now(),
@@ -135,6 +177,12 @@ pub struct ModelStats {
     pub cost_usd: f64,
     /// Total tokens for this model
     pub total_tokens: u64,
+    /// Prompt tokens served from cache for this model
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    /// Prompt tokens written to cache for this model
+    #[serde(default)]
+    pub cache_write_tokens: u64,
     /// Number of requests for this model
     pub request_count: usize,
 }
@@ -181,6 +229,36 @@ mod tests {
         assert_eq!(usage.total_tokens, 2000);
     }
 
+    #[test]
+    fn token_usage_prices_cached_prompt_tokens_separately() {
+        let pricing = ModelPricing {
+            input: 3.0,
+            output: 15.0,
+            cache_read: Some(0.3),
+            cache_write: Some(3.75),
+        };
+        let usage = TokenUsage::with_cache("test/model", 10_000, 1000, 6000, 2000, &pricing);
+
+        // 2000 uncached * 3 + 6000 read * 0.3 + 2000 write * 3.75 + 1000 out * 15
+        // = 0.006 + 0.0018 + 0.0075 + 0.015 = 0.0303
+        assert!((usage.cost_usd - 0.0303).abs() < 1e-9);
+        assert_eq!(usage.cache_read_tokens, 6000);
+        assert_eq!(usage.cache_write_tokens, 2000);
+        assert_eq!(usage.total_tokens, 11_000);
+    }
+
+    #[test]
+    fn token_usage_cache_prices_fall_back_to_input_price() {
+        let pricing = ModelPricing {
+            input: 3.0,
+            output: 15.0,
+            ..ModelPricing::default()
+        };
+        let cached = TokenUsage::with_cache("test/model", 1000, 500, 800, 0, &pricing);
+        let plain = TokenUsage::new("test/model", 1000, 500, 3.0, 15.0);
+        assert!((cached.cost_usd - plain.cost_usd).abs() < 1e-12);
+    }
+
     #[test]
     fn cost_record_creation() {
         let usage = TokenUsage::new("test/model", 100, 50, 1.0, 2.0);
@@ -9,18 +9,60 @@ use crate::cron::{
     due_jobs, next_run_for_schedule, record_last_run, record_run, remove_job, reschedule_after_run,
     update_job, CronJob, CronJobPatch, DeliveryConfig, JobType, Schedule, SessionTarget,
 };
+use crate::providers::structured::chat_structured;
+use crate::providers::{self, ChatMessage, Provider, ResponseFormat};
 use crate::security::SecurityPolicy;
+use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use futures_util::{stream, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
 use std::process::Stdio;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use tokio::process::Command;
 use tokio::time::{self, Duration};
 
 const MIN_POLL_SECONDS: u64 = 5;
 const SHELL_JOB_TIMEOUT_SECS: u64 = 120;
 const SCHEDULER_COMPONENT: &str = "scheduler";
+/// Characters of agent job output shown to the outcome judge.
+const JOB_JUDGE_OUTPUT_CHARS: usize = 4000;
+
+const JOB_JUDGE_SYSTEM_PROMPT: &str = "You review the final reply of a scheduled agent job. \
+Decide whether the job did what its prompt asked, based only on what the reply reports, \
+and summarize the outcome in one sentence. Use status \"error\" when the reply says the \
+work failed, was refused, or was only partly done.";
+
+/// Response format the agent job outcome judge must reply with.
+static JOB_OUTCOME_FORMAT: LazyLock<ResponseFormat> = LazyLock::new(|| {
+    ResponseFormat::json_schema(
+        "cron_job_outcome",
+        json!({
+            "type": "object",
+            "properties": {
+                "status": { "type": "string", "enum": ["ok", "error"] },
+                "summary": { "type": "string" }
+            },
+            "required": ["status", "summary"],
+            "additionalProperties": false
+        }),
+    )
+});
+
+/// Judge reply matching [`JOB_OUTCOME_FORMAT`].
+#[derive(Debug, Deserialize)]
+struct JobOutcome {
+    status: JobOutcomeStatus,
+    summary: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum JobOutcomeStatus {
+    Ok,
+    Error,
+}
 
 pub(crate) fn is_no_reply_sentinel(output: &str) -> bool {
     output.trim().eq_ignore_ascii_case("NO_REPLY")
@@ -188,18 +230,92 @@ async fn run_agent_job(
     };
 
     match run_result {
-        Ok(response) => (
-            true,
-            if response.trim().is_empty() {
-                "agent job executed".to_string()
-            } else {
-                response
-            },
-        ),
+        Ok(response) if response.trim().is_empty() => (true, "agent job executed".to_string()),
+        Ok(response) if is_no_reply_sentinel(&response) => (true, response),
+        Ok(response) => {
+            let model = job
+                .model
+                .as_deref()
+                .or(config.default_model.as_deref())
+                .unwrap_or("anthropic/claude-sonnet-4");
+            match create_judge_provider(config, model) {
+                Ok(provider) => {
+                    judge_agent_job(
+                        provider.as_ref(),
+                        model,
+                        config.default_temperature,
+                        &prompt,
+                        response,
+                    )
+                    .await
+                }
+                Err(e) => {
+                    tracing::warn!("Cron outcome judge unavailable: {e}");
+                    (true, response)
+                }
+            }
+        }
         Err(e) => (false, format!("agent job failed: {e}")),
     }
 }
 
+fn create_judge_provider(config: &Config, model: &str) -> Result<Box<dyn Provider>> {
+    let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+    providers::create_routed_provider_with_policy(
+        provider_name,
+        config.api_key.as_deref(),
+        config.api_url.as_deref(),
+        &config.reliability,
+        &config.model_routes,
+        model,
+        &providers::ProviderRuntimeOptions {
+            provider_api_url: config.api_url.clone(),
+            zeroclaw_dir: config.config_path.parent().map(std::path::PathBuf::from),
+            secrets_encrypt: config.secrets.encrypt,
+            ollama: config.ollama.clone(),
+            ..providers::ProviderRuntimeOptions::default()
+        },
+        providers::adaptive_routing::AdaptiveRoutingPolicy::from_config(config),
+    )
+}
+
+/// Classify an agent job's final reply through [`JOB_OUTCOME_FORMAT`].
+///
+/// A failed judge call keeps the job successful, since the agent run itself
+/// completed; a judged failure is reported with its summary ahead of the reply.
+async fn judge_agent_job(
+    provider: &dyn Provider,
+    model: &str,
+    temperature: f64,
+    prompt: &str,
+    response: String,
+) -> (bool, String) {
+    let messages = [
+        ChatMessage::system(JOB_JUDGE_SYSTEM_PROMPT),
+        ChatMessage::user(format!(
+            "Job prompt:\n{prompt}\n\nFinal reply:\n{}",
+            truncate_with_ellipsis(response.trim(), JOB_JUDGE_OUTPUT_CHARS)
+        )),
+    ];
+    let judged = chat_structured(provider, &messages, &JOB_OUTCOME_FORMAT, model, temperature)
+        .await
+        .and_then(|reply| reply.parse::<JobOutcome>());
+    match judged {
+        Ok(outcome) if outcome.status == JobOutcomeStatus::Error => (
+            false,
+            format!(
+                "agent job reported failure: {}\n\n{response}",
+                outcome.summary
+            ),
+        ),
+        Ok(_) => (true, response),
+        Err(e) => {
+            tracing::warn!("Cron outcome judge failed: {e}");
+            (true, response)
+        }
+    }
+}
+
 async fn persist_job_result(
     config: &Config,
     job: &CronJob,
@@ -850,6 +966,86 @@ mod tests {
         assert!(output.contains("agent job failed:"));
     }
 
+    struct ScriptedProvider {
+        replies: parking_lot::Mutex<Vec<&'static str>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            unreachable!("chat_with_history is overridden")
+        }
+
+        async fn chat_with_history(
+            &self,
+            _messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(self.replies.lock().remove(0).to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn judge_agent_job_reports_judged_failure() {
+        let provider = ScriptedProvider {
+            replies: parking_lot::Mutex::new(vec![
+                r#"{"status": "error", "summary": "The backup target was unreachable."}"#,
+            ]),
+        };
+        let (success, output) = judge_agent_job(
+            &provider,
+            "test-model",
+            0.0,
+            "Back up the notes",
+            "I tried, but the server did not answer.".into(),
+        )
+        .await;
+        assert!(!success);
+        assert!(
+            output.starts_with("agent job reported failure: The backup target was unreachable.")
+        );
+        assert!(output.ends_with("I tried, but the server did not answer."));
+    }
+
+    #[tokio::test]
+    async fn judge_agent_job_keeps_reply_when_ok_or_judge_fails() {
+        let provider = ScriptedProvider {
+            replies: parking_lot::Mutex::new(vec![
+                r#"{"status": "ok", "summary": "Notes backed up."}"#,
+                "not json",
+                "still not json",
+            ]),
+        };
+        let (success, output) = judge_agent_job(
+            &provider,
+            "test-model",
+            0.0,
+            "Back up the notes",
+            "Done.".into(),
+        )
+        .await;
+        assert!(success);
+        assert_eq!(output, "Done.");
+
+        let (success, output) = judge_agent_job(
+            &provider,
+            "test-model",
+            0.0,
+            "Back up the notes",
+            "Done.".into(),
+        )
+        .await;
+        assert!(success);
+        assert_eq!(output, "Done.");
+    }
+
     #[tokio::test]
     async fn run_agent_job_blocks_readonly_mode() {
         let tmp = TempDir::new().unwrap();
@@ -1,3 +1,5 @@
+pub mod replay;
+
 use crate::config::Config;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -630,12 +632,15 @@ fn provider_validation_error(name: &str) -> Option<String> {
 
 fn embedding_provider_validation_error(name: &str) -> Option<String> {
     let normalized = name.trim();
-    if normalized.eq_ignore_ascii_case("none") || normalized.eq_ignore_ascii_case("openai") {
+    if ["none", "openai", "local"]
+        .iter()
+        .any(|known| normalized.eq_ignore_ascii_case(known))
+    {
         return None;
     }
 
     let Some(url) = normalized.strip_prefix("custom:") else {
-        return Some("supported values: none, openai, custom:<url>".into());
+        return Some("supported values: none, openai, local, custom:<url>".into());
     };
 
     let url = url.trim();
@@ -1168,6 +1173,7 @@ mod tests {
             max_tokens: None,
             api_key: None,
             transport: None,
+            quality_tier: None,
         }];
         let mut items = Vec::new();
         check_config_semantics(&config, &mut items);
@@ -0,0 +1,703 @@
+//! `zeroclaw doctor replay` — re-run one recorded tool-loop turn.
+//!
+//! With `[observability] runtime_trace_replay = true`, every tool-loop turn
+//! writes a `turn_replay_input` event (starting history, tool names, sampling
+//! settings and config fingerprint) next to its `llm_response` and
+//! `tool_call_result` events. Replay rebuilds the turn from those events:
+//! provider responses are served by [`ScriptedProvider`] and tools return
+//! their recorded outputs, so the loop's own logic (parsing, dedupe, loop
+//! detection, budget fitting) is the only thing that runs for real. Either
+//! side can be swapped for a live component to check whether it is the one
+//! that changed.
+
+use crate::config::Config;
+use crate::observability::runtime_trace::{self, RuntimeTraceEvent};
+use crate::observability::NoopObserver;
+use crate::providers::scripted::{ScriptedProvider, ScriptedStep, ScriptedToolCall};
+use crate::providers::{self, ChatMessage, Provider};
+use crate::security::SecurityPolicy;
+use crate::tools::{self, Tool, ToolResult};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const REPLAY_INPUT_EVENT: &str = "turn_replay_input";
+
+/// Live components to use instead of recorded ones.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayOptions {
+    /// Call the configured provider instead of replaying recorded responses.
+    pub live_provider: bool,
+    /// Tools to execute for real instead of returning recorded outputs.
+    pub live_tools: Vec<String>,
+}
+
+/// Payload of the `turn_replay_input` event.
+#[derive(Debug, Deserialize)]
+struct ReplayInput {
+    history: Vec<ChatMessage>,
+    #[serde(default)]
+    tools: Vec<String>,
+    #[serde(default)]
+    excluded_tools: Vec<String>,
+    temperature: f64,
+    max_iterations: usize,
+    #[serde(default)]
+    config_hash: Option<String>,
+}
+
+/// A tool invocation, recorded or replayed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayedCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+#[derive(Debug, Clone)]
+struct RecordedOutput {
+    success: bool,
+    output: String,
+}
+
+/// Everything needed to re-run one turn, parsed from its trace events.
+struct RecordedTurn {
+    turn_id: String,
+    channel: String,
+    provider: String,
+    model: String,
+    input: ReplayInput,
+    steps: Vec<ScriptedStep>,
+    tool_outputs: Vec<(String, RecordedOutput)>,
+    tool_calls: Vec<ReplayedCall>,
+    final_text: Option<String>,
+    error: Option<String>,
+}
+
+impl RecordedTurn {
+    fn from_events(turn_id: &str, events: &[RuntimeTraceEvent]) -> Result<Self> {
+        let input_event = events
+            .iter()
+            .find(|event| event.event_type == REPLAY_INPUT_EVENT)
+            .with_context(|| {
+                format!(
+                    "Turn {turn_id} has no replay capture. Set [observability] runtime_trace_replay = true and reproduce the turn."
+                )
+            })?;
+        let input: ReplayInput = serde_json::from_value(input_event.payload.clone())
+            .context("Malformed turn_replay_input payload")?;
+
+        let mut turn = Self {
+            turn_id: turn_id.to_string(),
+            channel: input_event.channel.clone().unwrap_or_else(|| "cli".into()),
+            provider: input_event.provider.clone().unwrap_or_default(),
+            model: input_event.model.clone().unwrap_or_default(),
+            input,
+            steps: Vec::new(),
+            tool_outputs: Vec::new(),
+            tool_calls: Vec::new(),
+            final_text: None,
+            error: None,
+        };
+
+        for event in events {
+            let payload = &event.payload;
+            match event.event_type.as_str() {
+                "llm_response" if event.success == Some(true) => {
+                    turn.steps.push(recorded_step(payload));
+                }
+                "llm_response" => {
+                    let message = event.message.clone().unwrap_or_default();
+                    turn.error = Some(message.clone());
+                    turn.steps.push(ScriptedStep {
+                        error: Some(message),
+                        ..ScriptedStep::default()
+                    });
+                }
+                "tool_call_start" => {
+                    turn.tool_calls.push(ReplayedCall {
+                        name: payload_str(payload, "tool").to_string(),
+                        arguments: parse_arguments(payload_str(payload, "arguments")),
+                    });
+                }
+                // Blocked, denied and deduplicated calls carry no output; the
+                // loop reproduces those itself.
+                "tool_call_result" if payload.get("output").is_some() => {
+                    turn.tool_outputs.push((
+                        payload_str(payload, "tool").to_string(),
+                        RecordedOutput {
+                            success: event.success.unwrap_or(false),
+                            output: payload_str(payload, "output").to_string(),
+                        },
+                    ));
+                }
+                "turn_final_response" => {
+                    turn.final_text = Some(payload_str(payload, "text").to_string());
+                    turn.error = None;
+                }
+                "tool_loop_exhausted" | "loop_detected_hard_stop" => {
+                    turn.error = event.message.clone().or(Some(event.event_type.clone()));
+                }
+                _ => {}
+            }
+        }
+        Ok(turn)
+    }
+}
+
+fn payload_str<'a>(payload: &'a serde_json::Value, key: &str) -> &'a str {
+    payload
+        .get(key)
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+}
+
+fn parse_arguments(raw: &str) -> serde_json::Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::String(raw.to_string()))
+}
+
+fn recorded_step(payload: &serde_json::Value) -> ScriptedStep {
+    let text = payload_str(payload, "raw_response");
+    let tool_calls = payload
+        .get("tool_calls")
+        .and_then(serde_json::Value::as_array)
+        .map(|calls| {
+            calls
+                .iter()
+                .map(|call| ScriptedToolCall {
+                    name: payload_str(call, "name").to_string(),
+                    arguments: parse_arguments(payload_str(call, "arguments")),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    ScriptedStep {
+        text: (!text.is_empty()).then(|| text.to_string()),
+        tool_calls,
+        input_tokens: payload
+            .get("input_tokens")
+            .and_then(serde_json::Value::as_u64),
+        output_tokens: payload
+            .get("output_tokens")
+            .and_then(serde_json::Value::as_u64),
+        error: None,
+    }
+}
+
+/// Outcome of a replay compared with the recording.
+#[derive(Debug)]
+pub struct ReplayReport {
+    pub turn_id: String,
+    pub config_changed: bool,
+    pub recorded_calls: Vec<ReplayedCall>,
+    pub replayed_calls: Vec<ReplayedCall>,
+    pub recorded_final: Option<String>,
+    pub recorded_error: Option<String>,
+    pub replayed_final: Option<String>,
+    pub replayed_error: Option<String>,
+    /// Recorded provider responses the replay did not consume.
+    pub unused_responses: usize,
+    pub divergences: Vec<String>,
+}
+
+impl ReplayReport {
+    pub fn matched(&self) -> bool {
+        self.divergences.is_empty()
+    }
+
+    fn compare(&mut self) {
+        let first_mismatch = self
+            .recorded_calls
+            .iter()
+            .zip(&self.replayed_calls)
+            .position(|(recorded, replayed)| recorded != replayed);
+        if let Some(index) = first_mismatch {
+            let recorded = &self.recorded_calls[index];
+            let replayed = &self.replayed_calls[index];
+            self.divergences.push(format!(
+                "tool call #{} differs: recorded {}({}) vs replayed {}({})",
+                index + 1,
+                recorded.name,
+                recorded.arguments,
+                replayed.name,
+                replayed.arguments
+            ));
+        } else if self.recorded_calls.len() != self.replayed_calls.len() {
+            self.divergences.push(format!(
+                "recorded {} tool call(s), replay made {}",
+                self.recorded_calls.len(),
+                self.replayed_calls.len()
+            ));
+        }
+
+        match (&self.recorded_final, &self.replayed_final) {
+            (Some(recorded), Some(replayed)) if recorded.trim() != replayed.trim() => {
+                self.divergences.push("final response differs".into());
+            }
+            (Some(_), None) => self.divergences.push(format!(
+                "recorded a final response, replay failed: {}",
+                self.replayed_error.as_deref().unwrap_or("unknown error")
+            )),
+            (None, Some(_)) => self.divergences.push(format!(
+                "recording ended without a final response ({}), replay produced one",
+                self.recorded_error.as_deref().unwrap_or("interrupted")
+            )),
+            _ => {}
+        }
+
+        if self.unused_responses > 0 {
+            self.divergences.push(format!(
+                "{} recorded provider response(s) were not consumed",
+                self.unused_responses
+            ));
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Replay of turn {}", self.turn_id);
+        if self.config_changed {
+            let _ = writeln!(
+                out,
+                "⚠️  Config changed since the turn was recorded; differences may come from config."
+            );
+        }
+        let _ = writeln!(out, "\nTool calls (recorded → replayed):");
+        let rows = self.recorded_calls.len().max(self.replayed_calls.len());
+        for index in 0..rows {
+            let describe = |call: Option<&ReplayedCall>| {
+                call.map_or_else(|| "—".to_string(), |call| call.name.clone())
+            };
+            let recorded = self.recorded_calls.get(index);
+            let replayed = self.replayed_calls.get(index);
+            let marker = if recorded == replayed { "  " } else { "≠ " };
+            let _ = writeln!(
+                out,
+                "  {marker}{:>2}. {} → {}",
+                index + 1,
+                describe(recorded),
+                describe(replayed)
+            );
+        }
+        if rows == 0 {
+            let _ = writeln!(out, "  (none)");
+        }
+
+        let _ = writeln!(out, "\nRecorded result:");
+        write_outcome(
+            &mut out,
+            self.recorded_final.as_deref(),
+            self.recorded_error.as_deref(),
+        );
+        let _ = writeln!(out, "Replayed result:");
+        write_outcome(
+            &mut out,
+            self.replayed_final.as_deref(),
+            self.replayed_error.as_deref(),
+        );
+
+        if self.matched() {
+            let _ = writeln!(out, "\n✅ Replay matches the recording.");
+        } else {
+            let _ = writeln!(out, "\n❌ Replay diverged:");
+            for divergence in &self.divergences {
+                let _ = writeln!(out, "  - {divergence}");
+            }
+        }
+        out
+    }
+}
+
+fn write_outcome(out: &mut String, text: Option<&str>, error: Option<&str>) {
+    match (text, error) {
+        (Some(text), _) => {
+            let _ = writeln!(out, "  {}", crate::util::truncate_with_ellipsis(text, 400));
+        }
+        (None, Some(error)) => {
+            let _ = writeln!(out, "  error: {error}");
+        }
+        (None, None) => {
+            let _ = writeln!(out, "  (no final response recorded)");
+        }
+    }
+}
+
+/// Tool that serves recorded outputs in order, or runs a live tool, while
+/// logging every call it receives.
+struct ReplayTool {
+    name: String,
+    description: String,
+    schema: serde_json::Value,
+    live: Option<Box<dyn Tool>>,
+    recorded: Mutex<VecDeque<RecordedOutput>>,
+    calls: Arc<Mutex<Vec<ReplayedCall>>>,
+}
+
+#[async_trait]
+impl Tool for ReplayTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.schema.clone()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<ToolResult> {
+        self.calls.lock().push(ReplayedCall {
+            name: self.name.clone(),
+            arguments: args.clone(),
+        });
+        if let Some(live) = &self.live {
+            return live.execute(args).await;
+        }
+        let Some(recorded) = self.recorded.lock().pop_front() else {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!(
+                    "Replay has no recorded output left for '{}'",
+                    self.name
+                )),
+            });
+        };
+        Ok(if recorded.success {
+            ToolResult {
+                success: true,
+                output: recorded.output,
+                error: None,
+            }
+        } else {
+            let reason = recorded
+                .output
+                .strip_prefix("Error: ")
+                .unwrap_or(&recorded.output)
+                .to_string();
+            ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(reason),
+            }
+        })
+    }
+}
+
+fn build_tools(
+    config: &Config,
+    turn: &RecordedTurn,
+    options: &ReplayOptions,
+    calls: &Arc<Mutex<Vec<ReplayedCall>>>,
+) -> Result<Vec<Box<dyn Tool>>> {
+    let security = Arc::new(SecurityPolicy::from_config(
+        &config.autonomy,
+        &config.workspace_dir,
+    ));
+    let mut available = tools::default_tools(security.clone());
+    if options
+        .live_tools
+        .iter()
+        .any(|name| name.starts_with("memory_"))
+    {
+        let memory: Arc<dyn crate::memory::Memory> = Arc::from(crate::memory::create_memory(
+            &config.memory,
+            &config.workspace_dir,
+            config.api_key.as_deref(),
+        )?);
+        available.push(Box::new(tools::MemoryStoreTool::new(
+            memory.clone(),
+            security.clone(),
+        )));
+        available.push(Box::new(tools::MemoryRecallTool::new(memory.clone())));
+        available.push(Box::new(tools::MemoryForgetTool::new(memory, security)));
+    }
+
+    for name in &options.live_tools {
+        if !turn.input.tools.contains(name) {
+            bail!(
+                "--live-tool {name}: the recorded turn did not expose that tool (recorded: {})",
+                turn.input.tools.join(", ")
+            );
+        }
+        if !available.iter().any(|tool| tool.name() == name) {
+            bail!("--live-tool {name}: no built-in tool with that name can run outside its original runtime");
+        }
+    }
+
+    let mut replay_tools: Vec<Box<dyn Tool>> = Vec::new();
+    for name in &turn.input.tools {
+        let real = available
+            .iter()
+            .position(|tool| tool.name() == name)
+            .map(|index| available.swap_remove(index));
+        let description = real.as_ref().map_or_else(
+            || format!("Replayed tool '{name}'"),
+            |tool| tool.description().to_string(),
+        );
+        let schema = real.as_ref().map_or_else(
+            || serde_json::json!({ "type": "object", "additionalProperties": true }),
+            |tool| tool.parameters_schema(),
+        );
+        let recorded = turn
+            .tool_outputs
+            .iter()
+            .filter(|(tool, _)| tool == name)
+            .map(|(_, output)| output.clone())
+            .collect();
+        replay_tools.push(Box::new(ReplayTool {
+            name: name.clone(),
+            description,
+            schema,
+            live: real.filter(|_| options.live_tools.contains(name)),
+            recorded: Mutex::new(recorded),
+            calls: calls.clone(),
+        }));
+    }
+    Ok(replay_tools)
+}
+
+/// Re-run the turn that contains `event_id` and compare it with the recording.
+pub async fn replay_event(
+    config: &Config,
+    event_id: &str,
+    options: &ReplayOptions,
+) -> Result<ReplayReport> {
+    let path = runtime_trace::resolve_trace_path(&config.observability, &config.workspace_dir);
+    let event = runtime_trace::find_event_by_id(&path, event_id)?
+        .with_context(|| format!("No runtime trace event '{event_id}' in {}", path.display()))?;
+    let turn_id = event
+        .turn_id
+        .with_context(|| format!("Trace event '{event_id}' does not belong to an agent turn"))?;
+    let events = runtime_trace::load_turn_events(&path, &turn_id)?;
+    let turn = RecordedTurn::from_events(&turn_id, &events)?;
+    replay_turn(config, turn, options).await
+}
+
+async fn replay_turn(
+    config: &Config,
+    turn: RecordedTurn,
+    options: &ReplayOptions,
+) -> Result<ReplayReport> {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let tools = build_tools(config, &turn, options, &calls)?;
+
+    let scripted = ScriptedProvider::new(turn.steps.clone());
+    let live_provider: Option<Box<dyn Provider>> = if options.live_provider {
+        Some(providers::create_routed_provider_with_policy(
+            &turn.provider,
+            config.api_key.as_deref(),
+            config.api_url.as_deref(),
+            &config.reliability,
+            &config.model_routes,
+            &turn.model,
+            &providers::ProviderRuntimeOptions::default(),
+            providers::adaptive_routing::AdaptiveRoutingPolicy::from_config(config),
+        )?)
+    } else {
+        None
+    };
+    let provider: &dyn Provider = match &live_provider {
+        Some(provider) => provider.as_ref(),
+        None => &scripted,
+    };
+
+    let mut history = turn.input.history.clone();
+    let result = crate::agent::loop_::run_tool_call_loop(
+        provider,
+        &mut history,
+        &tools,
+        &NoopObserver,
+        &turn.provider,
+        &turn.model,
+        turn.input.temperature,
+        true,
+        None,
+        &turn.channel,
+        &config.multimodal,
+        turn.input.max_iterations,
+        None,
+        None,
+        None,
+        &turn.input.excluded_tools,
+    )
+    .await;
+
+    let current_hash = runtime_trace::config_fingerprint(config);
+    let (replayed_final, replayed_error) = match result {
+        Ok(text) => (Some(text), None),
+        Err(err) => (None, Some(format!("{err:#}"))),
+    };
+    let mut report = ReplayReport {
+        turn_id: turn.turn_id,
+        config_changed: turn
+            .input
+            .config_hash
+            .is_some_and(|recorded| recorded != current_hash),
+        recorded_calls: turn.tool_calls,
+        replayed_calls: calls.lock().clone(),
+        recorded_final: turn.final_text,
+        recorded_error: turn.error,
+        replayed_final,
+        replayed_error,
+        unused_responses: if options.live_provider {
+            0
+        } else {
+            scripted.remaining()
+        },
+        divergences: Vec::new(),
+    };
+    report.compare();
+    Ok(report)
+}
+
+/// CLI entry point for `zeroclaw doctor replay`.
+pub async fn run(config: &Config, event_id: &str, options: &ReplayOptions) -> Result<()> {
+    let report = replay_event(config, event_id.trim(), options).await?;
+    print!("{}", report.to_text());
+    if !report.matched() {
+        bail!("Replay diverged from the recorded turn");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn event(
+        event_type: &str,
+        success: Option<bool>,
+        payload: serde_json::Value,
+    ) -> RuntimeTraceEvent {
+        RuntimeTraceEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            event_type: event_type.into(),
+            channel: Some("telegram".into()),
+            provider: Some("openrouter".into()),
+            model: Some("test-model".into()),
+            turn_id: Some("turn-1".into()),
+            success,
+            message: None,
+            payload,
+        }
+    }
+
+    /// A turn that reads a file with `file_read` and then answers.
+    fn recorded_events() -> Vec<RuntimeTraceEvent> {
+        vec![
+            event(
+                REPLAY_INPUT_EVENT,
+                None,
+                serde_json::json!({
+                    "history": [
+                        {"role": "system", "content": "You are helpful."},
+                        {"role": "user", "content": "What is in notes.txt?"},
+                    ],
+                    "tools": ["file_read"],
+                    "excluded_tools": [],
+                    "temperature": 0.2,
+                    "max_iterations": 5,
+                    "config_hash": null,
+                }),
+            ),
+            event(
+                "llm_response",
+                Some(true),
+                serde_json::json!({
+                    "raw_response": "",
+                    "tool_calls": [
+                        {"id": "c1", "name": "file_read", "arguments": "{\"path\":\"notes.txt\"}"}
+                    ],
+                }),
+            ),
+            event(
+                "tool_call_start",
+                None,
+                serde_json::json!({"tool": "file_read", "arguments": "{\"path\":\"notes.txt\"}"}),
+            ),
+            event(
+                "tool_call_result",
+                Some(true),
+                serde_json::json!({"tool": "file_read", "output": "buy milk"}),
+            ),
+            event(
+                "llm_response",
+                Some(true),
+                serde_json::json!({"raw_response": "You need to buy milk.", "tool_calls": []}),
+            ),
+            event(
+                "turn_final_response",
+                Some(true),
+                serde_json::json!({"text": "You need to buy milk."}),
+            ),
+        ]
+    }
+
+    #[test]
+    fn recorded_turn_requires_replay_capture() {
+        let events = &recorded_events()[1..];
+        let err = RecordedTurn::from_events("turn-1", events)
+            .err()
+            .expect("missing capture");
+        assert!(err.to_string().contains("runtime_trace_replay"));
+    }
+
+    #[tokio::test]
+    async fn replay_with_recorded_components_matches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..Config::default()
+        };
+        let turn = RecordedTurn::from_events("turn-1", &recorded_events()).unwrap();
+        assert_eq!(turn.steps.len(), 2);
+        assert_eq!(turn.tool_outputs.len(), 1);
+
+        let report = replay_turn(&config, turn, &ReplayOptions::default())
+            .await
+            .unwrap();
+        assert!(report.matched(), "{}", report.to_text());
+        assert_eq!(
+            report.replayed_final.as_deref(),
+            Some("You need to buy milk.")
+        );
+        assert_eq!(report.replayed_calls[0].arguments["path"], "notes.txt");
+    }
+
+    #[tokio::test]
+    async fn live_tool_substitution_reports_divergence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..Config::default()
+        };
+        let mut events = recorded_events();
+        // The recording shows a different path than the model asked for.
+        events[2].payload["arguments"] = serde_json::json!("{\"path\":\"todo.txt\"}");
+        let turn = RecordedTurn::from_events("turn-1", &events).unwrap();
+
+        let options = ReplayOptions {
+            live_provider: false,
+            live_tools: vec!["file_read".into()],
+        };
+        let report = replay_turn(&config, turn, &options).await.unwrap();
+        assert!(!report.matched());
+        assert!(report.divergences[0].contains("tool call #1 differs"));
+
+        let turn = RecordedTurn::from_events("turn-1", &recorded_events()).unwrap();
+        let options = ReplayOptions {
+            live_provider: false,
+            live_tools: vec!["shell".into()],
+        };
+        let err = replay_turn(&config, turn, &options).await.unwrap_err();
+        assert!(err.to_string().contains("did not expose"));
+    }
+}
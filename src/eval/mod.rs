@@ -0,0 +1,56 @@
+//! Scenario-based evaluation of the agent loop (`zeroclaw eval`).
+//!
+//! A suite is a TOML file of scenarios. Each scenario sends one or more user
+//! messages through `Agent::turn` or `run_tool_call_loop`, against either a
+//! scripted provider or a real one, with real tools sandboxed to a throwaway
+//! workspace plus optional stub tools. Afterwards the observed tool calls,
+//! final reply, memory writes and token cost are checked against the
+//! scenario's expectations, and the results are reported as text, JSON or
+//! JUnit XML.
+
+pub mod report;
+pub mod runner;
+pub mod suite;
+
+pub use report::ReportFormat;
+
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+use runner::run_suite;
+use std::path::Path;
+use suite::EvalSuite;
+
+/// Handle `zeroclaw eval run`: execute a suite, print or write the report,
+/// and fail when any scenario fails.
+pub async fn run_command(
+    config: &Config,
+    suite_path: &Path,
+    format: ReportFormat,
+    output: Option<&Path>,
+    filter: Option<&str>,
+) -> Result<()> {
+    let suite = EvalSuite::load(suite_path)?;
+    let report = run_suite(config, &suite, filter).await;
+    if report.scenarios.is_empty() {
+        bail!("No scenarios in '{}' match the filter", suite.name);
+    }
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, report.render(format))
+                .with_context(|| format!("Failed to write eval report to {}", path.display()))?;
+            print!("{}", report.to_text());
+            println!("Report written to {}", path.display());
+        }
+        None => print!("{}", report.render(format)),
+    }
+
+    if report.failed > 0 {
+        bail!(
+            "{} of {} scenarios failed",
+            report.failed,
+            report.scenarios.len()
+        );
+    }
+    Ok(())
+}
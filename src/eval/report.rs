@@ -0,0 +1,172 @@
+use super::runner::EvalReport;
+use std::fmt::Write;
+
+/// Report format for `zeroclaw eval run`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Human-readable summary.
+    #[default]
+    Text,
+    /// Full report as JSON, including observed tool calls, memory and usage.
+    Json,
+    /// JUnit XML for CI test dashboards.
+    Junit,
+}
+
+impl EvalReport {
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Text => self.to_text(),
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".into())
+            }
+            ReportFormat::Junit => self.to_junit(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Suite: {}", self.suite);
+        for scenario in &self.scenarios {
+            let usage = &scenario.observation.usage;
+            let _ = writeln!(
+                out,
+                "  {} {} ({} ms, {} tool calls, {} tokens, ${:.4})",
+                if scenario.passed { "✅" } else { "❌" },
+                scenario.name,
+                scenario.duration_ms,
+                scenario.observation.tool_calls.len(),
+                usage.total_tokens(),
+                usage.cost_usd
+            );
+            for failure in &scenario.failures {
+                let _ = writeln!(out, "      - {failure}");
+            }
+        }
+        let _ = writeln!(
+            out,
+            "{} passed, {} failed ({} ms)",
+            self.passed, self.failed, self.duration_ms
+        );
+        out
+    }
+
+    pub fn to_junit(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(
+            out,
+            r#"<testsuites name="zeroclaw-eval" tests="{}" failures="{}" time="{}">"#,
+            self.scenarios.len(),
+            self.failed,
+            seconds(self.duration_ms)
+        );
+        let _ = writeln!(
+            out,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" time="{}">"#,
+            xml_escape(&self.suite),
+            self.scenarios.len(),
+            self.failed,
+            seconds(self.duration_ms)
+        );
+        for scenario in &self.scenarios {
+            let _ = write!(
+                out,
+                r#"    <testcase classname="{}" name="{}" time="{}""#,
+                xml_escape(&self.suite),
+                xml_escape(&scenario.name),
+                seconds(scenario.duration_ms)
+            );
+            if scenario.passed {
+                let _ = writeln!(out, " />");
+                continue;
+            }
+            let _ = writeln!(out, ">");
+            let message = scenario.failures.first().map_or("", String::as_str);
+            let _ = writeln!(
+                out,
+                r#"      <failure message="{}">{}</failure>"#,
+                xml_escape(message),
+                xml_escape(&scenario.failures.join("\n"))
+            );
+            if let Some(text) = &scenario.observation.final_text {
+                let _ = writeln!(out, "      <system-out>{}</system-out>", xml_escape(text));
+            }
+            let _ = writeln!(out, "    </testcase>");
+        }
+        let _ = writeln!(out, "  </testsuite>");
+        let _ = writeln!(out, "</testsuites>");
+        out
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn seconds(ms: u64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+/// Escape XML reserved characters and drop control characters XML 1.0 forbids.
+fn xml_escape(raw: &str) -> String {
+    raw.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::runner::{Observation, ScenarioResult};
+
+    fn report() -> EvalReport {
+        EvalReport {
+            suite: "flows".into(),
+            passed: 1,
+            failed: 1,
+            duration_ms: 1500,
+            scenarios: vec![
+                ScenarioResult {
+                    name: "ok".into(),
+                    passed: true,
+                    duration_ms: 500,
+                    failures: vec![],
+                    observation: Observation::default(),
+                },
+                ScenarioResult {
+                    name: "broken <one>".into(),
+                    passed: false,
+                    duration_ms: 1000,
+                    failures: vec!["final reply does not contain 'milk'".into()],
+                    observation: Observation {
+                        final_text: Some("eggs & bread\u{1b}".into()),
+                        ..Observation::default()
+                    },
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn junit_report_marks_failures_and_escapes_text() {
+        let xml = report().to_junit();
+        assert!(xml.contains(r#"<testsuite name="flows" tests="2" failures="1" time="1.500">"#));
+        assert!(xml.contains(r#"<testcase classname="flows" name="ok" time="0.500" />"#));
+        assert!(xml.contains(r#"name="broken &lt;one&gt;""#));
+        assert!(
+            xml.contains(r#"<failure message="final reply does not contain &apos;milk&apos;">"#)
+        );
+        assert!(xml.contains("<system-out>eggs &amp; bread</system-out>"));
+    }
+
+    #[test]
+    fn json_report_round_trips() {
+        let json = report().render(ReportFormat::Json);
+        let parsed: EvalReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.failed, 1);
+        assert_eq!(parsed.scenarios[1].failures.len(), 1);
+    }
+}
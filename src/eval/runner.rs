@@ -0,0 +1,913 @@
+use super::suite::{EvalRunner, EvalSuite, Expectations, MockTool, Scenario, SCRIPTED_PROVIDER};
+use crate::agent::agent::Agent;
+use crate::agent::dispatcher::{NativeToolDispatcher, ToolDispatcher, XmlToolDispatcher};
+use crate::agent::memory_loader::DefaultMemoryLoader;
+use crate::agent::prompt::SystemPromptBuilder;
+use crate::config::schema::ModelPricing;
+use crate::config::Config;
+use crate::memory::{self, Memory};
+use crate::observability::cost::{lookup_pricing, DEFAULT_INPUT_PRICE, DEFAULT_OUTPUT_PRICE};
+use crate::observability::NoopObserver;
+use crate::providers::scripted::ScriptedProvider;
+use crate::providers::traits::ProviderCapabilities;
+use crate::providers::{self, ChatMessage, ChatRequest, ChatResponse, Provider};
+use crate::security::SecurityPolicy;
+use crate::tools::{self, Tool, ToolResult};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest tool output kept in a report.
+const TOOL_OUTPUT_PREVIEW_CHARS: usize = 500;
+
+// ── Results ─────────────────────────────────────────────────────
+
+/// A tool invocation observed during a scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub name: String,
+    pub arguments: serde_json::Value,
+    pub success: bool,
+    pub output: String,
+}
+
+/// A memory entry present in the scenario workspace after the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryWrite {
+    pub key: String,
+    pub content: String,
+    pub category: String,
+}
+
+/// Token usage and estimated cost summed over every provider call.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub provider_calls: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens.saturating_add(self.output_tokens)
+    }
+}
+
+/// Everything the assertions look at.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Observation {
+    pub final_text: Option<String>,
+    pub error: Option<String>,
+    pub tool_calls: Vec<ToolCallRecord>,
+    pub memory_writes: Vec<MemoryWrite>,
+    pub usage: UsageTotals,
+}
+
+/// Outcome of one scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    pub name: String,
+    pub passed: bool,
+    pub duration_ms: u64,
+    pub failures: Vec<String>,
+    #[serde(flatten)]
+    pub observation: Observation,
+}
+
+/// Outcome of a whole suite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub suite: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub duration_ms: u64,
+    pub scenarios: Vec<ScenarioResult>,
+}
+
+// ── Suite execution ─────────────────────────────────────────────
+
+/// Run every scenario whose name contains `filter` (all when `None`).
+pub async fn run_suite(config: &Config, suite: &EvalSuite, filter: Option<&str>) -> EvalReport {
+    let started = Instant::now();
+    let mut scenarios = Vec::new();
+    for scenario in &suite.scenarios {
+        if filter.is_some_and(|needle| !scenario.name.contains(needle)) {
+            continue;
+        }
+        scenarios.push(run_scenario(config, suite, scenario).await);
+    }
+    let passed = scenarios.iter().filter(|result| result.passed).count();
+    EvalReport {
+        suite: suite.name.clone(),
+        passed,
+        failed: scenarios.len() - passed,
+        duration_ms: elapsed_ms(started),
+        scenarios,
+    }
+}
+
+/// Run one scenario in a fresh workspace and check its expectations.
+pub async fn run_scenario(
+    config: &Config,
+    suite: &EvalSuite,
+    scenario: &Scenario,
+) -> ScenarioResult {
+    let started = Instant::now();
+    let timeout = Duration::from_secs(scenario.timeout_secs(&suite.defaults).max(1));
+
+    let observation = match tokio::time::timeout(timeout, execute(config, suite, scenario)).await {
+        Ok(Ok(observation)) => observation,
+        Ok(Err(err)) => Observation {
+            error: Some(format!("{err:#}")),
+            ..Observation::default()
+        },
+        Err(_) => Observation {
+            error: Some(format!("Scenario timed out after {}s", timeout.as_secs())),
+            ..Observation::default()
+        },
+    };
+
+    let failures = check_expectations(&scenario.expect, &observation);
+    ScenarioResult {
+        name: scenario.name.clone(),
+        passed: failures.is_empty(),
+        duration_ms: elapsed_ms(started),
+        failures,
+        observation,
+    }
+}
+
+fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Set up the sandbox, drive the agent and collect observations. Errors from
+/// the agent itself are recorded in the observation; only setup failures are
+/// returned as `Err`.
+async fn execute(config: &Config, suite: &EvalSuite, scenario: &Scenario) -> Result<Observation> {
+    let workspace = tempfile::Builder::new()
+        .prefix("zeroclaw-eval-")
+        .tempdir()
+        .context("Failed to create eval workspace")?;
+    let workspace_dir = workspace.path().to_path_buf();
+    for (relative, contents) in &scenario.files {
+        let path = workspace_dir.join(relative);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, contents).await?;
+    }
+
+    let mut config = config.clone();
+    config.workspace_dir = workspace_dir.clone();
+    config.memory.backend = "sqlite".into();
+    config.memory.embedding_provider = "none".into();
+    config.memory.auto_save = false;
+
+    let memory: Arc<dyn Memory> =
+        Arc::from(memory::create_memory(&config.memory, &workspace_dir, None)?);
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let tools = build_tools(&config, scenario, memory.clone(), &calls)?;
+
+    let provider_name = scenario.provider_name(&suite.defaults);
+    let model = scenario
+        .model
+        .clone()
+        .or_else(|| suite.defaults.model.clone())
+        .or_else(|| (provider_name == SCRIPTED_PROVIDER).then(|| SCRIPTED_PROVIDER.to_string()))
+        .or_else(|| config.default_model.clone())
+        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+    let temperature = scenario
+        .temperature
+        .or(suite.defaults.temperature)
+        .unwrap_or(config.default_temperature);
+    let (provider, resolved_provider) = build_provider(&config, scenario, &provider_name, &model)?;
+    let usage = Arc::new(Mutex::new(UsageTotals::default()));
+    let provider = MeteredProvider {
+        inner: provider,
+        usage: usage.clone(),
+        prices: config.cost.prices.clone(),
+        provider_name: resolved_provider.clone(),
+    };
+
+    let result = match scenario.runner(&suite.defaults) {
+        EvalRunner::Agent => {
+            run_with_agent(
+                &config,
+                scenario,
+                Box::new(provider),
+                tools,
+                memory.clone(),
+                &model,
+                temperature,
+            )
+            .await
+        }
+        EvalRunner::Loop => {
+            run_with_loop(
+                &config,
+                scenario,
+                &provider,
+                &tools,
+                &resolved_provider,
+                &model,
+                temperature,
+            )
+            .await
+        }
+    };
+
+    let memory_writes = memory
+        .list(None, None)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| MemoryWrite {
+            key: entry.key,
+            content: entry.content,
+            category: entry.category.to_string(),
+        })
+        .collect();
+    let tool_calls = calls.lock().clone();
+    let usage = usage.lock().clone();
+
+    let (final_text, error) = match result {
+        Ok(text) => (Some(text), None),
+        Err(err) => (None, Some(format!("{err:#}"))),
+    };
+    Ok(Observation {
+        final_text,
+        error,
+        tool_calls,
+        memory_writes,
+        usage,
+    })
+}
+
+fn build_provider(
+    config: &Config,
+    scenario: &Scenario,
+    provider_name: &str,
+    model: &str,
+) -> Result<(Box<dyn Provider>, String)> {
+    if provider_name == SCRIPTED_PROVIDER {
+        return Ok((
+            Box::new(ScriptedProvider::new(scenario.script.clone())),
+            SCRIPTED_PROVIDER.to_string(),
+        ));
+    }
+    let resolved = if provider_name == "config" {
+        config.default_provider.as_deref().unwrap_or("openrouter")
+    } else {
+        provider_name
+    };
+    let provider = providers::create_routed_provider_with_policy(
+        resolved,
+        config.api_key.as_deref(),
+        config.api_url.as_deref(),
+        &config.reliability,
+        &config.model_routes,
+        model,
+        &providers::ProviderRuntimeOptions::default(),
+        providers::adaptive_routing::AdaptiveRoutingPolicy::from_config(config),
+    )?;
+    Ok((provider, resolved.to_string()))
+}
+
+/// Real tools named by the scenario, sandboxed to the scenario workspace,
+/// followed by its mock tools. Every tool records its calls into `calls`.
+fn build_tools(
+    config: &Config,
+    scenario: &Scenario,
+    memory: Arc<dyn Memory>,
+    calls: &Arc<Mutex<Vec<ToolCallRecord>>>,
+) -> Result<Vec<Box<dyn Tool>>> {
+    let security = Arc::new(SecurityPolicy::from_config(
+        &config.autonomy,
+        &config.workspace_dir,
+    ));
+    let mut available = tools::default_tools(security.clone());
+    available.push(Box::new(tools::MemoryStoreTool::new(
+        memory.clone(),
+        security.clone(),
+    )));
+    available.push(Box::new(tools::MemoryRecallTool::new(memory.clone())));
+    available.push(Box::new(tools::MemoryForgetTool::new(memory, security)));
+
+    let mut selected: Vec<Box<dyn Tool>> = Vec::new();
+    for name in &scenario.tools {
+        let Some(index) = available.iter().position(|tool| tool.name() == name) else {
+            let names: Vec<&str> = available.iter().map(|tool| tool.name()).collect();
+            bail!(
+                "Scenario '{}' requests unknown tool '{name}' (available: {})",
+                scenario.name,
+                names.join(", ")
+            );
+        };
+        selected.push(available.swap_remove(index));
+    }
+    selected.extend(
+        scenario
+            .mock_tools
+            .iter()
+            .cloned()
+            .map(|mock| Box::new(ScenarioMockTool(mock)) as Box<dyn Tool>),
+    );
+
+    Ok(selected
+        .into_iter()
+        .map(|inner| {
+            Box::new(RecordingTool {
+                inner,
+                calls: calls.clone(),
+            }) as Box<dyn Tool>
+        })
+        .collect())
+}
+
+async fn run_with_agent(
+    config: &Config,
+    scenario: &Scenario,
+    provider: Box<dyn Provider>,
+    tools: Vec<Box<dyn Tool>>,
+    memory: Arc<dyn Memory>,
+    model: &str,
+    temperature: f64,
+) -> Result<String> {
+    let tool_dispatcher: Box<dyn ToolDispatcher> = match config.agent.tool_dispatcher.as_str() {
+        "native" => Box::new(NativeToolDispatcher),
+        "xml" => Box::new(XmlToolDispatcher),
+        _ if provider.supports_native_tools() => Box::new(NativeToolDispatcher),
+        _ => Box::new(XmlToolDispatcher),
+    };
+    let route_model_by_hint: HashMap<String, String> = config
+        .model_routes
+        .iter()
+        .map(|route| (route.hint.clone(), route.model.clone()))
+        .collect();
+    let available_hints: Vec<String> = route_model_by_hint.keys().cloned().collect();
+
+    let mut agent = Agent::builder()
+        .provider(provider)
+        .tools(tools)
+        .memory(memory)
+        .observer(Arc::new(NoopObserver))
+        .tool_dispatcher(tool_dispatcher)
+        .memory_loader(Box::new(DefaultMemoryLoader::new(
+            5,
+            config.memory.min_relevance_score,
+        )))
+        .prompt_builder(SystemPromptBuilder::with_defaults())
+        .config(config.agent.clone())
+        .model_name(model.to_string())
+        .temperature(temperature)
+        .workspace_dir(config.workspace_dir.clone())
+        .classification_config(config.query_classification.clone())
+        .available_hints(available_hints)
+        .route_model_by_hint(route_model_by_hint)
+        .identity_config(config.identity.clone())
+        .skills_prompt_mode(config.skills.prompt_injection_mode)
+        .auto_save(false)
+        .build()?;
+
+    let mut reply = String::new();
+    for message in &scenario.messages {
+        reply = agent.turn(message).await?;
+    }
+    Ok(reply)
+}
+
+async fn run_with_loop(
+    config: &Config,
+    scenario: &Scenario,
+    provider: &dyn Provider,
+    tools: &[Box<dyn Tool>],
+    provider_name: &str,
+    model: &str,
+    temperature: f64,
+) -> Result<String> {
+    let tool_descs: Vec<(&str, &str)> = tools
+        .iter()
+        .map(|tool| (tool.name(), tool.description()))
+        .collect();
+    let bootstrap_max_chars = config.agent.compact_context.then_some(6000);
+    let native_tools = provider.supports_native_tools();
+    let mut system_prompt = crate::channels::build_system_prompt_with_mode(
+        &config.workspace_dir,
+        model,
+        &tool_descs,
+        &[],
+        Some(&config.identity),
+        bootstrap_max_chars,
+        native_tools,
+        config.skills.prompt_injection_mode,
+    );
+    if !native_tools {
+        system_prompt.push_str(&crate::agent::loop_::build_tool_instructions(tools));
+    }
+    system_prompt.push_str(&crate::agent::loop_::build_shell_policy_instructions(
+        &config.autonomy,
+    ));
+
+    let mut history = vec![ChatMessage::system(system_prompt)];
+    let mut reply = String::new();
+    for message in &scenario.messages {
+        history.push(ChatMessage::user(message));
+        reply = crate::agent::loop_::run_tool_call_loop(
+            provider,
+            &mut history,
+            tools,
+            &NoopObserver,
+            provider_name,
+            model,
+            temperature,
+            true,
+            None,
+            "eval",
+            &config.multimodal,
+            config.agent.max_tool_iterations,
+            None,
+            None,
+            None,
+            &[],
+        )
+        .await?;
+    }
+    Ok(reply)
+}
+
+// ── Assertions ──────────────────────────────────────────────────
+
+/// Compare an observation with the scenario's expectations. Returns one
+/// message per failed assertion.
+pub fn check_expectations(expect: &Expectations, observed: &Observation) -> Vec<String> {
+    let mut failures = Vec::new();
+
+    match (&expect.error_contains, &observed.error) {
+        (Some(needle), Some(error)) => {
+            if !error.to_lowercase().contains(&needle.to_lowercase()) {
+                failures.push(format!(
+                    "expected error containing '{needle}', got: {error}"
+                ));
+            }
+        }
+        (Some(needle), None) => {
+            failures.push(format!(
+                "expected an error containing '{needle}', but the run succeeded"
+            ));
+        }
+        (None, Some(error)) => failures.push(format!("run failed: {error}")),
+        (None, None) => {}
+    }
+
+    let called: Vec<&str> = observed
+        .tool_calls
+        .iter()
+        .map(|call| call.name.as_str())
+        .collect();
+    let mut cursor = 0;
+    for expected in &expect.tools_called {
+        match called[cursor..].iter().position(|name| name == expected) {
+            Some(offset) => cursor += offset + 1,
+            None => {
+                failures.push(format!(
+                    "expected tool '{expected}' to be called (in order); calls were [{}]",
+                    called.join(", ")
+                ));
+                break;
+            }
+        }
+    }
+    for forbidden in &expect.tools_not_called {
+        if called.contains(&forbidden.as_str()) {
+            failures.push(format!("tool '{forbidden}' must not be called"));
+        }
+    }
+    if let Some(max) = expect.max_tool_calls {
+        if called.len() > max {
+            failures.push(format!(
+                "{} tool calls exceed max_tool_calls {max}",
+                called.len()
+            ));
+        }
+    }
+    for expected in &expect.tool_call_args {
+        let matched = observed.tool_calls.iter().any(|call| {
+            call.name == expected.name
+                && expected
+                    .args
+                    .as_ref()
+                    .is_none_or(|args| json_contains(&call.arguments, args))
+        });
+        if !matched {
+            let args = expected
+                .args
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            failures.push(format!("no '{}' call with arguments {args}", expected.name));
+        }
+    }
+
+    let final_text = observed.final_text.as_deref().unwrap_or_default();
+    let lowered = final_text.to_lowercase();
+    for needle in &expect.final_contains {
+        if !lowered.contains(&needle.to_lowercase()) {
+            failures.push(format!("final reply does not contain '{needle}'"));
+        }
+    }
+    for needle in &expect.final_not_contains {
+        if lowered.contains(&needle.to_lowercase()) {
+            failures.push(format!("final reply contains forbidden '{needle}'"));
+        }
+    }
+    if let Some(pattern) = &expect.final_matches {
+        match regex::Regex::new(pattern) {
+            Ok(re) if re.is_match(final_text) => {}
+            Ok(_) => failures.push(format!("final reply does not match /{pattern}/")),
+            Err(err) => failures.push(format!("invalid final_matches regex: {err}")),
+        }
+    }
+
+    for needle in &expect.memory_contains {
+        let needle_lower = needle.to_lowercase();
+        let found = observed.memory_writes.iter().any(|entry| {
+            entry.key.to_lowercase().contains(&needle_lower)
+                || entry.content.to_lowercase().contains(&needle_lower)
+        });
+        if !found {
+            failures.push(format!("no memory entry contains '{needle}'"));
+        }
+    }
+    if expect.no_memory_writes && !observed.memory_writes.is_empty() {
+        failures.push(format!(
+            "expected no memory writes, found {}",
+            observed.memory_writes.len()
+        ));
+    }
+
+    if let Some(max) = expect.max_cost_usd {
+        if observed.usage.cost_usd > max {
+            failures.push(format!(
+                "cost ${:.6} exceeds max_cost_usd ${max:.6}",
+                observed.usage.cost_usd
+            ));
+        }
+    }
+    if let Some(max) = expect.max_total_tokens {
+        if observed.usage.total_tokens() > max {
+            failures.push(format!(
+                "{} tokens exceed max_total_tokens {max}",
+                observed.usage.total_tokens()
+            ));
+        }
+    }
+
+    failures
+}
+
+/// True when every field of `expected` appears in `actual` with an equal or
+/// (for objects) recursively contained value.
+fn json_contains(actual: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    match (actual, expected) {
+        (serde_json::Value::Object(actual), serde_json::Value::Object(expected)) => {
+            expected.iter().all(|(key, value)| {
+                actual
+                    .get(key)
+                    .is_some_and(|found| json_contains(found, value))
+            })
+        }
+        _ => actual == expected,
+    }
+}
+
+// ── Instrumentation ─────────────────────────────────────────────
+
+/// Tool wrapper that records every call for assertions.
+struct RecordingTool {
+    inner: Box<dyn Tool>,
+    calls: Arc<Mutex<Vec<ToolCallRecord>>>,
+}
+
+#[async_trait]
+impl Tool for RecordingTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.inner.parameters_schema()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<ToolResult> {
+        let result = self.inner.execute(args.clone()).await;
+        let (success, output) = match &result {
+            Ok(result) if result.success => (true, result.output.clone()),
+            Ok(result) => (false, result.error.clone().unwrap_or(result.output.clone())),
+            Err(err) => (false, err.to_string()),
+        };
+        self.calls.lock().push(ToolCallRecord {
+            name: self.inner.name().to_string(),
+            arguments: args,
+            success,
+            output: crate::util::truncate_with_ellipsis(&output, TOOL_OUTPUT_PREVIEW_CHARS),
+        });
+        result
+    }
+}
+
+/// Stub tool defined inline in a scenario.
+struct ScenarioMockTool(MockTool);
+
+#[async_trait]
+impl Tool for ScenarioMockTool {
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    fn description(&self) -> &str {
+        self.0
+            .description
+            .as_deref()
+            .unwrap_or("Scenario stub tool; returns a fixed result.")
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({ "type": "object", "additionalProperties": true })
+    }
+
+    async fn execute(&self, _args: serde_json::Value) -> Result<ToolResult> {
+        Ok(if self.0.success {
+            ToolResult {
+                success: true,
+                output: self.0.output.clone(),
+                error: None,
+            }
+        } else {
+            ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(self.0.output.clone()),
+            }
+        })
+    }
+}
+
+/// Provider wrapper that sums reported token usage and prices it with
+/// `[cost.prices]`.
+struct MeteredProvider {
+    inner: Box<dyn Provider>,
+    usage: Arc<Mutex<UsageTotals>>,
+    prices: HashMap<String, ModelPricing>,
+    provider_name: String,
+}
+
+impl MeteredProvider {
+    fn record(&self, model: &str, response: &ChatResponse) {
+        let mut totals = self.usage.lock();
+        totals.provider_calls += 1;
+        let Some(usage) = &response.usage else {
+            return;
+        };
+        let input = usage.input_tokens.unwrap_or(0);
+        let output = usage.output_tokens.unwrap_or(0);
+        let pricing =
+            lookup_pricing(&self.prices, &self.provider_name, model).unwrap_or(ModelPricing {
+                input: DEFAULT_INPUT_PRICE,
+                output: DEFAULT_OUTPUT_PRICE,
+                ..ModelPricing::default()
+            });
+        let cost = crate::cost::TokenUsage::with_cache(
+            model,
+            input,
+            output,
+            usage.cache_read_tokens.unwrap_or(0),
+            usage.cache_write_tokens.unwrap_or(0),
+            &pricing,
+        );
+        totals.input_tokens = totals.input_tokens.saturating_add(input);
+        totals.output_tokens = totals.output_tokens.saturating_add(output);
+        totals.cost_usd += cost.cost_usd;
+    }
+}
+
+#[async_trait]
+impl Provider for MeteredProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.inner.supports_native_tools()
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.inner.supports_vision()
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> Result<String> {
+        self.usage.lock().provider_calls += 1;
+        self.inner
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> Result<String> {
+        self.usage.lock().provider_calls += 1;
+        self.inner
+            .chat_with_history(messages, model, temperature)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> Result<ChatResponse> {
+        let response = self.inner.chat(request, model, temperature).await?;
+        self.record(model, &response);
+        Ok(response)
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        model: &str,
+        temperature: f64,
+    ) -> Result<ChatResponse> {
+        let response = self
+            .inner
+            .chat_with_tools(messages, tools, model, temperature)
+            .await?;
+        self.record(model, &response);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::suite::ToolCallExpectation;
+
+    fn call(name: &str, arguments: serde_json::Value) -> ToolCallRecord {
+        ToolCallRecord {
+            name: name.into(),
+            arguments,
+            success: true,
+            output: String::new(),
+        }
+    }
+
+    #[test]
+    fn expectations_check_tool_order_args_text_memory_and_cost() {
+        let observed = Observation {
+            final_text: Some("Saved: buy MILK".into()),
+            error: None,
+            tool_calls: vec![
+                call(
+                    "file_read",
+                    serde_json::json!({"path": "notes.txt", "limit": 5}),
+                ),
+                call("memory_store", serde_json::json!({"key": "groceries"})),
+            ],
+            memory_writes: vec![MemoryWrite {
+                key: "groceries".into(),
+                content: "buy milk".into(),
+                category: "core".into(),
+            }],
+            usage: UsageTotals {
+                provider_calls: 3,
+                input_tokens: 900,
+                output_tokens: 100,
+                cost_usd: 0.01,
+            },
+        };
+
+        let passing = Expectations {
+            tools_called: vec!["file_read".into(), "memory_store".into()],
+            tools_not_called: vec!["shell".into()],
+            max_tool_calls: Some(2),
+            tool_call_args: vec![ToolCallExpectation {
+                name: "file_read".into(),
+                args: Some(serde_json::json!({"path": "notes.txt"})),
+            }],
+            final_contains: vec!["milk".into()],
+            final_matches: Some(r"^Saved:".into()),
+            memory_contains: vec!["buy milk".into()],
+            max_cost_usd: Some(0.02),
+            max_total_tokens: Some(1000),
+            ..Expectations::default()
+        };
+        assert!(check_expectations(&passing, &observed).is_empty());
+
+        let failing = Expectations {
+            tools_called: vec!["memory_store".into(), "file_read".into()],
+            tools_not_called: vec!["file_read".into()],
+            tool_call_args: vec![ToolCallExpectation {
+                name: "file_read".into(),
+                args: Some(serde_json::json!({"path": "other.txt"})),
+            }],
+            final_not_contains: vec!["milk".into()],
+            no_memory_writes: true,
+            max_cost_usd: Some(0.001),
+            max_total_tokens: Some(10),
+            ..Expectations::default()
+        };
+        assert_eq!(check_expectations(&failing, &observed).len(), 7);
+    }
+
+    #[test]
+    fn expectations_handle_expected_and_unexpected_errors() {
+        let failed = Observation {
+            error: Some("Agent exceeded maximum tool iterations (3)".into()),
+            ..Observation::default()
+        };
+        let expect_error = Expectations {
+            error_contains: Some("maximum tool iterations".into()),
+            ..Expectations::default()
+        };
+        assert!(check_expectations(&expect_error, &failed).is_empty());
+
+        let failures = check_expectations(&Expectations::default(), &failed);
+        assert!(failures[0].starts_with("run failed"));
+
+        let failures = check_expectations(&expect_error, &Observation::default());
+        assert!(failures[0].contains("run succeeded"));
+    }
+
+    #[tokio::test]
+    async fn scripted_scenario_runs_real_tools_in_sandbox_for_both_runners() {
+        let suite: EvalSuite = toml::from_str(
+            r#"
+            [[scenario]]
+            name = "notes"
+            messages = ["Remember what notes.txt says"]
+            tools = ["file_read", "memory_store"]
+            files = { "notes.txt" = "buy milk" }
+
+            [[scenario.script]]
+            tool_calls = [{ name = "file_read", arguments = { path = "notes.txt" } }]
+            input_tokens = 1000
+            output_tokens = 10
+
+            [[scenario.script]]
+            tool_calls = [{ name = "memory_store", arguments = { key = "groceries", content = "buy milk", category = "core" } }]
+
+            [[scenario.script]]
+            text = "Noted: buy milk."
+
+            [[scenario.mock_tool]]
+            name = "calendar"
+            output = "free all day"
+
+            [scenario.expect]
+            tools_called = ["file_read", "memory_store"]
+            tools_not_called = ["calendar"]
+            final_contains = ["buy milk"]
+            memory_contains = ["groceries"]
+            max_total_tokens = 1010
+            "#,
+        )
+        .unwrap();
+        suite.validate().unwrap();
+
+        let tmp = tempfile::tempdir().unwrap();
+        let config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..Config::default()
+        };
+
+        for runner in [EvalRunner::Agent, EvalRunner::Loop] {
+            let mut suite = suite.clone();
+            suite.defaults.runner = Some(runner);
+            let report = run_suite(&config, &suite, None).await;
+            let result = &report.scenarios[0];
+            assert!(result.passed, "{runner:?} failed: {:?}", result.failures);
+            assert!(result.observation.tool_calls[0].output.contains("buy milk"));
+            assert_eq!(result.observation.usage.provider_calls, 3);
+            assert!(result.observation.usage.cost_usd > 0.0);
+        }
+    }
+}
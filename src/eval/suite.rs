@@ -0,0 +1,340 @@
+use crate::providers::scripted::ScriptedStep;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+// ── Suite ───────────────────────────────────────────────────────
+
+/// An evaluation suite loaded from a TOML file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvalSuite {
+    /// Suite name used in reports. Defaults to the file stem.
+    #[serde(default)]
+    pub name: String,
+    /// Defaults applied to every scenario that does not override them.
+    #[serde(default)]
+    pub defaults: ScenarioDefaults,
+    #[serde(default, rename = "scenario")]
+    pub scenarios: Vec<Scenario>,
+}
+
+impl EvalSuite {
+    /// Parse and validate a suite file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read eval suite {}", path.display()))?;
+        let mut suite: Self = toml::from_str(&raw)
+            .with_context(|| format!("Failed to parse eval suite {}", path.display()))?;
+        if suite.name.trim().is_empty() {
+            suite.name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("eval")
+                .to_string();
+        }
+        suite.validate()?;
+        Ok(suite)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.scenarios.is_empty() {
+            bail!("Eval suite '{}' defines no [[scenario]] entries", self.name);
+        }
+        let mut seen = std::collections::HashSet::new();
+        for scenario in &self.scenarios {
+            if scenario.name.trim().is_empty() {
+                bail!("Every scenario needs a non-empty name");
+            }
+            if !seen.insert(scenario.name.as_str()) {
+                bail!("Duplicate scenario name '{}'", scenario.name);
+            }
+            if scenario.messages.is_empty() {
+                bail!("Scenario '{}' has no messages", scenario.name);
+            }
+            let provider = scenario.provider_name(&self.defaults);
+            if provider == SCRIPTED_PROVIDER && scenario.script.is_empty() {
+                bail!(
+                    "Scenario '{}' uses the scripted provider but has no [[scenario.script]] steps",
+                    scenario.name
+                );
+            }
+            if provider != SCRIPTED_PROVIDER && !scenario.script.is_empty() {
+                bail!(
+                    "Scenario '{}' defines a script but uses provider '{provider}'",
+                    scenario.name
+                );
+            }
+            if let Some(pattern) = &scenario.expect.final_matches {
+                regex::Regex::new(pattern).with_context(|| {
+                    format!(
+                        "Scenario '{}' has an invalid final_matches regex",
+                        scenario.name
+                    )
+                })?;
+            }
+            for relative in scenario.files.keys() {
+                let path = Path::new(relative);
+                if path.is_absolute()
+                    || path
+                        .components()
+                        .any(|part| matches!(part, std::path::Component::ParentDir))
+                {
+                    bail!(
+                        "Scenario '{}' file '{relative}' must be a relative path inside the workspace",
+                        scenario.name
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Provider name that selects the built-in [`ScriptedStep`] player.
+pub const SCRIPTED_PROVIDER: &str = "scripted";
+
+/// Suite-level defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScenarioDefaults {
+    /// `scripted` (default when a scenario has a script), `config` for the
+    /// configured default provider and routes, or an explicit provider name.
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(default)]
+    pub runner: Option<EvalRunner>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Which agent entry point drives the scenario.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvalRunner {
+    /// `Agent::turn` with the prompt from `agent/prompt.rs`.
+    #[default]
+    Agent,
+    /// `run_tool_call_loop`, the loop used by `zeroclaw agent` and channels.
+    Loop,
+}
+
+// ── Scenario ────────────────────────────────────────────────────
+
+/// One scripted conversation and its expectations.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// User messages, sent one turn at a time.
+    pub messages: Vec<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(default)]
+    pub runner: Option<EvalRunner>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Real tools to expose, by name. They run inside a throwaway workspace.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Stub tools that return canned output.
+    #[serde(default, rename = "mock_tool")]
+    pub mock_tools: Vec<MockTool>,
+    /// Files seeded into the scenario workspace (relative path → contents).
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    /// Scripted provider responses, consumed in order.
+    #[serde(default)]
+    pub script: Vec<ScriptedStep>,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+impl Scenario {
+    pub fn provider_name(&self, defaults: &ScenarioDefaults) -> String {
+        self.provider
+            .as_deref()
+            .or(defaults.provider.as_deref())
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(if self.script.is_empty() {
+                "config"
+            } else {
+                SCRIPTED_PROVIDER
+            })
+            .to_string()
+    }
+
+    pub fn runner(&self, defaults: &ScenarioDefaults) -> EvalRunner {
+        self.runner.or(defaults.runner).unwrap_or_default()
+    }
+
+    pub fn timeout_secs(&self, defaults: &ScenarioDefaults) -> u64 {
+        self.timeout_secs
+            .or(defaults.timeout_secs)
+            .unwrap_or(DEFAULT_SCENARIO_TIMEOUT_SECS)
+    }
+}
+
+const DEFAULT_SCENARIO_TIMEOUT_SECS: u64 = 300;
+
+/// A stub tool with a fixed response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub output: String,
+    /// When false the tool reports failure with `output` as the error.
+    #[serde(default = "default_true")]
+    pub success: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// ── Expectations ────────────────────────────────────────────────
+
+/// Assertions checked after the scenario finishes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Expectations {
+    /// Tools that must be called, in this relative order.
+    #[serde(default)]
+    pub tools_called: Vec<String>,
+    /// Tools that must not be called.
+    #[serde(default)]
+    pub tools_not_called: Vec<String>,
+    #[serde(default)]
+    pub max_tool_calls: Option<usize>,
+    /// Individual calls that must appear, matched by name and argument subset.
+    #[serde(default, rename = "tool_call")]
+    pub tool_call_args: Vec<ToolCallExpectation>,
+    /// Substrings the final reply must contain (case-insensitive).
+    #[serde(default)]
+    pub final_contains: Vec<String>,
+    /// Substrings the final reply must not contain (case-insensitive).
+    #[serde(default)]
+    pub final_not_contains: Vec<String>,
+    /// Regex the final reply must match.
+    #[serde(default)]
+    pub final_matches: Option<String>,
+    /// Substrings that must appear in the key or content of a memory entry
+    /// written during the scenario.
+    #[serde(default)]
+    pub memory_contains: Vec<String>,
+    /// Require that no memory was written.
+    #[serde(default)]
+    pub no_memory_writes: bool,
+    #[serde(default)]
+    pub max_cost_usd: Option<f64>,
+    #[serde(default)]
+    pub max_total_tokens: Option<u64>,
+    /// Expect the run to fail with an error containing this text.
+    #[serde(default)]
+    pub error_contains: Option<String>,
+}
+
+/// A tool call that must appear with at least these arguments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallExpectation {
+    pub name: String,
+    /// JSON subset the call's arguments must contain.
+    #[serde(default)]
+    pub args: Option<serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUITE: &str = r#"
+name = "workflows"
+
+[defaults]
+runner = "loop"
+
+[[scenario]]
+name = "reads notes"
+messages = ["What is in notes.txt?"]
+tools = ["file_read"]
+files = { "notes.txt" = "buy milk" }
+
+[[scenario.script]]
+tool_calls = [{ name = "file_read", arguments = { path = "notes.txt" } }]
+
+[[scenario.script]]
+text = "You need to buy milk."
+
+[scenario.expect]
+tools_called = ["file_read"]
+final_contains = ["milk"]
+
+[[scenario.expect.tool_call]]
+name = "file_read"
+args = { path = "notes.txt" }
+
+[[scenario]]
+name = "live"
+provider = "config"
+runner = "agent"
+messages = ["hi"]
+"#;
+
+    #[test]
+    fn suite_parses_scenarios_script_and_expectations() {
+        let suite: EvalSuite = toml::from_str(SUITE).unwrap();
+        suite.validate().unwrap();
+        assert_eq!(suite.scenarios.len(), 2);
+
+        let first = &suite.scenarios[0];
+        assert_eq!(first.provider_name(&suite.defaults), SCRIPTED_PROVIDER);
+        assert_eq!(first.runner(&suite.defaults), EvalRunner::Loop);
+        assert_eq!(first.script.len(), 2);
+        assert_eq!(first.files["notes.txt"], "buy milk");
+        assert_eq!(first.expect.tool_call_args[0].name, "file_read");
+
+        let second = &suite.scenarios[1];
+        assert_eq!(second.provider_name(&suite.defaults), "config");
+        assert_eq!(second.runner(&suite.defaults), EvalRunner::Agent);
+    }
+
+    #[test]
+    fn validation_rejects_inconsistent_scenarios() {
+        let mut suite: EvalSuite = toml::from_str(SUITE).unwrap();
+        suite.scenarios[1].name = "reads notes".into();
+        assert!(suite
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("Duplicate"));
+
+        let mut suite: EvalSuite = toml::from_str(SUITE).unwrap();
+        suite.scenarios[0].provider = Some(SCRIPTED_PROVIDER.into());
+        suite.scenarios[0].script.clear();
+        assert!(suite
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("no [[scenario.script]]"));
+
+        let mut suite: EvalSuite = toml::from_str(SUITE).unwrap();
+        suite.scenarios[0]
+            .files
+            .insert("../escape.txt".into(), String::new());
+        assert!(suite
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("relative path"));
+    }
+}
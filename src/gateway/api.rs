@@ -59,6 +59,12 @@ pub struct MemoryStoreBody {
     pub category: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct SessionsQuery {
+    pub channel: Option<String>,
+    pub limit: Option<usize>,
+}
+
 #[derive(Deserialize)]
 pub struct CronAddBody {
     pub name: Option<String>,
@@ -308,6 +314,70 @@ pub async fn handle_api_cron_delete(
     }
 }
 
+/// GET /api/sessions — list persisted channel conversation sessions
+pub async fn handle_api_sessions_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<SessionsQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let config = state.config.lock().clone();
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let result = crate::channels::sessions::ChannelSessionStore::open(
+        &config.workspace_dir,
+        &config.channels_config.sessions,
+    )
+    .and_then(|store| store.list(params.channel.as_deref(), limit));
+
+    match result {
+        Ok(sessions) => Json(serde_json::json!({"sessions": sessions})).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to list sessions: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/sessions/:id — one channel session with its message history
+pub async fn handle_api_session_get(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let config = state.config.lock().clone();
+    let result = crate::channels::sessions::ChannelSessionStore::open(
+        &config.workspace_dir,
+        &config.channels_config.sessions,
+    )
+    .and_then(|store| store.get(&id));
+
+    match result {
+        Ok(Some((session, messages))) => Json(serde_json::json!({
+            "session": session,
+            "messages": messages,
+        }))
+        .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": format!("Session '{id}' not found")})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to load session: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
 /// GET /api/integrations — list all integrations with status
 pub async fn handle_api_integrations(
     State(state): State<AppState>,
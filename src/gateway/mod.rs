@@ -370,6 +370,9 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
             custom_provider_api_mode: config.provider_api.map(|mode| mode.as_compatible_mode()),
             max_tokens_override: None,
             model_support_vision: config.model_support_vision,
+            thinking_budget_tokens: config.effective_thinking_budget_tokens(),
+            ollama: config.ollama.clone(),
+            observer: None,
         },
     )?);
     let model = config
@@ -758,6 +761,8 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .route("/api/memory", get(api::handle_api_memory_list))
         .route("/api/memory", post(api::handle_api_memory_store))
         .route("/api/memory/{key}", delete(api::handle_api_memory_delete))
+        .route("/api/sessions", get(api::handle_api_sessions_list))
+        .route("/api/sessions/{id}", get(api::handle_api_session_get))
         .route("/api/cost", get(api::handle_api_cost))
         .route("/api/cli-tools", get(api::handle_api_cli_tools))
         .route("/api/health", get(api::handle_api_health))
@@ -1241,6 +1246,8 @@ fn handle_webhook_streaming(
                             error_message: None,
                             input_tokens: None,
                             output_tokens: None,
+                            cache_read_tokens: None,
+                            cache_write_tokens: None,
                         },
                     );
                     state_for_call.observer.record_metric(
@@ -1274,6 +1281,8 @@ fn handle_webhook_streaming(
                             error_message: Some(sanitized.clone()),
                             input_tokens: None,
                             output_tokens: None,
+                            cache_read_tokens: None,
+                            cache_write_tokens: None,
                         },
                     );
                     state_for_call.observer.record_metric(
@@ -1340,6 +1349,8 @@ fn handle_webhook_streaming(
                         error_message: None,
                         input_tokens: None,
                         output_tokens: None,
+                        cache_read_tokens: None,
+                        cache_write_tokens: None,
                     },
                 );
                 state_for_stream.observer.record_metric(
@@ -1381,6 +1392,8 @@ fn handle_webhook_streaming(
                     error_message: Some(sanitized.clone()),
                     input_tokens: None,
                     output_tokens: None,
+                    cache_read_tokens: None,
+                    cache_write_tokens: None,
                 },
             );
             state_for_stream.observer.record_metric(
@@ -1571,6 +1584,8 @@ async fn handle_webhook(
                         error_message: Some(sanitized.clone()),
                         input_tokens: None,
                         output_tokens: None,
+                        cache_read_tokens: None,
+                        cache_write_tokens: None,
                     });
                 state.observer.record_metric(
                     &crate::observability::traits::ObserverMetric::RequestLatency(duration),
@@ -1621,6 +1636,8 @@ async fn handle_webhook(
                     error_message: None,
                     input_tokens: None,
                     output_tokens: None,
+                    cache_read_tokens: None,
+                    cache_write_tokens: None,
                 });
             state.observer.record_metric(
                 &crate::observability::traits::ObserverMetric::RequestLatency(duration),
@@ -1652,6 +1669,8 @@ async fn handle_webhook(
                     error_message: Some(sanitized.clone()),
                     input_tokens: None,
                     output_tokens: None,
+                    cache_read_tokens: None,
+                    cache_write_tokens: None,
                 });
             state.observer.record_metric(
                 &crate::observability::traits::ObserverMetric::RequestLatency(duration),
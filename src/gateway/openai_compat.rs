@@ -544,6 +544,8 @@ fn record_success(
             error_message: None,
             input_tokens: None,
             output_tokens: None,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
     state
         .observer
@@ -567,6 +569,8 @@ fn record_failure(
             error_message: Some(error_message.to_string()),
             input_tokens: None,
             output_tokens: None,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
     state
         .observer
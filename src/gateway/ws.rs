@@ -3,18 +3,27 @@
 //! Protocol:
 //! ```text
 //! Client -> Server: {"type":"message","content":"Hello"}
+//! Server -> Client: {"type":"thinking","index":0,"content":"The user wants..."}
 //! Server -> Client: {"type":"chunk","content":"Hi! "}
+//! Server -> Client: {"type":"tool_call_delta","index":0,"id":"call_1","name":"shell","args_delta":"{\"comm"}
 //! Server -> Client: {"type":"tool_call","name":"shell","args":{...}}
 //! Server -> Client: {"type":"tool_result","name":"shell","output":"..."}
 //! Server -> Client: {"type":"done","full_response":"..."}
 //! ```
+//!
+//! `chunk` and `tool_call_delta` frames are only sent when the provider can
+//! stream native tool calls; `id` and `name` appear on the first fragment of
+//! each call and `args_delta` carries raw, possibly partial JSON.
+//! `thinking` frames carry reasoning text and are only sent when
+//! `observability.show_reasoning` is enabled.
 
 use super::AppState;
 use crate::agent::loop_::{
     build_shell_policy_instructions, build_tool_instructions_from_specs, run_tool_call_loop,
+    scope_tool_stream_events,
 };
 use crate::approval::ApprovalManager;
-use crate::providers::ChatMessage;
+use crate::providers::{ChatMessage, StreamEvent};
 use axum::{
     extract::{
         ws::{Message, WebSocket},
@@ -113,6 +122,33 @@ fn finalize_ws_response(
     EMPTY_WS_RESPONSE_FALLBACK.to_string()
 }
 
+/// Client frame for a streamed model event; usage and completion markers are
+/// not forwarded (the `done` frame follows the whole turn), and reasoning is
+/// forwarded only when `show_reasoning` is set.
+fn stream_event_frame(event: &StreamEvent, show_reasoning: bool) -> Option<serde_json::Value> {
+    match event {
+        StreamEvent::TextDelta(text) => Some(serde_json::json!({
+            "type": "chunk",
+            "content": text,
+        })),
+        StreamEvent::ToolCallDelta(delta) => Some(serde_json::json!({
+            "type": "tool_call_delta",
+            "index": delta.index,
+            "id": delta.id,
+            "name": delta.name,
+            "args_delta": delta.arguments,
+        })),
+        StreamEvent::ThinkingDelta(delta) if show_reasoning && !delta.text.is_empty() => {
+            Some(serde_json::json!({
+                "type": "thinking",
+                "index": delta.index,
+                "content": delta.text,
+            }))
+        }
+        StreamEvent::ThinkingDelta(_) | StreamEvent::Usage(_) | StreamEvent::Done => None,
+    }
+}
+
 fn build_ws_system_prompt(
     config: &crate::config::Config,
     model: &str,
@@ -261,7 +297,31 @@ async fn handle_socket(mut socket: WebSocket, state: AppState) {
         }));
 
         // Full agentic loop with tools (includes WASM skills, shell, memory, etc.)
-        match super::run_gateway_chat_with_tools(&state, &content).await {
+        // Streamed model output is relayed while the loop runs.
+        let show_reasoning = state.config.lock().observability.show_reasoning;
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::channel::<StreamEvent>(64);
+        let chat = scope_tool_stream_events(
+            event_tx,
+            super::run_gateway_chat_with_tools(&state, &content),
+        );
+        tokio::pin!(chat);
+        let result = loop {
+            tokio::select! {
+                result = &mut chat => break result,
+                Some(event) = event_rx.recv() => {
+                    if let Some(frame) = stream_event_frame(&event, show_reasoning) {
+                        let _ = socket.send(Message::Text(frame.to_string().into())).await;
+                    }
+                }
+            }
+        };
+        while let Ok(event) = event_rx.try_recv() {
+            if let Some(frame) = stream_event_frame(&event, show_reasoning) {
+                let _ = socket.send(Message::Text(frame.to_string().into())).await;
+            }
+        }
+
+        match result {
             Ok(response) => {
                 let safe_response =
                     finalize_ws_response(&response, &history, state.tools_registry_exec.as_ref());
@@ -383,6 +443,43 @@ mod tests {
         assert!(extract_ws_bearer_token(&headers).is_none());
     }
 
+    #[test]
+    fn stream_event_frame_maps_text_and_tool_call_deltas() {
+        let chunk = stream_event_frame(&StreamEvent::TextDelta("Hi".into()), false).unwrap();
+        assert_eq!(chunk, serde_json::json!({"type": "chunk", "content": "Hi"}));
+
+        let delta = stream_event_frame(
+            &StreamEvent::ToolCallDelta(crate::providers::traits::ToolCallDelta {
+                index: 1,
+                id: Some("call_1".into()),
+                name: Some("shell".into()),
+                arguments: "{\"comm".into(),
+            }),
+            false,
+        )
+        .unwrap();
+        assert_eq!(delta["type"], "tool_call_delta");
+        assert_eq!(delta["index"], 1);
+        assert_eq!(delta["name"], "shell");
+        assert_eq!(delta["args_delta"], "{\"comm");
+
+        assert!(stream_event_frame(&StreamEvent::Done, false).is_none());
+    }
+
+    #[test]
+    fn stream_event_frame_forwards_thinking_only_when_enabled() {
+        let event = StreamEvent::ThinkingDelta(crate::providers::traits::ThinkingDelta {
+            index: 0,
+            text: "Checking the logs first.".into(),
+            ..Default::default()
+        });
+
+        assert!(stream_event_frame(&event, false).is_none());
+        let frame = stream_event_frame(&event, true).unwrap();
+        assert_eq!(frame["type"], "thinking");
+        assert_eq!(frame["content"], "Checking the logs first.");
+    }
+
     struct MockScheduleTool;
 
     #[async_trait]
@@ -48,6 +48,7 @@ pub mod coordination;
 pub(crate) mod cost;
 pub(crate) mod cron;
 pub mod economic;
+pub mod eval;
 pub(crate) mod daemon;
 pub(crate) mod doctor;
 pub mod gateway;
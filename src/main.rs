@@ -62,6 +62,7 @@ mod cost;
 mod cron;
 mod daemon;
 mod doctor;
+mod eval;
 mod gateway;
 mod goals;
 mod hardware;
@@ -294,6 +295,24 @@ Examples:
         service_command: ServiceCommands,
     },
 
+    /// Run agent evaluation suites
+    #[command(long_about = "\
+Run agent evaluation suites.
+
+A suite is a TOML file of scenarios: user messages, a scripted or \
+real provider, sandboxed tools, and assertions on tool calls, the \
+final reply, memory writes and cost. Exits non-zero when any \
+scenario fails.
+
+Examples:
+  zeroclaw eval run evals/workflows.toml
+  zeroclaw eval run evals/workflows.toml --filter notes
+  zeroclaw eval run evals/workflows.toml --format junit --output eval.xml")]
+    Eval {
+        #[command(subcommand)]
+        eval_command: EvalCommands,
+    },
+
     /// Run diagnostics for daemon/scheduler/channel freshness
     Doctor {
         #[command(subcommand)]
@@ -650,6 +669,20 @@ enum ModelCommands {
         /// Provider name (defaults to configured default provider)
         #[arg(long)]
         provider: Option<String>,
+
+        /// List models installed on the local Ollama server instead
+        #[arg(long, conflicts_with = "provider")]
+        local: bool,
+    },
+    /// Download a model to the local Ollama server
+    Pull {
+        /// Model name (e.g. llama3.2:3b)
+        model: String,
+    },
+    /// Remove a model from the local Ollama server
+    Remove {
+        /// Model name to remove
+        model: String,
     },
     /// Set the default model in config
     Set {
@@ -660,6 +693,24 @@ enum ModelCommands {
     Status,
 }
 
+#[derive(Subcommand, Debug)]
+enum EvalCommands {
+    /// Run every scenario in a suite file
+    Run {
+        /// Path to the suite TOML file
+        suite: std::path::PathBuf,
+        /// Report format
+        #[arg(long, value_enum, default_value = "text")]
+        format: eval::ReportFormat,
+        /// Write the report to this file (a text summary is still printed)
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+        /// Only run scenarios whose name contains this text
+        #[arg(long)]
+        filter: Option<String>,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 enum DoctorCommands {
     /// Probe model catalogs across providers and report availability
@@ -687,6 +738,18 @@ enum DoctorCommands {
         #[arg(long, default_value = "20")]
         limit: usize,
     },
+    /// Re-run the agent turn containing a trace event with recorded provider
+    /// responses and tool outputs (requires runtime_trace_replay = true)
+    Replay {
+        /// Any trace event id from the turn (see `zeroclaw doctor traces`)
+        id: String,
+        /// Call the configured provider instead of replaying recorded responses
+        #[arg(long)]
+        live_provider: bool,
+        /// Execute this tool for real instead of returning its recorded output (repeatable)
+        #[arg(long = "live-tool", value_name = "TOOL")]
+        live_tools: Vec<String>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -823,6 +886,7 @@ async fn main() -> Result<()> {
     let mut config = Config::load_or_init().await?;
     config.apply_env_overrides();
     observability::runtime_trace::init_from_config(&config.observability, &config.workspace_dir);
+    observability::runtime_trace::set_config_fingerprint(&config);
     if config.security.otp.enabled {
         let config_dir = config
             .config_path
@@ -1045,9 +1109,15 @@ async fn main() -> Result<()> {
                     onboard::run_models_refresh(&config, provider.as_deref(), force).await
                 }
             }
-            ModelCommands::List { provider } => {
-                onboard::run_models_list(&config, provider.as_deref()).await
+            ModelCommands::List { provider, local } => {
+                if local {
+                    onboard::run_models_list_local(&config).await
+                } else {
+                    onboard::run_models_list(&config, provider.as_deref()).await
+                }
             }
+            ModelCommands::Pull { model } => onboard::run_models_pull(&config, &model).await,
+            ModelCommands::Remove { model } => onboard::run_models_remove(&config, &model).await,
             ModelCommands::Set { model } => onboard::run_models_set(&config, &model).await,
             ModelCommands::Status => onboard::run_models_status(&config).await,
         },
@@ -1093,6 +1163,24 @@ async fn main() -> Result<()> {
             service::handle_command(&service_command, &config, init_system)
         }
 
+        Commands::Eval { eval_command } => match eval_command {
+            EvalCommands::Run {
+                suite,
+                format,
+                output,
+                filter,
+            } => {
+                eval::run_command(
+                    &config,
+                    &suite,
+                    format,
+                    output.as_deref(),
+                    filter.as_deref(),
+                )
+                .await
+            }
+        },
+
         Commands::Doctor { doctor_command } => match doctor_command {
             Some(DoctorCommands::Models {
                 provider,
@@ -1110,6 +1198,21 @@ async fn main() -> Result<()> {
                 contains.as_deref(),
                 limit,
             ),
+            Some(DoctorCommands::Replay {
+                id,
+                live_provider,
+                live_tools,
+            }) => {
+                doctor::replay::run(
+                    &config,
+                    &id,
+                    &doctor::replay::ReplayOptions {
+                        live_provider,
+                        live_tools,
+                    },
+                )
+                .await
+            }
             None => doctor::run(&config),
         },
 
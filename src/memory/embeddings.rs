@@ -154,6 +154,119 @@ impl EmbeddingProvider for OpenAiEmbedding {
     }
 }
 
+// ── Local hashed n-gram provider (offline) ───────────────────
+
+/// Dimension used by [`LocalEmbedding`] when none is configured.
+pub const DEFAULT_LOCAL_DIMENSIONS: usize = 384;
+
+/// Offline embeddings built from signed feature hashing of word unigrams,
+/// word bigrams and character trigrams. No network, model files or GPU:
+/// vectors are deterministic, so stored embeddings stay valid across
+/// restarts and releases. Captures lexical and sub-word similarity
+/// (typos, inflections, shared phrases), not deep semantics.
+#[derive(Debug, Clone)]
+pub struct LocalEmbedding {
+    dims: usize,
+}
+
+impl LocalEmbedding {
+    const WORD_WEIGHT: f32 = 1.0;
+    const BIGRAM_WEIGHT: f32 = 0.7;
+    const TRIGRAM_WEIGHT: f32 = 0.4;
+
+    pub fn new(dims: usize) -> Self {
+        Self {
+            dims: if dims == 0 {
+                DEFAULT_LOCAL_DIMENSIONS
+            } else {
+                dims
+            },
+        }
+    }
+
+    /// Embed one text synchronously. The result is L2-normalized, or all
+    /// zeros when the text has no alphanumeric content.
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dims];
+        let lower = text.to_lowercase();
+        let tokens: Vec<&str> = lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|token| !token.is_empty())
+            .collect();
+
+        for (i, token) in tokens.iter().enumerate() {
+            self.add_feature(&mut vector, &[b"w:", token.as_bytes()], Self::WORD_WEIGHT);
+            if let Some(previous) = i.checked_sub(1).map(|p| tokens[p]) {
+                self.add_feature(
+                    &mut vector,
+                    &[b"b:", previous.as_bytes(), b" ", token.as_bytes()],
+                    Self::BIGRAM_WEIGHT,
+                );
+            }
+
+            let padded: Vec<char> = std::iter::once('^')
+                .chain(token.chars())
+                .chain(std::iter::once('$'))
+                .collect();
+            if padded.len() > 3 {
+                for window in padded.windows(3) {
+                    let trigram: String = window.iter().collect();
+                    self.add_feature(
+                        &mut vector,
+                        &[b"c:", trigram.as_bytes()],
+                        Self::TRIGRAM_WEIGHT,
+                    );
+                }
+            }
+        }
+
+        // Sublinear term frequency keeps repeated words from dominating.
+        for value in &mut vector {
+            *value = value.signum() * value.abs().ln_1p();
+        }
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for value in &mut vector {
+                *value /= norm;
+            }
+        }
+        vector
+    }
+
+    fn add_feature(&self, vector: &mut [f32], parts: &[&[u8]], weight: f32) {
+        let hash = fnv1a(parts);
+        #[allow(clippy::cast_possible_truncation)]
+        let index = (hash % self.dims as u64) as usize;
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[index] += sign * weight;
+    }
+}
+
+/// FNV-1a: stable across platforms and Rust versions, unlike `DefaultHasher`.
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalEmbedding {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dims
+    }
+
+    async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| self.embed_text(text)).collect())
+    }
+}
+
 // ── Factory ──────────────────────────────────────────────────
 
 pub fn create_embedding_provider(
@@ -186,6 +299,7 @@ pub fn create_embedding_provider(
             let key = api_key.unwrap_or("");
             Box::new(OpenAiEmbedding::new(base_url, key, model, dims))
         }
+        "local" => Box::new(LocalEmbedding::new(dims)),
         _ => Box::new(NoopEmbedding),
     }
 }
@@ -355,4 +469,45 @@ mod tests {
             "https://my-api.example.com/api/v2/embeddings"
         );
     }
+
+    #[test]
+    fn factory_local() {
+        let p = create_embedding_provider("local", None, "ignored", 256);
+        assert_eq!(p.name(), "local");
+        assert_eq!(p.dimensions(), 256);
+    }
+
+    #[test]
+    fn local_zero_dims_uses_default() {
+        let p = LocalEmbedding::new(0);
+        assert_eq!(p.dimensions(), DEFAULT_LOCAL_DIMENSIONS);
+    }
+
+    #[tokio::test]
+    async fn local_embeddings_are_deterministic_and_normalized() {
+        let p = LocalEmbedding::new(128);
+        let first = p.embed_one("Rust ownership and borrowing").await.unwrap();
+        let second = p.embed_one("Rust ownership and borrowing").await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 128);
+        let norm: f32 = first.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-4);
+
+        let empty = p.embed_one("  ...  ").await.unwrap();
+        assert!(empty.iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn local_embeddings_rank_related_text_higher() {
+        let p = LocalEmbedding::new(DEFAULT_LOCAL_DIMENSIONS);
+        let query = p.embed_text("user prefers dark theme in the editor");
+        let related = p.embed_text("The user's preferred editor theme is dark");
+        let unrelated = p.embed_text("deploy the kubernetes cluster on friday");
+        let related_score = crate::memory::vector::cosine_similarity(&query, &related);
+        let unrelated_score = crate::memory::vector::cosine_similarity(&query, &unrelated);
+        assert!(
+            related_score > unrelated_score + 0.2,
+            "related={related_score} unrelated={unrelated_score}"
+        );
+    }
 }
@@ -402,6 +402,22 @@ pub fn create_memory_for_migration(
     )
 }
 
+/// Factory: create the `[memory]` embedding provider, resolving `hint:` models
+/// through `[[embedding_routes]]`.
+pub fn create_embedder(
+    config: &MemoryConfig,
+    embedding_routes: &[EmbeddingRouteConfig],
+    api_key: Option<&str>,
+) -> Arc<dyn embeddings::EmbeddingProvider> {
+    let resolved = resolve_embedding_config(config, embedding_routes, api_key);
+    Arc::from(embeddings::create_embedding_provider(
+        &resolved.provider,
+        resolved.api_key.as_deref(),
+        &resolved.model,
+        resolved.dimensions,
+    ))
+}
+
 /// Factory: create an optional response cache from config.
 pub fn create_response_cache(config: &MemoryConfig, workspace_dir: &Path) -> Option<ResponseCache> {
     if !config.response_cache_enabled {
@@ -229,6 +229,7 @@ impl SqliteMemory {
 
         let hash = Self::content_hash(text);
         let now = Local::now().to_rfc3339();
+        let dims = self.embedder.dimensions();
 
         // Check cache (offloaded to blocking thread)
         let conn = self.conn.clone();
@@ -239,7 +240,8 @@ impl SqliteMemory {
             let mut stmt =
                 conn.prepare("SELECT embedding FROM embedding_cache WHERE content_hash = ?1")?;
             let blob: Option<Vec<u8>> = stmt.query_row(params![hash_c], |row| row.get(0)).ok();
-            if let Some(bytes) = blob {
+            // Entries cached by an embedder with another dimension are stale.
+            if let Some(bytes) = blob.filter(|bytes| bytes.len() == dims * 4) {
                 conn.execute(
                     "UPDATE embedding_cache SET accessed_at = ?1 WHERE content_hash = ?2",
                     params![now_c, hash_c],
@@ -9,6 +9,11 @@ use crate::cost::{CostTracker, TokenUsage};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Input price (USD per 1M tokens) assumed for models without configured pricing.
+pub(crate) const DEFAULT_INPUT_PRICE: f64 = 3.0;
+/// Output price (USD per 1M tokens) assumed for models without configured pricing.
+pub(crate) const DEFAULT_OUTPUT_PRICE: f64 = 15.0;
+
 /// Observer that records token usage to a CostTracker.
 ///
 /// Listens for `LlmResponse` events and calculates costs using model pricing.
@@ -27,43 +32,15 @@ impl CostObserver {
             tracker,
             prices,
             // Conservative defaults for unknown models
-            default_input_price: 3.0,
-            default_output_price: 15.0,
+            default_input_price: DEFAULT_INPUT_PRICE,
+            default_output_price: DEFAULT_OUTPUT_PRICE,
         }
     }
 
-    /// Look up pricing for a model, trying various name formats.
-    fn get_pricing(&self, provider: &str, model: &str) -> (f64, f64) {
-        // Try exact match first: "provider/model"
-        let full_name = format!("{provider}/{model}");
-        if let Some(pricing) = self.prices.get(&full_name) {
-            return (pricing.input, pricing.output);
-        }
-
-        // Try just the model name
-        if let Some(pricing) = self.prices.get(model) {
-            return (pricing.input, pricing.output);
-        }
-
-        // Try model family matching (e.g., "claude-sonnet-4" matches any claude-sonnet-4-*)
-        for (key, pricing) in &self.prices {
-            // Strip provider prefix if present
-            let key_model = key.split('/').last().unwrap_or(key);
-
-            // Check if model starts with the key (family match)
-            if model.starts_with(key_model) || key_model.starts_with(model) {
-                return (pricing.input, pricing.output);
-            }
-
-            // Check for common model name patterns
-            // e.g., "claude-3-5-sonnet-20241022" should match "claude-3.5-sonnet"
-            let normalized_model = model.replace('-', ".");
-            let normalized_key = key_model.replace('-', ".");
-            if normalized_model.contains(&normalized_key)
-                || normalized_key.contains(&normalized_model)
-            {
-                return (pricing.input, pricing.output);
-            }
+    /// Look up pricing for a model, falling back to conservative defaults.
+    fn get_pricing(&self, provider: &str, model: &str) -> ModelPricing {
+        if let Some(pricing) = lookup_pricing(&self.prices, provider, model) {
+            return pricing;
         }
 
         // Fall back to defaults
@@ -74,10 +51,54 @@ impl CostObserver {
             self.default_input_price,
             self.default_output_price
         );
-        (self.default_input_price, self.default_output_price)
+        ModelPricing {
+            input: self.default_input_price,
+            output: self.default_output_price,
+            ..ModelPricing::default()
+        }
     }
 }
 
+/// Look up pricing for a model, trying various name formats.
+pub(crate) fn lookup_pricing(
+    prices: &HashMap<String, ModelPricing>,
+    provider: &str,
+    model: &str,
+) -> Option<ModelPricing> {
+    // Try exact match first: "provider/model"
+    let full_name = format!("{provider}/{model}");
+    if let Some(pricing) = prices.get(&full_name) {
+        return Some(pricing.clone());
+    }
+
+    // Try just the model name
+    if let Some(pricing) = prices.get(model) {
+        return Some(pricing.clone());
+    }
+
+    // Try model family matching (e.g., "claude-sonnet-4" matches any claude-sonnet-4-*)
+    for (key, pricing) in prices {
+        // Strip provider prefix if present
+        let key_model = key.split('/').next_back().unwrap_or(key);
+
+        // Check if model starts with the key (family match)
+        if model.starts_with(key_model) || key_model.starts_with(model) {
+            return Some(pricing.clone());
+        }
+
+        // Check for common model name patterns
+        // e.g., "claude-3-5-sonnet-20241022" should match "claude-3.5-sonnet"
+        let normalized_model = model.replace('-', ".");
+        let normalized_key = key_model.replace('-', ".");
+        if normalized_model.contains(&normalized_key) || normalized_key.contains(&normalized_model)
+        {
+            return Some(pricing.clone());
+        }
+    }
+
+    None
+}
+
 impl Observer for CostObserver {
     fn record_event(&self, event: &ObserverEvent) {
         if let ObserverEvent::LlmResponse {
@@ -86,6 +107,8 @@ impl Observer for CostObserver {
             success: true,
             input_tokens,
             output_tokens,
+            cache_read_tokens,
+            cache_write_tokens,
             ..
         } = event
         {
@@ -97,15 +120,16 @@ impl Observer for CostObserver {
                 return;
             }
 
-            let (input_price, output_price) = self.get_pricing(provider, model);
+            let pricing = self.get_pricing(provider, model);
             let full_model_name = format!("{provider}/{model}");
 
-            let usage = TokenUsage::new(
+            let usage = TokenUsage::with_cache(
                 full_model_name,
                 input,
                 output,
-                input_price,
-                output_price,
+                cache_read_tokens.unwrap_or(0),
+                cache_write_tokens.unwrap_or(0),
+                &pricing,
             );
 
             if let Err(e) = self.tracker.record_usage(usage) {
@@ -153,6 +177,7 @@ mod tests {
             ModelPricing {
                 input: 3.0,
                 output: 15.0,
+                ..ModelPricing::default()
             },
         );
 
@@ -166,6 +191,8 @@ mod tests {
             error_message: None,
             input_tokens: Some(1000),
             output_tokens: Some(500),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
@@ -187,6 +214,8 @@ mod tests {
             error_message: Some("API error".into()),
             input_tokens: Some(1000),
             output_tokens: Some(500),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
@@ -206,6 +235,8 @@ mod tests {
             error_message: None,
             input_tokens: None,
             output_tokens: None,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
@@ -225,6 +256,8 @@ mod tests {
             error_message: None,
             input_tokens: Some(1_000_000), // 1M tokens
             output_tokens: Some(1_000_000),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
@@ -242,6 +275,7 @@ mod tests {
             ModelPricing {
                 input: 5.0,
                 output: 15.0,
+                ..ModelPricing::default()
             },
         );
 
@@ -256,10 +290,48 @@ mod tests {
             error_message: None,
             input_tokens: Some(1_000_000),
             output_tokens: Some(0),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
         // Should use $5 input price, not default $3
         assert!((summary.session_cost_usd - 5.0).abs() < 0.01);
     }
+
+    #[test]
+    fn cost_observer_prices_cache_tokens_separately() {
+        let (_tmp, tracker) = create_test_tracker();
+        let mut prices = HashMap::new();
+        prices.insert(
+            "anthropic/claude-sonnet-4-20250514".into(),
+            ModelPricing {
+                input: 3.0,
+                output: 15.0,
+                cache_read: Some(0.3),
+                cache_write: Some(3.75),
+            },
+        );
+
+        let observer = CostObserver::new(tracker.clone(), prices);
+
+        observer.record_event(&ObserverEvent::LlmResponse {
+            provider: "anthropic".into(),
+            model: "claude-sonnet-4-20250514".into(),
+            duration: Duration::from_millis(100),
+            success: true,
+            error_message: None,
+            input_tokens: Some(1_000_000),
+            output_tokens: Some(0),
+            cache_read_tokens: Some(800_000),
+            cache_write_tokens: Some(100_000),
+        });
+
+        let summary = tracker.get_summary().unwrap();
+        // 100k uncached * $3 + 800k read * $0.3 + 100k write * $3.75 = 0.3 + 0.24 + 0.375
+        assert!((summary.session_cost_usd - 0.915).abs() < 0.0001);
+        let stats = &summary.by_model["anthropic/claude-sonnet-4-20250514"];
+        assert_eq!(stats.cache_read_tokens, 800_000);
+        assert_eq!(stats.cache_write_tokens, 100_000);
+    }
 }
@@ -3,11 +3,21 @@ use std::any::Any;
 use tracing::info;
 
 /// Log-based observer — uses tracing, zero external deps
-pub struct LogObserver;
+pub struct LogObserver {
+    show_reasoning: bool,
+}
 
 impl LogObserver {
     pub fn new() -> Self {
-        Self
+        Self {
+            show_reasoning: false,
+        }
+    }
+
+    /// Include reasoning text in `llm.reasoning` log lines.
+    pub fn with_reasoning_text(mut self, show: bool) -> Self {
+        self.show_reasoning = show;
+        self
     }
 }
 
@@ -44,6 +54,22 @@ impl Observer for LogObserver {
             ObserverEvent::ChannelMessage { channel, direction } => {
                 info!(channel = %channel, direction = %direction, "channel.message");
             }
+            ObserverEvent::ModelPull {
+                provider,
+                model,
+                status,
+                completed_bytes,
+                total_bytes,
+            } => {
+                info!(
+                    provider = %provider,
+                    model = %model,
+                    status = %status,
+                    completed_bytes = ?completed_bytes,
+                    total_bytes = ?total_bytes,
+                    "model.pull"
+                );
+            }
             ObserverEvent::HeartbeatTick => {
                 info!("heartbeat.tick");
             }
@@ -70,6 +96,8 @@ impl Observer for LogObserver {
                 error_message,
                 input_tokens,
                 output_tokens,
+                cache_read_tokens,
+                cache_write_tokens,
             } => {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
                 info!(
@@ -80,9 +108,23 @@ impl Observer for LogObserver {
                     error = ?error_message,
                     input_tokens = ?input_tokens,
                     output_tokens = ?output_tokens,
+                    cache_read_tokens = ?cache_read_tokens,
+                    cache_write_tokens = ?cache_write_tokens,
                     "llm.response"
                 );
             }
+            ObserverEvent::LlmReasoning {
+                provider,
+                model,
+                blocks,
+                text,
+            } => {
+                if self.show_reasoning {
+                    info!(provider = %provider, model = %model, blocks = blocks, chars = text.len(), text = %text, "llm.reasoning");
+                } else {
+                    info!(provider = %provider, model = %model, blocks = blocks, chars = text.len(), "llm.reasoning");
+                }
+            }
         }
     }
 
@@ -152,6 +194,8 @@ mod tests {
             error_message: None,
             input_tokens: Some(100),
             output_tokens: Some(50),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
         obs.record_event(&ObserverEvent::LlmResponse {
             provider: "openrouter".into(),
@@ -161,6 +205,8 @@ mod tests {
             error_message: Some("rate limited".into()),
             input_tokens: None,
             output_tokens: None,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
         obs.record_event(&ObserverEvent::ToolCall {
             tool: "shell".into(),
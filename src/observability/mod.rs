@@ -57,7 +57,7 @@ pub fn create_observer_with_cost_tracking(
 
 fn create_observer_internal(config: &ObservabilityConfig) -> Box<dyn Observer> {
     match config.backend.as_str() {
-        "log" => Box::new(LogObserver::new()),
+        "log" => Box::new(LogObserver::new().with_reasoning_text(config.show_reasoning)),
         "prometheus" => Box::new(PrometheusObserver::new()),
         "otel" | "opentelemetry" | "otlp" => {
             #[cfg(feature = "observability-otel")]
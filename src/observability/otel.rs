@@ -187,8 +187,10 @@ impl Observer for OtelObserver {
                 );
             }
             ObserverEvent::LlmRequest { .. }
+            | ObserverEvent::LlmReasoning { .. }
             | ObserverEvent::ToolCallStart { .. }
-            | ObserverEvent::TurnComplete => {}
+            | ObserverEvent::TurnComplete
+            | ObserverEvent::ModelPull { .. } => {}
             ObserverEvent::LlmResponse {
                 provider,
                 model,
@@ -197,6 +199,8 @@ impl Observer for OtelObserver {
                 error_message: _,
                 input_tokens: _,
                 output_tokens: _,
+                cache_read_tokens: _,
+                cache_write_tokens: _,
             } => {
                 let secs = duration.as_secs_f64();
                 let attrs = [
@@ -417,6 +421,8 @@ mod tests {
             error_message: None,
             input_tokens: Some(100),
             output_tokens: Some(50),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
             provider: "openrouter".into(),
@@ -497,6 +503,8 @@ mod tests {
             error_message: Some("404 Not Found".into()),
             input_tokens: None,
             output_tokens: None,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
     }
 
@@ -12,6 +12,8 @@ pub struct PrometheusObserver {
     llm_requests: IntCounterVec,
     tokens_input_total: IntCounterVec,
     tokens_output_total: IntCounterVec,
+    tokens_cache_read_total: IntCounterVec,
+    tokens_cache_write_total: IntCounterVec,
     tool_calls: IntCounterVec,
     channel_messages: IntCounterVec,
     heartbeat_ticks: prometheus::IntCounter,
@@ -59,6 +61,24 @@ impl PrometheusObserver {
         )
         .expect("valid metric");
 
+        let tokens_cache_read_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "zeroclaw_tokens_cache_read_total",
+                "Total input tokens served from the provider prompt cache",
+            ),
+            &["provider", "model"],
+        )
+        .expect("valid metric");
+
+        let tokens_cache_write_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "zeroclaw_tokens_cache_write_total",
+                "Total input tokens written to the provider prompt cache",
+            ),
+            &["provider", "model"],
+        )
+        .expect("valid metric");
+
         let tool_calls = IntCounterVec::new(
             prometheus::Opts::new("zeroclaw_tool_calls_total", "Total tool calls"),
             &["tool", "success"],
@@ -135,6 +155,12 @@ impl PrometheusObserver {
         registry
             .register(Box::new(tokens_output_total.clone()))
             .ok();
+        registry
+            .register(Box::new(tokens_cache_read_total.clone()))
+            .ok();
+        registry
+            .register(Box::new(tokens_cache_write_total.clone()))
+            .ok();
         registry.register(Box::new(tool_calls.clone())).ok();
         registry.register(Box::new(channel_messages.clone())).ok();
         registry.register(Box::new(heartbeat_ticks.clone())).ok();
@@ -152,6 +178,8 @@ impl PrometheusObserver {
             llm_requests,
             tokens_input_total,
             tokens_output_total,
+            tokens_cache_read_total,
+            tokens_cache_write_total,
             tool_calls,
             channel_messages,
             heartbeat_ticks,
@@ -204,6 +232,8 @@ impl Observer for PrometheusObserver {
                 success,
                 input_tokens,
                 output_tokens,
+                cache_read_tokens,
+                cache_write_tokens,
                 ..
             } => {
                 let success_str = if *success { "true" } else { "false" };
@@ -220,10 +250,22 @@ impl Observer for PrometheusObserver {
                         .with_label_values(&[provider.as_str(), model.as_str()])
                         .inc_by(*output);
                 }
+                if let Some(read) = cache_read_tokens {
+                    self.tokens_cache_read_total
+                        .with_label_values(&[provider.as_str(), model.as_str()])
+                        .inc_by(*read);
+                }
+                if let Some(write) = cache_write_tokens {
+                    self.tokens_cache_write_total
+                        .with_label_values(&[provider.as_str(), model.as_str()])
+                        .inc_by(*write);
+                }
             }
             ObserverEvent::ToolCallStart { tool: _ }
             | ObserverEvent::TurnComplete
-            | ObserverEvent::LlmRequest { .. } => {}
+            | ObserverEvent::LlmRequest { .. }
+            | ObserverEvent::LlmReasoning { .. }
+            | ObserverEvent::ModelPull { .. } => {}
             ObserverEvent::ToolCall {
                 tool,
                 duration,
@@ -448,6 +490,8 @@ mod tests {
             error_message: None,
             input_tokens: Some(100),
             output_tokens: Some(50),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
         obs.record_event(&ObserverEvent::LlmResponse {
             provider: "openrouter".into(),
@@ -457,6 +501,8 @@ mod tests {
             error_message: None,
             input_tokens: Some(200),
             output_tokens: Some(80),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
 
         let output = obs.encode();
@@ -483,6 +529,8 @@ mod tests {
             error_message: Some("timeout".into()),
             input_tokens: None,
             output_tokens: None,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
 
         let output = obs.encode();
@@ -493,4 +541,29 @@ mod tests {
         assert!(!output.contains("zeroclaw_tokens_input_total{"));
         assert!(!output.contains("zeroclaw_tokens_output_total{"));
     }
+
+    #[test]
+    fn llm_response_tracks_cache_tokens() {
+        let obs = PrometheusObserver::new();
+
+        obs.record_event(&ObserverEvent::LlmResponse {
+            provider: "anthropic".into(),
+            model: "claude-sonnet".into(),
+            duration: Duration::from_millis(200),
+            success: true,
+            error_message: None,
+            input_tokens: Some(1000),
+            output_tokens: Some(50),
+            cache_read_tokens: Some(800),
+            cache_write_tokens: Some(150),
+        });
+
+        let output = obs.encode();
+        assert!(output.contains(
+            r#"zeroclaw_tokens_cache_read_total{model="claude-sonnet",provider="anthropic"} 800"#
+        ));
+        assert!(output.contains(
+            r#"zeroclaw_tokens_cache_write_total{model="claude-sonnet",provider="anthropic"} 150"#
+        ));
+    }
 }
@@ -1,8 +1,9 @@
-use crate::config::ObservabilityConfig;
+use crate::config::{Config, ObservabilityConfig};
 use anyhow::Result;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -10,6 +11,8 @@ use std::sync::{Arc, LazyLock, RwLock};
 use uuid::Uuid;
 
 const DEFAULT_TRACE_REL_PATH: &str = "state/runtime-trace.jsonl";
+/// Hex characters kept from the config digest.
+const CONFIG_FINGERPRINT_CHARS: usize = 16;
 
 /// Runtime trace storage policy.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -55,6 +58,8 @@ struct RuntimeTraceLogger {
     mode: RuntimeTraceStorageMode,
     max_entries: usize,
     path: PathBuf,
+    show_reasoning: bool,
+    capture_replay: bool,
     write_lock: std::sync::Mutex<()>,
 }
 
@@ -64,6 +69,8 @@ impl RuntimeTraceLogger {
             mode,
             max_entries: max_entries.max(1),
             path,
+            show_reasoning: false,
+            capture_replay: false,
             write_lock: std::sync::Mutex::new(()),
         }
     }
@@ -144,6 +151,8 @@ impl RuntimeTraceLogger {
 static TRACE_LOGGER: LazyLock<RwLock<Option<Arc<RuntimeTraceLogger>>>> =
     LazyLock::new(|| RwLock::new(None));
 
+static CONFIG_FINGERPRINT: LazyLock<RwLock<Option<String>>> = LazyLock::new(|| RwLock::new(None));
+
 /// Resolve runtime trace storage mode from config.
 pub fn storage_mode_from_config(config: &ObservabilityConfig) -> RuntimeTraceStorageMode {
     let mode = RuntimeTraceStorageMode::from_raw(&config.runtime_trace_mode);
@@ -181,17 +190,65 @@ pub fn init_from_config(config: &ObservabilityConfig, workspace_dir: &Path) {
     let logger = if mode == RuntimeTraceStorageMode::None {
         None
     } else {
-        Some(Arc::new(RuntimeTraceLogger::new(
-            mode,
-            config.runtime_trace_max_entries.max(1),
-            resolve_trace_path(config, workspace_dir),
-        )))
+        Some(Arc::new(RuntimeTraceLogger {
+            show_reasoning: config.show_reasoning,
+            capture_replay: config.runtime_trace_replay,
+            ..RuntimeTraceLogger::new(
+                mode,
+                config.runtime_trace_max_entries.max(1),
+                resolve_trace_path(config, workspace_dir),
+            )
+        }))
     };
 
     let mut guard = TRACE_LOGGER.write().unwrap_or_else(|e| e.into_inner());
     *guard = logger;
 }
 
+/// Whether trace payloads may include model reasoning text
+/// (`observability.show_reasoning`).
+pub fn reasoning_visible() -> bool {
+    TRACE_LOGGER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .is_some_and(|logger| logger.show_reasoning)
+}
+
+/// Whether turns should record the inputs `zeroclaw doctor replay` needs
+/// (`observability.runtime_trace_replay`).
+pub fn replay_capture_enabled() -> bool {
+    TRACE_LOGGER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .is_some_and(|logger| logger.capture_replay)
+}
+
+/// Short digest of the effective configuration, so a replay can tell whether
+/// the config changed since the turn was recorded.
+pub fn config_fingerprint(config: &Config) -> String {
+    let serialized = toml::to_string(config).unwrap_or_default();
+    let digest = hex::encode(Sha256::digest(serialized.as_bytes()));
+    digest[..CONFIG_FINGERPRINT_CHARS].to_string()
+}
+
+/// Remember the running config's fingerprint for replay captures.
+pub fn set_config_fingerprint(config: &Config) {
+    let mut guard = CONFIG_FINGERPRINT
+        .write()
+        .unwrap_or_else(|e| e.into_inner());
+    *guard = Some(config_fingerprint(config));
+}
+
+/// Fingerprint set by [`set_config_fingerprint`], if any.
+pub fn current_config_fingerprint() -> Option<String> {
+    CONFIG_FINGERPRINT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
 /// Record a runtime trace event.
 pub fn record_event(
     event_type: &str,
@@ -291,6 +348,22 @@ pub fn load_events(
     Ok(events)
 }
 
+/// Load every event of one agent turn, oldest first.
+pub fn load_turn_events(path: &Path, turn_id: &str) -> Result<Vec<RuntimeTraceEvent>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(path)?;
+    Ok(raw
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| serde_json::from_str::<RuntimeTraceEvent>(line).ok())
+        .filter(|event| event.turn_id.as_deref() == Some(turn_id))
+        .collect())
+}
+
 /// Find a runtime trace event by id.
 pub fn find_event_by_id(path: &Path, id: &str) -> Result<Option<RuntimeTraceEvent>> {
     if !path.exists() {
@@ -325,6 +398,8 @@ mod tests {
             runtime_trace_mode: "rolling".to_string(),
             runtime_trace_path: "state/runtime-trace.jsonl".to_string(),
             runtime_trace_max_entries: 3,
+            show_reasoning: false,
+            runtime_trace_replay: false,
         }
     }
 
@@ -386,6 +461,44 @@ mod tests {
         assert_eq!(events[1].message.as_deref(), Some("event-3"));
     }
 
+    #[test]
+    fn load_turn_events_keeps_only_that_turn_in_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("trace.jsonl");
+        let logger = RuntimeTraceLogger::new(RuntimeTraceStorageMode::Full, 100, path.clone());
+
+        for (i, turn) in ["turn-a", "turn-b", "turn-a"].into_iter().enumerate() {
+            let event = RuntimeTraceEvent {
+                id: format!("id-{i}"),
+                timestamp: Utc::now().to_rfc3339(),
+                event_type: "llm_response".into(),
+                channel: None,
+                provider: None,
+                model: None,
+                turn_id: Some(turn.into()),
+                success: Some(true),
+                message: None,
+                payload: serde_json::json!({ "i": i }),
+            };
+            logger.append(&event).unwrap();
+        }
+
+        let events = load_turn_events(&path, "turn-a").unwrap();
+        let ids: Vec<&str> = events.iter().map(|event| event.id.as_str()).collect();
+        assert_eq!(ids, ["id-0", "id-2"]);
+    }
+
+    #[test]
+    fn config_fingerprint_tracks_config_changes() {
+        let mut config = Config::default();
+        let before = config_fingerprint(&config);
+        assert_eq!(before.len(), CONFIG_FINGERPRINT_CHARS);
+        assert_eq!(before, config_fingerprint(&config));
+
+        config.default_temperature = 0.1;
+        assert_ne!(before, config_fingerprint(&config));
+    }
+
     #[test]
     fn find_event_by_id_returns_match() {
         let tmp = tempfile::tempdir().unwrap();
@@ -28,6 +28,21 @@ pub enum ObserverEvent {
         error_message: Option<String>,
         input_tokens: Option<u64>,
         output_tokens: Option<u64>,
+        /// Prompt tokens served from the provider's prompt cache (included in `input_tokens`).
+        cache_read_tokens: Option<u64>,
+        /// Prompt tokens written to the provider's prompt cache (included in `input_tokens`).
+        cache_write_tokens: Option<u64>,
+    },
+    /// Reasoning/thinking produced by an LLM call.
+    ///
+    /// Carries the full text; observers decide whether to expose it
+    /// (`observability.show_reasoning`) or only report its size.
+    LlmReasoning {
+        provider: String,
+        model: String,
+        /// Number of thinking blocks (0 for unstructured reasoning text).
+        blocks: usize,
+        text: String,
     },
     /// The agent session has finished.
     ///
@@ -56,6 +71,15 @@ pub enum ObserverEvent {
         /// `"inbound"` or `"outbound"`.
         direction: String,
     },
+    /// Progress of a model download, e.g. an Ollama auto-pull.
+    ModelPull {
+        provider: String,
+        model: String,
+        /// Provider-reported phase (e.g. `"pulling manifest"`, `"success"`).
+        status: String,
+        completed_bytes: Option<u64>,
+        total_bytes: Option<u64>,
+    },
     /// Periodic heartbeat tick from the runtime keep-alive loop.
     HeartbeatTick,
     /// An error occurred in a named component.
@@ -126,6 +150,14 @@ pub trait Observer: Send + Sync + 'static {
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
+impl std::fmt::Debug for dyn Observer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Observer")
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -33,6 +33,9 @@ impl Observer for VerboseObserver {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
                 eprintln!("< Receive (success={success}, duration_ms={ms})");
             }
+            ObserverEvent::LlmReasoning { text, .. } => {
+                eprintln!("< Reasoning ({} chars)", text.chars().count());
+            }
             ObserverEvent::ToolCallStart { tool } => {
                 eprintln!("> Tool {tool}");
             }
@@ -89,6 +92,8 @@ mod tests {
             error_message: None,
             input_tokens: Some(50),
             output_tokens: Some(25),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
         obs.record_event(&ObserverEvent::ToolCallStart {
             tool: "shell".into(),
@@ -3,8 +3,9 @@ pub mod wizard;
 // Re-exported for CLI and external use
 #[allow(unused_imports)]
 pub use wizard::{
-    run_channels_repair_wizard, run_models_list, run_models_refresh, run_models_refresh_all,
-    run_models_set, run_models_status, run_quick_setup, run_wizard,
+    run_channels_repair_wizard, run_models_list, run_models_list_local, run_models_pull,
+    run_models_refresh, run_models_refresh_all, run_models_remove, run_models_set,
+    run_models_status, run_quick_setup, run_wizard,
 };
 
 #[cfg(test)]
@@ -20,6 +21,9 @@ mod tests {
         assert_reexport_exists(run_quick_setup);
         assert_reexport_exists(run_models_refresh);
         assert_reexport_exists(run_models_list);
+        assert_reexport_exists(run_models_list_local);
+        assert_reexport_exists(run_models_pull);
+        assert_reexport_exists(run_models_remove);
         assert_reexport_exists(run_models_set);
         assert_reexport_exists(run_models_status);
         assert_reexport_exists(run_models_refresh_all);
@@ -193,9 +193,13 @@ pub async fn run_wizard(force: bool) -> Result<Config> {
         plugins: crate::config::PluginsConfig::default(),
         hardware: hardware_config,
         query_classification: crate::config::QueryClassificationConfig::default(),
+        adaptive_routing: crate::config::AdaptiveRoutingConfig::default(),
+        ollama: crate::config::OllamaConfig::default(),
         transcription: crate::config::TranscriptionConfig::default(),
+        tts: crate::config::TtsConfig::default(),
         agents_ipc: crate::config::AgentsIpcConfig::default(),
         mcp: crate::config::schema::McpConfig::default(),
+        tools: std::collections::HashMap::new(),
         model_support_vision: None,
         wasm: crate::config::WasmConfig::default(),
         routing: None,
@@ -559,9 +563,13 @@ async fn run_quick_setup_with_home(
         plugins: crate::config::PluginsConfig::default(),
         hardware: crate::config::HardwareConfig::default(),
         query_classification: crate::config::QueryClassificationConfig::default(),
+        adaptive_routing: crate::config::AdaptiveRoutingConfig::default(),
+        ollama: crate::config::OllamaConfig::default(),
         transcription: crate::config::TranscriptionConfig::default(),
+        tts: crate::config::TtsConfig::default(),
         agents_ipc: crate::config::AgentsIpcConfig::default(),
         mcp: crate::config::schema::McpConfig::default(),
+        tools: std::collections::HashMap::new(),
         model_support_vision: None,
         wasm: crate::config::WasmConfig::default(),
         routing: None,
@@ -1990,6 +1998,117 @@ pub async fn run_models_list(config: &Config, provider_override: Option<&str>) -
     Ok(())
 }
 
+fn local_ollama_provider(config: &Config) -> crate::providers::ollama::OllamaProvider {
+    let uses_ollama = config.default_provider.as_deref() == Some("ollama");
+    let base_url = config.api_url.as_deref().filter(|_| uses_ollama);
+    let api_key = config.api_key.as_deref().filter(|_| uses_ollama);
+    crate::providers::ollama::OllamaProvider::new(base_url, api_key).with_lifecycle(&config.ollama)
+}
+
+fn humanize_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+pub async fn run_models_list_local(config: &Config) -> Result<()> {
+    let provider = local_ollama_provider(config);
+    let models = provider
+        .list_local_models()
+        .await
+        .context("Failed to list local Ollama models. Is Ollama running? (ollama serve)")?;
+
+    println!();
+    if models.is_empty() {
+        println!("  No local Ollama models installed. Run: zeroclaw models pull <model>");
+        println!();
+        return Ok(());
+    }
+
+    println!("  {} local Ollama models:", models.len());
+    println!();
+    for model in &models {
+        let marker = if config.default_model.as_deref() == Some(model.name.as_str()) {
+            "* "
+        } else {
+            "  "
+        };
+        let details = [
+            model.details.parameter_size.as_deref(),
+            model.details.quantization_level.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ");
+        println!(
+            "  {marker}{:<40} {:>10}  {}",
+            model.name,
+            humanize_bytes(model.size),
+            style(details).dim()
+        );
+    }
+    println!();
+    Ok(())
+}
+
+pub async fn run_models_pull(config: &Config, model: &str) -> Result<()> {
+    let model = model.trim();
+    if model.is_empty() {
+        anyhow::bail!("Model name cannot be empty");
+    }
+
+    let provider = local_ollama_provider(config);
+    println!();
+    println!(
+        "  Pulling '{}' from the Ollama registry...",
+        style(model).cyan()
+    );
+    let mut last_line = String::new();
+    provider
+        .pull_model(model, |progress| {
+            let line = match (progress.percent(), progress.total) {
+                (Some(percent), Some(total)) => format!(
+                    "{} {percent}% of {}",
+                    progress.status,
+                    humanize_bytes(total)
+                ),
+                _ => progress.status.clone(),
+            };
+            if line != last_line {
+                println!("  {line}");
+                last_line = line;
+            }
+        })
+        .await?;
+    println!();
+    println!("  Model '{}' is ready.", style(model).green().bold());
+    println!();
+    Ok(())
+}
+
+pub async fn run_models_remove(config: &Config, model: &str) -> Result<()> {
+    let model = model.trim();
+    if model.is_empty() {
+        anyhow::bail!("Model name cannot be empty");
+    }
+
+    local_ollama_provider(config).delete_model(model).await?;
+    println!();
+    println!("  Removed local Ollama model '{}'.", style(model).green());
+    println!();
+    Ok(())
+}
+
 pub async fn run_models_set(config: &Config, model: &str) -> Result<()> {
     let model = model.trim();
     if model.is_empty() {
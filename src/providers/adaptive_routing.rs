@@ -0,0 +1,507 @@
+//! Adaptive route selection for [`RouterProvider`](super::router::RouterProvider).
+//!
+//! When several `[[model_routes]]` share a hint, the policy keeps smoothed
+//! per-route latency, error-rate and cost statistics and picks the cheapest
+//! route that meets the configured quality tier and latency SLO. Every
+//! decision records a `route_decision` runtime trace event listing each
+//! candidate and why it was or was not chosen.
+
+use super::traits::TokenUsage;
+use crate::config::schema::{AdaptiveRoutingConfig, Config, ModelPricing};
+use crate::cost::TokenUsage as CostUsage;
+use crate::observability::runtime_trace;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Share of prompt tokens assumed when ranking routes that have no observed
+/// usage yet (agent turns are dominated by prompt tokens).
+const PRIOR_INPUT_SHARE: f64 = 0.8;
+
+/// A route the policy may choose for a hint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteCandidate {
+    /// Provider id registered in the router (e.g. `"anthropic#chat"`).
+    pub provider_id: String,
+    /// Model sent to the provider.
+    pub model: String,
+    /// Declared quality tier (`None` = tier 0).
+    pub quality_tier: Option<u8>,
+}
+
+impl RouteCandidate {
+    /// Stable key used for statistics: `provider/model` without the hint suffix.
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.base_provider(), self.model)
+    }
+
+    fn base_provider(&self) -> &str {
+        self.provider_id
+            .split_once('#')
+            .map_or(self.provider_id.as_str(), |(name, _)| name)
+    }
+
+    fn tier(&self) -> u8 {
+        self.quality_tier.unwrap_or(0)
+    }
+}
+
+/// Smoothed observations for one route.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RouteStats {
+    pub samples: u32,
+    pub latency_ms: f64,
+    pub error_rate: f64,
+    /// Observed USD per 1M tokens for this route's real token mix.
+    pub cost_per_mtok: Option<f64>,
+    #[serde(skip)]
+    last_observed: Option<Instant>,
+}
+
+/// Outcome of a routing decision.
+#[derive(Debug, Clone)]
+pub struct RouteDecision {
+    /// Index into the candidate slice passed to [`AdaptiveRoutingPolicy::choose`].
+    pub index: usize,
+    /// Human-readable reason for the choice.
+    pub reason: String,
+    /// Per-candidate evaluation, in candidate order.
+    pub candidates: Vec<CandidateReport>,
+}
+
+/// Why a candidate was (not) eligible, as recorded in the decision trace.
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateReport {
+    pub route: String,
+    pub quality_tier: u8,
+    pub samples: u32,
+    pub latency_ms: f64,
+    pub error_rate: f64,
+    pub cost_per_mtok: Option<f64>,
+    pub status: &'static str,
+}
+
+/// Learned routing policy shared by all requests through a router.
+pub struct AdaptiveRoutingPolicy {
+    config: AdaptiveRoutingConfig,
+    prices: HashMap<String, ModelPricing>,
+    stats: Mutex<HashMap<String, RouteStats>>,
+}
+
+impl AdaptiveRoutingPolicy {
+    pub fn new(config: AdaptiveRoutingConfig, prices: HashMap<String, ModelPricing>) -> Self {
+        Self {
+            config,
+            prices,
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Build a policy from `[adaptive_routing]` and `[cost.prices]`, or `None`
+    /// when adaptive routing is disabled.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        config
+            .adaptive_routing
+            .enabled
+            .then(|| Self::new(config.adaptive_routing.clone(), config.cost.prices.clone()))
+    }
+
+    fn pricing(&self, candidate: &RouteCandidate) -> Option<&ModelPricing> {
+        self.prices
+            .get(&candidate.key())
+            .or_else(|| self.prices.get(&candidate.model))
+    }
+
+    /// Expected USD per 1M tokens: observed when available, otherwise a prior
+    /// derived from configured prices.
+    fn expected_cost(&self, candidate: &RouteCandidate, stats: &RouteStats) -> Option<f64> {
+        stats.cost_per_mtok.or_else(|| {
+            self.pricing(candidate).map(|pricing| {
+                pricing.input * PRIOR_INPUT_SHARE + pricing.output * (1.0 - PRIOR_INPUT_SHARE)
+            })
+        })
+    }
+
+    fn is_healthy(&self, stats: &RouteStats) -> bool {
+        stats.error_rate <= self.config.max_error_rate
+            && (self.config.latency_slo_ms == 0
+                || stats.latency_ms <= self.config.latency_slo_ms as f64)
+    }
+
+    fn needs_probe(&self, stats: &RouteStats, now: Instant) -> bool {
+        if stats.samples < self.config.min_samples {
+            return true;
+        }
+        !self.is_healthy(stats)
+            && stats.last_observed.is_none_or(|at| {
+                now.duration_since(at) >= Duration::from_secs(self.config.reprobe_secs)
+            })
+    }
+
+    /// Pick a route for `hint` among `candidates` (must be non-empty).
+    pub fn choose(&self, hint: &str, candidates: &[RouteCandidate]) -> RouteDecision {
+        let now = Instant::now();
+        let stats_map = self.stats.lock();
+        let stats: Vec<RouteStats> = candidates
+            .iter()
+            .map(|candidate| stats_map.get(&candidate.key()).cloned().unwrap_or_default())
+            .collect();
+        drop(stats_map);
+
+        let min_tier = self.config.min_quality_tier;
+        let top_tier = candidates
+            .iter()
+            .map(RouteCandidate::tier)
+            .max()
+            .unwrap_or(0);
+        let tier_floor = if top_tier >= min_tier {
+            min_tier
+        } else {
+            top_tier
+        };
+        let meets_tier = |i: usize| candidates[i].tier() >= tier_floor;
+
+        let cost_key = |i: usize| {
+            self.expected_cost(&candidates[i], &stats[i])
+                .unwrap_or(f64::INFINITY)
+        };
+        let by_cost_then_latency = |a: &usize, b: &usize| {
+            cost_key(*a)
+                .total_cmp(&cost_key(*b))
+                .then(stats[*a].latency_ms.total_cmp(&stats[*b].latency_ms))
+        };
+
+        let tiered: Vec<usize> = (0..candidates.len()).filter(|&i| meets_tier(i)).collect();
+        let probe = tiered
+            .iter()
+            .copied()
+            .filter(|&i| self.needs_probe(&stats[i], now))
+            .min_by(by_cost_then_latency);
+        let healthy = tiered
+            .iter()
+            .copied()
+            .filter(|&i| self.is_healthy(&stats[i]) && stats[i].samples >= self.config.min_samples)
+            .min_by(by_cost_then_latency);
+
+        let (index, reason) = if let Some(i) = probe {
+            let s = &stats[i];
+            if s.samples < self.config.min_samples {
+                (
+                    i,
+                    format!(
+                        "exploring route ({}/{} samples)",
+                        s.samples, self.config.min_samples
+                    ),
+                )
+            } else {
+                (i, "re-probing route that missed its SLO".to_string())
+            }
+        } else if let Some(i) = healthy {
+            (
+                i,
+                "cheapest route meeting quality tier and latency/error SLO".to_string(),
+            )
+        } else {
+            let i = tiered
+                .iter()
+                .copied()
+                .min_by(|a, b| {
+                    stats[*a]
+                        .error_rate
+                        .total_cmp(&stats[*b].error_rate)
+                        .then(stats[*a].latency_ms.total_cmp(&stats[*b].latency_ms))
+                })
+                .unwrap_or(0);
+            (
+                i,
+                "no route meets the SLO; using the most reliable".to_string(),
+            )
+        };
+        let reason = if tier_floor < min_tier {
+            format!("{reason} (no route reaches quality tier {min_tier}; using tier {tier_floor})")
+        } else {
+            reason
+        };
+
+        let reports = candidates
+            .iter()
+            .zip(&stats)
+            .enumerate()
+            .map(|(i, (candidate, s))| CandidateReport {
+                route: candidate.key(),
+                quality_tier: candidate.tier(),
+                samples: s.samples,
+                latency_ms: s.latency_ms,
+                error_rate: s.error_rate,
+                cost_per_mtok: self.expected_cost(candidate, s),
+                status: if i == index {
+                    "selected"
+                } else if !meets_tier(i) {
+                    "below_quality_tier"
+                } else if s.error_rate > self.config.max_error_rate {
+                    "error_rate_exceeded"
+                } else if !self.is_healthy(s) {
+                    "latency_slo_exceeded"
+                } else {
+                    "more_expensive"
+                },
+            })
+            .collect::<Vec<_>>();
+
+        let chosen = &candidates[index];
+        tracing::info!(
+            hint,
+            provider = chosen.provider_id.as_str(),
+            model = chosen.model.as_str(),
+            reason = reason.as_str(),
+            "Adaptive router selected route"
+        );
+        runtime_trace::record_event(
+            "route_decision",
+            None,
+            Some(chosen.base_provider()),
+            Some(&chosen.model),
+            None,
+            None,
+            Some(&reason),
+            serde_json::json!({
+                "hint": hint,
+                "min_quality_tier": min_tier,
+                "latency_slo_ms": self.config.latency_slo_ms,
+                "max_error_rate": self.config.max_error_rate,
+                "candidates": reports,
+            }),
+        );
+
+        RouteDecision {
+            index,
+            reason,
+            candidates: reports,
+        }
+    }
+
+    /// Fold the outcome of one call into the route's moving averages.
+    pub fn record(
+        &self,
+        candidate: &RouteCandidate,
+        latency: Duration,
+        success: bool,
+        usage: Option<&TokenUsage>,
+    ) {
+        let alpha = self.config.smoothing.clamp(f64::MIN_POSITIVE, 1.0);
+        let observed_cost = usage.and_then(|usage| self.cost_per_mtok(candidate, usage));
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        let error = if success { 0.0 } else { 1.0 };
+
+        let mut stats_map = self.stats.lock();
+        let stats = stats_map.entry(candidate.key()).or_default();
+        if stats.samples == 0 {
+            stats.latency_ms = latency_ms;
+            stats.error_rate = error;
+        } else {
+            stats.latency_ms += alpha * (latency_ms - stats.latency_ms);
+            stats.error_rate += alpha * (error - stats.error_rate);
+        }
+        if let Some(cost) = observed_cost {
+            stats.cost_per_mtok = Some(match stats.cost_per_mtok {
+                Some(previous) => previous + alpha * (cost - previous),
+                None => cost,
+            });
+        }
+        stats.samples = stats.samples.saturating_add(1);
+        stats.last_observed = Some(Instant::now());
+    }
+
+    fn cost_per_mtok(&self, candidate: &RouteCandidate, usage: &TokenUsage) -> Option<f64> {
+        let pricing = self.pricing(candidate)?;
+        let input = usage.input_tokens.unwrap_or(0);
+        let output = usage.output_tokens.unwrap_or(0);
+        let priced = CostUsage::with_cache(
+            candidate.key(),
+            input,
+            output,
+            usage.cache_read_tokens.unwrap_or(0),
+            usage.cache_write_tokens.unwrap_or(0),
+            pricing,
+        );
+        (priced.total_tokens > 0)
+            .then(|| priced.cost_usd * 1_000_000.0 / priced.total_tokens as f64)
+    }
+
+    /// Snapshot of the current statistics for `candidate`.
+    pub fn stats_for(&self, candidate: &RouteCandidate) -> RouteStats {
+        self.stats
+            .lock()
+            .get(&candidate.key())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(provider: &str, model: &str, tier: u8) -> RouteCandidate {
+        RouteCandidate {
+            provider_id: format!("{provider}#chat"),
+            model: model.into(),
+            quality_tier: Some(tier),
+        }
+    }
+
+    fn pricing(input: f64, output: f64) -> ModelPricing {
+        ModelPricing {
+            input,
+            output,
+            ..ModelPricing::default()
+        }
+    }
+
+    fn policy(config: AdaptiveRoutingConfig) -> AdaptiveRoutingPolicy {
+        let mut prices = HashMap::new();
+        prices.insert("premium/big".to_string(), pricing(15.0, 75.0));
+        prices.insert("budget/small".to_string(), pricing(0.15, 0.6));
+        prices.insert("mid/medium".to_string(), pricing(3.0, 15.0));
+        AdaptiveRoutingPolicy::new(config, prices)
+    }
+
+    fn warmed(policy: &AdaptiveRoutingPolicy, candidate: &RouteCandidate, ms: u64, ok: bool) {
+        for _ in 0..policy.config.min_samples {
+            policy.record(candidate, Duration::from_millis(ms), ok, None);
+        }
+    }
+
+    fn enabled() -> AdaptiveRoutingConfig {
+        AdaptiveRoutingConfig {
+            enabled: true,
+            ..AdaptiveRoutingConfig::default()
+        }
+    }
+
+    #[test]
+    fn explores_unsampled_routes_cheapest_first() {
+        let policy = policy(enabled());
+        let routes = [
+            candidate("premium", "big", 3),
+            candidate("budget", "small", 1),
+        ];
+
+        let decision = policy.choose("chat", &routes);
+        assert_eq!(decision.index, 1);
+        assert!(decision.reason.starts_with("exploring"));
+    }
+
+    #[test]
+    fn picks_cheapest_route_meeting_quality_tier() {
+        let policy = policy(AdaptiveRoutingConfig {
+            min_quality_tier: 2,
+            ..enabled()
+        });
+        let routes = [
+            candidate("premium", "big", 3),
+            candidate("budget", "small", 1),
+            candidate("mid", "medium", 2),
+        ];
+        for route in &routes {
+            warmed(&policy, route, 100, true);
+        }
+
+        let decision = policy.choose("chat", &routes);
+        assert_eq!(decision.index, 2);
+        assert_eq!(decision.candidates[1].status, "below_quality_tier");
+        assert_eq!(decision.candidates[0].status, "more_expensive");
+    }
+
+    #[test]
+    fn skips_routes_over_latency_slo_or_error_budget() {
+        let policy = policy(AdaptiveRoutingConfig {
+            latency_slo_ms: 1_000,
+            ..enabled()
+        });
+        let routes = [
+            candidate("premium", "big", 3),
+            candidate("budget", "small", 1),
+            candidate("mid", "medium", 2),
+        ];
+        warmed(&policy, &routes[0], 200, true);
+        warmed(&policy, &routes[1], 5_000, true);
+        warmed(&policy, &routes[2], 200, false);
+
+        let decision = policy.choose("chat", &routes);
+        assert_eq!(decision.index, 0);
+        assert_eq!(decision.candidates[1].status, "latency_slo_exceeded");
+        assert_eq!(decision.candidates[2].status, "error_rate_exceeded");
+    }
+
+    #[test]
+    fn falls_back_to_most_reliable_when_nothing_meets_slo() {
+        let policy = policy(AdaptiveRoutingConfig {
+            latency_slo_ms: 10,
+            ..enabled()
+        });
+        let routes = [
+            candidate("premium", "big", 3),
+            candidate("budget", "small", 1),
+        ];
+        warmed(&policy, &routes[0], 300, true);
+        warmed(&policy, &routes[1], 200, true);
+
+        let decision = policy.choose("chat", &routes);
+        assert_eq!(decision.index, 1);
+        assert!(decision.reason.contains("most reliable"));
+    }
+
+    #[test]
+    fn uses_highest_tier_when_none_reaches_minimum() {
+        let policy = policy(AdaptiveRoutingConfig {
+            min_quality_tier: 5,
+            ..enabled()
+        });
+        let routes = [
+            candidate("premium", "big", 3),
+            candidate("budget", "small", 1),
+        ];
+        warmed(&policy, &routes[0], 100, true);
+        warmed(&policy, &routes[1], 100, true);
+
+        let decision = policy.choose("chat", &routes);
+        assert_eq!(decision.index, 0);
+        assert!(decision.reason.contains("using tier 3"));
+    }
+
+    #[test]
+    fn observed_cost_reflects_prompt_cache_hits() {
+        let policy = policy(enabled());
+        let route = candidate("mid", "medium", 2);
+        let usage = TokenUsage {
+            input_tokens: Some(1_000_000),
+            output_tokens: Some(0),
+            cache_read_tokens: Some(1_000_000),
+            cache_write_tokens: None,
+        };
+        policy.record(&route, Duration::from_millis(50), true, Some(&usage));
+
+        // All prompt tokens were cache reads priced at the input fallback (3.0).
+        let stats = policy.stats_for(&route);
+        assert!((stats.cost_per_mtok.unwrap() - 3.0).abs() < 1e-9);
+        assert_eq!(stats.samples, 1);
+    }
+
+    #[test]
+    fn error_rate_is_smoothed() {
+        let policy = policy(enabled());
+        let route = candidate("mid", "medium", 2);
+        policy.record(&route, Duration::from_millis(10), true, None);
+        policy.record(&route, Duration::from_millis(10), false, None);
+
+        let stats = policy.stats_for(&route);
+        assert!((stats.error_rate - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn candidate_key_strips_hint_suffix() {
+        assert_eq!(candidate("openai", "gpt-4o", 1).key(), "openai/gpt-4o");
+    }
+}
@@ -1,17 +1,28 @@
+use crate::providers::streaming::{error_stream, send_sse_request, SseEventParser};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, ProviderCapabilities, TokenUsage, ToolCall as ProviderToolCall,
+    Provider, ProviderCapabilities, ResponseFormat, StreamError, StreamEvent, StreamOptions,
+    StreamResult, ThinkingBlock, ThinkingDelta, TokenUsage, ToolCall as ProviderToolCall,
+    ToolCallDelta,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
+use futures_util::stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 pub struct AnthropicProvider {
     credential: Option<String>,
     base_url: String,
+    thinking_budget_tokens: Option<u32>,
 }
 
+/// Default output budget for a Messages API call.
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Smallest thinking budget the Messages API accepts.
+const MIN_THINKING_BUDGET_TOKENS: u32 = 1024;
+
 #[derive(Debug, Serialize)]
 struct ChatRequest {
     model: String,
@@ -51,6 +62,19 @@ struct NativeChatRequest<'a> {
     temperature: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<NativeToolSpec<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<ThinkingConfig>,
+}
+
+#[derive(Debug, Serialize)]
+struct ThinkingConfig {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    budget_tokens: u32,
 }
 
 #[derive(Debug, Serialize)]
@@ -62,6 +86,10 @@ struct NativeMessage {
 #[derive(Debug, Serialize)]
 #[serde(tag = "type")]
 enum NativeContentOut {
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String, signature: String },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
     #[serde(rename = "text")]
     Text {
         text: String,
@@ -104,6 +132,65 @@ struct NativeToolSpec<'a> {
     cache_control: Option<CacheControl>,
 }
 
+/// Key used to wrap non-object schemas, since Anthropic tool inputs must be objects.
+const STRUCTURED_OUTPUT_WRAPPER_KEY: &str = "value";
+
+/// Anthropic has no response-format parameter; structured output is obtained
+/// by forcing a single tool whose input schema is the requested schema.
+struct ForcedOutputTool {
+    name: String,
+    input_schema: serde_json::Value,
+    wrapped: bool,
+}
+
+impl ForcedOutputTool {
+    fn new(format: &ResponseFormat) -> Self {
+        let is_object = format
+            .schema
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            == Some("object");
+        if is_object {
+            Self {
+                name: format.name.clone(),
+                input_schema: format.schema.clone(),
+                wrapped: false,
+            }
+        } else {
+            Self {
+                name: format.name.clone(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": { STRUCTURED_OUTPUT_WRAPPER_KEY: format.schema },
+                    "required": [STRUCTURED_OUTPUT_WRAPPER_KEY],
+                }),
+                wrapped: true,
+            }
+        }
+    }
+
+    /// Move the forced tool call's input into the response text.
+    fn unwrap_into(&self, response: &mut ProviderChatResponse) {
+        let Some(index) = response
+            .tool_calls
+            .iter()
+            .position(|call| call.name == self.name)
+        else {
+            return;
+        };
+        let call = response.tool_calls.remove(index);
+        let text = if self.wrapped {
+            serde_json::from_str::<serde_json::Value>(&call.arguments)
+                .ok()
+                .and_then(|input| input.get(STRUCTURED_OUTPUT_WRAPPER_KEY).cloned())
+                .map_or(call.arguments, |value| value.to_string())
+        } else {
+            call.arguments
+        };
+        response.text = Some(text);
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct CacheControl {
     #[serde(rename = "type")]
@@ -148,6 +235,27 @@ struct AnthropicUsage {
     input_tokens: Option<u64>,
     #[serde(default)]
     output_tokens: Option<u64>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u64>,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<u64>,
+}
+
+impl AnthropicUsage {
+    /// Anthropic reports cache reads and writes outside `input_tokens`; add
+    /// them back so `input_tokens` covers the whole prompt.
+    fn to_token_usage(&self) -> TokenUsage {
+        let cached = self
+            .cache_read_input_tokens
+            .unwrap_or(0)
+            .saturating_add(self.cache_creation_input_tokens.unwrap_or(0));
+        TokenUsage {
+            input_tokens: self.input_tokens.map(|input| input.saturating_add(cached)),
+            output_tokens: self.output_tokens,
+            cache_read_tokens: self.cache_read_input_tokens,
+            cache_write_tokens: self.cache_creation_input_tokens,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -162,6 +270,167 @@ struct NativeContentIn {
     name: Option<String>,
     #[serde(default)]
     input: Option<serde_json::Value>,
+    #[serde(default)]
+    thinking: Option<String>,
+    #[serde(default)]
+    signature: Option<String>,
+    #[serde(default)]
+    data: Option<String>,
+}
+
+/// One Messages API streaming event (`data:` payload).
+#[derive(Debug, Deserialize)]
+struct NativeStreamEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    index: Option<usize>,
+    #[serde(default)]
+    message: Option<NativeStreamMessage>,
+    #[serde(default)]
+    content_block: Option<NativeContentIn>,
+    #[serde(default)]
+    delta: Option<NativeStreamDelta>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+    #[serde(default)]
+    error: Option<NativeStreamError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NativeStreamMessage {
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NativeStreamDelta {
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    partial_json: Option<String>,
+    #[serde(default)]
+    thinking: Option<String>,
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NativeStreamError {
+    #[serde(default)]
+    message: String,
+}
+
+/// Maps Messages API stream events onto [`StreamEvent`]s.
+///
+/// Content block indices count text, thinking and tool blocks together, so
+/// tool-use and thinking blocks are renumbered into consecutive indices of
+/// their own kind.
+#[derive(Debug, Default)]
+struct AnthropicStreamParser {
+    tool_indices: std::collections::HashMap<usize, usize>,
+    thinking_indices: std::collections::HashMap<usize, usize>,
+    prompt_usage: Option<AnthropicUsage>,
+}
+
+impl SseEventParser for AnthropicStreamParser {
+    fn parse(&mut self, data: &str) -> StreamResult<Vec<StreamEvent>> {
+        let event: NativeStreamEvent = serde_json::from_str(data).map_err(StreamError::Json)?;
+        let block_index = event.index.unwrap_or_default();
+
+        let stream_event = match event.kind.as_str() {
+            "message_start" => {
+                self.prompt_usage = event.message.and_then(|message| message.usage);
+                None
+            }
+            "content_block_start" => {
+                event
+                    .content_block
+                    .and_then(|block| match block.kind.as_str() {
+                        "tool_use" => {
+                            let next_index = self.tool_indices.len();
+                            let index = *self.tool_indices.entry(block_index).or_insert(next_index);
+                            Some(StreamEvent::ToolCallDelta(ToolCallDelta {
+                                index,
+                                id: block.id,
+                                name: block.name,
+                                arguments: String::new(),
+                            }))
+                        }
+                        "thinking" | "redacted_thinking" => {
+                            let next_index = self.thinking_indices.len();
+                            let index = *self
+                                .thinking_indices
+                                .entry(block_index)
+                                .or_insert(next_index);
+                            Some(StreamEvent::ThinkingDelta(ThinkingDelta {
+                                index,
+                                text: block.thinking.unwrap_or_default(),
+                                signature: block.signature.filter(|s| !s.is_empty()),
+                                redacted: block.data,
+                            }))
+                        }
+                        _ => None,
+                    })
+            }
+            "content_block_delta" => event.delta.and_then(|delta| match delta.kind.as_deref() {
+                Some("text_delta") => delta
+                    .text
+                    .filter(|text| !text.is_empty())
+                    .map(StreamEvent::TextDelta),
+                Some("input_json_delta") => {
+                    let index = *self.tool_indices.get(&block_index)?;
+                    Some(StreamEvent::ToolCallDelta(ToolCallDelta {
+                        index,
+                        id: None,
+                        name: None,
+                        arguments: delta.partial_json.unwrap_or_default(),
+                    }))
+                }
+                Some("thinking_delta") => {
+                    let index = *self.thinking_indices.get(&block_index)?;
+                    Some(StreamEvent::ThinkingDelta(ThinkingDelta {
+                        index,
+                        text: delta.thinking.unwrap_or_default(),
+                        ..ThinkingDelta::default()
+                    }))
+                }
+                Some("signature_delta") => {
+                    let index = *self.thinking_indices.get(&block_index)?;
+                    Some(StreamEvent::ThinkingDelta(ThinkingDelta {
+                        index,
+                        signature: delta.signature,
+                        ..ThinkingDelta::default()
+                    }))
+                }
+                _ => None,
+            }),
+            "message_delta" => event.usage.map(|usage| {
+                let mut token_usage = self
+                    .prompt_usage
+                    .as_ref()
+                    .map(AnthropicUsage::to_token_usage)
+                    .unwrap_or_default();
+                token_usage.output_tokens = usage.output_tokens;
+                StreamEvent::Usage(token_usage)
+            }),
+            "message_stop" => Some(StreamEvent::Done),
+            "error" => {
+                let message = event
+                    .error
+                    .map(|error| error.message)
+                    .unwrap_or_else(|| "unknown streaming error".to_string());
+                return Err(StreamError::Provider(format!(
+                    "Anthropic API error: {message}"
+                )));
+            }
+            _ => None,
+        };
+
+        Ok(stream_event.into_iter().collect())
+    }
 }
 
 impl AnthropicProvider {
@@ -180,6 +449,38 @@ impl AnthropicProvider {
                 .filter(|k| !k.is_empty())
                 .map(ToString::to_string),
             base_url,
+            thinking_budget_tokens: None,
+        }
+    }
+
+    /// Enable extended thinking with the given token budget.
+    ///
+    /// Budgets below the API minimum of 1024 tokens are raised to it.
+    pub fn with_thinking_budget(mut self, budget_tokens: Option<u32>) -> Self {
+        self.thinking_budget_tokens =
+            budget_tokens.map(|budget| budget.max(MIN_THINKING_BUDGET_TOKENS));
+        self
+    }
+
+    /// Thinking config plus the `max_tokens` and temperature to send with it.
+    ///
+    /// Extended thinking requires `max_tokens` above the budget and the
+    /// default temperature, and is incompatible with forced tool choice.
+    fn thinking_settings(
+        &self,
+        forced_tool_choice: bool,
+        temperature: f64,
+    ) -> (Option<ThinkingConfig>, u32, f64) {
+        match self.thinking_budget_tokens {
+            Some(budget_tokens) if !forced_tool_choice => (
+                Some(ThinkingConfig {
+                    kind: "enabled",
+                    budget_tokens,
+                }),
+                budget_tokens.saturating_add(DEFAULT_MAX_TOKENS),
+                1.0,
+            ),
+            _ => (None, DEFAULT_MAX_TOKENS, temperature),
         }
     }
 
@@ -220,7 +521,10 @@ impl AnthropicProvider {
                     | NativeContentOut::ToolResult { cache_control, .. } => {
                         *cache_control = Some(CacheControl::ephemeral());
                     }
-                    NativeContentOut::ToolUse { .. } | NativeContentOut::Image { .. } => {}
+                    NativeContentOut::ToolUse { .. }
+                    | NativeContentOut::Image { .. }
+                    | NativeContentOut::Thinking { .. }
+                    | NativeContentOut::RedactedThinking { .. } => {}
                 }
             }
         }
@@ -255,7 +559,23 @@ impl AnthropicProvider {
             .get("tool_calls")
             .and_then(|v| serde_json::from_value::<Vec<ProviderToolCall>>(v.clone()).ok())?;
 
-        let mut blocks = Vec::new();
+        // Thinking blocks must come first and be echoed verbatim; unsigned
+        // blocks (e.g. from another provider) would be rejected, so drop them.
+        let thinking = value
+            .get("thinking")
+            .and_then(|v| serde_json::from_value::<Vec<ThinkingBlock>>(v.clone()).ok())
+            .unwrap_or_default();
+        let mut blocks: Vec<NativeContentOut> = thinking
+            .into_iter()
+            .filter_map(|block| match (block.redacted, block.signature) {
+                (Some(data), _) => Some(NativeContentOut::RedactedThinking { data }),
+                (None, Some(signature)) => Some(NativeContentOut::Thinking {
+                    thinking: block.text,
+                    signature,
+                }),
+                (None, None) => None,
+            })
+            .collect();
         if let Some(text) = value
             .get("content")
             .and_then(serde_json::Value::as_str)
@@ -404,6 +724,15 @@ impl AnthropicProvider {
         (system_prompt, native_messages)
     }
 
+    /// Convert messages and auto-cache the last one when the conversation is long.
+    fn prepare_messages(messages: &[ChatMessage]) -> (Option<SystemPrompt>, Vec<NativeMessage>) {
+        let (system_prompt, mut native_messages) = Self::convert_messages(messages);
+        if Self::should_cache_conversation(messages) {
+            Self::apply_cache_to_last_message(&mut native_messages);
+        }
+        (system_prompt, native_messages)
+    }
+
     fn parse_text_response(response: ChatResponse) -> anyhow::Result<String> {
         response
             .content
@@ -416,11 +745,9 @@ impl AnthropicProvider {
     fn parse_native_response(response: NativeChatResponse) -> ProviderChatResponse {
         let mut text_parts = Vec::new();
         let mut tool_calls = Vec::new();
+        let mut thinking = Vec::new();
 
-        let usage = response.usage.map(|u| TokenUsage {
-            input_tokens: u.input_tokens,
-            output_tokens: u.output_tokens,
-        });
+        let usage = response.usage.as_ref().map(AnthropicUsage::to_token_usage);
 
         for block in response.content {
             match block.kind.as_str() {
@@ -445,6 +772,15 @@ impl AnthropicProvider {
                         arguments: arguments.to_string(),
                     });
                 }
+                "thinking" => thinking.push(ThinkingBlock {
+                    text: block.thinking.unwrap_or_default(),
+                    signature: block.signature,
+                    redacted: None,
+                }),
+                "redacted_thinking" => thinking.push(ThinkingBlock {
+                    redacted: block.data,
+                    ..ThinkingBlock::default()
+                }),
                 _ => {}
             }
         }
@@ -457,7 +793,8 @@ impl AnthropicProvider {
             },
             tool_calls,
             usage,
-            reasoning_content: None,
+            reasoning_content: ThinkingBlock::joined_text(&thinking),
+            thinking,
         }
     }
 
@@ -501,7 +838,8 @@ impl Provider for AnthropicProvider {
 
         request = self.apply_auth(request, credential);
 
-        let response = request.send().await?;
+        let response =
+            super::quota_scheduler::send("anthropic", Some(credential.as_str()), request).await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("Anthropic", response).await);
@@ -523,20 +861,32 @@ impl Provider for AnthropicProvider {
             )
         })?;
 
-        let (system_prompt, mut messages) = Self::convert_messages(request.messages);
+        let (system_prompt, messages) = Self::prepare_messages(request.messages);
 
-        // Auto-cache last message if conversation is long
-        if Self::should_cache_conversation(request.messages) {
-            Self::apply_cache_to_last_message(&mut messages);
-        }
+        let forced_output = request.response_format.map(ForcedOutputTool::new);
+        let mut tools = Self::convert_tools(request.tools);
+        let tool_choice = forced_output.as_ref().map(|forced| {
+            tools.get_or_insert_with(Vec::new).push(NativeToolSpec {
+                name: &forced.name,
+                description: "Submit the final answer in the required structured format.",
+                input_schema: &forced.input_schema,
+                cache_control: None,
+            });
+            serde_json::json!({ "type": "tool", "name": forced.name })
+        });
 
+        let (thinking, max_tokens, temperature) =
+            self.thinking_settings(tool_choice.is_some(), temperature);
         let native_request = NativeChatRequest {
             model: model.to_string(),
-            max_tokens: 4096,
+            max_tokens,
             system: system_prompt,
             messages,
             temperature,
-            tools: Self::convert_tools(request.tools),
+            tools,
+            tool_choice,
+            stream: None,
+            thinking,
         };
 
         let req = self
@@ -546,19 +896,85 @@ impl Provider for AnthropicProvider {
             .header("content-type", "application/json")
             .json(&native_request);
 
-        let response = self.apply_auth(req, credential).send().await?;
+        let response = super::quota_scheduler::send(
+            "anthropic",
+            Some(credential.as_str()),
+            self.apply_auth(req, credential),
+        )
+        .await?;
         if !response.status().is_success() {
             return Err(super::api_error("Anthropic", response).await);
         }
 
         let native_response: NativeChatResponse = response.json().await?;
-        Ok(Self::parse_native_response(native_response))
+        let mut result = Self::parse_native_response(native_response);
+        if let Some(forced) = &forced_output {
+            forced.unwrap_into(&mut result);
+        }
+        Ok(result)
     }
 
     fn supports_native_tools(&self) -> bool {
         true
     }
 
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
+    fn supports_tool_call_streaming(&self) -> bool {
+        true
+    }
+
+    fn stream_chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        _options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+        let Some(credential) = self.credential.as_ref() else {
+            return error_stream(StreamError::Provider(
+                "Anthropic credentials not set. Set ANTHROPIC_API_KEY or ANTHROPIC_OAUTH_TOKEN (setup-token)."
+                    .to_string(),
+            ));
+        };
+        // Structured output relies on unwrapping a forced tool call, which
+        // only the buffered `chat` path does.
+        if request.response_format.is_some() {
+            return error_stream(StreamError::Provider(
+                "Anthropic structured output is not supported when streaming".to_string(),
+            ));
+        }
+
+        let (system_prompt, messages) = Self::prepare_messages(request.messages);
+        let (thinking, max_tokens, temperature) = self.thinking_settings(false, temperature);
+        let native_request = NativeChatRequest {
+            model: model.to_string(),
+            max_tokens,
+            system: system_prompt,
+            messages,
+            temperature,
+            tools: Self::convert_tools(request.tools),
+            tool_choice: None,
+            stream: Some(true),
+            thinking,
+        };
+
+        let req = self
+            .http_client()
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&native_request);
+        send_sse_request(
+            "Anthropic",
+            Some(credential.as_str()),
+            self.apply_auth(req, credential),
+            AnthropicStreamParser::default(),
+        )
+    }
+
     fn capabilities(&self) -> ProviderCapabilities {
         ProviderCapabilities {
             native_tool_calling: true,
@@ -609,6 +1025,7 @@ impl Provider for AnthropicProvider {
             } else {
                 Some(&tool_specs)
             },
+            response_format: None,
         };
         self.chat(request, model, temperature).await
     }
@@ -1208,6 +1625,9 @@ mod tests {
             }],
             temperature: 0.7,
             tools: None,
+            tool_choice: None,
+            stream: None,
+            thinking: None,
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -1215,6 +1635,56 @@ mod tests {
         assert!(json.contains(r#""system":"System""#));
     }
 
+    #[test]
+    fn forced_output_tool_moves_input_into_text() {
+        let format = ResponseFormat::json_schema(
+            "verdict",
+            serde_json::json!({"type": "object", "properties": {"ok": {"type": "boolean"}}}),
+        );
+        let forced = ForcedOutputTool::new(&format);
+        assert!(!forced.wrapped);
+
+        let mut response = ProviderChatResponse {
+            text: None,
+            tool_calls: vec![ProviderToolCall {
+                id: "toolu_1".into(),
+                name: "verdict".into(),
+                arguments: r#"{"ok":true}"#.into(),
+            }],
+            usage: None,
+            reasoning_content: None,
+            thinking: Vec::new(),
+        };
+        forced.unwrap_into(&mut response);
+        assert!(response.tool_calls.is_empty());
+        assert_eq!(response.text.as_deref(), Some(r#"{"ok":true}"#));
+    }
+
+    #[test]
+    fn forced_output_tool_wraps_non_object_schemas() {
+        let format = ResponseFormat::json_schema(
+            "labels",
+            serde_json::json!({"type": "array", "items": {"type": "string"}}),
+        );
+        let forced = ForcedOutputTool::new(&format);
+        assert!(forced.wrapped);
+        assert_eq!(forced.input_schema["type"], "object");
+
+        let mut response = ProviderChatResponse {
+            text: None,
+            tool_calls: vec![ProviderToolCall {
+                id: "toolu_1".into(),
+                name: "labels".into(),
+                arguments: r#"{"value":["a","b"]}"#.into(),
+            }],
+            usage: None,
+            reasoning_content: None,
+            thinking: Vec::new(),
+        };
+        forced.unwrap_into(&mut response);
+        assert_eq!(response.text.as_deref(), Some(r#"["a","b"]"#));
+    }
+
     #[tokio::test]
     async fn warmup_without_key_is_noop() {
         let provider = AnthropicProvider::new(None);
@@ -1297,6 +1767,7 @@ mod tests {
         let provider = AnthropicProvider {
             credential: Some("test-key".to_string()),
             base_url: format!("http://{addr}"),
+            thinking_budget_tokens: None,
         };
 
         // Multi-turn conversation: system → user (Go code) → assistant (code response) → user (follow-up)
@@ -1398,6 +1869,26 @@ mod tests {
         assert_eq!(usage.output_tokens, Some(75));
     }
 
+    #[test]
+    fn native_response_folds_cache_tokens_into_input() {
+        let json = r#"{
+            "content": [{"type": "text", "text": "Hello"}],
+            "usage": {
+                "input_tokens": 50,
+                "output_tokens": 20,
+                "cache_read_input_tokens": 4000,
+                "cache_creation_input_tokens": 1000
+            }
+        }"#;
+        let resp: NativeChatResponse = serde_json::from_str(json).unwrap();
+        let usage = AnthropicProvider::parse_native_response(resp)
+            .usage
+            .unwrap();
+        assert_eq!(usage.input_tokens, Some(5050));
+        assert_eq!(usage.cache_read_tokens, Some(4000));
+        assert_eq!(usage.cache_write_tokens, Some(1000));
+    }
+
     #[test]
     fn native_response_parses_without_usage() {
         let json = r#"{"content": [{"type": "text", "text": "Hello"}]}"#;
@@ -1413,4 +1904,139 @@ mod tests {
         assert!(caps.vision);
         assert!(caps.native_tool_calling);
     }
+
+    #[test]
+    fn stream_parser_maps_text_and_tool_use_blocks() {
+        let payloads = [
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":42,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Checking."}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"shell","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"command\":"}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"ls\"}"}}"#,
+            r#"{"type":"content_block_stop","index":1}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":18}}"#,
+            r#"{"type":"message_stop"}"#,
+        ];
+
+        let mut parser = AnthropicStreamParser::default();
+        let mut accumulator = crate::providers::traits::StreamAccumulator::new();
+        let mut saw_done = false;
+        for payload in payloads {
+            for event in parser.parse(payload).unwrap() {
+                saw_done |= matches!(event, StreamEvent::Done);
+                accumulator.push(&event);
+            }
+        }
+        let response = accumulator.finish();
+
+        assert!(saw_done);
+        assert_eq!(response.text.as_deref(), Some("Checking."));
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].id, "toolu_1");
+        assert_eq!(response.tool_calls[0].name, "shell");
+        assert_eq!(response.tool_calls[0].arguments, r#"{"command":"ls"}"#);
+        let usage = response.usage.unwrap();
+        assert_eq!(usage.input_tokens, Some(42));
+        assert_eq!(usage.output_tokens, Some(18));
+    }
+
+    #[test]
+    fn native_response_parses_thinking_blocks() {
+        let json = r#"{"content":[
+            {"type":"thinking","thinking":"Need to list files.","signature":"sig-1"},
+            {"type":"redacted_thinking","data":"opaque"},
+            {"type":"tool_use","id":"toolu_1","name":"shell","input":{"command":"ls"}}
+        ]}"#;
+        let response: NativeChatResponse = serde_json::from_str(json).unwrap();
+        let result = AnthropicProvider::parse_native_response(response);
+
+        assert_eq!(result.thinking.len(), 2);
+        assert_eq!(result.thinking[0].text, "Need to list files.");
+        assert_eq!(result.thinking[0].signature.as_deref(), Some("sig-1"));
+        assert_eq!(result.thinking[1].redacted.as_deref(), Some("opaque"));
+        assert_eq!(
+            result.reasoning_content.as_deref(),
+            Some("Need to list files.")
+        );
+        assert_eq!(result.tool_calls.len(), 1);
+    }
+
+    #[test]
+    fn assistant_history_replays_signed_thinking_before_tool_use() {
+        let content = serde_json::json!({
+            "content": "",
+            "tool_calls": [{"id": "toolu_1", "name": "shell", "arguments": "{\"command\":\"ls\"}"}],
+            "thinking": [
+                {"text": "Need to list files.", "signature": "sig-1"},
+                {"text": "", "redacted": "opaque"},
+                {"text": "unsigned from another provider"}
+            ]
+        })
+        .to_string();
+
+        let blocks = AnthropicProvider::parse_assistant_tool_call_message(&content).unwrap();
+        let json = serde_json::to_value(&blocks).unwrap();
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(json[0]["type"], "thinking");
+        assert_eq!(json[0]["thinking"], "Need to list files.");
+        assert_eq!(json[0]["signature"], "sig-1");
+        assert_eq!(json[1]["type"], "redacted_thinking");
+        assert_eq!(json[1]["data"], "opaque");
+        assert_eq!(json[2]["type"], "tool_use");
+    }
+
+    #[test]
+    fn thinking_budget_raises_max_tokens_and_skips_forced_tool_choice() {
+        let provider = AnthropicProvider::new(Some("test-key")).with_thinking_budget(Some(100));
+
+        let (thinking, max_tokens, temperature) = provider.thinking_settings(false, 0.2);
+        let thinking = thinking.unwrap();
+        assert_eq!(thinking.budget_tokens, MIN_THINKING_BUDGET_TOKENS);
+        assert_eq!(max_tokens, MIN_THINKING_BUDGET_TOKENS + DEFAULT_MAX_TOKENS);
+        assert!((temperature - 1.0).abs() < f64::EPSILON);
+
+        let (thinking, max_tokens, temperature) = provider.thinking_settings(true, 0.2);
+        assert!(thinking.is_none());
+        assert_eq!(max_tokens, DEFAULT_MAX_TOKENS);
+        assert!((temperature - 0.2).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn stream_parser_maps_thinking_and_signature_deltas() {
+        let payloads = [
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Let me "}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"check."}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"sig-1"}}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"shell","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{}"}}"#,
+        ];
+
+        let mut parser = AnthropicStreamParser::default();
+        let mut accumulator = crate::providers::traits::StreamAccumulator::new();
+        for payload in payloads {
+            for event in parser.parse(payload).unwrap() {
+                accumulator.push(&event);
+            }
+        }
+        let response = accumulator.finish();
+
+        assert_eq!(response.thinking.len(), 1);
+        assert_eq!(response.thinking[0].text, "Let me check.");
+        assert_eq!(response.thinking[0].signature.as_deref(), Some("sig-1"));
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].id, "toolu_1");
+    }
+
+    #[test]
+    fn stream_parser_surfaces_error_events() {
+        let mut parser = AnthropicStreamParser::default();
+        let err = parser
+            .parse(r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("Overloaded"));
+    }
 }
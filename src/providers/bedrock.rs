@@ -5,9 +5,10 @@
 //! using hmac/sha2 crates — no AWS SDK dependency.
 
 use crate::providers::traits::{
-    ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, ProviderCapabilities, StreamChunk, StreamError, StreamOptions, StreamResult,
-    TokenUsage, ToolCall as ProviderToolCall, ToolsPayload,
+    with_response_format_instructions, ChatMessage, ChatRequest as ProviderChatRequest,
+    ChatResponse as ProviderChatResponse, Provider, ProviderCapabilities, StreamChunk, StreamError,
+    StreamOptions, StreamResult, ThinkingBlock, TokenUsage, ToolCall as ProviderToolCall,
+    ToolsPayload,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
@@ -23,6 +24,8 @@ const ENDPOINT_PREFIX: &str = "bedrock-runtime";
 const SIGNING_SERVICE: &str = "bedrock";
 const DEFAULT_REGION: &str = "us-east-1";
 const DEFAULT_MAX_TOKENS: u32 = 4096;
+/// Smallest extended-thinking budget Claude models accept.
+const MIN_THINKING_BUDGET_TOKENS: u32 = 1024;
 
 // ── AWS Credentials ─────────────────────────────────────────────
 
@@ -256,6 +259,8 @@ struct ConverseRequest {
     inference_config: Option<InferenceConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_config: Option<ToolConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    additional_model_request_fields: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -265,7 +270,8 @@ struct ConverseMessage {
 }
 
 /// Content blocks use Bedrock's union style:
-/// `{"text": "..."}`, `{"toolUse": {...}}`, `{"toolResult": {...}}`, `{"cachePoint": {...}}`.
+/// `{"text": "..."}`, `{"toolUse": {...}}`, `{"toolResult": {...}}`, `{"cachePoint": {...}}`,
+/// `{"reasoningContent": {...}}`.
 ///
 /// Note: `text` is a simple string value, not a nested object. `toolUse` and `toolResult`
 /// are nested objects. We use `#[serde(untagged)]` with manual struct wrappers to
@@ -278,6 +284,30 @@ enum ContentBlock {
     ToolResult(ToolResultWrapper),
     CachePointBlock(CachePointWrapper),
     Image(ImageWrapper),
+    ReasoningContent(ReasoningContentWrapper),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReasoningContentWrapper {
+    reasoning_content: ReasoningContentBlock,
+}
+
+/// Either signed reasoning text or an encrypted redacted payload.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReasoningContentBlock {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reasoning_text: Option<ReasoningText>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    redacted_content: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReasoningText {
+    text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -416,6 +446,27 @@ struct BedrockUsage {
     input_tokens: Option<u64>,
     #[serde(default)]
     output_tokens: Option<u64>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u64>,
+    #[serde(default)]
+    cache_write_input_tokens: Option<u64>,
+}
+
+impl BedrockUsage {
+    /// Bedrock reports cache reads and writes outside `inputTokens`; add them
+    /// back so `input_tokens` covers the whole prompt.
+    fn into_token_usage(self) -> TokenUsage {
+        let cached = self
+            .cache_read_input_tokens
+            .unwrap_or(0)
+            .saturating_add(self.cache_write_input_tokens.unwrap_or(0));
+        TokenUsage {
+            input_tokens: self.input_tokens.map(|input| input.saturating_add(cached)),
+            output_tokens: self.output_tokens,
+            cache_read_tokens: self.cache_read_input_tokens,
+            cache_write_tokens: self.cache_write_input_tokens,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -435,13 +486,14 @@ struct ConverseOutputMessage {
 ///
 /// Uses `#[serde(untagged)]` to match Bedrock's union format where `text` is a
 /// simple string value and `toolUse` is a nested object. Unknown block types
-/// (e.g. `reasoningContent`, `guardContent`) are captured as `Other` to prevent
-/// deserialization failures.
+/// (e.g. `guardContent`) are captured as `Other` to prevent deserialization
+/// failures.
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum ResponseContentBlock {
     ToolUse(ResponseToolUseWrapper),
     Text(TextBlock),
+    Reasoning(ReasoningContentWrapper),
     Other(serde_json::Value),
 }
 
@@ -455,18 +507,61 @@ struct ResponseToolUseWrapper {
 
 pub struct BedrockProvider {
     credentials: Option<AwsCredentials>,
+    thinking_budget_tokens: Option<u32>,
 }
 
 impl BedrockProvider {
     pub fn new() -> Self {
         Self {
             credentials: AwsCredentials::from_env().ok(),
+            thinking_budget_tokens: None,
         }
     }
 
     pub async fn new_async() -> Self {
         let credentials = AwsCredentials::resolve().await.ok();
-        Self { credentials }
+        Self {
+            credentials,
+            thinking_budget_tokens: None,
+        }
+    }
+
+    /// Enable extended thinking for Claude models with the given budget.
+    ///
+    /// Budgets below 1024 tokens are raised to the minimum.
+    pub fn with_thinking_budget(mut self, budget_tokens: Option<u32>) -> Self {
+        self.thinking_budget_tokens =
+            budget_tokens.map(|budget| budget.max(MIN_THINKING_BUDGET_TOKENS));
+        self
+    }
+
+    /// Inference config and extra model fields for a Converse call.
+    ///
+    /// Thinking is only sent to Claude models; it needs `maxTokens` above the
+    /// budget and the default temperature.
+    fn inference_settings(
+        &self,
+        model: &str,
+        temperature: f64,
+    ) -> (InferenceConfig, Option<serde_json::Value>) {
+        match self.thinking_budget_tokens {
+            Some(budget_tokens) if model.contains("anthropic.claude") => (
+                InferenceConfig {
+                    max_tokens: budget_tokens.saturating_add(DEFAULT_MAX_TOKENS),
+                    temperature: 1.0,
+                },
+                Some(serde_json::json!({
+                    "thinking": { "type": "enabled", "budget_tokens": budget_tokens }
+                })),
+            ),
+            _ => (
+                InferenceConfig {
+                    max_tokens: DEFAULT_MAX_TOKENS,
+                    temperature,
+                },
+                None,
+            ),
+        }
     }
 
     fn http_client(&self) -> Client {
@@ -764,7 +859,34 @@ impl BedrockProvider {
             .get("tool_calls")
             .and_then(|v| serde_json::from_value::<Vec<ProviderToolCall>>(v.clone()).ok())?;
 
-        let mut blocks = Vec::new();
+        // Reasoning must precede the tool calls and be replayed verbatim;
+        // unsigned text would be rejected, so it is dropped.
+        let thinking = value
+            .get("thinking")
+            .and_then(|v| serde_json::from_value::<Vec<ThinkingBlock>>(v.clone()).ok())
+            .unwrap_or_default();
+        let mut blocks: Vec<ContentBlock> = thinking
+            .into_iter()
+            .filter_map(|block| {
+                let reasoning_content = match (block.redacted, block.signature) {
+                    (Some(redacted), _) => ReasoningContentBlock {
+                        reasoning_text: None,
+                        redacted_content: Some(redacted),
+                    },
+                    (None, Some(signature)) => ReasoningContentBlock {
+                        reasoning_text: Some(ReasoningText {
+                            text: block.text,
+                            signature: Some(signature),
+                        }),
+                        redacted_content: None,
+                    },
+                    (None, None) => return None,
+                };
+                Some(ContentBlock::ReasoningContent(ReasoningContentWrapper {
+                    reasoning_content,
+                }))
+            })
+            .collect();
         if let Some(text) = value
             .get("content")
             .and_then(serde_json::Value::as_str)
@@ -842,11 +964,9 @@ impl BedrockProvider {
     fn parse_converse_response(response: ConverseResponse) -> ProviderChatResponse {
         let mut text_parts = Vec::new();
         let mut tool_calls = Vec::new();
+        let mut thinking = Vec::new();
 
-        let usage = response.usage.map(|u| TokenUsage {
-            input_tokens: u.input_tokens,
-            output_tokens: u.output_tokens,
-        });
+        let usage = response.usage.map(BedrockUsage::into_token_usage);
 
         if let Some(output) = response.output {
             if let Some(message) = output.message {
@@ -867,6 +987,20 @@ impl BedrockProvider {
                                 });
                             }
                         }
+                        ResponseContentBlock::Reasoning(wrapper) => {
+                            let block = wrapper.reasoning_content;
+                            thinking.push(match block.reasoning_text {
+                                Some(reasoning) => ThinkingBlock {
+                                    text: reasoning.text,
+                                    signature: reasoning.signature,
+                                    redacted: None,
+                                },
+                                None => ThinkingBlock {
+                                    redacted: block.redacted_content,
+                                    ..ThinkingBlock::default()
+                                },
+                            });
+                        }
                         ResponseContentBlock::Other(_) => {}
                     }
                 }
@@ -881,7 +1015,8 @@ impl BedrockProvider {
             },
             tool_calls,
             usage,
-            reasoning_content: None,
+            reasoning_content: ThinkingBlock::joined_text(&thinking),
+            thinking,
         }
     }
 
@@ -1267,6 +1402,7 @@ impl Provider for BedrockProvider {
                 temperature,
             }),
             tool_config: None,
+            additional_model_request_fields: None,
         };
 
         let response = self
@@ -1286,7 +1422,12 @@ impl Provider for BedrockProvider {
     ) -> anyhow::Result<ProviderChatResponse> {
         let credentials = self.resolve_credentials().await?;
 
-        let (system_blocks, mut converse_messages) = Self::convert_messages(request.messages);
+        // No native structured-output support here: guide the model via the prompt.
+        let guided_messages = request
+            .response_format
+            .map(|format| with_response_format_instructions(request.messages, format));
+        let messages = guided_messages.as_deref().unwrap_or(request.messages);
+        let (system_blocks, mut converse_messages) = Self::convert_messages(messages);
 
         // Apply cachePoint to system if large.
         let system = system_blocks.map(|mut blocks| {
@@ -1313,15 +1454,15 @@ impl Provider for BedrockProvider {
         }
 
         let tool_config = Self::convert_tools_to_converse(request.tools);
+        let (inference_config, additional_model_request_fields) =
+            self.inference_settings(model, temperature);
 
         let converse_request = ConverseRequest {
             system,
             messages: converse_messages,
-            inference_config: Some(InferenceConfig {
-                max_tokens: DEFAULT_MAX_TOKENS,
-                temperature,
-            }),
+            inference_config: Some(inference_config),
             tool_config,
+            additional_model_request_fields,
         };
 
         let response = self
@@ -1378,6 +1519,7 @@ impl Provider for BedrockProvider {
                 temperature,
             }),
             tool_config: None,
+            additional_model_request_fields: None,
         };
 
         // Clone what we need for the async block
@@ -1695,7 +1837,10 @@ mod tests {
 
     #[tokio::test]
     async fn chat_fails_without_credentials() {
-        let provider = BedrockProvider { credentials: None };
+        let provider = BedrockProvider {
+            credentials: None,
+            thinking_budget_tokens: None,
+        };
         let result = provider
             .chat_with_system(None, "hello", "anthropic.claude-sonnet-4-6", 0.7)
             .await;
@@ -1885,6 +2030,7 @@ mod tests {
                 temperature: 0.7,
             }),
             tool_config: None,
+            additional_model_request_fields: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("system"));
@@ -1991,14 +2137,20 @@ mod tests {
 
     #[tokio::test]
     async fn warmup_without_credentials_is_noop() {
-        let provider = BedrockProvider { credentials: None };
+        let provider = BedrockProvider {
+            credentials: None,
+            thinking_budget_tokens: None,
+        };
         let result = provider.warmup().await;
         assert!(result.is_ok());
     }
 
     #[test]
     fn capabilities_reports_native_tool_calling() {
-        let provider = BedrockProvider { credentials: None };
+        let provider = BedrockProvider {
+            credentials: None,
+            thinking_budget_tokens: None,
+        };
         let caps = provider.capabilities();
         assert!(caps.native_tool_calling);
     }
@@ -2015,6 +2167,26 @@ mod tests {
         assert_eq!(usage.output_tokens, Some(100));
     }
 
+    #[test]
+    fn converse_usage_folds_cache_tokens_into_input() {
+        let json = r#"{
+            "output": {"message": {"role": "assistant", "content": []}},
+            "usage": {
+                "inputTokens": 20,
+                "outputTokens": 10,
+                "cacheReadInputTokens": 3000,
+                "cacheWriteInputTokens": 500
+            }
+        }"#;
+        let resp: ConverseResponse = serde_json::from_str(json).unwrap();
+        let usage = BedrockProvider::parse_converse_response(resp)
+            .usage
+            .unwrap();
+        assert_eq!(usage.input_tokens, Some(3520));
+        assert_eq!(usage.cache_read_tokens, Some(3000));
+        assert_eq!(usage.cache_write_tokens, Some(500));
+    }
+
     #[test]
     fn converse_response_parses_without_usage() {
         let json = r#"{"output": {"message": {"role": "assistant", "content": []}}}"#;
@@ -2022,6 +2194,56 @@ mod tests {
         assert!(resp.usage.is_none());
     }
 
+    #[test]
+    fn converse_response_parses_reasoning_content() {
+        let json = r#"{"output": {"message": {"role": "assistant", "content": [
+            {"reasoningContent": {"reasoningText": {"text": "Check the disk.", "signature": "sig-1"}}},
+            {"reasoningContent": {"redactedContent": "b3BhcXVl"}},
+            {"toolUse": {"toolUseId": "t1", "name": "shell", "input": {"command": "df"}}}
+        ]}}}"#;
+        let resp: ConverseResponse = serde_json::from_str(json).unwrap();
+        let parsed = BedrockProvider::parse_converse_response(resp);
+
+        assert_eq!(parsed.thinking.len(), 2);
+        assert_eq!(parsed.thinking[0].text, "Check the disk.");
+        assert_eq!(parsed.thinking[0].signature.as_deref(), Some("sig-1"));
+        assert_eq!(parsed.thinking[1].redacted.as_deref(), Some("b3BhcXVl"));
+        assert_eq!(parsed.reasoning_content.as_deref(), Some("Check the disk."));
+        assert_eq!(parsed.tool_calls.len(), 1);
+    }
+
+    #[test]
+    fn assistant_history_replays_reasoning_before_tool_use() {
+        let content = r#"{"content":"","tool_calls":[{"id":"t1","name":"shell","arguments":"{}"}],"thinking":[{"text":"Check the disk.","signature":"sig-1"}]}"#;
+        let blocks = BedrockProvider::parse_assistant_tool_call_message(content).unwrap();
+        let json = serde_json::to_value(&blocks).unwrap();
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(
+            json[0]["reasoningContent"]["reasoningText"]["signature"],
+            "sig-1"
+        );
+        assert!(json[1].get("toolUse").is_some());
+    }
+
+    #[test]
+    fn thinking_budget_only_applies_to_claude_models() {
+        let provider = BedrockProvider {
+            credentials: None,
+            thinking_budget_tokens: None,
+        }
+        .with_thinking_budget(Some(2048));
+
+        let (config, extra) =
+            provider.inference_settings("us.anthropic.claude-3-7-sonnet-20250219-v1:0", 0.3);
+        assert_eq!(config.max_tokens, 2048 + DEFAULT_MAX_TOKENS);
+        assert_eq!(extra.unwrap()["thinking"]["budget_tokens"], 2048);
+
+        let (config, extra) = provider.inference_settings("amazon.nova-pro-v1:0", 0.3);
+        assert_eq!(config.max_tokens, DEFAULT_MAX_TOKENS);
+        assert!(extra.is_none());
+    }
+
     // ── Tool result fallback & merge tests ───────────────────────
 
     #[test]
@@ -2052,7 +2274,10 @@ mod tests {
 
     #[test]
     fn supports_streaming_returns_true() {
-        let provider = BedrockProvider { credentials: None };
+        let provider = BedrockProvider {
+            credentials: None,
+            thinking_budget_tokens: None,
+        };
         assert!(provider.supports_streaming());
     }
 
@@ -3,10 +3,11 @@
 //! This module provides a single implementation that works for all of them.
 
 use crate::multimodal;
+use crate::providers::streaming::{error_stream, send_sse_request, OpenAiStreamParser};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, StreamChunk, StreamError, StreamOptions, StreamResult, TokenUsage,
-    ToolCall as ProviderToolCall,
+    Provider, ResponseFormat, StreamChunk, StreamError, StreamEvent, StreamOptions, StreamResult,
+    TokenUsage, ToolCall as ProviderToolCall,
 };
 use async_trait::async_trait;
 use futures_util::{stream, SinkExt, StreamExt};
@@ -443,6 +444,15 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// OpenAI reports cached prompt tokens (already counted in `prompt_tokens`) here.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -597,6 +607,8 @@ struct NativeChatRequest {
     tools: Option<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -936,6 +948,7 @@ fn parse_responses_chat_response(response: ResponsesResponse) -> ProviderChatRes
         tool_calls,
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     }
 }
 
@@ -1293,10 +1306,12 @@ impl OpenAiCompatibleProvider {
 
         let url = self.responses_url();
 
-        let response = self
-            .apply_auth_header(self.http_client().post(&url).json(&request), credential)
-            .send()
-            .await?;
+        let response = super::quota_scheduler::send(
+            &self.name,
+            Some(credential),
+            self.apply_auth_header(self.http_client().post(&url).json(&request), credential),
+        )
+        .await?;
 
         if !response.status().is_success() {
             let error = response.text().await?;
@@ -1578,6 +1593,7 @@ impl OpenAiCompatibleProvider {
             tool_calls,
             usage: None,
             reasoning_content,
+            thinking: Vec::new(),
         }
     }
 
@@ -1601,6 +1617,31 @@ impl OpenAiCompatibleProvider {
         .iter()
         .any(|hint| lower.contains(hint))
     }
+
+    fn convert_response_format(format: &ResponseFormat) -> Value {
+        serde_json::json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": format.name,
+                "schema": format.schema,
+                "strict": format.strict,
+            }
+        })
+    }
+
+    fn is_response_format_unsupported(status: reqwest::StatusCode, error: &str) -> bool {
+        if !matches!(
+            status,
+            reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::UNPROCESSABLE_ENTITY
+        ) {
+            return false;
+        }
+
+        let lower = error.to_lowercase();
+        ["response_format", "json_schema", "structured output"]
+            .iter()
+            .any(|hint| lower.contains(hint))
+    }
 }
 
 #[async_trait]
@@ -1682,10 +1723,12 @@ impl Provider for OpenAiCompatibleProvider {
                 .await;
         }
 
-        let response = match self
-            .apply_auth_header(self.http_client().post(&url).json(&request), credential)
-            .send()
-            .await
+        let response = match super::quota_scheduler::send(
+            &self.name,
+            Some(credential),
+            self.apply_auth_header(self.http_client().post(&url).json(&request), credential),
+        )
+        .await
         {
             Ok(response) => response,
             Err(chat_error) => {
@@ -1799,10 +1842,12 @@ impl Provider for OpenAiCompatibleProvider {
         }
 
         let url = self.chat_completions_url();
-        let response = match self
-            .apply_auth_header(self.http_client().post(&url).json(&request), credential)
-            .send()
-            .await
+        let response = match super::quota_scheduler::send(
+            &self.name,
+            Some(credential),
+            self.apply_auth_header(self.http_client().post(&url).json(&request), credential),
+        )
+        .await
         {
             Ok(response) => response,
             Err(chat_error) => {
@@ -1929,10 +1974,12 @@ impl Provider for OpenAiCompatibleProvider {
         }
 
         let url = self.chat_completions_url();
-        let response = match self
-            .apply_auth_header(self.http_client().post(&url).json(&request), credential)
-            .send()
-            .await
+        let response = match super::quota_scheduler::send(
+            &self.name,
+            Some(credential),
+            self.apply_auth_header(self.http_client().post(&url).json(&request), credential),
+        )
+        .await
         {
             Ok(response) => response,
             Err(error) => {
@@ -1946,6 +1993,7 @@ impl Provider for OpenAiCompatibleProvider {
                     tool_calls: vec![],
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 });
             }
         };
@@ -1970,6 +2018,10 @@ impl Provider for OpenAiCompatibleProvider {
         let usage = chat_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens),
+            cache_write_tokens: None,
         });
         let choice = chat_response
             .choices
@@ -2001,6 +2053,7 @@ impl Provider for OpenAiCompatibleProvider {
             tool_calls,
             usage,
             reasoning_content,
+            thinking: Vec::new(),
         })
     }
 
@@ -2035,13 +2088,23 @@ impl Provider for OpenAiCompatibleProvider {
             stream: Some(false),
             tool_choice: tools.as_ref().map(|_| "auto".to_string()),
             tools,
+            response_format: request.response_format.map(Self::convert_response_format),
+        };
+        // The Responses API path has no `response_format` field, so a
+        // requested schema is expressed as prompt instructions there.
+        let responses_messages = match request.response_format {
+            Some(format) => crate::providers::traits::with_response_format_instructions(
+                &effective_messages,
+                format,
+            ),
+            None => effective_messages.clone(),
         };
 
         if self.should_use_responses_mode() {
             return self
                 .chat_via_responses_chat(
                     credential,
-                    &effective_messages,
+                    &responses_messages,
                     model,
                     response_tools.clone(),
                 )
@@ -2049,13 +2112,15 @@ impl Provider for OpenAiCompatibleProvider {
         }
 
         let url = self.chat_completions_url();
-        let response = match self
-            .apply_auth_header(
+        let response = match super::quota_scheduler::send(
+            &self.name,
+            Some(credential),
+            self.apply_auth_header(
                 self.http_client().post(&url).json(&native_request),
                 credential,
-            )
-            .send()
-            .await
+            ),
+        )
+        .await
         {
             Ok(response) => response,
             Err(chat_error) => {
@@ -2064,7 +2129,7 @@ impl Provider for OpenAiCompatibleProvider {
                     return self
                         .chat_via_responses_chat(
                             credential,
-                            &effective_messages,
+                            &responses_messages,
                             model,
                             response_tools.clone(),
                         )
@@ -2086,9 +2151,40 @@ impl Provider for OpenAiCompatibleProvider {
             let error = response.text().await?;
             let sanitized = super::sanitize_api_error(&error);
 
+            if let Some(format) = request.response_format {
+                if Self::is_response_format_unsupported(status, &sanitized) {
+                    tracing::info!(
+                        provider = self.name.as_str(),
+                        "Backend rejected response_format; retrying with prompt-guided schema"
+                    );
+                    let fallback_messages =
+                        crate::providers::traits::with_response_format_instructions(
+                            request.messages,
+                            format,
+                        );
+                    return self
+                        .chat(
+                            ProviderChatRequest {
+                                messages: &fallback_messages,
+                                tools: request.tools,
+                                response_format: None,
+                            },
+                            model,
+                            temperature,
+                        )
+                        .await;
+                }
+            }
+
             if Self::is_native_tool_schema_unsupported(status, &sanitized) {
-                let fallback_messages =
+                let mut fallback_messages =
                     Self::with_prompt_guided_tool_instructions(request.messages, request.tools);
+                if let Some(format) = request.response_format {
+                    fallback_messages = crate::providers::traits::with_response_format_instructions(
+                        &fallback_messages,
+                        format,
+                    );
+                }
                 let text = self
                     .chat_with_history(&fallback_messages, model, temperature)
                     .await?;
@@ -2097,6 +2193,7 @@ impl Provider for OpenAiCompatibleProvider {
                     tool_calls: vec![],
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 });
             }
 
@@ -2104,7 +2201,7 @@ impl Provider for OpenAiCompatibleProvider {
                 return self
                     .chat_via_responses_chat(
                         credential,
-                        &effective_messages,
+                        &responses_messages,
                         model,
                         response_tools.clone(),
                     )
@@ -2124,6 +2221,10 @@ impl Provider for OpenAiCompatibleProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
@@ -2192,6 +2293,7 @@ impl Provider for OpenAiCompatibleProvider {
         let url = self.chat_completions_url();
         let client = self.http_client();
         let auth_header = self.auth_header.clone();
+        let provider_name = self.name.clone();
 
         // Use a channel to bridge the async HTTP response to the stream
         let (tx, rx) = tokio::sync::mpsc::channel::<StreamResult<StreamChunk>>(100);
@@ -2212,8 +2314,14 @@ impl Provider for OpenAiCompatibleProvider {
             // Set accept header for streaming
             req_builder = req_builder.header("Accept", "text/event-stream");
 
-            // Send request
-            let response = match req_builder.send().await {
+            // Send request, paced by the shared quota scheduler
+            let response = match super::quota_scheduler::send(
+                &provider_name,
+                Some(credential.as_str()),
+                req_builder,
+            )
+            .await
+            {
                 Ok(r) => r,
                 Err(e) => {
                     let _ = tx.send(Err(StreamError::Http(e))).await;
@@ -2250,16 +2358,82 @@ impl Provider for OpenAiCompatibleProvider {
         .boxed()
     }
 
+    fn supports_tool_call_streaming(&self) -> bool {
+        self.native_tool_calling && !self.should_use_responses_mode()
+    }
+
+    fn stream_chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        _options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+        let Some(credential) = self.credential.as_ref() else {
+            return error_stream(StreamError::Provider(format!(
+                "{} API key not set",
+                self.name
+            )));
+        };
+        if self.should_use_responses_mode() {
+            return error_stream(StreamError::Provider(format!(
+                "{} event streaming is not available in Responses API mode",
+                self.name
+            )));
+        }
+
+        if !self.native_tool_calling && request.tools.is_some_and(|tools| !tools.is_empty()) {
+            return error_stream(StreamError::Provider(format!(
+                "{} does not support streaming tool calls",
+                self.name
+            )));
+        }
+
+        let tools = Self::convert_tool_specs(request.tools);
+        let effective_messages = if self.merge_system_into_user {
+            Self::flatten_system_messages(request.messages)
+        } else {
+            request.messages.to_vec()
+        };
+        let native_request = NativeChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages_for_native(
+                &effective_messages,
+                !self.merge_system_into_user,
+            ),
+            temperature,
+            max_tokens: self.effective_max_tokens(),
+            stream: Some(true),
+            tool_choice: tools.as_ref().map(|_| "auto".to_string()),
+            tools,
+            response_format: request.response_format.map(Self::convert_response_format),
+        };
+
+        let url = self.chat_completions_url();
+        let request = self.apply_auth_header(
+            self.http_client().post(&url).json(&native_request),
+            credential,
+        );
+        send_sse_request(
+            &self.name,
+            Some(credential.as_str()),
+            request,
+            OpenAiStreamParser,
+        )
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         if let Some(credential) = self.credential.as_ref() {
             // Hit the chat completions URL with a GET to establish the connection pool.
             // The server will likely return 405 Method Not Allowed, which is fine -
             // the goal is TLS handshake and HTTP/2 negotiation.
             let url = self.chat_completions_url();
-            let _ = self
-                .apply_auth_header(self.http_client().get(&url), credential)
-                .send()
-                .await?;
+            let _ = super::quota_scheduler::send(
+                &self.name,
+                Some(credential.as_str()),
+                self.apply_auth_header(self.http_client().get(&url), credential),
+            )
+            .await?;
         }
         Ok(())
     }
@@ -2975,6 +3149,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn response_format_unsupported_detection_is_precise() {
+        assert!(OpenAiCompatibleProvider::is_response_format_unsupported(
+            reqwest::StatusCode::BAD_REQUEST,
+            "Unrecognized request argument supplied: response_format"
+        ));
+        assert!(!OpenAiCompatibleProvider::is_response_format_unsupported(
+            reqwest::StatusCode::BAD_REQUEST,
+            "context length exceeded"
+        ));
+        assert!(!OpenAiCompatibleProvider::is_response_format_unsupported(
+            reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+            "response_format"
+        ));
+    }
+
     #[test]
     fn prompt_guided_tool_fallback_injects_system_instruction() {
         let input = vec![ChatMessage::user("check status")];
@@ -12,8 +12,8 @@
 //! third-party integrations simultaneously.
 
 use crate::providers::traits::{
-    ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, TokenUsage, ToolCall as ProviderToolCall,
+    with_response_format_instructions, ChatMessage, ChatRequest as ProviderChatRequest,
+    ChatResponse as ProviderChatResponse, Provider, TokenUsage, ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
@@ -144,6 +144,15 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// OpenAI reports cached prompt tokens (already counted in `prompt_tokens`) here.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -353,6 +362,10 @@ impl CopilotProvider {
         let usage = api_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens),
+            cache_write_tokens: None,
         });
         let choice = api_response
             .choices
@@ -379,6 +392,7 @@ impl CopilotProvider {
             tool_calls,
             usage,
             reasoning_content: None,
+            thinking: Vec::new(),
         })
     }
 
@@ -645,8 +659,13 @@ impl Provider for CopilotProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ProviderChatResponse> {
+        // No native structured-output support here: guide the model via the prompt.
+        let guided_messages = request
+            .response_format
+            .map(|format| with_response_format_instructions(request.messages, format));
+        let messages = guided_messages.as_deref().unwrap_or(request.messages);
         self.send_chat_request(
-            Self::convert_messages(request.messages),
+            Self::convert_messages(messages),
             request.tools,
             model,
             temperature,
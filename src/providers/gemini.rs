@@ -5,10 +5,16 @@
 //! - Google Cloud ADC (`GOOGLE_APPLICATION_CREDENTIALS`)
 
 use crate::auth::AuthService;
-use crate::providers::traits::{ChatMessage, ChatResponse, Provider, TokenUsage};
+use crate::providers::streaming::{error_stream, send_sse_request, SseEventParser};
+use crate::providers::traits::{
+    ChatMessage, ChatResponse, Provider, ResponseFormat, StreamError, StreamEvent, StreamOptions,
+    StreamResult, ThinkingBlock, ThinkingDelta, TokenUsage,
+};
+use crate::tools::schema::SchemaCleanr;
 use async_trait::async_trait;
 use base64::Engine;
 use directories::UserDirs;
+use futures_util::stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -24,6 +30,8 @@ pub struct GeminiProvider {
     auth_service: Option<AuthService>,
     /// Override profile name for managed auth.
     auth_profile_override: Option<String>,
+    /// Thinking budget for thinking-capable models; `None` keeps the model default.
+    thinking_budget_tokens: Option<u32>,
 }
 
 /// Mutable OAuth token state — supports runtime refresh for long-lived processes.
@@ -76,6 +84,16 @@ impl GeminiAuth {
             GeminiAuth::OAuthToken(_) | GeminiAuth::ManagedOAuth => "",
         }
     }
+
+    /// Credential that keys the rate-limit bucket: the API key, or the
+    /// current OAuth access token.
+    fn quota_credential<'a>(&'a self, oauth_token: Option<&'a str>) -> Option<&'a str> {
+        if self.is_api_key() {
+            Some(self.api_key_credential())
+        } else {
+            oauth_token
+        }
+    }
 }
 
 // ══════════════════════════════════════════════════════════════════════════════
@@ -144,6 +162,32 @@ struct GenerationConfig {
     temperature: f64,
     #[serde(rename = "maxOutputTokens")]
     max_output_tokens: u32,
+    #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(rename = "responseSchema", skip_serializing_if = "Option::is_none")]
+    response_schema: Option<serde_json::Value>,
+    #[serde(rename = "thinkingConfig", skip_serializing_if = "Option::is_none")]
+    thinking_config: Option<ThinkingConfig>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ThinkingConfig {
+    #[serde(rename = "thinkingBudget")]
+    thinking_budget: u32,
+    #[serde(rename = "includeThoughts")]
+    include_thoughts: bool,
+}
+
+impl GenerationConfig {
+    /// Request JSON output constrained to `format` via `responseSchema`.
+    /// Gemini accepts an OpenAPI subset, so the schema is cleaned first.
+    fn with_response_format(mut self, format: Option<&ResponseFormat>) -> Self {
+        if let Some(format) = format {
+            self.response_mime_type = Some("application/json".to_string());
+            self.response_schema = Some(SchemaCleanr::clean_for_gemini(format.schema.clone()));
+        }
+        self
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -162,6 +206,20 @@ struct GeminiUsageMetadata {
     prompt_token_count: Option<u64>,
     #[serde(default, rename = "candidatesTokenCount")]
     candidates_token_count: Option<u64>,
+    /// Part of `promptTokenCount` served from cached content.
+    #[serde(default, rename = "cachedContentTokenCount")]
+    cached_content_token_count: Option<u64>,
+}
+
+impl GeminiUsageMetadata {
+    fn into_token_usage(self) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.prompt_token_count,
+            output_tokens: self.candidates_token_count,
+            cache_read_tokens: self.cached_content_token_count,
+            cache_write_tokens: None,
+        }
+    }
 }
 
 /// Response envelope for the internal cloudcode-pa API.
@@ -189,9 +247,27 @@ struct ResponsePart {
     /// Thinking models (e.g. gemini-3-pro-preview) mark reasoning parts with `thought: true`.
     #[serde(default)]
     thought: bool,
+    #[serde(default, rename = "thoughtSignature")]
+    thought_signature: Option<String>,
 }
 
 impl CandidateContent {
+    /// Collect thought-summary parts as thinking blocks.
+    fn thinking_blocks(&self) -> Vec<ThinkingBlock> {
+        self.parts
+            .iter()
+            .filter(|part| part.thought)
+            .filter_map(|part| {
+                let text = part.text.clone().filter(|text| !text.is_empty())?;
+                Some(ThinkingBlock {
+                    text,
+                    signature: part.thought_signature.clone(),
+                    redacted: None,
+                })
+            })
+            .collect()
+    }
+
     /// Extract effective text, skipping thinking/signature parts.
     ///
     /// Gemini thinking models (e.g. gemini-3-pro-preview) return parts like:
@@ -244,6 +320,54 @@ impl GenerateContentResponse {
     }
 }
 
+/// Maps `streamGenerateContent` SSE chunks onto [`StreamEvent`]s.
+#[derive(Debug, Default)]
+struct GeminiStreamParser;
+
+impl SseEventParser for GeminiStreamParser {
+    fn parse(&mut self, data: &str) -> StreamResult<Vec<StreamEvent>> {
+        let chunk: GenerateContentResponse =
+            serde_json::from_str(data).map_err(StreamError::Json)?;
+        let chunk = chunk.into_effective_response();
+        if let Some(err) = chunk.error {
+            return Err(StreamError::Provider(format!(
+                "Gemini API error: {}",
+                err.message
+            )));
+        }
+
+        // Thought summaries stream as one thinking block.
+        let mut events: Vec<StreamEvent> = chunk
+            .candidates
+            .and_then(|c| c.into_iter().next())
+            .and_then(|c| c.content)
+            .map(|content| content.parts)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|part| {
+                let text = part.text.filter(|text| !text.is_empty())?;
+                Some(if part.thought {
+                    StreamEvent::ThinkingDelta(ThinkingDelta {
+                        index: 0,
+                        text,
+                        signature: part.thought_signature,
+                        redacted: None,
+                    })
+                } else {
+                    StreamEvent::TextDelta(text)
+                })
+            })
+            .collect();
+
+        // Each chunk repeats the running usage totals; the last one wins.
+        if let Some(usage) = chunk.usage_metadata {
+            events.push(StreamEvent::Usage(usage.into_token_usage()));
+        }
+
+        Ok(events)
+    }
+}
+
 // ══════════════════════════════════════════════════════════════════════════════
 // GEMINI CLI TOKEN STRUCTURES
 // ══════════════════════════════════════════════════════════════════════════════
@@ -450,6 +574,7 @@ impl GeminiProvider {
             oauth_index: Arc::new(tokio::sync::Mutex::new(0)),
             auth_service: None,
             auth_profile_override: None,
+            thinking_budget_tokens: None,
         }
     }
 
@@ -523,9 +648,24 @@ impl GeminiProvider {
                 None
             },
             auth_profile_override: profile_override,
+            thinking_budget_tokens: None,
         }
     }
 
+    /// Set the thinking budget and request thought summaries.
+    pub fn with_thinking_budget(mut self, budget_tokens: Option<u32>) -> Self {
+        self.thinking_budget_tokens = budget_tokens;
+        self
+    }
+
+    fn thinking_config(&self) -> Option<ThinkingConfig> {
+        self.thinking_budget_tokens
+            .map(|thinking_budget| ThinkingConfig {
+                thinking_budget,
+                include_thoughts: true,
+            })
+    }
+
     fn normalize_non_empty(value: &str) -> Option<String> {
         let trimmed = value.trim();
         if trimmed.is_empty() {
@@ -796,6 +936,57 @@ impl GeminiProvider {
         }
     }
 
+    /// SSE streaming URL. Only available for API-key auth; the internal
+    /// OAuth endpoint needs an async token and project lookup per request.
+    fn build_stream_generate_content_url(model: &str, auth: &GeminiAuth) -> Option<String> {
+        auth.is_api_key().then(|| {
+            format!(
+                "{PUBLIC_API_ENDPOINT}/{}:streamGenerateContent?alt=sse&key={}",
+                Self::format_model_name(model),
+                auth.api_key_credential()
+            )
+        })
+    }
+
+    /// Split chat messages into Gemini contents and a merged system instruction.
+    fn build_contents(messages: &[ChatMessage]) -> (Vec<Content>, Option<Content>) {
+        let mut system_parts: Vec<&str> = Vec::new();
+        let mut contents: Vec<Content> = Vec::new();
+
+        for msg in messages {
+            match msg.role.as_str() {
+                "system" => system_parts.push(&msg.content),
+                "user" => contents.push(Content {
+                    role: Some("user".to_string()),
+                    parts: vec![Part {
+                        text: msg.content.clone(),
+                    }],
+                }),
+                // Gemini API uses "model" role instead of "assistant"
+                "assistant" => contents.push(Content {
+                    role: Some("model".to_string()),
+                    parts: vec![Part {
+                        text: msg.content.clone(),
+                    }],
+                }),
+                _ => {}
+            }
+        }
+
+        let system_instruction = if system_parts.is_empty() {
+            None
+        } else {
+            Some(Content {
+                role: None,
+                parts: vec![Part {
+                    text: system_parts.join("\n\n"),
+                }],
+            })
+        };
+
+        (contents, system_instruction)
+    }
+
     fn http_client(&self) -> Client {
         crate::config::build_runtime_proxy_client_with_timeouts("provider.gemini", 120, 10)
     }
@@ -818,7 +1009,7 @@ impl GeminiProvider {
 
         // Call loadCodeAssist
         let client = self.http_client();
-        let response = client
+        let request = client
             .post(LOAD_CODE_ASSIST_ENDPOINT)
             .bearer_auth(token)
             .json(&serde_json::json!({
@@ -829,9 +1020,8 @@ impl GeminiProvider {
                     "pluginType": "GEMINI",
                     "duetProject": duet_project_for_request,
                 }
-            }))
-            .send()
-            .await?;
+            }));
+        let response = super::quota_scheduler::send("gemini", Some(token), request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -939,7 +1129,8 @@ impl GeminiProvider {
         system_instruction: Option<Content>,
         model: &str,
         temperature: f64,
-    ) -> anyhow::Result<(String, Option<TokenUsage>)> {
+        response_format: Option<&ResponseFormat>,
+    ) -> anyhow::Result<(String, Option<TokenUsage>, Vec<ThinkingBlock>)> {
         let auth = self.auth.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
                 "Gemini API key not found. Options:\n\
@@ -988,13 +1179,19 @@ impl GeminiProvider {
             generation_config: GenerationConfig {
                 temperature,
                 max_output_tokens: 8192,
-            },
+                response_mime_type: None,
+                response_schema: None,
+                thinking_config: self.thinking_config(),
+            }
+            .with_response_format(response_format),
         };
 
         let url = Self::build_generate_content_url(model, auth);
 
-        let mut response = self
-            .build_generate_content_request(
+        let mut response = super::quota_scheduler::send(
+            "gemini",
+            auth.quota_credential(oauth_token.as_deref()),
+            self.build_generate_content_request(
                 auth,
                 &url,
                 &request,
@@ -1002,9 +1199,9 @@ impl GeminiProvider {
                 true,
                 project.as_deref(),
                 oauth_token.as_deref(),
-            )
-            .send()
-            .await?;
+            ),
+        )
+        .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -1048,8 +1245,10 @@ impl GeminiProvider {
                     };
                     oauth_token = Some(new_token);
                     project = Some(new_project);
-                    response = self
-                        .build_generate_content_request(
+                    response = super::quota_scheduler::send(
+                        "gemini",
+                        auth.quota_credential(oauth_token.as_deref()),
+                        self.build_generate_content_request(
                             auth,
                             &url,
                             &request,
@@ -1057,9 +1256,9 @@ impl GeminiProvider {
                             true,
                             project.as_deref(),
                             oauth_token.as_deref(),
-                        )
-                        .send()
-                        .await?;
+                        ),
+                    )
+                    .await?;
                 } else {
                     anyhow::bail!("Gemini API error ({status}): {error_text}");
                 }
@@ -1069,8 +1268,10 @@ impl GeminiProvider {
                 tracing::warn!(
                     "Gemini OAuth internal endpoint rejected generationConfig; retrying without generationConfig"
                 );
-                response = self
-                    .build_generate_content_request(
+                response = super::quota_scheduler::send(
+                    "gemini",
+                    auth.quota_credential(oauth_token.as_deref()),
+                    self.build_generate_content_request(
                         auth,
                         &url,
                         &request,
@@ -1078,9 +1279,9 @@ impl GeminiProvider {
                         false,
                         project.as_deref(),
                         oauth_token.as_deref(),
-                    )
-                    .send()
-                    .await?;
+                    ),
+                )
+                .await?;
             } else {
                 anyhow::bail!("Gemini API error ({status}): {error_text}");
             }
@@ -1095,8 +1296,10 @@ impl GeminiProvider {
                 tracing::warn!(
                     "Gemini OAuth internal endpoint rejected generationConfig; retrying without generationConfig"
                 );
-                response = self
-                    .build_generate_content_request(
+                response = super::quota_scheduler::send(
+                    "gemini",
+                    auth.quota_credential(oauth_token.as_deref()),
+                    self.build_generate_content_request(
                         auth,
                         &url,
                         &request,
@@ -1104,9 +1307,9 @@ impl GeminiProvider {
                         false,
                         project.as_deref(),
                         oauth_token.as_deref(),
-                    )
-                    .send()
-                    .await?;
+                    ),
+                )
+                .await?;
             } else {
                 anyhow::bail!("Gemini API error ({status}): {error_text}");
             }
@@ -1127,19 +1330,23 @@ impl GeminiProvider {
             anyhow::bail!("Gemini API error: {}", err.message);
         }
 
-        let usage = result.usage_metadata.map(|u| TokenUsage {
-            input_tokens: u.prompt_token_count,
-            output_tokens: u.candidates_token_count,
-        });
+        let usage = result
+            .usage_metadata
+            .map(GeminiUsageMetadata::into_token_usage);
 
-        let text = result
+        let content = result
             .candidates
             .and_then(|c| c.into_iter().next())
-            .and_then(|c| c.content)
+            .and_then(|c| c.content);
+        let thinking = content
+            .as_ref()
+            .map(CandidateContent::thinking_blocks)
+            .unwrap_or_default();
+        let text = content
             .and_then(|c| c.effective_text())
             .ok_or_else(|| anyhow::anyhow!("No response from Gemini"))?;
 
-        Ok((text, usage))
+        Ok((text, usage, thinking))
     }
 }
 
@@ -1166,8 +1373,8 @@ impl Provider for GeminiProvider {
             }],
         }];
 
-        let (text, _usage) = self
-            .send_generate_content(contents, system_instruction, model, temperature)
+        let (text, _usage, _thinking) = self
+            .send_generate_content(contents, system_instruction, model, temperature, None)
             .await?;
         Ok(text)
     }
@@ -1178,48 +1385,10 @@ impl Provider for GeminiProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let mut system_parts: Vec<&str> = Vec::new();
-        let mut contents: Vec<Content> = Vec::new();
-
-        for msg in messages {
-            match msg.role.as_str() {
-                "system" => {
-                    system_parts.push(&msg.content);
-                }
-                "user" => {
-                    contents.push(Content {
-                        role: Some("user".to_string()),
-                        parts: vec![Part {
-                            text: msg.content.clone(),
-                        }],
-                    });
-                }
-                "assistant" => {
-                    // Gemini API uses "model" role instead of "assistant"
-                    contents.push(Content {
-                        role: Some("model".to_string()),
-                        parts: vec![Part {
-                            text: msg.content.clone(),
-                        }],
-                    });
-                }
-                _ => {}
-            }
-        }
+        let (contents, system_instruction) = Self::build_contents(messages);
 
-        let system_instruction = if system_parts.is_empty() {
-            None
-        } else {
-            Some(Content {
-                role: None,
-                parts: vec![Part {
-                    text: system_parts.join("\n\n"),
-                }],
-            })
-        };
-
-        let (text, _usage) = self
-            .send_generate_content(contents, system_instruction, model, temperature)
+        let (text, _usage, _thinking) = self
+            .send_generate_content(contents, system_instruction, model, temperature, None)
             .await?;
         Ok(text)
     }
@@ -1230,51 +1399,75 @@ impl Provider for GeminiProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let mut system_parts: Vec<&str> = Vec::new();
-        let mut contents: Vec<Content> = Vec::new();
+        let (contents, system_instruction) = Self::build_contents(request.messages);
 
-        for msg in request.messages {
-            match msg.role.as_str() {
-                "system" => system_parts.push(&msg.content),
-                "user" => contents.push(Content {
-                    role: Some("user".to_string()),
-                    parts: vec![Part {
-                        text: msg.content.clone(),
-                    }],
-                }),
-                "assistant" => contents.push(Content {
-                    role: Some("model".to_string()),
-                    parts: vec![Part {
-                        text: msg.content.clone(),
-                    }],
-                }),
-                _ => {}
-            }
-        }
-
-        let system_instruction = if system_parts.is_empty() {
-            None
-        } else {
-            Some(Content {
-                role: None,
-                parts: vec![Part {
-                    text: system_parts.join("\n\n"),
-                }],
-            })
-        };
-
-        let (text, usage) = self
-            .send_generate_content(contents, system_instruction, model, temperature)
+        let (text, usage, thinking) = self
+            .send_generate_content(
+                contents,
+                system_instruction,
+                model,
+                temperature,
+                request.response_format,
+            )
             .await?;
 
         Ok(ChatResponse {
             text: Some(text),
             tool_calls: Vec::new(),
             usage,
-            reasoning_content: None,
+            reasoning_content: ThinkingBlock::joined_text(&thinking),
+            thinking,
         })
     }
 
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
+    fn stream_chat(
+        &self,
+        request: crate::providers::traits::ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        _options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+        if request.tools.is_some_and(|tools| !tools.is_empty()) {
+            return error_stream(StreamError::Provider(
+                "Gemini does not support streaming tool calls".to_string(),
+            ));
+        }
+        let Some(url) = self
+            .auth
+            .as_ref()
+            .and_then(|auth| Self::build_stream_generate_content_url(model, auth))
+        else {
+            return error_stream(StreamError::Provider(
+                "Gemini streaming requires an API key (GEMINI_API_KEY or config)".to_string(),
+            ));
+        };
+
+        let (contents, system_instruction) = Self::build_contents(request.messages);
+        let generate_request = GenerateContentRequest {
+            contents,
+            system_instruction,
+            generation_config: GenerationConfig {
+                temperature,
+                max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
+                thinking_config: self.thinking_config(),
+            }
+            .with_response_format(request.response_format),
+        };
+
+        let request = self.http_client().post(url).json(&generate_request);
+        let credential = self
+            .auth
+            .as_ref()
+            .and_then(|auth| auth.quota_credential(None));
+        send_sse_request("Gemini", credential, request, GeminiStreamParser)
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         if let Some(auth) = self.auth.as_ref() {
             match auth {
@@ -1314,11 +1507,13 @@ impl Provider for GeminiProvider {
                         "https://generativelanguage.googleapis.com/v1beta/models".to_string()
                     };
 
-                    self.http_client()
-                        .get(&url)
-                        .send()
-                        .await?
-                        .error_for_status()?;
+                    super::quota_scheduler::send(
+                        "gemini",
+                        auth.quota_credential(None),
+                        self.http_client().get(&url),
+                    )
+                    .await?
+                    .error_for_status()?;
                 }
             }
         }
@@ -1350,6 +1545,7 @@ mod tests {
             oauth_index: Arc::new(tokio::sync::Mutex::new(0)),
             auth_service: None,
             auth_profile_override: None,
+            thinking_budget_tokens: None,
         }
     }
 
@@ -1552,6 +1748,9 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
+                thinking_config: None,
             },
         };
 
@@ -1593,6 +1792,9 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
+                thinking_config: None,
             },
         };
 
@@ -1637,6 +1839,9 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
+                thinking_config: None,
             },
         };
 
@@ -1674,6 +1879,9 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
+                thinking_config: None,
             },
         };
 
@@ -1703,6 +1911,9 @@ mod tests {
                 generation_config: Some(GenerationConfig {
                     temperature: 0.7,
                     max_output_tokens: 8192,
+                    response_mime_type: None,
+                    response_schema: None,
+                    thinking_config: None,
                 }),
             },
         };
@@ -1741,6 +1952,34 @@ mod tests {
         assert!(json.contains("\"model\":\"gemini-3-pro-preview\""));
     }
 
+    #[test]
+    fn generation_config_serializes_response_schema() {
+        let format = ResponseFormat::json_schema(
+            "verdict",
+            serde_json::json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {"ok": {"type": "boolean"}},
+                "required": ["ok"]
+            }),
+        );
+        let plain = GenerationConfig {
+            temperature: 0.2,
+            max_output_tokens: 8192,
+            response_mime_type: None,
+            response_schema: None,
+            thinking_config: None,
+        };
+        let json = serde_json::to_value(plain.clone()).unwrap();
+        assert!(json.get("responseMimeType").is_none());
+        assert!(json.get("responseSchema").is_none());
+
+        let json = serde_json::to_value(plain.with_response_format(Some(&format))).unwrap();
+        assert_eq!(json["responseMimeType"], "application/json");
+        assert_eq!(json["responseSchema"]["required"][0], "ok");
+        assert!(json["responseSchema"].get("$schema").is_none());
+    }
+
     #[test]
     fn internal_request_includes_project() {
         let request = InternalGenerateContentEnvelope {
@@ -2022,6 +2261,80 @@ mod tests {
         assert_eq!(text, Some("Part one. Part two.".to_string()));
     }
 
+    #[test]
+    fn stream_parser_separates_thought_parts_and_reports_usage() {
+        let events = GeminiStreamParser
+            .parse(
+                r#"{"candidates":[{"content":{"parts":[
+                    {"thought": true, "text": "pondering"},
+                    {"text": "Hello"}
+                ]}}],"usageMetadata":{"promptTokenCount":9,"candidatesTokenCount":2}}"#,
+            )
+            .unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            StreamEvent::ThinkingDelta(ThinkingDelta { index: 0, text, .. }) if text == "pondering"
+        ));
+        assert!(matches!(&events[1], StreamEvent::TextDelta(text) if text == "Hello"));
+        assert!(matches!(
+            &events[2],
+            StreamEvent::Usage(TokenUsage {
+                input_tokens: Some(9),
+                output_tokens: Some(2),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn thought_parts_become_thinking_blocks() {
+        let content: CandidateContent = serde_json::from_str(
+            r#"{"parts":[
+                {"thought": true, "text": "Weighing options", "thoughtSignature": "c2ln"},
+                {"text": "Answer"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let blocks = content.thinking_blocks();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].text, "Weighing options");
+        assert_eq!(blocks[0].signature.as_deref(), Some("c2ln"));
+        assert_eq!(content.effective_text().as_deref(), Some("Answer"));
+    }
+
+    #[test]
+    fn thinking_budget_serializes_thinking_config() {
+        let provider = test_provider(None).with_thinking_budget(Some(2048));
+        let config = GenerationConfig {
+            temperature: 0.7,
+            max_output_tokens: 8192,
+            response_mime_type: None,
+            response_schema: None,
+            thinking_config: provider.thinking_config(),
+        };
+        let json = serde_json::to_value(config).unwrap();
+        assert_eq!(json["thinkingConfig"]["thinkingBudget"], 2048);
+        assert_eq!(json["thinkingConfig"]["includeThoughts"], true);
+    }
+
+    #[test]
+    fn stream_url_requires_api_key_auth() {
+        let url = GeminiProvider::build_stream_generate_content_url(
+            "gemini-2.0-flash",
+            &GeminiAuth::ExplicitKey("k".into()),
+        )
+        .unwrap();
+        assert!(url.ends_with("models/gemini-2.0-flash:streamGenerateContent?alt=sse&key=k"));
+        assert!(GeminiProvider::build_stream_generate_content_url(
+            "gemini-2.0-flash",
+            &GeminiAuth::ManagedOAuth
+        )
+        .is_none());
+    }
+
     #[test]
     fn thought_signature_only_parts_skipped() {
         let json = r#"{
@@ -2123,6 +2436,7 @@ mod tests {
             oauth_index: Arc::new(tokio::sync::Mutex::new(0)),
             auth_service: None, // Missing auth_service
             auth_profile_override: None,
+            thinking_budget_tokens: None,
         };
 
         let result = provider.warmup().await;
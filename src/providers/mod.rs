@@ -16,6 +16,7 @@
 //! To add a new provider, implement [`Provider`] in a new submodule and register it
 //! in [`create_provider_with_url`]. See `AGENTS.md` §7.1 for the full change playbook.
 
+pub mod adaptive_routing;
 pub mod anthropic;
 pub mod bedrock;
 pub mod compatible;
@@ -25,15 +26,22 @@ pub mod ollama;
 pub mod openai;
 pub mod openai_codex;
 pub mod openrouter;
+pub mod quota_adapter;
+pub mod quota_scheduler;
+pub mod quota_types;
+pub mod record_replay;
 pub mod reliable;
 pub mod router;
+pub mod scripted;
+pub mod streaming;
+pub mod structured;
 pub mod telnyx;
 pub mod traits;
 
 #[allow(unused_imports)]
 pub use traits::{
     ChatMessage, ChatRequest, ChatResponse, ConversationMessage, Provider, ProviderCapabilityError,
-    ToolCall, ToolResultMessage,
+    ResponseFormat, StreamEvent, ThinkingBlock, ToolCall, ToolResultMessage,
 };
 
 use crate::auth::AuthService;
@@ -698,6 +706,9 @@ pub struct ProviderRuntimeOptions {
     pub custom_provider_api_mode: Option<CompatibleApiMode>,
     pub max_tokens_override: Option<u32>,
     pub model_support_vision: Option<bool>,
+    pub thinking_budget_tokens: Option<u32>,
+    pub ollama: crate::config::OllamaConfig,
+    pub observer: Option<std::sync::Arc<dyn crate::observability::Observer>>,
 }
 
 impl Default for ProviderRuntimeOptions {
@@ -713,6 +724,9 @@ impl Default for ProviderRuntimeOptions {
             custom_provider_api_mode: None,
             max_tokens_override: None,
             model_support_vision: None,
+            thinking_budget_tokens: None,
+            ollama: crate::config::OllamaConfig::default(),
+            observer: None,
         }
     }
 }
@@ -1067,18 +1081,24 @@ fn create_provider_with_url_and_options(
             key,
             options.max_tokens_override,
         ))),
-        "anthropic" => Ok(Box::new(anthropic::AnthropicProvider::new(key))),
-        "openai" => Ok(Box::new(openai::OpenAiProvider::with_base_url_and_max_tokens(
-            api_url,
-            key,
-            options.max_tokens_override,
-        ))),
+        "anthropic" => Ok(Box::new(
+            anthropic::AnthropicProvider::new(key)
+                .with_thinking_budget(options.thinking_budget_tokens),
+        )),
+        "openai" => Ok(Box::new(
+            openai::OpenAiProvider::with_base_url_and_max_tokens(
+                api_url,
+                key,
+                options.max_tokens_override,
+            )
+            .with_reasoning_effort(options.reasoning_level.clone()),
+        )),
         // Ollama uses api_url for custom base URL (e.g. remote Ollama instance)
-        "ollama" => Ok(Box::new(ollama::OllamaProvider::new_with_reasoning(
-            api_url,
-            key,
-            options.reasoning_enabled,
-        ))),
+        "ollama" => Ok(Box::new(
+            ollama::OllamaProvider::new_with_reasoning(api_url, key, options.reasoning_enabled)
+                .with_lifecycle(&options.ollama)
+                .with_observer(options.observer.clone()),
+        )),
         "gemini" | "google" | "google-gemini" => {
             let state_dir = options
                 .zeroclaw_dir
@@ -1090,11 +1110,14 @@ fn create_provider_with_url_and_options(
                     )
                 });
             let auth_service = AuthService::new(&state_dir, options.secrets_encrypt);
-            Ok(Box::new(gemini::GeminiProvider::new_with_auth(
-                key,
-                auth_service,
-                options.auth_profile_override.clone(),
-            )))
+            Ok(Box::new(
+                gemini::GeminiProvider::new_with_auth(
+                    key,
+                    auth_service,
+                    options.auth_profile_override.clone(),
+                )
+                .with_thinking_budget(options.thinking_budget_tokens),
+            ))
         }
         "telnyx" => Ok(Box::new(telnyx::TelnyxProvider::new(key))),
 
@@ -1157,7 +1180,9 @@ fn create_provider_with_url_and_options(
                 AuthStyle::Bearer,
             )
         )),
-        "bedrock" | "aws-bedrock" => Ok(Box::new(bedrock::BedrockProvider::new())),
+        "bedrock" | "aws-bedrock" => Ok(Box::new(
+            bedrock::BedrockProvider::new().with_thinking_budget(options.thinking_budget_tokens),
+        )),
         name if is_qwen_oauth_alias(name) => {
             let base_url = api_url
                 .map(str::trim)
@@ -1350,10 +1375,10 @@ fn create_provider_with_url_and_options(
                 "Anthropic-custom provider",
                 "anthropic-custom:https://your-api.com",
             )?;
-            Ok(Box::new(anthropic::AnthropicProvider::with_base_url(
-                key,
-                Some(&base_url),
-            )))
+            Ok(Box::new(
+                anthropic::AnthropicProvider::with_base_url(key, Some(&base_url))
+                    .with_thinking_budget(options.thinking_budget_tokens),
+            ))
         }
 
         _ => anyhow::bail!(
@@ -1397,6 +1422,9 @@ pub fn create_resilient_provider(
 }
 
 /// Create provider chain with retry/fallback behavior and auth runtime options.
+///
+/// When `ZEROCLAW_PROVIDER_CASSETTE` is set, the chain is wrapped in a
+/// [`record_replay::RecordReplayProvider`].
 pub fn create_resilient_provider_with_options(
     primary_name: &str,
     api_key: Option<&str>,
@@ -1404,6 +1432,27 @@ pub fn create_resilient_provider_with_options(
     reliability: &crate::config::ReliabilityConfig,
     options: &ProviderRuntimeOptions,
 ) -> anyhow::Result<Box<dyn Provider>> {
+    record_replay::wrap_from_env(build_resilient_provider(
+        primary_name,
+        api_key,
+        api_url,
+        reliability,
+        options,
+    )?)
+}
+
+fn build_resilient_provider(
+    primary_name: &str,
+    api_key: Option<&str>,
+    api_url: Option<&str>,
+    reliability: &crate::config::ReliabilityConfig,
+    options: &ProviderRuntimeOptions,
+) -> anyhow::Result<Box<dyn Provider>> {
+    quota_scheduler::configure(
+        reliability.rate_limit_pacing,
+        std::time::Duration::from_secs(reliability.rate_limit_max_wait_secs),
+    );
+
     let mut providers: Vec<(String, Box<dyn Provider>)> = Vec::new();
 
     let primary_provider = match primary_name {
@@ -1457,7 +1506,11 @@ pub fn create_resilient_provider_with_options(
     )
     .with_api_keys(reliability.api_keys.clone())
     .with_model_fallbacks(reliability.model_fallbacks.clone())
-    .with_vision_override(options.model_support_vision);
+    .with_vision_override(options.model_support_vision)
+    .with_hedging(reliability.hedge_enabled.then(|| reliable::HedgePolicy {
+        percentile: reliability.hedge_percentile,
+        initial_delay: std::time::Duration::from_millis(reliability.hedge_initial_delay_ms),
+    }));
 
     Ok(Box::new(reliable))
 }
@@ -1493,6 +1546,31 @@ pub fn create_routed_provider_with_options(
     model_routes: &[crate::config::ModelRouteConfig],
     default_model: &str,
     options: &ProviderRuntimeOptions,
+) -> anyhow::Result<Box<dyn Provider>> {
+    create_routed_provider_with_policy(
+        primary_name,
+        api_key,
+        api_url,
+        reliability,
+        model_routes,
+        default_model,
+        options,
+        None,
+    )
+}
+
+/// Create a routed provider that resolves shared route hints through an
+/// adaptive routing policy (see [`adaptive_routing`]).
+#[allow(clippy::too_many_arguments)]
+pub fn create_routed_provider_with_policy(
+    primary_name: &str,
+    api_key: Option<&str>,
+    api_url: Option<&str>,
+    reliability: &crate::config::ReliabilityConfig,
+    model_routes: &[crate::config::ModelRouteConfig],
+    default_model: &str,
+    options: &ProviderRuntimeOptions,
+    adaptive: Option<adaptive_routing::AdaptiveRoutingPolicy>,
 ) -> anyhow::Result<Box<dyn Provider>> {
     if model_routes.is_empty() {
         return create_resilient_provider_with_options(
@@ -1505,13 +1583,8 @@ pub fn create_routed_provider_with_options(
     }
 
     // Keep a default provider for non-routed model hints.
-    let default_provider = create_resilient_provider_with_options(
-        primary_name,
-        api_key,
-        api_url,
-        reliability,
-        options,
-    )?;
+    let default_provider =
+        build_resilient_provider(primary_name, api_key, api_url, reliability, options)?;
     let mut providers: Vec<(String, Box<dyn Provider>)> =
         vec![(primary_name.to_string(), default_provider)];
 
@@ -1539,21 +1612,21 @@ pub fn create_routed_provider_with_options(
             route_options.provider_transport = Some(transport.to_string());
         }
 
-        match create_resilient_provider_with_options(
-            &route.provider,
-            key,
-            url,
-            reliability,
-            &route_options,
-        ) {
+        match build_resilient_provider(&route.provider, key, url, reliability, &route_options) {
             Ok(provider) => {
-                let provider_id = format!("{}#{}", route.provider, route.hint);
+                let mut provider_id = format!("{}#{}", route.provider, route.hint);
+                // Several routes may share a provider and hint under adaptive
+                // routing; keep their provider ids distinct.
+                if providers.iter().any(|(name, _)| *name == provider_id) {
+                    provider_id = format!("{provider_id}#{}", providers.len());
+                }
                 providers.push((provider_id.clone(), provider));
                 routes.push((
                     route.hint.clone(),
                     router::Route {
                         provider_name: provider_id,
                         model: route.model.clone(),
+                        quality_tier: route.quality_tier,
                     },
                 ));
             }
@@ -1570,9 +1643,10 @@ pub fn create_routed_provider_with_options(
     // Keep only successfully initialized routed providers and preserve
     // their provider-id bindings (e.g. "<provider>#<hint>").
 
-    Ok(Box::new(
+    record_replay::wrap_from_env(Box::new(
         router::RouterProvider::new(providers, routes, default_model.to_string())
-            .with_vision_override(options.model_support_vision),
+            .with_vision_override(options.model_support_vision)
+            .with_adaptive_routing(adaptive),
     ))
 }
 
@@ -2663,6 +2737,11 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            hedge_enabled: false,
+            hedge_percentile: 95.0,
+            hedge_initial_delay_ms: 3000,
+            rate_limit_pacing: true,
+            rate_limit_max_wait_secs: 60,
         };
 
         let provider = create_resilient_provider(
@@ -2702,6 +2781,11 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            hedge_enabled: false,
+            hedge_percentile: 95.0,
+            hedge_initial_delay_ms: 3000,
+            rate_limit_pacing: true,
+            rate_limit_max_wait_secs: 60,
         };
 
         // Primary uses a ZAI key; fallbacks (lmstudio, ollama) should NOT
@@ -2724,6 +2808,11 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            hedge_enabled: false,
+            hedge_percentile: 95.0,
+            hedge_initial_delay_ms: 3000,
+            rate_limit_pacing: true,
+            rate_limit_max_wait_secs: 60,
         };
 
         let provider =
@@ -2750,6 +2839,11 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            hedge_enabled: false,
+            hedge_percentile: 95.0,
+            hedge_initial_delay_ms: 3000,
+            rate_limit_pacing: true,
+            rate_limit_max_wait_secs: 60,
         };
 
         let provider = create_resilient_provider("zai", Some("zai-test-key"), None, &reliability);
@@ -2782,6 +2876,11 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            hedge_enabled: false,
+            hedge_percentile: 95.0,
+            hedge_initial_delay_ms: 3000,
+            rate_limit_pacing: true,
+            rate_limit_max_wait_secs: 60,
         };
 
         let provider = create_resilient_provider("zai", Some("zai-test-key"), None, &reliability);
@@ -3091,6 +3190,7 @@ mod tests {
             max_tokens: Some(4096),
             api_key: None,
             transport: None,
+            quality_tier: None,
         }];
 
         let provider = create_routed_provider_with_options(
@@ -3167,6 +3267,11 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            hedge_enabled: false,
+            hedge_percentile: 95.0,
+            hedge_initial_delay_ms: 3000,
+            rate_limit_pacing: true,
+            rate_limit_max_wait_secs: 60,
         };
 
         // openai-codex resolves its own OAuth credential; it should not
@@ -3196,6 +3301,11 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            hedge_enabled: false,
+            hedge_percentile: 95.0,
+            hedge_initial_delay_ms: 3000,
+            rate_limit_pacing: true,
+            rate_limit_max_wait_secs: 60,
         };
 
         let provider = create_resilient_provider("ollama", None, None, &reliability);
@@ -1,16 +1,28 @@
+use crate::config::OllamaConfig;
 use crate::multimodal;
+use crate::observability::{Observer, ObserverEvent};
 use crate::providers::traits::{
-    ChatMessage, ChatResponse, Provider, ProviderCapabilities, TokenUsage, ToolCall,
+    ChatMessage, ChatResponse, Provider, ProviderCapabilities, ResponseFormat, TokenUsage, ToolCall,
 };
 use async_trait::async_trait;
-use reqwest::Client;
+use futures_util::StreamExt;
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Model downloads can take a long time on slow links; allow up to six hours.
+const PULL_TIMEOUT_SECS: u64 = 6 * 60 * 60;
 
 pub struct OllamaProvider {
     base_url: String,
     api_key: Option<String>,
     reasoning_enabled: Option<bool>,
+    auto_pull: bool,
+    keep_alive: Option<serde_json::Value>,
+    preload_models: Vec<String>,
+    num_ctx: Option<u32>,
+    observer: Option<Arc<dyn Observer>>,
 }
 
 // ─── Request Structures ───────────────────────────────────────────────────────
@@ -25,6 +37,11 @@ struct ChatRequest {
     think: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<serde_json::Value>>,
+    /// JSON Schema the reply must conform to (structured outputs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -56,6 +73,8 @@ struct OutgoingFunction {
 #[derive(Debug, Serialize)]
 struct Options {
     temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_ctx: Option<u32>,
 }
 
 // ─── Response Structures ──────────────────────────────────────────────────────
@@ -93,6 +112,57 @@ struct OllamaFunction {
     arguments: serde_json::Value,
 }
 
+/// One progress line streamed by `POST /api/pull`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PullProgress {
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub digest: Option<String>,
+    #[serde(default)]
+    pub total: Option<u64>,
+    #[serde(default)]
+    pub completed: Option<u64>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl PullProgress {
+    /// Download progress of the current layer, when the server reports sizes.
+    pub fn percent(&self) -> Option<u64> {
+        match (self.completed, self.total) {
+            (Some(completed), Some(total)) if total > 0 => Some(completed.min(total) * 100 / total),
+            _ => None,
+        }
+    }
+}
+
+/// A model installed on the Ollama server (`GET /api/tags`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct LocalModel {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub modified_at: Option<String>,
+    #[serde(default)]
+    pub details: LocalModelDetails,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LocalModelDetails {
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+    #[serde(default)]
+    pub quantization_level: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<LocalModel>,
+}
+
 // ─── Implementation ───────────────────────────────────────────────────────────
 
 impl OllamaProvider {
@@ -127,7 +197,47 @@ impl OllamaProvider {
             base_url: Self::normalize_base_url(base_url.unwrap_or("http://localhost:11434")),
             api_key,
             reasoning_enabled,
+            auto_pull: false,
+            keep_alive: None,
+            preload_models: Vec::new(),
+            num_ctx: None,
+            observer: None,
+        }
+    }
+
+    /// Apply `[ollama]` settings: auto-pull, keep-alive, preloading and `num_ctx`.
+    pub fn with_lifecycle(mut self, config: &OllamaConfig) -> Self {
+        self.auto_pull = config.auto_pull;
+        self.keep_alive = config
+            .keep_alive
+            .as_deref()
+            .and_then(Self::keep_alive_value);
+        self.preload_models = config
+            .preload_models
+            .iter()
+            .map(|model| model.trim().to_string())
+            .filter(|model| !model.is_empty())
+            .collect();
+        self.num_ctx = config.num_ctx.filter(|num_ctx| *num_ctx > 0);
+        self
+    }
+
+    /// Report model pull progress to `observer`.
+    pub fn with_observer(mut self, observer: Option<Arc<dyn Observer>>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    /// Ollama accepts either a duration string (`"5m"`) or a number of seconds.
+    fn keep_alive_value(raw: &str) -> Option<serde_json::Value> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            return None;
         }
+        Some(trimmed.parse::<i64>().map_or_else(
+            |_| serde_json::json!(trimmed),
+            |secs| serde_json::json!(secs),
+        ))
     }
 
     fn is_local_endpoint(&self) -> bool {
@@ -141,6 +251,188 @@ impl OllamaProvider {
         crate::config::build_runtime_proxy_client_with_timeouts("provider.ollama", 300, 10)
     }
 
+    fn authorize(
+        &self,
+        request: reqwest::RequestBuilder,
+        should_auth: bool,
+    ) -> reqwest::RequestBuilder {
+        match self.api_key.as_ref() {
+            Some(key) if should_auth => request.bearer_auth(key),
+            _ => request,
+        }
+    }
+
+    fn management_auth(&self) -> bool {
+        self.api_key.is_some() && !self.is_local_endpoint()
+    }
+
+    fn is_missing_model_error(status: StatusCode, body: &str) -> bool {
+        let lower = body.to_ascii_lowercase();
+        status == StatusCode::NOT_FOUND && lower.contains("model") && lower.contains("not found")
+    }
+
+    /// List models installed on the Ollama server.
+    pub async fn list_local_models(&self) -> anyhow::Result<Vec<LocalModel>> {
+        let url = format!("{}/api/tags", self.base_url);
+        let response = self
+            .authorize(self.http_client().get(&url), self.management_auth())
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("Ollama", response).await);
+        }
+        let tags: TagsResponse = response.json().await?;
+        Ok(tags.models)
+    }
+
+    /// Whether `model` is installed (an untagged name matches `:latest`).
+    pub async fn has_model(&self, model: &str) -> anyhow::Result<bool> {
+        let installed = self.list_local_models().await?;
+        Ok(installed
+            .iter()
+            .any(|local| Self::model_names_match(&local.name, model)))
+    }
+
+    fn model_names_match(installed: &str, requested: &str) -> bool {
+        installed == requested
+            || (!requested.contains(':') && installed == format!("{requested}:latest"))
+    }
+
+    /// Remove a model from the Ollama server.
+    pub async fn delete_model(&self, model: &str) -> anyhow::Result<()> {
+        let url = format!("{}/api/delete", self.base_url);
+        let response = self
+            .authorize(
+                self.http_client()
+                    .delete(&url)
+                    .json(&serde_json::json!({ "model": model })),
+                self.management_auth(),
+            )
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            anyhow::bail!("Ollama model '{model}' is not installed");
+        }
+        if !response.status().is_success() {
+            return Err(super::api_error("Ollama", response).await);
+        }
+        Ok(())
+    }
+
+    /// Download `model` from the Ollama registry, calling `on_progress` for
+    /// every progress line the server streams back.
+    pub async fn pull_model<F: FnMut(&PullProgress)>(
+        &self,
+        model: &str,
+        mut on_progress: F,
+    ) -> anyhow::Result<()> {
+        let url = format!("{}/api/pull", self.base_url);
+        let client = crate::config::build_runtime_proxy_client_with_timeouts(
+            "provider.ollama",
+            PULL_TIMEOUT_SECS,
+            10,
+        );
+        let response = self
+            .authorize(
+                client
+                    .post(&url)
+                    .json(&serde_json::json!({ "model": model, "stream": true })),
+                self.management_auth(),
+            )
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("Ollama", response).await);
+        }
+
+        let mut succeeded = false;
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            buffer.extend_from_slice(&chunk?);
+            while let Some(newline) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=newline).collect();
+                if let Some(progress) = Self::parse_pull_line(&String::from_utf8_lossy(&line))? {
+                    succeeded |= progress.status == "success";
+                    on_progress(&progress);
+                }
+            }
+        }
+        if let Some(progress) = Self::parse_pull_line(&String::from_utf8_lossy(&buffer))? {
+            succeeded |= progress.status == "success";
+            on_progress(&progress);
+        }
+
+        if !succeeded {
+            anyhow::bail!("Ollama pull of '{model}' ended before completing");
+        }
+        Ok(())
+    }
+
+    fn parse_pull_line(line: &str) -> anyhow::Result<Option<PullProgress>> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let progress: PullProgress = serde_json::from_str(line)
+            .map_err(|e| anyhow::anyhow!("Failed to parse Ollama pull progress: {e}"))?;
+        if let Some(error) = progress.error.as_deref() {
+            anyhow::bail!("Ollama pull failed: {}", super::sanitize_api_error(error));
+        }
+        Ok(Some(progress))
+    }
+
+    /// Pull `model`, reporting progress to the observer and logs at most once
+    /// per phase and 10% step.
+    async fn pull_with_progress(&self, model: &str) -> anyhow::Result<()> {
+        tracing::info!(model, "Ollama model not installed; pulling it");
+        let mut last_reported: Option<(String, Option<u64>)> = None;
+        self.pull_model(model, |progress| {
+            let step = (progress.status.clone(), progress.percent().map(|p| p / 10));
+            if last_reported.as_ref() == Some(&step) {
+                return;
+            }
+            last_reported = Some(step);
+            tracing::info!(
+                model,
+                status = %progress.status,
+                percent = ?progress.percent(),
+                "Pulling Ollama model"
+            );
+            if let Some(observer) = self.observer.as_ref() {
+                observer.record_event(&ObserverEvent::ModelPull {
+                    provider: "ollama".to_string(),
+                    model: model.to_string(),
+                    status: progress.status.clone(),
+                    completed_bytes: progress.completed,
+                    total_bytes: progress.total,
+                });
+            }
+        })
+        .await
+    }
+
+    /// Load `model` into memory without generating anything.
+    async fn preload_model(&self, model: &str) -> anyhow::Result<()> {
+        let url = format!("{}/api/generate", self.base_url);
+        let mut body = serde_json::json!({ "model": model });
+        if let Some(keep_alive) = self.keep_alive.clone() {
+            body["keep_alive"] = keep_alive;
+        }
+        let response = self
+            .authorize(
+                self.http_client().post(&url).json(&body),
+                self.management_auth(),
+            )
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("Ollama", response).await);
+        }
+        tracing::info!(model, "Preloaded Ollama model");
+        Ok(())
+    }
+
     fn resolve_request_details(&self, model: &str) -> anyhow::Result<(String, bool)> {
         let requests_cloud = model.ends_with(":cloud");
         let normalized_model = model.strip_suffix(":cloud").unwrap_or(model).to_string();
@@ -210,9 +502,14 @@ impl OllamaProvider {
             model: model.to_string(),
             messages,
             stream: false,
-            options: Options { temperature },
+            options: Options {
+                temperature,
+                num_ctx: self.num_ctx,
+            },
             think: self.reasoning_enabled,
             tools: tools.map(|t| t.to_vec()),
+            format: None,
+            keep_alive: self.keep_alive.clone(),
         }
     }
 
@@ -343,6 +640,94 @@ impl OllamaProvider {
             .collect()
     }
 
+    /// Send a native `/api/chat` request with optional tools and JSON Schema
+    /// `format`, returning a structured response.
+    async fn chat_native(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        format: Option<&ResponseFormat>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let (normalized_model, should_auth) = self.resolve_request_details(model)?;
+
+        let api_messages = self.convert_messages(messages);
+
+        // Tools arrive pre-formatted in OpenAI/Ollama-compatible JSON from
+        // tools_to_openai_format() in loop_.rs — pass them through directly.
+        let tools_opt = if tools.is_empty() { None } else { Some(tools) };
+
+        let response = self
+            .send_request(
+                api_messages,
+                &normalized_model,
+                temperature,
+                should_auth,
+                tools_opt,
+                format,
+            )
+            .await?;
+
+        let usage = if response.prompt_eval_count.is_some() || response.eval_count.is_some() {
+            Some(TokenUsage {
+                input_tokens: response.prompt_eval_count,
+                output_tokens: response.eval_count,
+                cache_read_tokens: None,
+                cache_write_tokens: None,
+            })
+        } else {
+            None
+        };
+
+        // Native tool calls returned by the model.
+        if !response.message.tool_calls.is_empty() {
+            let tool_calls: Vec<ToolCall> = response
+                .message
+                .tool_calls
+                .iter()
+                .map(|tc| {
+                    let (name, args) = self.extract_tool_name_and_args(tc);
+                    ToolCall {
+                        id: tc
+                            .id
+                            .clone()
+                            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                        name,
+                        arguments: serde_json::to_string(&args)
+                            .unwrap_or_else(|_| "{}".to_string()),
+                    }
+                })
+                .collect();
+            let text = Self::normalize_response_text(response.message.content);
+            return Ok(ChatResponse {
+                text,
+                tool_calls,
+                usage,
+                reasoning_content: None,
+                thinking: Vec::new(),
+            });
+        }
+
+        // Plain text response.
+        let content = response.message.content;
+        let text = if let Some(content) = Self::normalize_response_text(content) {
+            content
+        } else {
+            Self::fallback_text_for_empty_content(
+                &normalized_model,
+                response.message.thinking.as_deref(),
+            )
+        };
+        Ok(ChatResponse {
+            text: Some(text),
+            tool_calls: vec![],
+            usage,
+            reasoning_content: None,
+            thinking: Vec::new(),
+        })
+    }
+
     /// Send a request to Ollama and get the parsed response.
     /// Pass `tools` to enable native function-calling for models that support it.
     async fn send_request(
@@ -352,8 +737,10 @@ impl OllamaProvider {
         temperature: f64,
         should_auth: bool,
         tools: Option<&[serde_json::Value]>,
+        format: Option<&ResponseFormat>,
     ) -> anyhow::Result<ApiChatResponse> {
-        let request = self.build_chat_request(messages, model, temperature, tools);
+        let mut request = self.build_chat_request(messages, model, temperature, tools);
+        request.format = format.map(|f| f.schema.clone());
 
         let url = format!("{}/api/chat", self.base_url);
 
@@ -367,20 +754,30 @@ impl OllamaProvider {
             request.tools.as_ref().map_or(0, |t| t.len()),
         );
 
-        let mut request_builder = self.http_client().post(&url).json(&request);
-
-        if should_auth {
-            if let Some(key) = self.api_key.as_ref() {
-                request_builder = request_builder.bearer_auth(key);
+        let mut pulled = false;
+        let (status, body) = loop {
+            let response = self
+                .authorize(self.http_client().post(&url).json(&request), should_auth)
+                .send()
+                .await?;
+            let status = response.status();
+            tracing::debug!("Ollama response status: {}", status);
+
+            let body = response.bytes().await?;
+            tracing::debug!("Ollama response body length: {} bytes", body.len());
+
+            if Self::is_missing_model_error(status, &String::from_utf8_lossy(&body)) {
+                if self.auto_pull && !pulled {
+                    self.pull_with_progress(model).await?;
+                    pulled = true;
+                    continue;
+                }
+                anyhow::bail!(
+                    "Ollama model '{model}' is not installed. Run `zeroclaw models pull {model}` or set `ollama.auto_pull = true`."
+                );
             }
-        }
-
-        let response = request_builder.send().await?;
-        let status = response.status();
-        tracing::debug!("Ollama response status: {}", status);
-
-        let body = response.bytes().await?;
-        tracing::debug!("Ollama response body length: {} bytes", body.len());
+            break (status, body);
+        };
 
         if !status.is_success() {
             let raw = String::from_utf8_lossy(&body);
@@ -494,6 +891,17 @@ impl Provider for OllamaProvider {
         }
     }
 
+    async fn warmup(&self) -> anyhow::Result<()> {
+        for model in &self.preload_models {
+            let (model, _) = self.resolve_request_details(model)?;
+            if self.auto_pull && !self.has_model(&model).await? {
+                self.pull_with_progress(&model).await?;
+            }
+            self.preload_model(&model).await?;
+        }
+        Ok(())
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -525,7 +933,14 @@ impl Provider for OllamaProvider {
         });
 
         let response = self
-            .send_request(messages, &normalized_model, temperature, should_auth, None)
+            .send_request(
+                messages,
+                &normalized_model,
+                temperature,
+                should_auth,
+                None,
+                None,
+            )
             .await?;
 
         // If model returned tool calls, format them for loop_.rs's parse_tool_calls
@@ -566,6 +981,7 @@ impl Provider for OllamaProvider {
                 temperature,
                 should_auth,
                 None,
+                None,
             )
             .await?;
 
@@ -597,77 +1013,8 @@ impl Provider for OllamaProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let (normalized_model, should_auth) = self.resolve_request_details(model)?;
-
-        let api_messages = self.convert_messages(messages);
-
-        // Tools arrive pre-formatted in OpenAI/Ollama-compatible JSON from
-        // tools_to_openai_format() in loop_.rs — pass them through directly.
-        let tools_opt = if tools.is_empty() { None } else { Some(tools) };
-
-        let response = self
-            .send_request(
-                api_messages,
-                &normalized_model,
-                temperature,
-                should_auth,
-                tools_opt,
-            )
-            .await?;
-
-        let usage = if response.prompt_eval_count.is_some() || response.eval_count.is_some() {
-            Some(TokenUsage {
-                input_tokens: response.prompt_eval_count,
-                output_tokens: response.eval_count,
-            })
-        } else {
-            None
-        };
-
-        // Native tool calls returned by the model.
-        if !response.message.tool_calls.is_empty() {
-            let tool_calls: Vec<ToolCall> = response
-                .message
-                .tool_calls
-                .iter()
-                .map(|tc| {
-                    let (name, args) = self.extract_tool_name_and_args(tc);
-                    ToolCall {
-                        id: tc
-                            .id
-                            .clone()
-                            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
-                        name,
-                        arguments: serde_json::to_string(&args)
-                            .unwrap_or_else(|_| "{}".to_string()),
-                    }
-                })
-                .collect();
-            let text = Self::normalize_response_text(response.message.content);
-            return Ok(ChatResponse {
-                text,
-                tool_calls,
-                usage,
-                reasoning_content: None,
-            });
-        }
-
-        // Plain text response.
-        let content = response.message.content;
-        let text = if let Some(content) = Self::normalize_response_text(content) {
-            content
-        } else {
-            Self::fallback_text_for_empty_content(
-                &normalized_model,
-                response.message.thinking.as_deref(),
-            )
-        };
-        Ok(ChatResponse {
-            text: Some(text),
-            tool_calls: vec![],
-            usage,
-            reasoning_content: None,
-        })
+        self.chat_native(messages, tools, None, model, temperature)
+            .await
     }
 
     fn supports_native_tools(&self) -> bool {
@@ -677,13 +1024,17 @@ impl Provider for OllamaProvider {
         true
     }
 
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
     async fn chat(
         &self,
         request: crate::providers::traits::ChatRequest<'_>,
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        // Convert ToolSpec to OpenAI-compatible JSON and delegate to chat_with_tools.
+        // Convert ToolSpec to OpenAI-compatible JSON and send natively.
         if let Some(specs) = request.tools {
             if !specs.is_empty() {
                 let tools: Vec<serde_json::Value> = specs
@@ -700,11 +1051,29 @@ impl Provider for OllamaProvider {
                     })
                     .collect();
                 return self
-                    .chat_with_tools(request.messages, &tools, model, temperature)
+                    .chat_native(
+                        request.messages,
+                        &tools,
+                        request.response_format,
+                        model,
+                        temperature,
+                    )
                     .await;
             }
         }
 
+        if request.response_format.is_some() {
+            return self
+                .chat_native(
+                    request.messages,
+                    &[],
+                    request.response_format,
+                    model,
+                    temperature,
+                )
+                .await;
+        }
+
         // No tools — fall back to plain text chat.
         let text = self
             .chat_with_history(request.messages, model, temperature)
@@ -714,6 +1083,7 @@ impl Provider for OllamaProvider {
             tool_calls: vec![],
             usage: None,
             reasoning_content: None,
+            thinking: Vec::new(),
         })
     }
 }
@@ -846,6 +1216,27 @@ mod tests {
         assert_eq!(json.get("think"), Some(&serde_json::json!(false)));
     }
 
+    #[test]
+    fn request_format_is_serialized_only_when_set() {
+        let provider = OllamaProvider::new(None, None);
+        let message = || Message {
+            role: "user".to_string(),
+            content: Some("hello".to_string()),
+            images: None,
+            tool_calls: None,
+            tool_name: None,
+        };
+
+        let request = provider.build_chat_request(vec![message()], "llama3", 0.7, None);
+        let json = serde_json::to_value(request).unwrap();
+        assert!(json.get("format").is_none());
+
+        let mut request = provider.build_chat_request(vec![message()], "llama3", 0.7, None);
+        request.format = Some(serde_json::json!({"type": "object"}));
+        let json = serde_json::to_value(request).unwrap();
+        assert_eq!(json["format"]["type"], "object");
+    }
+
     #[test]
     fn response_deserializes() {
         let json = r#"{"message":{"role":"assistant","content":"Hello from Ollama!"}}"#;
@@ -1069,4 +1460,108 @@ mod tests {
         assert!(resp.prompt_eval_count.is_none());
         assert!(resp.eval_count.is_none());
     }
+
+    #[test]
+    fn keep_alive_is_sent_as_seconds_or_duration() {
+        let config = OllamaConfig {
+            keep_alive: Some("3600".into()),
+            ..OllamaConfig::default()
+        };
+        let provider = OllamaProvider::new(None, None).with_lifecycle(&config);
+        let request = provider.build_chat_request(Vec::new(), "llama3", 0.7, None);
+        let json = serde_json::to_value(request).unwrap();
+        assert_eq!(json["keep_alive"], serde_json::json!(3600));
+
+        let config = OllamaConfig {
+            keep_alive: Some("30m".into()),
+            ..OllamaConfig::default()
+        };
+        let provider = OllamaProvider::new(None, None).with_lifecycle(&config);
+        let request = provider.build_chat_request(Vec::new(), "llama3", 0.7, None);
+        let json = serde_json::to_value(request).unwrap();
+        assert_eq!(json["keep_alive"], serde_json::json!("30m"));
+
+        let provider = OllamaProvider::new(None, None);
+        let request = provider.build_chat_request(Vec::new(), "llama3", 0.7, None);
+        let json = serde_json::to_value(request).unwrap();
+        assert!(json.get("keep_alive").is_none());
+    }
+
+    #[test]
+    fn num_ctx_is_sent_in_options_when_configured() {
+        let config = OllamaConfig {
+            num_ctx: Some(16_384),
+            ..OllamaConfig::default()
+        };
+        let provider = OllamaProvider::new(None, None).with_lifecycle(&config);
+        let request = provider.build_chat_request(Vec::new(), "llama3.2", 0.7, None);
+        let json = serde_json::to_value(request).unwrap();
+        assert_eq!(json["options"]["num_ctx"], serde_json::json!(16_384));
+
+        let provider = OllamaProvider::new(None, None);
+        let request = provider.build_chat_request(Vec::new(), "llama3.2", 0.7, None);
+        let json = serde_json::to_value(request).unwrap();
+        assert!(json["options"].get("num_ctx").is_none());
+    }
+
+    #[test]
+    fn missing_model_error_is_detected() {
+        assert!(OllamaProvider::is_missing_model_error(
+            StatusCode::NOT_FOUND,
+            r#"{"error":"model \"llama9\" not found, try pulling it first"}"#
+        ));
+        assert!(!OllamaProvider::is_missing_model_error(
+            StatusCode::NOT_FOUND,
+            "404 page not found"
+        ));
+        assert!(!OllamaProvider::is_missing_model_error(
+            StatusCode::BAD_REQUEST,
+            r#"{"error":"model \"llama9\" not found"}"#
+        ));
+    }
+
+    #[test]
+    fn pull_lines_parse_progress_and_errors() {
+        let progress = OllamaProvider::parse_pull_line(
+            r#"{"status":"pulling 6a0746a1ec1a","digest":"sha256:6a07","total":2000,"completed":500}"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(progress.status, "pulling 6a0746a1ec1a");
+        assert_eq!(progress.percent(), Some(25));
+
+        let done = OllamaProvider::parse_pull_line(r#"{"status":"success"}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(done.percent(), None);
+
+        assert!(OllamaProvider::parse_pull_line("  \n").unwrap().is_none());
+        let err = OllamaProvider::parse_pull_line(
+            r#"{"error":"pull model manifest: file does not exist"}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("file does not exist"));
+    }
+
+    #[test]
+    fn tags_response_parses_local_models() {
+        let json = r#"{"models":[{"name":"llama3.2:latest","size":2019393189,
+            "modified_at":"2024-10-01T12:00:00Z",
+            "details":{"parameter_size":"3.2B","quantization_level":"Q4_K_M"}}]}"#;
+        let tags: TagsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(tags.models.len(), 1);
+        assert_eq!(tags.models[0].size, 2_019_393_189);
+        assert_eq!(
+            tags.models[0].details.quantization_level.as_deref(),
+            Some("Q4_K_M")
+        );
+        assert!(OllamaProvider::model_names_match(
+            "llama3.2:latest",
+            "llama3.2"
+        ));
+        assert!(!OllamaProvider::model_names_match(
+            "llama3.2:latest",
+            "llama3.2:1b"
+        ));
+    }
 }
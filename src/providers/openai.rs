@@ -1,9 +1,12 @@
+use crate::providers::streaming::{error_stream, send_sse_request, OpenAiStreamParser};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, TokenUsage, ToolCall as ProviderToolCall,
+    Provider, ResponseFormat, StreamError, StreamEvent, StreamOptions, StreamResult, TokenUsage,
+    ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
+use futures_util::stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +14,7 @@ pub struct OpenAiProvider {
     base_url: String,
     credential: Option<String>,
     max_tokens_override: Option<u32>,
+    reasoning_effort: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -67,6 +71,14 @@ struct NativeChatRequest {
     tools: Option<Vec<NativeToolSpec>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_effort: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -140,6 +152,15 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// OpenAI reports cached prompt tokens (already counted in `prompt_tokens`) here.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -189,9 +210,29 @@ impl OpenAiProvider {
                 .unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
             credential: credential.map(ToString::to_string),
             max_tokens_override: max_tokens_override.filter(|value| *value > 0),
+            reasoning_effort: None,
         }
     }
 
+    /// Set `reasoning_effort` (`minimal`, `low`, `medium`, `high`) for
+    /// reasoning models; other models never receive it.
+    pub fn with_reasoning_effort(mut self, reasoning_effort: Option<String>) -> Self {
+        self.reasoning_effort = reasoning_effort;
+        self
+    }
+
+    fn is_reasoning_model(model: &str) -> bool {
+        ["o1", "o3", "o4", "gpt-5"]
+            .iter()
+            .any(|prefix| model.starts_with(prefix))
+    }
+
+    fn reasoning_effort_for(&self, model: &str) -> Option<String> {
+        self.reasoning_effort
+            .clone()
+            .filter(|_| Self::is_reasoning_model(model))
+    }
+
     fn convert_tools(tools: Option<&[ToolSpec]>) -> Option<Vec<NativeToolSpec>> {
         tools.map(|items| {
             items
@@ -208,6 +249,18 @@ impl OpenAiProvider {
         })
     }
 
+    /// Map a structured-output request to the `json_schema` response format.
+    fn convert_response_format(format: &ResponseFormat) -> serde_json::Value {
+        serde_json::json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": format.name,
+                "schema": format.schema,
+                "strict": format.strict,
+            }
+        })
+    }
+
     fn convert_messages(messages: &[ChatMessage]) -> Vec<NativeMessage> {
         messages
             .iter()
@@ -301,6 +354,7 @@ impl OpenAiProvider {
             tool_calls,
             usage: None,
             reasoning_content,
+            thinking: Vec::new(),
         }
     }
 
@@ -343,13 +397,13 @@ impl Provider for OpenAiProvider {
             max_tokens: self.max_tokens_override,
         };
 
-        let response = self
+        let req = self
             .http_client()
             .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {credential}"))
-            .json(&request)
-            .send()
-            .await?;
+            .json(&request);
+        let response =
+            super::quota_scheduler::send("openai", Some(credential.as_str()), req).await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenAI", response).await);
@@ -383,15 +437,19 @@ impl Provider for OpenAiProvider {
             max_tokens: self.max_tokens_override,
             tool_choice: tools.as_ref().map(|_| "auto".to_string()),
             tools,
+            response_format: request.response_format.map(Self::convert_response_format),
+            stream: None,
+            stream_options: None,
+            reasoning_effort: self.reasoning_effort_for(model),
         };
 
-        let response = self
+        let req = self
             .http_client()
             .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {credential}"))
-            .json(&native_request)
-            .send()
-            .await?;
+            .json(&native_request);
+        let response =
+            super::quota_scheduler::send("openai", Some(credential.as_str()), req).await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenAI", response).await);
@@ -401,6 +459,10 @@ impl Provider for OpenAiProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
@@ -417,6 +479,54 @@ impl Provider for OpenAiProvider {
         true
     }
 
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
+    fn supports_tool_call_streaming(&self) -> bool {
+        true
+    }
+
+    fn stream_chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        _options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+        let Some(credential) = self.credential.as_ref() else {
+            return error_stream(StreamError::Provider(
+                "OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.".to_string(),
+            ));
+        };
+
+        let tools = Self::convert_tools(request.tools);
+        let native_request = NativeChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(request.messages),
+            temperature,
+            max_tokens: self.max_tokens_override,
+            tool_choice: tools.as_ref().map(|_| "auto".to_string()),
+            tools,
+            response_format: request.response_format.map(Self::convert_response_format),
+            stream: Some(true),
+            stream_options: Some(serde_json::json!({ "include_usage": true })),
+            reasoning_effort: self.reasoning_effort_for(model),
+        };
+
+        let request = self
+            .http_client()
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("Authorization", format!("Bearer {credential}"))
+            .json(&native_request);
+        send_sse_request(
+            "OpenAI",
+            Some(credential.as_str()),
+            request,
+            OpenAiStreamParser,
+        )
+    }
+
     async fn chat_with_tools(
         &self,
         messages: &[ChatMessage],
@@ -447,15 +557,19 @@ impl Provider for OpenAiProvider {
             max_tokens: self.max_tokens_override,
             tool_choice: native_tools.as_ref().map(|_| "auto".to_string()),
             tools: native_tools,
+            response_format: None,
+            stream: None,
+            stream_options: None,
+            reasoning_effort: self.reasoning_effort_for(model),
         };
 
-        let response = self
+        let req = self
             .http_client()
             .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {credential}"))
-            .json(&native_request)
-            .send()
-            .await?;
+            .json(&native_request);
+        let response =
+            super::quota_scheduler::send("openai", Some(credential.as_str()), req).await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenAI", response).await);
@@ -465,6 +579,10 @@ impl Provider for OpenAiProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
@@ -745,6 +863,21 @@ mod tests {
         assert_eq!(usage.completion_tokens, Some(50));
     }
 
+    #[test]
+    fn native_response_parses_cached_prompt_tokens() {
+        let json = r#"{
+            "choices": [{"message": {"content": "Hello"}}],
+            "usage": {
+                "prompt_tokens": 2000,
+                "completion_tokens": 50,
+                "prompt_tokens_details": {"cached_tokens": 1536}
+            }
+        }"#;
+        let resp: NativeChatResponse = serde_json::from_str(json).unwrap();
+        let details = resp.usage.unwrap().prompt_tokens_details.unwrap();
+        assert_eq!(details.cached_tokens, Some(1536));
+    }
+
     #[test]
     fn native_response_parses_without_usage() {
         let json = r#"{"choices": [{"message": {"content": "Hello"}}]}"#;
@@ -821,6 +954,19 @@ mod tests {
         assert!(native[0].reasoning_content.is_none());
     }
 
+    #[test]
+    fn convert_response_format_uses_json_schema_type() {
+        let format = ResponseFormat::json_schema(
+            "verdict",
+            serde_json::json!({"type": "object", "properties": {"ok": {"type": "boolean"}}}),
+        );
+        let value = OpenAiProvider::convert_response_format(&format);
+        assert_eq!(value["type"], "json_schema");
+        assert_eq!(value["json_schema"]["name"], "verdict");
+        assert_eq!(value["json_schema"]["strict"], true);
+        assert_eq!(value["json_schema"]["schema"]["type"], "object");
+    }
+
     #[test]
     fn native_message_omits_reasoning_content_when_none() {
         let msg = NativeMessage {
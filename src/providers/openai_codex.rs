@@ -1593,6 +1593,9 @@ data: [DONE]
             custom_provider_api_mode: None,
             max_tokens_override: None,
             model_support_vision: None,
+            thinking_budget_tokens: None,
+            ollama: Default::default(),
+            observer: None,
         };
         let provider =
             OpenAiCodexProvider::new(&options, None).expect("provider should initialize");
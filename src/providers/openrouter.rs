@@ -1,10 +1,14 @@
 use crate::multimodal;
+use crate::providers::streaming::{error_stream, send_sse_request, OpenAiStreamParser};
 use crate::providers::traits::{
-    ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, ProviderCapabilities, TokenUsage, ToolCall as ProviderToolCall,
+    with_response_format_instructions, ChatMessage, ChatRequest as ProviderChatRequest,
+    ChatResponse as ProviderChatResponse, Provider, ProviderCapabilities, TokenUsage,
+    ToolCall as ProviderToolCall,
 };
+use crate::providers::traits::{StreamError, StreamEvent, StreamOptions, StreamResult};
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
+use futures_util::stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
@@ -73,6 +77,10 @@ struct NativeChatRequest {
     tools: Option<Vec<NativeToolSpec>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -132,6 +140,15 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// OpenAI reports cached prompt tokens (already counted in `prompt_tokens`) here.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -302,6 +319,7 @@ impl OpenRouterProvider {
             tool_calls,
             usage: None,
             reasoning_content,
+            thinking: Vec::new(),
         }
     }
 
@@ -364,7 +382,7 @@ impl Provider for OpenRouterProvider {
             max_tokens: self.max_tokens_override,
         };
 
-        let response = self
+        let req = self
             .http_client()
             .post("https://openrouter.ai/api/v1/chat/completions")
             .header("Authorization", format!("Bearer {credential}"))
@@ -373,9 +391,9 @@ impl Provider for OpenRouterProvider {
                 "https://github.com/theonlyhennygod/zeroclaw",
             )
             .header("X-Title", "ZeroClaw")
-            .json(&request)
-            .send()
-            .await?;
+            .json(&request);
+        let response =
+            super::quota_scheduler::send("openrouter", Some(credential.as_str()), req).await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenRouter", response).await);
@@ -415,7 +433,7 @@ impl Provider for OpenRouterProvider {
             max_tokens: self.max_tokens_override,
         };
 
-        let response = self
+        let req = self
             .http_client()
             .post("https://openrouter.ai/api/v1/chat/completions")
             .header("Authorization", format!("Bearer {credential}"))
@@ -424,9 +442,9 @@ impl Provider for OpenRouterProvider {
                 "https://github.com/theonlyhennygod/zeroclaw",
             )
             .header("X-Title", "ZeroClaw")
-            .json(&request)
-            .send()
-            .await?;
+            .json(&request);
+        let response =
+            super::quota_scheduler::send("openrouter", Some(credential.as_str()), req).await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenRouter", response).await);
@@ -454,17 +472,24 @@ impl Provider for OpenRouterProvider {
         )
         })?;
 
+        // No native structured-output support here: guide the model via the prompt.
+        let guided_messages = request
+            .response_format
+            .map(|format| with_response_format_instructions(request.messages, format));
+        let messages = guided_messages.as_deref().unwrap_or(request.messages);
         let tools = Self::convert_tools(request.tools);
         let native_request = NativeChatRequest {
             model: model.to_string(),
-            messages: Self::convert_messages(request.messages),
+            messages: Self::convert_messages(messages),
             temperature,
             max_tokens: self.max_tokens_override,
             tool_choice: tools.as_ref().map(|_| "auto".to_string()),
             tools,
+            stream: None,
+            stream_options: None,
         };
 
-        let response = self
+        let req = self
             .http_client()
             .post("https://openrouter.ai/api/v1/chat/completions")
             .header("Authorization", format!("Bearer {credential}"))
@@ -473,9 +498,9 @@ impl Provider for OpenRouterProvider {
                 "https://github.com/theonlyhennygod/zeroclaw",
             )
             .header("X-Title", "ZeroClaw")
-            .json(&native_request)
-            .send()
-            .await?;
+            .json(&native_request);
+        let response =
+            super::quota_scheduler::send("openrouter", Some(credential.as_str()), req).await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenRouter", response).await);
@@ -485,6 +510,10 @@ impl Provider for OpenRouterProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
@@ -501,6 +530,58 @@ impl Provider for OpenRouterProvider {
         true
     }
 
+    fn supports_tool_call_streaming(&self) -> bool {
+        true
+    }
+
+    fn stream_chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        _options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+        let Some(credential) = self.credential.as_ref() else {
+            return error_stream(StreamError::Provider(
+                "OpenRouter API key not set. Run `zeroclaw onboard` or set OPENROUTER_API_KEY env var."
+                    .to_string(),
+            ));
+        };
+
+        let guided_messages = request
+            .response_format
+            .map(|format| with_response_format_instructions(request.messages, format));
+        let messages = guided_messages.as_deref().unwrap_or(request.messages);
+        let tools = Self::convert_tools(request.tools);
+        let native_request = NativeChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(messages),
+            temperature,
+            max_tokens: self.max_tokens_override,
+            tool_choice: tools.as_ref().map(|_| "auto".to_string()),
+            tools,
+            stream: Some(true),
+            stream_options: Some(serde_json::json!({ "include_usage": true })),
+        };
+
+        let request = self
+            .http_client()
+            .post("https://openrouter.ai/api/v1/chat/completions")
+            .header("Authorization", format!("Bearer {credential}"))
+            .header(
+                "HTTP-Referer",
+                "https://github.com/theonlyhennygod/zeroclaw",
+            )
+            .header("X-Title", "ZeroClaw")
+            .json(&native_request);
+        send_sse_request(
+            "OpenRouter",
+            Some(credential.as_str()),
+            request,
+            OpenAiStreamParser,
+        )
+    }
+
     async fn chat_with_tools(
         &self,
         messages: &[ChatMessage],
@@ -557,9 +638,11 @@ impl Provider for OpenRouterProvider {
             max_tokens: self.max_tokens_override,
             tool_choice: native_tools.as_ref().map(|_| "auto".to_string()),
             tools: native_tools,
+            stream: None,
+            stream_options: None,
         };
 
-        let response = self
+        let req = self
             .http_client()
             .post("https://openrouter.ai/api/v1/chat/completions")
             .header("Authorization", format!("Bearer {credential}"))
@@ -568,9 +651,9 @@ impl Provider for OpenRouterProvider {
                 "https://github.com/theonlyhennygod/zeroclaw",
             )
             .header("X-Title", "ZeroClaw")
-            .json(&native_request)
-            .send()
-            .await?;
+            .json(&native_request);
+        let response =
+            super::quota_scheduler::send("openrouter", Some(credential.as_str()), req).await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenRouter", response).await);
@@ -580,6 +663,10 @@ impl Provider for OpenRouterProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
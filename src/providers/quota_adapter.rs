@@ -1,8 +1,9 @@
 //! Universal quota extractor for different provider rate limit mechanisms.
 //!
 //! Each provider has its own rate limit header format:
-//! - OpenAI: `X-RateLimit-Remaining`, `X-RateLimit-Reset`
-//! - Anthropic: `anthropic-ratelimit-requests-remaining`, `retry-after`
+//! - OpenAI: `X-RateLimit-Remaining`, `X-RateLimit-Reset`, plus the
+//!   `x-ratelimit-{remaining,limit,reset}-{requests,tokens}` family
+//! - Anthropic: `anthropic-ratelimit-{requests,tokens}-{remaining,limit,reset}`, `retry-after`
 //! - Gemini: `X-Goog-RateLimit-Requests-Remaining`
 //! - Custom/generic: May not return headers at all
 //!
@@ -13,6 +14,62 @@ use super::quota_types::QuotaMetadata;
 use chrono::{DateTime, Utc};
 use reqwest::header::HeaderMap;
 use std::collections::HashMap;
+use std::time::Duration;
+
+fn header_str<'a>(headers: &'a HeaderMap, names: &[&str]) -> Option<&'a str> {
+    names
+        .iter()
+        .find_map(|name| headers.get(*name))
+        .and_then(|v| v.to_str().ok())
+}
+
+fn header_u64(headers: &HeaderMap, names: &[&str]) -> Option<u64> {
+    header_str(headers, names).and_then(|s| s.trim().parse::<u64>().ok())
+}
+
+/// Parse OpenAI-style reset durations such as `"1s"`, `"6m0s"`, `"20ms"` or `"1h2m3.5s"`.
+pub(crate) fn parse_reset_duration(raw: &str) -> Option<Duration> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    let mut total_ms = 0.0_f64;
+    let mut number = String::new();
+    let mut chars = raw.chars().peekable();
+    let mut parsed_any = false;
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let value: f64 = number.parse().ok()?;
+        number.clear();
+        let unit_ms = match c {
+            'h' => 3_600_000.0,
+            'm' if chars.peek() == Some(&'s') => {
+                chars.next();
+                1.0
+            }
+            'm' => 60_000.0,
+            's' => 1_000.0,
+            _ => return None,
+        };
+        total_ms += value * unit_ms;
+        parsed_any = true;
+    }
+    if !number.is_empty() {
+        // Bare number: seconds.
+        total_ms += number.parse::<f64>().ok()? * 1_000.0;
+        parsed_any = true;
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    parsed_any.then(|| Duration::from_millis(total_ms.max(0.0).round() as u64))
+}
+
+fn reset_after(raw: Option<&str>) -> Option<DateTime<Utc>> {
+    let duration = parse_reset_duration(raw?)?;
+    Some(Utc::now() + chrono::Duration::from_std(duration).ok()?)
+}
 
 /// Trait for extracting quota metadata from provider responses.
 pub trait QuotaExtractor: Send + Sync {
@@ -28,40 +85,40 @@ pub struct OpenAIQuotaExtractor;
 
 impl QuotaExtractor for OpenAIQuotaExtractor {
     fn extract_from_headers(&self, headers: &HeaderMap) -> Option<QuotaMetadata> {
-        let rate_limit_remaining = headers
-            .get("X-RateLimit-Remaining")
-            .or_else(|| headers.get("x-ratelimit-remaining"))
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok());
+        let rate_limit_remaining = header_u64(
+            headers,
+            &["x-ratelimit-remaining-requests", "x-ratelimit-remaining"],
+        );
 
-        let rate_limit_total = headers
-            .get("X-RateLimit-Limit")
-            .or_else(|| headers.get("x-ratelimit-limit"))
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok());
+        let rate_limit_total = header_u64(
+            headers,
+            &["x-ratelimit-limit-requests", "x-ratelimit-limit"],
+        );
 
-        let rate_limit_reset_at = headers
-            .get("X-RateLimit-Reset")
-            .or_else(|| headers.get("x-ratelimit-reset"))
-            .and_then(|v| v.to_str().ok())
+        let rate_limit_reset_at = header_str(headers, &["x-ratelimit-reset"])
             .and_then(|s| s.parse::<i64>().ok())
-            .and_then(|ts| DateTime::from_timestamp(ts, 0));
+            .and_then(|ts| DateTime::from_timestamp(ts, 0))
+            .or_else(|| reset_after(header_str(headers, &["x-ratelimit-reset-requests"])));
 
-        let retry_after_seconds = headers
-            .get("Retry-After")
-            .or_else(|| headers.get("retry-after"))
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok());
+        let retry_after_seconds = header_u64(headers, &["retry-after"]);
+
+        let tokens_remaining = header_u64(headers, &["x-ratelimit-remaining-tokens"]);
+        let tokens_total = header_u64(headers, &["x-ratelimit-limit-tokens"]);
+        let tokens_reset_at = reset_after(header_str(headers, &["x-ratelimit-reset-tokens"]));
 
         if rate_limit_remaining.is_some()
             || rate_limit_reset_at.is_some()
             || retry_after_seconds.is_some()
+            || tokens_remaining.is_some()
         {
             Some(QuotaMetadata {
                 rate_limit_remaining,
                 rate_limit_reset_at,
                 retry_after_seconds,
                 rate_limit_total,
+                tokens_remaining,
+                tokens_total,
+                tokens_reset_at,
             })
         } else {
             None
@@ -87,6 +144,7 @@ impl QuotaExtractor for OpenAIQuotaExtractor {
                 rate_limit_reset_at: None,
                 retry_after_seconds,
                 rate_limit_total: None,
+                ..Default::default()
             })
         } else {
             None
@@ -99,40 +157,34 @@ pub struct AnthropicQuotaExtractor;
 
 impl QuotaExtractor for AnthropicQuotaExtractor {
     fn extract_from_headers(&self, headers: &HeaderMap) -> Option<QuotaMetadata> {
-        let rate_limit_remaining = headers
-            .get("anthropic-ratelimit-requests-remaining")
-            .or_else(|| headers.get("Anthropic-RateLimit-Requests-Remaining"))
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok());
-
-        let rate_limit_total = headers
-            .get("anthropic-ratelimit-requests-limit")
-            .or_else(|| headers.get("Anthropic-RateLimit-Requests-Limit"))
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok());
+        let rfc3339 = |name: &str| {
+            header_str(headers, &[name])
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+        };
 
-        let rate_limit_reset_at = headers
-            .get("anthropic-ratelimit-requests-reset")
-            .or_else(|| headers.get("Anthropic-RateLimit-Requests-Reset"))
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-            .map(|dt| dt.with_timezone(&Utc));
+        let rate_limit_remaining = header_u64(headers, &["anthropic-ratelimit-requests-remaining"]);
+        let rate_limit_total = header_u64(headers, &["anthropic-ratelimit-requests-limit"]);
+        let rate_limit_reset_at = rfc3339("anthropic-ratelimit-requests-reset");
+        let retry_after_seconds = header_u64(headers, &["retry-after"]);
 
-        let retry_after_seconds = headers
-            .get("retry-after")
-            .or_else(|| headers.get("Retry-After"))
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok());
+        let tokens_remaining = header_u64(headers, &["anthropic-ratelimit-tokens-remaining"]);
+        let tokens_total = header_u64(headers, &["anthropic-ratelimit-tokens-limit"]);
+        let tokens_reset_at = rfc3339("anthropic-ratelimit-tokens-reset");
 
         if rate_limit_remaining.is_some()
             || rate_limit_reset_at.is_some()
             || retry_after_seconds.is_some()
+            || tokens_remaining.is_some()
         {
             Some(QuotaMetadata {
                 rate_limit_remaining,
                 rate_limit_reset_at,
                 retry_after_seconds,
                 rate_limit_total,
+                tokens_remaining,
+                tokens_total,
+                tokens_reset_at,
             })
         } else {
             None
@@ -149,6 +201,7 @@ impl QuotaExtractor for AnthropicQuotaExtractor {
                 rate_limit_reset_at: None,
                 retry_after_seconds: Some(60), // Default 60s backoff
                 rate_limit_total: None,
+                ..Default::default()
             })
         } else {
             None
@@ -185,6 +238,7 @@ impl QuotaExtractor for GeminiQuotaExtractor {
                 rate_limit_reset_at: None, // Gemini may not provide reset timestamp
                 retry_after_seconds,
                 rate_limit_total,
+                ..Default::default()
             })
         } else {
             None
@@ -201,6 +255,7 @@ impl QuotaExtractor for GeminiQuotaExtractor {
                 rate_limit_reset_at: None,
                 retry_after_seconds: Some(3600), // 1 hour default for quota exhaustion
                 rate_limit_total: None,
+                ..Default::default()
             })
         } else {
             None
@@ -236,6 +291,7 @@ impl QuotaExtractor for QwenQuotaExtractor {
                 rate_limit_reset_at: None,
                 retry_after_seconds: Some(3600), // 1 hour default backoff
                 rate_limit_total: Some(1000),    // OAuth free tier limit
+                ..Default::default()
             })
         } else {
             None
@@ -383,6 +439,69 @@ mod tests {
         assert_eq!(quota.retry_after_seconds, Some(30));
     }
 
+    #[test]
+    fn test_openai_extractor_request_and_token_buckets() {
+        let extractor = OpenAIQuotaExtractor;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining-requests", "59".parse().unwrap());
+        headers.insert("x-ratelimit-limit-requests", "60".parse().unwrap());
+        headers.insert("x-ratelimit-reset-requests", "1s".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-tokens", "149984".parse().unwrap());
+        headers.insert("x-ratelimit-limit-tokens", "150000".parse().unwrap());
+        headers.insert("x-ratelimit-reset-tokens", "6m0s".parse().unwrap());
+
+        let quota = extractor.extract_from_headers(&headers).unwrap();
+        assert_eq!(quota.rate_limit_remaining, Some(59));
+        assert_eq!(quota.rate_limit_total, Some(60));
+        assert!(quota.rate_limit_reset_at.is_some());
+        assert_eq!(quota.tokens_remaining, Some(149_984));
+        assert_eq!(quota.tokens_total, Some(150_000));
+        let token_reset = quota.tokens_reset_at.unwrap() - Utc::now();
+        assert!(token_reset.num_seconds() > 350 && token_reset.num_seconds() <= 360);
+    }
+
+    #[test]
+    fn test_anthropic_extractor_token_headers() {
+        let extractor = AnthropicQuotaExtractor;
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "anthropic-ratelimit-tokens-remaining",
+            "8000".parse().unwrap(),
+        );
+        headers.insert("anthropic-ratelimit-tokens-limit", "80000".parse().unwrap());
+        headers.insert(
+            "anthropic-ratelimit-tokens-reset",
+            "2030-01-01T00:00:00Z".parse().unwrap(),
+        );
+
+        let quota = extractor.extract_from_headers(&headers).unwrap();
+        assert_eq!(quota.tokens_remaining, Some(8000));
+        assert_eq!(quota.tokens_total, Some(80_000));
+        assert_eq!(
+            quota.tokens_reset_at,
+            DateTime::parse_from_rfc3339("2030-01-01T00:00:00Z")
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+        );
+    }
+
+    #[test]
+    fn parse_reset_duration_handles_openai_formats() {
+        assert_eq!(parse_reset_duration("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_reset_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(
+            parse_reset_duration("20ms"),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            parse_reset_duration("1h2m3.5s"),
+            Some(Duration::from_millis(3_723_500))
+        );
+        assert_eq!(parse_reset_duration("12"), Some(Duration::from_secs(12)));
+        assert_eq!(parse_reset_duration(""), None);
+        assert_eq!(parse_reset_duration("soon"), None);
+    }
+
     #[test]
     fn test_gemini_extractor_headers() {
         let extractor = GeminiQuotaExtractor;
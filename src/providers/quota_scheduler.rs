@@ -0,0 +1,410 @@
+//! Proactive request pacing driven by provider rate-limit headers.
+//!
+//! Every provider response is fed through [`UniversalQuotaExtractor`] and the
+//! resulting remaining/reset counters are kept in a process-wide bucket keyed by
+//! provider and (hashed) credential. Before a request goes out, the scheduler
+//! reserves a send slot from that bucket:
+//!
+//! - when the request budget is exhausted, the caller waits for the window reset;
+//! - once the request budget runs low, requests are spread evenly over the time
+//!   left in the window, in arrival order, so concurrent channel sessions, cron
+//!   jobs and subagents sharing a key take turns instead of racing each other
+//!   into 429s; with ample budget they go out immediately;
+//! - a `retry-after` from the server blocks the bucket until it elapses;
+//! - when the token budget is nearly spent, requests wait for the token reset.
+//!
+//! Waits are capped by `reliability.rate_limit_max_wait_secs`; after that the
+//! request is sent anyway and the normal retry/fallback chain takes over.
+
+use super::quota_adapter::UniversalQuotaExtractor;
+use super::quota_types::QuotaMetadata;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use reqwest::header::HeaderMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// Default cap on how long a single request waits for budget.
+const DEFAULT_MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// Request budgets below this fraction of the limit are paced over the rest of
+/// the window; above it requests are sent immediately.
+const REQUEST_LOW_WATERMARK_DIVISOR: u64 = 10;
+
+/// Token budgets below this fraction of the limit wait for the reset, since
+/// the cost of the next request is not known up front.
+const TOKEN_LOW_WATERMARK_DIVISOR: u64 = 20;
+
+static SCHEDULER: LazyLock<QuotaScheduler> = LazyLock::new(QuotaScheduler::new);
+
+/// Enable or disable pacing and set the per-request wait cap.
+pub fn configure(enabled: bool, max_wait: Duration) {
+    SCHEDULER.configure(enabled, max_wait);
+}
+
+/// Send `request` once the provider/credential bucket has budget, then record
+/// the rate-limit headers of the response (including 429 responses).
+pub async fn send(
+    provider: &str,
+    credential: Option<&str>,
+    request: reqwest::RequestBuilder,
+) -> reqwest::Result<reqwest::Response> {
+    let key = bucket_key(provider, credential);
+    let wait = SCHEDULER.reserve(&key, Instant::now());
+    if !wait.is_zero() {
+        tracing::debug!(
+            provider,
+            wait_ms = u64::try_from(wait.as_millis()).unwrap_or(u64::MAX),
+            "Pacing provider request to stay within rate limits"
+        );
+        tokio::time::sleep(wait).await;
+    }
+
+    let response = request.send().await?;
+    SCHEDULER.observe_headers(&key, provider, response.headers());
+    Ok(response)
+}
+
+/// Wait the next request for `provider`/`credential` would incur.
+#[cfg(test)]
+pub(crate) fn pending_wait(provider: &str, credential: Option<&str>) -> Duration {
+    SCHEDULER.reserve(&bucket_key(provider, credential), Instant::now())
+}
+
+/// Bucket key for a provider and credential. The credential is hashed so that
+/// keys never sit in memory longer than the provider client itself holds them.
+fn bucket_key(provider: &str, credential: Option<&str>) -> String {
+    let provider = provider.trim().to_ascii_lowercase();
+    match credential.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(credential) => {
+            let mut hasher = DefaultHasher::new();
+            credential.hash(&mut hasher);
+            format!("{provider}:{:016x}", hasher.finish())
+        }
+        None => provider,
+    }
+}
+
+fn instant_at(at: DateTime<Utc>, now: Instant) -> Instant {
+    now + (at - Utc::now()).to_std().unwrap_or_default()
+}
+
+/// One rate-limit window (requests or tokens) as last reported by the server.
+#[derive(Debug, Default, Clone)]
+struct Window {
+    remaining: Option<u64>,
+    limit: Option<u64>,
+    reset_at: Option<Instant>,
+    next_slot: Option<Instant>,
+}
+
+impl Window {
+    fn update(
+        &mut self,
+        remaining: Option<u64>,
+        limit: Option<u64>,
+        reset_at: Option<DateTime<Utc>>,
+        now: Instant,
+    ) {
+        let Some(remaining) = remaining else {
+            return;
+        };
+        self.remaining = Some(remaining);
+        if limit.is_some() {
+            self.limit = limit;
+        }
+        self.reset_at = reset_at.map(|at| instant_at(at, now));
+    }
+
+    /// Start a fresh window once the reset time has passed.
+    fn roll(&mut self, at: Instant) {
+        if self.reset_at.is_some_and(|reset| reset <= at) {
+            self.remaining = self.limit;
+            self.reset_at = None;
+            self.next_slot = None;
+        }
+    }
+
+    /// Remaining budget below which this window holds requests back.
+    fn low_watermark(&self, divisor: u64) -> u64 {
+        self.limit.map_or(0, |limit| limit / divisor)
+    }
+
+    /// Reserve one request from this window, returning the earliest send time.
+    fn reserve_request(&mut self, not_before: Instant) -> Instant {
+        self.roll(not_before);
+        let Some(remaining) = self.remaining else {
+            return not_before;
+        };
+        let Some(reset) = self.reset_at else {
+            self.remaining = Some(remaining.saturating_sub(1));
+            return not_before;
+        };
+        if remaining == 0 {
+            return reset.max(not_before);
+        }
+
+        self.remaining = Some(remaining - 1);
+        if remaining >= self.low_watermark(REQUEST_LOW_WATERMARK_DIVISOR) {
+            return not_before;
+        }
+        let slot = self
+            .next_slot
+            .map_or(not_before, |next| next.max(not_before));
+        let spacing =
+            reset.saturating_duration_since(slot) / u32::try_from(remaining).unwrap_or(u32::MAX);
+        self.next_slot = Some(slot + spacing);
+        slot
+    }
+
+    /// Earliest send time given the token budget; token cost is unknown until
+    /// the response arrives, so only a nearly empty budget holds requests back.
+    fn token_gate(&mut self, not_before: Instant) -> Instant {
+        self.roll(not_before);
+        let (Some(remaining), Some(reset)) = (self.remaining, self.reset_at) else {
+            return not_before;
+        };
+        if remaining == 0 || remaining < self.low_watermark(TOKEN_LOW_WATERMARK_DIVISOR) {
+            reset.max(not_before)
+        } else {
+            not_before
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Bucket {
+    requests: Window,
+    tokens: Window,
+    blocked_until: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Settings {
+    enabled: bool,
+    max_wait: Duration,
+}
+
+/// Shared per-provider/per-key request budget fed by rate-limit headers.
+pub struct QuotaScheduler {
+    settings: RwLock<Settings>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    extractor: UniversalQuotaExtractor,
+}
+
+impl QuotaScheduler {
+    pub fn new() -> Self {
+        Self {
+            settings: RwLock::new(Settings {
+                enabled: true,
+                max_wait: DEFAULT_MAX_WAIT,
+            }),
+            buckets: Mutex::new(HashMap::new()),
+            extractor: UniversalQuotaExtractor::new(),
+        }
+    }
+
+    pub fn configure(&self, enabled: bool, max_wait: Duration) {
+        *self.settings.write() = Settings { enabled, max_wait };
+    }
+
+    /// Reserve a send slot for `key` and return how long to wait before sending.
+    pub fn reserve(&self, key: &str, now: Instant) -> Duration {
+        let settings = *self.settings.read();
+        if !settings.enabled {
+            return Duration::ZERO;
+        }
+
+        let mut buckets = self.buckets.lock();
+        let Some(bucket) = buckets.get_mut(key) else {
+            return Duration::ZERO;
+        };
+
+        let mut send_at = now;
+        if let Some(blocked_until) = bucket.blocked_until {
+            if blocked_until > now {
+                send_at = blocked_until;
+            } else {
+                bucket.blocked_until = None;
+            }
+        }
+        send_at = bucket.tokens.token_gate(send_at);
+        send_at = bucket.requests.reserve_request(send_at);
+
+        send_at
+            .saturating_duration_since(now)
+            .min(settings.max_wait)
+    }
+
+    /// Record rate-limit headers from a provider response.
+    pub fn observe_headers(&self, key: &str, provider: &str, headers: &HeaderMap) {
+        if !self.settings.read().enabled {
+            return;
+        }
+        let provider = provider.trim().to_ascii_lowercase();
+        if let Some(quota) = self.extractor.extract(&provider, headers, None) {
+            self.observe(key, &quota, Instant::now());
+        }
+    }
+
+    /// Record already-extracted quota metadata for `key`.
+    pub fn observe(&self, key: &str, quota: &QuotaMetadata, now: Instant) {
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets.entry(key.to_string()).or_default();
+
+        bucket.requests.update(
+            quota.rate_limit_remaining,
+            quota.rate_limit_total,
+            quota.rate_limit_reset_at,
+            now,
+        );
+        bucket.tokens.update(
+            quota.tokens_remaining,
+            quota.tokens_total,
+            quota.tokens_reset_at,
+            now,
+        );
+        if let Some(seconds) = quota.retry_after_seconds {
+            let until = now + Duration::from_secs(seconds);
+            bucket.blocked_until = Some(bucket.blocked_until.map_or(until, |b| b.max(until)));
+        }
+    }
+}
+
+impl Default for QuotaScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota(remaining: u64, limit: u64, reset_in: Duration) -> QuotaMetadata {
+        QuotaMetadata {
+            rate_limit_remaining: Some(remaining),
+            rate_limit_total: Some(limit),
+            rate_limit_reset_at: Some(Utc::now() + chrono::Duration::from_std(reset_in).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unknown_bucket_sends_immediately() {
+        let scheduler = QuotaScheduler::new();
+        assert_eq!(scheduler.reserve("openai", Instant::now()), Duration::ZERO);
+    }
+
+    #[test]
+    fn exhausted_budget_waits_for_reset() {
+        let scheduler = QuotaScheduler::new();
+        let now = Instant::now();
+        scheduler.observe("openai", &quota(0, 60, Duration::from_secs(10)), now);
+
+        let wait = scheduler.reserve("openai", now);
+        assert!(wait > Duration::from_secs(8) && wait <= Duration::from_secs(10));
+    }
+
+    #[test]
+    fn ample_request_budget_sends_immediately() {
+        let scheduler = QuotaScheduler::new();
+        let now = Instant::now();
+        scheduler.observe("openai", &quota(45, 50, Duration::from_secs(60)), now);
+
+        for _ in 0..10 {
+            assert_eq!(scheduler.reserve("openai", now), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn concurrent_callers_are_spread_across_the_window() {
+        let scheduler = QuotaScheduler::new();
+        let now = Instant::now();
+        scheduler.observe("anthropic", &quota(4, 50, Duration::from_secs(8)), now);
+
+        let waits: Vec<Duration> = (0..4)
+            .map(|_| scheduler.reserve("anthropic", now))
+            .collect();
+        assert_eq!(waits[0], Duration::ZERO);
+        for pair in waits.windows(2) {
+            assert!(
+                pair[1] > pair[0],
+                "slots must be handed out in order: {waits:?}"
+            );
+        }
+        assert!(waits[3] < Duration::from_secs(8));
+
+        // Budget is now spent: the next caller waits for the reset.
+        assert!(scheduler.reserve("anthropic", now) >= Duration::from_secs(7));
+    }
+
+    #[test]
+    fn retry_after_blocks_bucket() {
+        let scheduler = QuotaScheduler::new();
+        let now = Instant::now();
+        let blocked = QuotaMetadata {
+            retry_after_seconds: Some(5),
+            ..Default::default()
+        };
+        scheduler.observe("gemini", &blocked, now);
+
+        assert_eq!(scheduler.reserve("gemini", now), Duration::from_secs(5));
+        assert_eq!(
+            scheduler.reserve("gemini", now + Duration::from_secs(6)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn low_token_budget_waits_for_token_reset() {
+        let scheduler = QuotaScheduler::new();
+        let now = Instant::now();
+        let low_tokens = QuotaMetadata {
+            tokens_remaining: Some(100),
+            tokens_total: Some(100_000),
+            tokens_reset_at: Some(Utc::now() + chrono::Duration::seconds(20)),
+            ..Default::default()
+        };
+        scheduler.observe("openai", &low_tokens, now);
+
+        assert!(scheduler.reserve("openai", now) > Duration::from_secs(18));
+    }
+
+    #[test]
+    fn window_rolls_over_after_reset() {
+        let scheduler = QuotaScheduler::new();
+        let now = Instant::now();
+        scheduler.observe("openai", &quota(0, 60, Duration::from_secs(1)), now);
+
+        let later = now + Duration::from_secs(2);
+        assert_eq!(scheduler.reserve("openai", later), Duration::ZERO);
+    }
+
+    #[test]
+    fn waits_are_capped_and_pacing_can_be_disabled() {
+        let scheduler = QuotaScheduler::new();
+        let now = Instant::now();
+        scheduler.observe("openai", &quota(0, 60, Duration::from_secs(600)), now);
+
+        scheduler.configure(true, Duration::from_secs(30));
+        assert_eq!(scheduler.reserve("openai", now), Duration::from_secs(30));
+
+        scheduler.configure(false, Duration::from_secs(30));
+        assert_eq!(scheduler.reserve("openai", now), Duration::ZERO);
+    }
+
+    #[test]
+    fn bucket_key_separates_credentials_without_storing_them() {
+        let a = bucket_key("OpenAI", Some("sk-first"));
+        let b = bucket_key("openai", Some("sk-second"));
+        assert_ne!(a, b);
+        assert!(a.starts_with("openai:"));
+        assert!(!a.contains("sk-first"));
+        assert_eq!(bucket_key("openai", None), "openai");
+        assert_eq!(bucket_key("openai", Some("  ")), "openai");
+    }
+}
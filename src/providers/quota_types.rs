@@ -0,0 +1,25 @@
+//! Shared quota/rate-limit data types used by the quota adapter and scheduler.
+
+use chrono::{DateTime, Utc};
+
+/// Rate-limit state reported by a provider, normalized across header formats.
+///
+/// Request counters come from `*-requests-*` (or un-suffixed) headers; token
+/// counters from `*-tokens-*` headers where the provider sends them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuotaMetadata {
+    /// Requests left in the current window.
+    pub rate_limit_remaining: Option<u64>,
+    /// When the request window resets.
+    pub rate_limit_reset_at: Option<DateTime<Utc>>,
+    /// Server-requested delay before the next request.
+    pub retry_after_seconds: Option<u64>,
+    /// Request limit for the window.
+    pub rate_limit_total: Option<u64>,
+    /// Tokens left in the current window.
+    pub tokens_remaining: Option<u64>,
+    /// Token limit for the window.
+    pub tokens_total: Option<u64>,
+    /// When the token window resets.
+    pub tokens_reset_at: Option<DateTime<Utc>>,
+}
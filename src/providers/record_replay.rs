@@ -0,0 +1,1369 @@
+//! Record/replay provider wrapper for deterministic agent tests.
+//!
+//! [`RecordReplayProvider`] decorates any [`Provider`]. In record mode it
+//! forwards every `chat`, `chat_with_tools`, `chat_with_history`,
+//! `stream_chat_with_history` and `stream_chat` call to the wrapped provider
+//! and appends the exchange to a JSON cassette on disk. Streams are stored
+//! event by event (text, tool-call and thinking fragments, usage), so a replay
+//! yields what the live provider yielded. In replay mode it serves responses
+//! from that cassette, matched by a hash of the normalized request, and never
+//! touches the network.
+//!
+//! Normalization masks values that legitimately change between runs (dates,
+//! clock times, UUIDs) so that prompts embedding the current time still match.
+//! When a replayed request has no recorded counterpart, the error carries a
+//! line diff against the closest recorded request of the same kind.
+//!
+//! The factory wraps the top-level provider automatically when
+//! `ZEROCLAW_PROVIDER_CASSETTE` points at a cassette file; set
+//! `ZEROCLAW_PROVIDER_CASSETTE_MODE=record` to capture a session.
+
+use super::traits::{
+    ChatMessage, ChatRequest, ChatResponse, ProviderCapabilities, StreamChunk, StreamError,
+    StreamEvent, StreamOptions, StreamResult, ThinkingDelta, TokenUsage, ToolCallDelta,
+};
+use super::Provider;
+use async_trait::async_trait;
+use futures_util::{stream, StreamExt};
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+/// Environment variable holding the cassette path used by the provider factory.
+pub const CASSETTE_PATH_ENV: &str = "ZEROCLAW_PROVIDER_CASSETTE";
+/// Environment variable selecting `record` or `replay` (default) mode.
+pub const CASSETTE_MODE_ENV: &str = "ZEROCLAW_PROVIDER_CASSETTE_MODE";
+
+const CASSETTE_VERSION: u32 = 2;
+/// Upper bound on LCS table cells before the diff falls back to a
+/// first-divergence report.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+const MAX_DIFF_LINES: usize = 80;
+
+static DATETIME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(:\d{2}(\.\d+)?)?(Z|[+-]\d{2}:?\d{2})?").unwrap()
+});
+static DATE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap());
+static TIME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\d{1,2}:\d{2}(:\d{2})?\b").unwrap());
+static UUID_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b").unwrap()
+});
+
+/// Whether the wrapper captures a live session or serves a recorded one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+impl CassetteMode {
+    fn parse(raw: &str) -> anyhow::Result<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "record" => Ok(Self::Record),
+            "" | "replay" => Ok(Self::Replay),
+            other => anyhow::bail!(
+                "Invalid {CASSETTE_MODE_ENV} value '{other}' (expected 'record' or 'replay')"
+            ),
+        }
+    }
+}
+
+/// Provider call that produced a recorded interaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractionKind {
+    ChatWithHistory,
+    Chat,
+    ChatWithTools,
+    StreamChatWithHistory,
+    StreamChat,
+}
+
+impl InteractionKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::ChatWithHistory => "chat_with_history",
+            Self::Chat => "chat",
+            Self::ChatWithTools => "chat_with_tools",
+            Self::StreamChatWithHistory => "stream_chat_with_history",
+            Self::StreamChat => "stream_chat",
+        }
+    }
+}
+
+/// Capabilities of the recorded provider, served verbatim during replay so the
+/// agent loop takes the same native-tool / streaming / structured-output
+/// branches.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordedCapabilities {
+    pub native_tool_calling: bool,
+    pub vision: bool,
+    pub streaming: bool,
+    #[serde(default)]
+    pub tool_call_streaming: bool,
+    #[serde(default)]
+    pub structured_output: bool,
+}
+
+/// A single streamed event (or stream error) as recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedEvent {
+    Text {
+        delta: String,
+    },
+    ToolCall {
+        index: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(default)]
+        arguments: String,
+    },
+    Thinking {
+        index: usize,
+        #[serde(default)]
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        redacted: Option<String>,
+    },
+    Usage {
+        usage: TokenUsage,
+    },
+    Done,
+    Error {
+        message: String,
+    },
+}
+
+impl RecordedEvent {
+    fn from_event(event: &StreamResult<StreamEvent>) -> Self {
+        match event {
+            Ok(StreamEvent::TextDelta(delta)) => Self::Text {
+                delta: delta.clone(),
+            },
+            Ok(StreamEvent::ToolCallDelta(delta)) => Self::ToolCall {
+                index: delta.index,
+                id: delta.id.clone(),
+                name: delta.name.clone(),
+                arguments: delta.arguments.clone(),
+            },
+            Ok(StreamEvent::ThinkingDelta(delta)) => Self::Thinking {
+                index: delta.index,
+                text: delta.text.clone(),
+                signature: delta.signature.clone(),
+                redacted: delta.redacted.clone(),
+            },
+            Ok(StreamEvent::Usage(usage)) => Self::Usage {
+                usage: usage.clone(),
+            },
+            Ok(StreamEvent::Done) => Self::Done,
+            Err(e) => Self::Error {
+                message: e.to_string(),
+            },
+        }
+    }
+
+    /// Events for a text chunk from `stream_chat_with_history`.
+    fn from_chunk(chunk: &StreamResult<StreamChunk>) -> Vec<Self> {
+        match chunk {
+            Ok(chunk) => {
+                let mut events = Vec::with_capacity(2);
+                if !chunk.delta.is_empty() {
+                    events.push(Self::Text {
+                        delta: chunk.delta.clone(),
+                    });
+                }
+                if chunk.is_final {
+                    events.push(Self::Done);
+                }
+                events
+            }
+            Err(e) => vec![Self::Error {
+                message: e.to_string(),
+            }],
+        }
+    }
+
+    fn into_event(self) -> StreamResult<StreamEvent> {
+        Ok(match self {
+            Self::Text { delta } => StreamEvent::TextDelta(delta),
+            Self::ToolCall {
+                index,
+                id,
+                name,
+                arguments,
+            } => StreamEvent::ToolCallDelta(ToolCallDelta {
+                index,
+                id,
+                name,
+                arguments,
+            }),
+            Self::Thinking {
+                index,
+                text,
+                signature,
+                redacted,
+            } => StreamEvent::ThinkingDelta(ThinkingDelta {
+                index,
+                text,
+                signature,
+                redacted,
+            }),
+            Self::Usage { usage } => StreamEvent::Usage(usage),
+            Self::Done => StreamEvent::Done,
+            Self::Error { message } => return Err(StreamError::Provider(message)),
+        })
+    }
+
+    /// The text chunk for this event; only text, completion and errors have one.
+    fn into_chunk(self) -> Option<StreamResult<StreamChunk>> {
+        match self {
+            Self::Text { delta } => Some(Ok(StreamChunk::delta(delta))),
+            Self::Done => Some(Ok(StreamChunk::final_chunk())),
+            Self::Error { message } => Some(Err(StreamError::Provider(message))),
+            Self::ToolCall { .. } | Self::Thinking { .. } | Self::Usage { .. } => None,
+        }
+    }
+}
+
+/// Recorded outcome of one provider call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedOutcome {
+    Text(String),
+    Response(ChatResponse),
+    Stream(Vec<RecordedEvent>),
+    Error(String),
+}
+
+/// One request/response exchange stored in a cassette.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub kind: InteractionKind,
+    pub request_hash: String,
+    pub request: serde_json::Value,
+    pub outcome: RecordedOutcome,
+}
+
+/// On-disk cassette format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cassette {
+    pub version: u32,
+    #[serde(default)]
+    pub capabilities: RecordedCapabilities,
+    #[serde(default)]
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    fn new(capabilities: RecordedCapabilities) -> Self {
+        Self {
+            version: CASSETTE_VERSION,
+            capabilities,
+            interactions: Vec::new(),
+        }
+    }
+
+    /// Load a cassette from disk.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read cassette {}: {e}", path.display()))?;
+        let cassette: Self = serde_json::from_str(&raw)
+            .map_err(|e| anyhow::anyhow!("Failed to parse cassette {}: {e}", path.display()))?;
+        if cassette.version != CASSETTE_VERSION {
+            anyhow::bail!(
+                "Unsupported cassette version {} in {} (expected {CASSETTE_VERSION})",
+                cassette.version,
+                path.display()
+            );
+        }
+        Ok(cassette)
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+struct RecordState {
+    cassette: Cassette,
+    path: PathBuf,
+}
+
+impl RecordState {
+    fn append(&mut self, interaction: Interaction) {
+        self.cassette.interactions.push(interaction);
+        if let Err(e) = self.cassette.save(&self.path) {
+            tracing::warn!(
+                cassette = %self.path.display(),
+                "Failed to persist provider cassette: {e}"
+            );
+        }
+    }
+}
+
+struct ReplayState {
+    cassette: Cassette,
+    path: PathBuf,
+    /// Unconsumed interaction indices keyed by (kind, request hash).
+    pending: HashMap<(InteractionKind, String), VecDeque<usize>>,
+    /// Number of requests served so far, per kind.
+    served: HashMap<InteractionKind, usize>,
+}
+
+impl ReplayState {
+    fn new(cassette: Cassette, path: PathBuf) -> Self {
+        let mut pending: HashMap<(InteractionKind, String), VecDeque<usize>> = HashMap::new();
+        for (index, interaction) in cassette.interactions.iter().enumerate() {
+            pending
+                .entry((interaction.kind, interaction.request_hash.clone()))
+                .or_default()
+                .push_back(index);
+        }
+        Self {
+            cassette,
+            path,
+            pending,
+            served: HashMap::new(),
+        }
+    }
+
+    fn take(
+        &mut self,
+        kind: InteractionKind,
+        hash: &str,
+        request: &serde_json::Value,
+    ) -> anyhow::Result<RecordedOutcome> {
+        let ordinal = self.served.get(&kind).copied().unwrap_or(0);
+        let matched = self
+            .pending
+            .get_mut(&(kind, hash.to_string()))
+            .and_then(VecDeque::pop_front);
+
+        let Some(index) = matched else {
+            return Err(anyhow::anyhow!(self.mismatch_report(kind, ordinal, request)));
+        };
+        *self.served.entry(kind).or_insert(0) += 1;
+        Ok(self.cassette.interactions[index].outcome.clone())
+    }
+
+    /// Describe why `request` has no recorded counterpart, diffing it against
+    /// the recorded request of the same kind at the same call position.
+    fn mismatch_report(
+        &self,
+        kind: InteractionKind,
+        ordinal: usize,
+        request: &serde_json::Value,
+    ) -> String {
+        let same_kind: Vec<&Interaction> = self
+            .cassette
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.kind == kind)
+            .collect();
+
+        let mut report = format!(
+            "Cassette mismatch: no recorded {} request matches call #{} (cassette: {})",
+            kind.as_str(),
+            ordinal + 1,
+            self.path.display()
+        );
+
+        let Some(expected) = same_kind.get(ordinal).or_else(|| same_kind.last()) else {
+            let _ = write!(
+                report,
+                "\nThe cassette contains no {} interactions; re-record with {CASSETTE_MODE_ENV}=record.",
+                kind.as_str()
+            );
+            return report;
+        };
+
+        if same_kind.len() <= ordinal
+            && same_kind
+                .iter()
+                .any(|interaction| interaction.request == *request)
+        {
+            let _ = write!(
+                report,
+                "\nThe request was recorded, but every recorded copy has already been replayed ({} {} interactions in cassette).",
+                same_kind.len(),
+                kind.as_str()
+            );
+            return report;
+        }
+
+        report.push_str("\n--- recorded\n+++ requested\n");
+        report.push_str(&line_diff(
+            &render_request(&expected.request),
+            &render_request(request),
+        ));
+        report
+    }
+}
+
+enum Backend {
+    Record {
+        inner: Box<dyn Provider>,
+        state: Arc<Mutex<RecordState>>,
+    },
+    Replay {
+        state: Arc<Mutex<ReplayState>>,
+    },
+}
+
+/// Provider decorator that records exchanges to, or replays them from, a
+/// cassette file.
+pub struct RecordReplayProvider {
+    backend: Backend,
+    capabilities: RecordedCapabilities,
+}
+
+impl RecordReplayProvider {
+    /// Wrap `inner` and record every exchange to `path`, replacing any
+    /// existing cassette at that location.
+    pub fn record(inner: Box<dyn Provider>, path: impl Into<PathBuf>) -> Self {
+        let capabilities = RecordedCapabilities {
+            native_tool_calling: inner.supports_native_tools(),
+            vision: inner.supports_vision(),
+            streaming: inner.supports_streaming(),
+            tool_call_streaming: inner.supports_tool_call_streaming(),
+            structured_output: inner.supports_structured_output(),
+        };
+        let state = RecordState {
+            cassette: Cassette::new(capabilities.clone()),
+            path: path.into(),
+        };
+        Self {
+            backend: Backend::Record {
+                inner,
+                state: Arc::new(Mutex::new(state)),
+            },
+            capabilities,
+        }
+    }
+
+    /// Serve responses from the cassette at `path` without a live provider.
+    pub fn replay(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let cassette = Cassette::load(&path)?;
+        Ok(Self::from_cassette(cassette, path))
+    }
+
+    /// Serve responses from an in-memory cassette. `path` is only used in
+    /// mismatch reports.
+    pub fn from_cassette(cassette: Cassette, path: impl Into<PathBuf>) -> Self {
+        let capabilities = cassette.capabilities.clone();
+        Self {
+            backend: Backend::Replay {
+                state: Arc::new(Mutex::new(ReplayState::new(cassette, path.into()))),
+            },
+            capabilities,
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        match self.backend {
+            Backend::Record { .. } => CassetteMode::Record,
+            Backend::Replay { .. } => CassetteMode::Replay,
+        }
+    }
+
+    /// Snapshot of the interactions recorded (or loaded) so far.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        match &self.backend {
+            Backend::Record { state, .. } => state.lock().cassette.interactions.clone(),
+            Backend::Replay { state } => state.lock().cassette.interactions.clone(),
+        }
+    }
+
+    /// The live provider in record mode; `None` when replaying.
+    fn live(&self) -> Option<&dyn Provider> {
+        match &self.backend {
+            Backend::Record { inner, .. } => Some(inner.as_ref()),
+            Backend::Replay { .. } => None,
+        }
+    }
+
+    /// Persist a live result in record mode. No-op when replaying.
+    fn persist<T>(
+        &self,
+        kind: InteractionKind,
+        request: serde_json::Value,
+        result: &anyhow::Result<T>,
+        encode: impl FnOnce(&T) -> RecordedOutcome,
+    ) {
+        if let Backend::Record { state, .. } = &self.backend {
+            let outcome = match result {
+                Ok(value) => encode(value),
+                Err(e) => RecordedOutcome::Error(e.to_string()),
+            };
+            state.lock().append(Interaction {
+                kind,
+                request_hash: hash_request(&request),
+                request,
+                outcome,
+            });
+        }
+    }
+
+    /// Record `live` event by event and append it to the cassette once the
+    /// stream ends. No-op when replaying.
+    fn record_stream<T: Send + 'static>(
+        &self,
+        kind: InteractionKind,
+        request: serde_json::Value,
+        live: stream::BoxStream<'static, StreamResult<T>>,
+        encode: fn(&StreamResult<T>) -> Vec<RecordedEvent>,
+    ) -> stream::BoxStream<'static, StreamResult<T>> {
+        let Backend::Record { state, .. } = &self.backend else {
+            return live;
+        };
+        let events: Arc<Mutex<Vec<RecordedEvent>>> = Arc::new(Mutex::new(Vec::new()));
+        let captured = Arc::clone(&events);
+        let state = Arc::clone(state);
+
+        let live = live.inspect(move |item| captured.lock().extend(encode(item)));
+        let persist = stream::once(async move {
+            let recorded = std::mem::take(&mut *events.lock());
+            state.lock().append(Interaction {
+                kind,
+                request_hash: hash_request(&request),
+                request,
+                outcome: RecordedOutcome::Stream(recorded),
+            });
+        })
+        .filter_map(|()| async { None });
+
+        live.chain(persist).boxed()
+    }
+
+    /// Recorded events for a streamed `request` in replay mode. Failures are
+    /// returned as a single error event.
+    fn replayed_stream(
+        &self,
+        kind: InteractionKind,
+        request: &serde_json::Value,
+    ) -> Vec<RecordedEvent> {
+        match self.replayed(kind, request) {
+            Ok(RecordedOutcome::Stream(events)) => events,
+            Ok(RecordedOutcome::Error(message)) => vec![RecordedEvent::Error { message }],
+            Ok(other) => vec![RecordedEvent::Error {
+                message: format!("Cassette holds {other:?} where a stream was expected"),
+            }],
+            Err(e) => vec![RecordedEvent::Error {
+                message: e.to_string(),
+            }],
+        }
+    }
+
+    /// Serve the recorded outcome for `request` in replay mode.
+    fn replayed(
+        &self,
+        kind: InteractionKind,
+        request: &serde_json::Value,
+    ) -> anyhow::Result<RecordedOutcome> {
+        match &self.backend {
+            Backend::Replay { state } => state.lock().take(kind, &hash_request(request), request),
+            Backend::Record { .. } => anyhow::bail!("Provider cassette is not in replay mode"),
+        }
+    }
+}
+
+fn decode_text(outcome: RecordedOutcome) -> anyhow::Result<String> {
+    match outcome {
+        RecordedOutcome::Text(text) => Ok(text),
+        RecordedOutcome::Error(message) => Err(anyhow::anyhow!(message)),
+        other => anyhow::bail!("Cassette holds {other:?} where a text reply was expected"),
+    }
+}
+
+fn decode_response(outcome: RecordedOutcome) -> anyhow::Result<ChatResponse> {
+    match outcome {
+        RecordedOutcome::Response(response) => Ok(response),
+        RecordedOutcome::Error(message) => Err(anyhow::anyhow!(message)),
+        other => anyhow::bail!("Cassette holds {other:?} where a chat response was expected"),
+    }
+}
+
+#[async_trait]
+impl Provider for RecordReplayProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_tool_calling: self.capabilities.native_tool_calling,
+            vision: self.capabilities.vision,
+        }
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.capabilities.native_tool_calling
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.capabilities.vision
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.capabilities.streaming
+    }
+
+    fn supports_tool_call_streaming(&self) -> bool {
+        self.capabilities.tool_call_streaming
+    }
+
+    fn supports_structured_output(&self) -> bool {
+        self.capabilities.structured_output
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        match &self.backend {
+            Backend::Record { inner, .. } => inner.warmup().await,
+            Backend::Replay { .. } => Ok(()),
+        }
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_with_history(&messages, model, temperature).await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let request = normalize_request(model, temperature, messages, None);
+        let kind = InteractionKind::ChatWithHistory;
+        let Some(inner) = self.live() else {
+            return decode_text(self.replayed(kind, &request)?);
+        };
+        let result = inner.chat_with_history(messages, model, temperature).await;
+        self.persist(kind, request, &result, |text| {
+            RecordedOutcome::Text(text.clone())
+        });
+        result
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let normalized = normalize_chat_request(&request, model, temperature);
+        let kind = InteractionKind::Chat;
+        let Some(inner) = self.live() else {
+            return decode_response(self.replayed(kind, &normalized)?);
+        };
+        let result = inner.chat(request, model, temperature).await;
+        self.persist(kind, normalized, &result, |response| {
+            RecordedOutcome::Response(response.clone())
+        });
+        result
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let normalized = normalize_request(
+            model,
+            temperature,
+            messages,
+            Some(serde_json::Value::Array(tools.to_vec())),
+        );
+        let kind = InteractionKind::ChatWithTools;
+        let Some(inner) = self.live() else {
+            return decode_response(self.replayed(kind, &normalized)?);
+        };
+        let result = inner
+            .chat_with_tools(messages, tools, model, temperature)
+            .await;
+        self.persist(kind, normalized, &result, |response| {
+            RecordedOutcome::Response(response.clone())
+        });
+        result
+    }
+
+    fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.stream_chat_with_history(&messages, model, temperature, options)
+    }
+
+    fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        let request = normalize_request(model, temperature, messages, None);
+        let kind = InteractionKind::StreamChatWithHistory;
+        let Some(inner) = self.live() else {
+            let chunks: Vec<StreamResult<StreamChunk>> = self
+                .replayed_stream(kind, &request)
+                .into_iter()
+                .filter_map(RecordedEvent::into_chunk)
+                .map(|item| {
+                    if options.count_tokens {
+                        item.map(StreamChunk::with_token_estimate)
+                    } else {
+                        item
+                    }
+                })
+                .collect();
+            return stream::iter(chunks).boxed();
+        };
+        let live = inner.stream_chat_with_history(messages, model, temperature, options);
+        self.record_stream(kind, request, live, RecordedEvent::from_chunk)
+    }
+
+    fn stream_chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+        let normalized = normalize_chat_request(&request, model, temperature);
+        let kind = InteractionKind::StreamChat;
+        let Some(inner) = self.live() else {
+            let events: Vec<StreamResult<StreamEvent>> = self
+                .replayed_stream(kind, &normalized)
+                .into_iter()
+                .map(RecordedEvent::into_event)
+                .collect();
+            return stream::iter(events).boxed();
+        };
+        let live = inner.stream_chat(request, model, temperature, options);
+        self.record_stream(kind, normalized, live, |event| {
+            vec![RecordedEvent::from_event(event)]
+        })
+    }
+}
+
+/// Build the provider-agnostic request description used for hashing and diffs.
+fn normalize_request(
+    model: &str,
+    temperature: f64,
+    messages: &[ChatMessage],
+    tools: Option<serde_json::Value>,
+) -> serde_json::Value {
+    let messages: Vec<serde_json::Value> = messages
+        .iter()
+        .map(|message| {
+            serde_json::json!({
+                "role": message.role,
+                "content": normalize_text(&message.content),
+            })
+        })
+        .collect();
+
+    let mut request = serde_json::json!({
+        "model": model,
+        "temperature": format!("{temperature:.2}"),
+        "messages": messages,
+    });
+    if let Some(tools) = tools {
+        request["tools"] = tools;
+    }
+    request
+}
+
+/// [`normalize_request`] for a [`ChatRequest`], including its tools and
+/// response format.
+fn normalize_chat_request(
+    request: &ChatRequest<'_>,
+    model: &str,
+    temperature: f64,
+) -> serde_json::Value {
+    let tools = request
+        .tools
+        .map(|tools| serde_json::to_value(tools).unwrap_or_default());
+    let mut normalized = normalize_request(model, temperature, request.messages, tools);
+    if let Some(format) = request.response_format {
+        normalized["response_format"] = serde_json::to_value(format).unwrap_or_default();
+    }
+    normalized
+}
+
+/// Mask run-dependent values and canonicalize whitespace.
+pub fn normalize_text(text: &str) -> String {
+    let text = text.replace("\r\n", "\n");
+    let text = DATETIME_RE.replace_all(&text, "<datetime>");
+    let text = DATE_RE.replace_all(&text, "<date>");
+    let text = TIME_RE.replace_all(&text, "<time>");
+    let text = UUID_RE.replace_all(&text, "<uuid>");
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn hash_request(request: &serde_json::Value) -> String {
+    let canonical = serde_json::to_string(request).unwrap_or_default();
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+/// Render a normalized request as readable lines for diffing.
+fn render_request(request: &serde_json::Value) -> Vec<String> {
+    let mut lines = Vec::new();
+    let field = |key: &str| {
+        request
+            .get(key)
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("")
+            .to_string()
+    };
+    lines.push(format!("model: {}", field("model")));
+    lines.push(format!("temperature: {}", field("temperature")));
+
+    if let Some(tools) = request.get("tools").and_then(serde_json::Value::as_array) {
+        for tool in tools {
+            let name = tool
+                .get("name")
+                .or_else(|| tool.pointer("/function/name"))
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("?");
+            lines.push(format!("tool: {name}"));
+        }
+    }
+
+    let messages = request
+        .get("messages")
+        .and_then(serde_json::Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for (index, message) in messages.iter().enumerate() {
+        let role = message
+            .get("role")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("?");
+        lines.push(format!("[{index}] {role}:"));
+        let content = message
+            .get("content")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+        lines.extend(content.lines().map(|line| format!("    {line}")));
+    }
+    lines
+}
+
+/// Minimal LCS line diff. Only changed lines (and their neighbours) are shown.
+fn line_diff(expected: &[String], actual: &[String]) -> String {
+    if expected.len().saturating_mul(actual.len()) > MAX_DIFF_CELLS {
+        let position = expected
+            .iter()
+            .zip(actual)
+            .position(|(a, b)| a != b)
+            .unwrap_or_else(|| expected.len().min(actual.len()));
+        return format!(
+            "(request too large for a full diff; first divergence at line {})\n- {}\n+ {}\n",
+            position + 1,
+            expected.get(position).map_or("<end>", String::as_str),
+            actual.get(position).map_or("<end>", String::as_str)
+        );
+    }
+
+    let (n, m) = (expected.len(), actual.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<(char, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && expected[i] == actual[j] {
+            ops.push((' ', &expected[i]));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            ops.push(('+', &actual[j]));
+            j += 1;
+        } else {
+            ops.push(('-', &expected[i]));
+            i += 1;
+        }
+    }
+
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != ' ')
+        .map(|(index, _)| index)
+        .collect();
+    if changed.is_empty() {
+        return "(requests render identically; they differ only in tool schemas)\n".to_string();
+    }
+
+    let mut out = String::new();
+    let mut emitted = 0;
+    let mut last_shown: Option<usize> = None;
+    for (index, (op, line)) in ops.iter().enumerate() {
+        let near_change = changed.iter().any(|&c| index + 2 >= c && index <= c + 2);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_some_and(|last| index > last + 1) {
+            out.push_str("...\n");
+        }
+        if emitted == MAX_DIFF_LINES {
+            out.push_str("... (diff truncated)\n");
+            break;
+        }
+        out.push(*op);
+        out.push(' ');
+        out.push_str(line);
+        out.push('\n');
+        emitted += 1;
+        last_shown = Some(index);
+    }
+    out
+}
+
+/// Wrap `provider` according to the cassette environment variables, if set.
+pub fn wrap_from_env(provider: Box<dyn Provider>) -> anyhow::Result<Box<dyn Provider>> {
+    let Some(path) = std::env::var(CASSETTE_PATH_ENV)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(provider);
+    };
+    let mode = CassetteMode::parse(&std::env::var(CASSETTE_MODE_ENV).unwrap_or_default())?;
+    let path = PathBuf::from(shellexpand::tilde(&path).into_owned());
+
+    tracing::info!(cassette = %path.display(), ?mode, "Provider cassette enabled");
+    Ok(match mode {
+        CassetteMode::Record => Box::new(RecordReplayProvider::record(provider, path)),
+        CassetteMode::Replay => Box::new(RecordReplayProvider::replay(path)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::traits::{StreamAccumulator, ToolCall};
+    use crate::tools::ToolSpec;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct ScriptedProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                native_tool_calling: true,
+                vision: false,
+            }
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        fn supports_tool_call_streaming(&self) -> bool {
+            true
+        }
+
+        fn supports_structured_output(&self) -> bool {
+            true
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if message == "fail" {
+                anyhow::bail!("upstream exploded");
+            }
+            Ok(format!("echo: {message}"))
+        }
+
+        async fn chat(
+            &self,
+            request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(ChatResponse {
+                text: Some(format!("turn {n}")),
+                tool_calls: vec![ToolCall {
+                    id: format!("call_{n}"),
+                    name: "shell".into(),
+                    arguments: format!(r#"{{"messages":{}}}"#, request.messages.len()),
+                }],
+                usage: None,
+                reasoning_content: None,
+                thinking: Vec::new(),
+            })
+        }
+
+        fn stream_chat_with_history(
+            &self,
+            _messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+            _options: StreamOptions,
+        ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            stream::iter(vec![
+                Ok(StreamChunk::delta("Hel")),
+                Ok(StreamChunk::delta("lo")),
+                Ok(StreamChunk::final_chunk()),
+            ])
+            .boxed()
+        }
+
+        fn stream_chat(
+            &self,
+            _request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+            _options: StreamOptions,
+        ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            stream::iter(vec![
+                Ok(StreamEvent::ThinkingDelta(ThinkingDelta {
+                    index: 0,
+                    text: "Need a listing.".into(),
+                    signature: Some("sig".into()),
+                    redacted: None,
+                })),
+                Ok(StreamEvent::TextDelta("Checking.".into())),
+                Ok(StreamEvent::ToolCallDelta(ToolCallDelta {
+                    index: 0,
+                    id: Some("call_1".into()),
+                    name: Some("shell".into()),
+                    arguments: r#"{"comm"#.into(),
+                })),
+                Ok(StreamEvent::ToolCallDelta(ToolCallDelta {
+                    index: 0,
+                    id: None,
+                    name: None,
+                    arguments: r#"and":"ls"}"#.into(),
+                })),
+                Ok(StreamEvent::Usage(TokenUsage {
+                    input_tokens: Some(12),
+                    output_tokens: Some(7),
+                    ..TokenUsage::default()
+                })),
+                Ok(StreamEvent::Done),
+            ])
+            .boxed()
+        }
+    }
+
+    fn scripted() -> (Box<dyn Provider>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        (
+            Box::new(ScriptedProvider {
+                calls: Arc::clone(&calls),
+            }),
+            calls,
+        )
+    }
+
+    #[tokio::test]
+    async fn chat_round_trips_through_cassette_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let (inner, calls) = scripted();
+        let messages = vec![ChatMessage::system("sys"), ChatMessage::user("hi")];
+
+        let recorder = RecordReplayProvider::record(inner, &path);
+        let recorded = recorder
+            .chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    response_format: None,
+                },
+                "model-a",
+                0.7,
+            )
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let replayer = RecordReplayProvider::replay(&path).unwrap();
+        assert_eq!(replayer.mode(), CassetteMode::Replay);
+        assert!(replayer.supports_native_tools());
+        assert!(replayer.supports_streaming());
+        let replayed = replayer
+            .chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    response_format: None,
+                },
+                "model-a",
+                0.7,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(replayed.text, recorded.text);
+        assert_eq!(replayed.tool_calls.len(), 1);
+        assert_eq!(replayed.tool_calls[0].id, "call_0");
+    }
+
+    #[tokio::test]
+    async fn replay_ignores_timestamps_and_uuids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let (inner, _) = scripted();
+
+        let recorder = RecordReplayProvider::record(inner, &path);
+        recorder
+            .chat_with_system(
+                Some("Now: 2026-01-02 10:11:12 UTC, run 0b6b0f7e-5c1a-4f39-9d1e-3c5a1d2e4f60"),
+                "hi",
+                "model-a",
+                0.7,
+            )
+            .await
+            .unwrap();
+
+        let replayer = RecordReplayProvider::replay(&path).unwrap();
+        let reply = replayer
+            .chat_with_system(
+                Some("Now: 2026-03-04 23:59:01 UTC, run 6f1c3e2d-0000-4000-8000-123456789abc"),
+                "hi",
+                "model-a",
+                0.7,
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply, "echo: hi");
+    }
+
+    #[tokio::test]
+    async fn replay_reports_diff_when_prompt_drifts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let (inner, _) = scripted();
+
+        let recorder = RecordReplayProvider::record(inner, &path);
+        recorder
+            .chat_with_system(Some("You are terse."), "list files", "model-a", 0.7)
+            .await
+            .unwrap();
+
+        let replayer = RecordReplayProvider::replay(&path).unwrap();
+        let err = replayer
+            .chat_with_system(Some("You are verbose."), "list files", "model-a", 0.7)
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("Cassette mismatch"), "{err}");
+        assert!(
+            err.contains("- ") && err.contains("You are terse."),
+            "{err}"
+        );
+        assert!(err.contains("+     You are verbose."), "{err}");
+    }
+
+    #[tokio::test]
+    async fn replay_rejects_requests_beyond_recorded_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let (inner, _) = scripted();
+
+        let recorder = RecordReplayProvider::record(inner, &path);
+        recorder.simple_chat("hi", "model-a", 0.7).await.unwrap();
+
+        let replayer = RecordReplayProvider::replay(&path).unwrap();
+        replayer.simple_chat("hi", "model-a", 0.7).await.unwrap();
+        let err = replayer
+            .simple_chat("hi", "model-a", 0.7)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("already been replayed"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn replay_returns_recorded_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let (inner, _) = scripted();
+
+        let recorder = RecordReplayProvider::record(inner, &path);
+        assert!(recorder.simple_chat("fail", "model-a", 0.7).await.is_err());
+
+        let replayer = RecordReplayProvider::replay(&path).unwrap();
+        let err = replayer
+            .simple_chat("fail", "model-a", 0.7)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("upstream exploded"));
+    }
+
+    #[tokio::test]
+    async fn stream_round_trips_through_cassette_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let (inner, calls) = scripted();
+        let messages = vec![ChatMessage::user("hi")];
+
+        let recorder = RecordReplayProvider::record(inner, &path);
+        let live: Vec<_> = recorder
+            .stream_chat_with_history(&messages, "model-a", 0.7, StreamOptions::new(true))
+            .collect()
+            .await;
+        assert_eq!(live.len(), 3);
+        assert_eq!(recorder.interactions().len(), 1);
+
+        let replayer = RecordReplayProvider::replay(&path).unwrap();
+        let replayed: Vec<_> = replayer
+            .stream_chat_with_history(&messages, "model-a", 0.7, StreamOptions::new(true))
+            .collect()
+            .await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let text: String = replayed
+            .iter()
+            .map(|chunk| chunk.as_ref().unwrap().delta.as_str())
+            .collect();
+        assert_eq!(text, "Hello");
+        assert!(replayed.last().unwrap().as_ref().unwrap().is_final);
+    }
+
+    #[tokio::test]
+    async fn streamed_tool_call_round_trips_through_cassette_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let (inner, calls) = scripted();
+        let messages = vec![ChatMessage::user("list files")];
+        let tools = vec![ToolSpec {
+            name: "shell".into(),
+            description: "Run a shell command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: Some(&tools),
+            response_format: None,
+        };
+
+        let recorder = RecordReplayProvider::record(inner, &path);
+        assert!(recorder.supports_tool_call_streaming());
+        let live: Vec<_> = recorder
+            .stream_chat(request, "model-a", 0.7, StreamOptions::new(true))
+            .collect()
+            .await;
+        assert_eq!(live.len(), 6);
+
+        let replayer = RecordReplayProvider::replay(&path).unwrap();
+        assert!(replayer.supports_tool_call_streaming());
+        assert!(replayer.supports_structured_output());
+        let mut accumulator = StreamAccumulator::new();
+        for event in replayer
+            .stream_chat(request, "model-a", 0.7, StreamOptions::new(true))
+            .collect::<Vec<_>>()
+            .await
+        {
+            accumulator.push(&event.unwrap());
+        }
+        let response = accumulator.finish();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(response.text.as_deref(), Some("Checking."));
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].id, "call_1");
+        assert_eq!(response.tool_calls[0].name, "shell");
+        assert_eq!(response.tool_calls[0].arguments, r#"{"command":"ls"}"#);
+        assert_eq!(response.thinking.len(), 1);
+        assert_eq!(
+            response.usage.and_then(|usage| usage.output_tokens),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn line_diff_marks_changed_lines_only() {
+        let expected: Vec<String> = ["a", "b", "c", "d", "e", "f", "g"]
+            .iter()
+            .map(|s| (*s).to_string())
+            .collect();
+        let mut actual = expected.clone();
+        actual[3] = "D".into();
+
+        let diff = line_diff(&expected, &actual);
+        assert!(diff.contains("- d\n"));
+        assert!(diff.contains("+ D\n"));
+        assert!(!diff.contains("  a\n"));
+    }
+
+    #[test]
+    fn cassette_mode_parses_env_values() {
+        assert_eq!(CassetteMode::parse("record").unwrap(), CassetteMode::Record);
+        assert_eq!(
+            CassetteMode::parse(" Replay ").unwrap(),
+            CassetteMode::Replay
+        );
+        assert_eq!(CassetteMode::parse("").unwrap(), CassetteMode::Replay);
+        assert!(CassetteMode::parse("rewind").is_err());
+    }
+}
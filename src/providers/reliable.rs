@@ -1,12 +1,14 @@
 use super::traits::{
-    ChatMessage, ChatRequest, ChatResponse, StreamChunk, StreamOptions, StreamResult,
+    ChatMessage, ChatRequest, ChatResponse, StreamChunk, StreamEvent, StreamOptions, StreamResult,
 };
 use super::Provider;
 use async_trait::async_trait;
 use futures_util::{stream, StreamExt};
-use std::collections::{HashMap, HashSet};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Duration;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 // ── Error Classification ─────────────────────────────────────────────────
 // Errors are split into retryable (transient server/network failures) and
@@ -71,9 +73,11 @@ fn is_non_retryable(err: &anyhow::Error) -> bool {
             || msg_lower.contains("invalid"))
 }
 
-fn is_context_window_exceeded(err: &anyhow::Error) -> bool {
+/// Check if an error reports that the request did not fit the model's context window.
+pub fn is_context_window_exceeded(err: &anyhow::Error) -> bool {
     let lower = err.to_string().to_lowercase();
     let hints = [
+        "exceeds model context window",
         "exceeds the context window",
         "context window of this model",
         "maximum context length",
@@ -221,6 +225,65 @@ fn push_failure(
 // Loop invariant: `failures` accumulates every failed attempt so the final
 // error message gives operators a complete diagnostic trail.
 
+// ── Hedged Requests ───────────────────────────────────────────────────────
+// When hedging is enabled, a call that has not returned within the observed
+// latency percentile of the primary provider is duplicated to the next
+// provider in the chain. Whichever request succeeds first wins; the other
+// future is dropped, which cancels its in-flight HTTP request.
+
+/// Number of recent primary-call latencies used to derive the hedge delay.
+const HEDGE_LATENCY_WINDOW: usize = 128;
+/// Samples needed before the observed percentile replaces the initial delay.
+const HEDGE_MIN_SAMPLES: usize = 10;
+/// Index of the provider hedged requests are sent to.
+const HEDGE_PROVIDER_INDEX: usize = 1;
+
+/// Latency-percentile hedging settings for [`ReliableProvider`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HedgePolicy {
+    /// Percentile (0–100) of recent primary latencies after which to hedge.
+    pub percentile: f64,
+    /// Hedge delay used until enough latency samples have been observed.
+    pub initial_delay: Duration,
+}
+
+impl HedgePolicy {
+    fn delay_for(&self, samples: &VecDeque<u64>) -> Duration {
+        if samples.len() < HEDGE_MIN_SAMPLES {
+            return self.initial_delay;
+        }
+        let mut sorted: Vec<u64> = samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = (self.percentile.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil();
+        // `rank` is clamped to 0..=len by the percentile clamp above.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let index = (rank as usize).saturating_sub(1).min(sorted.len() - 1);
+        Duration::from_millis(sorted[index])
+    }
+}
+
+/// Per-call owner of the hedge provider's first request. The hedge provider is
+/// also the failover path's first fallback, so whichever path reaches it first
+/// claims it and the other skips it rather than sending the request twice.
+#[derive(Debug, Default)]
+struct HedgeClaim(AtomicU8);
+
+impl HedgeClaim {
+    const FAILOVER: u8 = 1;
+    const HEDGE: u8 = 2;
+
+    /// Claim the request for `by`; true when `by` owns it afterwards.
+    fn claim(&self, by: u8) -> bool {
+        match self
+            .0
+            .compare_exchange(0, by, Ordering::SeqCst, Ordering::SeqCst)
+        {
+            Ok(_) => true,
+            Err(owner) => owner == by,
+        }
+    }
+}
+
 /// Provider wrapper with retry, fallback, auth rotation, and model failover.
 pub struct ReliableProvider {
     providers: Vec<(String, Box<dyn Provider>)>,
@@ -235,6 +298,10 @@ pub struct ReliableProvider {
     provider_model_fallbacks: HashMap<String, Vec<String>>,
     /// Vision support override from config (`None` = defer to provider).
     vision_override: Option<bool>,
+    /// Hedged-request policy (`None` = fall back only after errors).
+    hedge: Option<HedgePolicy>,
+    /// Recent successful primary-path latencies in milliseconds.
+    primary_latencies_ms: Mutex<VecDeque<u64>>,
 }
 
 impl ReliableProvider {
@@ -252,6 +319,8 @@ impl ReliableProvider {
             model_fallbacks: HashMap::new(),
             provider_model_fallbacks: HashMap::new(),
             vision_override: None,
+            hedge: None,
+            primary_latencies_ms: Mutex::new(VecDeque::with_capacity(HEDGE_LATENCY_WINDOW)),
         }
     }
 
@@ -288,6 +357,164 @@ impl ReliableProvider {
         self
     }
 
+    /// Enable hedged requests: when the primary path is slower than the policy's
+    /// latency percentile, race a second request against the next provider.
+    pub fn with_hedging(mut self, hedge: Option<HedgePolicy>) -> Self {
+        self.hedge = hedge;
+        self
+    }
+
+    /// Current hedge delay, or `None` when hedging is disabled or there is no
+    /// second provider to hedge against.
+    fn hedge_delay(&self) -> Option<Duration> {
+        let policy = self.hedge?;
+        if self.providers.len() < 2 {
+            return None;
+        }
+        Some(policy.delay_for(&self.primary_latencies_ms.lock()))
+    }
+
+    /// Resolve the hedge target for `model`: the second provider and the model
+    /// it should be sent, plus how long to wait before firing it.
+    fn hedge_target<'a>(
+        &'a self,
+        model: &'a str,
+    ) -> Option<(&'a str, &'a dyn Provider, &'a str, Duration)> {
+        let delay = self.hedge_delay()?;
+        let (name, provider) = self.providers.get(HEDGE_PROVIDER_INDEX)?;
+        let hedge_model = self
+            .provider_model_chain(model, name, false)
+            .first()
+            .copied()
+            .unwrap_or(model);
+        Some((name.as_str(), provider.as_ref(), hedge_model, delay))
+    }
+
+    /// Whether the failover path must skip sending `sent_model` to the
+    /// provider at `provider_index` because the hedged request already did.
+    fn hedge_covers_attempt(
+        &self,
+        claim: &HedgeClaim,
+        provider_index: usize,
+        model: &str,
+        sent_model: &str,
+    ) -> bool {
+        if provider_index != HEDGE_PROVIDER_INDEX {
+            return false;
+        }
+        match self.hedge_target(model) {
+            Some((_, _, hedge_model, _)) if hedge_model == sent_model => {
+                !claim.claim(HedgeClaim::FAILOVER)
+            }
+            _ => false,
+        }
+    }
+
+    fn record_primary_latency(&self, elapsed: Duration) {
+        let ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        let mut samples = self.primary_latencies_ms.lock();
+        if samples.len() == HEDGE_LATENCY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(ms);
+    }
+
+    /// Race the failover path against a delayed hedge request. The first
+    /// success wins and the other future is dropped (cancelled). If both fail,
+    /// the primary path's aggregated error is returned. The hedge is not sent
+    /// when the failover path has already moved on to the hedge provider.
+    async fn race_hedged<T>(
+        &self,
+        primary: impl Future<Output = anyhow::Result<T>>,
+        hedge: impl Future<Output = anyhow::Result<T>>,
+        claim: &HedgeClaim,
+        hedge_name: &str,
+        hedge_model: &str,
+        delay: Duration,
+    ) -> anyhow::Result<T> {
+        let started = Instant::now();
+        tokio::pin!(primary);
+
+        tokio::select! {
+            result = &mut primary => {
+                if result.is_ok() {
+                    self.record_primary_latency(started.elapsed());
+                }
+                return result;
+            }
+            () = tokio::time::sleep(delay) => {}
+        }
+
+        if !claim.claim(HedgeClaim::HEDGE) {
+            tracing::debug!(
+                provider = hedge_name,
+                model = hedge_model,
+                "Failover already reached the hedge provider, not hedging"
+            );
+            return primary.await;
+        }
+
+        tracing::info!(
+            provider = hedge_name,
+            model = hedge_model,
+            delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+            "Primary provider slow, sending hedged request"
+        );
+        tokio::pin!(hedge);
+
+        tokio::select! {
+            result = &mut primary => match result {
+                Ok(resp) => {
+                    self.record_primary_latency(started.elapsed());
+                    Ok(resp)
+                }
+                Err(primary_err) => match hedge.await {
+                    Ok(resp) => {
+                        tracing::info!(
+                            provider = hedge_name,
+                            model = hedge_model,
+                            "Hedged request recovered after primary failure"
+                        );
+                        Ok(resp)
+                    }
+                    Err(hedge_err) => {
+                        tracing::warn!(
+                            provider = hedge_name,
+                            model = hedge_model,
+                            error = %compact_error_detail(&hedge_err),
+                            "Hedged request failed"
+                        );
+                        Err(primary_err)
+                    }
+                },
+            },
+            result = &mut hedge => match result {
+                Ok(resp) => {
+                    tracing::info!(
+                        provider = hedge_name,
+                        model = hedge_model,
+                        elapsed_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+                        "Hedged request won, cancelling primary"
+                    );
+                    Ok(resp)
+                }
+                Err(hedge_err) => {
+                    tracing::warn!(
+                        provider = hedge_name,
+                        model = hedge_model,
+                        error = %compact_error_detail(&hedge_err),
+                        "Hedged request failed, waiting for primary"
+                    );
+                    let result = primary.await;
+                    if result.is_ok() {
+                        self.record_primary_latency(started.elapsed());
+                    }
+                    result
+                }
+            },
+        }
+    }
+
     /// Build the list of models to try: [original, fallback1, fallback2, ...]
     fn model_chain<'a>(&'a self, model: &'a str) -> Vec<&'a str> {
         let mut chain = vec![model];
@@ -349,24 +576,17 @@ impl ReliableProvider {
     }
 }
 
-#[async_trait]
-impl Provider for ReliableProvider {
-    async fn warmup(&self) -> anyhow::Result<()> {
-        for (name, provider) in &self.providers {
-            tracing::info!(provider = name, "Warming up provider connection pool");
-            if provider.warmup().await.is_err() {
-                tracing::warn!(provider = name, "Warmup failed (non-fatal)");
-            }
-        }
-        Ok(())
-    }
-
-    async fn chat_with_system(
+// Failover paths: the unhedged model → provider → retry chains behind each
+// `Provider` method. The trait impl races these against a hedged request
+// when hedging is enabled.
+impl ReliableProvider {
+    async fn failover_chat_with_system(
         &self,
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
         temperature: f64,
+        claim: &HedgeClaim,
     ) -> anyhow::Result<String> {
         let models = self.model_chain(model);
         let mut failures = Vec::new();
@@ -380,6 +600,14 @@ impl Provider for ReliableProvider {
                 let sent_models =
                     self.provider_model_chain(current_model, provider_name, provider_index == 0);
                 for sent_model in sent_models {
+                    if self.hedge_covers_attempt(claim, provider_index, model, sent_model) {
+                        tracing::info!(
+                            provider = provider_name,
+                            model = sent_model,
+                            "Hedged request already sent to this provider, skipping"
+                        );
+                        continue;
+                    }
                     let mut backoff_ms = self.base_backoff_ms;
 
                     for attempt in 0..=self.max_retries {
@@ -491,11 +719,12 @@ impl Provider for ReliableProvider {
         )
     }
 
-    async fn chat_with_history(
+    async fn failover_chat_with_history(
         &self,
         messages: &[ChatMessage],
         model: &str,
         temperature: f64,
+        claim: &HedgeClaim,
     ) -> anyhow::Result<String> {
         let models = self.model_chain(model);
         let mut failures = Vec::new();
@@ -505,6 +734,14 @@ impl Provider for ReliableProvider {
                 let sent_models =
                     self.provider_model_chain(current_model, provider_name, provider_index == 0);
                 for sent_model in sent_models {
+                    if self.hedge_covers_attempt(claim, provider_index, model, sent_model) {
+                        tracing::info!(
+                            provider = provider_name,
+                            model = sent_model,
+                            "Hedged request already sent to this provider, skipping"
+                        );
+                        continue;
+                    }
                     let mut backoff_ms = self.base_backoff_ms;
 
                     for attempt in 0..=self.max_retries {
@@ -606,27 +843,13 @@ impl Provider for ReliableProvider {
         )
     }
 
-    fn supports_native_tools(&self) -> bool {
-        self.providers
-            .first()
-            .map(|(_, p)| p.supports_native_tools())
-            .unwrap_or(false)
-    }
-
-    fn supports_vision(&self) -> bool {
-        self.vision_override.unwrap_or_else(|| {
-            self.providers
-                .iter()
-                .any(|(_, provider)| provider.supports_vision())
-        })
-    }
-
-    async fn chat_with_tools(
+    async fn failover_chat_with_tools(
         &self,
         messages: &[ChatMessage],
         tools: &[serde_json::Value],
         model: &str,
         temperature: f64,
+        claim: &HedgeClaim,
     ) -> anyhow::Result<ChatResponse> {
         let models = self.model_chain(model);
         let mut failures = Vec::new();
@@ -636,6 +859,14 @@ impl Provider for ReliableProvider {
                 let sent_models =
                     self.provider_model_chain(current_model, provider_name, provider_index == 0);
                 for sent_model in sent_models {
+                    if self.hedge_covers_attempt(claim, provider_index, model, sent_model) {
+                        tracing::info!(
+                            provider = provider_name,
+                            model = sent_model,
+                            "Hedged request already sent to this provider, skipping"
+                        );
+                        continue;
+                    }
                     let mut backoff_ms = self.base_backoff_ms;
 
                     for attempt in 0..=self.max_retries {
@@ -737,11 +968,12 @@ impl Provider for ReliableProvider {
         )
     }
 
-    async fn chat(
+    async fn failover_chat(
         &self,
         request: ChatRequest<'_>,
         model: &str,
         temperature: f64,
+        claim: &HedgeClaim,
     ) -> anyhow::Result<ChatResponse> {
         let models = self.model_chain(model);
         let mut failures = Vec::new();
@@ -751,12 +983,21 @@ impl Provider for ReliableProvider {
                 let sent_models =
                     self.provider_model_chain(current_model, provider_name, provider_index == 0);
                 for sent_model in sent_models {
+                    if self.hedge_covers_attempt(claim, provider_index, model, sent_model) {
+                        tracing::info!(
+                            provider = provider_name,
+                            model = sent_model,
+                            "Hedged request already sent to this provider, skipping"
+                        );
+                        continue;
+                    }
                     let mut backoff_ms = self.base_backoff_ms;
 
                     for attempt in 0..=self.max_retries {
                         let req = ChatRequest {
                             messages: request.messages,
                             tools: request.tools,
+                            response_format: request.response_format,
                         };
                         match provider.chat(req, sent_model, temperature).await {
                             Ok(resp) => {
@@ -860,6 +1101,129 @@ impl Provider for ReliableProvider {
             failures.join("\n")
         )
     }
+}
+
+#[async_trait]
+impl Provider for ReliableProvider {
+    async fn warmup(&self) -> anyhow::Result<()> {
+        for (name, provider) in &self.providers {
+            tracing::info!(provider = name, "Warming up provider connection pool");
+            if provider.warmup().await.is_err() {
+                tracing::warn!(provider = name, "Warmup failed (non-fatal)");
+            }
+        }
+        Ok(())
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let claim = HedgeClaim::default();
+        let primary =
+            self.failover_chat_with_system(system_prompt, message, model, temperature, &claim);
+        match self.hedge_target(model) {
+            Some((hedge_name, hedge_provider, hedge_model, delay)) => {
+                let hedge = hedge_provider.chat_with_system(
+                    system_prompt,
+                    message,
+                    hedge_model,
+                    temperature,
+                );
+                self.race_hedged(primary, hedge, &claim, hedge_name, hedge_model, delay)
+                    .await
+            }
+            None => primary.await,
+        }
+    }
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let claim = HedgeClaim::default();
+        let primary = self.failover_chat_with_history(messages, model, temperature, &claim);
+        match self.hedge_target(model) {
+            Some((hedge_name, hedge_provider, hedge_model, delay)) => {
+                let hedge = hedge_provider.chat_with_history(messages, hedge_model, temperature);
+                self.race_hedged(primary, hedge, &claim, hedge_name, hedge_model, delay)
+                    .await
+            }
+            None => primary.await,
+        }
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.providers
+            .first()
+            .map(|(_, p)| p.supports_native_tools())
+            .unwrap_or(false)
+    }
+
+    fn supports_structured_output(&self) -> bool {
+        self.providers
+            .first()
+            .map(|(_, p)| p.supports_structured_output())
+            .unwrap_or(false)
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.vision_override.unwrap_or_else(|| {
+            self.providers
+                .iter()
+                .any(|(_, provider)| provider.supports_vision())
+        })
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let claim = HedgeClaim::default();
+        let primary = self.failover_chat_with_tools(messages, tools, model, temperature, &claim);
+        match self.hedge_target(model) {
+            Some((hedge_name, hedge_provider, hedge_model, delay)) => {
+                let hedge =
+                    hedge_provider.chat_with_tools(messages, tools, hedge_model, temperature);
+                self.race_hedged(primary, hedge, &claim, hedge_name, hedge_model, delay)
+                    .await
+            }
+            None => primary.await,
+        }
+    }
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let claim = HedgeClaim::default();
+        let primary = self.failover_chat(
+            ChatRequest {
+                messages: request.messages,
+                tools: request.tools,
+                response_format: request.response_format,
+            },
+            model,
+            temperature,
+            &claim,
+        );
+        match self.hedge_target(model) {
+            Some((hedge_name, hedge_provider, hedge_model, delay)) => {
+                let hedge = hedge_provider.chat(request, hedge_model, temperature);
+                self.race_hedged(primary, hedge, &claim, hedge_name, hedge_model, delay)
+                    .await
+            }
+            None => primary.await,
+        }
+    }
 
     fn supports_streaming(&self) -> bool {
         self.providers.iter().any(|(_, p)| p.supports_streaming())
@@ -939,6 +1303,59 @@ impl Provider for ReliableProvider {
         })
         .boxed()
     }
+
+    fn supports_tool_call_streaming(&self) -> bool {
+        self.providers
+            .iter()
+            .any(|(_, p)| p.supports_tool_call_streaming())
+    }
+
+    fn stream_chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+        // Same policy as `stream_chat_with_system`: a single attempt on the
+        // first capable provider. The tool loop falls back to `chat()` (and
+        // therefore the full retry/fallback chain) when the stream fails
+        // before producing anything.
+        for (provider_index, (provider_name, provider)) in self.providers.iter().enumerate() {
+            if !provider.supports_tool_call_streaming() {
+                continue;
+            }
+
+            let base_model = match self.model_chain(model).first() {
+                Some(m) => *m,
+                None => model,
+            };
+            let current_model = self
+                .provider_model_chain(base_model, provider_name, provider_index == 0)
+                .first()
+                .copied()
+                .unwrap_or(base_model)
+                .to_string();
+
+            let stream = provider.stream_chat(request, &current_model, temperature, options);
+            let provider_name = provider_name.clone();
+            return stream
+                .inspect(move |event| {
+                    if let Err(e) = event {
+                        tracing::warn!(
+                            provider = provider_name,
+                            model = current_model,
+                            "Streaming error: {e}"
+                        );
+                    }
+                })
+                .boxed();
+        }
+
+        super::streaming::error_stream(super::traits::StreamError::Provider(
+            "No provider supports tool-call streaming".to_string(),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -1195,6 +1612,10 @@ mod tests {
 
         assert!(msg.contains("context window"));
         assert!(msg.contains("skipped"));
+        assert!(
+            is_context_window_exceeded(&err),
+            "callers must still recognize the aggregated error as an overflow"
+        );
         assert_eq!(calls.load(Ordering::SeqCst), 1);
     }
 
@@ -1807,6 +2228,7 @@ mod tests {
                 tool_calls: self.tool_calls.clone(),
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             })
         }
     }
@@ -1838,6 +2260,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            response_format: None,
         };
         let result = provider.chat(request, "test-model", 0.0).await.unwrap();
 
@@ -1874,6 +2297,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            response_format: None,
         };
         let result = provider.chat(request, "test-model", 0.0).await.unwrap();
 
@@ -1945,6 +2369,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            response_format: None,
         };
         let err = provider
             .chat(request, "test", 0.0)
@@ -2000,6 +2425,7 @@ mod tests {
                 tool_calls: vec![],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             })
         }
     }
@@ -2061,6 +2487,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            response_format: None,
         };
         let result = provider.chat(request, "claude-opus", 0.0).await.unwrap();
         assert_eq!(result.text.as_deref(), Some("ok from sonnet"));
@@ -2109,6 +2536,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            response_format: None,
         };
         let result = provider.chat(request, "test", 0.0).await.unwrap();
         assert_eq!(result.text.as_deref(), Some("from fallback"));
@@ -2180,4 +2608,176 @@ mod tests {
         // No override set → should defer to provider default (false)
         assert!(!provider.supports_vision());
     }
+
+    // ── Hedged requests ──
+
+    /// Mock that answers after a fixed delay and counts calls.
+    struct DelayedMock {
+        calls: Arc<AtomicUsize>,
+        delay: Duration,
+        response: &'static str,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Provider for DelayedMock {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            if self.fail {
+                anyhow::bail!("500 upstream unavailable");
+            }
+            Ok(self.response.to_string())
+        }
+    }
+
+    fn hedged_pair(
+        primary_delay_ms: u64,
+        primary_fail: bool,
+        fallback_delay_ms: u64,
+        hedge: Option<HedgePolicy>,
+    ) -> (ReliableProvider, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(DelayedMock {
+                        calls: Arc::clone(&primary_calls),
+                        delay: Duration::from_millis(primary_delay_ms),
+                        response: "from primary",
+                        fail: primary_fail,
+                    }) as Box<dyn Provider>,
+                ),
+                (
+                    "fallback".into(),
+                    Box::new(DelayedMock {
+                        calls: Arc::clone(&fallback_calls),
+                        delay: Duration::from_millis(fallback_delay_ms),
+                        response: "from fallback",
+                        fail: false,
+                    }) as Box<dyn Provider>,
+                ),
+            ],
+            0,
+            1,
+        )
+        .with_hedging(hedge);
+        (provider, primary_calls, fallback_calls)
+    }
+
+    fn hedge_after(ms: u64) -> Option<HedgePolicy> {
+        Some(HedgePolicy {
+            percentile: 95.0,
+            initial_delay: Duration::from_millis(ms),
+        })
+    }
+
+    #[tokio::test]
+    async fn hedge_wins_when_primary_is_slow() {
+        let (provider, primary_calls, fallback_calls) =
+            hedged_pair(5_000, false, 0, hedge_after(20));
+
+        let started = Instant::now();
+        let result = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+
+        assert_eq!(result, "from fallback");
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn hedge_not_sent_when_primary_is_fast() {
+        let (provider, _, fallback_calls) = hedged_pair(0, false, 0, hedge_after(1_000));
+
+        let result = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+
+        assert_eq!(result, "from primary");
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(provider.primary_latencies_ms.lock().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn hedging_disabled_waits_for_primary() {
+        let (provider, _, fallback_calls) = hedged_pair(50, false, 0, None);
+
+        let result = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+
+        assert_eq!(result, "from primary");
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn hedge_answer_used_when_primary_fails_after_hedging() {
+        let (provider, _, fallback_calls) = hedged_pair(50, true, 200, hedge_after(10));
+
+        let result = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+
+        assert_eq!(result, "from fallback");
+        // Failover after the primary error leaves the fallback to the hedge.
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn hedge_skipped_when_failover_already_on_fallback() {
+        let (provider, primary_calls, fallback_calls) = hedged_pair(5, true, 200, hedge_after(50));
+
+        let result = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+
+        assert_eq!(result, "from fallback");
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn hedge_delay_uses_initial_delay_until_enough_samples() {
+        let policy = HedgePolicy {
+            percentile: 50.0,
+            initial_delay: Duration::from_millis(750),
+        };
+        let few: VecDeque<u64> = (1..HEDGE_MIN_SAMPLES as u64).collect();
+        assert_eq!(policy.delay_for(&few), Duration::from_millis(750));
+    }
+
+    #[test]
+    fn hedge_delay_tracks_latency_percentile() {
+        let samples: VecDeque<u64> = (1..=100).map(|ms| ms * 10).collect();
+        let p95 = HedgePolicy {
+            percentile: 95.0,
+            initial_delay: Duration::ZERO,
+        };
+        let p50 = HedgePolicy {
+            percentile: 50.0,
+            initial_delay: Duration::ZERO,
+        };
+        assert_eq!(p95.delay_for(&samples), Duration::from_millis(950));
+        assert_eq!(p50.delay_for(&samples), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn hedge_requires_a_second_provider() {
+        let provider = ReliableProvider::new(
+            vec![(
+                "primary".into(),
+                Box::new(DelayedMock {
+                    calls: Arc::new(AtomicUsize::new(0)),
+                    delay: Duration::ZERO,
+                    response: "ok",
+                    fail: false,
+                }) as Box<dyn Provider>,
+            )],
+            0,
+            1,
+        )
+        .with_hedging(hedge_after(10));
+        assert!(provider.hedge_delay().is_none());
+    }
 }
@@ -1,13 +1,21 @@
-use super::traits::{ChatMessage, ChatRequest, ChatResponse};
+use super::adaptive_routing::{AdaptiveRoutingPolicy, RouteCandidate};
+use super::traits::{
+    ChatMessage, ChatRequest, ChatResponse, StreamEvent, StreamOptions, StreamResult, TokenUsage,
+};
 use super::Provider;
 use async_trait::async_trait;
+use futures_util::{stream, StreamExt};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
 
 /// A single route: maps a task hint to a provider + model combo.
 #[derive(Debug, Clone)]
 pub struct Route {
     pub provider_name: String,
     pub model: String,
+    /// Quality tier used by adaptive routing (`None` = tier 0).
+    pub quality_tier: Option<u8>,
 }
 
 /// Multi-model router — routes requests to different provider+model combos
@@ -18,8 +26,13 @@ pub struct Route {
 /// - A hint-prefixed string (e.g. "hint:reasoning") → resolves via route table
 ///
 /// This wraps multiple pre-created providers and selects the right one per request.
+/// With an [`AdaptiveRoutingPolicy`] attached, a hint shared by several routes is
+/// resolved per request to the route the policy currently prefers.
 pub struct RouterProvider {
     routes: HashMap<String, (usize, String)>, // hint → (provider_index, model)
+    /// All routes per hint, in config order (adaptive routing candidates).
+    route_pools: HashMap<String, Vec<(usize, RouteCandidate)>>,
+    adaptive: Option<Arc<AdaptiveRoutingPolicy>>,
     providers: Vec<(String, Box<dyn Provider>)>,
     default_index: usize,
     default_model: String,
@@ -45,26 +58,32 @@ impl RouterProvider {
             .collect();
 
         // Resolve routes to provider indices
-        let resolved_routes: HashMap<String, (usize, String)> = routes
-            .into_iter()
-            .filter_map(|(hint, route)| {
-                let index = name_to_index.get(route.provider_name.as_str()).copied();
-                match index {
-                    Some(i) => Some((hint, (i, route.model))),
-                    None => {
-                        tracing::warn!(
-                            hint = hint,
-                            provider = route.provider_name,
-                            "Route references unknown provider, skipping"
-                        );
-                        None
-                    }
-                }
-            })
-            .collect();
+        let mut resolved_routes: HashMap<String, (usize, String)> = HashMap::new();
+        let mut route_pools: HashMap<String, Vec<(usize, RouteCandidate)>> = HashMap::new();
+        for (hint, route) in routes {
+            let Some(i) = name_to_index.get(route.provider_name.as_str()).copied() else {
+                tracing::warn!(
+                    hint = hint,
+                    provider = route.provider_name,
+                    "Route references unknown provider, skipping"
+                );
+                continue;
+            };
+            route_pools.entry(hint.clone()).or_default().push((
+                i,
+                RouteCandidate {
+                    provider_id: route.provider_name,
+                    model: route.model.clone(),
+                    quality_tier: route.quality_tier,
+                },
+            ));
+            resolved_routes.insert(hint, (i, route.model));
+        }
 
         Self {
             routes: resolved_routes,
+            route_pools,
+            adaptive: None,
             providers,
             default_index: 0,
             default_model,
@@ -78,6 +97,45 @@ impl RouterProvider {
         self
     }
 
+    /// Attach an adaptive routing policy used for hints with route candidates.
+    pub fn with_adaptive_routing(mut self, policy: Option<AdaptiveRoutingPolicy>) -> Self {
+        self.adaptive = policy.map(Arc::new);
+        self
+    }
+
+    /// Like [`Self::resolve`], but lets the adaptive policy pick among routes
+    /// sharing the hint. Returns the chosen candidate so its outcome can be
+    /// recorded.
+    fn select(&self, model: &str) -> (usize, String, Option<RouteCandidate>) {
+        if let (Some(policy), Some(hint)) = (&self.adaptive, model.strip_prefix("hint:")) {
+            if let Some(pool) = self.route_pools.get(hint) {
+                let candidates: Vec<RouteCandidate> = pool
+                    .iter()
+                    .map(|(_, candidate)| candidate.clone())
+                    .collect();
+                let decision = policy.choose(hint, &candidates);
+                let (idx, candidate) = &pool[decision.index];
+                return (*idx, candidate.model.clone(), Some(candidate.clone()));
+            }
+        }
+
+        let (idx, resolved_model) = self.resolve(model);
+        (idx, resolved_model, None)
+    }
+
+    /// Feed a call's latency, success and usage back into the adaptive policy.
+    fn record_outcome(
+        &self,
+        candidate: Option<&RouteCandidate>,
+        started: Instant,
+        success: bool,
+        usage: Option<&TokenUsage>,
+    ) {
+        if let (Some(policy), Some(candidate)) = (&self.adaptive, candidate) {
+            policy.record(candidate, started.elapsed(), success, usage);
+        }
+    }
+
     /// Resolve a model parameter to a (provider, actual_model) pair.
     ///
     /// If the model starts with "hint:", look up the hint in the route table.
@@ -108,7 +166,7 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let (provider_idx, resolved_model, candidate) = self.select(model);
 
         let (provider_name, provider) = &self.providers[provider_idx];
         tracing::info!(
@@ -117,9 +175,12 @@ impl Provider for RouterProvider {
             "Router dispatching request"
         );
 
-        provider
+        let started = Instant::now();
+        let result = provider
             .chat_with_system(system_prompt, message, &resolved_model, temperature)
-            .await
+            .await;
+        self.record_outcome(candidate.as_ref(), started, result.is_ok(), None);
+        result
     }
 
     async fn chat_with_history(
@@ -128,11 +189,14 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let (provider_idx, resolved_model, candidate) = self.select(model);
         let (_, provider) = &self.providers[provider_idx];
-        provider
+        let started = Instant::now();
+        let result = provider
             .chat_with_history(messages, &resolved_model, temperature)
-            .await
+            .await;
+        self.record_outcome(candidate.as_ref(), started, result.is_ok(), None);
+        result
     }
 
     async fn chat(
@@ -141,9 +205,17 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let (provider_idx, resolved_model, candidate) = self.select(model);
         let (_, provider) = &self.providers[provider_idx];
-        provider.chat(request, &resolved_model, temperature).await
+        let started = Instant::now();
+        let result = provider.chat(request, &resolved_model, temperature).await;
+        self.record_outcome(
+            candidate.as_ref(),
+            started,
+            result.is_ok(),
+            result.as_ref().ok().and_then(|resp| resp.usage.as_ref()),
+        );
+        result
     }
 
     async fn chat_with_tools(
@@ -153,11 +225,19 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let (provider_idx, resolved_model, candidate) = self.select(model);
         let (_, provider) = &self.providers[provider_idx];
-        provider
+        let started = Instant::now();
+        let result = provider
             .chat_with_tools(messages, tools, &resolved_model, temperature)
-            .await
+            .await;
+        self.record_outcome(
+            candidate.as_ref(),
+            started,
+            result.is_ok(),
+            result.as_ref().ok().and_then(|resp| resp.usage.as_ref()),
+        );
+        result
     }
 
     fn supports_native_tools(&self) -> bool {
@@ -167,6 +247,70 @@ impl Provider for RouterProvider {
             .unwrap_or(false)
     }
 
+    fn supports_structured_output(&self) -> bool {
+        self.providers
+            .get(self.default_index)
+            .map(|(_, p)| p.supports_structured_output())
+            .unwrap_or(false)
+    }
+
+    fn supports_tool_call_streaming(&self) -> bool {
+        // The route is only known per request, so report support when any
+        // route can stream; `stream_chat` checks the route it actually picks.
+        self.providers
+            .iter()
+            .any(|(_, p)| p.supports_tool_call_streaming())
+    }
+
+    fn stream_chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+        let (provider_idx, resolved_model, candidate) = self.select(model);
+        let (provider_name, provider) = &self.providers[provider_idx];
+
+        // The tool loop falls back to `chat()` when the stream fails before
+        // producing anything, so an incapable route is not a route failure.
+        if request.tools.is_some_and(|tools| !tools.is_empty())
+            && !provider.supports_tool_call_streaming()
+        {
+            return super::streaming::error_stream(super::traits::StreamError::Provider(format!(
+                "Routed provider `{provider_name}` does not support streaming tool calls"
+            )));
+        }
+
+        let stream = provider.stream_chat(request, &resolved_model, temperature, options);
+        let (Some(policy), Some(candidate)) = (self.adaptive.clone(), candidate) else {
+            return stream;
+        };
+
+        // Record the outcome once, when the stream completes or errors.
+        let started = Instant::now();
+        let mut usage: Option<TokenUsage> = None;
+        let mut recorded = false;
+        stream
+            .inspect(move |event| {
+                if recorded {
+                    return;
+                }
+                let success = match event {
+                    Ok(StreamEvent::Usage(reported)) => {
+                        usage = Some(reported.clone());
+                        return;
+                    }
+                    Ok(StreamEvent::Done) => true,
+                    Ok(_) => return,
+                    Err(_) => false,
+                };
+                recorded = true;
+                policy.record(&candidate, started.elapsed(), success, usage.as_ref());
+            })
+            .boxed()
+    }
+
     fn supports_vision(&self) -> bool {
         self.vision_override.unwrap_or_else(|| {
             self.providers
@@ -229,6 +373,22 @@ mod tests {
             *self.last_model.lock() = model.to_string();
             Ok(self.response.to_string())
         }
+
+        fn stream_chat(
+            &self,
+            _request: ChatRequest<'_>,
+            model: &str,
+            _temperature: f64,
+            _options: StreamOptions,
+        ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            *self.last_model.lock() = model.to_string();
+            stream::iter(vec![
+                Ok(StreamEvent::TextDelta(self.response.to_string())),
+                Ok(StreamEvent::Done),
+            ])
+            .boxed()
+        }
     }
 
     fn make_router(
@@ -259,6 +419,7 @@ mod tests {
                     Route {
                         provider_name: provider_name.to_string(),
                         model: model.to_string(),
+                        quality_tier: None,
                     },
                 )
             })
@@ -283,6 +444,17 @@ mod tests {
                 .chat_with_system(system_prompt, message, model, temperature)
                 .await
         }
+
+        fn stream_chat(
+            &self,
+            request: ChatRequest<'_>,
+            model: &str,
+            temperature: f64,
+            options: StreamOptions,
+        ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+            self.as_ref()
+                .stream_chat(request, model, temperature, options)
+        }
     }
 
     #[tokio::test]
@@ -472,4 +644,106 @@ mod tests {
         assert_eq!(mocks[1].last_model(), "claude-opus");
         assert_eq!(mocks[0].call_count(), 0);
     }
+
+    #[tokio::test]
+    async fn adaptive_routing_explores_then_prefers_cheapest_route() {
+        let (router, mocks) = make_router(
+            vec![
+                ("default", "default-response"),
+                ("cheap", "cheap-response"),
+                ("premium", "premium-response"),
+            ],
+            vec![("chat", "cheap", "small"), ("chat", "premium", "big")],
+        );
+        let mut prices = HashMap::new();
+        prices.insert(
+            "cheap/small".to_string(),
+            crate::config::schema::ModelPricing {
+                input: 0.1,
+                output: 0.4,
+                ..Default::default()
+            },
+        );
+        prices.insert(
+            "premium/big".to_string(),
+            crate::config::schema::ModelPricing {
+                input: 15.0,
+                output: 75.0,
+                ..Default::default()
+            },
+        );
+        let router = router.with_adaptive_routing(Some(AdaptiveRoutingPolicy::new(
+            crate::config::AdaptiveRoutingConfig {
+                enabled: true,
+                min_samples: 1,
+                ..Default::default()
+            },
+            prices,
+        )));
+
+        let mut replies = Vec::new();
+        for _ in 0..3 {
+            replies.push(router.simple_chat("hi", "hint:chat", 0.0).await.unwrap());
+        }
+
+        // Explore the cheap route, then the unsampled premium route, then
+        // settle on the cheapest healthy route.
+        assert_eq!(
+            replies,
+            vec!["cheap-response", "premium-response", "cheap-response"]
+        );
+        assert_eq!(mocks[0].call_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn adaptive_routing_records_streamed_turns() {
+        let (router, mocks) = make_router(
+            vec![
+                ("default", "default-response"),
+                ("cheap", "cheap-response"),
+                ("premium", "premium-response"),
+            ],
+            vec![("chat", "cheap", "small"), ("chat", "premium", "big")],
+        );
+        let router = router.with_adaptive_routing(Some(AdaptiveRoutingPolicy::new(
+            crate::config::AdaptiveRoutingConfig {
+                enabled: true,
+                min_samples: 1,
+                ..Default::default()
+            },
+            HashMap::new(),
+        )));
+
+        let messages = [ChatMessage::user("hi")];
+        for _ in 0..2 {
+            let request = ChatRequest {
+                messages: &messages,
+                tools: None,
+                response_format: None,
+            };
+            let events: Vec<_> = router
+                .stream_chat(request, "hint:chat", 0.0, StreamOptions::new(true))
+                .collect()
+                .await;
+            assert!(events.iter().all(Result::is_ok));
+        }
+
+        // The first streamed turn was recorded, so the second one explores
+        // the still-unsampled route instead of repeating the first.
+        assert_eq!(mocks[1].call_count(), 1);
+        assert_eq!(mocks[2].call_count(), 1);
+        assert_eq!(mocks[0].call_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn shared_hint_without_adaptive_routing_uses_last_route() {
+        let (router, mocks) = make_router(
+            vec![("cheap", "cheap-response"), ("premium", "premium-response")],
+            vec![("chat", "cheap", "small"), ("chat", "premium", "big")],
+        );
+
+        let result = router.simple_chat("hi", "hint:chat", 0.0).await.unwrap();
+        assert_eq!(result, "premium-response");
+        assert_eq!(mocks[0].call_count(), 0);
+    }
 }
@@ -0,0 +1,272 @@
+//! Scripted provider that replays a fixed sequence of responses.
+//!
+//! [`ScriptedProvider`] serves one [`ScriptedStep`] per `chat` call, in order,
+//! and reports native tool-calling support so the agent loop executes the
+//! scripted tool calls through its normal dispatch path. It is the default
+//! provider for `zeroclaw eval` scenarios: the model side of the conversation
+//! is pinned, so any change in tool execution, prompts or memory writes shows
+//! up as a scenario failure instead of model noise.
+//!
+//! Running past the end of the script is an error rather than a canned
+//! answer, so a loop that makes more provider calls than the scenario expects
+//! fails loudly.
+
+use super::traits::{
+    ChatMessage, ChatRequest, ChatResponse, ProviderCapabilities, TokenUsage, ToolCall,
+};
+use super::Provider;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// A tool call emitted by a scripted step.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScriptedToolCall {
+    pub name: String,
+    #[serde(default = "empty_arguments")]
+    pub arguments: serde_json::Value,
+}
+
+fn empty_arguments() -> serde_json::Value {
+    serde_json::Value::Object(serde_json::Map::new())
+}
+
+/// One provider response in a script.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScriptedStep {
+    /// Assistant text for this response.
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Tool calls requested by this response.
+    #[serde(default)]
+    pub tool_calls: Vec<ScriptedToolCall>,
+    /// Reported prompt tokens, used for cost assertions.
+    #[serde(default)]
+    pub input_tokens: Option<u64>,
+    /// Reported completion tokens, used for cost assertions.
+    #[serde(default)]
+    pub output_tokens: Option<u64>,
+    /// Fail this call with the given message instead of responding.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl ScriptedStep {
+    /// A plain text response.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Self::default()
+        }
+    }
+
+    /// A response that calls a single tool.
+    pub fn tool_call(name: impl Into<String>, arguments: serde_json::Value) -> Self {
+        Self {
+            tool_calls: vec![ScriptedToolCall {
+                name: name.into(),
+                arguments,
+            }],
+            ..Self::default()
+        }
+    }
+
+    fn into_response(self, index: usize) -> anyhow::Result<ChatResponse> {
+        if let Some(error) = self.error {
+            anyhow::bail!("{error}");
+        }
+        let tool_calls = self
+            .tool_calls
+            .into_iter()
+            .enumerate()
+            .map(|(call_index, call)| ToolCall {
+                id: format!("call_{index}_{call_index}"),
+                name: call.name,
+                arguments: call.arguments.to_string(),
+            })
+            .collect();
+        let usage =
+            (self.input_tokens.is_some() || self.output_tokens.is_some()).then(|| TokenUsage {
+                input_tokens: self.input_tokens,
+                output_tokens: self.output_tokens,
+                ..TokenUsage::default()
+            });
+        Ok(ChatResponse {
+            text: self.text,
+            tool_calls,
+            usage,
+            reasoning_content: None,
+            thinking: Vec::new(),
+        })
+    }
+}
+
+/// Provider that answers every call with the next [`ScriptedStep`].
+pub struct ScriptedProvider {
+    steps: Mutex<VecDeque<ScriptedStep>>,
+    total: usize,
+    requests: Mutex<Vec<Vec<ChatMessage>>>,
+}
+
+impl ScriptedProvider {
+    pub fn new(steps: Vec<ScriptedStep>) -> Self {
+        Self {
+            total: steps.len(),
+            steps: Mutex::new(steps.into()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Messages sent with every call so far, oldest first.
+    pub fn requests(&self) -> Vec<Vec<ChatMessage>> {
+        self.requests.lock().clone()
+    }
+
+    /// Number of scripted steps not yet consumed.
+    pub fn remaining(&self) -> usize {
+        self.steps.lock().len()
+    }
+
+    fn next_response(&self, messages: &[ChatMessage]) -> anyhow::Result<ChatResponse> {
+        let index = {
+            let mut requests = self.requests.lock();
+            requests.push(messages.to_vec());
+            requests.len() - 1
+        };
+        let Some(step) = self.steps.lock().pop_front() else {
+            anyhow::bail!(
+                "Scripted provider exhausted: call {} but the script has only {} step(s)",
+                index + 1,
+                self.total
+            );
+        };
+        step.into_response(index)
+    }
+}
+
+#[async_trait]
+impl Provider for ScriptedProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_tool_calling: true,
+            vision: false,
+        }
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_with_history(&messages, model, temperature).await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<String> {
+        Ok(self.next_response(messages)?.text.unwrap_or_default())
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.next_response(request.messages)
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        _tools: &[serde_json::Value],
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.next_response(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(messages: &[ChatMessage]) -> ChatRequest<'_> {
+        ChatRequest {
+            messages,
+            tools: None,
+            response_format: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_steps_in_order_with_tool_call_ids_and_usage() {
+        let mut first = ScriptedStep::tool_call("file_read", serde_json::json!({"path": "a.txt"}));
+        first.input_tokens = Some(100);
+        let provider = ScriptedProvider::new(vec![first, ScriptedStep::text("done")]);
+        let messages = [ChatMessage::user("hi")];
+
+        let response = provider.chat(request(&messages), "m", 0.0).await.unwrap();
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].id, "call_0_0");
+        assert_eq!(response.tool_calls[0].arguments, r#"{"path":"a.txt"}"#);
+        assert_eq!(response.usage.unwrap().input_tokens, Some(100));
+
+        let response = provider.chat(request(&messages), "m", 0.0).await.unwrap();
+        assert_eq!(response.text.as_deref(), Some("done"));
+        assert!(response.usage.is_none());
+        assert_eq!(provider.remaining(), 0);
+        assert_eq!(provider.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn errors_when_script_is_exhausted_or_step_fails() {
+        let provider = ScriptedProvider::new(vec![ScriptedStep {
+            error: Some("rate limited".into()),
+            ..ScriptedStep::default()
+        }]);
+        let messages = [ChatMessage::user("hi")];
+
+        let err = provider
+            .chat(request(&messages), "m", 0.0)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "rate limited");
+
+        let err = provider
+            .chat(request(&messages), "m", 0.0)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exhausted"));
+    }
+
+    #[test]
+    fn step_deserializes_from_toml() {
+        let step: ScriptedStep = toml::from_str(
+            r#"
+            text = "Checking"
+            tool_calls = [{ name = "shell", arguments = { command = "ls" } }]
+            output_tokens = 12
+            "#,
+        )
+        .unwrap();
+        assert_eq!(step.tool_calls[0].name, "shell");
+        assert_eq!(step.tool_calls[0].arguments["command"], "ls");
+        assert_eq!(step.output_tokens, Some(12));
+
+        let step: ScriptedStep =
+            toml::from_str(r#"tool_calls = [{ name = "memory_recall" }]"#).unwrap();
+        assert!(step.tool_calls[0].arguments.is_object());
+    }
+}
@@ -0,0 +1,335 @@
+//! Server-Sent Events decoding for [`Provider::stream_chat`](super::Provider::stream_chat).
+//!
+//! [`sse_event_stream`] turns an HTTP response body into [`StreamEvent`]s by
+//! feeding each `data:` payload to a provider-specific parser.
+//! [`OpenAiStreamParser`] handles the OpenAI chat-completions chunk format
+//! shared by OpenAI, OpenRouter and the compatible providers.
+
+use super::traits::{StreamError, StreamEvent, StreamResult, TokenUsage, ToolCallDelta};
+use futures_util::{stream, StreamExt};
+use serde::Deserialize;
+
+/// Parses one SSE `data:` payload into zero or more events.
+pub(crate) trait SseEventParser: Send + 'static {
+    fn parse(&mut self, data: &str) -> StreamResult<Vec<StreamEvent>>;
+}
+
+/// Decode an SSE response body into [`StreamEvent`]s.
+///
+/// Lines are split on raw bytes so multi-byte characters spanning network
+/// chunks stay intact. A [`StreamEvent::Done`] is emitted when the body ends
+/// if the parser has not produced one.
+fn sse_event_stream<P: SseEventParser>(
+    response: reqwest::Response,
+    mut parser: P,
+) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+    let (tx, rx) = tokio::sync::mpsc::channel::<StreamResult<StreamEvent>>(100);
+
+    tokio::spawn(async move {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut done = false;
+        let mut bytes_stream = response.bytes_stream();
+
+        'read: while let Some(item) = bytes_stream.next().await {
+            let bytes = match item {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    let _ = tx.send(Err(StreamError::Http(e))).await;
+                    return;
+                }
+            };
+            buffer.extend_from_slice(&bytes);
+
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                let line = match std::str::from_utf8(&line) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        let _ = tx
+                            .send(Err(StreamError::InvalidSse(format!("Invalid UTF-8: {e}"))))
+                            .await;
+                        return;
+                    }
+                };
+                let Some(data) = sse_data(line) else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    break 'read;
+                }
+                match parser.parse(data) {
+                    Ok(events) => {
+                        for event in events {
+                            done |= matches!(event, StreamEvent::Done);
+                            if tx.send(Ok(event)).await.is_err() {
+                                return; // Receiver dropped
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+                        return;
+                    }
+                }
+            }
+        }
+
+        if !done {
+            let _ = tx.send(Ok(StreamEvent::Done)).await;
+        }
+    });
+
+    stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|event| (event, rx))
+    })
+    .boxed()
+}
+
+/// Extract the payload of an SSE `data:` line; other fields and comments are skipped.
+fn sse_data(line: &str) -> Option<&str> {
+    let data = line.trim().strip_prefix("data:")?.trim();
+    (!data.is_empty()).then_some(data)
+}
+
+/// Send a streaming request and decode its SSE body with `parser`.
+///
+/// The request is paced by the shared quota scheduler under the
+/// `provider`/`credential` bucket. Transport failures and non-success
+/// statuses surface as a single error event.
+pub(crate) fn send_sse_request<P: SseEventParser>(
+    provider: &str,
+    credential: Option<&str>,
+    request: reqwest::RequestBuilder,
+    parser: P,
+) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+    let provider = provider.to_string();
+    let credential = credential.map(str::to_string);
+    stream::once(async move {
+        let request = request.header("Accept", "text/event-stream");
+        let response =
+            match super::quota_scheduler::send(&provider, credential.as_deref(), request).await {
+                Ok(response) => response,
+                Err(e) => return error_stream(StreamError::Http(e)),
+            };
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return error_stream(StreamError::Provider(format!(
+                "{provider} API error ({status}): {}",
+                super::sanitize_api_error(&body)
+            )));
+        }
+        sse_event_stream(response, parser)
+    })
+    .flatten()
+    .boxed()
+}
+
+/// A stream yielding only `error`.
+pub(crate) fn error_stream(
+    error: StreamError,
+) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+    stream::once(async move { Err(error) }).boxed()
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamChunk {
+    #[serde(default)]
+    choices: Vec<OpenAiStreamChoice>,
+    #[serde(default)]
+    usage: Option<OpenAiStreamUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamChoice {
+    #[serde(default)]
+    delta: Option<OpenAiStreamDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamDelta {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<OpenAiToolCallDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiToolCallDelta {
+    #[serde(default)]
+    index: Option<usize>,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<OpenAiFunctionDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiFunctionDelta {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamUsage {
+    #[serde(default)]
+    prompt_tokens: Option<u64>,
+    #[serde(default)]
+    completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// OpenAI reports cached prompt tokens (already counted in `prompt_tokens`) here.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
+}
+
+/// Parser for OpenAI chat-completions streaming chunks.
+#[derive(Debug, Default)]
+pub(crate) struct OpenAiStreamParser;
+
+impl SseEventParser for OpenAiStreamParser {
+    fn parse(&mut self, data: &str) -> StreamResult<Vec<StreamEvent>> {
+        let chunk: OpenAiStreamChunk = serde_json::from_str(data).map_err(StreamError::Json)?;
+        let mut events = Vec::new();
+
+        if let Some(choice) = chunk.choices.into_iter().next() {
+            if let Some(delta) = choice.delta {
+                if let Some(content) = delta.content.filter(|c| !c.is_empty()) {
+                    events.push(StreamEvent::TextDelta(content));
+                }
+                for (position, call) in delta.tool_calls.into_iter().enumerate() {
+                    let (name, arguments) = call
+                        .function
+                        .map(|f| (f.name, f.arguments.unwrap_or_default()))
+                        .unwrap_or_default();
+                    events.push(StreamEvent::ToolCallDelta(ToolCallDelta {
+                        // Some compatible backends omit `index` for single calls.
+                        index: call.index.unwrap_or(position),
+                        id: call.id,
+                        name,
+                        arguments,
+                    }));
+                }
+            }
+        }
+
+        // With `include_usage`, usage arrives in a trailing chunk after the
+        // finish reason, so only the end of the body completes the stream.
+        if let Some(usage) = chunk.usage {
+            events.push(StreamEvent::Usage(TokenUsage {
+                input_tokens: usage.prompt_tokens,
+                output_tokens: usage.completion_tokens,
+                cache_read_tokens: usage
+                    .prompt_tokens_details
+                    .and_then(|details| details.cached_tokens),
+                cache_write_tokens: None,
+            }));
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::traits::StreamAccumulator;
+
+    #[tokio::test]
+    async fn sse_requests_feed_rate_limit_headers_to_the_scheduler() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "30"))
+            .mount(&server)
+            .await;
+
+        let request = reqwest::Client::new().post(server.uri());
+        let events: Vec<_> =
+            send_sse_request("OpenAI", Some("sk-sse-pacing"), request, OpenAiStreamParser)
+                .collect()
+                .await;
+
+        assert_eq!(events.len(), 1);
+        assert!(events[0].is_err());
+        assert!(
+            !crate::providers::quota_scheduler::pending_wait("openai", Some("sk-sse-pacing"))
+                .is_zero()
+        );
+    }
+
+    #[test]
+    fn sse_data_skips_comments_and_event_lines() {
+        assert_eq!(sse_data("data: {\"a\":1}\n"), Some("{\"a\":1}"));
+        assert_eq!(sse_data("data:[DONE]"), Some("[DONE]"));
+        assert_eq!(sse_data(": keep-alive"), None);
+        assert_eq!(sse_data("event: message_start"), None);
+        assert_eq!(sse_data("data: "), None);
+    }
+
+    #[test]
+    fn openai_parser_accumulates_text_and_tool_call_fragments() {
+        let mut parser = OpenAiStreamParser;
+        let payloads = [
+            r#"{"choices":[{"delta":{"role":"assistant","content":"Let me check."}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"shell","arguments":""}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"command\":"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"ls\"}"}}]}}]}"#,
+            r#"{"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":7}}"#,
+        ];
+
+        let mut accumulator = StreamAccumulator::new();
+        for payload in payloads {
+            for event in parser.parse(payload).unwrap() {
+                accumulator.push(&event);
+            }
+        }
+        let response = accumulator.finish();
+
+        assert_eq!(response.text.as_deref(), Some("Let me check."));
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].id, "call_1");
+        assert_eq!(response.tool_calls[0].name, "shell");
+        assert_eq!(response.tool_calls[0].arguments, r#"{"command":"ls"}"#);
+        let usage = response.usage.unwrap();
+        assert_eq!(usage.input_tokens, Some(12));
+        assert_eq!(usage.output_tokens, Some(7));
+    }
+
+    #[test]
+    fn openai_parser_keeps_parallel_calls_apart() {
+        let mut parser = OpenAiStreamParser;
+        let events = parser
+            .parse(
+                r#"{"choices":[{"delta":{"tool_calls":[
+                    {"index":0,"id":"a","function":{"name":"file_read","arguments":"{}"}},
+                    {"index":1,"id":"b","function":{"name":"shell","arguments":"{}"}}
+                ]}}]}"#,
+            )
+            .unwrap();
+
+        let mut accumulator = StreamAccumulator::new();
+        for event in &events {
+            accumulator.push(event);
+        }
+        assert_eq!(accumulator.tool_call_name(1), Some("shell"));
+        let response = accumulator.finish();
+        assert_eq!(response.tool_calls.len(), 2);
+        assert_eq!(response.tool_calls[0].name, "file_read");
+        assert_eq!(response.tool_calls[1].id, "b");
+    }
+
+    #[test]
+    fn openai_parser_rejects_malformed_json() {
+        assert!(OpenAiStreamParser.parse("{not json").is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! Structured output (JSON Schema response format) helpers.
+//!
+//! [`chat_structured`] sends a [`ChatRequest`] carrying a [`ResponseFormat`],
+//! extracts the JSON value from the reply, validates it against the schema and
+//! retries once with the validation errors attached when it does not conform.
+//! Providers map the format to their native mechanism (OpenAI
+//! `response_format`, Gemini `responseSchema`, Ollama `format`, Anthropic tool
+//! forcing) or fall back to prompt-guided instructions.
+
+use super::traits::{ChatMessage, ChatRequest, ChatResponse, ResponseFormat};
+use super::Provider;
+use crate::tools::schema::{SchemaValidator, SchemaViolation};
+use std::fmt::Write;
+
+/// A schema-conforming reply.
+#[derive(Debug, Clone)]
+pub struct StructuredResponse {
+    /// The validated JSON value.
+    pub value: serde_json::Value,
+    /// The provider response that produced `value`.
+    pub response: ChatResponse,
+    /// Whether a corrective retry was needed.
+    pub retried: bool,
+}
+
+impl StructuredResponse {
+    /// Deserialize the validated value into a concrete type.
+    pub fn parse<T: serde::de::DeserializeOwned>(&self) -> anyhow::Result<T> {
+        serde_json::from_value(self.value.clone())
+            .map_err(|e| anyhow::anyhow!("Structured reply does not fit target type: {e}"))
+    }
+}
+
+/// Request a reply matching `format`, validating it and retrying once with
+/// the validation errors attached.
+pub async fn chat_structured(
+    provider: &dyn Provider,
+    messages: &[ChatMessage],
+    format: &ResponseFormat,
+    model: &str,
+    temperature: f64,
+) -> anyhow::Result<StructuredResponse> {
+    let response = provider
+        .chat(
+            ChatRequest {
+                messages,
+                tools: None,
+                response_format: Some(format),
+            },
+            model,
+            temperature,
+        )
+        .await?;
+
+    let problems = match check_reply(&response, format) {
+        Ok(value) => {
+            return Ok(StructuredResponse {
+                value,
+                response,
+                retried: false,
+            })
+        }
+        Err(problems) => problems,
+    };
+
+    tracing::debug!(
+        schema = format.name.as_str(),
+        "Structured reply failed validation, retrying once: {problems}"
+    );
+
+    let mut retry_messages = messages.to_vec();
+    retry_messages.push(ChatMessage::assistant(response.text_or_empty()));
+    retry_messages.push(ChatMessage::user(correction_prompt(format, &problems)));
+
+    let response = provider
+        .chat(
+            ChatRequest {
+                messages: &retry_messages,
+                tools: None,
+                response_format: Some(format),
+            },
+            model,
+            temperature,
+        )
+        .await?;
+
+    match check_reply(&response, format) {
+        Ok(value) => Ok(StructuredResponse {
+            value,
+            response,
+            retried: true,
+        }),
+        Err(problems) => anyhow::bail!(
+            "Structured reply for schema `{}` is still invalid after retry: {problems}",
+            format.name
+        ),
+    }
+}
+
+/// Parse and validate a reply, returning a description of every problem.
+fn check_reply(
+    response: &ChatResponse,
+    format: &ResponseFormat,
+) -> Result<serde_json::Value, String> {
+    let text = response.text_or_empty();
+    let value = extract_json(text).ok_or_else(|| {
+        if text.trim().is_empty() {
+            "the reply was empty".to_string()
+        } else {
+            "the reply did not contain a parseable JSON value".to_string()
+        }
+    })?;
+
+    let violations = SchemaValidator::validate(&format.schema, &value);
+    if violations.is_empty() {
+        Ok(value)
+    } else {
+        Err(render_violations(&violations))
+    }
+}
+
+fn render_violations(violations: &[SchemaViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn correction_prompt(format: &ResponseFormat, problems: &str) -> String {
+    let mut prompt = String::new();
+    let _ = writeln!(
+        prompt,
+        "Your previous reply does not match the required JSON schema `{}`.",
+        format.name
+    );
+    let _ = writeln!(prompt, "Problems: {problems}");
+    prompt.push_str("Reply again with only the corrected JSON value.");
+    prompt
+}
+
+/// Extract a JSON value from model output, tolerating Markdown code fences
+/// and leading/trailing prose.
+pub fn extract_json(text: &str) -> Option<serde_json::Value> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    if let Some(fenced) = strip_code_fence(trimmed) {
+        if let Ok(value) = serde_json::from_str(fenced) {
+            return Some(value);
+        }
+    }
+
+    // Fall back to the first balanced object or array in the text.
+    for (start, open) in trimmed
+        .char_indices()
+        .filter(|(_, c)| matches!(c, '{' | '['))
+    {
+        let candidate = &trimmed[start..];
+        let mut stream = serde_json::Deserializer::from_str(candidate).into_iter();
+        if let Some(Ok(value)) = stream.next() {
+            let value: serde_json::Value = value;
+            if (open == '{' && value.is_object()) || (open == '[' && value.is_array()) {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
+
+fn strip_code_fence(text: &str) -> Option<&str> {
+    let start = text.find("```")?;
+    let after = &text[start + 3..];
+    let body_start = after.find('\n')? + 1;
+    let body = &after[body_start..];
+    let end = body.find("```")?;
+    Some(body[..end].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+    use serde_json::json;
+
+    struct ScriptedProvider {
+        replies: Mutex<Vec<&'static str>>,
+        seen: Mutex<Vec<Vec<ChatMessage>>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(replies: Vec<&'static str>) -> Self {
+            Self {
+                replies: Mutex::new(replies),
+                seen: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            unreachable!("chat_with_history is overridden")
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.seen.lock().push(messages.to_vec());
+            Ok(self.replies.lock().remove(0).to_string())
+        }
+    }
+
+    fn verdict_format() -> ResponseFormat {
+        ResponseFormat::json_schema(
+            "verdict",
+            json!({
+                "type": "object",
+                "properties": {"success": {"type": "boolean"}, "reason": {"type": "string"}},
+                "required": ["success", "reason"],
+                "additionalProperties": false
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn valid_reply_is_returned_without_retry() {
+        let provider = ScriptedProvider::new(vec![r#"{"success": true, "reason": "ok"}"#]);
+        let result = chat_structured(
+            &provider,
+            &[ChatMessage::user("did it work?")],
+            &verdict_format(),
+            "model",
+            0.0,
+        )
+        .await
+        .unwrap();
+
+        assert!(!result.retried);
+        assert_eq!(result.value["success"], true);
+        let seen = provider.seen.lock();
+        assert!(seen[0][0].content.contains("## Response Format"));
+    }
+
+    #[tokio::test]
+    async fn invalid_reply_is_retried_with_validation_errors() {
+        let provider = ScriptedProvider::new(vec![
+            r#"{"success": "yes"}"#,
+            "```json\n{\"success\": false, \"reason\": \"disk full\"}\n```",
+        ]);
+        let result = chat_structured(
+            &provider,
+            &[ChatMessage::user("did it work?")],
+            &verdict_format(),
+            "model",
+            0.0,
+        )
+        .await
+        .unwrap();
+
+        assert!(result.retried);
+        assert_eq!(result.value["reason"], "disk full");
+        let seen = provider.seen.lock();
+        let correction = &seen[1].last().unwrap().content;
+        assert!(correction.contains("`$`: missing required property `reason`"));
+        assert!(correction.contains("`$.success`: expected boolean, got string"));
+    }
+
+    #[tokio::test]
+    async fn second_invalid_reply_is_an_error() {
+        let provider = ScriptedProvider::new(vec!["no idea", "still no idea"]);
+        let err = chat_structured(
+            &provider,
+            &[ChatMessage::user("did it work?")],
+            &verdict_format(),
+            "model",
+            0.0,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("still invalid after retry"));
+    }
+
+    #[test]
+    fn extract_json_handles_prose_and_fences() {
+        assert_eq!(extract_json(r#"{"a":1}"#), Some(json!({"a": 1})));
+        assert_eq!(
+            extract_json("Here you go:\n```json\n{\"a\": 2}\n```"),
+            Some(json!({"a": 2}))
+        );
+        assert_eq!(
+            extract_json("Result: {\"a\": [1, 2]} — done"),
+            Some(json!({"a": [1, 2]}))
+        );
+        assert_eq!(extract_json("nothing here"), None);
+    }
+}
@@ -50,18 +50,28 @@ pub struct ToolCall {
 }
 
 /// Raw token counts from a single LLM API response.
-#[derive(Debug, Clone, Default)]
+///
+/// `input_tokens` covers the whole prompt; the cache counts are the parts of
+/// it that were read from or written to the provider's prompt cache.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TokenUsage {
     pub input_tokens: Option<u64>,
     pub output_tokens: Option<u64>,
+    /// Prompt tokens served from the provider's prompt cache.
+    #[serde(default)]
+    pub cache_read_tokens: Option<u64>,
+    /// Prompt tokens written to the provider's prompt cache.
+    #[serde(default)]
+    pub cache_write_tokens: Option<u64>,
 }
 
 /// An LLM response that may contain text, tool calls, or both.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatResponse {
     /// Text content of the response (may be empty if only tool calls).
     pub text: Option<String>,
     /// Tool calls requested by the LLM.
+    #[serde(default)]
     pub tool_calls: Vec<ToolCall>,
     /// Token usage reported by the provider, if available.
     pub usage: Option<TokenUsage>,
@@ -70,6 +80,38 @@ pub struct ChatResponse {
     /// sent back in subsequent API requests — some providers reject tool-call
     /// history that omits this field.
     pub reasoning_content: Option<String>,
+    /// Structured thinking blocks from extended-thinking models (Anthropic,
+    /// Bedrock Claude, Gemini). Must be echoed back unchanged, signatures
+    /// included, on the assistant turn that carries the matching tool calls.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thinking: Vec<ThinkingBlock>,
+}
+
+/// One reasoning block produced by an extended-thinking model.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThinkingBlock {
+    /// Reasoning text; empty for redacted blocks.
+    #[serde(default)]
+    pub text: String,
+    /// Opaque signature the provider verifies when the block is sent back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Encrypted payload of a redacted block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redacted: Option<String>,
+}
+
+impl ThinkingBlock {
+    /// Join the readable text of `blocks`, or `None` when there is none.
+    pub fn joined_text(blocks: &[ThinkingBlock]) -> Option<String> {
+        let text = blocks
+            .iter()
+            .map(|block| block.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        (!text.is_empty()).then_some(text)
+    }
 }
 
 impl ChatResponse {
@@ -89,6 +131,46 @@ impl ChatResponse {
 pub struct ChatRequest<'a> {
     pub messages: &'a [ChatMessage],
     pub tools: Option<&'a [ToolSpec]>,
+    /// Ask for a reply matching a JSON Schema. Providers with native support
+    /// enforce it server-side; the rest fall back to prompt-guided instructions.
+    pub response_format: Option<&'a ResponseFormat>,
+}
+
+/// JSON Schema the model's reply must conform to (structured output).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseFormat {
+    /// Short identifier for the schema (OpenAI `json_schema.name`, Anthropic
+    /// forced tool name). Must match `^[a-zA-Z0-9_-]{1,64}$`.
+    pub name: String,
+    /// The JSON Schema itself.
+    pub schema: serde_json::Value,
+    /// Request strict schema adherence where the provider supports it.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl ResponseFormat {
+    /// Create a strict JSON Schema response format.
+    pub fn json_schema(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        Self {
+            name: name.into(),
+            schema,
+            strict: true,
+        }
+    }
+
+    /// Instructions used when the provider cannot enforce the schema natively.
+    pub fn prompt_instructions(&self) -> String {
+        let schema =
+            serde_json::to_string_pretty(&self.schema).unwrap_or_else(|_| "{}".to_string());
+        format!(
+            "## Response Format\n\n\
+             Reply with a single JSON value that conforms to the JSON Schema below. \
+             Output only the JSON: no prose, no explanations, no Markdown code fences.\n\n\
+             Schema `{}`:\n{schema}\n",
+            self.name
+        )
+    }
 }
 
 /// A tool result to feed back to the LLM.
@@ -111,6 +193,9 @@ pub enum ConversationMessage {
         /// Raw reasoning content from thinking models, preserved for round-trip
         /// fidelity with provider APIs that require it.
         reasoning_content: Option<String>,
+        /// Signed thinking blocks that must precede the tool calls when replayed.
+        #[serde(default)]
+        thinking: Vec<ThinkingBlock>,
     },
     /// Results of tool executions, fed back to the LLM.
     ToolResults(Vec<ToolResultMessage>),
@@ -162,6 +247,168 @@ impl StreamChunk {
     }
 }
 
+/// An incremental event from [`Provider::stream_chat`].
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    /// Text content delta.
+    TextDelta(String),
+    /// Fragment of a native tool call.
+    ToolCallDelta(ToolCallDelta),
+    /// Fragment of a thinking block.
+    ThinkingDelta(ThinkingDelta),
+    /// Token usage, usually reported once near the end of the stream.
+    Usage(TokenUsage),
+    /// The response is complete.
+    Done,
+}
+
+/// Fragment of a native tool call being generated.
+///
+/// Fragments with the same `index` belong to the same call. `id` and `name`
+/// arrive on the first fragment; `arguments` carries the next piece of the
+/// JSON-encoded arguments and is concatenated in order.
+#[derive(Debug, Clone, Default)]
+pub struct ToolCallDelta {
+    /// Position of the call within the response.
+    pub index: usize,
+    /// Provider-assigned call id.
+    pub id: Option<String>,
+    /// Tool name.
+    pub name: Option<String>,
+    /// Next fragment of the JSON-encoded arguments.
+    pub arguments: String,
+}
+
+/// Fragment of a thinking block being generated.
+///
+/// Fragments with the same `index` belong to the same block; `text` is
+/// appended in order while `signature` and `redacted` replace earlier values.
+#[derive(Debug, Clone, Default)]
+pub struct ThinkingDelta {
+    /// Position of the block among the response's thinking blocks.
+    pub index: usize,
+    /// Next piece of reasoning text.
+    pub text: String,
+    /// Block signature, usually sent once when the block closes.
+    pub signature: Option<String>,
+    /// Encrypted payload of a redacted block.
+    pub redacted: Option<String>,
+}
+
+/// Folds [`StreamEvent`]s into a complete [`ChatResponse`].
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    text: String,
+    tool_calls: std::collections::BTreeMap<usize, ToolCall>,
+    thinking: std::collections::BTreeMap<usize, ThinkingBlock>,
+    usage: Option<TokenUsage>,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply one event.
+    pub fn push(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::TextDelta(text) => self.text.push_str(text),
+            StreamEvent::ToolCallDelta(delta) => {
+                let call = self
+                    .tool_calls
+                    .entry(delta.index)
+                    .or_insert_with(|| ToolCall {
+                        id: String::new(),
+                        name: String::new(),
+                        arguments: String::new(),
+                    });
+                if let Some(id) = delta.id.as_deref().filter(|id| !id.is_empty()) {
+                    call.id = id.to_string();
+                }
+                if let Some(name) = delta.name.as_deref().filter(|name| !name.is_empty()) {
+                    call.name = name.to_string();
+                }
+                call.arguments.push_str(&delta.arguments);
+            }
+            StreamEvent::ThinkingDelta(delta) => {
+                let block = self.thinking.entry(delta.index).or_default();
+                block.text.push_str(&delta.text);
+                if delta.signature.is_some() {
+                    block.signature.clone_from(&delta.signature);
+                }
+                if delta.redacted.is_some() {
+                    block.redacted.clone_from(&delta.redacted);
+                }
+            }
+            StreamEvent::Usage(usage) => self.usage = Some(usage.clone()),
+            StreamEvent::Done => {}
+        }
+    }
+
+    /// Name of the tool call at `index`, once known.
+    pub fn tool_call_name(&self, index: usize) -> Option<&str> {
+        self.tool_calls
+            .get(&index)
+            .map(|call| call.name.as_str())
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Build the final response. Calls without a name are dropped; missing ids
+    /// are generated and empty arguments become `{}`.
+    pub fn finish(self) -> ChatResponse {
+        let tool_calls = self
+            .tool_calls
+            .into_values()
+            .filter(|call| !call.name.is_empty())
+            .map(|mut call| {
+                if call.id.is_empty() {
+                    call.id = uuid::Uuid::new_v4().to_string();
+                }
+                if call.arguments.trim().is_empty() {
+                    call.arguments = "{}".to_string();
+                }
+                call
+            })
+            .collect();
+        let thinking: Vec<ThinkingBlock> = self.thinking.into_values().collect();
+        ChatResponse {
+            text: if self.text.is_empty() {
+                None
+            } else {
+                Some(self.text)
+            },
+            tool_calls,
+            usage: self.usage,
+            reasoning_content: ThinkingBlock::joined_text(&thinking),
+            thinking,
+        }
+    }
+}
+
+/// Map a text chunk stream onto [`StreamEvent`]s.
+pub fn text_chunk_events(
+    chunks: stream::BoxStream<'static, StreamResult<StreamChunk>>,
+) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+    chunks
+        .flat_map(|chunk| {
+            let events = match chunk {
+                Ok(chunk) => {
+                    let mut events = Vec::with_capacity(2);
+                    if !chunk.delta.is_empty() {
+                        events.push(Ok(StreamEvent::TextDelta(chunk.delta)));
+                    }
+                    if chunk.is_final {
+                        events.push(Ok(StreamEvent::Done));
+                    }
+                    events
+                }
+                Err(e) => vec![Err(e)],
+            };
+            stream::iter(events)
+        })
+        .boxed()
+}
+
 /// Options for streaming chat requests.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct StreamOptions {
@@ -328,6 +575,17 @@ pub trait Provider: Send + Sync {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
+        // The default path has no native structured output: a requested
+        // response format is expressed as prompt instructions.
+        let formatted_messages;
+        let messages = match request.response_format {
+            Some(format) => {
+                formatted_messages = with_response_format_instructions(request.messages, format);
+                formatted_messages.as_slice()
+            }
+            None => request.messages,
+        };
+
         // If tools are provided but provider doesn't support native tools,
         // inject tool instructions into system prompt as fallback.
         if let Some(tools) = request.tools {
@@ -340,7 +598,7 @@ pub trait Provider: Send + Sync {
                         )
                     }
                 };
-                let mut modified_messages = request.messages.to_vec();
+                let mut modified_messages = messages.to_vec();
 
                 // Inject tool instructions into an existing system message.
                 // If none exists, prepend one to the conversation.
@@ -363,18 +621,18 @@ pub trait Provider: Send + Sync {
                     tool_calls: Vec::new(),
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 });
             }
         }
 
-        let text = self
-            .chat_with_history(request.messages, model, temperature)
-            .await?;
+        let text = self.chat_with_history(messages, model, temperature).await?;
         Ok(ChatResponse {
             text: Some(text),
             tool_calls: Vec::new(),
             usage: None,
             reasoning_content: None,
+            thinking: Vec::new(),
         })
     }
 
@@ -388,6 +646,12 @@ pub trait Provider: Send + Sync {
         self.capabilities().vision
     }
 
+    /// Whether provider enforces `ChatRequest::response_format` natively
+    /// (API-level JSON Schema or tool forcing) rather than via prompt text.
+    fn supports_structured_output(&self) -> bool {
+        false
+    }
+
     /// Warm up the HTTP connection pool (TLS handshake, DNS, HTTP/2 setup).
     /// Default implementation is a no-op; providers with HTTP clients should override.
     async fn warmup(&self) -> anyhow::Result<()> {
@@ -410,6 +674,7 @@ pub trait Provider: Send + Sync {
             tool_calls: Vec::new(),
             usage: None,
             reasoning_content: None,
+            thinking: Vec::new(),
         })
     }
 
@@ -455,6 +720,59 @@ pub trait Provider: Send + Sync {
             .unwrap_or("");
         self.stream_chat_with_system(system, last_user, model, temperature, options)
     }
+
+    /// Whether `stream_chat` streams native tool calls incrementally.
+    /// Default implementation returns false.
+    fn supports_tool_call_streaming(&self) -> bool {
+        false
+    }
+
+    /// Streaming counterpart of [`Provider::chat`].
+    ///
+    /// Yields text deltas and tool-call fragments as they are generated.
+    /// Default implementation streams text via `stream_chat_with_history` and
+    /// rejects requests carrying tools.
+    fn stream_chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamEvent>> {
+        if request.tools.is_some_and(|tools| !tools.is_empty()) {
+            return stream::once(async {
+                Err(StreamError::Provider(
+                    "Provider does not support streaming tool calls".to_string(),
+                ))
+            })
+            .boxed();
+        }
+        text_chunk_events(self.stream_chat_with_history(
+            request.messages,
+            model,
+            temperature,
+            options,
+        ))
+    }
+}
+
+/// Append response-format instructions to the system prompt, prepending a
+/// system message when the conversation has none.
+pub fn with_response_format_instructions(
+    messages: &[ChatMessage],
+    format: &ResponseFormat,
+) -> Vec<ChatMessage> {
+    let instructions = format.prompt_instructions();
+    let mut modified_messages = messages.to_vec();
+    if let Some(system_message) = modified_messages.iter_mut().find(|m| m.role == "system") {
+        if !system_message.content.is_empty() {
+            system_message.content.push_str("\n\n");
+        }
+        system_message.content.push_str(&instructions);
+    } else {
+        modified_messages.insert(0, ChatMessage::system(instructions));
+    }
+    modified_messages
 }
 
 /// Build tool instructions text for prompt-guided tool calling.
@@ -539,6 +857,7 @@ mod tests {
             tool_calls: vec![],
             usage: None,
             reasoning_content: None,
+            thinking: Vec::new(),
         };
         assert!(!empty.has_tool_calls());
         assert_eq!(empty.text_or_empty(), "");
@@ -552,6 +871,7 @@ mod tests {
             }],
             usage: None,
             reasoning_content: None,
+            thinking: Vec::new(),
         };
         assert!(with_tools.has_tool_calls());
         assert_eq!(with_tools.text_or_empty(), "Let me check");
@@ -572,8 +892,11 @@ mod tests {
             usage: Some(TokenUsage {
                 input_tokens: Some(100),
                 output_tokens: Some(50),
+                cache_read_tokens: None,
+                cache_write_tokens: None,
             }),
             reasoning_content: None,
+            thinking: Vec::new(),
         };
         assert_eq!(resp.usage.as_ref().unwrap().input_tokens, Some(100));
         assert_eq!(resp.usage.as_ref().unwrap().output_tokens, Some(50));
@@ -784,6 +1107,7 @@ mod tests {
         let request = ChatRequest {
             messages: &[ChatMessage::user("Hello")],
             tools: Some(&tools),
+            response_format: None,
         };
 
         let response = provider.chat(request, "model", 0.7).await.unwrap();
@@ -801,6 +1125,7 @@ mod tests {
         let request = ChatRequest {
             messages: &[ChatMessage::user("Hello")],
             tools: None,
+            response_format: None,
         };
 
         let response = provider.chat(request, "model", 0.7).await.unwrap();
@@ -901,6 +1226,7 @@ mod tests {
                 ChatMessage::system("BASE_SYSTEM_PROMPT"),
             ],
             tools: Some(&tools),
+            response_format: None,
         };
 
         let response = provider.chat(request, "model", 0.7).await.unwrap();
@@ -923,6 +1249,7 @@ mod tests {
         let request = ChatRequest {
             messages: &[ChatMessage::system("BASE"), ChatMessage::user("Hello")],
             tools: Some(&tools),
+            response_format: None,
         };
 
         let response = provider.chat(request, "model", 0.7).await.unwrap();
@@ -945,6 +1272,7 @@ mod tests {
         let request = ChatRequest {
             messages: &[ChatMessage::user("Hello")],
             tools: Some(&tools),
+            response_format: None,
         };
 
         let err = provider.chat(request, "model", 0.7).await.unwrap_err();
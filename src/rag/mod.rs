@@ -4,9 +4,11 @@
 //! - Markdown and text datasheets (always)
 //! - PDF ingestion (with `rag-pdf` feature)
 //! - Pin/alias tables (e.g. `red_led: 13`) for explicit lookup
-//! - Keyword retrieval (default) or semantic search via embeddings (optional)
+//! - Keyword retrieval (default) or semantic search via offline local embeddings (optional)
 
 use crate::memory::chunker;
+use crate::memory::embeddings::LocalEmbedding;
+use crate::memory::vector::cosine_similarity;
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -143,8 +145,15 @@ pub struct HardwareRag {
     chunks: Vec<DatasheetChunk>,
     /// Per-board pin aliases (board -> alias -> pin).
     pin_aliases: HashMap<String, PinAliases>,
+    /// Optional embedder plus one vector per chunk (same order as `chunks`).
+    semantic: Option<(LocalEmbedding, Vec<Vec<f32>>)>,
 }
 
+/// Minimum cosine similarity for a chunk to match on embeddings alone.
+const SEMANTIC_MATCH_THRESHOLD: f32 = 0.12;
+/// Weight of the cosine similarity relative to one matched keyword.
+const SEMANTIC_WEIGHT: f32 = 3.0;
+
 impl HardwareRag {
     /// Load datasheets from a directory. Expects .md, .txt, and optionally .pdf (with rag-pdf).
     /// Filename (without extension) is used as board tag.
@@ -155,6 +164,7 @@ impl HardwareRag {
             return Ok(Self {
                 chunks: Vec::new(),
                 pin_aliases: HashMap::new(),
+                semantic: None,
             });
         }
 
@@ -212,9 +222,24 @@ impl HardwareRag {
         Ok(Self {
             chunks,
             pin_aliases,
+            semantic: None,
         })
     }
 
+    /// Index chunks with offline local embeddings of `dimensions` size (`0`
+    /// uses the default) so retrieval also ranks by vector similarity, not
+    /// only exact keyword hits.
+    pub fn with_local_embeddings(mut self, dimensions: usize) -> Self {
+        let embedder = LocalEmbedding::new(dimensions);
+        let vectors = self
+            .chunks
+            .iter()
+            .map(|chunk| embedder.embed_text(&chunk.content))
+            .collect();
+        self.semantic = Some((embedder, vectors));
+        self
+    }
+
     /// Get pin aliases for a board (e.g. "red_led" -> 13).
     pub fn pin_aliases_for_board(&self, board: &str) -> Option<&PinAliases> {
         self.pin_aliases.get(board)
@@ -248,7 +273,8 @@ impl HardwareRag {
     }
 
     /// Retrieve chunks relevant to the query and boards.
-    /// Uses keyword matching and board filter. Pin-alias context is built separately via `pin_alias_context`.
+    /// Uses keyword matching (plus embedding similarity when indexed) and board filter.
+    /// Pin-alias context is built separately via `pin_alias_context`.
     pub fn retrieve(&self, query: &str, boards: &[String], limit: usize) -> Vec<&DatasheetChunk> {
         if self.chunks.is_empty() || limit == 0 {
             return Vec::new();
@@ -260,8 +286,13 @@ impl HardwareRag {
             .filter(|w| w.len() > 2)
             .collect();
 
+        let query_vector = self
+            .semantic
+            .as_ref()
+            .map(|(embedder, _)| embedder.embed_text(query));
+
         let mut scored: Vec<(&DatasheetChunk, f32)> = Vec::new();
-        for chunk in &self.chunks {
+        for (index, chunk) in self.chunks.iter().enumerate() {
             let content_lower = chunk.content.to_lowercase();
             let mut score = 0.0f32;
 
@@ -271,6 +302,13 @@ impl HardwareRag {
                 }
             }
 
+            if let (Some(query_vector), Some((_, vectors))) = (&query_vector, &self.semantic) {
+                let similarity = cosine_similarity(query_vector, &vectors[index]);
+                if similarity >= SEMANTIC_MATCH_THRESHOLD || score > 0.0 {
+                    score += similarity * SEMANTIC_WEIGHT;
+                }
+            }
+
             if score > 0.0 {
                 let board_match = chunk.board.as_ref().map_or(false, |b| boards.contains(b));
                 if board_match {
@@ -392,4 +430,24 @@ Pin 13: LED
         let rag = HardwareRag::load(tmp.path(), "empty_ds").unwrap();
         assert!(rag.is_empty());
     }
+
+    #[test]
+    fn hardware_rag_local_embeddings_match_word_variants() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path().join("datasheets");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::write(
+            base.join("test-board.md"),
+            "# Timers\nConfigure the PWM timer to blink the onboard LED.\n",
+        )
+        .unwrap();
+
+        let rag = HardwareRag::load(tmp.path(), "datasheets").unwrap();
+        assert!(rag.retrieve("blinking leds", &[], 5).is_empty());
+
+        let rag = rag.with_local_embeddings(256);
+        let chunks = rag.retrieve("blinking leds", &[], 5);
+        assert_eq!(chunks.len(), 1);
+        assert!(rag.retrieve("wifi antenna", &[], 5).is_empty());
+    }
 }
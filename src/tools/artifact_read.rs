@@ -0,0 +1,517 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::AgentConfig;
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Tool that reads spilled outputs back; never spilled itself.
+const ARTIFACT_READ_TOOL_NAME: &str = "artifact_read";
+/// Hex characters of the content hash used as artifact id.
+const ARTIFACT_ID_LEN: usize = 12;
+/// Default and maximum lines returned per page.
+const DEFAULT_PAGE_LINES: usize = 200;
+const MAX_PAGE_LINES: usize = 1_000;
+/// Upper bound on characters returned by a single read.
+const MAX_READ_CHARS: usize = 12_000;
+/// Maximum context lines around each grep match.
+const MAX_GREP_CONTEXT: usize = 5;
+
+static ARTIFACT_HANDLE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:artifact:)?([0-9a-f]{12})$").unwrap());
+
+static ARTIFACT_MARKER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[(IMAGE|DOCUMENT|VIDEO|AUDIO|VOICE):\s*artifact:([0-9a-f]{12})\s*\]").unwrap()
+});
+
+/// Workspace directory holding tool-output artifacts (`<workspace>/artifacts`).
+pub fn artifacts_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join("artifacts")
+}
+
+fn artifact_path(workspace_dir: &Path, id: &str) -> PathBuf {
+    artifacts_dir(workspace_dir).join(format!("{id}.txt"))
+}
+
+/// Replace `[DOCUMENT:artifact:<id>]`-style attachment markers with the
+/// artifact's absolute path so channels can upload it. Unknown handles are
+/// left untouched.
+pub fn resolve_artifact_markers(text: &str, workspace_dir: &Path) -> String {
+    if !text.contains("artifact:") {
+        return text.to_string();
+    }
+    ARTIFACT_MARKER_REGEX
+        .replace_all(text, |caps: &regex::Captures<'_>| {
+            let path = artifact_path(workspace_dir, &caps[2]);
+            if path.is_file() {
+                format!("[{}:{}]", &caps[1], path.display())
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Spill policy for large tool outputs, built from `[agent]` settings.
+#[derive(Debug, Clone)]
+pub struct ToolOutputSpill {
+    workspace_dir: PathBuf,
+    threshold_chars: usize,
+    preview_chars: usize,
+    max_artifacts: usize,
+}
+
+impl ToolOutputSpill {
+    /// `None` when spilling is disabled (`tool_output_spill_chars = 0`).
+    pub fn from_config(config: &AgentConfig, workspace_dir: &Path) -> Option<Self> {
+        (config.tool_output_spill_chars > 0).then(|| Self {
+            workspace_dir: workspace_dir.to_path_buf(),
+            threshold_chars: config.tool_output_spill_chars,
+            preview_chars: config.tool_output_preview_chars,
+            max_artifacts: config.tool_output_artifact_limit.max(1),
+        })
+    }
+
+    /// Store `output` as an artifact when it exceeds the threshold and return
+    /// the preview that replaces it in context. `None` leaves the output as is.
+    pub fn apply(&self, tool_name: &str, output: &str) -> Option<String> {
+        if tool_name == ARTIFACT_READ_TOOL_NAME {
+            return None;
+        }
+        let total_chars = output.chars().count();
+        if total_chars <= self.threshold_chars {
+            return None;
+        }
+
+        let id = match self.store(output) {
+            Ok(id) => id,
+            Err(err) => {
+                tracing::warn!(
+                    tool = tool_name,
+                    "Failed to store tool output artifact: {err}"
+                );
+                return None;
+            }
+        };
+
+        let head_chars = self.preview_chars * 2 / 3;
+        let tail_chars = self.preview_chars - head_chars;
+        let head: String = output.chars().take(head_chars).collect();
+        let mut tail: Vec<char> = output.chars().rev().take(tail_chars).collect();
+        tail.reverse();
+        let tail: String = tail.into_iter().collect();
+
+        let mut preview = format!(
+            "[Output of `{tool_name}` was too large ({total_chars} chars, {} lines) and is stored as artifact:{id}]\n",
+            output.lines().count()
+        );
+        if !head.is_empty() {
+            let _ = write!(preview, "--- head ---\n{head}\n");
+        }
+        if !tail.is_empty() {
+            let _ = write!(preview, "--- tail ---\n{tail}\n");
+        }
+        let _ = write!(
+            preview,
+            "[Use `artifact_read` with handle \"artifact:{id}\" to page (offset/limit), grep (pattern) or slice (start/end) the full output. Attach it to a channel reply with [DOCUMENT:artifact:{id}].]"
+        );
+        Some(preview)
+    }
+
+    /// Write `output` under its content hash, so repeated outputs share one
+    /// artifact and one handle, then prune the oldest artifacts.
+    fn store(&self, output: &str) -> std::io::Result<String> {
+        let digest = hex::encode(Sha256::digest(output.as_bytes()));
+        let id = digest[..ARTIFACT_ID_LEN].to_string();
+        let dir = artifacts_dir(&self.workspace_dir);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(artifact_path(&self.workspace_dir, &id), output)?;
+        prune_artifacts(&dir, self.max_artifacts);
+        Ok(id)
+    }
+}
+
+fn prune_artifacts(dir: &Path, max_artifacts: usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+    if files.len() <= max_artifacts {
+        return;
+    }
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in files.into_iter().skip(max_artifacts) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Page, grep or slice tool outputs that were spilled to artifacts.
+pub struct ArtifactReadTool {
+    workspace_dir: PathBuf,
+}
+
+impl ArtifactReadTool {
+    pub fn new(workspace_dir: PathBuf) -> Self {
+        Self { workspace_dir }
+    }
+
+    fn failure(error: String) -> ToolResult {
+        ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(error),
+        }
+    }
+
+    fn page(id: &str, content: &str, args: &serde_json::Value) -> String {
+        let lines: Vec<&str> = content.lines().collect();
+        let offset = usize_arg(args, "offset").unwrap_or(1).max(1);
+        let limit = usize_arg(args, "limit")
+            .unwrap_or(DEFAULT_PAGE_LINES)
+            .clamp(1, MAX_PAGE_LINES);
+        if offset > lines.len() {
+            return format!(
+                "artifact:{id} has {} lines; offset {offset} is past the end.",
+                lines.len()
+            );
+        }
+
+        let end = (offset - 1 + limit).min(lines.len());
+        let mut body = String::new();
+        let mut last = offset - 1;
+        for (index, line) in lines[offset - 1..end].iter().enumerate() {
+            if body.len() + line.len() > MAX_READ_CHARS {
+                if body.is_empty() {
+                    let _ = writeln!(
+                        body,
+                        "{:>6}  {} [line truncated; use start/end to slice]",
+                        offset + index,
+                        crate::util::truncate_with_ellipsis(line, MAX_READ_CHARS)
+                    );
+                    last = offset + index;
+                }
+                break;
+            }
+            let _ = writeln!(body, "{:>6}  {line}", offset + index);
+            last = offset + index;
+        }
+        let mut output = format!("artifact:{id} lines {offset}-{last} of {}\n", lines.len());
+        output.push_str(&body);
+        if last < lines.len() {
+            let _ = write!(output, "[more: continue with offset {}]", last + 1);
+        }
+        output
+    }
+
+    fn grep(id: &str, content: &str, pattern: &Regex, args: &serde_json::Value) -> String {
+        let lines: Vec<&str> = content.lines().collect();
+        let context = usize_arg(args, "context")
+            .unwrap_or(0)
+            .min(MAX_GREP_CONTEXT);
+        let max_matches = usize_arg(args, "limit")
+            .unwrap_or(DEFAULT_PAGE_LINES)
+            .clamp(1, MAX_PAGE_LINES);
+
+        let matches: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| pattern.is_match(line))
+            .map(|(index, _)| index)
+            .collect();
+        let mut output = format!(
+            "{} matching line(s) for /{}/ in artifact:{id}\n",
+            matches.len(),
+            pattern.as_str()
+        );
+
+        let mut printed_until = 0usize;
+        let mut shown = 0usize;
+        for &index in matches.iter().take(max_matches) {
+            let start = index.saturating_sub(context).max(printed_until);
+            let end = (index + context + 1).min(lines.len());
+            if context > 0 && start > printed_until && printed_until > 0 {
+                output.push_str("--\n");
+            }
+            for (offset, line) in lines[start..end].iter().enumerate() {
+                let number = start + offset + 1;
+                let marker = if start + offset == index { ':' } else { '-' };
+                let _ = writeln!(output, "{number:>6}{marker} {line}");
+            }
+            printed_until = end;
+            shown += 1;
+            if output.len() > MAX_READ_CHARS {
+                break;
+            }
+        }
+        if shown < matches.len() {
+            let _ = write!(
+                output,
+                "[{} more match(es) not shown; narrow the pattern or raise limit]",
+                matches.len() - shown
+            );
+        }
+        output
+    }
+
+    fn slice(id: &str, content: &str, args: &serde_json::Value) -> String {
+        let total = content.chars().count();
+        let start = usize_arg(args, "start").unwrap_or(0).min(total);
+        let end = usize_arg(args, "end")
+            .unwrap_or(start + MAX_READ_CHARS)
+            .clamp(start, total)
+            .min(start + MAX_READ_CHARS);
+        let text: String = content.chars().skip(start).take(end - start).collect();
+        format!("artifact:{id} chars {start}..{end} of {total}\n{text}")
+    }
+}
+
+fn usize_arg(args: &serde_json::Value, key: &str) -> Option<usize> {
+    args.get(key)
+        .and_then(serde_json::Value::as_u64)
+        .and_then(|value| usize::try_from(value).ok())
+}
+
+#[async_trait]
+impl Tool for ArtifactReadTool {
+    fn name(&self) -> &str {
+        ARTIFACT_READ_TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Read a large tool output that was stored as an artifact (handle `artifact:<id>`). \
+         Pages by line (offset/limit), greps with a regex (pattern, optional context), \
+         or slices by character range (start/end)."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "handle": {
+                    "type": "string",
+                    "description": "Artifact handle, e.g. \"artifact:3f9a1c2b7d4e\""
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "1-based first line to return when paging (default: 1)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Lines per page, or maximum matches when grepping (default: 200, max: 1000)"
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Regex; return only matching lines with their line numbers"
+                },
+                "context": {
+                    "type": "integer",
+                    "description": "Lines of context around each match (default: 0, max: 5)"
+                },
+                "start": {
+                    "type": "integer",
+                    "description": "Character offset to slice from (slicing mode)"
+                },
+                "end": {
+                    "type": "integer",
+                    "description": "Character offset to slice to, exclusive (slicing mode)"
+                }
+            },
+            "required": ["handle"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let handle = args
+            .get("handle")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'handle' parameter"))?
+            .trim();
+
+        let Some(caps) = ARTIFACT_HANDLE_REGEX.captures(handle) else {
+            return Ok(Self::failure(format!(
+                "Invalid artifact handle: {handle} (expected artifact:<12 hex chars>)"
+            )));
+        };
+        let id = &caps[1];
+
+        let content = match tokio::fs::read_to_string(artifact_path(&self.workspace_dir, id)).await
+        {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::failure(format!(
+                    "Artifact not found: artifact:{id} (it may have been pruned)"
+                )));
+            }
+            Err(e) => return Err(anyhow::anyhow!("Failed to read artifact: {e}")),
+        };
+
+        let output = if let Some(pattern) = args.get("pattern").and_then(|v| v.as_str()) {
+            match Regex::new(pattern) {
+                Ok(regex) => Self::grep(id, &content, &regex, &args),
+                Err(e) => return Ok(Self::failure(format!("Invalid pattern: {e}"))),
+            }
+        } else if args.get("start").is_some() || args.get("end").is_some() {
+            Self::slice(id, &content, &args)
+        } else {
+            Self::page(id, &content, &args)
+        };
+
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spill(dir: &Path, threshold: usize) -> ToolOutputSpill {
+        let config = AgentConfig {
+            tool_output_spill_chars: threshold,
+            tool_output_preview_chars: 30,
+            tool_output_artifact_limit: 2,
+            ..AgentConfig::default()
+        };
+        ToolOutputSpill::from_config(&config, dir).unwrap()
+    }
+
+    fn numbered_lines(count: usize) -> String {
+        (1..=count)
+            .map(|n| format!("line {n}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn handle_of(preview: &str) -> String {
+        let start = preview.find("artifact:").unwrap();
+        preview[start..start + "artifact:".len() + ARTIFACT_ID_LEN].to_string()
+    }
+
+    #[test]
+    fn small_outputs_and_artifact_reads_are_not_spilled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let spill = spill(tmp.path(), 100);
+        assert!(spill.apply("shell", "short").is_none());
+        assert!(spill.apply("artifact_read", &"x".repeat(500)).is_none());
+        assert!(!artifacts_dir(tmp.path()).exists());
+        assert!(ToolOutputSpill::from_config(
+            &AgentConfig {
+                tool_output_spill_chars: 0,
+                ..AgentConfig::default()
+            },
+            tmp.path()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn large_output_is_stored_under_a_stable_handle() {
+        let tmp = tempfile::tempdir().unwrap();
+        let spill = spill(tmp.path(), 100);
+        let output = numbered_lines(500);
+
+        let preview = spill.apply("shell", &output).unwrap();
+        assert!(preview.contains("500 lines"));
+        assert!(preview.starts_with("[Output of `shell` was too large"));
+        assert!(preview.contains("line 1\n"));
+        assert!(preview.contains("line 500"));
+        assert_eq!(spill.apply("shell", &output).unwrap(), preview);
+
+        let handle = handle_of(&preview);
+        let stored = artifact_path(tmp.path(), &handle["artifact:".len()..]);
+        assert_eq!(std::fs::read_to_string(stored).unwrap(), output);
+    }
+
+    #[test]
+    fn oldest_artifacts_are_pruned() {
+        let tmp = tempfile::tempdir().unwrap();
+        let spill = spill(tmp.path(), 10);
+        for n in 0..4 {
+            spill.apply("shell", &format!("{n}{}", "y".repeat(50)));
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        let remaining = std::fs::read_dir(artifacts_dir(tmp.path()))
+            .unwrap()
+            .count();
+        assert_eq!(remaining, 2);
+    }
+
+    #[tokio::test]
+    async fn artifact_read_pages_greps_and_slices() {
+        let tmp = tempfile::tempdir().unwrap();
+        let handle = handle_of(
+            &spill(tmp.path(), 100)
+                .apply("web_fetch", &numbered_lines(300))
+                .unwrap(),
+        );
+        let tool = ArtifactReadTool::new(tmp.path().to_path_buf());
+
+        let page = tool
+            .execute(json!({"handle": handle, "offset": 11, "limit": 5}))
+            .await
+            .unwrap();
+        assert!(page.success);
+        assert!(page
+            .output
+            .starts_with(&format!("{handle} lines 11-15 of 300")));
+        assert!(page.output.contains("    15  line 15"));
+        assert!(page.output.contains("continue with offset 16"));
+
+        let grep = tool
+            .execute(
+                json!({"handle": handle, "pattern": "^line 2\\d\\d$", "limit": 3, "context": 1}),
+            )
+            .await
+            .unwrap();
+        assert!(grep.output.starts_with("100 matching line(s)"));
+        assert!(grep.output.contains("   200: line 200"));
+        assert!(grep.output.contains("   199- line 199"));
+        assert!(grep.output.contains("97 more match(es)"));
+
+        let slice = tool
+            .execute(json!({"handle": handle, "start": 0, "end": 6}))
+            .await
+            .unwrap();
+        assert!(slice.output.ends_with("\nline 1"));
+
+        let missing = tool
+            .execute(json!({"handle": "artifact:000000000000"}))
+            .await
+            .unwrap();
+        assert!(!missing.success);
+        let invalid = tool.execute(json!({"handle": "../etc"})).await.unwrap();
+        assert!(invalid.error.unwrap().contains("Invalid artifact handle"));
+    }
+
+    #[test]
+    fn attachment_markers_resolve_to_artifact_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        let handle = handle_of(
+            &spill(tmp.path(), 10)
+                .apply("shell", &"z".repeat(40))
+                .unwrap(),
+        );
+        let id = &handle["artifact:".len()..];
+
+        let text = format!("Full log: [DOCUMENT:{handle}] and [DOCUMENT:artifact:000000000000]");
+        let resolved = resolve_artifact_markers(&text, tmp.path());
+        assert!(resolved.contains(&format!(
+            "[DOCUMENT:{}]",
+            artifact_path(tmp.path(), id).display()
+        )));
+        assert!(resolved.contains("[DOCUMENT:artifact:000000000000]"));
+    }
+}
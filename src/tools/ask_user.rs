@@ -0,0 +1,475 @@
+use super::traits::{Tool, ToolResult};
+use crate::channels::traits::Channel;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+/// How long a channel question waits for a reply by default.
+const DEFAULT_ASK_TIMEOUT_SECS: u64 = 300;
+/// Longest wait a caller may request.
+const MAX_ASK_TIMEOUT_SECS: u64 = 3_600;
+/// Maximum number of suggested options shown with a question.
+const MAX_OPTIONS: usize = 10;
+/// Share of the remaining turn time held back so the turn can still finish
+/// after a question goes unanswered.
+const TURN_RESERVE_DIVISOR: u32 = 4;
+/// Upper bound on the time held back from a question's wait.
+const MAX_TURN_RESERVE: Duration = Duration::from_secs(60);
+
+/// Where an `ask_user` question goes for the current turn.
+#[derive(Clone)]
+pub(crate) enum AskUserContext {
+    /// Interactive terminal session: ask on stdin/stderr.
+    Cli,
+    /// Channel conversation that started the turn.
+    Channel {
+        channel: Arc<dyn Channel>,
+        reply_target: String,
+        thread_ts: Option<String>,
+        /// Sender scope whose next reply is taken as the answer.
+        scope_key: String,
+        /// When the turn's own timeout fires; questions never wait past it.
+        deadline: Option<Instant>,
+    },
+}
+
+tokio::task_local! {
+    static ASK_USER_CONTEXT: Option<AskUserContext>;
+}
+
+/// Run `future` with `context` as the destination of `ask_user` questions.
+pub(crate) async fn scope_ask_user_context<F: std::future::Future>(
+    context: Option<AskUserContext>,
+    future: F,
+) -> F::Output {
+    ASK_USER_CONTEXT.scope(context, future).await
+}
+
+struct PendingQuestion {
+    id: u64,
+    answer_tx: oneshot::Sender<String>,
+}
+
+static PENDING_QUESTIONS: LazyLock<Mutex<HashMap<String, PendingQuestion>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_QUESTION_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Deliver an inbound channel message to a turn waiting in `ask_user`.
+/// Returns true when the message was consumed as an answer. Slash commands
+/// are never consumed, so approvals and `/new` keep working mid-question.
+pub(crate) fn answer_pending_question(scope_key: &str, content: &str) -> bool {
+    if content.trim_start().starts_with('/') {
+        return false;
+    }
+    let Some(pending) = PENDING_QUESTIONS.lock().remove(scope_key) else {
+        return false;
+    };
+    pending.answer_tx.send(content.trim().to_string()).is_ok()
+}
+
+/// Removes the pending entry if the waiting turn ends without an answer
+/// (timeout or cancellation).
+struct PendingQuestionGuard {
+    scope_key: String,
+    id: u64,
+}
+
+impl Drop for PendingQuestionGuard {
+    fn drop(&mut self) {
+        let mut pending = PENDING_QUESTIONS.lock();
+        if pending
+            .get(&self.scope_key)
+            .is_some_and(|question| question.id == self.id)
+        {
+            pending.remove(&self.scope_key);
+        }
+    }
+}
+
+/// Shorten `timeout` so an unanswered question still leaves the turn time to
+/// continue before its `deadline`.
+fn clamp_to_turn_deadline(timeout: Duration, deadline: Option<Instant>, now: Instant) -> Duration {
+    let Some(deadline) = deadline else {
+        return timeout;
+    };
+    let remaining = deadline.saturating_duration_since(now);
+    let reserve = (remaining / TURN_RESERVE_DIVISOR).min(MAX_TURN_RESERVE);
+    timeout.min(remaining.saturating_sub(reserve))
+}
+
+/// Map an option number ("2") to its text; anything else is the answer itself.
+fn resolve_answer(answer: &str, options: &[String]) -> String {
+    answer
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_sub(1))
+        .and_then(|index| options.get(index))
+        .cloned()
+        .unwrap_or_else(|| answer.trim().to_string())
+}
+
+/// Let the agent ask the originating user a clarifying question mid-turn and
+/// continue with the answer.
+pub struct AskUserTool;
+
+impl AskUserTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn failure(error: String) -> ToolResult {
+        ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(error),
+        }
+    }
+
+    fn answered(answer: &str) -> ToolResult {
+        ToolResult {
+            success: true,
+            output: format!("User answered: {answer}"),
+            error: None,
+        }
+    }
+
+    fn unanswered(default: Option<&str>, reason: &str) -> ToolResult {
+        match default {
+            Some(default) => ToolResult {
+                success: true,
+                output: format!("{reason}; proceeding with the default answer: {default}"),
+                error: None,
+            },
+            None => Self::failure(format!(
+                "{reason}. Continue with your best judgement and state the assumption you made."
+            )),
+        }
+    }
+
+    async fn ask_cli(question: &str, options: &[String], default: Option<&str>) -> ToolResult {
+        if !std::io::stdin().is_terminal() {
+            return Self::unanswered(default, "No interactive terminal to ask the user");
+        }
+
+        let mut prompt = format!("\n❓ {question}\n");
+        for (index, option) in options.iter().enumerate() {
+            let _ = writeln!(prompt, "   {}. {option}", index + 1);
+        }
+        let read = tokio::task::spawn_blocking(move || {
+            eprint!("{prompt}> ");
+            let _ = std::io::stderr().flush();
+            let mut line = String::new();
+            std::io::stdin().lock().read_line(&mut line).map(|_| line)
+        })
+        .await;
+
+        match read {
+            Ok(Ok(line)) if !line.trim().is_empty() => {
+                Self::answered(&resolve_answer(&line, options))
+            }
+            _ => Self::unanswered(default, "The user gave no answer"),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn ask_channel(
+        channel: &dyn Channel,
+        reply_target: &str,
+        thread_ts: Option<String>,
+        scope_key: &str,
+        question: &str,
+        options: &[String],
+        default: Option<&str>,
+        timeout: Duration,
+    ) -> ToolResult {
+        if timeout.is_zero() {
+            return Self::unanswered(default, "This turn has no time left to wait for an answer");
+        }
+        let id = NEXT_QUESTION_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let (answer_tx, answer_rx) = oneshot::channel();
+        {
+            let mut pending = PENDING_QUESTIONS.lock();
+            if pending.contains_key(scope_key) {
+                return Self::failure(
+                    "Another question to this user is still waiting for an answer; ask one question at a time."
+                        .to_string(),
+                );
+            }
+            pending.insert(scope_key.to_string(), PendingQuestion { id, answer_tx });
+        }
+        let _guard = PendingQuestionGuard {
+            scope_key: scope_key.to_string(),
+            id,
+        };
+
+        if let Err(err) = channel
+            .send_question_prompt(reply_target, question, options, thread_ts)
+            .await
+        {
+            return Self::failure(format!("Failed to deliver the question: {err}"));
+        }
+
+        match tokio::time::timeout(timeout, answer_rx).await {
+            Ok(Ok(answer)) => Self::answered(&resolve_answer(&answer, options)),
+            Ok(Err(_)) => Self::unanswered(default, "The question was withdrawn"),
+            Err(_) => Self::unanswered(
+                default,
+                &format!("The user did not answer within {}s", timeout.as_secs()),
+            ),
+        }
+    }
+}
+
+impl Default for AskUserTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for AskUserTool {
+    fn name(&self) -> &str {
+        "ask_user"
+    }
+
+    fn description(&self) -> &str {
+        "Ask the user a clarifying question and wait for the reply before continuing. \
+         Use only when the task cannot proceed safely without their input; otherwise make a \
+         reasonable assumption. Provide `default` so the turn can continue if nobody answers."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "question": {
+                    "type": "string",
+                    "description": "The question to ask, phrased for the user"
+                },
+                "options": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional suggested answers; the user may reply with a number"
+                },
+                "default": {
+                    "type": "string",
+                    "description": "Answer to use if the user does not reply in time"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Seconds to wait for a reply on chat channels (default: 300, max: 3600, and never past the turn's own timeout)"
+                }
+            },
+            "required": ["question"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let question = args
+            .get("question")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|question| !question.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'question' parameter"))?;
+        let options: Vec<String> = args
+            .get("options")
+            .and_then(serde_json::Value::as_array)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(serde_json::Value::as_str)
+                    .map(str::trim)
+                    .filter(|option| !option.is_empty())
+                    .take(MAX_OPTIONS)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let default = args
+            .get("default")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|default| !default.is_empty());
+        let timeout = Duration::from_secs(
+            args.get("timeout_secs")
+                .and_then(serde_json::Value::as_u64)
+                .unwrap_or(DEFAULT_ASK_TIMEOUT_SECS)
+                .clamp(1, MAX_ASK_TIMEOUT_SECS),
+        );
+
+        let context = ASK_USER_CONTEXT.try_with(Clone::clone).ok().flatten();
+        let result = match context {
+            Some(AskUserContext::Cli) => Self::ask_cli(question, &options, default).await,
+            Some(AskUserContext::Channel {
+                channel,
+                reply_target,
+                thread_ts,
+                scope_key,
+                deadline,
+            }) => {
+                let timeout = clamp_to_turn_deadline(timeout, deadline, Instant::now());
+                Self::ask_channel(
+                    channel.as_ref(),
+                    &reply_target,
+                    thread_ts,
+                    &scope_key,
+                    question,
+                    &options,
+                    default,
+                    timeout,
+                )
+                .await
+            }
+            None => Self::unanswered(default, "No user is attached to this run to answer"),
+        };
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::traits::{ChannelMessage, SendMessage};
+
+    #[derive(Default)]
+    struct RecordingChannel {
+        sent: Mutex<Vec<(String, Option<String>)>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
+            self.sent
+                .lock()
+                .push((message.content.clone(), message.thread_ts.clone()));
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn channel_context(channel: &Arc<RecordingChannel>, scope_key: &str) -> AskUserContext {
+        AskUserContext::Channel {
+            channel: Arc::clone(channel) as Arc<dyn Channel>,
+            reply_target: "chat-1".into(),
+            thread_ts: Some("thread-9".into()),
+            scope_key: scope_key.into(),
+            deadline: None,
+        }
+    }
+
+    #[test]
+    fn question_wait_is_clamped_to_turn_deadline() {
+        let now = Instant::now();
+        let timeout = Duration::from_secs(300);
+        assert_eq!(clamp_to_turn_deadline(timeout, None, now), timeout);
+        assert_eq!(
+            clamp_to_turn_deadline(timeout, Some(now + Duration::from_secs(3_600)), now),
+            timeout
+        );
+        assert_eq!(
+            clamp_to_turn_deadline(timeout, Some(now + Duration::from_secs(200)), now),
+            Duration::from_secs(150)
+        );
+        assert_eq!(
+            clamp_to_turn_deadline(timeout, Some(now + Duration::from_secs(1_200)), now),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            clamp_to_turn_deadline(timeout, Some(now), now),
+            Duration::ZERO
+        );
+    }
+
+    #[tokio::test]
+    async fn channel_question_resumes_with_reply() {
+        let channel = Arc::new(RecordingChannel::default());
+        let tool = AskUserTool::new();
+        let ask = scope_ask_user_context(
+            Some(channel_context(&channel, "recording_chat-1_alice")),
+            tool.execute(json!({
+                "question": "Which environment?",
+                "options": ["staging", "production"]
+            })),
+        );
+        let answer = async {
+            while !PENDING_QUESTIONS
+                .lock()
+                .contains_key("recording_chat-1_alice")
+            {
+                tokio::task::yield_now().await;
+            }
+            assert!(!answer_pending_question(
+                "recording_chat-1_alice",
+                "/approvals"
+            ));
+            assert!(answer_pending_question("recording_chat-1_alice", " 2 "));
+        };
+
+        let (result, ()) = tokio::join!(ask, answer);
+        let result = result.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "User answered: production");
+
+        let sent = channel.sent.lock();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0]
+            .0
+            .contains("Which environment?\n1. staging\n2. production"));
+        assert_eq!(sent[0].1.as_deref(), Some("thread-9"));
+        assert!(!PENDING_QUESTIONS
+            .lock()
+            .contains_key("recording_chat-1_alice"));
+    }
+
+    #[tokio::test]
+    async fn channel_question_times_out_to_default() {
+        let channel = Arc::new(RecordingChannel::default());
+        let result = scope_ask_user_context(
+            Some(channel_context(&channel, "recording_chat-1_bob")),
+            AskUserTool::new().execute(json!({
+                "question": "Proceed?",
+                "default": "yes",
+                "timeout_secs": 1
+            })),
+        )
+        .await
+        .unwrap();
+
+        assert!(result.success);
+        assert!(result.output.contains("did not answer within 1s"));
+        assert!(result.output.ends_with("default answer: yes"));
+        assert!(!answer_pending_question("recording_chat-1_bob", "late"));
+    }
+
+    #[tokio::test]
+    async fn question_without_user_fails_unless_default_given() {
+        let tool = AskUserTool::new();
+        let result = tool.execute(json!({"question": "Which?"})).await.unwrap();
+        assert!(!result.success);
+
+        let result = tool
+            .execute(json!({"question": "Which?", "default": "first"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.ends_with("default answer: first"));
+    }
+}
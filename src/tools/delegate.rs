@@ -1,5 +1,6 @@
 use super::traits::{Tool, ToolResult};
 use crate::agent::loop_::run_tool_call_loop;
+use crate::agent::tool_retrieval::scope_tool_retrieval;
 use crate::config::DelegateAgentConfig;
 use crate::coordination::{CoordinationEnvelope, CoordinationPayload, InMemoryMessageBus};
 use crate::observability::traits::{Observer, ObserverEvent, ObserverMetric};
@@ -489,25 +490,30 @@ impl DelegateTool {
 
         let noop_observer = NoopObserver;
 
+        // The sub-agent sends its own (small) tool set; keep it out of the
+        // parent turn's tool selection.
         let result = tokio::time::timeout(
             Duration::from_secs(DELEGATE_AGENTIC_TIMEOUT_SECS),
-            run_tool_call_loop(
-                provider,
-                &mut history,
-                &sub_tools,
-                &noop_observer,
-                &agent_config.provider,
-                &agent_config.model,
-                temperature,
-                true,
-                None,
-                "delegate",
-                &self.multimodal_config,
-                agent_config.max_iterations,
+            scope_tool_retrieval(
                 None,
-                None,
-                None,
-                &[],
+                run_tool_call_loop(
+                    provider,
+                    &mut history,
+                    &sub_tools,
+                    &noop_observer,
+                    &agent_config.provider,
+                    &agent_config.model,
+                    temperature,
+                    true,
+                    None,
+                    "delegate",
+                    &self.multimodal_config,
+                    agent_config.max_iterations,
+                    None,
+                    None,
+                    None,
+                    &[],
+                ),
             ),
         )
         .await;
@@ -880,6 +886,7 @@ mod tests {
                     tool_calls: Vec::new(),
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 })
             } else {
                 Ok(ChatResponse {
@@ -891,6 +898,7 @@ mod tests {
                     }],
                     usage: None,
                     reasoning_content: None,
+                    thinking: Vec::new(),
                 })
             }
         }
@@ -925,6 +933,7 @@ mod tests {
                 }],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             })
         }
     }
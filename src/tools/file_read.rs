@@ -744,6 +744,7 @@ mod tests {
                         tool_calls: vec![],
                         usage: None,
                         reasoning_content: None,
+                        thinking: Vec::new(),
                     });
                 }
                 Ok(guard.remove(0))
@@ -804,6 +805,7 @@ mod tests {
                 }],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             },
             // Turn 1 continued: provider sees tool result and answers
             ChatResponse {
@@ -811,6 +813,7 @@ mod tests {
                 tool_calls: vec![],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             },
         ]);
 
@@ -897,12 +900,14 @@ mod tests {
                 }],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             },
             ChatResponse {
                 text: Some("The file appears to be binary data.".into()),
                 tool_calls: vec![],
                 usage: None,
                 reasoning_content: None,
+                thinking: Vec::new(),
             },
         ]);
 
@@ -17,6 +17,8 @@
 
 pub mod agents_ipc;
 pub mod apply_patch;
+pub mod artifact_read;
+pub mod ask_user;
 pub mod browser;
 pub mod browser_open;
 pub mod cli_discovery;
@@ -55,6 +57,7 @@ pub mod memory_recall;
 pub mod memory_store;
 pub mod model_routing_config;
 pub mod pdf_read;
+pub mod policy;
 pub mod process;
 pub mod proxy_config;
 pub mod pushover;
@@ -67,6 +70,7 @@ pub mod subagent_manage;
 pub mod subagent_registry;
 pub mod subagent_spawn;
 pub mod task_plan;
+pub mod tool_search;
 pub mod traits;
 pub mod url_validation;
 pub mod wasm_module;
@@ -75,6 +79,8 @@ pub mod web_fetch;
 pub mod web_search_tool;
 
 pub use apply_patch::ApplyPatchTool;
+pub use artifact_read::ArtifactReadTool;
+pub use ask_user::AskUserTool;
 pub use browser::{BrowserTool, ComputerUseConfig};
 pub use browser_open::BrowserOpenTool;
 pub use composio::ComposioTool;
@@ -123,6 +129,7 @@ pub use subagent_manage::SubAgentManageTool;
 pub use subagent_registry::SubAgentRegistry;
 pub use subagent_spawn::SubAgentSpawnTool;
 pub use task_plan::TaskPlanTool;
+pub use tool_search::ToolSearchTool;
 pub use traits::Tool;
 #[allow(unused_imports)]
 pub use traits::{ToolResult, ToolSpec};
@@ -279,6 +286,7 @@ pub fn all_tools_with_runtime(
         Arc::new(MemoryForgetTool::new(memory, security.clone())),
         Arc::new(ScheduleTool::new(security.clone(), root_config.clone())),
         Arc::new(TaskPlanTool::new(security.clone())),
+        Arc::new(AskUserTool::new()),
         Arc::new(ModelRoutingConfigTool::new(
             config.clone(),
             security.clone(),
@@ -409,6 +417,16 @@ pub fn all_tools_with_runtime(
     tool_arcs.push(Arc::new(ScreenshotTool::new(security.clone())));
     tool_arcs.push(Arc::new(ImageInfoTool::new(security.clone())));
 
+    // Reader for large tool outputs spilled to workspace artifacts
+    if root_config.agent.tool_output_spill_chars > 0 {
+        tool_arcs.push(Arc::new(ArtifactReadTool::new(workspace_dir.to_path_buf())));
+    }
+
+    // Registry search for turns that only offer a retrieved subset of tools
+    if root_config.agent.tool_retrieval.enabled {
+        tool_arcs.push(Arc::new(ToolSearchTool::new()));
+    }
+
     if let Some(key) = composio_key {
         if !key.is_empty() {
             tool_arcs.push(Arc::new(ComposioTool::new(
@@ -445,6 +463,9 @@ pub fn all_tools_with_runtime(
                 .map(|mode| mode.as_compatible_mode()),
             max_tokens_override: None,
             model_support_vision: root_config.model_support_vision,
+            thinking_budget_tokens: root_config.effective_thinking_budget_tokens(),
+            ollama: root_config.ollama.clone(),
+            observer: None,
         };
         let parent_tools = Arc::new(tool_arcs.clone());
         let mut delegate_tool = DelegateTool::new_with_options(
@@ -551,7 +572,7 @@ pub fn all_tools_with_runtime(
         }
     }
 
-    boxed_registry_from_arcs(tool_arcs)
+    policy::apply_tool_policies(boxed_registry_from_arcs(tool_arcs), &root_config.tools)
 }
 
 #[cfg(test)]
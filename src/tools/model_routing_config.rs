@@ -505,6 +505,7 @@ impl ModelRoutingConfigTool {
             max_tokens: None,
             api_key: None,
             transport: None,
+            quality_tier: None,
         });
 
         next_route.hint = hint.clone();
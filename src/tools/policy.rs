@@ -0,0 +1,346 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::ToolPolicyConfig;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// Upper bound on cached results kept per tool.
+const MAX_CACHE_ENTRIES: usize = 256;
+/// Longest delay between two retries, whatever the backoff doubling reaches.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Error and output fragments that mark a failure worth retrying.
+const TRANSIENT_FAILURE_HINTS: &[&str] = &[
+    "timed out",
+    "timeout",
+    "connection reset",
+    "connection refused",
+    "connection closed",
+    "broken pipe",
+    "temporarily unavailable",
+    "service unavailable",
+    "bad gateway",
+    "gateway timeout",
+    "too many requests",
+    "rate limit",
+];
+
+/// Wrap every tool that has a `[tools.<name>]` section in a [`PolicyTool`].
+///
+/// Tools without a section are returned unchanged.
+#[allow(clippy::implicit_hasher)]
+pub fn apply_tool_policies(
+    tools: Vec<Box<dyn Tool>>,
+    policies: &HashMap<String, ToolPolicyConfig>,
+) -> Vec<Box<dyn Tool>> {
+    if policies.is_empty() {
+        return tools;
+    }
+    tools
+        .into_iter()
+        .map(|tool| match policies.get(tool.name()) {
+            Some(policy) => Box::new(PolicyTool::new(tool, policy.clone())) as Box<dyn Tool>,
+            None => tool,
+        })
+        .collect()
+}
+
+/// Enforces a [`ToolPolicyConfig`] around another tool: per-attempt timeout,
+/// retries with exponential backoff on transient failures, a cap on
+/// concurrent invocations and a short-lived cache of successful results
+/// keyed by normalized arguments.
+pub struct PolicyTool {
+    inner: Box<dyn Tool>,
+    policy: ToolPolicyConfig,
+    permits: Option<Arc<Semaphore>>,
+    cache: Mutex<HashMap<String, (Instant, ToolResult)>>,
+}
+
+impl PolicyTool {
+    pub fn new(inner: Box<dyn Tool>, policy: ToolPolicyConfig) -> Self {
+        let permits = policy
+            .max_concurrent
+            .map(|limit| Arc::new(Semaphore::new(limit.max(1))));
+        Self {
+            inner,
+            policy,
+            permits,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn cache_ttl(&self) -> Option<Duration> {
+        (self.policy.cache_ttl_secs > 0).then(|| Duration::from_secs(self.policy.cache_ttl_secs))
+    }
+
+    fn cached(&self, key: &str, ttl: Duration) -> Option<ToolResult> {
+        let cache = self.cache.lock();
+        cache
+            .get(key)
+            .filter(|(stored_at, _)| stored_at.elapsed() < ttl)
+            .map(|(_, result)| result.clone())
+    }
+
+    fn store(&self, key: String, ttl: Duration, result: &ToolResult) {
+        let mut cache = self.cache.lock();
+        cache.retain(|_, (stored_at, _)| stored_at.elapsed() < ttl);
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            if let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, (stored_at, _))| *stored_at)
+                .map(|(key, _)| key.clone())
+            {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(key, (Instant::now(), result.clone()));
+    }
+
+    async fn attempt(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let Some(secs) = self.policy.timeout_secs else {
+            return self.inner.execute(args).await;
+        };
+        match tokio::time::timeout(Duration::from_secs(secs), self.inner.execute(args)).await {
+            Ok(result) => result,
+            Err(_) => anyhow::bail!("{} timed out after {secs}s", self.inner.name()),
+        }
+    }
+
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u64.checked_shl(retry).unwrap_or(u64::MAX);
+        Duration::from_millis(self.policy.retry_backoff_ms.saturating_mul(factor))
+            .min(MAX_RETRY_BACKOFF)
+    }
+}
+
+#[async_trait]
+impl Tool for PolicyTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.inner.parameters_schema()
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let cache_key = self
+            .cache_ttl()
+            .map(|ttl| (normalized_args_key(&args), ttl));
+        if let Some((key, ttl)) = &cache_key {
+            if let Some(result) = self.cached(key, *ttl) {
+                tracing::debug!(tool = self.name(), "Serving tool result from policy cache");
+                return Ok(result);
+            }
+        }
+
+        let _permit = match &self.permits {
+            Some(permits) => Some(Arc::clone(permits).acquire_owned().await?),
+            None => None,
+        };
+
+        let mut retry = 0;
+        loop {
+            let outcome = self.attempt(args.clone()).await;
+            let transient = match &outcome {
+                Ok(result) if result.success => false,
+                Ok(result) => {
+                    is_transient_failure(result.error.as_deref().unwrap_or(result.output.as_str()))
+                }
+                Err(err) => is_transient_error(err),
+            };
+            if !transient || retry >= self.policy.max_retries {
+                if let (Ok(result), Some((key, ttl))) = (&outcome, cache_key) {
+                    if result.success {
+                        self.store(key, ttl, result);
+                    }
+                }
+                return outcome;
+            }
+
+            let delay = self.backoff(retry);
+            retry += 1;
+            tracing::debug!(
+                tool = self.name(),
+                retry,
+                delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+                "Retrying tool after transient failure"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Canonical cache key for tool arguments: object keys sorted recursively so
+/// `{"a":1,"b":2}` and `{"b":2,"a":1}` share an entry.
+fn normalized_args_key(args: &Value) -> String {
+    fn normalize(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by_key(|(key, _)| *key);
+                Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key.clone(), normalize(value)))
+                        .collect(),
+                )
+            }
+            Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+            other => other.clone(),
+        }
+    }
+    normalize(args).to_string()
+}
+
+fn is_transient_error(err: &anyhow::Error) -> bool {
+    if let Some(reqwest_err) = err.downcast_ref::<reqwest::Error>() {
+        if reqwest_err.is_timeout() || reqwest_err.is_connect() {
+            return true;
+        }
+        if let Some(status) = reqwest_err.status() {
+            return status.is_server_error() || status.as_u16() == 429;
+        }
+    }
+    is_transient_failure(&err.to_string())
+}
+
+fn is_transient_failure(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    if TRANSIENT_FAILURE_HINTS
+        .iter()
+        .any(|hint| lower.contains(hint))
+    {
+        return true;
+    }
+    lower
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|word| word.parse::<u16>().ok())
+        .any(|code| matches!(code, 429 | 502 | 503 | 504))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct ScriptedTool {
+        calls: Arc<AtomicUsize>,
+        active: Arc<AtomicUsize>,
+        max_active: Arc<AtomicUsize>,
+        failures_before_success: usize,
+        delay: Duration,
+    }
+
+    impl ScriptedTool {
+        fn new(failures_before_success: usize, delay: Duration) -> Self {
+            Self {
+                calls: Arc::new(AtomicUsize::new(0)),
+                active: Arc::new(AtomicUsize::new(0)),
+                max_active: Arc::new(AtomicUsize::new(0)),
+                failures_before_success,
+                delay,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Tool for ScriptedTool {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn description(&self) -> &str {
+            "Fails a fixed number of times, then succeeds"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let now_active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_active.fetch_max(now_active, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            if call < self.failures_before_success {
+                anyhow::bail!("upstream returned 503 Service Unavailable");
+            }
+            Ok(ToolResult {
+                success: true,
+                output: format!("ok:{args}"),
+                error: None,
+            })
+        }
+    }
+
+    fn policy(update: impl FnOnce(&mut ToolPolicyConfig)) -> ToolPolicyConfig {
+        let mut policy = ToolPolicyConfig {
+            retry_backoff_ms: 1,
+            ..ToolPolicyConfig::default()
+        };
+        update(&mut policy);
+        policy
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures_until_success() {
+        let inner = ScriptedTool::new(2, Duration::ZERO);
+        let calls = Arc::clone(&inner.calls);
+        let tool = PolicyTool::new(Box::new(inner), policy(|p| p.max_retries = 2));
+
+        let result = tool.execute(json!({})).await.unwrap();
+        assert!(result.success);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let inner = ScriptedTool::new(5, Duration::ZERO);
+        let tool = PolicyTool::new(Box::new(inner), policy(|p| p.max_retries = 1));
+        assert!(tool.execute(json!({})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn times_out_slow_attempts() {
+        let inner = ScriptedTool::new(0, Duration::from_secs(5));
+        let tool = PolicyTool::new(Box::new(inner), policy(|p| p.timeout_secs = Some(1)));
+
+        let err = tool.execute(json!({})).await.unwrap_err();
+        assert!(err.to_string().contains("scripted timed out after 1s"));
+    }
+
+    #[tokio::test]
+    async fn limits_concurrent_invocations() {
+        let inner = ScriptedTool::new(0, Duration::from_millis(20));
+        let max_active = Arc::clone(&inner.max_active);
+        let tool = PolicyTool::new(Box::new(inner), policy(|p| p.max_concurrent = Some(2)));
+
+        let calls = (0..6).map(|i| tool.execute(json!({ "i": i })));
+        let results = futures_util::future::join_all(calls).await;
+        assert!(results.iter().all(|r| r.as_ref().is_ok_and(|r| r.success)));
+        assert_eq!(max_active.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn caches_results_by_normalized_arguments() {
+        let inner = ScriptedTool::new(0, Duration::ZERO);
+        let calls = Arc::clone(&inner.calls);
+        let tool = PolicyTool::new(Box::new(inner), policy(|p| p.cache_ttl_secs = 60));
+
+        tool.execute(json!({"q": "rust", "n": 3})).await.unwrap();
+        let cached = tool.execute(json!({"n": 3, "q": "rust"})).await.unwrap();
+        assert!(cached.success);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        tool.execute(json!({"q": "zig", "n": 3})).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Different providers support different subsets of JSON Schema. This module
 //! normalizes tool schemas to improve cross-provider compatibility while
-//! preserving semantic intent.
+//! preserving semantic intent. [`SchemaValidator`] checks model-produced
+//! values against those schemas.
 //!
 //! ## What this module does
 //!
@@ -535,6 +536,355 @@ impl SchemaCleanr {
     }
 }
 
+/// A single mismatch between a JSON value and the schema it was checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON path of the offending value (e.g. `$.steps[2].status`).
+    pub path: String,
+    /// Human- and model-readable description of what to fix.
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}`: {}", self.path, self.message)
+    }
+}
+
+/// Maximum schema nesting followed before validation gives up on a branch.
+const MAX_VALIDATION_DEPTH: usize = 32;
+
+/// Lightweight JSON Schema validator covering the subset LLM tool and
+/// response schemas use in practice: `type`, `enum`, `const`, `required`,
+/// `properties`, `additionalProperties`, `items`, string/number/array bounds,
+/// `pattern`, `anyOf`/`oneOf`/`allOf` and local `$ref`s.
+///
+/// Unknown keywords are ignored, so validation never rejects a value the
+/// schema does not explicitly constrain.
+pub struct SchemaValidator;
+
+impl SchemaValidator {
+    /// Validate `value` against `schema`, returning every violation found.
+    pub fn validate(schema: &Value, value: &Value) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        Self::check(schema, schema, value, "$", 0, &mut violations);
+        violations
+    }
+
+    fn check(
+        root: &Value,
+        schema: &Value,
+        value: &Value,
+        path: &str,
+        depth: usize,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        if depth > MAX_VALIDATION_DEPTH {
+            return;
+        }
+        let violation = |out: &mut Vec<SchemaViolation>, message: String| {
+            out.push(SchemaViolation {
+                path: path.to_string(),
+                message,
+            });
+        };
+
+        let obj = match schema {
+            Value::Bool(false) => {
+                violation(out, "no value is allowed here".to_string());
+                return;
+            }
+            Value::Object(obj) => obj,
+            _ => return,
+        };
+
+        if let Some(Value::String(reference)) = obj.get("$ref") {
+            match Self::resolve_ref(root, reference) {
+                Some(target) => Self::check(root, target, value, path, depth + 1, out),
+                None => tracing::debug!(reference, "Skipping unresolvable schema $ref"),
+            }
+        }
+
+        if let Some(expected) = obj.get("type") {
+            let allowed: Vec<&str> = match expected {
+                Value::String(t) => vec![t.as_str()],
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !allowed.is_empty() && !allowed.iter().any(|t| Self::matches_type(t, value)) {
+                violation(
+                    out,
+                    format!(
+                        "expected {}, got {}",
+                        allowed.join(" or "),
+                        Self::describe_type(value)
+                    ),
+                );
+                return;
+            }
+        }
+
+        if let Some(Value::Array(options)) = obj.get("enum") {
+            if !options.contains(value) {
+                let rendered: Vec<String> = options.iter().map(Value::to_string).collect();
+                violation(
+                    out,
+                    format!("must be one of [{}], got {value}", rendered.join(", ")),
+                );
+            }
+        }
+
+        if let Some(expected) = obj.get("const") {
+            if expected != value {
+                violation(out, format!("must equal {expected}, got {value}"));
+            }
+        }
+
+        match value {
+            Value::String(text) => Self::check_string(obj, text, path, out),
+            Value::Number(number) => {
+                if let Some(n) = number.as_f64() {
+                    Self::check_number(obj, n, path, out);
+                }
+            }
+            Value::Array(items) => {
+                let len = items.len() as u64;
+                if let Some(min) = obj.get("minItems").and_then(Value::as_u64) {
+                    if len < min {
+                        violation(
+                            out,
+                            format!("must contain at least {min} item(s), got {len}"),
+                        );
+                    }
+                }
+                if let Some(max) = obj.get("maxItems").and_then(Value::as_u64) {
+                    if len > max {
+                        violation(
+                            out,
+                            format!("must contain at most {max} item(s), got {len}"),
+                        );
+                    }
+                }
+                if let Some(item_schema) = obj.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        let item_path = format!("{path}[{index}]");
+                        Self::check(root, item_schema, item, &item_path, depth + 1, out);
+                    }
+                }
+            }
+            Value::Object(fields) => {
+                if let Some(Value::Array(required)) = obj.get("required") {
+                    for name in required.iter().filter_map(Value::as_str) {
+                        if !fields.contains_key(name) {
+                            violation(out, format!("missing required property `{name}`"));
+                        }
+                    }
+                }
+                let properties = obj.get("properties").and_then(Value::as_object);
+                for (name, field_value) in fields {
+                    let field_path = format!("{path}.{name}");
+                    match properties.and_then(|props| props.get(name)) {
+                        Some(field_schema) => Self::check(
+                            root,
+                            field_schema,
+                            field_value,
+                            &field_path,
+                            depth + 1,
+                            out,
+                        ),
+                        None => match obj.get("additionalProperties") {
+                            Some(Value::Bool(false)) => {
+                                let known: Vec<&str> = properties
+                                    .map(|props| props.keys().map(String::as_str).collect())
+                                    .unwrap_or_default();
+                                out.push(SchemaViolation {
+                                    path: field_path.clone(),
+                                    message: format!(
+                                        "unexpected property `{name}` (allowed: {})",
+                                        known.join(", ")
+                                    ),
+                                });
+                            }
+                            Some(extra @ Value::Object(_)) => {
+                                Self::check(root, extra, field_value, &field_path, depth + 1, out);
+                            }
+                            _ => {}
+                        },
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(Value::Array(variants)) = obj.get("allOf") {
+            for variant in variants {
+                Self::check(root, variant, value, path, depth + 1, out);
+            }
+        }
+
+        let matches_variant = |variant: &Value| {
+            let mut scratch = Vec::new();
+            Self::check(root, variant, value, path, depth + 1, &mut scratch);
+            scratch.is_empty()
+        };
+        if let Some(Value::Array(variants)) = obj.get("anyOf") {
+            if !variants.iter().any(matches_variant) {
+                violation(
+                    out,
+                    "does not match any of the allowed variants".to_string(),
+                );
+            }
+        }
+        if let Some(Value::Array(variants)) = obj.get("oneOf") {
+            match variants
+                .iter()
+                .filter(|variant| matches_variant(variant))
+                .count()
+            {
+                1 => {}
+                0 => violation(
+                    out,
+                    "does not match any of the allowed variants".to_string(),
+                ),
+                matched => violation(
+                    out,
+                    format!("matches {matched} variants but must match exactly one"),
+                ),
+            }
+        }
+    }
+
+    fn check_string(
+        obj: &Map<String, Value>,
+        text: &str,
+        path: &str,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        let len = text.chars().count() as u64;
+        let mut push = |message: String| {
+            out.push(SchemaViolation {
+                path: path.to_string(),
+                message,
+            });
+        };
+        if let Some(min) = obj.get("minLength").and_then(Value::as_u64) {
+            if len < min {
+                push(format!(
+                    "must be at least {min} character(s) long, got {len}"
+                ));
+            }
+        }
+        if let Some(max) = obj.get("maxLength").and_then(Value::as_u64) {
+            if len > max {
+                push(format!(
+                    "must be at most {max} character(s) long, got {len}"
+                ));
+            }
+        }
+        if let Some(pattern) = obj.get("pattern").and_then(Value::as_str) {
+            if let Ok(re) = regex::Regex::new(pattern) {
+                if !re.is_match(text) {
+                    push(format!("must match pattern `{pattern}`"));
+                }
+            }
+        }
+    }
+
+    fn check_number(obj: &Map<String, Value>, n: f64, path: &str, out: &mut Vec<SchemaViolation>) {
+        let mut push = |message: String| {
+            out.push(SchemaViolation {
+                path: path.to_string(),
+                message,
+            });
+        };
+        if let Some(min) = obj.get("minimum").and_then(Value::as_f64) {
+            if n < min {
+                push(format!("must be >= {min}, got {n}"));
+            }
+        }
+        if let Some(max) = obj.get("maximum").and_then(Value::as_f64) {
+            if n > max {
+                push(format!("must be <= {max}, got {n}"));
+            }
+        }
+        if let Some(min) = obj.get("exclusiveMinimum").and_then(Value::as_f64) {
+            if n <= min {
+                push(format!("must be > {min}, got {n}"));
+            }
+        }
+        if let Some(max) = obj.get("exclusiveMaximum").and_then(Value::as_f64) {
+            if n >= max {
+                push(format!("must be < {max}, got {n}"));
+            }
+        }
+    }
+
+    fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+        let pointer = reference.strip_prefix('#')?;
+        if pointer.is_empty() {
+            return Some(root);
+        }
+        root.pointer(pointer)
+    }
+
+    fn matches_type(expected: &str, value: &Value) -> bool {
+        match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            "number" => value.is_number(),
+            "integer" => {
+                value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+            }
+            _ => true,
+        }
+    }
+
+    fn describe_type(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+}
+
+/// Check tool-call arguments against the tool's `parameters_schema()`.
+///
+/// Returns `None` when the arguments fit, otherwise a correction message that
+/// lists every violation by JSON path so the model can repair the call.
+/// Missing (`null`) arguments are checked as an empty object.
+pub fn check_tool_arguments(tool_name: &str, schema: &Value, arguments: &Value) -> Option<String> {
+    let empty = Value::Object(Map::new());
+    let arguments = if arguments.is_null() {
+        &empty
+    } else {
+        arguments
+    };
+    let violations = SchemaValidator::validate(schema, arguments);
+    if violations.is_empty() {
+        return None;
+    }
+
+    let mut message = format!("Invalid arguments for tool `{tool_name}`:\n");
+    for violation in &violations {
+        message.push_str("- ");
+        message.push_str(&violation.to_string());
+        message.push('\n');
+    }
+    message.push_str("Expected parameters schema: ");
+    message.push_str(&schema.to_string());
+    message.push('\n');
+    message.push_str("The tool was not run. Fix the arguments and call it again.");
+    Some(message)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -835,4 +1185,107 @@ mod tests {
         assert_eq!(cleaned["not"]["type"], "integer");
         assert!(cleaned["not"].get("minimum").is_none());
     }
+
+    #[test]
+    fn test_validator_accepts_conforming_value() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "status": {"type": "string", "enum": ["done", "failed"]},
+                "count": {"type": "integer", "minimum": 0}
+            },
+            "required": ["status"],
+            "additionalProperties": false
+        });
+
+        let violations = SchemaValidator::validate(&schema, &json!({"status": "done", "count": 3}));
+        assert!(violations.is_empty(), "{violations:?}");
+    }
+
+    #[test]
+    fn test_validator_reports_each_violation_with_path() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "status": {"type": "string", "enum": ["done", "failed"]},
+                "count": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 2}
+            },
+            "required": ["status", "summary"],
+            "additionalProperties": false
+        });
+
+        let violations = SchemaValidator::validate(
+            &schema,
+            &json!({"status": "maybe", "count": -1, "tags": ["a", 2], "extra": true}),
+        );
+        let rendered: Vec<String> = violations.iter().map(ToString::to_string).collect();
+
+        assert!(rendered.contains(&"`$`: missing required property `summary`".to_string()));
+        assert!(rendered
+            .iter()
+            .any(|v| v.starts_with("`$.status`: must be one of")));
+        assert!(rendered.contains(&"`$.count`: must be >= 0, got -1".to_string()));
+        assert!(rendered.contains(&"`$.tags[1]`: expected string, got integer".to_string()));
+        assert!(rendered
+            .iter()
+            .any(|v| v.starts_with("`$.extra`: unexpected property `extra`")));
+    }
+
+    #[test]
+    fn test_validator_resolves_local_refs_and_unions() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "age": {"$ref": "#/$defs/Age"},
+                "name": {"anyOf": [{"type": "string"}, {"type": "null"}]}
+            },
+            "$defs": {"Age": {"type": "integer", "maximum": 150}}
+        });
+
+        assert!(SchemaValidator::validate(&schema, &json!({"age": 30, "name": null})).is_empty());
+        let violations = SchemaValidator::validate(&schema, &json!({"age": 200, "name": 5}));
+        assert_eq!(violations.len(), 2, "{violations:?}");
+        assert_eq!(violations[0].path, "$.age");
+        assert_eq!(violations[1].path, "$.name");
+    }
+
+    #[test]
+    fn test_validator_one_of_requires_exactly_one_match() {
+        let schema = json!({"oneOf": [{"type": "integer"}, {"type": "number"}]});
+        assert!(SchemaValidator::validate(&schema, &json!(1.5)).is_empty());
+        let violations = SchemaValidator::validate(&schema, &json!(2));
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.contains("exactly one"));
+        assert_eq!(SchemaValidator::validate(&schema, &json!("x")).len(), 1);
+    }
+
+    #[test]
+    fn test_validator_integer_accepts_whole_floats() {
+        let schema = json!({"type": "integer"});
+        assert!(SchemaValidator::validate(&schema, &json!(4.0)).is_empty());
+        assert_eq!(SchemaValidator::validate(&schema, &json!(4.5)).len(), 1);
+    }
+
+    #[test]
+    fn test_check_tool_arguments_reports_fixable_errors() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "action": {"type": "string", "enum": ["read", "write"]},
+                "limit": {"type": "integer", "minimum": 1}
+            },
+            "required": ["action"]
+        });
+        assert!(check_tool_arguments("demo", &schema, &json!({"action": "read"})).is_none());
+
+        let message = check_tool_arguments("demo", &schema, &json!({"limit": 0})).unwrap();
+        assert!(message.starts_with("Invalid arguments for tool `demo`"));
+        assert!(message.contains("missing required property `action`"));
+        assert!(message.contains("`$.limit`: must be >= 1, got 0"));
+        assert!(message.contains("The tool was not run"));
+
+        let message = check_tool_arguments("demo", &schema, &Value::Null).unwrap();
+        assert!(message.contains("missing required property `action`"));
+    }
 }
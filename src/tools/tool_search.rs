@@ -0,0 +1,119 @@
+use super::traits::{Tool, ToolResult};
+use crate::agent::tool_retrieval::{self, TOOL_SEARCH_TOOL_NAME};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+
+/// Matches returned when the caller does not ask for a limit.
+const DEFAULT_SEARCH_LIMIT: u64 = 5;
+/// Most matches a single search may activate.
+const MAX_SEARCH_LIMIT: u64 = 20;
+
+/// Meta-tool that searches the full tool registry when retrieval-based tool
+/// selection (`[agent.tool_retrieval]`) offers only part of it, and makes the
+/// matches callable for the rest of the turn.
+pub struct ToolSearchTool;
+
+impl ToolSearchTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for ToolSearchTool {
+    fn name(&self) -> &str {
+        TOOL_SEARCH_TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Find more tools. Only part of the tool registry is offered each turn; \
+         search it by describing the capability you need. Matching tools become \
+         callable for the rest of this turn."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "Capability to look for, e.g. 'create a GitHub issue'"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_SEARCH_LIMIT,
+                    "description": "Maximum tools to return (default: 5)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .unwrap_or_default();
+        let limit = args
+            .get("limit")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_SEARCH_LIMIT);
+
+        let Some(selection) = tool_retrieval::current_selection() else {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(
+                    "Tool search is not active for this turn; every tool is already offered."
+                        .to_string(),
+                ),
+            });
+        };
+
+        let matches = selection
+            .search(query, usize::try_from(limit).unwrap_or(usize::MAX))
+            .await;
+        if matches.is_empty() {
+            return Ok(ToolResult {
+                success: true,
+                output: format!("No tools match \"{query}\". Try different words."),
+                error: None,
+            });
+        }
+
+        let mut output = format!(
+            "Found {} tool(s); they can be called from now on in this turn:\n\n",
+            matches.len()
+        );
+        for spec in &matches {
+            let _ = writeln!(
+                output,
+                "**{}**: {}\nParameters: `{}`\n",
+                spec.name, spec.description, spec.parameters
+            );
+        }
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_inactive_outside_retrieval_turns() {
+        let tool = ToolSearchTool::new();
+        let result = tool.execute(json!({"query": "calendar"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("not active"));
+    }
+}
@@ -86,6 +86,7 @@ async fn provider_vision_support() -> Result<()> {
     let request = ChatRequest {
         messages: &messages,
         tools: None,
+        response_format: None,
     };
 
     // Send request to provider
@@ -156,6 +157,9 @@ async fn openai_codex_second_vision_support() -> Result<()> {
         custom_provider_api_mode: None,
         max_tokens_override: None,
         model_support_vision: None,
+        thinking_budget_tokens: None,
+        ollama: Default::default(),
+        observer: None,
     };
 
     let provider = zeroclaw::providers::create_provider_with_options("openai-codex", None, &opts)?;
@@ -213,6 +217,7 @@ async fn openai_codex_second_vision_support() -> Result<()> {
     let request = ChatRequest {
         messages: &messages,
         tools: None,
+        response_format: None,
     };
 
     // Send request to provider
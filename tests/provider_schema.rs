@@ -155,6 +155,7 @@ fn chat_response_text_only() {
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     assert_eq!(resp.text_or_empty(), "Hello world");
@@ -172,6 +173,7 @@ fn chat_response_with_tool_calls() {
         }],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     assert!(resp.has_tool_calls());
@@ -186,6 +188,7 @@ fn chat_response_text_or_empty_handles_none() {
         tool_calls: vec![],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     assert_eq!(resp.text_or_empty(), "");
@@ -209,6 +212,7 @@ fn chat_response_multiple_tool_calls() {
         ],
         usage: None,
         reasoning_content: None,
+        thinking: Vec::new(),
     };
 
     assert!(resp.has_tool_calls());